@@ -0,0 +1,30 @@
+//! Decoding the model enums from their text columns (`sqlx` feature)
+
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgTypeInfo, PgValueRef, Postgres};
+use sqlx::{Decode, Type};
+
+use crate::memory::{Confidence, MemoryType, Scope};
+
+/// Decode an enum from a text or varchar column through its `FromStr`
+macro_rules! text_column {
+    ($($ty:ty),*) => {$(
+        impl Type<Postgres> for $ty {
+            fn type_info() -> PgTypeInfo {
+                <String as Type<Postgres>>::type_info()
+            }
+
+            fn compatible(ty: &PgTypeInfo) -> bool {
+                <String as Type<Postgres>>::compatible(ty)
+            }
+        }
+
+        impl<'r> Decode<'r, Postgres> for $ty {
+            fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+                Ok(<&str as Decode<Postgres>>::decode(value)?.parse()?)
+            }
+        }
+    )*};
+}
+
+text_column!(MemoryType, Confidence, Scope);
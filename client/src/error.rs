@@ -0,0 +1,47 @@
+use thiserror::Error;
+
+use crate::response::{ConflictResponse, DuplicateResponse, LintRejectedResponse};
+
+/// Invalid value for one of the model enums
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ParseError {
+    #[error("Invalid memory type: {0}. Must be one of: convention, architecture, gotcha, api, learning, preference")]
+    InvalidMemoryType(String),
+
+    #[error("Invalid confidence level: {0}. Must be one of: high, medium, low")]
+    InvalidConfidence(String),
+
+    #[error("Invalid tier: {0}. Must be one of: project, global, both")]
+    InvalidTier(String),
+
+    #[error("Invalid scope: {0}. Must be one of: project, global")]
+    InvalidScope(String),
+}
+
+/// Failure of a [`Hippocampus`](crate::Hippocampus) call
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("Could not run claude-hippocampus: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Unexpected output: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The command ran and reported an error
+    #[error("{0}")]
+    Command(String),
+
+    /// `add-memory` found an existing duplicate
+    #[error("{}", .0.message)]
+    Duplicate(Box<DuplicateResponse>),
+
+    /// `update-memory` found the memory changed since the expected revision
+    #[error("{}", .0.message)]
+    Conflict(Box<ConflictResponse>),
+
+    /// Lint rules rejected the content
+    #[error("{}", .0.error)]
+    Rejected(Box<LintRejectedResponse>),
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
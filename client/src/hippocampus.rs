@@ -0,0 +1,188 @@
+//! Typed access to the `claude-hippocampus` CLI
+//!
+//! Each call runs the binary and deserializes its JSON output into the
+//! shared response types. Failures the CLI reports (`"success": false`)
+//! become [`ClientError`] variants, with duplicates and update conflicts
+//! kept structured so callers can retry.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+use crate::error::{ClientError, Result};
+use crate::memory::{Confidence, MemoryType, Scope, Tier};
+use crate::response::{
+    AddMemoryData, GetMemoryData, ListRecentData, SearchResult, UpdateMemoryData,
+};
+
+/// Client for a `claude-hippocampus` binary
+#[derive(Debug, Clone)]
+pub struct Hippocampus {
+    program: PathBuf,
+    project_path: Option<String>,
+}
+
+impl Default for Hippocampus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hippocampus {
+    /// Client for `claude-hippocampus` on the PATH
+    pub fn new() -> Self {
+        Self::with_program("claude-hippocampus")
+    }
+
+    /// Client for the binary at `program`
+    pub fn with_program(program: impl Into<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+            project_path: None,
+        }
+    }
+
+    /// Project that project-tier memories belong to (`PROJECT_PATH`)
+    pub fn project_path(mut self, path: impl Into<String>) -> Self {
+        self.project_path = Some(path.into());
+        self
+    }
+
+    /// Add a memory and return its ID
+    pub fn add_memory(
+        &self,
+        memory_type: MemoryType,
+        content: &str,
+        tags: &[&str],
+        confidence: Confidence,
+        scope: Scope,
+    ) -> Result<Uuid> {
+        let tags = tags.join(",");
+        let data: AddMemoryData = self.run(&[
+            "add-memory",
+            memory_type.as_str(),
+            content,
+            &tags,
+            confidence.as_str(),
+            scope.as_str(),
+        ])?;
+        Ok(data.id)
+    }
+
+    pub fn get_memory(&self, id: Uuid) -> Result<GetMemoryData> {
+        self.run(&["get-memory", &id.to_string()])
+    }
+
+    /// Replace a memory's content.
+    ///
+    /// With `expected_updated_at` (from [`get_memory`](Self::get_memory)),
+    /// a concurrent change fails with [`ClientError::Conflict`].
+    pub fn update_memory(
+        &self,
+        id: Uuid,
+        content: &str,
+        scope: Scope,
+        expected_updated_at: Option<DateTime<Utc>>,
+    ) -> Result<UpdateMemoryData> {
+        let id = id.to_string();
+        let mut args = vec!["update-memory", &id, content, scope.as_str()];
+        let expected = expected_updated_at.map(|t| t.to_rfc3339());
+        if let Some(expected) = &expected {
+            args.extend(["--expected-updated-at", expected]);
+        }
+        self.run(&args)
+    }
+
+    pub fn search_keyword(&self, query: &str, tier: Tier, limit: i64) -> Result<SearchResult> {
+        self.run(&["search-keyword", query, tier.as_str(), &limit.to_string()])
+    }
+
+    pub fn list_recent(&self, n: i64, tier: Tier) -> Result<ListRecentData> {
+        self.run(&["list-recent", &n.to_string(), tier.as_str()])
+    }
+
+    /// Run any command and deserialize its output as `T`
+    pub fn run<T: DeserializeOwned>(&self, args: &[&str]) -> Result<T> {
+        let mut command = Command::new(&self.program);
+        command.args(args);
+        if let Some(path) = &self.project_path {
+            command.env("PROJECT_PATH", path);
+        }
+        let output = command.output()?;
+        parse_output(&output.stdout)
+    }
+}
+
+/// Deserialize CLI output, turning reported failures into errors
+fn parse_output<T: DeserializeOwned>(stdout: &[u8]) -> Result<T> {
+    let value: serde_json::Value = serde_json::from_slice(stdout)?;
+    if value.get("success").and_then(|s| s.as_bool()) == Some(false) {
+        if value.get("conflict").is_some() {
+            let conflict = serde_json::from_value(value)?;
+            return Err(ClientError::Conflict(Box::new(conflict)));
+        }
+        if value.get("duplicate").is_some() {
+            let duplicate = serde_json::from_value(value)?;
+            return Err(ClientError::Duplicate(Box::new(duplicate)));
+        }
+        if value.get("rejected").is_some() {
+            let rejected = serde_json::from_value(value)?;
+            return Err(ClientError::Rejected(Box::new(rejected)));
+        }
+        let error = value.get("error").and_then(|e| e.as_str()).unwrap_or("Unknown error");
+        return Err(ClientError::Command(error.to_string()));
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output_success() {
+        let data: UpdateMemoryData = parse_output(
+            br#"{"success": true, "id": "550e8400-e29b-41d4-a716-446655440000",
+                 "updatedAt": "2024-03-01T12:00:00.123456Z"}"#,
+        )
+        .unwrap();
+        assert_eq!(data.id.to_string(), "550e8400-e29b-41d4-a716-446655440000");
+        assert_eq!(data.updated_at.timestamp_subsec_micros(), 123456);
+    }
+
+    #[test]
+    fn test_parse_output_errors() {
+        let err = parse_output::<AddMemoryData>(br#"{"success": false, "error": "Memory not found: x"}"#)
+            .unwrap_err();
+        assert!(matches!(err, ClientError::Command(ref e) if e == "Memory not found: x"));
+
+        let err = parse_output::<UpdateMemoryData>(
+            br#"{"success": false, "conflict": true,
+                 "id": "550e8400-e29b-41d4-a716-446655440000",
+                 "expectedUpdatedAt": "2024-03-01T12:00:00Z",
+                 "currentUpdatedAt": "2024-03-01T12:05:00Z",
+                 "currentContent": "Edited elsewhere", "message": "changed"}"#,
+        )
+        .unwrap_err();
+        match err {
+            ClientError::Conflict(conflict) => assert_eq!(conflict.current_content, "Edited elsewhere"),
+            other => panic!("Expected conflict, got {:?}", other),
+        }
+
+        let err = parse_output::<AddMemoryData>(
+            br#"{"success": false, "rejected": true, "error": "Content rejected by lint rules: no-pii",
+                 "issues": [{"rule": "no-pii", "message": "Content contains an email address"}]}"#,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ClientError::Rejected(ref r) if r.issues[0].rule == "no-pii"));
+
+        assert!(matches!(parse_output::<AddMemoryData>(b"not json"), Err(ClientError::Json(_))));
+    }
+}
@@ -0,0 +1,21 @@
+//! Shared types for claude-hippocampus output.
+//!
+//! The memory, turn and response models the CLI serializes, without the
+//! database stack, so other tools can deserialize its JSON with the same
+//! types. [`Hippocampus`] runs the CLI and returns them typed.
+//!
+//! With the `sqlx` feature, [`Memory`] and [`Turn`] derive `sqlx::FromRow`
+//! and the enum columns decode from their text values.
+
+#[cfg(feature = "sqlx")]
+mod db;
+pub mod error;
+pub mod hippocampus;
+pub mod memory;
+pub mod response;
+pub mod turn;
+
+pub use error::{ClientError, ParseError};
+pub use hippocampus::Hippocampus;
+pub use memory::{Confidence, Memory, MemorySummary, MemoryType, Scope, Tier};
+pub use turn::{CreateTurn, Turn, TurnSummary, UpdateTurn};
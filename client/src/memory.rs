@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 use uuid::Uuid;
 
-use crate::error::HippocampusError;
+use crate::error::ParseError;
 
 // ============================================================================
 // MemoryType
@@ -34,7 +34,7 @@ impl MemoryType {
 }
 
 impl FromStr for MemoryType {
-    type Err = HippocampusError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
@@ -44,7 +44,7 @@ impl FromStr for MemoryType {
             "api" => Ok(Self::Api),
             "learning" => Ok(Self::Learning),
             "preference" => Ok(Self::Preference),
-            _ => Err(HippocampusError::InvalidMemoryType(s.to_string())),
+            _ => Err(ParseError::InvalidMemoryType(s.to_string())),
         }
     }
 }
@@ -90,14 +90,14 @@ impl Confidence {
 }
 
 impl FromStr for Confidence {
-    type Err = HippocampusError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "high" => Ok(Self::High),
             "medium" => Ok(Self::Medium),
             "low" => Ok(Self::Low),
-            _ => Err(HippocampusError::InvalidConfidence(s.to_string())),
+            _ => Err(ParseError::InvalidConfidence(s.to_string())),
         }
     }
 }
@@ -123,13 +123,13 @@ impl Scope {
 }
 
 impl FromStr for Scope {
-    type Err = HippocampusError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "project" => Ok(Self::Project),
             "global" => Ok(Self::Global),
-            _ => Err(HippocampusError::InvalidScope(s.to_string())),
+            _ => Err(ParseError::InvalidScope(s.to_string())),
         }
     }
 }
@@ -157,14 +157,14 @@ impl Tier {
 }
 
 impl FromStr for Tier {
-    type Err = HippocampusError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "project" => Ok(Self::Project),
             "global" => Ok(Self::Global),
             "both" => Ok(Self::Both),
-            _ => Err(HippocampusError::InvalidTier(s.to_string())),
+            _ => Err(ParseError::InvalidTier(s.to_string())),
         }
     }
 }
@@ -174,15 +174,20 @@ impl FromStr for Tier {
 // ============================================================================
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
 #[serde(rename_all = "camelCase")]
 pub struct Memory {
     pub id: Uuid,
     #[serde(rename = "type")]
+    #[cfg_attr(feature = "sqlx", sqlx(rename = "type"))]
     pub memory_type: MemoryType,
     pub scope: Scope,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub project_path: Option<String>,
     pub content: String,
+    /// Generated from `content` when it is written; see [`summarize`]
+    #[serde(default)]
+    pub summary: String,
     pub tags: Vec<String>,
     pub confidence: Confidence,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -201,6 +206,18 @@ pub struct Memory {
     pub superseded_at: Option<DateTime<Utc>>,
     #[serde(default = "default_is_active")]
     pub is_active: bool,
+    /// Free-form metadata; `constraints` holds environment constraints
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+    /// Set while `decay` has the memory archived: kept and searchable,
+    /// but left out of the context block
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<DateTime<Utc>>,
+    /// Pinned memories head the context block and are never pruned,
+    /// archived or removed as duplicates
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
 }
 
 fn default_is_active() -> bool {
@@ -215,7 +232,7 @@ pub struct MemorySummary {
     #[serde(rename = "type")]
     pub memory_type: MemoryType,
     pub tier: Scope,
-    pub summary: String, // First 100 chars of content
+    pub summary: String, // Memory::summary
     pub tags: Vec<String>,
     pub confidence: Confidence,
     pub created: DateTime<Utc>,
@@ -227,22 +244,28 @@ pub struct MemorySummary {
     pub superseded_at: Option<DateTime<Utc>>,
     #[serde(default = "default_is_active")]
     pub is_active: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
 }
 
 impl Memory {
+    /// Environment constraints declared in metadata (e.g. `os=linux`)
+    pub fn constraints(&self) -> Vec<String> {
+        self.metadata
+            .as_ref()
+            .and_then(|m| m.get("constraints"))
+            .and_then(|c| c.as_array())
+            .map(|c| c.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+            .unwrap_or_default()
+    }
+
     /// Convert to summary view
     pub fn to_summary(&self) -> MemorySummary {
-        let summary = if self.content.len() > 100 {
-            format!("{}...", &self.content[..97])
-        } else {
-            self.content.clone()
-        };
-
         MemorySummary {
             id: self.id,
             memory_type: self.memory_type,
             tier: self.scope,
-            summary,
+            summary: self.summary.clone(),
             tags: self.tags.clone(),
             confidence: self.confidence,
             created: self.created_at,
@@ -250,10 +273,42 @@ impl Memory {
             superseded_by: self.superseded_by,
             superseded_at: self.superseded_at,
             is_active: self.is_active,
+            pinned: self.pinned,
         }
     }
 }
 
+/// Longest memory summary, in characters
+pub const SUMMARY_MAX_CHARS: usize = 100;
+
+/// Summary of memory content: its first sentence, or first line when that
+/// is shorter, cut to [`SUMMARY_MAX_CHARS`] with a trailing "...".
+///
+/// A sentence ends at `.`, `!` or `?` followed by whitespace and an
+/// uppercase letter, so "e.g. use" does not end one. Migration 13 applies
+/// the same rule in SQL to existing memories.
+pub fn summarize(content: &str) -> String {
+    let content = content.trim();
+    let line = content.lines().next().unwrap_or("").trim_end();
+
+    let sentence = line
+        .char_indices()
+        .filter(|(_, c)| matches!(c, '.' | '!' | '?'))
+        .find(|(i, _)| {
+            let rest = &line[i + 1..];
+            let next = rest.trim_start();
+            next.len() < rest.len() && next.starts_with(char::is_uppercase)
+        })
+        .map_or(line, |(i, _)| &line[..=i]);
+
+    if sentence.chars().count() > SUMMARY_MAX_CHARS {
+        let cut: String = sentence.chars().take(SUMMARY_MAX_CHARS - 3).collect();
+        format!("{}...", cut)
+    } else {
+        sentence.to_string()
+    }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -447,6 +502,7 @@ mod tests {
             scope: Scope::Project,
             project_path: Some("/test".to_string()),
             content: "Short content".to_string(),
+            summary: summarize("Short content"),
             tags: vec!["tag1".to_string()],
             confidence: Confidence::High,
             source_session_id: None,
@@ -457,7 +513,10 @@ mod tests {
             access_count: 0,
             superseded_by: None,
             superseded_at: None,
+            archived_at: None,
             is_active: true,
+            metadata: None,
+            pinned: false,
         };
 
         let summary = memory.to_summary();
@@ -476,6 +535,7 @@ mod tests {
             memory_type: MemoryType::Gotcha,
             scope: Scope::Global,
             project_path: None,
+            summary: summarize(&long_content),
             content: long_content,
             tags: vec![],
             confidence: Confidence::Medium,
@@ -487,7 +547,10 @@ mod tests {
             access_count: 5,
             superseded_by: None,
             superseded_at: None,
+            archived_at: None,
             is_active: true,
+            metadata: None,
+            pinned: false,
         };
 
         let summary = memory.to_summary();
@@ -503,6 +566,7 @@ mod tests {
             scope: Scope::Project,
             project_path: Some("/test/project".to_string()),
             content: "API quirk discovered".to_string(),
+            summary: summarize("API quirk discovered"),
             tags: vec!["api".to_string(), "quirk".to_string()],
             confidence: Confidence::High,
             source_session_id: None,
@@ -517,7 +581,10 @@ mod tests {
             access_count: 0,
             superseded_by: None,
             superseded_at: None,
+            archived_at: None,
             is_active: true,
+            metadata: None,
+            pinned: false,
         };
 
         let json = serde_json::to_string(&memory).unwrap();
@@ -538,6 +605,7 @@ mod tests {
             scope: Scope::Project,
             project_path: None,
             content: "Old learning".to_string(),
+            summary: summarize("Old learning"),
             tags: vec![],
             confidence: Confidence::Medium,
             source_session_id: None,
@@ -548,7 +616,10 @@ mod tests {
             access_count: 0,
             superseded_by: Some(superseding_id),
             superseded_at: Some(superseded_time),
+            archived_at: None,
             is_active: false,
+            metadata: None,
+            pinned: false,
         };
 
         let summary = memory.to_summary();
@@ -556,4 +627,49 @@ mod tests {
         assert_eq!(summary.superseded_by, Some(superseding_id));
         assert!(summary.superseded_at.is_some());
     }
+    #[test]
+    fn test_summarize_first_sentence() {
+        assert_eq!(summarize("Run migrations first. Tests fail otherwise."), "Run migrations first.");
+        assert_eq!(summarize("  Check the logs!\nThey rotate daily"), "Check the logs!");
+        assert_eq!(summarize("Prefer sqlx, e.g. for queries. Not diesel"), "Prefer sqlx, e.g. for queries.");
+        assert_eq!(summarize("Version 1.75 is required"), "Version 1.75 is required");
+        assert_eq!(summarize(""), "");
+    }
+
+    #[test]
+    fn test_summarize_truncates_by_characters() {
+        let summary = summarize(&"é".repeat(150));
+        assert_eq!(summary.chars().count(), SUMMARY_MAX_CHARS);
+        assert!(summary.ends_with("..."));
+    }
+
+    #[test]
+    fn test_memory_constraints_from_metadata() {
+        let mut memory = Memory {
+            id: Uuid::new_v4(),
+            memory_type: MemoryType::Gotcha,
+            scope: Scope::Global,
+            project_path: None,
+            content: "Use gsed on macOS".to_string(),
+            summary: summarize("Use gsed on macOS"),
+            tags: vec![],
+            confidence: Confidence::High,
+            source_session_id: None,
+            source_turn_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            accessed_at: None,
+            access_count: 0,
+            superseded_by: None,
+            superseded_at: None,
+            archived_at: None,
+            is_active: true,
+            metadata: None,
+            pinned: false,
+        };
+        assert!(memory.constraints().is_empty());
+
+        memory.metadata = Some(serde_json::json!({"constraints": ["os=macos", "rust>=1.75"]}));
+        assert_eq!(memory.constraints(), vec!["os=macos", "rust>=1.75"]);
+    }
 }
@@ -0,0 +1,1009 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::memory::{Confidence, Memory, MemorySummary, MemoryType, Scope};
+
+// ============================================================================
+// Base Response Types
+// ============================================================================
+
+/// Wrapper for successful responses with data
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SuccessResponse<T> {
+    pub success: bool,
+    #[serde(flatten)]
+    pub data: T,
+}
+
+impl<T: Serialize> SuccessResponse<T> {
+    pub fn new(data: T) -> Self {
+        Self {
+            success: true,
+            data,
+        }
+    }
+}
+
+/// Error response format
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub success: bool,
+    pub error: String,
+    /// Command-line argument that was rejected, e.g. `--session`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub argument: Option<String>,
+}
+
+impl ErrorResponse {
+    pub fn new(error: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            error: error.into(),
+            argument: None,
+        }
+    }
+
+    /// Name the argument the error is about
+    pub fn with_argument(mut self, argument: Option<&str>) -> Self {
+        self.argument = argument.map(str::to_string);
+        self
+    }
+}
+
+// ============================================================================
+// Memory Operation Responses
+// ============================================================================
+
+/// Response for successful memory creation
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddMemoryData {
+    pub id: Uuid,
+    /// Lint issues in the saved content (lint mode `warn`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<LintIssue>,
+    /// Existing memories the new one may contradict (`--check-conflicts`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<MemoryConflict>,
+}
+
+/// An existing memory that a new one may contradict
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryConflict {
+    pub id: Uuid,
+    pub summary: String,
+    /// Trigram similarity to the new memory's content
+    pub similarity: f64,
+    pub reason: String,
+}
+
+/// Content breaking a lint rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LintIssue {
+    /// Rule name, e.g. `max-length`
+    pub rule: String,
+    pub message: String,
+}
+
+impl LintIssue {
+    pub fn new(rule: &str, message: impl Into<String>) -> Self {
+        Self {
+            rule: rule.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Response when lint rules reject content (lint mode `reject`)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LintRejectedResponse {
+    pub success: bool,
+    pub rejected: bool,
+    pub issues: Vec<LintIssue>,
+    pub error: String,
+}
+
+impl LintRejectedResponse {
+    pub fn new(issues: Vec<LintIssue>) -> Self {
+        let rules: Vec<&str> = issues.iter().map(|i| i.rule.as_str()).collect();
+        Self {
+            success: false,
+            rejected: true,
+            error: format!("Content rejected by lint rules: {}", rules.join(", ")),
+            issues,
+        }
+    }
+}
+
+/// Response when duplicate memory is detected
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateResponse {
+    pub success: bool,
+    pub duplicate: bool,
+    pub reason: String,
+    pub existing_id: Uuid,
+    pub existing_tier: String,
+    pub existing_summary: String,
+    /// Trigram similarity of a near-duplicate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<f64>,
+    /// Set for a near-duplicate, which is more likely an update than a copy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<SupersedeSuggestion>,
+    /// How the attempted content differs from the existing memory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<ContentDiff>,
+    pub message: String,
+}
+
+/// Suggestion to re-add a memory with `--supersedes`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupersedeSuggestion {
+    /// Memory the new one likely replaces
+    pub supersedes: Uuid,
+    pub similarity: f64,
+    pub message: String,
+}
+
+impl DuplicateResponse {
+    pub fn new(
+        existing_id: Uuid,
+        existing_tier: &str,
+        existing_summary: &str,
+        similarity: Option<f64>,
+    ) -> Self {
+        let reason = match similarity {
+            Some(s) => format!("Near-duplicate memory detected (similarity {:.2})", s),
+            None => "Duplicate memory detected (matching summary)".to_string(),
+        };
+        Self {
+            success: false,
+            duplicate: true,
+            reason,
+            existing_id,
+            existing_tier: existing_tier.to_string(),
+            existing_summary: existing_summary.to_string(),
+            similarity,
+            suggestion: similarity.map(|s| SupersedeSuggestion::new(existing_id, s, "add-memory")),
+            diff: None,
+            message: format!(
+                "Memory with similar content already exists (id: {})",
+                existing_id
+            ),
+        }
+    }
+
+    /// Point the suggestion at `retry` instead of add-memory
+    pub fn with_retry(mut self, retry: &str) -> Self {
+        self.suggestion = self
+            .suggestion
+            .map(|s| SupersedeSuggestion::new(s.supersedes, s.similarity, retry));
+        self
+    }
+
+    /// Compare the attempted content with the existing memory's
+    pub fn with_diff(mut self, existing: &str, attempted: &str) -> Self {
+        self.diff = Some(ContentDiff::between(existing, attempted));
+        self
+    }
+}
+
+/// Most runs of words listed on each side of a [`ContentDiff`]
+const MAX_DIFF_RUNS: usize = 5;
+
+/// Longest run of words listed in a [`ContentDiff`], in characters
+const MAX_DIFF_RUN_CHARS: usize = 120;
+
+/// Word-level difference between an existing memory and attempted content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentDiff {
+    /// Share of words the two contents have in common, in order (0 to 1)
+    pub word_similarity: f64,
+    /// Runs of words only in the attempted content: what adding it would bring
+    pub added: Vec<String>,
+    /// Runs of words only in the existing memory
+    pub removed: Vec<String>,
+}
+
+impl ContentDiff {
+    /// Diff the words of `existing` and `attempted`, ignoring case.
+    ///
+    /// Lists at most [`MAX_DIFF_RUNS`] runs per side.
+    pub fn between(existing: &str, attempted: &str) -> Self {
+        let old: Vec<&str> = existing.split_whitespace().collect();
+        let new: Vec<&str> = attempted.split_whitespace().collect();
+        let same = |a: &str, b: &str| a.to_lowercase() == b.to_lowercase();
+
+        // Longest common subsequence table, filled from the end
+        let mut lcs = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+        for i in (0..old.len()).rev() {
+            for j in (0..new.len()).rev() {
+                lcs[i][j] = if same(old[i], new[j]) {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+        let (mut run_added, mut run_removed) = (Vec::new(), Vec::new());
+        let (mut i, mut j) = (0, 0);
+        while i < old.len() || j < new.len() {
+            if i < old.len() && j < new.len() && same(old[i], new[j]) {
+                push_run(&mut added, &mut run_added);
+                push_run(&mut removed, &mut run_removed);
+                i += 1;
+                j += 1;
+            } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+                run_added.push(new[j]);
+                j += 1;
+            } else {
+                run_removed.push(old[i]);
+                i += 1;
+            }
+        }
+        push_run(&mut added, &mut run_added);
+        push_run(&mut removed, &mut run_removed);
+
+        let total = old.len() + new.len();
+        let word_similarity = if total == 0 {
+            1.0
+        } else {
+            (2.0 * lcs[0][0] as f64 / total as f64 * 1000.0).round() / 1000.0
+        };
+        Self {
+            word_similarity,
+            added,
+            removed,
+        }
+    }
+}
+
+/// Move a finished run of words into `runs`, clipped
+fn push_run(runs: &mut Vec<String>, run: &mut Vec<&str>) {
+    if run.is_empty() {
+        return;
+    }
+    if runs.len() < MAX_DIFF_RUNS {
+        let text = run.join(" ");
+        runs.push(if text.chars().count() > MAX_DIFF_RUN_CHARS {
+            let cut: String = text.chars().take(MAX_DIFF_RUN_CHARS - 3).collect();
+            format!("{}...", cut.trim_end())
+        } else {
+            text
+        });
+    }
+    run.clear();
+}
+
+impl SupersedeSuggestion {
+    /// `retry` is the command line to re-run with `--supersedes`
+    pub fn new(supersedes: Uuid, similarity: f64, retry: &str) -> Self {
+        Self {
+            supersedes,
+            similarity,
+            message: format!(
+                "Likely supersedes {}; re-run {} with --supersedes {} to replace it",
+                supersedes, retry, supersedes
+            ),
+        }
+    }
+}
+
+/// Response when a memory changed since the caller read it
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictResponse {
+    pub success: bool,
+    pub conflict: bool,
+    pub id: Uuid,
+    pub expected_updated_at: DateTime<Utc>,
+    pub current_updated_at: DateTime<Utc>,
+    pub current_content: String,
+    pub message: String,
+}
+
+impl ConflictResponse {
+    pub fn new(id: Uuid, expected_updated_at: DateTime<Utc>, current: &Memory) -> Self {
+        Self {
+            success: false,
+            conflict: true,
+            id,
+            expected_updated_at,
+            current_updated_at: current.updated_at,
+            current_content: current.content.clone(),
+            message: format!(
+                "Memory {} was updated at {} (expected {}); re-read it and retry",
+                id,
+                current.updated_at.to_rfc3339(),
+                expected_updated_at.to_rfc3339()
+            ),
+        }
+    }
+}
+
+/// Response for memory update
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateMemoryData {
+    pub id: Uuid,
+    /// Pass as `--expected-updated-at` to the next update
+    pub updated_at: DateTime<Utc>,
+    /// The memory replaced by `id` (`--as-new`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supersedes: Option<Uuid>,
+    /// Lint issues in the new content (lint mode `warn`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<LintIssue>,
+}
+
+/// Response for a partial memory edit
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditMemoryData {
+    pub memory: MemorySummary,
+    /// Pass as `--expected-updated-at` to the next edit
+    pub updated_at: DateTime<Utc>,
+    /// Lint issues in the new content (lint mode `warn`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<LintIssue>,
+}
+
+/// Response for memory deletion
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteMemoryData {
+    pub deleted: Uuid,
+}
+
+/// Response for single memory retrieval
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetMemoryData {
+    pub memory: MemorySummary,
+    /// Revision for `update-memory --expected-updated-at`
+    pub updated_at: DateTime<Utc>,
+    /// Point in time the memory was resolved at (`--as-of`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub as_of: Option<DateTime<Utc>>,
+    /// Memories linked to this one (`--neighborhood`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub neighborhood: Option<Vec<LinkedMemory>>,
+}
+
+// ============================================================================
+// Search Responses
+// ============================================================================
+
+/// Response for keyword search
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchResultData {
+    pub results: Vec<MemorySummary>,
+    pub count: usize,
+}
+
+/// Result of a keyword search
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchResult {
+    /// Matched memories (with full content)
+    pub results: Vec<MemorySearchItem>,
+    /// Number of results
+    pub count: usize,
+    /// Pass as `--cursor` for the next page; absent on the last page
+    #[serde(rename = "nextCursor", default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// A single search result item (includes full content unlike MemorySummary)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemorySearchItem {
+    pub id: Uuid,
+    #[serde(rename = "type")]
+    pub memory_type: MemoryType,
+    pub tier: Scope,
+    pub summary: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub confidence: Confidence,
+    pub created: DateTime<Utc>,
+    pub accessed: Option<DateTime<Utc>>,
+    pub access_count: i32,
+    /// Full-text relevance (FTS mode only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<f32>,
+    /// When the memory was archived (searches with `--include-archived`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+}
+
+impl From<Memory> for MemorySearchItem {
+    fn from(m: Memory) -> Self {
+        Self {
+            id: m.id,
+            memory_type: m.memory_type,
+            tier: m.scope,
+            summary: m.summary,
+            content: m.content,
+            tags: m.tags,
+            confidence: m.confidence,
+            created: m.created_at,
+            accessed: m.accessed_at,
+            access_count: m.access_count,
+            rank: None,
+            archived_at: m.archived_at,
+            pinned: m.pinned,
+        }
+    }
+}
+
+/// Response for context retrieval
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ContextData {
+    pub context: String,
+    pub count: usize,
+    pub entries: Vec<MemorySummary>,
+    /// Sections of a block grouped by type (`--group-by-type`), in order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sections: Option<Vec<ContextSection>>,
+}
+
+/// One headed section of a context block grouped by type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextSection {
+    #[serde(rename = "type")]
+    pub memory_type: MemoryType,
+    pub heading: String,
+    /// Entries under the heading
+    pub count: usize,
+}
+
+/// Response for listing recent memories
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListRecentData {
+    pub entries: Vec<MemorySummary>,
+    pub total: usize,
+    /// Pass as `--cursor` for the next page; absent on the last page
+    #[serde(rename = "nextCursor", default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+// ============================================================================
+// Maintenance Responses
+// ============================================================================
+
+/// Response for consolidate operation
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsolidateData {
+    pub removed: usize,
+    pub duplicate_ids: Vec<Uuid>,
+}
+
+/// Response for prune operation
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneData {
+    pub pruned: usize,
+    pub pruned_ids: Vec<Uuid>,
+}
+
+/// Response for tiered prune operation (low/medium confidence)
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TieredPruneData {
+    pub low_pruned: usize,
+    pub low_pruned_ids: Vec<Uuid>,
+    pub medium_pruned: usize,
+    pub medium_pruned_ids: Vec<Uuid>,
+    pub total_pruned: usize,
+}
+
+/// Response for session summary save
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveSessionSummaryData {
+    pub session_id: Uuid,
+    /// Memories the summary refers to, stored with it as `memoryIds`
+    #[serde(default)]
+    pub memory_ids: Vec<Uuid>,
+}
+
+// ============================================================================
+// Log Responses
+// ============================================================================
+
+/// Single log entry
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    pub operation: String,
+    #[serde(flatten)]
+    pub details: serde_json::Value,
+}
+
+/// Response for reading logs
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogsData {
+    pub entries: Vec<LogEntry>,
+    pub count: usize,
+    pub total: usize,
+}
+
+/// Response for clearing logs
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClearLogsData {
+    pub cleared: bool,
+}
+
+// ============================================================================
+// Supersession Responses
+// ============================================================================
+
+use chrono::{DateTime, Utc};
+
+/// Data for showing a memory's supersession chain
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainData {
+    /// The memory itself
+    pub memory: MemorySummary,
+    /// Memories that this one superseded (predecessors)
+    pub predecessors: Vec<MemorySummary>,
+    /// Memories that superseded this one (successors)
+    pub successors: Vec<MemorySummary>,
+    /// Memories linked to this one (`link`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<LinkedMemory>,
+}
+
+/// A memory linked to another, with the link's relation and direction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkedMemory {
+    /// relates-to, contradicts or refines
+    pub relation: String,
+    /// "outgoing" for a link from the memory asked about to this one,
+    /// "incoming" for a link from this one to it
+    pub direction: String,
+    pub memory: MemorySummary,
+}
+
+/// A superseded memory with its replacement info
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupersededMemory {
+    pub memory: MemorySummary,
+    pub superseded_by_id: Uuid,
+    pub superseded_at: DateTime<Utc>,
+}
+
+/// Response for listing superseded memories
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListSupersededData {
+    pub entries: Vec<SupersededMemory>,
+    pub count: usize,
+    /// Pass as `--cursor` for the next page; absent on the last page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Response for purging superseded memories
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeSupersededData {
+    pub purged: usize,
+    pub purged_ids: Vec<Uuid>,
+}
+
+// ============================================================================
+// Review Queue Responses
+// ============================================================================
+
+/// A memory waiting for review
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewItem {
+    pub memory: MemorySummary,
+    pub reason: Option<String>,
+    pub flagged_at: Option<DateTime<Utc>>,
+}
+
+/// Response for listing the review queue
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewQueueData {
+    pub entries: Vec<ReviewItem>,
+    pub count: usize,
+}
+
+/// Response for resolving a review flag
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveReviewData {
+    pub id: Uuid,
+    pub resolved: bool,
+}
+
+/// A row written during a simulated hook run
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedWrite {
+    pub table: String,
+    pub id: Uuid,
+    /// "insert" or "update"
+    pub action: String,
+    pub summary: Option<String>,
+}
+
+/// Response for simulate-hook
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateHookData {
+    pub hook: String,
+    pub output: serde_json::Value,
+    pub writes: Vec<SimulatedWrite>,
+    pub skipped_effects: Vec<String>,
+    /// Failures the hook recovered from (surfaced by strict mode)
+    pub failures: Vec<String>,
+    pub rolled_back: bool,
+}
+
+/// Response for lifecycle data pruning
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneDataResult {
+    pub tool_calls_pruned: usize,
+    pub turns_pruned: usize,
+    pub sessions_pruned: usize,
+    pub dry_run: bool,
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::summarize;
+    use chrono::Utc;
+    
+    #[test]
+    fn test_success_response_serialization() {
+        let data = AddMemoryData {
+            id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap(),
+            warnings: Vec::new(),
+            conflicts: Vec::new(),
+        };
+        let response = SuccessResponse::new(data);
+
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.contains("\"success\":true"));
+        assert!(json.contains("\"id\":\"550e8400-e29b-41d4-a716-446655440000\""));
+        assert!(!json.contains("warnings"));
+        assert!(!json.contains("conflicts"));
+    }
+
+    #[test]
+    fn test_lint_rejected_response() {
+        let response = LintRejectedResponse::new(vec![
+            LintIssue::new("max-length", "Content is 1200 characters; the limit is 1000"),
+            LintIssue::new("no-pii", "Content contains an email address"),
+        ]);
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["success"], false);
+        assert_eq!(json["rejected"], true);
+        assert_eq!(json["error"], "Content rejected by lint rules: max-length, no-pii");
+        assert_eq!(json["issues"][1]["rule"], "no-pii");
+    }
+
+    #[test]
+    fn test_error_response_serialization() {
+        let response = ErrorResponse::new("Something went wrong");
+
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.contains("\"success\":false"));
+        assert!(json.contains("\"error\":\"Something went wrong\""));
+    }
+
+    #[test]
+    fn test_duplicate_response_serialization() {
+        let response = DuplicateResponse::new(
+            Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap(),
+            "project",
+            "Some existing memory content...",
+            None,
+        );
+
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.contains("\"success\":false"));
+        assert!(json.contains("\"duplicate\":true"));
+        assert!(json.contains("\"existingId\"")); // camelCase
+        assert!(json.contains("\"existingTier\":\"project\""));
+        assert!(!json.contains("similarity"));
+        assert!(!json.contains("suggestion"));
+    }
+
+    #[test]
+    fn test_near_duplicate_response_reports_similarity() {
+        let response = DuplicateResponse::new(Uuid::nil(), "global", "Reworded memory", Some(0.873));
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["similarity"], 0.873);
+        assert_eq!(json["reason"], "Near-duplicate memory detected (similarity 0.87)");
+        assert_eq!(json["suggestion"]["supersedes"], Uuid::nil().to_string());
+        assert_eq!(json["suggestion"]["similarity"], 0.873);
+    }
+
+    #[test]
+    fn test_duplicate_response_with_retry() {
+        let response = DuplicateResponse::new(Uuid::nil(), "global", "Reworded memory", Some(0.9))
+            .with_retry("commit-memory abc");
+
+        let message = response.suggestion.unwrap().message;
+        assert!(message.contains("re-run commit-memory abc with --supersedes"));
+    }
+
+    #[test]
+    fn test_content_diff_lists_new_words() {
+        let diff = ContentDiff::between(
+            "Run migrations before tests",
+            "Run migrations before integration tests with --release",
+        );
+        assert_eq!(diff.added, vec!["integration", "with --release"]);
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.word_similarity, 0.727);
+
+        let same = ContentDiff::between("Use gsed", "use  GSED");
+        assert_eq!(same.word_similarity, 1.0);
+        assert!(same.added.is_empty() && same.removed.is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_response_with_diff() {
+        let response = DuplicateResponse::new(Uuid::nil(), "project", "Pool size", Some(0.8))
+            .with_diff("Pool size caps at 10", "Pool size caps at 20");
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["diff"]["added"], serde_json::json!(["20"]));
+        assert_eq!(json["diff"]["removed"], serde_json::json!(["10"]));
+        assert_eq!(json["diff"]["wordSimilarity"], 0.8);
+    }
+
+    #[test]
+    fn test_conflict_response_serialization() {
+        let current = Memory {
+            id: Uuid::nil(),
+            memory_type: MemoryType::Learning,
+            scope: Scope::Project,
+            project_path: None,
+            content: "Edited by someone else".to_string(),
+            summary: summarize("Edited by someone else"),
+            tags: vec![],
+            confidence: Confidence::Medium,
+            source_session_id: None,
+            source_turn_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            accessed_at: None,
+            access_count: 0,
+            superseded_by: None,
+            superseded_at: None,
+            archived_at: None,
+            is_active: true,
+            metadata: None,
+            pinned: false,
+        };
+        let expected = current.updated_at - chrono::Duration::seconds(5);
+
+        let json = serde_json::to_value(ConflictResponse::new(Uuid::nil(), expected, &current)).unwrap();
+        assert_eq!(json["success"], false);
+        assert_eq!(json["conflict"], true);
+        assert_eq!(json["currentContent"], "Edited by someone else");
+        assert!(json["expectedUpdatedAt"].is_string());
+        assert!(json["currentUpdatedAt"].is_string());
+    }
+
+    #[test]
+    fn test_search_result_data_serialization() {
+        let summary = MemorySummary {
+            id: Uuid::new_v4(),
+            memory_type: MemoryType::Learning,
+            tier: Scope::Global,
+            summary: "Test summary".to_string(),
+            tags: vec!["test".to_string()],
+            confidence: Confidence::High,
+            created: Utc::now(),
+            access_count: 5,
+            superseded_by: None,
+            superseded_at: None,
+            is_active: true,
+            pinned: false,
+        };
+
+        let data = SearchResultData {
+            results: vec![summary],
+            count: 1,
+        };
+        let response = SuccessResponse::new(data);
+
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.contains("\"success\":true"));
+        assert!(json.contains("\"count\":1"));
+        assert!(json.contains("\"results\""));
+    }
+
+    #[test]
+    fn test_consolidate_data_serialization() {
+        let data = ConsolidateData {
+            removed: 3,
+            duplicate_ids: vec![Uuid::new_v4(), Uuid::new_v4()],
+        };
+        let response = SuccessResponse::new(data);
+
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.contains("\"removed\":3"));
+        assert!(json.contains("\"duplicateIds\"")); // camelCase
+    }
+
+    #[test]
+    fn test_prune_data_serialization() {
+        let data = PruneData {
+            pruned: 5,
+            pruned_ids: vec![Uuid::new_v4()],
+        };
+        let response = SuccessResponse::new(data);
+
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.contains("\"pruned\":5"));
+        assert!(json.contains("\"prunedIds\"")); // camelCase
+    }
+
+    #[test]
+    fn test_context_data_has_correct_fields() {
+        let data = ContextData {
+            context: "## Memory Context\n\n- ★ learning: Test".to_string(),
+            count: 1,
+            entries: vec![],
+            sections: None,
+        };
+        let response = SuccessResponse::new(data);
+
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.contains("\"context\""));
+        assert!(json.contains("\"count\":1"));
+        assert!(json.contains("\"entries\""));
+        assert!(!json.contains("sections"));
+    }
+    // -------------------------------------------------------------------------
+    // MemorySearchItem tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_memory_search_item_from_memory_short_content() {
+        let memory = Memory {
+            id: Uuid::new_v4(),
+            memory_type: MemoryType::Learning,
+            scope: Scope::Project,
+            project_path: Some("/test".to_string()),
+            content: "Short content".to_string(),
+            summary: summarize("Short content"),
+            tags: vec!["test".to_string()],
+            confidence: Confidence::High,
+            source_session_id: None,
+            source_turn_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            accessed_at: None,
+            access_count: 5,
+            superseded_by: None,
+            superseded_at: None,
+            archived_at: None,
+            is_active: true,
+            metadata: None,
+            pinned: false,
+        };
+
+        let item: MemorySearchItem = memory.into();
+
+        assert_eq!(item.summary, "Short content");
+        assert_eq!(item.content, "Short content");
+        assert_eq!(item.memory_type, MemoryType::Learning);
+        assert_eq!(item.confidence, Confidence::High);
+        assert_eq!(item.access_count, 5);
+    }
+
+    #[test]
+    fn test_memory_search_item_from_memory_long_content() {
+        let long_content = "x".repeat(150);
+        let memory = Memory {
+            id: Uuid::new_v4(),
+            memory_type: MemoryType::Gotcha,
+            scope: Scope::Global,
+            project_path: None,
+            content: long_content.clone(),
+            summary: summarize(&long_content),
+            tags: vec![],
+            confidence: Confidence::Medium,
+            source_session_id: None,
+            source_turn_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            accessed_at: Some(Utc::now()),
+            access_count: 0,
+            superseded_by: None,
+            superseded_at: None,
+            archived_at: None,
+            is_active: true,
+            metadata: None,
+            pinned: false,
+        };
+
+        let item: MemorySearchItem = memory.into();
+
+        assert_eq!(item.summary.len(), 100); // 97 + "..."
+        assert!(item.summary.ends_with("..."));
+        assert_eq!(item.content, long_content);
+        assert!(item.accessed.is_some());
+    }
+
+    #[test]
+    fn test_memory_search_item_json_serialization() {
+        let item = MemorySearchItem {
+            id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap(),
+            memory_type: MemoryType::Learning,
+            tier: Scope::Project,
+            summary: "Test summary".to_string(),
+            content: "Test content".to_string(),
+            tags: vec!["tag1".to_string(), "tag2".to_string()],
+            confidence: Confidence::High,
+            created: chrono::DateTime::parse_from_rfc3339("2024-01-15T10:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            accessed: None,
+            access_count: 5,
+            rank: None,
+            archived_at: None,
+            pinned: false,
+        };
+
+        let json = serde_json::to_string(&item).unwrap();
+
+        // Verify camelCase field names (matching Node.js output)
+        assert!(json.contains("\"type\":\"learning\"")); // renamed from memoryType
+        assert!(json.contains("\"accessCount\":5"));
+
+        // Verify field presence
+        assert!(json.contains("\"id\":"));
+        assert!(json.contains("\"tier\":\"project\""));
+        assert!(json.contains("\"summary\":"));
+        assert!(json.contains("\"content\":"));
+        assert!(json.contains("\"tags\":"));
+        assert!(json.contains("\"confidence\":\"high\""));
+        assert!(!json.contains("\"rank\""));
+    }
+
+    #[test]
+    fn test_search_result_json_serialization() {
+        let result = SearchResult {
+            results: vec![],
+            count: 0,
+            next_cursor: None,
+        };
+
+        let json = serde_json::to_string(&result).unwrap();
+
+        assert!(json.contains("\"results\":[]"));
+        assert!(json.contains("\"count\":0"));
+        assert!(!json.contains("nextCursor"));
+    }
+}
@@ -8,6 +8,7 @@ use uuid::Uuid;
 
 /// A conversation turn (prompt/response pair).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
 #[serde(rename_all = "camelCase")]
 pub struct Turn {
     /// Unique identifier
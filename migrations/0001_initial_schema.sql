@@ -0,0 +1,63 @@
+-- Memories, sessions, conversation turns and tool calls
+CREATE TABLE IF NOT EXISTS memories (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  type VARCHAR(20) NOT NULL,
+  scope VARCHAR(10) NOT NULL,
+  project_path TEXT,
+  content TEXT NOT NULL,
+  tags TEXT[] DEFAULT '{}',
+  confidence VARCHAR(10) DEFAULT 'medium',
+  source_session_id UUID,
+  source_turn_id UUID,
+  created_at TIMESTAMPTZ DEFAULT NOW(),
+  updated_at TIMESTAMPTZ DEFAULT NOW(),
+  accessed_at TIMESTAMPTZ,
+  access_count INT DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS sessions (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  claude_session_id TEXT UNIQUE,
+  project_path TEXT,
+  git_status JSONB,
+  models_used JSONB DEFAULT '{}',
+  status VARCHAR(20) DEFAULT 'active',
+  summary JSONB,
+  started_at TIMESTAMPTZ DEFAULT NOW(),
+  ended_at TIMESTAMPTZ,
+  created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS conversation_turns (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  session_id UUID REFERENCES sessions(id),
+  turn_number INT NOT NULL,
+  user_prompt TEXT NOT NULL,
+  assistant_response TEXT,
+  model_used VARCHAR(50),
+  input_tokens INT,
+  output_tokens INT,
+  started_at TIMESTAMPTZ DEFAULT NOW(),
+  ended_at TIMESTAMPTZ,
+  created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS tool_calls (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  session_id UUID REFERENCES sessions(id),
+  turn_id UUID REFERENCES conversation_turns(id),
+  tool_name VARCHAR(100) NOT NULL,
+  parameters JSONB,
+  result_summary TEXT,
+  called_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_memories_type ON memories(type);
+CREATE INDEX IF NOT EXISTS idx_memories_scope ON memories(scope);
+CREATE INDEX IF NOT EXISTS idx_memories_project ON memories(project_path);
+CREATE INDEX IF NOT EXISTS idx_memories_confidence ON memories(confidence);
+CREATE INDEX IF NOT EXISTS idx_memories_created ON memories(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_sessions_claude_id ON sessions(claude_session_id);
+CREATE INDEX IF NOT EXISTS idx_turns_session ON conversation_turns(session_id);
+CREATE INDEX IF NOT EXISTS idx_tool_calls_session ON tool_calls(session_id);
+CREATE INDEX IF NOT EXISTS idx_tool_calls_turn ON tool_calls(turn_id);
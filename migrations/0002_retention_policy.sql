@@ -0,0 +1,7 @@
+-- Supersession tracking and active status
+ALTER TABLE memories ADD COLUMN IF NOT EXISTS superseded_by UUID REFERENCES memories(id);
+ALTER TABLE memories ADD COLUMN IF NOT EXISTS superseded_at TIMESTAMPTZ;
+ALTER TABLE memories ADD COLUMN IF NOT EXISTS is_active BOOLEAN DEFAULT true;
+
+CREATE INDEX IF NOT EXISTS idx_memories_is_active ON memories(is_active);
+CREATE INDEX IF NOT EXISTS idx_memories_superseded_by ON memories(superseded_by);
@@ -0,0 +1,6 @@
+-- Flag memories whose referenced files were deleted or rewritten
+ALTER TABLE memories ADD COLUMN IF NOT EXISTS needs_review BOOLEAN DEFAULT false;
+ALTER TABLE memories ADD COLUMN IF NOT EXISTS review_reason TEXT;
+ALTER TABLE memories ADD COLUMN IF NOT EXISTS review_flagged_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_memories_needs_review ON memories(needs_review) WHERE needs_review;
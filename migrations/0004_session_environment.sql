@@ -0,0 +1,2 @@
+-- OS and toolchain versions captured at session start
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS environment JSONB;
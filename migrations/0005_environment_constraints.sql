@@ -0,0 +1,3 @@
+-- Per-memory metadata; {"constraints": ["os=macos", "rust>=1.75"]} limits
+-- which environments get-context loads the memory in
+ALTER TABLE memories ADD COLUMN IF NOT EXISTS metadata JSONB DEFAULT '{}';
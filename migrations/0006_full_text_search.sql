@@ -0,0 +1,9 @@
+-- Tags (weight A, unstemmed) and content (weight B, english stemming)
+-- for search-fts and search-keyword --mode fts
+ALTER TABLE memories ADD COLUMN IF NOT EXISTS search_vector tsvector
+  GENERATED ALWAYS AS (
+    setweight(array_to_tsvector(COALESCE(tags, '{}')), 'A') ||
+    setweight(to_tsvector('english', content), 'B')
+  ) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_memories_search_vector ON memories USING GIN(search_vector);
@@ -0,0 +1,5 @@
+-- Daily memory access totals for health-report
+CREATE TABLE IF NOT EXISTS memory_access_stats (
+  day DATE PRIMARY KEY,
+  accesses BIGINT NOT NULL DEFAULT 0
+);
@@ -0,0 +1,11 @@
+-- Extraction backend calls per UTC day, checked against extraction_daily_cap
+CREATE TABLE IF NOT EXISTS extraction_usage (
+  day DATE PRIMARY KEY,
+  calls INT NOT NULL DEFAULT 0
+);
+
+-- Turns whose extraction was deferred because the daily cap was reached
+CREATE TABLE IF NOT EXISTS extraction_queue (
+  turn_id UUID PRIMARY KEY REFERENCES conversation_turns(id) ON DELETE CASCADE,
+  queued_at TIMESTAMPTZ DEFAULT NOW()
+);
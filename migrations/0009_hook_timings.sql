@@ -0,0 +1,12 @@
+-- Wall-clock time of each hook run, for turn-latency
+CREATE TABLE IF NOT EXISTS hook_timings (
+  id BIGSERIAL PRIMARY KEY,
+  session_id UUID REFERENCES sessions(id) ON DELETE CASCADE,
+  turn_id UUID REFERENCES conversation_turns(id) ON DELETE CASCADE,
+  hook VARCHAR(30) NOT NULL,
+  started_at TIMESTAMPTZ NOT NULL,
+  duration_ms INT NOT NULL,
+  success BOOLEAN NOT NULL DEFAULT true
+);
+
+CREATE INDEX IF NOT EXISTS idx_hook_timings_session ON hook_timings(session_id);
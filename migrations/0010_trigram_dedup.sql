@@ -0,0 +1,2 @@
+-- Trigram similarity for near-duplicate detection (add-memory, consolidate)
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
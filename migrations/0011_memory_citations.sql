@@ -0,0 +1,11 @@
+-- Where memories were used, for where-used
+CREATE TABLE IF NOT EXISTS memory_citations (
+  id BIGSERIAL PRIMARY KEY,
+  memory_id UUID NOT NULL REFERENCES memories(id) ON DELETE CASCADE,
+  session_id UUID REFERENCES sessions(id) ON DELETE CASCADE,
+  turn_id UUID REFERENCES conversation_turns(id) ON DELETE SET NULL,
+  kind VARCHAR(10) NOT NULL CHECK (kind IN ('injected', 'cited')),
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_memory_citations_memory ON memory_citations(memory_id);
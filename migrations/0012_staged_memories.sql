@@ -0,0 +1,19 @@
+-- Candidates from propose-memory awaiting commit-memory; expired ones are
+-- added to the review queue
+CREATE TABLE IF NOT EXISTS staged_memories (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  type VARCHAR(20) NOT NULL,
+  scope VARCHAR(10) NOT NULL,
+  project_path TEXT,
+  content TEXT NOT NULL,
+  tags TEXT[] DEFAULT '{}',
+  confidence VARCHAR(10) NOT NULL,
+  source_session_id UUID,
+  source_turn_id UUID,
+  supersedes UUID,
+  metadata JSONB,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_staged_memories_expires ON staged_memories(expires_at);
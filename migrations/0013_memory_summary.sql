@@ -0,0 +1,25 @@
+-- Summary stored at write time, for context blocks, search results and
+-- duplicate checks. Existing memories get the rule of `summarize` in the
+-- client crate: the first sentence (ended by . ! or ? before whitespace and
+-- an uppercase letter) of the first line, cut to 100 characters.
+ALTER TABLE memories ADD COLUMN IF NOT EXISTS summary TEXT;
+
+UPDATE memories
+SET summary = CASE
+    WHEN char_length(first.sentence) > 100 THEN left(first.sentence, 97) || '...'
+    ELSE first.sentence
+  END
+FROM (
+  SELECT id,
+         COALESCE(substring(line FROM '^(.*?[.!?])\s+[[:upper:]]'), line) AS sentence
+  FROM (
+    SELECT id, rtrim(split_part(btrim(content, E' \t\r\n'), E'\n', 1), E' \t\r') AS line
+    FROM memories
+    WHERE summary IS NULL
+  ) lines
+) first
+WHERE memories.id = first.id;
+
+ALTER TABLE memories ALTER COLUMN summary SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_memories_type_summary ON memories (type, LOWER(summary));
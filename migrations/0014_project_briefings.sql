@@ -0,0 +1,7 @@
+-- One curated markdown briefing per project, emitted first by get-context
+CREATE TABLE IF NOT EXISTS project_briefings (
+  project_path TEXT PRIMARY KEY,
+  content TEXT NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
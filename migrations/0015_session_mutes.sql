@@ -0,0 +1,7 @@
+-- Memories muted for the rest of one session, left out of its context injections
+CREATE TABLE IF NOT EXISTS session_mutes (
+  session_id UUID NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+  memory_id UUID NOT NULL REFERENCES memories(id) ON DELETE CASCADE,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (session_id, memory_id)
+);
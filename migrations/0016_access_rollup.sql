@@ -0,0 +1,17 @@
+-- Memory accesses are appended here on the hot path and folded into
+-- memories.access_count, memory_access_daily and memory_access_stats by
+-- the access rollup
+CREATE TABLE IF NOT EXISTS memory_access_events (
+  memory_id UUID NOT NULL,
+  accessed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Per-memory daily access counts, for top-memories and stats
+CREATE TABLE IF NOT EXISTS memory_access_daily (
+  day DATE NOT NULL,
+  memory_id UUID NOT NULL REFERENCES memories(id) ON DELETE CASCADE,
+  accesses BIGINT NOT NULL DEFAULT 0,
+  PRIMARY KEY (day, memory_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_memory_access_daily_memory ON memory_access_daily(memory_id);
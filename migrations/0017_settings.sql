@@ -0,0 +1,7 @@
+-- Settings shared by everything using this database, one JSON value per key;
+-- `retention_policy` holds the policy managed by `policy set`
+CREATE TABLE IF NOT EXISTS settings (
+  key TEXT PRIMARY KEY,
+  value JSONB NOT NULL,
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
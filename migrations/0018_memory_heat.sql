@@ -0,0 +1,7 @@
+-- Relevance that cools while a memory goes unused, recomputed by `decay`.
+-- Cold high-confidence memories are archived: kept, and found by searches
+-- with --include-archived, but left out of the context block
+ALTER TABLE memories ADD COLUMN IF NOT EXISTS heat DOUBLE PRECISION NOT NULL DEFAULT 1.0;
+ALTER TABLE memories ADD COLUMN IF NOT EXISTS archived_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_memories_archived ON memories(archived_at) WHERE archived_at IS NOT NULL;
@@ -0,0 +1,10 @@
+-- Earlier contents of a memory, one row per content edit. Revision N is the
+-- content the memory had before its Nth edit; the memories row holds the
+-- current one
+CREATE TABLE IF NOT EXISTS memory_revisions (
+  memory_id UUID NOT NULL REFERENCES memories(id) ON DELETE CASCADE,
+  revision INTEGER NOT NULL,
+  content TEXT NOT NULL,
+  replaced_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (memory_id, revision)
+);
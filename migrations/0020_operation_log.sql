@@ -0,0 +1,18 @@
+-- Audit log of commands that changed memories, their retention or briefings:
+-- who ran what, when, and which memories it touched
+CREATE TABLE IF NOT EXISTS operation_log (
+  id BIGSERIAL PRIMARY KEY,
+  operation TEXT NOT NULL,
+  arguments TEXT[] NOT NULL DEFAULT '{}',
+  actor TEXT NOT NULL,
+  db_user TEXT NOT NULL DEFAULT current_user,
+  session_id TEXT,
+  project_path TEXT,
+  memory_ids UUID[] NOT NULL DEFAULT '{}',
+  success BOOLEAN NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_operation_log_created ON operation_log(created_at);
+CREATE INDEX IF NOT EXISTS idx_operation_log_memory_ids ON operation_log USING GIN (memory_ids);
+CREATE INDEX IF NOT EXISTS idx_operation_log_session ON operation_log(session_id) WHERE session_id IS NOT NULL;
@@ -0,0 +1,9 @@
+-- Embeddings of conversation turns (prompt and response) for `recall`
+CREATE TABLE IF NOT EXISTS turn_embeddings (
+  turn_id UUID PRIMARY KEY REFERENCES conversation_turns(id) ON DELETE CASCADE,
+  model TEXT NOT NULL,
+  embedding REAL[] NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_turn_embeddings_model ON turn_embeddings(model);
@@ -0,0 +1,17 @@
+-- Background extraction attempts and how each ended, so failures show up
+-- in extraction-log and health-report rather than only in hook logs
+CREATE TABLE IF NOT EXISTS extractions (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  session_id UUID REFERENCES sessions(id) ON DELETE CASCADE,
+  turn_id UUID REFERENCES conversation_turns(id) ON DELETE SET NULL,
+  source VARCHAR(20) NOT NULL,
+  status VARCHAR(20) NOT NULL
+    CHECK (status IN ('spawned', 'parsed', 'saved', 'skipped', 'failed')),
+  reason TEXT,
+  memory_id UUID REFERENCES memories(id) ON DELETE SET NULL,
+  started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  finished_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_extractions_session ON extractions(session_id);
+CREATE INDEX IF NOT EXISTS idx_extractions_started ON extractions(started_at);
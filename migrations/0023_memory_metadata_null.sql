@@ -0,0 +1,4 @@
+-- Memories without metadata hold NULL rather than an empty object, so rows
+-- decode straight into `Memory`
+ALTER TABLE memories ALTER COLUMN metadata DROP DEFAULT;
+UPDATE memories SET metadata = NULL WHERE metadata = '{}'::jsonb;
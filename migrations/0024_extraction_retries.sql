@@ -0,0 +1,3 @@
+-- Whether an extraction was retried with the repair prompt after the
+-- backend answered with unusable JSON
+ALTER TABLE extractions ADD COLUMN IF NOT EXISTS retried BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,3 @@
+-- Prompts UserPromptSubmit did not record as turns (too short, or over the
+-- per-session limit), so sessions still show how much was said
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS skipped_prompts INTEGER NOT NULL DEFAULT 0;
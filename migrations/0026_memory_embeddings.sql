@@ -0,0 +1,9 @@
+-- Embeddings of memory content for hybrid `search`
+CREATE TABLE IF NOT EXISTS memory_embeddings (
+  memory_id UUID PRIMARY KEY REFERENCES memories(id) ON DELETE CASCADE,
+  model TEXT NOT NULL,
+  embedding REAL[] NOT NULL,
+  embedded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_memory_embeddings_model ON memory_embeddings(model);
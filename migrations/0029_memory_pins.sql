@@ -0,0 +1,6 @@
+-- Pinned memories head the context block whatever their rank, and are
+-- never pruned, archived or removed as duplicates
+ALTER TABLE memories ADD COLUMN IF NOT EXISTS pinned BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE memories ADD COLUMN IF NOT EXISTS pinned_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_memories_pinned ON memories(pinned) WHERE pinned;
@@ -0,0 +1,11 @@
+-- Typed links between memories, beyond the linear supersession chain
+CREATE TABLE IF NOT EXISTS memory_links (
+  source_id UUID NOT NULL REFERENCES memories(id) ON DELETE CASCADE,
+  target_id UUID NOT NULL REFERENCES memories(id) ON DELETE CASCADE,
+  relation TEXT NOT NULL CHECK (relation IN ('relates-to', 'contradicts', 'refines')),
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (source_id, target_id, relation),
+  CHECK (source_id <> target_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_memory_links_target ON memory_links(target_id);
@@ -0,0 +1,18 @@
+-- sessions.models_used holds model names as TEXT[], which is what `Session`
+-- decodes; the initial schema declared it JSONB. Rebuilt through to_jsonb
+-- so the statements work whichever type a database has: JSONB arrays keep
+-- their elements and objects their keys.
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS models_used_list TEXT[] DEFAULT '{}';
+
+UPDATE sessions SET models_used_list = ARRAY(
+  SELECT name FROM jsonb_array_elements_text(
+    CASE WHEN jsonb_typeof(to_jsonb(models_used)) = 'array' THEN to_jsonb(models_used) ELSE '[]' END
+  ) AS name
+  UNION ALL
+  SELECT name FROM jsonb_object_keys(
+    CASE WHEN jsonb_typeof(to_jsonb(models_used)) = 'object' THEN to_jsonb(models_used) ELSE '{}' END
+  ) AS name
+);
+
+ALTER TABLE sessions DROP COLUMN models_used;
+ALTER TABLE sessions RENAME COLUMN models_used_list TO models_used;
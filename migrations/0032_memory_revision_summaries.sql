@@ -0,0 +1,24 @@
+-- The summary a memory had with each earlier content, so it is read back
+-- with that content at a past time. Revisions kept before this get the
+-- rule of migration 0013 applied to their content.
+ALTER TABLE memory_revisions ADD COLUMN IF NOT EXISTS summary TEXT;
+
+UPDATE memory_revisions
+SET summary = CASE
+    WHEN char_length(first.sentence) > 100 THEN left(first.sentence, 97) || '...'
+    ELSE first.sentence
+  END
+FROM (
+  SELECT memory_id, revision,
+         COALESCE(substring(line FROM '^(.*?[.!?])\s+[[:upper:]]'), line) AS sentence
+  FROM (
+    SELECT memory_id, revision,
+           rtrim(split_part(btrim(content, E' \t\r\n'), E'\n', 1), E' \t\r') AS line
+    FROM memory_revisions
+    WHERE summary IS NULL
+  ) lines
+) first
+WHERE memory_revisions.memory_id = first.memory_id
+  AND memory_revisions.revision = first.revision;
+
+ALTER TABLE memory_revisions ALTER COLUMN summary SET NOT NULL;
@@ -0,0 +1,30 @@
+-- Memories inserted without a summary, by older clients or by hand, get the
+-- one migration 0013 derives from content instead of failing NOT NULL. A
+-- column default cannot read other columns, so a trigger fills it in.
+CREATE OR REPLACE FUNCTION memory_summary(content TEXT) RETURNS TEXT
+LANGUAGE sql IMMUTABLE AS $$
+  SELECT CASE
+      WHEN char_length(first.sentence) > 100 THEN left(first.sentence, 97) || '...'
+      ELSE first.sentence
+    END
+  FROM (
+    SELECT COALESCE(substring(line FROM '^(.*?[.!?])\s+[[:upper:]]'), line) AS sentence
+    FROM (
+      SELECT rtrim(split_part(btrim(content, E' \t\r\n'), E'\n', 1), E' \t\r') AS line
+    ) lines
+  ) first
+$$;
+
+CREATE OR REPLACE FUNCTION fill_memory_summary() RETURNS trigger
+LANGUAGE plpgsql AS $$
+BEGIN
+  NEW.summary := memory_summary(NEW.content);
+  RETURN NEW;
+END
+$$;
+
+DROP TRIGGER IF EXISTS memories_summary_default ON memories;
+CREATE TRIGGER memories_summary_default
+  BEFORE INSERT ON memories
+  FOR EACH ROW WHEN (NEW.summary IS NULL)
+  EXECUTE FUNCTION fill_memory_summary();
@@ -0,0 +1,3 @@
+-- Memories without metadata default to an empty object again, as before
+-- migration 0023. Queries read an empty object back as no metadata.
+ALTER TABLE memories ALTER COLUMN metadata SET DEFAULT '{}';
@@ -0,0 +1,364 @@
+//! Browser state and key handling
+//!
+//! Keys only change state and name the database work to do next (an
+//! [`Action`]); the event loop in `browse` performs it. That keeps every
+//! interaction testable without a terminal or a database.
+
+use ratatui::crossterm::event::KeyCode;
+use uuid::Uuid;
+
+use crate::db::queries::ChainResult;
+use crate::models::{Memory, MemoryType, Tier};
+
+/// Memories loaded per page
+pub const PAGE_SIZE: i64 = 50;
+
+/// Type filter values in the order `t` cycles through them
+const TYPES: [MemoryType; 6] = [
+    MemoryType::Convention,
+    MemoryType::Architecture,
+    MemoryType::Gotcha,
+    MemoryType::Api,
+    MemoryType::Learning,
+    MemoryType::Preference,
+];
+
+/// What the browser is showing or editing
+#[derive(Debug)]
+pub enum Mode {
+    List,
+    /// Supersession chain of the selected memory
+    Chain(ChainResult),
+    /// Editing the selected memory's content
+    Edit(String),
+    /// Typing a tag filter
+    TagFilter(String),
+    ConfirmDelete,
+}
+
+/// Database work requested by a key press
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    None,
+    Reload,
+    LoadChain(Uuid),
+    Save(Uuid, String),
+    Delete(Uuid),
+    Quit,
+}
+
+/// Browser state
+#[derive(Debug)]
+pub struct App {
+    pub memories: Vec<Memory>,
+    /// Memories matching the filters, across all pages
+    pub total: i64,
+    pub offset: i64,
+    pub selected: usize,
+    pub memory_type: Option<MemoryType>,
+    pub tier: Tier,
+    pub tag: Option<String>,
+    pub mode: Mode,
+    /// Result of the last action, shown until the next key
+    pub status: Option<String>,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self {
+            memories: Vec::new(),
+            total: 0,
+            offset: 0,
+            selected: 0,
+            memory_type: None,
+            tier: Tier::Both,
+            tag: None,
+            mode: Mode::List,
+            status: None,
+        }
+    }
+}
+
+impl App {
+    pub fn selected_memory(&self) -> Option<&Memory> {
+        self.memories.get(self.selected)
+    }
+
+    /// Replace the current page, keeping the selection in range
+    pub fn set_page(&mut self, memories: Vec<Memory>, total: i64) {
+        self.memories = memories;
+        self.total = total;
+        self.selected = self.selected.min(self.memories.len().saturating_sub(1));
+    }
+
+    /// Apply a key press and return the database work it calls for
+    pub fn handle_key(&mut self, key: KeyCode) -> Action {
+        self.status = None;
+        match std::mem::replace(&mut self.mode, Mode::List) {
+            Mode::List => self.handle_list_key(key),
+            Mode::Chain(chain) => {
+                if !matches!(key, KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('c')) {
+                    self.mode = Mode::Chain(chain);
+                }
+                Action::None
+            }
+            Mode::Edit(buffer) => self.handle_edit_key(key, buffer),
+            Mode::TagFilter(buffer) => self.handle_tag_key(key, buffer),
+            Mode::ConfirmDelete => match (key, self.selected_memory()) {
+                (KeyCode::Char('y'), Some(memory)) => Action::Delete(memory.id),
+                _ => Action::None,
+            },
+        }
+    }
+
+    fn handle_list_key(&mut self, key: KeyCode) -> Action {
+        let selected_id = self.selected_memory().map(|m| m.id);
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.selected + 1 < self.memories.len() {
+                    self.selected += 1;
+                }
+                Action::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::PageDown | KeyCode::Right | KeyCode::Char('n') => {
+                if self.offset + PAGE_SIZE >= self.total {
+                    return Action::None;
+                }
+                self.offset += PAGE_SIZE;
+                self.selected = 0;
+                Action::Reload
+            }
+            KeyCode::PageUp | KeyCode::Left | KeyCode::Char('p') => {
+                if self.offset == 0 {
+                    return Action::None;
+                }
+                self.offset = (self.offset - PAGE_SIZE).max(0);
+                self.selected = 0;
+                Action::Reload
+            }
+            KeyCode::Char('t') => {
+                self.memory_type = next_type(self.memory_type);
+                self.first_page()
+            }
+            KeyCode::Char('s') => {
+                self.tier = match self.tier {
+                    Tier::Both => Tier::Project,
+                    Tier::Project => Tier::Global,
+                    Tier::Global => Tier::Both,
+                };
+                self.first_page()
+            }
+            KeyCode::Char('/') => {
+                self.mode = Mode::TagFilter(self.tag.clone().unwrap_or_default());
+                Action::None
+            }
+            KeyCode::Char('r') => Action::Reload,
+            KeyCode::Char('c') => selected_id.map(Action::LoadChain).unwrap_or(Action::None),
+            KeyCode::Char('e') => {
+                if let Some(memory) = self.selected_memory() {
+                    self.mode = Mode::Edit(memory.content.clone());
+                }
+                Action::None
+            }
+            KeyCode::Char('d') => {
+                if selected_id.is_some() {
+                    self.mode = Mode::ConfirmDelete;
+                }
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn handle_edit_key(&mut self, key: KeyCode, mut buffer: String) -> Action {
+        match key {
+            KeyCode::Esc => Action::None,
+            KeyCode::Enter => match self.selected_memory() {
+                Some(memory) if !buffer.trim().is_empty() && buffer != memory.content => {
+                    Action::Save(memory.id, buffer.trim().to_string())
+                }
+                _ => Action::None,
+            },
+            KeyCode::Backspace => {
+                buffer.pop();
+                self.mode = Mode::Edit(buffer);
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                buffer.push(c);
+                self.mode = Mode::Edit(buffer);
+                Action::None
+            }
+            _ => {
+                self.mode = Mode::Edit(buffer);
+                Action::None
+            }
+        }
+    }
+
+    fn handle_tag_key(&mut self, key: KeyCode, mut buffer: String) -> Action {
+        match key {
+            KeyCode::Esc => Action::None,
+            KeyCode::Enter => {
+                let tag = buffer.trim();
+                self.tag = (!tag.is_empty()).then(|| tag.to_string());
+                self.first_page()
+            }
+            KeyCode::Backspace => {
+                buffer.pop();
+                self.mode = Mode::TagFilter(buffer);
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                buffer.push(c);
+                self.mode = Mode::TagFilter(buffer);
+                Action::None
+            }
+            _ => {
+                self.mode = Mode::TagFilter(buffer);
+                Action::None
+            }
+        }
+    }
+
+    /// Start over from the first page after a filter change
+    fn first_page(&mut self) -> Action {
+        self.offset = 0;
+        self.selected = 0;
+        Action::Reload
+    }
+}
+
+/// Next type filter: all types, then each type in turn
+fn next_type(current: Option<MemoryType>) -> Option<MemoryType> {
+    match current {
+        None => Some(TYPES[0]),
+        Some(t) => TYPES
+            .iter()
+            .position(|x| *x == t)
+            .and_then(|i| TYPES.get(i + 1))
+            .copied(),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_memory, Confidence};
+
+    fn memory(content: &str) -> Memory {
+        Memory {
+            confidence: Confidence::Medium,
+            ..test_memory(content, MemoryType::Learning)
+        }
+    }
+
+    fn app_with(contents: &[&str], total: i64) -> App {
+        let mut app = App::default();
+        app.set_page(contents.iter().map(|c| memory(c)).collect(), total);
+        app
+    }
+
+    #[test]
+    fn test_selection_stays_in_page() {
+        let mut app = app_with(&["a", "b"], 2);
+        app.handle_key(KeyCode::Up);
+        assert_eq!(app.selected, 0);
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Down);
+        assert_eq!(app.selected, 1);
+
+        app.set_page(vec![memory("only")], 1);
+        assert_eq!(app.selected, 0);
+    }
+
+    #[test]
+    fn test_paging_bounds() {
+        let mut app = app_with(&["a"], PAGE_SIZE + 1);
+        assert_eq!(app.handle_key(KeyCode::PageUp), Action::None);
+        assert_eq!(app.handle_key(KeyCode::PageDown), Action::Reload);
+        assert_eq!(app.offset, PAGE_SIZE);
+        assert_eq!(app.handle_key(KeyCode::PageDown), Action::None);
+        assert_eq!(app.handle_key(KeyCode::PageUp), Action::Reload);
+        assert_eq!(app.offset, 0);
+    }
+
+    #[test]
+    fn test_filter_cycles_reset_to_first_page() {
+        let mut app = app_with(&["a"], PAGE_SIZE * 3);
+        app.offset = PAGE_SIZE;
+
+        assert_eq!(app.handle_key(KeyCode::Char('t')), Action::Reload);
+        assert_eq!(app.memory_type, Some(MemoryType::Convention));
+        assert_eq!(app.offset, 0);
+
+        assert_eq!(next_type(Some(MemoryType::Preference)), None);
+        app.handle_key(KeyCode::Char('s'));
+        assert_eq!(app.tier, Tier::Project);
+    }
+
+    #[test]
+    fn test_tag_filter_input() {
+        let mut app = app_with(&["a"], 1);
+        app.handle_key(KeyCode::Char('/'));
+        for c in "sqlx".chars() {
+            app.handle_key(KeyCode::Char(c));
+        }
+        assert_eq!(app.handle_key(KeyCode::Enter), Action::Reload);
+        assert_eq!(app.tag.as_deref(), Some("sqlx"));
+
+        // An empty filter clears it
+        app.handle_key(KeyCode::Char('/'));
+        for _ in 0..4 {
+            app.handle_key(KeyCode::Backspace);
+        }
+        app.handle_key(KeyCode::Enter);
+        assert!(app.tag.is_none());
+    }
+
+    #[test]
+    fn test_edit_saves_changed_content() {
+        let mut app = app_with(&["old"], 1);
+        let id = app.memories[0].id;
+
+        app.handle_key(KeyCode::Char('e'));
+        app.handle_key(KeyCode::Char('!'));
+        assert_eq!(app.handle_key(KeyCode::Enter), Action::Save(id, "old!".to_string()));
+        assert!(matches!(app.mode, Mode::List));
+
+        // 'q' while editing is text, not quit
+        app.handle_key(KeyCode::Char('e'));
+        assert_eq!(app.handle_key(KeyCode::Char('q')), Action::None);
+        assert!(matches!(app.mode, Mode::Edit(ref b) if b == "oldq"));
+        assert_eq!(app.handle_key(KeyCode::Esc), Action::None);
+    }
+
+    #[test]
+    fn test_delete_needs_confirmation() {
+        let mut app = app_with(&["a"], 1);
+        let id = app.memories[0].id;
+
+        app.handle_key(KeyCode::Char('d'));
+        assert_eq!(app.handle_key(KeyCode::Char('n')), Action::None);
+        app.handle_key(KeyCode::Char('d'));
+        assert_eq!(app.handle_key(KeyCode::Char('y')), Action::Delete(id));
+    }
+
+    #[test]
+    fn test_empty_page_ignores_item_actions() {
+        let mut app = App::default();
+        assert_eq!(app.handle_key(KeyCode::Char('c')), Action::None);
+        app.handle_key(KeyCode::Char('d'));
+        assert!(matches!(app.mode, Mode::List));
+        assert_eq!(app.handle_key(KeyCode::Char('q')), Action::Quit);
+    }
+}
@@ -0,0 +1,106 @@
+//! Interactive terminal browser for memories (`browse`)
+//!
+//! Pages through active memories with type, tier and tag filters, shows a
+//! memory's supersession chain, and edits or deletes entries in place. The
+//! JSON commands remain the interface for scripts and hooks; this is for
+//! looking around.
+
+pub mod app;
+pub mod ui;
+
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use ratatui::DefaultTerminal;
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+
+use crate::commands::search::tier_to_scope_filter;
+use crate::db::queries;
+use crate::error::Result;
+
+use app::{Action, App, Mode, PAGE_SIZE};
+
+/// Changes made during a browse session
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowseSummary {
+    pub updated: usize,
+    pub deleted: usize,
+}
+
+/// Run the browser until the user quits.
+///
+/// The terminal is restored even when a database error ends the session.
+pub async fn browse(pool: &PgPool, project_paths: &[String]) -> Result<BrowseSummary> {
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, pool, project_paths).await;
+    ratatui::restore();
+    result
+}
+
+async fn run(
+    terminal: &mut DefaultTerminal,
+    pool: &PgPool,
+    project_paths: &[String],
+) -> Result<BrowseSummary> {
+    let mut app = App::default();
+    let mut summary = BrowseSummary::default();
+    load_page(pool, project_paths, &mut app).await?;
+
+    loop {
+        terminal.draw(|frame| ui::draw(frame, &app))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match app.handle_key(key.code) {
+            Action::None => {}
+            Action::Quit => break,
+            Action::Reload => load_page(pool, project_paths, &mut app).await?,
+            Action::LoadChain(id) => match queries::show_chain(pool, id).await {
+                Ok(chain) => app.mode = Mode::Chain(chain),
+                Err(e) => app.status = Some(format!("Could not load chain: {}", e)),
+            },
+            Action::Save(id, content) => {
+                queries::update_memory(pool, id, &content, None, None, None).await?;
+                summary.updated += 1;
+                load_page(pool, project_paths, &mut app).await?;
+                app.status = Some("Memory updated".to_string());
+            }
+            Action::Delete(id) => {
+                queries::delete_memory(pool, id).await?;
+                summary.deleted += 1;
+                load_page(pool, project_paths, &mut app).await?;
+                // Deleting the last entry of the last page steps back a page
+                if app.memories.is_empty() && app.offset > 0 {
+                    app.offset = (app.offset - PAGE_SIZE).max(0);
+                    load_page(pool, project_paths, &mut app).await?;
+                }
+                app.status = Some("Memory deleted".to_string());
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Load the page at the current offset and filters
+async fn load_page(pool: &PgPool, project_paths: &[String], app: &mut App) -> Result<()> {
+    let (scope_filter, include_both) = tier_to_scope_filter(app.tier);
+    let (memories, total) = queries::list_memories_page(
+        pool,
+        app.memory_type,
+        app.tag.as_deref(),
+        scope_filter,
+        project_paths,
+        include_both,
+        app.offset,
+        PAGE_SIZE,
+    )
+    .await?;
+    app.set_page(memories, total);
+    Ok(())
+}
@@ -0,0 +1,197 @@
+//! Browser rendering
+
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::Frame;
+
+use crate::db::queries::ChainResult;
+use crate::models::{Memory, MemorySummary};
+
+use super::app::{App, Mode};
+
+const LIST_HELP: &str =
+    "↑↓ move  ←→ page  t type  s tier  / tag  c chain  e edit  d delete  r reload  q quit";
+
+/// Draw the whole browser: filter bar, memory list, detail pane, key line
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [header, body, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [list_area, detail_area] =
+        Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)]).areas(body);
+
+    frame.render_widget(Paragraph::new(header_line(app)), header);
+    draw_list(frame, app, list_area);
+    match &app.mode {
+        Mode::Chain(chain) => draw_chain(frame, chain, detail_area),
+        _ => draw_detail(frame, app.selected_memory(), detail_area),
+    }
+    frame.render_widget(Paragraph::new(footer_line(app)), footer);
+}
+
+fn header_line(app: &App) -> String {
+    let range = if app.memories.is_empty() {
+        "0".to_string()
+    } else {
+        format!("{}-{}", app.offset + 1, app.offset + app.memories.len() as i64)
+    };
+    format!(
+        " Memories {} of {} │ type: {} │ tier: {} │ tag: {}",
+        range,
+        app.total,
+        app.memory_type.map(|t| t.as_str()).unwrap_or("all"),
+        app.tier.as_str(),
+        app.tag.as_deref().unwrap_or("-"),
+    )
+}
+
+fn footer_line(app: &App) -> String {
+    match &app.mode {
+        Mode::Edit(buffer) => format!(" Edit: {}█  (Enter save, Esc cancel)", buffer),
+        Mode::TagFilter(buffer) => {
+            format!(" Tag: {}█  (Enter apply, empty clears, Esc cancel)", buffer)
+        }
+        Mode::ConfirmDelete => " Delete this memory? (y/n)".to_string(),
+        Mode::Chain(_) => " Esc back".to_string(),
+        Mode::List => match &app.status {
+            Some(status) => format!(" {}", status),
+            None => format!(" {}", LIST_HELP),
+        },
+    }
+}
+
+fn draw_list(frame: &mut Frame, app: &App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .memories
+        .iter()
+        .map(|m| {
+            ListItem::new(format!(
+                "{} {:<12} {}",
+                m.confidence.symbol(),
+                m.memory_type.as_str(),
+                m.content.lines().next().unwrap_or("")
+            ))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(" Memories "))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(app.selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_detail(frame: &mut Frame, memory: Option<&Memory>, area: Rect) {
+    let block = Block::default().borders(Borders::ALL).title(" Detail ");
+    let Some(memory) = memory else {
+        frame.render_widget(Paragraph::new("No memories match.").block(block), area);
+        return;
+    };
+
+    let mut lines: Vec<Line> = memory.content.lines().map(Line::from).collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(format!("id:         {}", memory.id)));
+    lines.push(Line::from(format!(
+        "type:       {} ({}, {} confidence)",
+        memory.memory_type.as_str(),
+        memory.scope.as_str(),
+        memory.confidence.as_str()
+    )));
+    if !memory.tags.is_empty() {
+        lines.push(Line::from(format!("tags:       {}", memory.tags.join(", "))));
+    }
+    if let Some(path) = &memory.project_path {
+        lines.push(Line::from(format!("project:    {}", path)));
+    }
+    lines.push(Line::from(format!(
+        "created:    {}",
+        memory.created_at.format("%Y-%m-%d %H:%M")
+    )));
+    lines.push(Line::from(format!(
+        "updated:    {}",
+        memory.updated_at.format("%Y-%m-%d %H:%M")
+    )));
+    lines.push(Line::from(format!("accessed:   {} times", memory.access_count)));
+    if let Some(successor) = memory.superseded_by {
+        lines.push(Line::from(format!("superseded: by {}", successor)));
+    }
+
+    frame.render_widget(
+        Paragraph::new(lines).block(block).wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+fn draw_chain(frame: &mut Frame, chain: &ChainResult, area: Rect) {
+    let entry = |label: &str, summary: &MemorySummary| {
+        Line::from(format!(
+            "{} {} {}  {}",
+            label,
+            summary.created.format("%Y-%m-%d"),
+            summary.confidence.symbol(),
+            summary.summary
+        ))
+    };
+
+    let mut lines = Vec::new();
+    for predecessor in &chain.predecessors {
+        lines.push(entry("  older", predecessor));
+    }
+    lines.push(entry("→ this ", &chain.memory));
+    for successor in &chain.successors {
+        lines.push(entry("  newer", successor));
+    }
+    if chain.predecessors.is_empty() && chain.successors.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from("This memory has not superseded or been superseded by another."));
+    }
+
+    let block = Block::default().borders(Borders::ALL).title(" Supersession Chain ");
+    frame.render_widget(
+        Paragraph::new(lines).block(block).wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{MemoryType, Tier};
+
+    #[test]
+    fn test_header_line_shows_range_and_filters() {
+        let app = App {
+            total: 120,
+            offset: 50,
+            memory_type: Some(MemoryType::Gotcha),
+            tier: Tier::Project,
+            tag: Some("sqlx".to_string()),
+            ..App::default()
+        };
+        assert_eq!(
+            header_line(&app),
+            " Memories 0 of 120 │ type: gotcha │ tier: project │ tag: sqlx"
+        );
+    }
+
+    #[test]
+    fn test_footer_line_follows_mode() {
+        let mut app = App::default();
+        assert_eq!(footer_line(&app), format!(" {}", LIST_HELP));
+
+        app.status = Some("Memory deleted".to_string());
+        assert_eq!(footer_line(&app), " Memory deleted");
+
+        app.mode = Mode::TagFilter("gi".to_string());
+        assert!(footer_line(&app).starts_with(" Tag: gi█"));
+    }
+}
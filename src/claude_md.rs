@@ -0,0 +1,102 @@
+//! Memories already said by the project's CLAUDE.md
+//!
+//! Claude Code puts CLAUDE.md in the system prompt, so session start leaves
+//! out memories it covers rather than repeat them. A memory is covered when
+//! one block of the file (a paragraph, list item or heading) contains most
+//! of the memory's content words; the whole file is not compared at once,
+//! as a long file holds nearly any word somewhere.
+
+use std::fs;
+use std::path::Path;
+
+use crate::conflict::content_words;
+
+/// Files Claude Code loads from the project root, in the order it reads them
+const FILE_NAMES: &[&str] = &["CLAUDE.md", ".claude/CLAUDE.md", "CLAUDE.local.md"];
+
+/// Memories with fewer content words are never counted as covered
+const MIN_WORDS: usize = 2;
+
+/// The content words of each block of a project's CLAUDE.md files
+#[derive(Debug, Clone, Default)]
+pub struct ClaudeMd {
+    blocks: Vec<Vec<String>>,
+}
+
+impl ClaudeMd {
+    /// Read the CLAUDE.md files of `project_path`, or None when it has none
+    pub fn load(project_path: &Path) -> Option<Self> {
+        let text: Vec<String> = FILE_NAMES
+            .iter()
+            .filter_map(|name| fs::read_to_string(project_path.join(name)).ok())
+            .collect();
+        if text.is_empty() {
+            return None;
+        }
+        Some(Self::parse(&text.join("\n\n")))
+    }
+
+    /// Split markdown into blocks at blank lines, list items and headings
+    pub fn parse(text: &str) -> Self {
+        let mut blocks = Vec::new();
+        let mut block = String::new();
+        for line in text.lines() {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || starts_block(trimmed) {
+                blocks.push(content_words(&block));
+                block.clear();
+            }
+            block.push_str(trimmed);
+            block.push('\n');
+        }
+        blocks.push(content_words(&block));
+        blocks.retain(|words| !words.is_empty());
+        Self { blocks }
+    }
+
+    /// Whether one block contains at least `overlap` (0.0 - 1.0) of the
+    /// content words of `text`
+    pub fn covers(&self, text: &str, overlap: f64) -> bool {
+        let words = content_words(text);
+        if words.len() < MIN_WORDS {
+            return false;
+        }
+        self.blocks.iter().any(|block| {
+            let shared = words.iter().filter(|w| block.binary_search(w).is_ok()).count();
+            shared as f64 / words.len() as f64 >= overlap
+        })
+    }
+}
+
+fn starts_block(line: &str) -> bool {
+    line.starts_with('#')
+        || line.starts_with("- ")
+        || line.starts_with("* ")
+        || line.split_once(". ").is_some_and(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLAUDE_MD: &str = "# Conventions\n\n\
+        - Run migrations with sqlx before\n  starting the server\n\
+        - Commit messages start with the ticket id\n\n\
+        The API returns errors as JSON objects with a code field.\n";
+
+    #[test]
+    fn test_covers_memory_said_in_one_block() {
+        let claude_md = ClaudeMd::parse(CLAUDE_MD);
+        assert!(claude_md.covers("Run migrations with sqlx before starting the server", 0.8));
+        assert!(claude_md.covers("API errors are JSON objects with a code field", 0.8));
+        // Words spread over two list items
+        assert!(!claude_md.covers("Run migrations before commit messages", 0.8));
+        assert!(!claude_md.covers("Docker volumes drop file events on macOS", 0.8));
+        // Too short to tell
+        assert!(!claude_md.covers("Use sqlx", 0.8));
+    }
+}
@@ -1,9 +1,24 @@
 // CLI Parser - Clap derive definitions
 // Matches exact argument signatures from Node.js memory.js
 
-use clap::{Parser, Subcommand};
-
+use std::path::PathBuf;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use uuid::Uuid;
+
+use crate::commands::bundle::DEFAULT_LOG_LINES;
+use crate::commands::links::LinkRelation;
+use crate::commands::search::SearchMode;
+use crate::db::{LockMode, SearchFilters};
+use crate::environment::EnvConstraint;
+use crate::error::HippocampusError;
+use crate::logging::LogLevel;
 use crate::models::memory::{Confidence, MemoryType, Scope, Tier};
+use crate::models::SessionStatus;
+use crate::output::OutputFormat;
+use crate::util::cursor::Cursor;
+use crate::util::workers::DEFAULT_JOBS;
 
 /// Claude-Hippocampus: Memory System CLI
 #[derive(Parser, Debug)]
@@ -11,10 +26,45 @@ use crate::models::memory::{Confidence, MemoryType, Scope, Tier};
 #[command(version)]
 #[command(about = "PostgreSQL-backed persistent memory for Claude Code sessions")]
 pub struct Cli {
+    /// Print no diagnostics on stderr
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Print more diagnostics on stderr: -v info, -vv debug (with each query), -vvv trace
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Also read project memories of the other projects in the current
+    /// project's workspaces
+    #[arg(long, global = true)]
+    pub workspace: bool,
+
+    /// Output format: json (default); table or plain for list-recent,
+    /// search-keyword and stats; sql or mermaid for schema; jsonl or json
+    /// for export-conversations
+    #[arg(long, global = true, value_enum)]
+    pub format: Option<OutputFormat>,
+
     #[command(subcommand)]
     pub command: Command,
 }
 
+impl Cli {
+    /// Least severe diagnostics printed on stderr; None with --quiet.
+    /// Warnings and errors by default.
+    pub fn stderr_level(&self) -> Option<LogLevel> {
+        if self.quiet {
+            return None;
+        }
+        Some(match self.verbose {
+            0 => LogLevel::Warn,
+            1 => LogLevel::Info,
+            2 => LogLevel::Debug,
+            _ => LogLevel::Trace,
+        })
+    }
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Command {
     /// Add a new memory entry
@@ -45,6 +95,81 @@ pub enum Command {
         /// ID of memory this supersedes (marks old memory as inactive)
         #[arg(long = "supersedes")]
         supersedes: Option<String>,
+        /// Environment the memory applies to, e.g. os=linux or rust>=1.75 (repeatable)
+        #[arg(long = "constraint", value_parser = parse_constraint)]
+        constraints: Vec<EnvConstraint>,
+        /// Summary for context blocks and search results (default: first sentence)
+        #[arg(long = "summary")]
+        summary: Option<String>,
+        /// Drop a --session, --turn or --supersedes that is not a UUID instead of failing
+        #[arg(long = "ignore-invalid-refs")]
+        ignore_invalid_refs: bool,
+        /// Report similar memories of the same type that the new one may contradict
+        #[arg(long = "check-conflicts")]
+        check_conflicts: bool,
+    },
+
+    /// Stage a memory and report how it compares with existing memories.
+    ///
+    /// Nothing is added until commit-memory. A proposal left uncommitted
+    /// past its TTL is added flagged for review, unless it is a duplicate.
+    ProposeMemory {
+        /// Memory type: convention, architecture, gotcha, api, learning, preference
+        #[arg(value_parser = parse_memory_type)]
+        memory_type: MemoryType,
+        /// The content of the memory
+        content: String,
+        /// Comma-separated tags (optional)
+        #[arg(default_value = "")]
+        tags: String,
+        /// Confidence level: high, medium, low
+        #[arg(default_value = "high", value_parser = parse_confidence)]
+        confidence: Confidence,
+        /// Tier: project, global
+        #[arg(default_value = "project", value_parser = parse_scope)]
+        tier: Scope,
+        /// Source session ID
+        #[arg(long = "session")]
+        source_session_id: Option<String>,
+        /// Source turn ID
+        #[arg(long = "turn")]
+        source_turn_id: Option<String>,
+        /// ID of memory this supersedes (marks old memory as inactive on commit)
+        #[arg(long = "supersedes")]
+        supersedes: Option<String>,
+        /// Environment the memory applies to, e.g. os=linux or rust>=1.75 (repeatable)
+        #[arg(long = "constraint", value_parser = parse_constraint)]
+        constraints: Vec<EnvConstraint>,
+        /// Hours before an uncommitted proposal goes to the review queue
+        #[arg(long = "ttl-hours", default_value = "24")]
+        ttl_hours: u32,
+        /// Drop a --session, --turn or --supersedes that is not a UUID instead of failing
+        #[arg(long = "ignore-invalid-refs")]
+        ignore_invalid_refs: bool,
+    },
+
+    /// Add a memory staged by propose-memory
+    CommitMemory {
+        /// Staging ID returned by propose-memory
+        staging_id: String,
+        /// ID of memory this supersedes (overrides the proposal's)
+        #[arg(long = "supersedes")]
+        supersedes: Option<String>,
+    },
+
+    /// Add memories from newline-delimited JSON on stdin, in one transaction.
+    ///
+    /// One record per line with the fields of add-memory: type, content,
+    /// and optionally tags, confidence, tier, session, turn, supersedes and
+    /// constraints.
+    AddMemoriesBatch {
+        /// Report what would be added, skipped and superseded without
+        /// adding anything
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+        /// Records not added to list in a dry run
+        #[arg(long, default_value = "10", requires = "dry_run")]
+        conflicts: usize,
     },
 
     /// Update an existing memory entry
@@ -56,6 +181,42 @@ pub enum Command {
         /// Tier: project, global
         #[arg(default_value = "project", value_parser = parse_scope)]
         tier: Scope,
+        /// Only update if the memory's updatedAt still equals this (RFC3339, from get-memory)
+        #[arg(long = "expected-updated-at")]
+        expected_updated_at: Option<DateTime<Utc>>,
+        /// Add the new content as a new memory superseding this one, keeping its history
+        #[arg(long = "as-new")]
+        as_new: bool,
+    },
+
+    /// Change individual fields of a memory (content, type, confidence, tags)
+    #[command(group(
+        clap::ArgGroup::new("edits")
+            .required(true)
+            .multiple(true)
+            .args(["content", "memory_type", "confidence", "add_tags", "remove_tags"])
+    ))]
+    EditMemory {
+        /// Memory ID (UUID)
+        id: String,
+        /// New content
+        #[arg(long)]
+        content: Option<String>,
+        /// New type: convention, architecture, gotcha, api, learning, preference
+        #[arg(long = "type", value_parser = parse_memory_type)]
+        memory_type: Option<MemoryType>,
+        /// New confidence: high, medium, low
+        #[arg(long, value_parser = parse_confidence)]
+        confidence: Option<Confidence>,
+        /// Tag to add (repeatable; kept once, ignoring case)
+        #[arg(long = "add-tag", value_parser = parse_tag)]
+        add_tags: Vec<String>,
+        /// Tag to remove (repeatable; ignoring case)
+        #[arg(long = "remove-tag", value_parser = parse_tag)]
+        remove_tags: Vec<String>,
+        /// Only edit if the memory's updatedAt still equals this (RFC3339, from get-memory)
+        #[arg(long = "expected-updated-at")]
+        expected_updated_at: Option<DateTime<Utc>>,
     },
 
     /// Delete a memory entry
@@ -71,18 +232,74 @@ pub enum Command {
     GetMemory {
         /// Memory ID (UUID)
         id: String,
+        /// Show the version of the memory that was active at this time
+        #[arg(long = "as-of", value_parser = parse_timestamp)]
+        as_of: Option<DateTime<Utc>>,
+        /// Also return the memories linked to it
+        #[arg(long)]
+        neighborhood: bool,
     },
 
     /// Search memories by keyword
     SearchKeyword {
         /// Search query
         query: String,
-        /// Tier filter: project, global, both
-        #[arg(default_value = "both", value_parser = parse_tier)]
-        tier: Tier,
+        /// Tier filter: project, global, both (default: default_tier from config)
+        #[arg(value_parser = parse_tier)]
+        tier: Option<Tier>,
+        /// Maximum results to return
+        #[arg(default_value = "30")]
+        limit: i64,
+        /// Matching strategy: ilike (substring) or fts (full-text, ranked)
+        #[arg(long, value_enum, default_value_t = SearchMode::Ilike)]
+        mode: SearchMode,
+        /// Search the memories that were active at this time
+        #[arg(long = "as-of", value_parser = parse_timestamp)]
+        as_of: Option<DateTime<Utc>>,
+        /// Also search memories archived by `decay`
+        #[arg(long = "include-archived")]
+        include_archived: bool,
+        #[command(flatten)]
+        filters: SearchFilterArgs,
+        #[command(flatten)]
+        page: PageArgs,
+    },
+
+    /// Full-text search ranked by relevance (web search syntax: "phrase", -word, or)
+    SearchFts {
+        /// Search query
+        query: String,
+        /// Tier filter: project, global, both (default: default_tier from config)
+        #[arg(value_parser = parse_tier)]
+        tier: Option<Tier>,
         /// Maximum results to return
         #[arg(default_value = "30")]
         limit: i64,
+        /// Search the memories that were active at this time
+        #[arg(long = "as-of", value_parser = parse_timestamp)]
+        as_of: Option<DateTime<Utc>>,
+        /// Also search memories archived by `decay`
+        #[arg(long = "include-archived")]
+        include_archived: bool,
+        #[command(flatten)]
+        filters: SearchFilterArgs,
+        #[command(flatten)]
+        page: PageArgs,
+    },
+
+    /// Search memories by full-text and semantic similarity, fused into one
+    /// ranking with each source's scores
+    Search {
+        /// Search query
+        query: String,
+        /// Tier filter: project, global, both (default: default_tier from config)
+        #[arg(value_parser = parse_tier)]
+        tier: Option<Tier>,
+        /// Maximum results to return
+        #[arg(default_value = "10")]
+        limit: usize,
+        #[command(flatten)]
+        filters: SearchFilterArgs,
     },
 
     /// Search memories by type (with optional keyword filter)
@@ -92,12 +309,68 @@ pub enum Command {
         memory_type: MemoryType,
         /// Optional keyword filter
         query: Option<String>,
-        /// Tier filter: project, global, both
-        #[arg(default_value = "both", value_parser = parse_tier)]
-        tier: Tier,
+        /// Tier filter: project, global, both (default: default_tier from config)
+        #[arg(value_parser = parse_tier)]
+        tier: Option<Tier>,
+        /// Maximum results to return
+        #[arg(default_value = "30")]
+        limit: i64,
+        /// Also search memories archived by `decay`
+        #[arg(long = "include-archived")]
+        include_archived: bool,
+        #[command(flatten)]
+        filters: SearchFilterArgs,
+        #[command(flatten)]
+        page: PageArgs,
+    },
+
+    /// Search memories carrying a tag (ignoring case)
+    SearchByTag {
+        /// Tag to match
+        #[arg(value_parser = parse_tag)]
+        tag: String,
+        /// Tier filter: project, global, both (default: default_tier from config)
+        #[arg(value_parser = parse_tier)]
+        tier: Option<Tier>,
         /// Maximum results to return
         #[arg(default_value = "30")]
         limit: i64,
+        /// Also search memories archived by `decay`
+        #[arg(long = "include-archived")]
+        include_archived: bool,
+    },
+
+    /// List tags in use with memory counts
+    ListTags {
+        /// Tier filter: project, global, both (default: default_tier from config)
+        #[arg(value_parser = parse_tier)]
+        tier: Option<Tier>,
+    },
+
+    /// Rename a tag on all active memories
+    RenameTag {
+        /// Current tag (ignoring case)
+        #[arg(value_parser = parse_tag)]
+        from: String,
+        /// New tag
+        #[arg(value_parser = parse_tag)]
+        to: String,
+        /// Tier filter: project, global, both
+        #[arg(default_value = "both", value_parser = parse_tier)]
+        tier: Tier,
+    },
+
+    /// Merge several tags into one on all active memories
+    MergeTags {
+        /// Tags to merge away (ignoring case)
+        #[arg(required = true, value_parser = parse_tag)]
+        sources: Vec<String>,
+        /// Tag they become
+        #[arg(long, value_parser = parse_tag)]
+        into: String,
+        /// Tier filter: project, global, both
+        #[arg(long, default_value = "both", value_parser = parse_tier)]
+        tier: Tier,
     },
 
     /// Get context block for injection
@@ -105,6 +378,21 @@ pub enum Command {
         /// Maximum entries to return
         #[arg(default_value = "10")]
         limit: i64,
+        /// Only memories created or updated since the project's previous session
+        #[arg(long)]
+        since_last_session: bool,
+        /// Fit the block into this many (estimated) tokens, dropping the lowest-ranked memories
+        #[arg(long)]
+        max_tokens: Option<usize>,
+        /// Group entries under a heading per memory type (Conventions, Gotchas, ...)
+        #[arg(long)]
+        group_by_type: bool,
+        /// Most entries of one type, e.g. gotcha=3 (repeatable)
+        #[arg(long = "type-limit", value_parser = parse_type_limit)]
+        type_limits: Vec<(MemoryType, usize)>,
+        /// Render the block through this Tera template instead of the built-in markdown
+        #[arg(long)]
+        template: Option<PathBuf>,
     },
 
     /// List recent memory entries
@@ -112,29 +400,66 @@ pub enum Command {
         /// Number of entries
         #[arg(default_value = "10")]
         n: i64,
-        /// Tier filter: project, global, both
-        #[arg(default_value = "both", value_parser = parse_tier)]
-        tier: Tier,
+        /// Tier filter: project, global, both (default: default_tier from config)
+        #[arg(value_parser = parse_tier)]
+        tier: Option<Tier>,
+        #[command(flatten)]
+        page: PageArgs,
     },
 
+    /// Browse, filter, edit and delete memories in an interactive terminal UI
+    Browse,
+
     /// Merge duplicate memory entries
     Consolidate {
         /// Tier: project, global
         #[arg(default_value = "project", value_parser = parse_scope)]
         tier: Scope,
+        /// Fold duplicates into the copy kept (tags, confidence, access count) and mark them superseded instead of deleting them
+        #[arg(long)]
+        merge: bool,
+        #[command(flatten)]
+        lock: LockArgs,
+    },
+
+    /// Merge project memories with a matching global memory, superseding one of each pair
+    #[command(name = "dedupe-across-scopes")]
+    DedupeAcrossScopes {
+        /// Scope to keep for every pair (default: ask for each pair on the terminal)
+        #[arg(long, value_parser = parse_scope)]
+        prefer: Option<Scope>,
+        #[command(flatten)]
+        lock: LockArgs,
     },
 
     /// Remove old low-confidence entries with tiered retention
     Prune {
-        /// Days threshold for LOW confidence entries (remove if older than this, access_count=0)
-        #[arg(long = "low-days", default_value = "30")]
-        low_days: i64,
-        /// Days threshold for MEDIUM confidence entries (remove if older than this, access_count=0)
-        #[arg(long = "medium-days", default_value = "90")]
-        medium_days: i64,
+        /// Days threshold for LOW confidence entries (remove if older than this, access_count=0;
+        /// default: prune.low_days, 30)
+        #[arg(long = "low-days")]
+        low_days: Option<i64>,
+        /// Days threshold for MEDIUM confidence entries (remove if older than this, access_count=0;
+        /// default: prune.medium_days, 90)
+        #[arg(long = "medium-days")]
+        medium_days: Option<i64>,
         /// Tier: project, global
         #[arg(default_value = "project", value_parser = parse_scope)]
         tier: Scope,
+        #[command(flatten)]
+        lock: LockArgs,
+    },
+
+    /// Recompute memory heat and archive cold HIGH confidence memories
+    Decay {
+        /// Days without use before a HIGH confidence memory is archived
+        /// (default: prune.archive_days, 180)
+        #[arg(long = "archive-days")]
+        archive_days: Option<i64>,
+        /// Report what would change without changing it
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+        #[command(flatten)]
+        lock: LockArgs,
     },
 
     /// Save session summary
@@ -153,40 +478,303 @@ pub enum Command {
         id: String,
     },
 
+    /// Link one memory to another
+    Link {
+        /// Memory the link starts at (UUID)
+        source: String,
+        /// Memory the link points to (UUID)
+        target: String,
+        /// How the first memory relates to the second
+        #[arg(long, value_enum, default_value_t = LinkRelation::RelatesTo)]
+        relation: LinkRelation,
+    },
+
+    /// Remove the links between two memories
+    Unlink {
+        /// Memory ID (UUID)
+        a: String,
+        /// Memory ID (UUID)
+        b: String,
+        /// Only remove links of this relation
+        #[arg(long, value_enum)]
+        relation: Option<LinkRelation>,
+    },
+
+    /// Mark a memory as superseded by another existing memory
+    Supersede {
+        /// Memory to retire (UUID)
+        old_id: String,
+        /// Memory that replaces it (UUID)
+        new_id: String,
+    },
+
+    /// Make a superseded memory active again
+    Unsupersede {
+        /// Superseded memory (UUID)
+        id: String,
+        /// Retire the memory that superseded it, superseding it by this one
+        #[arg(long)]
+        deactivate_successor: bool,
+    },
+
+    /// List every content a memory has had, oldest first
+    History {
+        /// Memory ID (UUID)
+        id: String,
+    },
+
+    /// Show what an edit changed in a memory's content
+    Diff {
+        /// Memory ID (UUID)
+        id: String,
+
+        /// Revision the edit produced, diffed against the one before [default: current]
+        #[arg(long)]
+        rev: Option<i32>,
+    },
+
+    /// Show where a memory came from: source session, turn and nearby tool calls
+    ShowProvenance {
+        /// Memory ID (UUID)
+        id: String,
+    },
+
     /// List superseded (inactive) memories
     ListSuperseded {
-        /// Tier filter: project, global, both
-        #[arg(default_value = "both", value_parser = parse_tier)]
-        tier: Tier,
+        /// Tier filter: project, global, both (default: default_tier from config)
+        #[arg(value_parser = parse_tier)]
+        tier: Option<Tier>,
         /// Maximum results to return
         #[arg(default_value = "50")]
         limit: i64,
+        #[command(flatten)]
+        page: PageArgs,
     },
 
     /// Purge old superseded memories
     PurgeSuperseded {
-        /// Days threshold (remove superseded entries older than this)
-        #[arg(default_value = "30")]
-        days: i64,
+        /// Days threshold (remove superseded entries older than this; default:
+        /// prune.superseded_days, 30)
+        days: Option<i64>,
         /// Tier: project, global
         #[arg(default_value = "project", value_parser = parse_scope)]
         tier: Scope,
+        #[command(flatten)]
+        lock: LockArgs,
     },
 
     /// Prune lifecycle data (tool calls, turns, sessions)
     PruneData {
-        /// Days to keep tool calls (older will be deleted)
-        #[arg(long = "tool-calls-days", default_value = "14")]
-        tool_calls_days: i64,
-        /// Days to keep conversation turns (older will be deleted)
-        #[arg(long = "turns-days", default_value = "30")]
-        turns_days: i64,
-        /// Days to keep sessions (older completed sessions will be deleted)
-        #[arg(long = "sessions-days", default_value = "90")]
-        sessions_days: i64,
+        /// Days to keep tool calls (older will be deleted; default: prune.tool_calls_days, 14)
+        #[arg(long = "tool-calls-days")]
+        tool_calls_days: Option<i64>,
+        /// Days to keep conversation turns (older will be deleted; default: prune.turns_days, 30)
+        #[arg(long = "turns-days")]
+        turns_days: Option<i64>,
+        /// Days to keep sessions (older completed sessions will be deleted; default:
+        /// prune.sessions_days, 90)
+        #[arg(long = "sessions-days")]
+        sessions_days: Option<i64>,
         /// Dry run (show what would be deleted without actually deleting)
         #[arg(long = "dry-run")]
         dry_run: bool,
+        #[command(flatten)]
+        lock: LockArgs,
+    },
+
+    /// Manage memories flagged for review
+    Review {
+        #[command(subcommand)]
+        action: ReviewAction,
+    },
+
+    /// Cross-check api/convention memories against the project's files and
+    /// flag stale ones for review
+    Verify {
+        /// Project path (defaults to PROJECT_PATH or the current directory)
+        #[arg(long)]
+        project: Option<String>,
+    },
+
+    /// Brief a new contributor: architecture, conventions, gotchas, recent sessions
+    Onboard {
+        /// Project path (defaults to PROJECT_PATH or the current directory)
+        #[arg(long)]
+        project: Option<String>,
+        /// Maximum architecture and convention entries
+        #[arg(long, default_value = "10")]
+        limit: usize,
+        /// Number of recent session summaries
+        #[arg(long, default_value = "5")]
+        sessions: i64,
+    },
+
+    /// Compare high-confidence convention memories with the project's CLAUDE.md
+    DriftCheck {
+        /// Project path (defaults to PROJECT_PATH or the current directory)
+        #[arg(long)]
+        project: Option<String>,
+        /// Include a patch adding remembered conventions to CLAUDE.md
+        #[arg(long)]
+        patch: bool,
+    },
+
+    /// Set the project's pinned briefing (markdown; read from stdin when omitted)
+    SetBriefing {
+        /// Briefing content
+        content: Option<String>,
+        /// Project path (defaults to PROJECT_PATH or the current directory)
+        #[arg(long)]
+        project: Option<String>,
+        /// Remove the briefing instead
+        #[arg(long, conflicts_with = "content")]
+        clear: bool,
+    },
+
+    /// Show the project's pinned briefing
+    ShowBriefing {
+        /// Project path (defaults to PROJECT_PATH or the current directory)
+        #[arg(long)]
+        project: Option<String>,
+    },
+
+    /// Distill failed-then-fixed tool calls in a session into gotcha memories
+    MineGotchas {
+        /// Session ID (database UUID or Claude session ID)
+        #[arg(long)]
+        session: String,
+        /// List failure/fix sequences without extracting or saving anything
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+    },
+
+    /// Claim one extraction call from today's budget (queues the turn when over)
+    ReserveExtraction {
+        /// Turn to queue if the daily cap is reached
+        #[arg(long)]
+        turn: Option<String>,
+    },
+
+    /// Extract a memory from one turn (spawned detached by the Stop hook)
+    #[command(hide = true)]
+    ExtractWorker {
+        /// Extraction prompt for claude --print
+        #[arg(long)]
+        prompt: String,
+        /// Confidence for the saved memory: high, medium, low
+        #[arg(long, default_value = "medium", value_parser = parse_confidence)]
+        confidence: Confidence,
+        /// Source session ID
+        #[arg(long)]
+        session: Option<String>,
+        /// Source turn ID, queued if the daily cap is reached
+        #[arg(long)]
+        turn: Option<String>,
+    },
+
+    /// Extract memories from turns queued while over the daily cap
+    ExtractQueued {
+        /// Maximum queued turns to process
+        #[arg(long, default_value = "20")]
+        limit: i64,
+    },
+
+    /// List a session's extraction attempts and how each ended
+    ExtractionLog {
+        /// Session ID (database UUID or Claude session ID)
+        #[arg(long)]
+        session: String,
+        /// Maximum attempts to list, newest first
+        #[arg(long, default_value = "50")]
+        limit: i64,
+    },
+
+    /// List the sessions and turns a memory was injected or cited in
+    WhereUsed {
+        /// Memory ID (UUID)
+        id: String,
+    },
+
+    /// Keep a memory out of a session's context injections for the rest of the session
+    Mute {
+        /// Memory ID (UUID)
+        id: String,
+        /// Session ID (database UUID or Claude session ID)
+        #[arg(long)]
+        session: String,
+        /// Unmute the memory instead
+        #[arg(long)]
+        undo: bool,
+    },
+
+    /// Pin a memory to the top of every context block, safe from pruning
+    Pin {
+        /// Memory ID (UUID)
+        id: String,
+    },
+
+    /// Unpin a memory
+    Unpin {
+        /// Memory ID (UUID)
+        id: String,
+    },
+
+    /// Break a session's turn time into hook, model and tool time
+    TurnLatency {
+        /// Session ID (database UUID or Claude session ID)
+        #[arg(long)]
+        session: String,
+    },
+
+    /// Find past exchanges similar to a query, with the sessions they were in
+    Recall {
+        /// What the exchange was about
+        query: String,
+        /// Maximum exchanges to return
+        #[arg(long, default_value = "5")]
+        limit: usize,
+        /// Search sessions of every project, not just PROJECT_PATH's
+        #[arg(long = "all-projects")]
+        all_projects: bool,
+    },
+
+    /// Embed answered turns that have no embedding yet, for recall
+    EmbedTurns {
+        /// Only this turn (UUID)
+        #[arg(long)]
+        turn: Option<String>,
+        /// Maximum turns to embed, newest first
+        #[arg(long, default_value = "100")]
+        limit: i64,
+    },
+
+    /// Embed active memories that have no up-to-date embedding, for search
+    EmbedMemories {
+        /// Maximum memories to embed, newest first
+        #[arg(long, default_value = "500")]
+        limit: i64,
+    },
+
+    /// List recorded commands that changed memories, newest first
+    Audit {
+        /// Only commands that touched this memory (UUID)
+        #[arg(long)]
+        memory: Option<String>,
+        /// Only commands run from this Claude session
+        #[arg(long)]
+        session: Option<String>,
+        /// Only this command, e.g. delete-memory
+        #[arg(long)]
+        operation: Option<String>,
+        /// Only commands at or after this time (RFC3339, YYYY-MM-DD, or relative like 12h/7d)
+        #[arg(long, value_parser = parse_timestamp)]
+        since: Option<DateTime<Utc>>,
+        /// Only commands at or before this time (RFC3339, YYYY-MM-DD, or relative like 12h/7d)
+        #[arg(long, value_parser = parse_timestamp)]
+        until: Option<DateTime<Utc>>,
+        /// Maximum entries to return
+        #[arg(long, default_value = "50")]
+        limit: i64,
     },
 
     /// View operation logs
@@ -196,18 +784,149 @@ pub enum Command {
         n: i64,
         /// Filter by operation type
         operation: Option<String>,
+        /// Only show failed operations
+        #[arg(long)]
+        failed: bool,
+        /// Only show entries at or after this time (RFC3339, YYYY-MM-DD, or relative like 12h/7d)
+        #[arg(long, value_parser = parse_timestamp)]
+        since: Option<DateTime<Utc>>,
+        /// Only show entries at or before this time (RFC3339, YYYY-MM-DD, or relative like 12h/7d)
+        #[arg(long, value_parser = parse_timestamp)]
+        until: Option<DateTime<Utc>>,
+        /// Only show operations whose name starts with this prefix
+        #[arg(long = "op-prefix")]
+        op_prefix: Option<String>,
+        /// Only show entries at this level or more severe
+        #[arg(long, value_enum)]
+        level: Option<LogLevel>,
     },
 
     /// Clear all logs
     ClearLogs,
 
+    /// Show or change configuration (~/.claude/hippocampus.toml, .hippocampus.toml)
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Show or change the retention policy stored in the database
+    Policy {
+        #[command(subcommand)]
+        action: PolicyAction,
+    },
+
+    /// Group projects whose memories can be read together with --workspace
+    Workspace {
+        #[command(subcommand)]
+        action: WorkspaceAction,
+    },
+
+    /// Manage the project registry: other paths a project's memories are stored under
+    Project {
+        #[command(subcommand)]
+        action: ProjectAction,
+    },
+
+    /// Flag drops in memory access or spikes in memory creation
+    HealthReport,
+
+    /// Check the database, schema, claude CLI, hooks and directories, with fixes
+    Doctor {
+        /// Print a plain-text report instead of JSON
+        #[arg(long)]
+        text: bool,
+    },
+
+    /// Collect config, schema version, logs, hook inputs and health report into a tarball for bug reports
+    DebugBundle {
+        /// Where to write the tarball [default: ./hippocampus-debug-<timestamp>.tar.gz]
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        /// Lines kept from the end of each log file
+        #[arg(long, default_value_t = DEFAULT_LOG_LINES)]
+        log_lines: usize,
+    },
+
+    /// Apply pending schema migrations
+    Migrate {
+        /// Only report the current schema version and pending migrations
+        #[arg(long)]
+        status: bool,
+    },
+
+    /// Print the schema the migrations manage, as SQL or a Mermaid ER diagram
+    Schema {
+        /// --format sql (default) or mermaid
+        #[arg(from_global)]
+        format: Option<OutputFormat>,
+    },
+
+    /// Import memories from a legacy Node.js database into this one
+    MigrateLegacy {
+        /// Connection string of the legacy database
+        #[arg(long)]
+        from: String,
+
+        /// Legacy memories table, optionally schema-qualified
+        #[arg(long, default_value = "memories")]
+        table: String,
+
+        /// Report what would be imported without writing anything
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+
+        /// Rows written at once
+        #[arg(long, default_value_t = DEFAULT_JOBS, value_parser = parse_jobs)]
+        jobs: usize,
+    },
+
+    /// Fill the database with synthetic sessions and memories for testing
+    GenerateFixtures {
+        /// Memories to generate
+        #[arg(long, default_value = "1000")]
+        memories: usize,
+        /// Sessions to generate
+        #[arg(long, default_value = "50")]
+        sessions: usize,
+        /// Projects to spread them over, named /fixtures/project-N
+        #[arg(long, default_value = "3")]
+        projects: usize,
+        /// Days back the generated history reaches
+        #[arg(long, default_value = "365")]
+        days: i64,
+        /// Seed that reproduces the data (default: random)
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Delete earlier fixtures first
+        #[arg(long)]
+        clear: bool,
+    },
+
     /// Show memory statistics
     Stats {
-        /// Tier filter: project, global, both
-        #[arg(default_value = "both", value_parser = parse_tier)]
-        tier: Tier,
+        /// Tier filter: project, global, both (default: default_tier from config)
+        #[arg(value_parser = parse_tier)]
+        tier: Option<Tier>,
+    },
+
+    /// List the most accessed memories over recent days
+    TopMemories {
+        /// Tier filter: project, global, both (default: default_tier from config)
+        #[arg(value_parser = parse_tier)]
+        tier: Option<Tier>,
+        /// Days of access counts to rank by
+        #[arg(long, default_value = "30")]
+        days: i32,
+        /// Maximum entries
+        #[arg(long, default_value = "10")]
+        limit: i64,
     },
 
+    /// Fold pending memory access events into access counts and daily totals
+    RollupAccess,
+
     // =========================================================================
     // Session Management Commands
     // =========================================================================
@@ -237,6 +956,62 @@ pub enum Command {
         summary: Option<String>,
     },
 
+    /// List sessions, newest first, with turn and memory counts
+    ListSessions {
+        /// Only sessions with this status: active, completed, orphaned
+        #[arg(long, value_parser = parse_session_status)]
+        status: Option<SessionStatus>,
+        /// Only sessions started in this project
+        #[arg(long = "project-path")]
+        project_path: Option<String>,
+        /// Only sessions started at or after this time (RFC3339, YYYY-MM-DD, or 30m/12h/7d ago)
+        #[arg(long, value_parser = parse_timestamp)]
+        since: Option<DateTime<Utc>>,
+        /// Maximum sessions per page
+        #[arg(long, default_value = "20")]
+        limit: i64,
+        /// Sessions to skip, for paging
+        #[arg(long, default_value = "0")]
+        offset: i64,
+    },
+
+    /// Find sessions by their summary, git branch or files changed at start
+    SearchMeta {
+        /// Text to look for, ignoring case (e.g. feature/login)
+        query: String,
+        /// Only sessions started in this project
+        #[arg(long = "project-path")]
+        project_path: Option<String>,
+        /// Maximum sessions to return
+        #[arg(long, default_value = "20")]
+        limit: i64,
+    },
+
+    /// Replay a session as a transcript: prompts, responses, tool calls, memories created
+    ShowSession {
+        /// Session ID (UUID or claude_session_id)
+        id: String,
+        /// Print a plain-text transcript instead of JSON
+        #[arg(long)]
+        text: bool,
+    },
+
+    /// Export a project's prompt/response pairs, redacted and with the memories used, as a dataset
+    ExportConversations {
+        /// Project path (default: PROJECT_PATH or the current directory)
+        #[arg(long)]
+        project: Option<String>,
+        /// Only sessions started at or after this time (RFC3339, YYYY-MM-DD, or 30m/12h/7d ago)
+        #[arg(long, value_parser = parse_timestamp)]
+        since: Option<DateTime<Utc>>,
+        /// --format jsonl (default) or json
+        #[arg(from_global)]
+        format: Option<OutputFormat>,
+        /// Sessions read at once; the export keeps their order
+        #[arg(long, default_value_t = DEFAULT_JOBS, value_parser = parse_jobs)]
+        jobs: usize,
+    },
+
     // =========================================================================
     // Turn Management Commands
     // =========================================================================
@@ -270,10 +1045,13 @@ pub enum Command {
         output_tokens: Option<i32>,
     },
 
-    /// Get the current turn number for a session
+    /// Show a session's latest turn: number, timestamps and token counts
     GetTurn {
-        /// Session ID (claude_session_id, required)
+        /// Session ID (UUID or claude_session_id)
         session_id: String,
+        /// List every turn of the session
+        #[arg(long)]
+        all: bool,
     },
 
     // =========================================================================
@@ -285,10 +1063,57 @@ pub enum Command {
         #[command(subcommand)]
         hook_type: HookType,
     },
+
+    /// Add the hooks to ~/.claude/settings.json, replacing earlier entries
+    InstallHooks {
+        /// Write the project's .claude/settings.json instead
+        #[arg(long)]
+        project: bool,
+        /// Remove the hooks instead
+        #[arg(long)]
+        uninstall: bool,
+        /// Binary the hooks run (default: this executable)
+        #[arg(long, conflicts_with = "uninstall")]
+        binary: Option<String>,
+    },
+
+    /// Dry-run a hook against a captured input; all writes are rolled back
+    SimulateHook {
+        /// Hook type: session-start, user-prompt-submit, stop, session-end, post-tool-use
+        #[arg(value_enum)]
+        hook_type: HookType,
+        /// JSON file with the hook's stdin payload
+        #[arg(long)]
+        input: String,
+    },
+
+    /// Re-run a hook with its most recently recorded input (needs record_hooks in hippocampus.toml)
+    ReplayLastHook {
+        /// Hook type: session-start, user-prompt-submit, stop, session-end, post-tool-use
+        #[arg(value_enum)]
+        hook_type: HookType,
+        /// Roll back writes and skip side effects, as in simulate-hook
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Keep database connections open and run other invocations' commands on them
+    ///
+    /// Listens on ~/.claude/hippocampus.sock; while it runs, commands are
+    /// sent there instead of connecting themselves.
+    Daemon {
+        /// Exit after this many seconds without a command (0: never)
+        #[arg(long, default_value = "3600")]
+        idle_timeout: u64,
+    },
+
+    /// Run `claude-hippocampus-<name>` from PATH with the remaining arguments
+    #[command(external_subcommand)]
+    External(Vec<String>),
 }
 
 /// Hook types that can be invoked from settings.json
-#[derive(Subcommand, Debug, Clone, PartialEq)]
+#[derive(Subcommand, ValueEnum, Debug, Clone, PartialEq)]
 pub enum HookType {
     /// Session start hook - creates session, loads context
     SessionStart,
@@ -296,17 +1121,437 @@ pub enum HookType {
     UserPromptSubmit,
     /// Stop hook - runs after each Claude response
     Stop,
+    /// Subagent stop hook - extracts memories from a finished subagent
+    SubagentStop,
+    /// Notification hook - logs notifications to the session's tool calls
+    Notification,
     /// Session end hook - ends session, cleanup
     SessionEnd,
     /// Post tool use hook - records tool calls to database
     PostToolUse,
 }
 
-// Custom parsers for enum types
+/// How a maintenance command waits for another maintenance run
+#[derive(Args, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LockArgs {
+    /// Wait for a maintenance run in progress to finish instead of failing
+    #[arg(long)]
+    pub wait: bool,
+    /// Exit successfully without doing anything if a maintenance run is in progress
+    #[arg(long = "skip-if-running", conflicts_with = "wait")]
+    pub skip_if_running: bool,
+}
+
+impl LockArgs {
+    pub fn mode(&self) -> LockMode {
+        match (self.wait, self.skip_if_running) {
+            (true, _) => LockMode::Wait,
+            (_, true) => LockMode::Skip,
+            _ => LockMode::Fail,
+        }
+    }
+}
+
+/// Conditions narrowing a keyword or type search
+#[derive(Args, Debug, Clone, Default, PartialEq)]
+pub struct SearchFilterArgs {
+    /// Only memories created at or after this time (RFC3339, YYYY-MM-DD, or relative like 12h/7d)
+    #[arg(long, value_parser = parse_timestamp)]
+    pub since: Option<DateTime<Utc>>,
+    /// Only memories created at or before this time (RFC3339, YYYY-MM-DD, or relative like 12h/7d)
+    #[arg(long, value_parser = parse_timestamp)]
+    pub until: Option<DateTime<Utc>>,
+    /// Only memories of this confidence: high, medium, low
+    #[arg(long, value_parser = parse_confidence)]
+    pub confidence: Option<Confidence>,
+    /// Only memories carrying this tag (ignoring case)
+    #[arg(long, value_parser = parse_tag)]
+    pub tag: Option<String>,
+}
+
+impl SearchFilterArgs {
+    pub fn filters(self) -> SearchFilters {
+        SearchFilters {
+            since: self.since,
+            until: self.until,
+            confidence: self.confidence,
+            tag: self.tag,
+        }
+    }
+}
+
+/// Where a page of list or search results starts
+#[derive(Args, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PageArgs {
+    /// Skip this many results
+    #[arg(long, value_parser = clap::value_parser!(i64).range(0..))]
+    pub offset: Option<i64>,
+    /// Start where the page that returned this nextCursor ended
+    #[arg(long, value_parser = parse_cursor, conflicts_with = "offset")]
+    pub cursor: Option<Cursor>,
+}
+
+impl PageArgs {
+    /// Results to skip
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0)
+    }
+}
+
+/// Whether `name` is a built-in subcommand, which aliases and external
+/// subcommands cannot replace
+pub fn is_builtin_subcommand(name: &str) -> bool {
+    name == "help" || Cli::command().find_subcommand(name).is_some()
+}
+
+/// Index in `args` (the program name first) of the subcommand: the first
+/// argument that is neither a flag nor the value of a global option, as
+/// `table` in `--format table list-recent`
+pub fn subcommand_position<S: AsRef<std::ffi::OsStr>>(args: &[S]) -> usize {
+    let command = Cli::command();
+    let takes_value = |long: &str| {
+        command
+            .get_arguments()
+            .any(|arg| arg.get_long() == Some(long) && arg.get_action().takes_values())
+    };
+    let mut position = 1;
+    while let Some(arg) = args.get(position) {
+        let arg = arg.as_ref().to_string_lossy();
+        if !arg.starts_with('-') {
+            return position;
+        }
+        position += match arg.strip_prefix("--") {
+            Some(long) if takes_value(long) => 2,
+            _ => 1,
+        };
+    }
+    args.len()
+}
+
+impl Command {
+    /// Check every argument that must be a UUID, before anything connects
+    /// to the database. The error names the offending argument.
+    pub fn validate_refs(&self) -> crate::error::Result<()> {
+        match self {
+            Command::AddMemory {
+                source_session_id,
+                source_turn_id,
+                supersedes,
+                ignore_invalid_refs,
+                ..
+            }
+            | Command::ProposeMemory {
+                source_session_id,
+                source_turn_id,
+                supersedes,
+                ignore_invalid_refs,
+                ..
+            } => {
+                parse_ref("--session", source_session_id.as_deref(), *ignore_invalid_refs)?;
+                parse_ref("--turn", source_turn_id.as_deref(), *ignore_invalid_refs)?;
+                parse_ref("--supersedes", supersedes.as_deref(), *ignore_invalid_refs)?;
+            }
+            Command::CommitMemory { staging_id, supersedes } => {
+                parse_uuid_arg("<staging-id>", staging_id)?;
+                parse_ref("--supersedes", supersedes.as_deref(), false)?;
+            }
+            Command::UpdateMemory { id, .. }
+            | Command::EditMemory { id, .. }
+            | Command::DeleteMemory { id, .. }
+            | Command::GetMemory { id, .. }
+            | Command::ShowChain { id }
+            | Command::History { id }
+            | Command::Diff { id, .. }
+            | Command::ShowProvenance { id }
+            | Command::WhereUsed { id }
+            | Command::Mute { id, .. }
+            | Command::Pin { id }
+            | Command::Unpin { id }
+            | Command::Unsupersede { id, .. }
+            | Command::Review {
+                action: ReviewAction::Resolve { id },
+            } => {
+                parse_uuid_arg("<id>", id)?;
+            }
+            Command::Link { source: a, target: b, .. } | Command::Unlink { a, b, .. } => {
+                parse_uuid_arg("<id>", a)?;
+                parse_uuid_arg("<id>", b)?;
+            }
+            Command::Supersede { old_id, new_id } => {
+                parse_uuid_arg("<old-id>", old_id)?;
+                parse_uuid_arg("<new-id>", new_id)?;
+            }
+            Command::ReserveExtraction { turn } | Command::EmbedTurns { turn, .. } => {
+                parse_ref("--turn", turn.as_deref(), false)?;
+            }
+            Command::ExtractWorker { session, turn, .. } => {
+                parse_ref("--session", session.as_deref(), false)?;
+                parse_ref("--turn", turn.as_deref(), false)?;
+            }
+            Command::UpdateTurn { turn_id, .. } => {
+                parse_uuid_arg("--turn-id", turn_id)?;
+            }
+            Command::Audit { memory, .. } => {
+                parse_ref("--memory", memory.as_deref(), false)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Whether the command changes memories, their retention or briefings
+    /// and is recorded in the audit log. Session and turn bookkeeping done
+    /// by hooks is not.
+    pub fn is_audited(&self) -> bool {
+        match self {
+            Command::AddMemory { .. }
+            | Command::ProposeMemory { .. }
+            | Command::CommitMemory { .. }
+            | Command::UpdateMemory { .. }
+            | Command::EditMemory { .. }
+            | Command::DeleteMemory { .. }
+            | Command::RenameTag { .. }
+            | Command::MergeTags { .. }
+            | Command::Consolidate { .. }
+            | Command::DedupeAcrossScopes { .. }
+            | Command::Prune { .. }
+            | Command::PurgeSuperseded { .. }
+            | Command::SaveSessionSummary { .. }
+            | Command::SetBriefing { .. }
+            | Command::GenerateFixtures { .. }
+            | Command::ExtractWorker { .. }
+            | Command::ExtractQueued { .. }
+            | Command::Mute { .. }
+            | Command::Pin { .. }
+            | Command::Unpin { .. }
+            | Command::Link { .. }
+            | Command::Unlink { .. }
+            | Command::Supersede { .. }
+            | Command::Unsupersede { .. }
+            | Command::Verify { .. }
+            | Command::Review {
+                action: ReviewAction::Resolve { .. },
+            }
+            | Command::Policy {
+                action: PolicyAction::Set { .. } | PolicyAction::Unset { .. },
+            } => true,
+            Command::AddMemoriesBatch { dry_run, .. }
+            | Command::Decay { dry_run, .. }
+            | Command::PruneData { dry_run, .. }
+            | Command::MineGotchas { dry_run, .. }
+            | Command::MigrateLegacy { dry_run, .. } => !dry_run,
+            Command::Migrate { status } => !status,
+            _ => false,
+        }
+    }
+
+    /// Whether a running daemon can run the command: those using the
+    /// configured database, except the interactive `browse` and
+    /// `dedupe-across-scopes` without `--prefer`, and `migrate-legacy` with
+    /// its second database. Diagnostics run here, so
+    /// they report on this process's connection.
+    pub fn is_forwardable(&self) -> bool {
+        !matches!(
+            self,
+            Command::Daemon { .. }
+                | Command::External(_)
+                | Command::Browse
+                | Command::DedupeAcrossScopes { prefer: None, .. }
+                | Command::Logs { .. }
+                | Command::ClearLogs
+                | Command::Config { .. }
+                | Command::InstallHooks { .. }
+                | Command::Schema { .. }
+                | Command::Doctor { .. }
+                | Command::DebugBundle { .. }
+                | Command::MigrateLegacy { .. }
+        )
+    }
+
+    /// Whether the command reads stdin, which is sent along when it is
+    /// forwarded to the daemon
+    pub fn reads_stdin(&self) -> bool {
+        match self {
+            Command::Hook { .. } | Command::AddMemoriesBatch { .. } => true,
+            Command::SetBriefing { content, clear, .. } => content.is_none() && !clear,
+            _ => false,
+        }
+    }
+
+    /// Memories named in the arguments. Memories a command creates or
+    /// changes without naming them are only known from its result.
+    pub fn memory_refs(&self) -> Vec<Uuid> {
+        let refs: Vec<Option<&str>> = match self {
+            Command::UpdateMemory { id, .. }
+            | Command::EditMemory { id, .. }
+            | Command::DeleteMemory { id, .. }
+            | Command::Mute { id, .. }
+            | Command::Pin { id }
+            | Command::Unpin { id }
+            | Command::Unsupersede { id, .. }
+            | Command::Review {
+                action: ReviewAction::Resolve { id },
+            } => vec![Some(id)],
+            Command::Link { source: a, target: b, .. }
+            | Command::Unlink { a, b, .. }
+            | Command::Supersede { old_id: a, new_id: b } => vec![Some(a), Some(b)],
+            Command::AddMemory { supersedes, .. }
+            | Command::ProposeMemory { supersedes, .. }
+            | Command::CommitMemory { supersedes, .. } => vec![supersedes.as_deref()],
+            _ => Vec::new(),
+        };
+        refs.into_iter()
+            .flatten()
+            .filter_map(|id| Uuid::parse_str(id.trim()).ok())
+            .collect()
+    }
+}
+
+impl HookType {
+    /// Hook name as used on the command line
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SessionStart => "session-start",
+            Self::UserPromptSubmit => "user-prompt-submit",
+            Self::Stop => "stop",
+            Self::SubagentStop => "subagent-stop",
+            Self::Notification => "notification",
+            Self::SessionEnd => "session-end",
+            Self::PostToolUse => "post-tool-use",
+        }
+    }
+}
+
+/// Review queue actions
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum ReviewAction {
+    /// List memories flagged for review
+    List {
+        /// Tier filter: project, global, both (default: default_tier from config)
+        #[arg(value_parser = parse_tier)]
+        tier: Option<Tier>,
+        /// Maximum results to return
+        #[arg(default_value = "50")]
+        limit: i64,
+    },
+    /// Clear the review flag after checking a memory
+    Resolve {
+        /// Memory ID (UUID)
+        id: String,
+    },
+}
+
+/// Config actions
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum ConfigAction {
+    /// Show the merged configuration and the files it was read from
+    Show,
+    /// Set a key in ~/.claude/hippocampus.toml (or the project's .hippocampus.toml)
+    Set {
+        /// Dotted key, e.g. max_connections or prune.low_days
+        key: String,
+        /// Value as TOML (20, true, ["Bash"]); anything else is taken as a string
+        value: String,
+        /// Write the project's .hippocampus.toml instead
+        #[arg(long)]
+        project: bool,
+    },
+}
+
+/// Retention policy actions
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum PolicyAction {
+    /// Show the retention maintenance commands use and which keys the database sets
+    Show,
+    /// Store a retention key, e.g. low_days or sessions_days, for everything using the database
+    Set {
+        /// Key of the [prune] table, e.g. low_days
+        key: String,
+        /// Days
+        days: i64,
+    },
+    /// Remove a key from the policy, so [prune] in the config files applies again
+    Unset {
+        /// Key of the [prune] table, e.g. low_days
+        key: String,
+    },
+}
+
+/// Workspace actions
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum WorkspaceAction {
+    /// Create an empty workspace
+    Create {
+        /// Workspace name
+        name: String,
+    },
+    /// Add a project to a workspace
+    AddProject {
+        /// Workspace name
+        name: String,
+        /// Project path (default: PROJECT_PATH or the current directory)
+        path: Option<String>,
+    },
+    /// List workspaces and their projects
+    List,
+}
+
+/// Project registry actions
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum ProjectAction {
+    /// Paths that belong to a project, such as where it used to live
+    Alias {
+        #[command(subcommand)]
+        action: AliasAction,
+    },
+}
+
+/// Project alias actions
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum AliasAction {
+    /// Record a path as belonging to the current project (or --project)
+    Add {
+        /// Path the project's memories were stored under, e.g. its old location
+        path: String,
+        /// Project to add the alias to (default: PROJECT_PATH or the current directory)
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// List projects and their aliases
+    List,
+    /// Forget an alias; memories stored under it are no longer read with the project
+    Remove {
+        /// The aliased path
+        path: String,
+    },
+}
+
+// Custom parsers for enum types
 fn parse_memory_type(s: &str) -> Result<MemoryType, String> {
     s.parse::<MemoryType>().map_err(|e| format!("{}", e))
 }
 
+/// Parse a `TYPE=N` per-type limit
+fn parse_type_limit(s: &str) -> Result<(MemoryType, usize), String> {
+    let (memory_type, limit) = s
+        .split_once('=')
+        .ok_or_else(|| format!("Expected TYPE=N, e.g. gotcha=3: {}", s))?;
+    let limit = limit
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| format!("Invalid limit for {}: {}", memory_type, limit))?;
+    Ok((parse_memory_type(memory_type.trim())?, limit))
+}
+
+/// Parse `--jobs`: at least one worker
+fn parse_jobs(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(0) => Err("--jobs must be at least 1".to_string()),
+        Ok(jobs) => Ok(jobs),
+        Err(_) => Err(format!("Invalid number of jobs: {}", s)),
+    }
+}
+
 fn parse_confidence(s: &str) -> Result<Confidence, String> {
     s.parse::<Confidence>().map_err(|e| format!("{}", e))
 }
@@ -315,10 +1560,96 @@ fn parse_scope(s: &str) -> Result<Scope, String> {
     s.parse::<Scope>().map_err(|e| format!("{}", e))
 }
 
+/// Parse a `nextCursor` from an earlier page
+fn parse_cursor(s: &str) -> Result<Cursor, String> {
+    Cursor::decode(s).ok_or_else(|| format!("Invalid cursor: {}", s))
+}
+
 fn parse_tier(s: &str) -> Result<Tier, String> {
     s.parse::<Tier>().map_err(|e| format!("{}", e))
 }
 
+fn parse_session_status(s: &str) -> Result<SessionStatus, String> {
+    s.parse::<SessionStatus>().map_err(|e| format!("{}", e))
+}
+
+/// Parse a single tag: trimmed, non-empty, no commas
+fn parse_tag(s: &str) -> Result<String, String> {
+    let tag = s.trim();
+    if tag.is_empty() {
+        return Err("Tag must not be empty".to_string());
+    }
+    if tag.contains(',') {
+        return Err(format!("Invalid tag: {} (one tag, without commas)", tag));
+    }
+    Ok(tag.to_string())
+}
+
+fn parse_constraint(s: &str) -> Result<EnvConstraint, String> {
+    s.parse::<EnvConstraint>().map_err(|e| format!("{}", e))
+}
+
+/// Parse a point in time.
+///
+/// Accepts RFC3339 (`2024-01-24T10:30:00Z`), a local date (`2024-01-24`,
+/// meaning local midnight), or a relative offset into the past (`90m`, `12h`, `7d`).
+pub fn parse_timestamp(s: &str) -> Result<DateTime<Utc>, String> {
+    let s = s.trim();
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is valid");
+        return Local
+            .from_local_datetime(&midnight)
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc))
+            .ok_or_else(|| format!("Invalid local date: {}", s));
+    }
+
+    let invalid = || {
+        format!(
+            "Invalid time: {} (expected RFC3339, YYYY-MM-DD, or a relative offset like 30m, 12h, 7d)",
+            s
+        )
+    };
+    if s.len() < 2 {
+        return Err(invalid());
+    }
+    let (amount, unit) = s.split_at(s.len() - 1);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let offset = match unit {
+        "s" => Duration::seconds(amount),
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        "w" => Duration::weeks(amount),
+        _ => return Err(invalid()),
+    };
+    Ok(Utc::now() - offset)
+}
+
+/// Parse an argument that must be a UUID; `flag` names it in the error.
+pub fn parse_uuid_arg(flag: &str, value: &str) -> crate::error::Result<Uuid> {
+    Uuid::parse_str(value.trim()).map_err(|_| HippocampusError::InvalidArgument {
+        flag: flag.to_string(),
+        value: value.to_string(),
+    })
+}
+
+/// Parse an optional reference to a session, turn or memory.
+///
+/// With `ignore_invalid`, a value that is not a UUID is dropped instead.
+pub fn parse_ref(flag: &str, value: Option<&str>, ignore_invalid: bool) -> crate::error::Result<Option<Uuid>> {
+    match value {
+        None => Ok(None),
+        Some(value) if ignore_invalid => Ok(Uuid::parse_str(value.trim()).ok()),
+        Some(value) => parse_uuid_arg(flag, value).map(Some),
+    }
+}
+
 /// Parse comma-separated tags into a vector
 pub fn parse_tags(tags_str: &str) -> Vec<String> {
     if tags_str.is_empty() {
@@ -339,7 +1670,7 @@ pub fn parse_tags(tags_str: &str) -> Vec<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use clap::Parser;
+    use clap::{CommandFactory, Parser};
 
     // -------------------------------------------------------------------------
     // AddMemory command tests
@@ -359,6 +1690,10 @@ mod tests {
                 source_turn_id,
                 claude_session_id,
                 supersedes,
+                constraints,
+                summary,
+                ignore_invalid_refs,
+                check_conflicts,
             } => {
                 assert_eq!(memory_type, MemoryType::Learning);
                 assert_eq!(content, "Test content");
@@ -369,6 +1704,10 @@ mod tests {
                 assert!(source_turn_id.is_none());
                 assert!(claude_session_id.is_none());
                 assert!(supersedes.is_none());
+                assert!(constraints.is_empty());
+                assert!(summary.is_none());
+                assert!(!ignore_invalid_refs);
+                assert!(!check_conflicts);
             }
             _ => panic!("Expected AddMemory command"),
         }
@@ -387,6 +1726,7 @@ mod tests {
             "--session=sess-123",
             "--turn=turn-456",
             "--claude-session=claude-789",
+            "--summary=Bug in the parser",
         ]);
         match cli.command {
             Command::AddMemory {
@@ -399,6 +1739,9 @@ mod tests {
                 source_turn_id,
                 claude_session_id,
                 supersedes,
+                constraints,
+                summary,
+                ..
             } => {
                 assert_eq!(memory_type, MemoryType::Gotcha);
                 assert_eq!(content, "Found a bug");
@@ -409,6 +1752,8 @@ mod tests {
                 assert_eq!(source_turn_id, Some("turn-456".to_string()));
                 assert_eq!(claude_session_id, Some("claude-789".to_string()));
                 assert!(supersedes.is_none());
+                assert!(constraints.is_empty());
+                assert_eq!(summary.as_deref(), Some("Bug in the parser"));
             }
             _ => panic!("Expected AddMemory command"),
         }
@@ -471,15 +1816,29 @@ mod tests {
             "New content",
         ]);
         match cli.command {
-            Command::UpdateMemory { id, content, tier } => {
+            Command::UpdateMemory { id, content, tier, expected_updated_at, as_new } => {
                 assert_eq!(id, "550e8400-e29b-41d4-a716-446655440000");
                 assert_eq!(content, "New content");
                 assert_eq!(tier, Scope::Project);
+                assert!(expected_updated_at.is_none());
+                assert!(!as_new);
             }
             _ => panic!("Expected UpdateMemory command"),
         }
     }
 
+    #[test]
+    fn test_update_memory_as_new() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "update-memory",
+            "550e8400-e29b-41d4-a716-446655440000",
+            "New content",
+            "--as-new",
+        ]);
+        assert!(matches!(cli.command, Command::UpdateMemory { as_new: true, .. }));
+    }
+
     #[test]
     fn test_update_memory_with_tier() {
         let cli = Cli::parse_from([
@@ -497,6 +1856,93 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_update_memory_expected_updated_at() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "update-memory",
+            "550e8400-e29b-41d4-a716-446655440000",
+            "New content",
+            "--expected-updated-at",
+            "2024-03-01T12:00:00.123456Z",
+        ]);
+        match cli.command {
+            Command::UpdateMemory { expected_updated_at, .. } => {
+                let expected = expected_updated_at.unwrap();
+                assert_eq!(expected.timestamp_subsec_micros(), 123456);
+            }
+            _ => panic!("Expected UpdateMemory command"),
+        }
+
+        // Relative times are not a revision
+        assert!(Cli::try_parse_from([
+            "claude-hippocampus",
+            "update-memory",
+            "550e8400-e29b-41d4-a716-446655440000",
+            "New content",
+            "--expected-updated-at",
+            "12h",
+        ])
+        .is_err());
+    }
+
+    // -------------------------------------------------------------------------
+    // EditMemory command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_edit_memory_flags() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "edit-memory",
+            "550e8400-e29b-41d4-a716-446655440000",
+            "--type",
+            "gotcha",
+            "--add-tag",
+            "sqlx",
+            "--add-tag",
+            "postgres",
+            "--remove-tag",
+            "db",
+        ]);
+        match cli.command {
+            Command::EditMemory {
+                content,
+                memory_type,
+                confidence,
+                add_tags,
+                remove_tags,
+                ..
+            } => {
+                assert!(content.is_none());
+                assert_eq!(memory_type, Some(MemoryType::Gotcha));
+                assert!(confidence.is_none());
+                assert_eq!(add_tags, vec!["sqlx", "postgres"]);
+                assert_eq!(remove_tags, vec!["db"]);
+            }
+            _ => panic!("Expected EditMemory command"),
+        }
+    }
+
+    #[test]
+    fn test_edit_memory_requires_an_edit() {
+        let result = Cli::try_parse_from([
+            "claude-hippocampus",
+            "edit-memory",
+            "550e8400-e29b-41d4-a716-446655440000",
+        ]);
+        assert!(result.is_err());
+
+        let result = Cli::try_parse_from([
+            "claude-hippocampus",
+            "edit-memory",
+            "550e8400-e29b-41d4-a716-446655440000",
+            "--confidence",
+            "certain",
+        ]);
+        assert!(result.is_err());
+    }
+
     // -------------------------------------------------------------------------
     // DeleteMemory command tests
     // -------------------------------------------------------------------------
@@ -545,11 +1991,38 @@ mod tests {
             "550e8400-e29b-41d4-a716-446655440000",
         ]);
         match cli.command {
-            Command::GetMemory { id } => {
+            Command::GetMemory { id, as_of, neighborhood } => {
                 assert_eq!(id, "550e8400-e29b-41d4-a716-446655440000");
+                assert!(as_of.is_none());
+                assert!(!neighborhood);
+            }
+            _ => panic!("Expected GetMemory command"),
+        }
+    }
+
+    #[test]
+    fn test_get_memory_as_of() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "get-memory",
+            "550e8400-e29b-41d4-a716-446655440000",
+            "--as-of",
+            "2024-03-01T12:00:00Z",
+        ]);
+        match cli.command {
+            Command::GetMemory { as_of, .. } => {
+                assert_eq!(as_of.unwrap().to_rfc3339(), "2024-03-01T12:00:00+00:00");
             }
             _ => panic!("Expected GetMemory command"),
         }
+        assert!(Cli::try_parse_from([
+            "claude-hippocampus",
+            "get-memory",
+            "550e8400-e29b-41d4-a716-446655440000",
+            "--as-of",
+            "last tuesday",
+        ])
+        .is_err());
     }
 
     // -------------------------------------------------------------------------
@@ -560,10 +2033,24 @@ mod tests {
     fn test_search_keyword_minimal() {
         let cli = Cli::parse_from(["claude-hippocampus", "search-keyword", "test query"]);
         match cli.command {
-            Command::SearchKeyword { query, tier, limit } => {
+            Command::SearchKeyword {
+                query,
+                tier,
+                limit,
+                mode,
+                as_of,
+                include_archived,
+                filters,
+                page,
+            } => {
                 assert_eq!(query, "test query");
-                assert_eq!(tier, Tier::Both);
+                assert_eq!(tier, None);
                 assert_eq!(limit, 30);
+                assert_eq!(mode, SearchMode::Ilike);
+                assert!(as_of.is_none());
+                assert!(!include_archived);
+                assert_eq!(filters, SearchFilterArgs::default());
+                assert_eq!(page.offset(), 0);
             }
             _ => panic!("Expected SearchKeyword command"),
         }
@@ -579,7 +2066,7 @@ mod tests {
         ]);
         match cli.command {
             Command::SearchKeyword { tier, .. } => {
-                assert_eq!(tier, Tier::Project);
+                assert_eq!(tier, Some(Tier::Project));
             }
             _ => panic!("Expected SearchKeyword command"),
         }
@@ -602,6 +2089,54 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_search_keyword_mode() {
+        let cli = Cli::parse_from(["claude-hippocampus", "search-keyword", "query", "--mode", "fts"]);
+        match cli.command {
+            Command::SearchKeyword { mode, .. } => assert_eq!(mode, SearchMode::Fts),
+            _ => panic!("Expected SearchKeyword command"),
+        }
+        assert!(
+            Cli::try_parse_from(["claude-hippocampus", "search-keyword", "q", "--mode", "regex"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_search_fts() {
+        let cli = Cli::parse_from(["claude-hippocampus", "search-fts", "connection pool", "global", "5"]);
+        match cli.command {
+            Command::SearchFts {
+                query,
+                tier,
+                limit,
+                as_of,
+                ..
+            } => {
+                assert_eq!(query, "connection pool");
+                assert_eq!(tier, Some(Tier::Global));
+                assert_eq!(limit, 5);
+                assert!(as_of.is_none());
+            }
+            _ => panic!("Expected SearchFts command"),
+        }
+    }
+
+    #[test]
+    fn test_search_as_of() {
+        let cli = Cli::parse_from(["claude-hippocampus", "search-fts", "pool", "--as-of", "7d"]);
+        assert!(matches!(cli.command, Command::SearchFts { as_of: Some(_), .. }));
+
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "search-keyword",
+            "pool",
+            "--as-of",
+            "2024-01-01",
+        ]);
+        assert!(matches!(cli.command, Command::SearchKeyword { as_of: Some(_), .. }));
+    }
+
     // -------------------------------------------------------------------------
     // SearchByType command tests
     // -------------------------------------------------------------------------
@@ -615,14 +2150,48 @@ mod tests {
                 query,
                 tier,
                 limit,
+                include_archived,
+                filters,
+                ..
             } => {
                 assert_eq!(memory_type, MemoryType::Gotcha);
                 assert_eq!(query, None);
-                assert_eq!(tier, Tier::Both);
+                assert_eq!(tier, None);
                 assert_eq!(limit, 30);
+                assert!(!include_archived);
+                assert_eq!(filters, SearchFilterArgs::default());
+            }
+            _ => panic!("Expected SearchByType command"),
+        }
+    }
+
+    #[test]
+    fn test_search_by_type_filters() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "search-by-type",
+            "gotcha",
+            "--since",
+            "2024-01-24T00:00:00Z",
+            "--until",
+            "2024-01-31T00:00:00Z",
+            "--confidence",
+            "high",
+            "--tag",
+            "billing",
+        ]);
+        match cli.command {
+            Command::SearchByType { filters, .. } => {
+                let filters = filters.filters();
+                assert_eq!(filters.since, Some("2024-01-24T00:00:00Z".parse().unwrap()));
+                assert_eq!(filters.until, Some("2024-01-31T00:00:00Z".parse().unwrap()));
+                assert_eq!(filters.confidence, Some(Confidence::High));
+                assert_eq!(filters.tag.as_deref(), Some("billing"));
             }
             _ => panic!("Expected SearchByType command"),
         }
+        assert!(Cli::try_parse_from(["claude-hippocampus", "search-keyword", "q", "--tag", "a,b"])
+            .is_err());
     }
 
     #[test]
@@ -662,10 +2231,11 @@ mod tests {
                 query,
                 tier,
                 limit,
+                ..
             } => {
                 assert_eq!(memory_type, MemoryType::Architecture);
                 assert_eq!(query, Some("database".to_string()));
-                assert_eq!(tier, Tier::Project);
+                assert_eq!(tier, Some(Tier::Project));
                 assert_eq!(limit, 10);
             }
             _ => panic!("Expected SearchByType command"),
@@ -701,15 +2271,90 @@ mod tests {
     }
 
     // -------------------------------------------------------------------------
-    // GetContext command tests
+    // Tag command tests
     // -------------------------------------------------------------------------
 
     #[test]
-    fn test_get_context_default() {
-        let cli = Cli::parse_from(["claude-hippocampus", "get-context"]);
+    fn test_search_by_tag() {
+        let cli = Cli::parse_from(["claude-hippocampus", "search-by-tag", " sqlx "]);
         match cli.command {
-            Command::GetContext { limit } => {
-                assert_eq!(limit, 10);
+            Command::SearchByTag { tag, tier, limit, include_archived } => {
+                assert_eq!(tag, "sqlx");
+                assert_eq!(tier, None);
+                assert_eq!(limit, 30);
+                assert!(!include_archived);
+            }
+            _ => panic!("Expected SearchByTag command"),
+        }
+        let cli = Cli::parse_from(["claude-hippocampus", "search-by-tag", "sqlx", "--include-archived"]);
+        assert!(matches!(cli.command, Command::SearchByTag { include_archived: true, .. }));
+        assert!(Cli::try_parse_from(["claude-hippocampus", "search-by-tag", "a,b"]).is_err());
+    }
+
+    #[test]
+    fn test_list_tags() {
+        let cli = Cli::parse_from(["claude-hippocampus", "list-tags", "project"]);
+        assert!(matches!(cli.command, Command::ListTags { tier: Some(Tier::Project) }));
+    }
+
+    #[test]
+    fn test_rename_tag() {
+        let cli = Cli::parse_from(["claude-hippocampus", "rename-tag", "postgress", "postgres"]);
+        match cli.command {
+            Command::RenameTag { from, to, tier } => {
+                assert_eq!(from, "postgress");
+                assert_eq!(to, "postgres");
+                assert_eq!(tier, Tier::Both);
+            }
+            _ => panic!("Expected RenameTag command"),
+        }
+        assert!(Cli::try_parse_from(["claude-hippocampus", "rename-tag", "old", " "]).is_err());
+    }
+
+    #[test]
+    fn test_merge_tags() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "merge-tags",
+            "pg",
+            "postgresql",
+            "--into",
+            "postgres",
+        ]);
+        match cli.command {
+            Command::MergeTags { sources, into, tier } => {
+                assert_eq!(sources, vec!["pg", "postgresql"]);
+                assert_eq!(into, "postgres");
+                assert_eq!(tier, Tier::Both);
+            }
+            _ => panic!("Expected MergeTags command"),
+        }
+        assert!(Cli::try_parse_from(["claude-hippocampus", "merge-tags", "--into", "x"]).is_err());
+        assert!(Cli::try_parse_from(["claude-hippocampus", "merge-tags", "a", "b"]).is_err());
+    }
+
+    // -------------------------------------------------------------------------
+    // GetContext command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_get_context_default() {
+        let cli = Cli::parse_from(["claude-hippocampus", "get-context"]);
+        match cli.command {
+            Command::GetContext {
+                limit,
+                since_last_session,
+                max_tokens,
+                group_by_type,
+                type_limits,
+                template,
+            } => {
+                assert_eq!(limit, 10);
+                assert!(!since_last_session);
+                assert!(max_tokens.is_none());
+                assert!(!group_by_type);
+                assert!(type_limits.is_empty());
+                assert!(template.is_none());
             }
             _ => panic!("Expected GetContext command"),
         }
@@ -719,13 +2364,52 @@ mod tests {
     fn test_get_context_with_limit() {
         let cli = Cli::parse_from(["claude-hippocampus", "get-context", "25"]);
         match cli.command {
-            Command::GetContext { limit } => {
+            Command::GetContext { limit, .. } => {
                 assert_eq!(limit, 25);
             }
             _ => panic!("Expected GetContext command"),
         }
     }
 
+    #[test]
+    fn test_get_context_since_last_session() {
+        let cli = Cli::parse_from(["claude-hippocampus", "get-context", "5", "--since-last-session"]);
+        assert!(matches!(
+            cli.command,
+            Command::GetContext { limit: 5, since_last_session: true, .. }
+        ));
+    }
+
+    #[test]
+    fn test_get_context_max_tokens() {
+        let cli = Cli::parse_from(["claude-hippocampus", "get-context", "--max-tokens", "500"]);
+        match cli.command {
+            Command::GetContext { max_tokens, .. } => assert_eq!(max_tokens, Some(500)),
+            _ => panic!("Expected GetContext command"),
+        }
+    }
+
+    #[test]
+    fn test_get_context_group_by_type() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "get-context",
+            "--group-by-type",
+            "--type-limit",
+            "gotcha=3",
+            "--type-limit=convention=2",
+        ]);
+        match cli.command {
+            Command::GetContext { group_by_type, type_limits, .. } => {
+                assert!(group_by_type);
+                assert_eq!(type_limits, vec![(MemoryType::Gotcha, 3), (MemoryType::Convention, 2)]);
+            }
+            _ => panic!("Expected GetContext command"),
+        }
+        assert!(Cli::try_parse_from(["claude-hippocampus", "get-context", "--type-limit", "gotcha"]).is_err());
+        assert!(Cli::try_parse_from(["claude-hippocampus", "get-context", "--type-limit", "bug=2"]).is_err());
+    }
+
     // -------------------------------------------------------------------------
     // ListRecent command tests
     // -------------------------------------------------------------------------
@@ -734,9 +2418,10 @@ mod tests {
     fn test_list_recent_default() {
         let cli = Cli::parse_from(["claude-hippocampus", "list-recent"]);
         match cli.command {
-            Command::ListRecent { n, tier } => {
+            Command::ListRecent { n, tier, page } => {
                 assert_eq!(n, 10);
-                assert_eq!(tier, Tier::Both);
+                assert_eq!(page, PageArgs::default());
+                assert_eq!(tier, None);
             }
             _ => panic!("Expected ListRecent command"),
         }
@@ -746,9 +2431,9 @@ mod tests {
     fn test_list_recent_with_args() {
         let cli = Cli::parse_from(["claude-hippocampus", "list-recent", "20", "global"]);
         match cli.command {
-            Command::ListRecent { n, tier } => {
+            Command::ListRecent { n, tier, .. } => {
                 assert_eq!(n, 20);
-                assert_eq!(tier, Tier::Global);
+                assert_eq!(tier, Some(Tier::Global));
             }
             _ => panic!("Expected ListRecent command"),
         }
@@ -762,8 +2447,10 @@ mod tests {
     fn test_consolidate_default() {
         let cli = Cli::parse_from(["claude-hippocampus", "consolidate"]);
         match cli.command {
-            Command::Consolidate { tier } => {
+            Command::Consolidate { tier, merge, lock } => {
                 assert_eq!(tier, Scope::Project);
+                assert!(!merge);
+                assert_eq!(lock.mode(), LockMode::Fail);
             }
             _ => panic!("Expected Consolidate command"),
         }
@@ -773,13 +2460,42 @@ mod tests {
     fn test_consolidate_with_tier() {
         let cli = Cli::parse_from(["claude-hippocampus", "consolidate", "global"]);
         match cli.command {
-            Command::Consolidate { tier } => {
+            Command::Consolidate { tier, .. } => {
+                assert_eq!(tier, Scope::Global);
+            }
+            _ => panic!("Expected Consolidate command"),
+        }
+    }
+
+    #[test]
+    fn test_consolidate_merge() {
+        let cli = Cli::parse_from(["claude-hippocampus", "consolidate", "global", "--merge"]);
+        match cli.command {
+            Command::Consolidate { tier, merge, .. } => {
                 assert_eq!(tier, Scope::Global);
+                assert!(merge);
             }
             _ => panic!("Expected Consolidate command"),
         }
     }
 
+    #[test]
+    fn test_dedupe_across_scopes() {
+        let cli = Cli::parse_from(["claude-hippocampus", "dedupe-across-scopes"]);
+        assert!(matches!(cli.command, Command::DedupeAcrossScopes { prefer: None, .. }));
+        assert!(!cli.command.is_forwardable());
+
+        let cli =
+            Cli::parse_from(["claude-hippocampus", "dedupe-across-scopes", "--prefer", "global"]);
+        assert!(matches!(
+            cli.command,
+            Command::DedupeAcrossScopes { prefer: Some(Scope::Global), .. }
+        ));
+        assert!(cli.command.is_forwardable());
+        assert!(Cli::try_parse_from(["claude-hippocampus", "dedupe-across-scopes", "--prefer", "both"])
+            .is_err());
+    }
+
     // -------------------------------------------------------------------------
     // Prune command tests
     // -------------------------------------------------------------------------
@@ -788,15 +2504,30 @@ mod tests {
     fn test_prune_default() {
         let cli = Cli::parse_from(["claude-hippocampus", "prune"]);
         match cli.command {
-            Command::Prune { low_days, medium_days, tier } => {
-                assert_eq!(low_days, 30);
-                assert_eq!(medium_days, 90);
+            Command::Prune { low_days, medium_days, tier, lock } => {
+                assert_eq!(low_days, None);
+                assert_eq!(medium_days, None);
                 assert_eq!(tier, Scope::Project);
+                assert_eq!(lock.mode(), LockMode::Fail);
             }
             _ => panic!("Expected Prune command"),
         }
     }
 
+    #[test]
+    fn test_maintenance_lock_flags() {
+        let cli = Cli::parse_from(["claude-hippocampus", "prune", "--wait"]);
+        assert!(matches!(cli.command, Command::Prune { lock, .. } if lock.mode() == LockMode::Wait));
+
+        let cli = Cli::parse_from(["claude-hippocampus", "purge-superseded", "--skip-if-running"]);
+        assert!(
+            matches!(cli.command, Command::PurgeSuperseded { lock, .. } if lock.mode() == LockMode::Skip)
+        );
+
+        let result = Cli::try_parse_from(["claude-hippocampus", "decay", "--wait", "--skip-if-running"]);
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_prune_with_args() {
         let cli = Cli::parse_from([
@@ -807,15 +2538,24 @@ mod tests {
             "global",
         ]);
         match cli.command {
-            Command::Prune { low_days, medium_days, tier } => {
-                assert_eq!(low_days, 14);
-                assert_eq!(medium_days, 60);
+            Command::Prune { low_days, medium_days, tier, .. } => {
+                assert_eq!(low_days, Some(14));
+                assert_eq!(medium_days, Some(60));
                 assert_eq!(tier, Scope::Global);
             }
             _ => panic!("Expected Prune command"),
         }
     }
 
+    #[test]
+    fn test_decay() {
+        let cli = Cli::parse_from(["claude-hippocampus", "decay"]);
+        assert!(matches!(cli.command, Command::Decay { archive_days: None, dry_run: false, .. }));
+
+        let cli = Cli::parse_from(["claude-hippocampus", "decay", "--archive-days=365", "--dry-run"]);
+        assert!(matches!(cli.command, Command::Decay { archive_days: Some(365), dry_run: true, .. }));
+    }
+
     // -------------------------------------------------------------------------
     // SaveSessionSummary command tests
     // -------------------------------------------------------------------------
@@ -843,9 +2583,22 @@ mod tests {
     fn test_logs_default() {
         let cli = Cli::parse_from(["claude-hippocampus", "logs"]);
         match cli.command {
-            Command::Logs { n, operation } => {
+            Command::Logs {
+                n,
+                operation,
+                failed,
+                since,
+                until,
+                op_prefix,
+                level,
+            } => {
                 assert_eq!(n, 50);
                 assert!(operation.is_none());
+                assert!(!failed);
+                assert!(since.is_none());
+                assert!(until.is_none());
+                assert!(op_prefix.is_none());
+                assert!(level.is_none());
             }
             _ => panic!("Expected Logs command"),
         }
@@ -855,7 +2608,7 @@ mod tests {
     fn test_logs_with_args() {
         let cli = Cli::parse_from(["claude-hippocampus", "logs", "100", "addMemory"]);
         match cli.command {
-            Command::Logs { n, operation } => {
+            Command::Logs { n, operation, .. } => {
                 assert_eq!(n, 100);
                 assert_eq!(operation, Some("addMemory".to_string()));
             }
@@ -863,6 +2616,85 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_logs_with_filters() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "logs",
+            "--failed",
+            "--since",
+            "2024-01-24T00:00:00Z",
+            "--until",
+            "2024-01-25T00:00:00Z",
+            "--op-prefix",
+            "hook",
+            "--level",
+            "warn",
+        ]);
+        match cli.command {
+            Command::Logs {
+                n,
+                failed,
+                since,
+                until,
+                op_prefix,
+                level,
+                ..
+            } => {
+                assert_eq!(level, Some(LogLevel::Warn));
+                assert_eq!(n, 50);
+                assert!(failed);
+                assert_eq!(since, Some("2024-01-24T00:00:00Z".parse().unwrap()));
+                assert_eq!(until, Some("2024-01-25T00:00:00Z".parse().unwrap()));
+                assert_eq!(op_prefix, Some("hook".to_string()));
+            }
+            _ => panic!("Expected Logs command"),
+        }
+    }
+
+    #[test]
+    fn test_logs_invalid_since() {
+        let result = Cli::try_parse_from(["claude-hippocampus", "logs", "--since", "last night"]);
+        assert!(result.is_err());
+    }
+
+    // -------------------------------------------------------------------------
+    // Timestamp parsing tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_parse_timestamp_rfc3339() {
+        let dt = parse_timestamp("2024-01-24T10:30:00+02:00").unwrap();
+        assert_eq!(dt, "2024-01-24T08:30:00Z".parse::<DateTime<Utc>>().unwrap());
+    }
+
+    #[test]
+    fn test_parse_timestamp_date() {
+        let dt = parse_timestamp("2024-01-24").unwrap();
+        let local = dt.with_timezone(&Local);
+        assert_eq!(local.format("%Y-%m-%d %H:%M:%S").to_string(), "2024-01-24 00:00:00");
+    }
+
+    #[test]
+    fn test_parse_timestamp_relative() {
+        let before = Utc::now();
+        let dt = parse_timestamp("12h").unwrap();
+        let expected = before - Duration::hours(12);
+        assert!((dt - expected).num_seconds().abs() <= 1);
+
+        let dt = parse_timestamp("7d").unwrap();
+        assert!((Utc::now() - dt).num_days() == 7);
+    }
+
+    #[test]
+    fn test_parse_timestamp_invalid() {
+        assert!(parse_timestamp("").is_err());
+        assert!(parse_timestamp("h").is_err());
+        assert!(parse_timestamp("12y").is_err());
+        assert!(parse_timestamp("yesterday").is_err());
+        assert!(parse_timestamp("2024-13-01").is_err());
+    }
+
     // -------------------------------------------------------------------------
     // ClearLogs command tests
     // -------------------------------------------------------------------------
@@ -885,7 +2717,7 @@ mod tests {
         let cli = Cli::parse_from(["claude-hippocampus", "stats"]);
         match cli.command {
             Command::Stats { tier } => {
-                assert_eq!(tier, Tier::Both);
+                assert_eq!(tier, None);
             }
             _ => panic!("Expected Stats command"),
         }
@@ -896,7 +2728,7 @@ mod tests {
         let cli = Cli::parse_from(["claude-hippocampus", "stats", "project"]);
         match cli.command {
             Command::Stats { tier } => {
-                assert_eq!(tier, Tier::Project);
+                assert_eq!(tier, Some(Tier::Project));
             }
             _ => panic!("Expected Stats command"),
         }
@@ -907,12 +2739,44 @@ mod tests {
         let cli = Cli::parse_from(["claude-hippocampus", "stats", "global"]);
         match cli.command {
             Command::Stats { tier } => {
-                assert_eq!(tier, Tier::Global);
+                assert_eq!(tier, Some(Tier::Global));
             }
             _ => panic!("Expected Stats command"),
         }
     }
 
+    #[test]
+    fn test_top_memories_defaults() {
+        let cli = Cli::parse_from(["claude-hippocampus", "top-memories"]);
+        match cli.command {
+            Command::TopMemories { tier, days, limit } => {
+                assert_eq!(tier, None);
+                assert_eq!(days, 30);
+                assert_eq!(limit, 10);
+            }
+            _ => panic!("Expected TopMemories command"),
+        }
+    }
+
+    #[test]
+    fn test_top_memories_with_options() {
+        let cli = Cli::parse_from(["claude-hippocampus", "top-memories", "project", "--days", "7", "--limit", "3"]);
+        match cli.command {
+            Command::TopMemories { tier, days, limit } => {
+                assert_eq!(tier, Some(Tier::Project));
+                assert_eq!(days, 7);
+                assert_eq!(limit, 3);
+            }
+            _ => panic!("Expected TopMemories command"),
+        }
+    }
+
+    #[test]
+    fn test_rollup_access() {
+        let cli = Cli::parse_from(["claude-hippocampus", "rollup-access"]);
+        assert!(matches!(cli.command, Command::RollupAccess));
+    }
+
     // -------------------------------------------------------------------------
     // Error case tests
     // -------------------------------------------------------------------------
@@ -1109,6 +2973,126 @@ mod tests {
         assert!(result.is_err());
     }
 
+    // -------------------------------------------------------------------------
+    // ListSessions command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_list_sessions_defaults() {
+        let cli = Cli::parse_from(["claude-hippocampus", "list-sessions"]);
+        match cli.command {
+            Command::ListSessions { status, project_path, since, limit, offset } => {
+                assert!(status.is_none());
+                assert!(project_path.is_none());
+                assert!(since.is_none());
+                assert_eq!(limit, 20);
+                assert_eq!(offset, 0);
+            }
+            _ => panic!("Expected ListSessions command"),
+        }
+    }
+
+    #[test]
+    fn test_workspace() {
+        let cli = Cli::parse_from(["claude-hippocampus", "workspace", "add-project", "shop", "/repo/web"]);
+        match cli.command {
+            Command::Workspace { action } => assert_eq!(
+                action,
+                WorkspaceAction::AddProject { name: "shop".to_string(), path: Some("/repo/web".to_string()) }
+            ),
+            _ => panic!("Expected Workspace command"),
+        }
+        assert!(!cli.workspace);
+        let cli = Cli::parse_from(["claude-hippocampus", "search-keyword", "auth", "--workspace"]);
+        assert!(cli.workspace);
+    }
+
+    #[test]
+    fn test_project_alias() {
+        let cli = Cli::parse_from(["claude-hippocampus", "project", "alias", "add", "/old/repo"]);
+        match cli.command {
+            Command::Project { action: ProjectAction::Alias { action } } => {
+                assert_eq!(action, AliasAction::Add { path: "/old/repo".to_string(), project: None });
+            }
+            _ => panic!("Expected Project command"),
+        }
+        let cli = Cli::parse_from(["claude-hippocampus", "project", "alias", "list"]);
+        assert!(matches!(
+            cli.command,
+            Command::Project { action: ProjectAction::Alias { action: AliasAction::List } }
+        ));
+        assert!(Cli::try_parse_from(["claude-hippocampus", "project", "alias", "remove"]).is_err());
+    }
+
+    #[test]
+    fn test_search_meta() {
+        let cli = Cli::parse_from(["claude-hippocampus", "search-meta", "feature/login", "--limit", "5"]);
+        match cli.command {
+            Command::SearchMeta { query, project_path, limit } => {
+                assert_eq!(query, "feature/login");
+                assert!(project_path.is_none());
+                assert_eq!(limit, 5);
+            }
+            _ => panic!("Expected SearchMeta command"),
+        }
+    }
+
+    #[test]
+    fn test_list_sessions_filters() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "list-sessions",
+            "--status",
+            "completed",
+            "--project-path",
+            "/repo",
+            "--since",
+            "2024-01-24T00:00:00Z",
+            "--limit",
+            "5",
+            "--offset",
+            "10",
+        ]);
+        match cli.command {
+            Command::ListSessions { status, project_path, since, limit, offset } => {
+                assert_eq!(status, Some(SessionStatus::Completed));
+                assert_eq!(project_path.as_deref(), Some("/repo"));
+                assert_eq!(since.unwrap().to_rfc3339(), "2024-01-24T00:00:00+00:00");
+                assert_eq!(limit, 5);
+                assert_eq!(offset, 10);
+            }
+            _ => panic!("Expected ListSessions command"),
+        }
+    }
+
+    #[test]
+    fn test_list_sessions_rejects_unknown_status() {
+        let result = Cli::try_parse_from(["claude-hippocampus", "list-sessions", "--status", "paused"]);
+        assert!(result.is_err());
+    }
+
+    // -------------------------------------------------------------------------
+    // ShowSession command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_show_session() {
+        let cli = Cli::parse_from(["claude-hippocampus", "show-session", "abc-123"]);
+        match cli.command {
+            Command::ShowSession { id, text } => {
+                assert_eq!(id, "abc-123");
+                assert!(!text);
+            }
+            _ => panic!("Expected ShowSession command"),
+        }
+    }
+
+    #[test]
+    fn test_show_session_text() {
+        let cli = Cli::parse_from(["claude-hippocampus", "show-session", "abc-123", "--text"]);
+        assert!(matches!(cli.command, Command::ShowSession { text: true, .. }));
+    }
+
     // -------------------------------------------------------------------------
     // CreateTurn command tests
     // -------------------------------------------------------------------------
@@ -1253,13 +3237,20 @@ mod tests {
             "abc-123-def",
         ]);
         match cli.command {
-            Command::GetTurn { session_id } => {
+            Command::GetTurn { session_id, all } => {
                 assert_eq!(session_id, "abc-123-def");
+                assert!(!all);
             }
             _ => panic!("Expected GetTurn command"),
         }
     }
 
+    #[test]
+    fn test_get_turn_all() {
+        let cli = Cli::parse_from(["claude-hippocampus", "get-turn", "abc-123-def", "--all"]);
+        assert!(matches!(cli.command, Command::GetTurn { all: true, .. }));
+    }
+
     #[test]
     fn test_get_turn_missing_session_id_fails() {
         let result = Cli::try_parse_from(["claude-hippocampus", "get-turn"]);
@@ -1292,6 +3283,50 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_simulate_hook() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "simulate-hook",
+            "user-prompt-submit",
+            "--input",
+            "prompt.json",
+        ]);
+        match cli.command {
+            Command::SimulateHook { hook_type, input } => {
+                assert_eq!(hook_type, HookType::UserPromptSubmit);
+                assert_eq!(input, "prompt.json");
+            }
+            _ => panic!("Expected SimulateHook command"),
+        }
+    }
+
+    #[test]
+    fn test_replay_last_hook() {
+        let cli = Cli::parse_from(["claude-hippocampus", "replay-last-hook", "stop", "--dry-run"]);
+        match cli.command {
+            Command::ReplayLastHook { hook_type, dry_run } => {
+                assert_eq!(hook_type, HookType::Stop);
+                assert!(dry_run);
+            }
+            _ => panic!("Expected ReplayLastHook command"),
+        }
+    }
+
+    #[test]
+    fn test_hook_type_as_str_matches_cli_name() {
+        for hook_type in HookType::value_variants() {
+            let name = hook_type.to_possible_value().unwrap();
+            assert_eq!(hook_type.as_str(), name.get_name());
+        }
+    }
+
+    #[test]
+    fn test_simulate_hook_requires_input() {
+        let result = Cli::try_parse_from(["claude-hippocampus", "simulate-hook", "stop"]);
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_hook_stop() {
         let cli = Cli::parse_from(["claude-hippocampus", "hook", "stop"]);
@@ -1356,6 +3391,25 @@ mod tests {
         }
     }
 
+    // -------------------------------------------------------------------------
+    // ShowProvenance command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_show_provenance() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "show-provenance",
+            "550e8400-e29b-41d4-a716-446655440000",
+        ]);
+        match cli.command {
+            Command::ShowProvenance { id } => {
+                assert_eq!(id, "550e8400-e29b-41d4-a716-446655440000");
+            }
+            _ => panic!("Expected ShowProvenance command"),
+        }
+    }
+
     #[test]
     fn test_show_chain_missing_id_fails() {
         let result = Cli::try_parse_from(["claude-hippocampus", "show-chain"]);
@@ -1370,8 +3424,8 @@ mod tests {
     fn test_list_superseded_default() {
         let cli = Cli::parse_from(["claude-hippocampus", "list-superseded"]);
         match cli.command {
-            Command::ListSuperseded { tier, limit } => {
-                assert_eq!(tier, Tier::Both);
+            Command::ListSuperseded { tier, limit, .. } => {
+                assert_eq!(tier, None);
                 assert_eq!(limit, 50);
             }
             _ => panic!("Expected ListSuperseded command"),
@@ -1387,14 +3441,45 @@ mod tests {
             "10",
         ]);
         match cli.command {
-            Command::ListSuperseded { tier, limit } => {
-                assert_eq!(tier, Tier::Project);
+            Command::ListSuperseded { tier, limit, .. } => {
+                assert_eq!(tier, Some(Tier::Project));
                 assert_eq!(limit, 10);
             }
             _ => panic!("Expected ListSuperseded command"),
         }
     }
 
+    #[test]
+    fn test_page_args() {
+        let after = Cursor {
+            order: "recency".to_string(),
+            key: vec!["2".to_string()],
+            id: Uuid::new_v4(),
+            at: Utc::now(),
+        };
+        let cursor = after.encode();
+        let cli = Cli::parse_from(["claude-hippocampus", "list-superseded", "--cursor", &cursor]);
+        match cli.command {
+            Command::ListSuperseded { page, .. } => {
+                assert_eq!(page.cursor, Some(after));
+                assert_eq!(page.offset(), 0);
+            }
+            _ => panic!("Expected ListSuperseded command"),
+        }
+        let cli = Cli::parse_from(["claude-hippocampus", "list-recent", "--offset", "20"]);
+        assert!(matches!(cli.command, Command::ListRecent { page, .. } if page.offset() == 20));
+
+        for args in [
+            vec!["--cursor", "not-a-cursor"],
+            vec!["--offset", "-1"],
+            vec!["--offset", "10", "--cursor", &cursor],
+        ] {
+            let mut argv = vec!["claude-hippocampus", "search-keyword", "q"];
+            argv.extend(args);
+            assert!(Cli::try_parse_from(argv).is_err());
+        }
+    }
+
     // -------------------------------------------------------------------------
     // PurgeSuperseded command tests
     // -------------------------------------------------------------------------
@@ -1403,8 +3488,8 @@ mod tests {
     fn test_purge_superseded_default() {
         let cli = Cli::parse_from(["claude-hippocampus", "purge-superseded"]);
         match cli.command {
-            Command::PurgeSuperseded { days, tier } => {
-                assert_eq!(days, 30);
+            Command::PurgeSuperseded { days, tier, .. } => {
+                assert_eq!(days, None);
                 assert_eq!(tier, Scope::Project);
             }
             _ => panic!("Expected PurgeSuperseded command"),
@@ -1420,8 +3505,8 @@ mod tests {
             "global",
         ]);
         match cli.command {
-            Command::PurgeSuperseded { days, tier } => {
-                assert_eq!(days, 60);
+            Command::PurgeSuperseded { days, tier, .. } => {
+                assert_eq!(days, Some(60));
                 assert_eq!(tier, Scope::Global);
             }
             _ => panic!("Expected PurgeSuperseded command"),
@@ -1441,10 +3526,11 @@ mod tests {
                 turns_days,
                 sessions_days,
                 dry_run,
+                ..
             } => {
-                assert_eq!(tool_calls_days, 14);
-                assert_eq!(turns_days, 30);
-                assert_eq!(sessions_days, 90);
+                assert_eq!(tool_calls_days, None);
+                assert_eq!(turns_days, None);
+                assert_eq!(sessions_days, None);
                 assert!(!dry_run);
             }
             _ => panic!("Expected PruneData command"),
@@ -1467,10 +3553,11 @@ mod tests {
                 turns_days,
                 sessions_days,
                 dry_run,
+                ..
             } => {
-                assert_eq!(tool_calls_days, 7);
-                assert_eq!(turns_days, 14);
-                assert_eq!(sessions_days, 30);
+                assert_eq!(tool_calls_days, Some(7));
+                assert_eq!(turns_days, Some(14));
+                assert_eq!(sessions_days, Some(30));
                 assert!(dry_run);
             }
             _ => panic!("Expected PruneData command"),
@@ -1478,29 +3565,671 @@ mod tests {
     }
 
     // -------------------------------------------------------------------------
-    // AddMemory with supersedes tests
+    // Config command tests
     // -------------------------------------------------------------------------
 
     #[test]
-    fn test_add_memory_with_supersedes() {
+    fn test_config_set() {
         let cli = Cli::parse_from([
             "claude-hippocampus",
-            "add-memory",
-            "learning",
-            "New content",
-            "",
-            "high",
-            "project",
-            "--supersedes=550e8400-e29b-41d4-a716-446655440000",
+            "config",
+            "set",
+            "prune.low_days",
+            "14",
+            "--project",
         ]);
         match cli.command {
-            Command::AddMemory { supersedes, .. } => {
-                assert_eq!(
-                    supersedes,
-                    Some("550e8400-e29b-41d4-a716-446655440000".to_string())
-                );
+            Command::Config { action: ConfigAction::Set { key, value, project } } => {
+                assert_eq!(key, "prune.low_days");
+                assert_eq!(value, "14");
+                assert!(project);
             }
-            _ => panic!("Expected AddMemory command"),
+            _ => panic!("Expected Config Set command"),
+        }
+
+        let cli = Cli::parse_from(["claude-hippocampus", "config", "show"]);
+        assert!(matches!(cli.command, Command::Config { action: ConfigAction::Show }));
+    }
+
+    // -------------------------------------------------------------------------
+    // Policy command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_policy_actions() {
+        let cli = Cli::parse_from(["claude-hippocampus", "policy", "set", "sessions_days", "180"]);
+        match cli.command {
+            Command::Policy { action: PolicyAction::Set { key, days } } => {
+                assert_eq!(key, "sessions_days");
+                assert_eq!(days, 180);
+            }
+            _ => panic!("Expected Policy Set command"),
+        }
+
+        let cli = Cli::parse_from(["claude-hippocampus", "policy", "unset", "low_days"]);
+        assert!(matches!(cli.command, Command::Policy { action: PolicyAction::Unset { .. } }));
+
+        let cli = Cli::parse_from(["claude-hippocampus", "policy", "show"]);
+        assert!(matches!(cli.command, Command::Policy { action: PolicyAction::Show }));
+
+        assert!(Cli::try_parse_from(["claude-hippocampus", "policy", "set", "low_days", "soon"]).is_err());
+    }
+
+    #[test]
+    fn test_install_hooks_flags() {
+        let cli = Cli::parse_from(["claude-hippocampus", "install-hooks", "--project"]);
+        assert!(matches!(
+            cli.command,
+            Command::InstallHooks { project: true, uninstall: false, binary: None }
+        ));
+
+        let result = Cli::try_parse_from([
+            "claude-hippocampus",
+            "install-hooks",
+            "--uninstall",
+            "--binary=/usr/local/bin/claude-hippocampus",
+        ]);
+        assert!(result.is_err());
+    }
+
+    // -------------------------------------------------------------------------
+    // Review command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_review_list_default() {
+        let cli = Cli::parse_from(["claude-hippocampus", "review", "list"]);
+        match cli.command {
+            Command::Review {
+                action: ReviewAction::List { tier, limit },
+            } => {
+                assert_eq!(tier, None);
+                assert_eq!(limit, 50);
+            }
+            _ => panic!("Expected Review List command"),
+        }
+    }
+
+    #[test]
+    fn test_review_resolve() {
+        let cli = Cli::parse_from(["claude-hippocampus", "review", "resolve", "abc-123"]);
+        match cli.command {
+            Command::Review {
+                action: ReviewAction::Resolve { id },
+            } => assert_eq!(id, "abc-123"),
+            _ => panic!("Expected Review Resolve command"),
+        }
+    }
+
+    #[test]
+    fn test_review_requires_action() {
+        assert!(Cli::try_parse_from(["claude-hippocampus", "review"]).is_err());
+    }
+
+    // -------------------------------------------------------------------------
+    // Verify command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_verify_default() {
+        let cli = Cli::parse_from(["claude-hippocampus", "verify"]);
+        match cli.command {
+            Command::Verify { project } => assert!(project.is_none()),
+            _ => panic!("Expected Verify command"),
+        }
+    }
+
+    #[test]
+    fn test_verify_with_project() {
+        let cli = Cli::parse_from(["claude-hippocampus", "verify", "--project", "/repo"]);
+        match cli.command {
+            Command::Verify { project } => assert_eq!(project, Some("/repo".to_string())),
+            _ => panic!("Expected Verify command"),
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // HealthReport command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_health_report() {
+        let cli = Cli::parse_from(["claude-hippocampus", "health-report"]);
+        assert!(matches!(cli.command, Command::HealthReport));
+    }
+
+    // -------------------------------------------------------------------------
+    // Doctor command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_doctor_flags() {
+        let cli = Cli::parse_from(["claude-hippocampus", "doctor"]);
+        assert!(matches!(cli.command, Command::Doctor { text: false }));
+        let cli = Cli::parse_from(["claude-hippocampus", "doctor", "--text"]);
+        assert!(matches!(cli.command, Command::Doctor { text: true }));
+    }
+
+    // -------------------------------------------------------------------------
+    // Debug-bundle command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_debug_bundle_flags() {
+        let cli = Cli::parse_from(["claude-hippocampus", "debug-bundle"]);
+        match cli.command {
+            Command::DebugBundle { output, log_lines } => {
+                assert!(output.is_none());
+                assert_eq!(log_lines, DEFAULT_LOG_LINES);
+            }
+            _ => panic!("Expected DebugBundle command"),
+        }
+
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "debug-bundle",
+            "--output",
+            "/tmp/bundle.tar.gz",
+            "--log-lines",
+            "50",
+        ]);
+        match cli.command {
+            Command::DebugBundle { output, log_lines } => {
+                assert_eq!(output, Some(PathBuf::from("/tmp/bundle.tar.gz")));
+                assert_eq!(log_lines, 50);
+            }
+            _ => panic!("Expected DebugBundle command"),
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // Onboard command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_onboard_defaults() {
+        let cli = Cli::parse_from(["claude-hippocampus", "onboard"]);
+        match cli.command {
+            Command::Onboard {
+                project,
+                limit,
+                sessions,
+            } => {
+                assert!(project.is_none());
+                assert_eq!(limit, 10);
+                assert_eq!(sessions, 5);
+            }
+            _ => panic!("Expected Onboard command"),
+        }
+    }
+
+    #[test]
+    fn test_onboard_with_options() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "onboard",
+            "--project",
+            "/repo",
+            "--limit",
+            "3",
+            "--sessions",
+            "2",
+        ]);
+        match cli.command {
+            Command::Onboard {
+                project,
+                limit,
+                sessions,
+            } => {
+                assert_eq!(project, Some("/repo".to_string()));
+                assert_eq!(limit, 3);
+                assert_eq!(sessions, 2);
+            }
+            _ => panic!("Expected Onboard command"),
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // DriftCheck command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_drift_check_default() {
+        let cli = Cli::parse_from(["claude-hippocampus", "drift-check"]);
+        match cli.command {
+            Command::DriftCheck { project, patch } => {
+                assert!(project.is_none());
+                assert!(!patch);
+            }
+            _ => panic!("Expected DriftCheck command"),
+        }
+    }
+
+    #[test]
+    fn test_drift_check_with_patch() {
+        let cli = Cli::parse_from(["claude-hippocampus", "drift-check", "--project", "/repo", "--patch"]);
+        match cli.command {
+            Command::DriftCheck { project, patch } => {
+                assert_eq!(project, Some("/repo".to_string()));
+                assert!(patch);
+            }
+            _ => panic!("Expected DriftCheck command"),
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // Briefing command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_set_briefing() {
+        let cli = Cli::parse_from(["claude-hippocampus", "set-briefing", "# Project\n\nNotes"]);
+        match cli.command {
+            Command::SetBriefing {
+                content,
+                project,
+                clear,
+            } => {
+                assert_eq!(content, Some("# Project\n\nNotes".to_string()));
+                assert!(project.is_none());
+                assert!(!clear);
+            }
+            _ => panic!("Expected SetBriefing command"),
+        }
+    }
+
+    #[test]
+    fn test_set_briefing_clear() {
+        let cli = Cli::parse_from(["claude-hippocampus", "set-briefing", "--clear", "--project", "/repo"]);
+        match cli.command {
+            Command::SetBriefing {
+                content,
+                project,
+                clear,
+            } => {
+                assert!(content.is_none());
+                assert_eq!(project, Some("/repo".to_string()));
+                assert!(clear);
+            }
+            _ => panic!("Expected SetBriefing command"),
+        }
+    }
+
+    #[test]
+    fn test_set_briefing_clear_conflicts_with_content() {
+        let result = Cli::try_parse_from(["claude-hippocampus", "set-briefing", "text", "--clear"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_show_briefing() {
+        let cli = Cli::parse_from(["claude-hippocampus", "show-briefing"]);
+        match cli.command {
+            Command::ShowBriefing { project } => assert!(project.is_none()),
+            _ => panic!("Expected ShowBriefing command"),
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // MineGotchas command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_mine_gotchas() {
+        let cli = Cli::parse_from(["claude-hippocampus", "mine-gotchas", "--session", "abc"]);
+        match cli.command {
+            Command::MineGotchas { session, dry_run } => {
+                assert_eq!(session, "abc");
+                assert!(!dry_run);
+            }
+            _ => panic!("Expected MineGotchas command"),
+        }
+    }
+
+    #[test]
+    fn test_mine_gotchas_dry_run_requires_session() {
+        let cli = Cli::parse_from(["claude-hippocampus", "mine-gotchas", "--session", "abc", "--dry-run"]);
+        assert!(matches!(cli.command, Command::MineGotchas { dry_run: true, .. }));
+        assert!(Cli::try_parse_from(["claude-hippocampus", "mine-gotchas"]).is_err());
+    }
+
+    // -------------------------------------------------------------------------
+    // Extraction quota command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_reserve_extraction() {
+        let cli = Cli::parse_from(["claude-hippocampus", "reserve-extraction", "--turn", "t1"]);
+        match cli.command {
+            Command::ReserveExtraction { turn } => assert_eq!(turn.as_deref(), Some("t1")),
+            _ => panic!("Expected ReserveExtraction command"),
+        }
+        let cli = Cli::parse_from(["claude-hippocampus", "reserve-extraction"]);
+        assert!(matches!(cli.command, Command::ReserveExtraction { turn: None }));
+    }
+
+    #[test]
+    fn test_extract_worker_is_hidden() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus", "extract-worker", "--prompt", "p", "--confidence", "high",
+            "--turn", "t1",
+        ]);
+        match cli.command {
+            Command::ExtractWorker {
+                prompt,
+                confidence,
+                session,
+                turn,
+            } => {
+                assert_eq!(prompt, "p");
+                assert_eq!(confidence, Confidence::High);
+                assert!(session.is_none());
+                assert_eq!(turn.as_deref(), Some("t1"));
+            }
+            _ => panic!("Expected ExtractWorker command"),
+        }
+
+        let help = Cli::command().render_help().to_string();
+        assert!(!help.contains("extract-worker"));
+    }
+
+    #[test]
+    fn test_extract_queued_limit() {
+        let cli = Cli::parse_from(["claude-hippocampus", "extract-queued"]);
+        assert!(matches!(cli.command, Command::ExtractQueued { limit: 20 }));
+        let cli = Cli::parse_from(["claude-hippocampus", "extract-queued", "--limit", "5"]);
+        assert!(matches!(cli.command, Command::ExtractQueued { limit: 5 }));
+    }
+
+    #[test]
+    fn test_extraction_log() {
+        let cli = Cli::parse_from(["claude-hippocampus", "extraction-log", "--session", "abc"]);
+        match cli.command {
+            Command::ExtractionLog { session, limit } => {
+                assert_eq!(session, "abc");
+                assert_eq!(limit, 50);
+            }
+            _ => panic!("Expected ExtractionLog command"),
+        }
+        assert!(Cli::try_parse_from(["claude-hippocampus", "extraction-log"]).is_err());
+    }
+
+    // -------------------------------------------------------------------------
+    // History and Diff command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_history() {
+        let cli = Cli::parse_from(["claude-hippocampus", "history", "abc"]);
+        assert!(matches!(cli.command, Command::History { id } if id == "abc"));
+    }
+
+    #[test]
+    fn test_diff_rev() {
+        let cli = Cli::parse_from(["claude-hippocampus", "diff", "abc"]);
+        assert!(matches!(cli.command, Command::Diff { rev: None, .. }));
+        let cli = Cli::parse_from(["claude-hippocampus", "diff", "abc", "--rev", "2"]);
+        match cli.command {
+            Command::Diff { id, rev } => {
+                assert_eq!(id, "abc");
+                assert_eq!(rev, Some(2));
+            }
+            _ => panic!("Expected Diff command"),
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // External subcommand tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_external_subcommand() {
+        let cli = Cli::parse_from(["claude-hippocampus", "curate", "--team", "infra"]);
+        match cli.command {
+            Command::External(args) => assert_eq!(args, vec!["curate", "--team", "infra"]),
+            _ => panic!("Expected External command"),
+        }
+    }
+
+    #[test]
+    fn test_is_builtin_subcommand() {
+        assert!(is_builtin_subcommand("search-keyword"));
+        assert!(is_builtin_subcommand("help"));
+        assert!(!is_builtin_subcommand("curate"));
+    }
+
+    // -------------------------------------------------------------------------
+    // WhereUsed command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_where_used() {
+        let cli = Cli::parse_from(["claude-hippocampus", "where-used", "abc"]);
+        match cli.command {
+            Command::WhereUsed { id } => assert_eq!(id, "abc"),
+            _ => panic!("Expected WhereUsed command"),
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // Mute command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_mute() {
+        let cli = Cli::parse_from(["claude-hippocampus", "mute", "abc", "--session", "s1"]);
+        match cli.command {
+            Command::Mute { id, session, undo } => {
+                assert_eq!(id, "abc");
+                assert_eq!(session, "s1");
+                assert!(!undo);
+            }
+            _ => panic!("Expected Mute command"),
+        }
+    }
+
+    #[test]
+    fn test_mute_undo() {
+        let cli = Cli::parse_from(["claude-hippocampus", "mute", "abc", "--session", "s1", "--undo"]);
+        match cli.command {
+            Command::Mute { undo, .. } => assert!(undo),
+            _ => panic!("Expected Mute command"),
+        }
+    }
+
+    #[test]
+    fn test_pin_unpin() {
+        let cli = Cli::parse_from(["claude-hippocampus", "pin", "abc"]);
+        assert!(matches!(cli.command, Command::Pin { ref id } if id == "abc"));
+        assert!(cli.command.is_audited());
+        assert!(cli.command.validate_refs().is_err());
+        let cli = Cli::parse_from(["claude-hippocampus", "unpin", "abc"]);
+        assert!(matches!(cli.command, Command::Unpin { ref id } if id == "abc"));
+    }
+
+    #[test]
+    fn test_link_unlink() {
+        let cli = Cli::parse_from(["claude-hippocampus", "link", "a", "b", "--relation", "contradicts"]);
+        match &cli.command {
+            Command::Link { source, target, relation } => {
+                assert_eq!((source.as_str(), target.as_str()), ("a", "b"));
+                assert_eq!(*relation, LinkRelation::Contradicts);
+            }
+            _ => panic!("Expected Link command"),
+        }
+        assert!(cli.command.is_audited());
+        assert!(cli.command.validate_refs().is_err());
+
+        let cli = Cli::parse_from(["claude-hippocampus", "link", "a", "b"]);
+        assert!(matches!(cli.command, Command::Link { relation: LinkRelation::RelatesTo, .. }));
+        let cli = Cli::parse_from(["claude-hippocampus", "unlink", "a", "b"]);
+        assert!(matches!(cli.command, Command::Unlink { relation: None, .. }));
+    }
+
+    #[test]
+    fn test_supersede() {
+        let (old, new) = (Uuid::new_v4().to_string(), Uuid::new_v4().to_string());
+        let cli = Cli::parse_from(["claude-hippocampus", "supersede", &old, &new]);
+        assert!(matches!(cli.command, Command::Supersede { ref old_id, .. } if *old_id == old));
+        assert!(cli.command.is_audited());
+        assert!(cli.command.validate_refs().is_ok());
+        let cli = Cli::parse_from(["claude-hippocampus", "supersede", &old, "nope"]);
+        assert!(cli.command.validate_refs().is_err());
+
+        let cli = Cli::parse_from(["claude-hippocampus", "unsupersede", &old, "--deactivate-successor"]);
+        assert!(matches!(cli.command, Command::Unsupersede { deactivate_successor: true, .. }));
+        assert!(cli.command.is_audited());
+        assert!(cli.command.validate_refs().is_ok());
+        let cli = Cli::parse_from(["claude-hippocampus", "unsupersede", "nope"]);
+        assert!(cli.command.validate_refs().is_err());
+        assert!(Cli::try_parse_from(["claude-hippocampus", "link", "a", "b", "--relation", "causes"]).is_err());
+    }
+
+    #[test]
+    fn test_mute_requires_session() {
+        assert!(Cli::try_parse_from(["claude-hippocampus", "mute", "abc"]).is_err());
+    }
+
+    // -------------------------------------------------------------------------
+    // TurnLatency command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_turn_latency() {
+        let cli = Cli::parse_from(["claude-hippocampus", "turn-latency", "--session", "abc"]);
+        match cli.command {
+            Command::TurnLatency { session } => assert_eq!(session, "abc"),
+            _ => panic!("Expected TurnLatency command"),
+        }
+        assert!(Cli::try_parse_from(["claude-hippocampus", "turn-latency"]).is_err());
+    }
+
+    // -------------------------------------------------------------------------
+    // Browse command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_browse() {
+        let cli = Cli::parse_from(["claude-hippocampus", "browse"]);
+        assert!(matches!(cli.command, Command::Browse));
+    }
+
+    // -------------------------------------------------------------------------
+    // Migrate command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_migrate() {
+        let cli = Cli::parse_from(["claude-hippocampus", "migrate"]);
+        assert!(matches!(cli.command, Command::Migrate { status: false }));
+        let cli = Cli::parse_from(["claude-hippocampus", "migrate", "--status"]);
+        assert!(matches!(cli.command, Command::Migrate { status: true }));
+    }
+
+    #[test]
+    fn test_migrate_legacy() {
+        let cli = Cli::parse_from(["claude-hippocampus", "migrate-legacy", "--from", "postgres://old/memory"]);
+        match cli.command {
+            Command::MigrateLegacy { from, table, dry_run, jobs } => {
+                assert_eq!(from, "postgres://old/memory");
+                assert_eq!(table, "memories");
+                assert!(!dry_run);
+                assert_eq!(jobs, DEFAULT_JOBS);
+            }
+            _ => panic!("Expected MigrateLegacy command"),
+        }
+        assert!(Cli::try_parse_from(["claude-hippocampus", "migrate-legacy"]).is_err());
+        let cli = Cli::parse_from(["claude-hippocampus", "migrate-legacy", "--from", "x", "--jobs", "8"]);
+        assert!(matches!(cli.command, Command::MigrateLegacy { jobs: 8, .. }));
+        assert!(Cli::try_parse_from(["claude-hippocampus", "migrate-legacy", "--from", "x", "--jobs", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_add_memories_batch_dry_run() {
+        let cli = Cli::parse_from(["claude-hippocampus", "add-memories-batch", "--dry-run", "--conflicts", "3"]);
+        assert!(matches!(cli.command, Command::AddMemoriesBatch { dry_run: true, conflicts: 3 }));
+        assert!(!cli.command.is_audited());
+        let cli = Cli::parse_from(["claude-hippocampus", "add-memories-batch"]);
+        assert!(matches!(cli.command, Command::AddMemoriesBatch { dry_run: false, conflicts: 10 }));
+        assert!(cli.command.is_audited());
+        assert!(Cli::try_parse_from(["claude-hippocampus", "add-memories-batch", "--conflicts", "3"]).is_err());
+    }
+
+    #[test]
+    fn test_generate_fixtures() {
+        let cli = Cli::parse_from(["claude-hippocampus", "generate-fixtures", "--memories", "200", "--clear"]);
+        assert!(matches!(
+            cli.command,
+            Command::GenerateFixtures { memories: 200, sessions: 50, projects: 3, days: 365, seed: None, clear: true }
+        ));
+        assert!(cli.command.is_audited());
+    }
+
+    // -------------------------------------------------------------------------
+    // Schema command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_export_conversations() {
+        let cli = Cli::parse_from(["claude-hippocampus", "export-conversations"]);
+        assert!(matches!(
+            cli.command,
+            Command::ExportConversations { project: None, since: None, format: None, jobs: DEFAULT_JOBS }
+        ));
+
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "export-conversations",
+            "--project",
+            "/work/app",
+            "--format",
+            "json",
+        ]);
+        match cli.command {
+            Command::ExportConversations { project, format, .. } => {
+                assert_eq!(project.as_deref(), Some("/work/app"));
+                assert_eq!(format, Some(OutputFormat::Json));
+            }
+            _ => panic!("Expected ExportConversations command"),
+        }
+    }
+
+    #[test]
+    fn test_schema_defaults_to_sql() {
+        let cli = Cli::parse_from(["claude-hippocampus", "schema"]);
+        assert!(matches!(cli.command, Command::Schema { format: None }));
+    }
+
+    #[test]
+    fn test_schema_mermaid() {
+        let cli = Cli::parse_from(["claude-hippocampus", "schema", "--format", "mermaid"]);
+        assert!(matches!(cli.command, Command::Schema { format: Some(OutputFormat::Mermaid) }));
+    }
+
+    // -------------------------------------------------------------------------
+    // AddMemory with supersedes tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_add_memory_with_supersedes() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "add-memory",
+            "learning",
+            "New content",
+            "",
+            "high",
+            "project",
+            "--supersedes=550e8400-e29b-41d4-a716-446655440000",
+        ]);
+        match cli.command {
+            Command::AddMemory { supersedes, .. } => {
+                assert_eq!(
+                    supersedes,
+                    Some("550e8400-e29b-41d4-a716-446655440000".to_string())
+                );
+            }
+            _ => panic!("Expected AddMemory command"),
         }
     }
 
@@ -1519,5 +4248,260 @@ mod tests {
             _ => panic!("Expected AddMemory command"),
         }
     }
-}
+    #[test]
+    fn test_add_memory_with_constraints() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "add-memory",
+            "gotcha",
+            "BSD sed needs -i ''",
+            "--constraint=os=macos",
+            "--constraint",
+            "rust>=1.75",
+        ]);
+        match cli.command {
+            Command::AddMemory { constraints, .. } => {
+                let constraints: Vec<String> = constraints.iter().map(|c| c.to_string()).collect();
+                assert_eq!(constraints, vec!["os=macos", "rustc>=1.75"]);
+            }
+            _ => panic!("Expected AddMemory command"),
+        }
+    }
+
+    #[test]
+    fn test_add_memory_check_conflicts() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "add-memory",
+            "convention",
+            "Use tabs",
+            "--check-conflicts",
+        ]);
+        assert!(matches!(cli.command, Command::AddMemory { check_conflicts: true, .. }));
+    }
 
+    #[test]
+    fn test_add_memory_invalid_constraint() {
+        let result = Cli::try_parse_from([
+            "claude-hippocampus",
+            "add-memory",
+            "gotcha",
+            "content",
+            "--constraint=macos",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_validate_refs_names_invalid_flag() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "add-memory",
+            "learning",
+            "content",
+            "--session=550e8400-e29b-41d4-a716-446655440000",
+            "--turn=turn-456",
+        ]);
+        let err = cli.command.validate_refs().unwrap_err();
+        assert_eq!(err.argument(), Some("--turn"));
+        assert!(err.to_string().contains("turn-456"));
+    }
+
+    #[test]
+    fn test_validate_refs_ignore_invalid_refs() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "add-memory",
+            "learning",
+            "content",
+            "--session=sess-123",
+            "--ignore-invalid-refs",
+        ]);
+        assert!(cli.command.validate_refs().is_ok());
+        assert_eq!(parse_ref("--session", Some("sess-123"), true).unwrap(), None);
+    }
+
+    #[test]
+    fn test_validate_refs_positional_id() {
+        let cli = Cli::parse_from(["claude-hippocampus", "review", "resolve", "not-a-uuid"]);
+        let err = cli.command.validate_refs().unwrap_err();
+        assert_eq!(err.argument(), Some("<id>"));
+
+        let cli = Cli::parse_from(["claude-hippocampus", "get-session", "claude-789"]);
+        assert!(cli.command.validate_refs().is_ok());
+    }
+
+    // -------------------------------------------------------------------------
+    // Two-phase add tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_propose_memory_defaults() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "propose-memory",
+            "gotcha",
+            "Run migrations before tests",
+        ]);
+        match cli.command {
+            Command::ProposeMemory { confidence, tier, ttl_hours, supersedes, .. } => {
+                assert_eq!(confidence, Confidence::High);
+                assert_eq!(tier, Scope::Project);
+                assert_eq!(ttl_hours, 24);
+                assert!(supersedes.is_none());
+            }
+            _ => panic!("Expected ProposeMemory command"),
+        }
+    }
+
+    #[test]
+    fn test_commit_memory_with_supersedes() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "commit-memory",
+            "550e8400-e29b-41d4-a716-446655440000",
+            "--supersedes=6ba7b810-9dad-11d1-80b4-00c04fd430c8",
+        ]);
+        match cli.command {
+            Command::CommitMemory { staging_id, supersedes } => {
+                assert_eq!(staging_id, "550e8400-e29b-41d4-a716-446655440000");
+                assert_eq!(supersedes.as_deref(), Some("6ba7b810-9dad-11d1-80b4-00c04fd430c8"));
+            }
+            _ => panic!("Expected CommitMemory command"),
+        }
+    }
+
+    #[test]
+    fn test_output_format() {
+        let cli = Cli::parse_from(["claude-hippocampus", "list-recent"]);
+        assert_eq!(cli.format, None);
+        let cli = Cli::parse_from(["claude-hippocampus", "--format", "table", "stats"]);
+        assert_eq!(cli.format, Some(OutputFormat::Table));
+        let cli = Cli::parse_from(["claude-hippocampus", "list-recent", "2", "--format", "plain"]);
+        assert_eq!(cli.format, Some(OutputFormat::Plain));
+    }
+
+    #[test]
+    fn test_subcommand_position() {
+        assert_eq!(subcommand_position(&["claude-hippocampus", "lr"]), 1);
+        assert_eq!(subcommand_position(&["claude-hippocampus", "-q", "--format", "plain", "lr", "5"]), 4);
+        assert_eq!(subcommand_position(&["claude-hippocampus", "--format=plain", "lr"]), 2);
+        assert_eq!(subcommand_position(&["claude-hippocampus", "--workspace"]), 2);
+    }
+
+    // -------------------------------------------------------------------------
+    // Audit command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_audit_filters() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "audit",
+            "--memory=550e8400-e29b-41d4-a716-446655440000",
+            "--since=2024-01-24T10:30:00Z",
+        ]);
+        match &cli.command {
+            Command::Audit { memory, since, session, limit, .. } => {
+                assert_eq!(memory.as_deref(), Some("550e8400-e29b-41d4-a716-446655440000"));
+                assert!(since.is_some());
+                assert!(session.is_none());
+                assert_eq!(*limit, 50);
+            }
+            _ => panic!("Expected Audit command"),
+        }
+        assert!(!cli.command.is_audited());
+
+        let cli = Cli::parse_from(["claude-hippocampus", "audit", "--memory=abc"]);
+        assert_eq!(cli.command.validate_refs().unwrap_err().argument(), Some("--memory"));
+    }
+
+    #[test]
+    fn test_is_audited() {
+        let audited = |args: &[&str]| {
+            let args = std::iter::once("claude-hippocampus").chain(args.iter().copied());
+            Cli::parse_from(args).command.is_audited()
+        };
+        assert!(audited(&["delete-memory", "550e8400-e29b-41d4-a716-446655440000"]));
+        assert!(audited(&["policy", "set", "low_days", "20"]));
+        assert!(audited(&["decay"]));
+        assert!(!audited(&["decay", "--dry-run"]));
+        assert!(!audited(&["policy", "show"]));
+        assert!(!audited(&["search-keyword", "sed"]));
+        assert!(!audited(&["create-session", "--claude-session-id=abc-123"]));
+    }
+
+    #[test]
+    fn test_memory_refs() {
+        let cli = Cli::parse_from([
+            "claude-hippocampus",
+            "add-memory",
+            "gotcha",
+            "content",
+            "--supersedes=6ba7b810-9dad-11d1-80b4-00c04fd430c8",
+        ]);
+        assert_eq!(
+            cli.command.memory_refs(),
+            vec![Uuid::parse_str("6ba7b810-9dad-11d1-80b4-00c04fd430c8").unwrap()]
+        );
+
+        let cli = Cli::parse_from(["claude-hippocampus", "add-memory", "gotcha", "content"]);
+        assert!(cli.command.memory_refs().is_empty());
+    }
+
+    // -------------------------------------------------------------------------
+    // Recall command tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_recall_defaults() {
+        let cli = Cli::parse_from(["claude-hippocampus", "recall", "sqlx offline mode"]);
+        match cli.command {
+            Command::Recall { query, limit, all_projects } => {
+                assert_eq!(query, "sqlx offline mode");
+                assert_eq!(limit, 5);
+                assert!(!all_projects);
+            }
+            _ => panic!("Expected Recall command"),
+        }
+    }
+
+    #[test]
+    fn test_search_hybrid_defaults() {
+        let cli = Cli::parse_from(["claude-hippocampus", "search", "connection pool", "--tag", "db"]);
+        match cli.command {
+            Command::Search { query, tier, limit, filters } => {
+                assert_eq!(query, "connection pool");
+                assert_eq!(tier, None);
+                assert_eq!(limit, 10);
+                assert_eq!(filters.filters().tag.as_deref(), Some("db"));
+            }
+            _ => panic!("Expected Search command"),
+        }
+    }
+
+    #[test]
+    fn test_embed_turns_validates_turn() {
+        let cli = Cli::parse_from(["claude-hippocampus", "embed-turns", "--turn=abc"]);
+        assert_eq!(cli.command.validate_refs().unwrap_err().argument(), Some("--turn"));
+    }
+
+    // -------------------------------------------------------------------------
+    // Verbosity flag tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_stderr_level_from_flags() {
+        let level = |args: &[&str]| Cli::parse_from(args).stderr_level();
+        assert_eq!(level(&["claude-hippocampus", "stats"]), Some(LogLevel::Warn));
+        assert_eq!(level(&["claude-hippocampus", "-v", "stats"]), Some(LogLevel::Info));
+        assert_eq!(level(&["claude-hippocampus", "stats", "-vv"]), Some(LogLevel::Debug));
+        assert_eq!(level(&["claude-hippocampus", "-vvvv", "stats"]), Some(LogLevel::Trace));
+        assert_eq!(level(&["claude-hippocampus", "hook", "stop", "--quiet"]), None);
+    }
+
+    #[test]
+    fn test_quiet_conflicts_with_verbose() {
+        assert!(Cli::try_parse_from(["claude-hippocampus", "-q", "-v", "stats"]).is_err());
+    }
+}
@@ -0,0 +1,25 @@
+//! Audit command: which commands changed memories, and who ran them
+
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+
+use crate::db::audit::{self, AuditEntry, AuditQuery};
+use crate::error::Result;
+
+/// Result of audit command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditResult {
+    pub count: usize,
+    /// Newest first
+    pub entries: Vec<AuditEntry>,
+}
+
+/// List recorded commands matching `query`.
+pub async fn audit(pool: &PgPool, query: &AuditQuery) -> Result<AuditResult> {
+    let entries = audit::list(pool, query).await?;
+    Ok(AuditResult {
+        count: entries.len(),
+        entries,
+    })
+}
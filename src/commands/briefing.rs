@@ -0,0 +1,124 @@
+//! Briefing commands: one curated markdown document per project
+//!
+//! Memories are small and mostly captured automatically. A briefing is the
+//! opposite: a single, longer document a team maintains by hand as the
+//! anchor for the project. `get-context` always emits it before any memory.
+
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+
+use crate::db::queries::{self, ProjectBriefing};
+use crate::error::{HippocampusError, Result};
+
+/// Size allowance for a briefing, far above a regular memory's
+pub const BRIEFING_MAX_CHARS: usize = 20_000;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Result of set-briefing command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetBriefingResult {
+    pub project_path: String,
+    /// The stored briefing; absent after `--clear`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub briefing: Option<ProjectBriefing>,
+    /// Whether `--clear` removed an existing briefing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cleared: Option<bool>,
+}
+
+/// Result of show-briefing command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShowBriefingResult {
+    pub project_path: String,
+    pub briefing: Option<ProjectBriefing>,
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Store (or with `clear`, remove) a project's briefing.
+pub async fn set_briefing(
+    pool: &PgPool,
+    project_path: &str,
+    content: &str,
+    clear: bool,
+) -> Result<SetBriefingResult> {
+    if clear {
+        let cleared = queries::delete_briefing(pool, project_path).await?;
+        return Ok(SetBriefingResult {
+            project_path: project_path.to_string(),
+            briefing: None,
+            cleared: Some(cleared),
+        });
+    }
+
+    let content = validate_briefing(content)?;
+    let briefing = queries::set_briefing(pool, project_path, content).await?;
+    Ok(SetBriefingResult {
+        project_path: project_path.to_string(),
+        briefing: Some(briefing),
+        cleared: None,
+    })
+}
+
+/// Show a project's briefing, if it has one.
+pub async fn show_briefing(pool: &PgPool, project_path: &str) -> Result<ShowBriefingResult> {
+    let briefing = queries::get_briefing(pool, &[project_path.to_string()]).await?;
+    Ok(ShowBriefingResult {
+        project_path: project_path.to_string(),
+        briefing,
+    })
+}
+
+/// Trim a briefing and check it is non-empty and within the size allowance.
+fn validate_briefing(content: &str) -> Result<&str> {
+    let content = content.trim();
+    if content.is_empty() {
+        return Err(HippocampusError::Validation(
+            "Briefing is empty (use --clear to remove it)".to_string(),
+        ));
+    }
+    let chars = content.chars().count();
+    if chars > BRIEFING_MAX_CHARS {
+        return Err(HippocampusError::Validation(format!(
+            "Briefing is {} characters; the limit is {}",
+            chars, BRIEFING_MAX_CHARS
+        )));
+    }
+    Ok(content)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_briefing_trims() {
+        assert_eq!(
+            validate_briefing("\n# Project\n\nNotes\n\n").unwrap(),
+            "# Project\n\nNotes"
+        );
+    }
+
+    #[test]
+    fn test_validate_briefing_rejects_empty() {
+        assert!(validate_briefing("  \n ").is_err());
+    }
+
+    #[test]
+    fn test_validate_briefing_size_limit() {
+        assert!(validate_briefing(&"é".repeat(BRIEFING_MAX_CHARS)).is_ok());
+        let err = validate_briefing(&"a".repeat(BRIEFING_MAX_CHARS + 1)).unwrap_err();
+        assert!(err.to_string().contains("limit is 20000"));
+    }
+}
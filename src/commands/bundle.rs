@@ -0,0 +1,265 @@
+//! Debug-bundle command: the context a bug report needs, in one tarball
+//!
+//! Collects the merged config, the schema version, recent logs, the last
+//! recorded input of each hook and the health report. Secrets are redacted
+//! as in hook recordings and the home directory is shown as `~`; nothing is
+//! sent anywhere. Parts that cannot be collected (e.g. the database is
+//! down) are listed in the manifest instead of failing the bundle.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::commands::{config_show, health_report};
+use crate::config::DbConfig;
+use crate::db::{create_pool, migrations};
+use crate::error::{HippocampusError, Result};
+use crate::hooks::debug::hook_log_paths;
+use crate::hooks::recorder::{latest_recordings, recordings_dir, redact_payload, redact_text};
+use crate::logging::get_log_path;
+
+/// Log lines kept from the end of each log file by default
+pub const DEFAULT_LOG_LINES: usize = 200;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Result of debug-bundle command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugBundleResult {
+    /// The tarball written
+    pub path: String,
+    /// Files in the bundle
+    pub files: Vec<String>,
+    /// Parts that could not be collected, and why
+    pub skipped: Vec<String>,
+}
+
+/// Written to `manifest.json` in the bundle
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest<'a> {
+    created_at: String,
+    version: &'static str,
+    os: &'static str,
+    arch: &'static str,
+    files: &'a [String],
+    skipped: &'a [String],
+}
+
+/// Bundle files being collected in a staging directory
+struct Bundle {
+    dir: PathBuf,
+    home: Option<String>,
+    files: Vec<String>,
+    skipped: Vec<String>,
+}
+
+// ============================================================================
+// Command
+// ============================================================================
+
+/// Collect a debug bundle into a `.tar.gz` at `output`, by default
+/// `hippocampus-debug-<timestamp>.tar.gz` in the working directory.
+pub async fn debug_bundle(
+    project_path: Option<&str>,
+    output: Option<PathBuf>,
+    log_lines: usize,
+) -> Result<DebugBundleResult> {
+    let name = format!("hippocampus-debug-{}", Utc::now().format("%Y%m%d-%H%M%S"));
+    let output = output.unwrap_or_else(|| PathBuf::from(format!("{}.tar.gz", name)));
+    let staging = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+
+    let mut bundle = Bundle {
+        dir: staging.join(&name),
+        home: dirs::home_dir().map(|h| h.display().to_string()),
+        files: Vec::new(),
+        skipped: Vec::new(),
+    };
+    fs::create_dir_all(&bundle.dir)?;
+
+    let result = collect(&mut bundle, project_path, log_lines)
+        .await
+        .and_then(|_| archive(&staging, &name, &output));
+    let _ = fs::remove_dir_all(&staging);
+    result?;
+
+    Ok(DebugBundleResult {
+        path: output.display().to_string(),
+        files: bundle.files,
+        skipped: bundle.skipped,
+    })
+}
+
+async fn collect(bundle: &mut Bundle, project_path: Option<&str>, log_lines: usize) -> Result<()> {
+    match config_show(project_path) {
+        Ok(config) => bundle.write_json("config.json", &config)?,
+        Err(e) => bundle.skip("config.json", &e),
+    }
+
+    let config = DbConfig::load().unwrap_or_default();
+    match create_pool(&config).await {
+        Ok(pool) => {
+            match migrations::status(&pool).await {
+                Ok(status) => bundle.write_json("schema.json", &status)?,
+                Err(e) => bundle.skip("schema.json", &e),
+            }
+            match health_report(&pool, &config.anomalies).await {
+                Ok(report) => bundle.write_json("health-report.json", &report)?,
+                Err(e) => bundle.skip("health-report.json", &e),
+            }
+        }
+        Err(e) => {
+            bundle.skip("schema.json", &e);
+            bundle.skip("health-report.json", &e);
+        }
+    }
+
+    match get_log_path() {
+        Ok(path) => bundle.copy_tail(&path, "logs", log_lines)?,
+        Err(e) => bundle.skip("logs/memory.log", &e),
+    }
+    for path in hook_log_paths() {
+        bundle.copy_tail(&path, "logs", log_lines)?;
+    }
+
+    let recordings = recordings_dir().and_then(|dir| latest_recordings(&dir));
+    match recordings {
+        Ok(paths) if paths.is_empty() => {
+            bundle.skipped.push("hook-inputs: none recorded (set record_hooks to record them)".to_string())
+        }
+        Ok(paths) => {
+            for path in paths {
+                let raw = fs::read_to_string(&path)?;
+                bundle.write(&format!("hook-inputs/{}", file_name(&path)), &redact_payload(&raw))?;
+            }
+        }
+        Err(e) => bundle.skip("hook-inputs", &e),
+    }
+
+    let manifest = Manifest {
+        created_at: Utc::now().to_rfc3339(),
+        version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        files: &bundle.files.clone(),
+        skipped: &bundle.skipped.clone(),
+    };
+    bundle.write_json("manifest.json", &manifest)
+}
+
+/// Pack `staging/name` into a gzipped tarball with the system `tar`
+fn archive(staging: &Path, name: &str, output: &Path) -> Result<()> {
+    let output = std::path::absolute(output)?;
+    let status = Command::new("tar")
+        .arg("-czf")
+        .arg(&output)
+        .arg("-C")
+        .arg(staging)
+        .arg(name)
+        .status()
+        .map_err(|e| HippocampusError::Config(format!("Cannot run tar: {}", e)))?;
+    if !status.success() {
+        return Err(HippocampusError::Config(format!(
+            "tar failed writing {} ({})",
+            output.display(),
+            status
+        )));
+    }
+    Ok(())
+}
+
+impl Bundle {
+    /// Write a file into the bundle, redacted and with the home directory as `~`
+    fn write(&mut self, relative: &str, contents: &str) -> Result<()> {
+        let path = self.dir.join(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, anonymize(contents, self.home.as_deref()))?;
+        self.files.push(relative.to_string());
+        Ok(())
+    }
+
+    fn write_json<T: Serialize>(&mut self, relative: &str, value: &T) -> Result<()> {
+        let json = serde_json::to_string_pretty(value)?;
+        self.write(relative, &redact_payload(&json))
+    }
+
+    /// Copy the last `lines` lines of a file into `dir`, redacted
+    fn copy_tail(&mut self, path: &Path, dir: &str, lines: usize) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+        let contents = fs::read_to_string(path)?;
+        let all: Vec<&str> = contents.lines().collect();
+        let tail: Vec<String> = all[all.len().saturating_sub(lines)..]
+            .iter()
+            .map(|line| redact_text(line))
+            .collect();
+        self.write(&format!("{}/{}", dir, file_name(path)), &(tail.join("\n") + "\n"))
+    }
+
+    fn skip(&mut self, part: &str, error: &HippocampusError) {
+        let reason = anonymize(&redact_text(&error.to_string()), self.home.as_deref());
+        self.skipped.push(format!("{}: {}", part, reason));
+    }
+}
+
+/// Show the home directory as `~`
+fn anonymize(text: &str, home: Option<&str>) -> String {
+    match home {
+        Some(home) if home.len() > 1 => text.replace(home, "~"),
+        _ => text.to_string(),
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_anonymize_home() {
+        assert_eq!(
+            anonymize("/home/ana/.claude/logs/memory.log", Some("/home/ana")),
+            "~/.claude/logs/memory.log"
+        );
+        assert_eq!(anonymize("/x", Some("/")), "/x");
+        assert_eq!(anonymize("/x", None), "/x");
+    }
+
+    #[test]
+    fn test_copy_tail_redacts_last_lines() {
+        let dir = TempDir::new().unwrap();
+        let log = dir.path().join("memory.log");
+        fs::write(&log, "one\ntwo PASSWORD=hunter2\nthree\n").unwrap();
+
+        let mut bundle = Bundle {
+            dir: dir.path().join("bundle"),
+            home: None,
+            files: Vec::new(),
+            skipped: Vec::new(),
+        };
+        bundle.copy_tail(&log, "logs", 2).unwrap();
+
+        assert_eq!(bundle.files, vec!["logs/memory.log"]);
+        let copied = fs::read_to_string(dir.path().join("bundle/logs/memory.log")).unwrap();
+        assert_eq!(copied, "two PASSWORD=[REDACTED]\nthree\n");
+    }
+}
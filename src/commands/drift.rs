@@ -0,0 +1,369 @@
+//! Drift-check command: compare convention memories with CLAUDE.md
+//!
+//! CLAUDE.md is the human-readable record of a repo's conventions; memories
+//! are the machine one. Over time they drift apart. This command matches
+//! high-confidence convention memories against the bullet points in the
+//! project's CLAUDE.md and reports what each side is missing, optionally as
+//! a patch that appends the missing conventions to the file.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+
+use crate::db::queries;
+use crate::error::{HippocampusError, Result};
+use crate::models::{Confidence, Memory, MemorySummary, MemoryType};
+
+/// File compared against, relative to the project root
+const CLAUDE_MD: &str = "CLAUDE.md";
+
+/// Heading of the section added by `--patch`
+const PATCH_HEADING: &str = "## Conventions (from memory)";
+
+/// Dice similarity at which a memory and a bullet state the same convention
+const MATCH_THRESHOLD: f64 = 0.5;
+
+/// Bullets with fewer significant words are too terse to compare
+const MIN_BULLET_WORDS: usize = 3;
+
+/// Context lines around the hunk in generated patches
+const PATCH_CONTEXT: usize = 3;
+
+/// Words ignored when comparing conventions
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "with", "use", "using", "are", "not", "all", "any", "from", "into",
+    "that", "this", "than", "then", "when", "should", "must", "always", "never", "don",
+    "instead", "only", "via", "its", "our", "you", "your", "can", "will", "have", "has",
+];
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A convention bullet in CLAUDE.md
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileConvention {
+    /// 1-based line number in the file
+    pub line: usize,
+    pub text: String,
+}
+
+/// Result of drift-check command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriftCheckResult {
+    pub project_path: String,
+    pub claude_md_exists: bool,
+    /// High-confidence convention memories compared
+    pub memories_checked: usize,
+    /// Convention bullets found in CLAUDE.md
+    pub file_conventions: usize,
+    /// Memories with a matching bullet
+    pub in_sync: usize,
+    /// Conventions remembered but not written down
+    pub missing_from_file: Vec<MemorySummary>,
+    /// Conventions written down but not remembered
+    pub missing_from_memory: Vec<FileConvention>,
+    /// Unified diff adding `missing_from_file` to CLAUDE.md (with `--patch`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patch: Option<String>,
+}
+
+// ============================================================================
+// Command
+// ============================================================================
+
+/// Compare a project's high-confidence convention memories with its CLAUDE.md.
+pub async fn drift_check(
+    pool: &PgPool,
+    project_path: &str,
+    emit_patch: bool,
+) -> Result<DriftCheckResult> {
+    let root = Path::new(project_path);
+    if !root.is_dir() {
+        return Err(HippocampusError::Validation(format!(
+            "Project path is not a directory: {}",
+            project_path
+        )));
+    }
+
+    let memories: Vec<Memory> =
+        queries::list_project_memories_by_types(pool, project_path, &[MemoryType::Convention])
+            .await?
+            .into_iter()
+            .filter(|m| m.confidence == Confidence::High)
+            .collect();
+
+    let claude_md = fs::read_to_string(root.join(CLAUDE_MD)).ok();
+    let mut result = compare(&memories, claude_md.as_deref().unwrap_or(""), project_path);
+    result.claude_md_exists = claude_md.is_some();
+
+    if emit_patch && !result.missing_from_file.is_empty() {
+        let missing: Vec<&Memory> = memories
+            .iter()
+            .filter(|m| result.missing_from_file.iter().any(|s| s.id == m.id))
+            .collect();
+        result.patch = Some(build_patch(claude_md.as_deref(), &missing));
+    }
+
+    Ok(result)
+}
+
+/// Match memories against the bullets of a CLAUDE.md file.
+fn compare(memories: &[Memory], claude_md: &str, project_path: &str) -> DriftCheckResult {
+    let bullets = extract_bullets(claude_md);
+    let bullet_words: Vec<HashSet<String>> = bullets.iter().map(|b| significant_words(&b.text)).collect();
+    let mut matched_bullets = vec![false; bullets.len()];
+    let mut missing_from_file = Vec::new();
+
+    for memory in memories {
+        let words = significant_words(&memory.content);
+        let mut found = false;
+        for (i, other) in bullet_words.iter().enumerate() {
+            if similarity(&words, other) >= MATCH_THRESHOLD {
+                matched_bullets[i] = true;
+                found = true;
+            }
+        }
+        if !found {
+            missing_from_file.push(memory.to_summary());
+        }
+    }
+
+    let missing_from_memory: Vec<FileConvention> = bullets
+        .into_iter()
+        .zip(matched_bullets)
+        .filter(|(_, matched)| !matched)
+        .map(|(bullet, _)| bullet)
+        .collect();
+
+    DriftCheckResult {
+        project_path: project_path.to_string(),
+        claude_md_exists: true,
+        memories_checked: memories.len(),
+        file_conventions: bullet_words.len(),
+        in_sync: memories.len() - missing_from_file.len(),
+        missing_from_file,
+        missing_from_memory,
+        patch: None,
+    }
+}
+
+// ============================================================================
+// Matching
+// ============================================================================
+
+/// Collect list items outside code fences that are long enough to compare
+fn extract_bullets(markdown: &str) -> Vec<FileConvention> {
+    let mut bullets = Vec::new();
+    let mut in_fence = false;
+
+    for (i, line) in markdown.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let Some(text) = strip_list_marker(trimmed) else {
+            continue;
+        };
+        if significant_words(text).len() >= MIN_BULLET_WORDS {
+            bullets.push(FileConvention {
+                line: i + 1,
+                text: text.to_string(),
+            });
+        }
+    }
+
+    bullets
+}
+
+/// Text of a markdown list item (`- x`, `* x`, `+ x`, `1. x`)
+fn strip_list_marker(line: &str) -> Option<&str> {
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(rest) = line.strip_prefix(marker) {
+            return Some(rest.trim());
+        }
+    }
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        return line[digits..]
+            .strip_prefix(". ")
+            .or_else(|| line[digits..].strip_prefix(") "))
+            .map(str::trim);
+    }
+    None
+}
+
+/// Lowercased words of three or more characters, minus stopwords
+fn significant_words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .map(|w| w.to_lowercase())
+        .filter(|w| w.chars().count() >= 3 && !STOPWORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// Dice coefficient of two word sets
+fn similarity(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let shared = a.intersection(b).count();
+    2.0 * shared as f64 / (a.len() + b.len()) as f64
+}
+
+// ============================================================================
+// Patch
+// ============================================================================
+
+/// Build a unified diff appending the given conventions to CLAUDE.md.
+///
+/// The result applies with `git apply` or `patch -p1` from the project root.
+fn build_patch(existing: Option<&str>, memories: &[&Memory]) -> String {
+    let mut added: Vec<String> = Vec::new();
+    let content = existing.unwrap_or("");
+    if !content.trim().is_empty() {
+        added.push(String::new());
+    }
+    added.push(PATCH_HEADING.to_string());
+    added.push(String::new());
+    for memory in memories {
+        let text = memory.content.split_whitespace().collect::<Vec<_>>().join(" ");
+        added.push(format!("- {}", text));
+    }
+
+    let mut patch = String::new();
+    match existing {
+        None => {
+            patch.push_str(&format!("--- /dev/null\n+++ b/{}\n", CLAUDE_MD));
+            patch.push_str(&format!("@@ -0,0 +1,{} @@\n", added.len()));
+        }
+        Some(content) => {
+            let lines: Vec<&str> = content.lines().collect();
+            let context_start = lines.len().saturating_sub(PATCH_CONTEXT);
+            let context = &lines[context_start..];
+            let missing_newline = !content.is_empty() && !content.ends_with('\n');
+
+            patch.push_str(&format!("--- a/{0}\n+++ b/{0}\n", CLAUDE_MD));
+            patch.push_str(&format!(
+                "@@ -{},{} +{},{} @@\n",
+                hunk_start(context_start, context.len()),
+                context.len(),
+                hunk_start(context_start, context.len() + added.len()),
+                context.len() + added.len()
+            ));
+
+            for (i, line) in context.iter().enumerate() {
+                let last = i + 1 == context.len();
+                if last && missing_newline {
+                    // The old last line gains a newline, so it is replaced
+                    patch.push_str(&format!("-{}\n\\ No newline at end of file\n+{}\n", line, line));
+                } else {
+                    patch.push_str(&format!(" {}\n", line));
+                }
+            }
+        }
+    }
+
+    for line in &added {
+        patch.push_str(&format!("+{}\n", line));
+    }
+    patch
+}
+
+/// Hunk start line: 1-based, or 0 for an empty range
+fn hunk_start(offset: usize, len: usize) -> usize {
+    if len == 0 {
+        offset
+    } else {
+        offset + 1
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_memory;
+
+    fn memory(content: &str) -> Memory {
+        test_memory(content, MemoryType::Convention)
+    }
+
+    const CLAUDE_MD_SAMPLE: &str = "# Project\n\n## Conventions\n\n- Run cargo clippy with -D warnings before committing\n- Errors go through HippocampusError variants\n- `cargo test`\n\n```\n- not a bullet inside a fence block\n```\n";
+
+    // -------------------------------------------------------------------------
+    // Matching tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_extract_bullets_skips_fences_and_short_items() {
+        let bullets = extract_bullets(CLAUDE_MD_SAMPLE);
+        assert_eq!(bullets.len(), 2);
+        assert_eq!(bullets[0].line, 5);
+        assert!(bullets[1].text.starts_with("Errors go through"));
+    }
+
+    #[test]
+    fn test_strip_list_marker() {
+        assert_eq!(strip_list_marker("- item"), Some("item"));
+        assert_eq!(strip_list_marker("12. item"), Some("item"));
+        assert_eq!(strip_list_marker("2024 was a year"), None);
+        assert_eq!(strip_list_marker("plain text"), None);
+    }
+
+    #[test]
+    fn test_compare_reports_both_directions() {
+        let memories = vec![
+            memory("Always run cargo clippy with -D warnings before committing changes"),
+            memory("Timestamps are stored as TIMESTAMPTZ in UTC"),
+        ];
+        let result = compare(&memories, CLAUDE_MD_SAMPLE, "/test");
+
+        assert_eq!(result.memories_checked, 2);
+        assert_eq!(result.file_conventions, 2);
+        assert_eq!(result.in_sync, 1);
+        assert_eq!(result.missing_from_file.len(), 1);
+        assert!(result.missing_from_file[0].summary.contains("TIMESTAMPTZ"));
+        assert_eq!(result.missing_from_memory.len(), 1);
+        assert!(result.missing_from_memory[0].text.contains("HippocampusError"));
+    }
+
+    // -------------------------------------------------------------------------
+    // Patch tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_build_patch_appends_section() {
+        let m = memory("Use sqlx runtime queries,\n not macros");
+        let patch = build_patch(Some("# P\n\n- one two three\n- four five six\n"), &[&m]);
+        assert!(patch.starts_with("--- a/CLAUDE.md\n+++ b/CLAUDE.md\n@@ -2,3 +2,7 @@\n"));
+        assert!(patch.contains(" - four five six\n+\n+## Conventions (from memory)\n+\n"));
+        assert!(patch.ends_with("+- Use sqlx runtime queries, not macros\n"));
+    }
+
+    #[test]
+    fn test_build_patch_new_file() {
+        let m = memory("Prefer tempfile in tests");
+        let patch = build_patch(None, &[&m]);
+        assert!(patch.starts_with("--- /dev/null\n+++ b/CLAUDE.md\n@@ -0,0 +1,3 @@\n"));
+    }
+
+    #[test]
+    fn test_build_patch_without_trailing_newline() {
+        let m = memory("Prefer tempfile in tests");
+        let patch = build_patch(Some("# P"), &[&m]);
+        assert!(patch.contains("@@ -1,1 +1,5 @@\n-# P\n\\ No newline at end of file\n+# P\n+\n"));
+    }
+}
@@ -0,0 +1,257 @@
+//! Export-conversations command: a project's turns as a dataset
+//!
+//! Emits one record per answered turn — the prompt, the response, and the
+//! memories in play — for building evaluation or fine-tuning sets from
+//! one's own sessions. Prompts, responses and memory summaries go through
+//! the same redaction as recorded hook inputs.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::db::queries::{self, SessionCitation, SessionTurn};
+use crate::error::{HippocampusError, Result};
+use crate::hooks::recorder::redact_text;
+use crate::output::OutputFormat;
+use crate::util::workers::map_ordered;
+
+/// Output format of export-conversations
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON record per line
+    #[default]
+    Jsonl,
+    /// A single JSON response listing the records
+    Json,
+}
+
+impl ExportFormat {
+    /// The export format `--format` asks for, JSONL when not given
+    pub fn from_flag(format: Option<OutputFormat>) -> Result<Self> {
+        match format {
+            None | Some(OutputFormat::Jsonl) => Ok(ExportFormat::Jsonl),
+            Some(OutputFormat::Json) => Ok(ExportFormat::Json),
+            Some(other) => Err(HippocampusError::Validation(format!(
+                "export-conversations prints --format jsonl or json, not {}",
+                other.name()
+            ))),
+        }
+    }
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A memory used in a conversation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CitedMemory {
+    pub id: Uuid,
+    #[serde(rename = "type")]
+    pub memory_type: String,
+    pub summary: String,
+}
+
+/// One prompt/response pair
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationRecord {
+    /// Claude session ID
+    pub session_id: String,
+    pub turn_number: i32,
+    pub started_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub prompt: String,
+    pub response: String,
+    /// Memories in the context block the session started with
+    pub injected: Vec<CitedMemory>,
+    /// Memories returned by memory commands during this turn
+    pub cited: Vec<CitedMemory>,
+}
+
+/// Result of export-conversations --format json
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportConversationsResult {
+    pub project_path: String,
+    pub count: usize,
+    pub conversations: Vec<ConversationRecord>,
+}
+
+// ============================================================================
+// Command
+// ============================================================================
+
+/// Export the answered turns of a project's sessions, oldest first.
+///
+/// `since` limits the export to sessions started at or after it. Sessions
+/// are read by `jobs` workers and exported in order.
+pub async fn export_conversations(
+    pool: &PgPool,
+    project_path: &str,
+    since: Option<DateTime<Utc>>,
+    jobs: usize,
+) -> Result<ExportConversationsResult> {
+    let sessions = queries::list_answered_sessions(pool, project_path, since).await?;
+    let records = map_ordered(sessions, jobs, |(session_id, claude_session_id)| {
+        let pool = pool.clone();
+        async move {
+            let turns = queries::list_session_turns(&pool, session_id)
+                .await?
+                .into_iter()
+                .filter(|turn| turn.assistant_response.is_some())
+                .map(|turn| SessionTurn {
+                    claude_session_id: claude_session_id.clone(),
+                    turn,
+                })
+                .collect();
+            let citations = queries::list_session_citations(&pool, &[session_id]).await?;
+            Ok(build_records(turns, &citations))
+        }
+    })
+    .await?;
+
+    let conversations: Vec<ConversationRecord> = records.into_iter().flatten().collect();
+    Ok(ExportConversationsResult {
+        project_path: project_path.to_string(),
+        count: conversations.len(),
+        conversations,
+    })
+}
+
+/// Attach citations to turns and redact them
+fn build_records(turns: Vec<SessionTurn>, citations: &[SessionCitation]) -> Vec<ConversationRecord> {
+    let mut injected: HashMap<Uuid, Vec<CitedMemory>> = HashMap::new();
+    let mut cited: HashMap<Uuid, Vec<CitedMemory>> = HashMap::new();
+    for citation in citations {
+        let (map, key) = match citation.turn_id {
+            Some(turn_id) => (&mut cited, turn_id),
+            None => (&mut injected, citation.session_id),
+        };
+        let memories = map.entry(key).or_default();
+        if !memories.iter().any(|m| m.id == citation.memory_id) {
+            memories.push(CitedMemory {
+                id: citation.memory_id,
+                memory_type: citation.memory_type.clone(),
+                summary: redact_text(&citation.summary),
+            });
+        }
+    }
+
+    turns
+        .into_iter()
+        .map(|SessionTurn { claude_session_id, turn }| ConversationRecord {
+            session_id: claude_session_id,
+            turn_number: turn.turn_number,
+            started_at: turn.started_at,
+            model: turn.model_used,
+            prompt: redact_text(&turn.user_prompt),
+            response: redact_text(turn.assistant_response.as_deref().unwrap_or_default()),
+            injected: turn
+                .session_id
+                .and_then(|id| injected.get(&id).cloned())
+                .unwrap_or_default(),
+            cited: cited.remove(&turn.id).unwrap_or_default(),
+        })
+        .collect()
+}
+
+impl ExportConversationsResult {
+    /// The records as JSON Lines
+    pub fn to_jsonl(&self) -> Result<String> {
+        let mut out = String::new();
+        for record in &self.conversations {
+            out.push_str(&serde_json::to_string(record)?);
+            out.push('\n');
+        }
+        Ok(out)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Turn;
+
+    fn session_turn(session_id: Uuid, number: i32, prompt: &str) -> SessionTurn {
+        SessionTurn {
+            claude_session_id: "claude-1".to_string(),
+            turn: Turn {
+                id: Uuid::new_v4(),
+                session_id: Some(session_id),
+                turn_number: number,
+                user_prompt: prompt.to_string(),
+                assistant_response: Some("Done.".to_string()),
+                model_used: Some("model-a".to_string()),
+                input_tokens: None,
+                output_tokens: None,
+                started_at: Utc::now(),
+                ended_at: None,
+                created_at: Utc::now(),
+            },
+        }
+    }
+
+    fn citation(session_id: Uuid, turn_id: Option<Uuid>, memory_id: Uuid) -> SessionCitation {
+        SessionCitation {
+            session_id,
+            turn_id,
+            kind: if turn_id.is_some() { "cited" } else { "injected" }.to_string(),
+            memory_id,
+            memory_type: "gotcha".to_string(),
+            summary: "Run migrations before tests".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_build_records_attaches_citations() {
+        let session = Uuid::new_v4();
+        let (first, second) = (session_turn(session, 1, "Fix it"), session_turn(session, 2, "Again"));
+        let (injected, cited) = (Uuid::new_v4(), Uuid::new_v4());
+        let citations = vec![
+            citation(session, None, injected),
+            citation(session, None, injected),
+            citation(session, Some(second.turn.id), cited),
+        ];
+
+        let records = build_records(vec![first, second], &citations);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].injected.len(), 1);
+        assert!(records[0].cited.is_empty());
+        assert_eq!(records[1].injected[0].id, injected);
+        assert_eq!(records[1].cited[0].id, cited);
+    }
+
+    #[test]
+    fn test_build_records_redacts() {
+        let session = Uuid::new_v4();
+        let turn = session_turn(session, 1, "Use DB_PASSWORD=hunter2 here");
+        let records = build_records(vec![turn], &[]);
+        assert_eq!(records[0].prompt, "Use DB_PASSWORD=[REDACTED] here");
+    }
+
+    #[test]
+    fn test_to_jsonl_one_record_per_line() {
+        let session = Uuid::new_v4();
+        let conversations = build_records(vec![session_turn(session, 1, "a"), session_turn(session, 2, "b")], &[]);
+        let result = ExportConversationsResult {
+            project_path: "/p".to_string(),
+            count: conversations.len(),
+            conversations,
+        };
+        let jsonl = result.to_jsonl().unwrap();
+        let lines: Vec<&str> = jsonl.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let record: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(record["turnNumber"], 2);
+        assert_eq!(record["sessionId"], "claude-1");
+    }
+}
@@ -0,0 +1,449 @@
+//! Extraction commands: reserve-extraction, extract-worker and extract-queued
+//!
+//! Every call to the extraction backend spends API budget. With
+//! `extraction_daily_cap` set in hippocampus.toml, the `extract-worker` the
+//! Stop hook spawns reserves a call before running `claude --print`; once the
+//! day's cap is reached the turn is queued instead, and `extract-queued`
+//! works through the backlog when budget is available again.
+//!
+//! Each attempt is recorded in `extractions` as it goes (spawned, parsed,
+//! then saved, skipped or failed with a reason) for `extraction-log` and
+//! `health-report`. When the backend answers with something other than the
+//! expected JSON, it is asked once more with a repair prompt, which spends
+//! another call from the budget.
+
+use std::time::Duration;
+
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::commands::memory::{add_memory, AddMemoryOptions, AddMemoryResult};
+use crate::db::queries::{self, ExtractionAttempt, ExtractionStatus};
+use crate::config::DuplicateCheck;
+use crate::error::{HippocampusError, Result};
+use crate::extraction::{
+    build_extraction_prompt, build_repair_prompt, is_correction, parse_extraction_response,
+    run_extraction,
+};
+use crate::lint::LintRules;
+use crate::models::{Confidence, MemoryType, Tier};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Result of reserve-extraction command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReserveExtractionResult {
+    pub reserved: bool,
+    pub used_today: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cap: Option<u32>,
+    /// The turn was queued for a later extract-queued run
+    pub queued: bool,
+}
+
+/// Outcome of one queued turn
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedExtraction {
+    pub turn_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_id: Option<Uuid>,
+    pub duplicate: bool,
+    /// Why nothing was saved, when nothing was
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+}
+
+/// Result of extract-worker command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractWorkerResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_id: Option<Uuid>,
+    pub duplicate: bool,
+    /// Why nothing was saved, when nothing was
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+}
+
+/// Result of extract-queued command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractQueuedResult {
+    pub processed: Vec<QueuedExtraction>,
+    pub saved: usize,
+    /// Turns still waiting in the queue
+    pub remaining: i64,
+    /// Stopped early because today's cap was reached
+    pub quota_exhausted: bool,
+}
+
+/// Result of extraction-log command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractionLogResult {
+    pub session_id: Uuid,
+    pub count: usize,
+    pub failed: usize,
+    /// Attempts retried with the repair prompt
+    pub retried: usize,
+    /// Newest first
+    pub attempts: Vec<ExtractionAttempt>,
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Reserve one extraction call from today's budget.
+///
+/// When the cap is reached and `turn` is given, the turn is queued so
+/// `extract-queued` can pick it up later.
+pub async fn reserve_extraction(
+    pool: &PgPool,
+    cap: Option<u32>,
+    turn: Option<Uuid>,
+) -> Result<ReserveExtractionResult> {
+    let reserved = queries::reserve_extraction(pool, cap).await?;
+
+    let mut queued = false;
+    if reserved.is_none() {
+        if let Some(turn_id) = turn {
+            queries::queue_extraction(pool, turn_id).await?;
+            queued = true;
+        }
+    }
+
+    let used_today = match reserved {
+        Some(calls) => calls,
+        None => queries::extraction_calls_today(pool).await?,
+    };
+
+    Ok(ReserveExtractionResult {
+        reserved: reserved.is_some(),
+        used_today,
+        cap,
+        queued,
+    })
+}
+
+/// Extract memories from queued turns, oldest first.
+///
+/// Each turn spends one call from the daily budget. Processing stops when the
+/// cap is reached or the backend fails; those turns stay queued.
+pub async fn extract_queued(
+    pool: &PgPool,
+    cap: Option<u32>,
+    limit: i64,
+    duplicate_check: DuplicateCheck,
+    lint: &LintRules,
+    rules: &[String],
+) -> Result<ExtractQueuedResult> {
+    let mut processed = Vec::new();
+    let mut saved = 0;
+    let mut quota_exhausted = false;
+
+    for turn_id in queries::list_extraction_queue(pool, limit).await? {
+        let Some(turn) = queries::find_turn_by_id(pool, turn_id).await? else {
+            queries::dequeue_extraction(pool, turn_id).await?;
+            continue;
+        };
+        let Some(response) = turn.assistant_response.as_deref() else {
+            queries::dequeue_extraction(pool, turn_id).await?;
+            processed.push(skipped(turn_id, "turn has no response"));
+            continue;
+        };
+
+        if queries::reserve_extraction(pool, cap).await?.is_none() {
+            quota_exhausted = true;
+            break;
+        }
+
+        // A backend failure leaves the turn queued for the next run
+        let attempt = queries::start_extraction(pool, turn.session_id, Some(turn_id), "queued").await?;
+        let prompt = build_extraction_prompt(&turn.user_prompt, response, rules);
+        let output = run_with_repair(pool, attempt, &prompt, cap).await?;
+        queries::dequeue_extraction(pool, turn_id).await?;
+
+        let session = match turn.session_id {
+            Some(id) => queries::find_session_by_id(pool, id).await?,
+            None => None,
+        };
+        let confidence = if is_correction(&turn.user_prompt) {
+            Confidence::High
+        } else {
+            Confidence::Medium
+        };
+        let target = SaveTarget {
+            confidence,
+            project_path: session.and_then(|s| s.project_path),
+            session_id: turn.session_id,
+            turn_id: Some(turn_id),
+            duplicate_check,
+            lint: lint.clone(),
+            attempt,
+        };
+
+        let mut outcome = QueuedExtraction {
+            turn_id,
+            memory_id: None,
+            duplicate: false,
+            skipped: None,
+        };
+        match save_extraction(pool, &output, target).await? {
+            Saved::Memory(id) => {
+                outcome.memory_id = Some(id);
+                saved += 1;
+            }
+            Saved::Duplicate => outcome.duplicate = true,
+            Saved::Skipped(reason) | Saved::Failed(reason) => {
+                outcome.skipped = Some(reason.to_string())
+            }
+        }
+        processed.push(outcome);
+    }
+
+    Ok(ExtractQueuedResult {
+        processed,
+        saved,
+        remaining: queries::count_extraction_queue(pool).await?,
+        quota_exhausted,
+    })
+}
+
+/// Extract a memory from one turn, as the Stop hook's detached worker.
+///
+/// Reserves a call from today's budget first; over the cap, `turn` is
+/// queued for `extract-queued` instead.
+#[allow(clippy::too_many_arguments)]
+pub async fn extract_worker(
+    pool: &PgPool,
+    prompt: &str,
+    confidence: Confidence,
+    session: Option<Uuid>,
+    turn: Option<Uuid>,
+    project_path: Option<&str>,
+    cap: Option<u32>,
+    duplicate_check: DuplicateCheck,
+    lint: &LintRules,
+) -> Result<ExtractWorkerResult> {
+    let mut result = ExtractWorkerResult {
+        memory_id: None,
+        duplicate: false,
+        skipped: None,
+    };
+
+    let attempt = queries::start_extraction(pool, session, turn, "worker").await?;
+    if !reserve_extraction(pool, cap, turn).await?.reserved {
+        let reason = "daily extraction cap reached";
+        queries::update_extraction(pool, attempt, ExtractionStatus::Skipped, Some(reason), None).await?;
+        result.skipped = Some(reason.to_string());
+        return Ok(result);
+    }
+    let output = run_with_repair(pool, attempt, prompt, cap).await?;
+
+    let target = SaveTarget {
+        confidence,
+        project_path: project_path.map(str::to_string),
+        session_id: session,
+        turn_id: turn,
+        duplicate_check,
+        lint: lint.clone(),
+        attempt,
+    };
+    match save_extraction(pool, &output, target).await? {
+        Saved::Memory(id) => result.memory_id = Some(id),
+        Saved::Duplicate => result.duplicate = true,
+        Saved::Skipped(reason) | Saved::Failed(reason) => result.skipped = Some(reason.to_string()),
+    }
+    Ok(result)
+}
+
+/// List a session's extraction attempts and how each ended.
+///
+/// `session` is a database session UUID or a Claude session ID.
+pub async fn extraction_log(pool: &PgPool, session: &str, limit: i64) -> Result<ExtractionLogResult> {
+    let found = match Uuid::parse_str(session) {
+        Ok(id) => queries::find_session_by_id(pool, id).await?,
+        Err(_) => queries::find_session_by_claude_id(pool, session).await?,
+    };
+    let session = found.ok_or_else(|| HippocampusError::SessionNotFound(session.to_string()))?;
+
+    let attempts = queries::list_extractions(pool, session.id, limit).await?;
+    Ok(ExtractionLogResult {
+        session_id: session.id,
+        count: attempts.len(),
+        failed: attempts.iter().filter(|a| a.status == ExtractionStatus::Failed.as_str()).count(),
+        retried: attempts.iter().filter(|a| a.retried).count(),
+        attempts,
+    })
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Pause before asking the backend again with the repair prompt
+const REPAIR_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Where an extracted memory goes
+struct SaveTarget {
+    confidence: Confidence,
+    project_path: Option<String>,
+    session_id: Option<Uuid>,
+    turn_id: Option<Uuid>,
+    duplicate_check: DuplicateCheck,
+    lint: LintRules,
+    /// Recorded attempt
+    attempt: Uuid,
+}
+
+/// Outcome of saving one extraction
+enum Saved {
+    Memory(Uuid),
+    Duplicate,
+    Skipped(&'static str),
+    /// The backend's output could not be used
+    Failed(&'static str),
+}
+
+/// Parse backend output, save it as a project memory and record the outcome
+async fn save_extraction(pool: &PgPool, output: &str, target: SaveTarget) -> Result<Saved> {
+    let attempt = target.attempt;
+    let saved = save_parsed(pool, output, target).await?;
+    let (status, reason, memory_id) = match &saved {
+        Saved::Memory(id) => (ExtractionStatus::Saved, None, Some(*id)),
+        Saved::Duplicate => (ExtractionStatus::Skipped, Some("duplicate of an existing memory"), None),
+        Saved::Skipped(reason) => (ExtractionStatus::Skipped, Some(*reason), None),
+        Saved::Failed(reason) => (ExtractionStatus::Failed, Some(*reason), None),
+    };
+    queries::update_extraction(pool, attempt, status, reason, memory_id).await?;
+    Ok(saved)
+}
+
+async fn save_parsed(pool: &PgPool, output: &str, target: SaveTarget) -> Result<Saved> {
+    let Some(result) = parse_extraction_response(output) else {
+        return Ok(Saved::Failed("unparsable extraction output"));
+    };
+    let Ok(memory_type) = result.memory_type.parse::<MemoryType>() else {
+        return Ok(Saved::Failed("unknown memory type"));
+    };
+    queries::update_extraction(pool, target.attempt, ExtractionStatus::Parsed, None, None).await?;
+
+    let opts = AddMemoryOptions {
+        memory_type,
+        content: result.conclusion,
+        summary: None,
+        tags: crate::cli::parse_tags(&result.tags),
+        confidence: target.confidence,
+        tier: Tier::Project,
+        project_path: target.project_path,
+        source_session_id: target.session_id,
+        source_turn_id: target.turn_id,
+        supersedes: None,
+        constraints: Vec::new(),
+        duplicate_check: target.duplicate_check,
+        lint: target.lint,
+    };
+    Ok(match add_memory(pool, opts, None).await? {
+        AddMemoryResult::Success(json) => match json
+            .get("id")
+            .and_then(|id| id.as_str())
+            .and_then(|id| Uuid::parse_str(id).ok())
+        {
+            Some(id) => Saved::Memory(id),
+            None => Saved::Skipped("memory saved without an ID"),
+        },
+        AddMemoryResult::Duplicate(_) => Saved::Duplicate,
+        AddMemoryResult::Rejected(_) => Saved::Skipped("rejected by lint rules"),
+    })
+}
+
+/// Run the extraction backend, recording a failure against `attempt`
+async fn run_backend(pool: &PgPool, attempt: Uuid, prompt: &str) -> Result<String> {
+    let output = run_extraction(prompt);
+    if let Err(e) = &output {
+        let reason = e.to_string();
+        queries::update_extraction(pool, attempt, ExtractionStatus::Failed, Some(&reason), None).await?;
+    }
+    output
+}
+
+/// Run the extraction backend, asking once more with the repair prompt when
+/// its output is not a usable memory.
+///
+/// The retry spends another call from today's budget; over the cap, the
+/// first output is kept and fails to parse as before.
+async fn run_with_repair(
+    pool: &PgPool,
+    attempt: Uuid,
+    prompt: &str,
+    cap: Option<u32>,
+) -> Result<String> {
+    let output = run_backend(pool, attempt, prompt).await?;
+    if is_usable(&output) || queries::reserve_extraction(pool, cap).await?.is_none() {
+        return Ok(output);
+    }
+
+    tracing::info!("Extraction output was not usable JSON, retrying with the repair prompt");
+    queries::mark_extraction_retried(pool, attempt).await?;
+    tokio::time::sleep(REPAIR_BACKOFF).await;
+    run_backend(pool, attempt, &build_repair_prompt(&output)).await
+}
+
+/// Whether backend output parses into a memory of a known type
+fn is_usable(output: &str) -> bool {
+    parse_extraction_response(output).is_some_and(|r| r.memory_type.parse::<MemoryType>().is_ok())
+}
+
+fn skipped(turn_id: Uuid, reason: &str) -> QueuedExtraction {
+    QueuedExtraction {
+        turn_id,
+        memory_id: None,
+        duplicate: false,
+        skipped: Some(reason.to_string()),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve_result_shape() {
+        let result = ReserveExtractionResult {
+            reserved: false,
+            used_today: 20,
+            cap: Some(20),
+            queued: true,
+        };
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["reserved"], false);
+        assert_eq!(json["usedToday"], 20);
+        assert_eq!(json["queued"], true);
+
+        let unlimited = ReserveExtractionResult {
+            reserved: true,
+            used_today: 3,
+            cap: None,
+            queued: false,
+        };
+        assert!(serde_json::to_value(&unlimited).unwrap().get("cap").is_none());
+    }
+
+    #[test]
+    fn test_is_usable() {
+        assert!(is_usable(r#"{"type": "learning", "conclusion": "Use X", "tags": "x"}"#));
+        assert!(is_usable(r#"Here you go: {"type": "gotcha", "conclusion": "Y"}"#));
+        assert!(!is_usable("I could not find anything worth saving."));
+        assert!(!is_usable(r#"{"type": "trivia", "conclusion": "Z"}"#));
+    }
+}
@@ -0,0 +1,482 @@
+//! Fixture generation: generate-fixtures
+//!
+//! Fills the database with synthetic sessions and memories, to measure
+//! search and maintenance against realistic volumes and to work on `browse`
+//! without real data. Fixtures live in projects under `/fixtures`, are
+//! marked in their metadata (memories) or Claude session ID (sessions), and
+//! are removed again with `--clear`.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::db::{self, FixtureMemory, FixtureSession, FIXTURE_SESSION_PREFIX};
+use crate::error::{HippocampusError, Result};
+use crate::git::GitStatus;
+use crate::models::{Confidence, MemoryType};
+
+/// Directory the generated project paths live under
+const FIXTURE_ROOT: &str = "/fixtures";
+
+/// Share of memories that replace an earlier memory of their project
+const SUPERSEDE_RATE: f64 = 0.08;
+
+/// Share of memories flagged for review
+const REVIEW_RATE: f64 = 0.03;
+
+/// Share of memories attributed to a generated session
+const SOURCED_RATE: f64 = 0.7;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// What generate-fixtures creates
+#[derive(Debug, Clone)]
+pub struct FixtureOptions {
+    pub memories: usize,
+    pub sessions: usize,
+    pub projects: usize,
+    /// Days back the generated history reaches
+    pub days: i64,
+    /// Seed for the generated data; random when not given
+    pub seed: Option<u64>,
+    /// Delete earlier fixtures first
+    pub clear: bool,
+}
+
+/// Result of generate-fixtures
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateFixturesResult {
+    /// Seed that reproduces the data (IDs aside)
+    pub seed: u64,
+    pub projects: Vec<String>,
+    pub sessions: usize,
+    pub memories: usize,
+    /// Memories replaced by a later one, and so inactive
+    pub superseded: usize,
+    pub needs_review: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cleared: Option<ClearedFixtures>,
+}
+
+/// Earlier fixtures deleted by `--clear`
+#[derive(Debug, Serialize)]
+pub struct ClearedFixtures {
+    pub memories: i64,
+    pub sessions: i64,
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Generate sessions and memories spread over `options.projects` fixture
+/// projects and the last `options.days` days
+pub async fn generate_fixtures(pool: &PgPool, options: &FixtureOptions) -> Result<GenerateFixturesResult> {
+    if options.projects < 1 {
+        return Err(HippocampusError::Validation(
+            "Invalid projects: 0 (must be at least 1)".to_string(),
+        ));
+    }
+    if options.days < 1 {
+        return Err(HippocampusError::Validation(format!(
+            "Invalid days: {} (must be at least 1)",
+            options.days
+        )));
+    }
+
+    let cleared = if options.clear {
+        let (memories, sessions) = db::delete_fixtures(pool).await?;
+        Some(ClearedFixtures { memories, sessions })
+    } else {
+        None
+    };
+
+    let seed = options
+        .seed
+        .unwrap_or_else(|| Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64);
+    let projects: Vec<String> = (1..=options.projects)
+        .map(|n| format!("{}/project-{}", FIXTURE_ROOT, n))
+        .collect();
+    let mut rng = Rng(seed);
+    let now = Utc::now();
+    let sessions = generate_sessions(&mut rng, options.sessions, &projects, now, options.days);
+    let memories = generate_memories(&mut rng, options.memories, &projects, &sessions, now, options.days);
+
+    db::insert_fixtures(pool, &sessions, &memories).await?;
+
+    Ok(GenerateFixturesResult {
+        seed,
+        projects,
+        sessions: sessions.len(),
+        memories: memories.len(),
+        superseded: memories.iter().filter(|m| m.superseded_by.is_some()).count(),
+        needs_review: memories.iter().filter(|m| m.needs_review).count(),
+        cleared,
+    })
+}
+
+// ============================================================================
+// Generation
+// ============================================================================
+
+const MEMORY_TYPES: [MemoryType; 6] = [
+    MemoryType::Convention,
+    MemoryType::Architecture,
+    MemoryType::Gotcha,
+    MemoryType::Api,
+    MemoryType::Learning,
+    MemoryType::Preference,
+];
+
+const COMPONENTS: &[&str] = &[
+    "the connection pool",
+    "the migration runner",
+    "the session hook",
+    "the search index",
+    "the config loader",
+    "the HTTP client",
+    "the cache layer",
+    "the job queue",
+    "the auth middleware",
+    "the CSV importer",
+    "the websocket handler",
+    "the build script",
+];
+
+const CONDITIONS: &[&str] = &[
+    "the pool is exhausted",
+    "tests run in parallel",
+    "the system clock skews",
+    "the input file is empty",
+    "the network is slow",
+    "a migration is pending",
+    "the payload exceeds 1 MB",
+    "two requests race on the same row",
+];
+
+const PRACTICES: &[&str] = &[
+    "wrap errors with context",
+    "log the request id",
+    "use a transaction",
+    "batch the inserts",
+    "validate input at the boundary",
+    "set an explicit timeout",
+    "reuse the shared client",
+    "keep the lock scope small",
+];
+
+const IDENTS: &[&str] = &["fetch_all", "connect", "load", "flush", "retry", "parse", "spawn", "refresh"];
+
+const TAGS: &[&str] = &[
+    "postgres", "sqlx", "async", "testing", "config", "performance", "auth", "ci", "logging", "api",
+    "migration", "cache", "docker", "rust", "frontend",
+];
+
+const REVISIONS: &[&str] = &[
+    "since the 2.0 upgrade",
+    "except on Windows",
+    "unless the feature flag is off",
+    "as of the last refactor",
+    "now enforced by CI",
+];
+
+const BRANCHES: &[&str] = &["main", "feat/search", "fix/pool-timeout", "refactor/config", "chore/deps"];
+
+const FILES: &[&str] = &[
+    "src/main.rs",
+    "src/db/pool.rs",
+    "src/config.rs",
+    "src/api/handlers.rs",
+    "src/cache.rs",
+    "migrations/0004_jobs.sql",
+    "README.md",
+    "Cargo.toml",
+];
+
+/// Templates per memory type; placeholders are filled from the lists above
+fn templates(memory_type: MemoryType) -> &'static [&'static str] {
+    match memory_type {
+        MemoryType::Convention => &[
+            "Always {practice} in {component}",
+            "Prefer to {practice} when touching {component}",
+        ],
+        MemoryType::Architecture => &[
+            "{component} talks to {other} through a bounded channel",
+            "{component} owns all writes to the {topic} tables",
+        ],
+        MemoryType::Gotcha => &[
+            "{component} hangs when {condition}; {practice} to avoid it",
+            "Tests for {component} fail when {condition}",
+        ],
+        MemoryType::Api => &[
+            "{component} exposes `{ident}()`, which returns a Result and never panics",
+            "Call `{ident}()` on {component} before using {other}",
+        ],
+        MemoryType::Learning => &[
+            "Profiling showed {component} spends most of its time in {topic} code",
+            "{component} got faster once we started to {practice}",
+        ],
+        MemoryType::Preference => &[
+            "Keep {topic} changes small and reviewed on their own",
+            "Prefer {topic} fixes over workarounds in {component}",
+        ],
+    }
+}
+
+/// Small deterministic generator (SplitMix64), so a seed reproduces a dataset
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in [0, n); n must be positive
+    fn below(&mut self, n: usize) -> usize {
+        (self.unit() * n as f64) as usize
+    }
+
+    fn chance(&mut self, p: f64) -> bool {
+        self.unit() < p
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    /// A time between `from` and `to`
+    fn between(&mut self, from: DateTime<Utc>, to: DateTime<Utc>) -> DateTime<Utc> {
+        let span = (to - from).num_seconds().max(0);
+        from + Duration::seconds((self.unit() * span as f64) as i64)
+    }
+
+    /// A time in the last `days` days, more often recent than not
+    fn past(&mut self, now: DateTime<Utc>, days: i64) -> DateTime<Utc> {
+        let u = self.unit();
+        now - Duration::seconds((u * u * (days * 86_400) as f64) as i64)
+    }
+}
+
+fn generate_sessions(
+    rng: &mut Rng,
+    count: usize,
+    projects: &[String],
+    now: DateTime<Utc>,
+    days: i64,
+) -> Vec<FixtureSession> {
+    (0..count)
+        .map(|_| {
+            let started_at = rng.past(now, days);
+            let ended_at = (started_at + Duration::minutes(5 + rng.below(235) as i64)).min(now);
+            let git_status = GitStatus {
+                branch: rng.pick(BRANCHES).to_string(),
+                modified: (0..rng.below(4)).map(|_| rng.pick(FILES).to_string()).collect(),
+                untracked: Vec::new(),
+                staged: Vec::new(),
+            };
+            let summary = format!("Worked on {} around {}", rng.pick(TAGS), rng.pick(COMPONENTS));
+            FixtureSession {
+                id: Uuid::new_v4(),
+                claude_session_id: format!("{}{}", FIXTURE_SESSION_PREFIX, Uuid::new_v4()),
+                project_path: rng.pick(projects).clone(),
+                git_status: serde_json::to_value(git_status).unwrap_or_default(),
+                summary: serde_json::json!({ "summary": summary }),
+                started_at,
+                ended_at,
+            }
+        })
+        .collect()
+}
+
+/// Memories in creation order. About `SUPERSEDE_RATE` of them revise an
+/// earlier active memory of their project, which builds chains over time.
+fn generate_memories(
+    rng: &mut Rng,
+    count: usize,
+    projects: &[String],
+    sessions: &[FixtureSession],
+    now: DateTime<Utc>,
+    days: i64,
+) -> Vec<FixtureMemory> {
+    let mut memories: Vec<FixtureMemory> = Vec::with_capacity(count);
+    for _ in 0..count {
+        let predecessor = if !memories.is_empty() && rng.chance(SUPERSEDE_RATE) {
+            (0..5)
+                .map(|_| rng.below(memories.len()))
+                .find(|&i| memories[i].superseded_by.is_none())
+        } else {
+            None
+        };
+        let memory = match predecessor {
+            Some(i) => revise_memory(rng, &memories[i], now),
+            None => new_memory(rng, projects, sessions, now, days),
+        };
+        if let Some(i) = predecessor {
+            memories[i].superseded_by = Some(memory.id);
+            memories[i].superseded_at = Some(memory.created_at);
+        }
+        memories.push(memory);
+    }
+    memories
+}
+
+fn new_memory(
+    rng: &mut Rng,
+    projects: &[String],
+    sessions: &[FixtureSession],
+    now: DateTime<Utc>,
+    days: i64,
+) -> FixtureMemory {
+    let memory_type = *rng.pick(&MEMORY_TYPES);
+    let template = *rng.pick(templates(memory_type));
+    let content = template
+        .replace("{component}", rng.pick::<&str>(COMPONENTS))
+        .replace("{other}", rng.pick::<&str>(COMPONENTS))
+        .replace("{condition}", rng.pick::<&str>(CONDITIONS))
+        .replace("{practice}", rng.pick::<&str>(PRACTICES))
+        .replace("{ident}", rng.pick::<&str>(IDENTS))
+        .replace("{topic}", rng.pick::<&str>(TAGS));
+    let content = capitalize(&content);
+
+    let source = if !sessions.is_empty() && rng.chance(SOURCED_RATE) {
+        Some(rng.pick(sessions))
+    } else {
+        None
+    };
+    let (project_path, created_at) = match source {
+        Some(session) => (
+            session.project_path.clone(),
+            rng.between(session.started_at, session.ended_at),
+        ),
+        None => (rng.pick(projects).clone(), rng.past(now, days)),
+    };
+
+    let mut tags: Vec<String> = Vec::new();
+    for _ in 0..1 + rng.below(3) {
+        let tag = rng.pick(TAGS).to_string();
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    let confidence = match rng.unit() {
+        u if u < 0.3 => Confidence::High,
+        u if u < 0.75 => Confidence::Medium,
+        _ => Confidence::Low,
+    };
+
+    let mut memory = FixtureMemory {
+        id: Uuid::new_v4(),
+        memory_type,
+        project_path,
+        content,
+        tags,
+        confidence,
+        source_session_id: source.map(|s| s.id),
+        created_at,
+        updated_at: created_at,
+        accessed_at: None,
+        access_count: 0,
+        superseded_by: None,
+        superseded_at: None,
+        needs_review: rng.chance(REVIEW_RATE),
+    };
+    use_memory(rng, &mut memory, now);
+    memory
+}
+
+/// A later version of `previous`, in the same project
+fn revise_memory(rng: &mut Rng, previous: &FixtureMemory, now: DateTime<Utc>) -> FixtureMemory {
+    let base = previous.content.split(" (").next().unwrap_or(&previous.content);
+    let created_at = rng.between(previous.created_at, now);
+    let mut memory = FixtureMemory {
+        id: Uuid::new_v4(),
+        content: format!("{} ({})", base, rng.pick(REVISIONS)),
+        confidence: if rng.chance(0.5) { Confidence::High } else { Confidence::Medium },
+        source_session_id: None,
+        created_at,
+        updated_at: created_at,
+        accessed_at: None,
+        access_count: 0,
+        superseded_by: None,
+        superseded_at: None,
+        needs_review: false,
+        ..previous.clone()
+    };
+    use_memory(rng, &mut memory, now);
+    memory
+}
+
+/// Give a memory a skewed access history: many are never used, a few a lot
+fn use_memory(rng: &mut Rng, memory: &mut FixtureMemory, now: DateTime<Utc>) {
+    if rng.chance(0.2) {
+        memory.updated_at = rng.between(memory.created_at, now);
+    }
+    if rng.chance(0.6) {
+        memory.access_count = 1 + (rng.unit().powi(3) * 200.0) as i32;
+        memory.accessed_at = Some(rng.between(memory.created_at, now));
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn projects() -> Vec<String> {
+        vec!["/fixtures/project-1".to_string(), "/fixtures/project-2".to_string()]
+    }
+
+    #[test]
+    fn test_rng_is_reproducible() {
+        let mut a = Rng(42);
+        let mut b = Rng(42);
+        let draws: Vec<u64> = (0..5).map(|_| a.next_u64()).collect();
+        assert_eq!(draws, (0..5).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert!((0..1000).all(|_| a.below(7) < 7));
+    }
+
+    #[test]
+    fn test_generate_memories() {
+        let now = Utc::now();
+        let mut rng = Rng(7);
+        let sessions = generate_sessions(&mut rng, 20, &projects(), now, 90);
+        let memories = generate_memories(&mut rng, 500, &projects(), &sessions, now, 90);
+
+        assert_eq!(memories.len(), 500);
+        assert!(memories.iter().any(|m| m.superseded_by.is_some()));
+        assert!(memories.iter().any(|m| m.access_count == 0));
+        for memory in &memories {
+            assert!(projects().contains(&memory.project_path));
+            assert!(memory.created_at <= now && memory.updated_at >= memory.created_at);
+            if let Some(id) = memory.superseded_by {
+                let successor = memories.iter().find(|m| m.id == id).unwrap();
+                assert!(successor.created_at >= memory.created_at);
+                assert_eq!(successor.project_path, memory.project_path);
+            }
+        }
+    }
+}
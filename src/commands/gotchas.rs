@@ -0,0 +1,382 @@
+//! Mine-gotchas command: distill failure/fix sequences into gotcha memories
+//!
+//! Scans a session's recorded tool calls for attempts that failed and were
+//! later retried successfully (same tool, same command or file), then asks
+//! the extraction backend what the fix was. Corrections the agent discovers
+//! on its own are otherwise only captured if the user spells them out.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::commands::memory::{add_memory, AddMemoryOptions, AddMemoryResult};
+use crate::db::queries::{self, ToolCall};
+use crate::config::DuplicateCheck;
+use crate::error::{HippocampusError, Result};
+use crate::extraction::{parse_extraction_response, run_extraction};
+use crate::lint::LintRules;
+use crate::models::{Confidence, MemoryType, Tier};
+
+/// Markers of a failed tool call in its (lowercased) result summary
+const FAILURE_MARKERS: &[&str] = &[
+    "\"is_error\":true",
+    "\"iserror\":true",
+    "error:",
+    "error[e",
+    "panicked at",
+    "traceback (most recent call last)",
+    "command not found",
+    "no such file or directory",
+    "permission denied",
+    "test result: failed",
+    "build failed",
+    "compilation failed",
+];
+
+/// Characters of each tool call shown to the extraction backend
+const PROMPT_EXCERPT_CHARS: usize = 400;
+
+/// Failed attempts included in a prompt (the most recent ones)
+const MAX_PROMPT_FAILURES: usize = 3;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Failed attempts at something, followed by the call that succeeded
+#[derive(Debug, Clone)]
+pub struct FailureFix {
+    pub failures: Vec<ToolCall>,
+    pub fix: ToolCall,
+}
+
+/// A mined failure/fix sequence and what became of it
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GotchaCandidate {
+    pub tool_name: String,
+    /// What was being attempted (command or file)
+    pub target: String,
+    pub failed_attempts: usize,
+    /// The distilled gotcha, if the backend produced one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gotcha: Option<String>,
+    /// ID of the saved memory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_id: Option<Uuid>,
+    /// An equivalent memory already existed
+    pub duplicate: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of mine-gotchas command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MineGotchasResult {
+    pub session_id: Uuid,
+    pub tool_calls: usize,
+    pub candidates: Vec<GotchaCandidate>,
+    /// Number of new gotcha memories saved
+    pub saved: usize,
+}
+
+// ============================================================================
+// Sequence detection
+// ============================================================================
+
+/// Check whether a recorded tool call failed
+pub fn is_failed_call(call: &ToolCall) -> bool {
+    let Some(summary) = &call.result_summary else {
+        return false;
+    };
+    let summary = summary.to_lowercase().replace(": ", ":");
+    FAILURE_MARKERS.iter().any(|m| summary.contains(m)) || has_nonzero_exit(&summary)
+}
+
+/// Whether a (normalized) summary reports a nonzero shell exit code
+fn has_nonzero_exit(summary: &str) -> bool {
+    summary
+        .split("exit code:")
+        .skip(1)
+        .filter_map(|rest| rest.split(|c: char| !c.is_ascii_digit() && c != '-').next())
+        .filter_map(|code| code.parse::<i64>().ok())
+        .any(|code| code != 0)
+}
+
+/// What a tool call was attempting, used to pair failures with their fix.
+///
+/// Shell commands are keyed by their program (`cargo`, `npm`), file tools by path.
+pub fn call_target(call: &ToolCall) -> String {
+    let param = |key: &str| {
+        call.parameters
+            .as_ref()
+            .and_then(|p| p.get(key))
+            .and_then(|v| v.as_str())
+    };
+
+    if let Some(command) = param("command") {
+        let program = command
+            .split_whitespace()
+            .find(|w| !w.contains('=') && *w != "sudo")
+            .unwrap_or("");
+        return program.to_string();
+    }
+    param("file_path")
+        .or_else(|| param("path"))
+        .or_else(|| param("url"))
+        .unwrap_or("")
+        .to_string()
+}
+
+/// Pair runs of failed calls with the next successful call on the same target
+pub fn find_failure_fixes(calls: &[ToolCall]) -> Vec<FailureFix> {
+    let mut pending: HashMap<(String, String), Vec<ToolCall>> = HashMap::new();
+    let mut fixes = Vec::new();
+
+    for call in calls {
+        let key = (call.tool_name.clone(), call_target(call));
+        if is_failed_call(call) {
+            pending.entry(key).or_default().push(call.clone());
+        } else if let Some(failures) = pending.remove(&key) {
+            fixes.push(FailureFix {
+                failures,
+                fix: call.clone(),
+            });
+        }
+    }
+
+    fixes
+}
+
+fn excerpt(call: &ToolCall) -> String {
+    let params = call
+        .parameters
+        .as_ref()
+        .map(|p| p.to_string())
+        .unwrap_or_default();
+    let result = call.result_summary.as_deref().unwrap_or("");
+    format!(
+        "input: {}\nresult: {}",
+        params.chars().take(PROMPT_EXCERPT_CHARS).collect::<String>(),
+        result.chars().take(PROMPT_EXCERPT_CHARS).collect::<String>()
+    )
+}
+
+/// Build the prompt asking the backend to distill a fix into a gotcha
+fn build_gotcha_prompt(sequence: &FailureFix) -> String {
+    let skip = sequence.failures.len().saturating_sub(MAX_PROMPT_FAILURES);
+    let failures: Vec<String> = sequence.failures[skip..]
+        .iter()
+        .enumerate()
+        .map(|(i, call)| format!("FAILED ATTEMPT {}:\n{}", i + 1, excerpt(call)))
+        .collect();
+
+    format!(
+        r#"You are a memory extraction assistant. A coding agent ran the {tool} tool, failed, and then succeeded.
+
+{failures}
+
+SUCCESSFUL ATTEMPT:
+{fix}
+
+TASK: If the difference between the failed and successful attempts teaches something reusable about this project or its tools, state it as a gotcha. If the failure was a plain typo or transient (network, flaky test), there is no gotcha.
+
+Output JSON:
+{{"type": "gotcha", "conclusion": "<max 150 chars: what fails and what to do instead>", "tags": "<comma,separated>"}}
+or, if there is nothing worth remembering:
+{{"type": "none", "conclusion": "none"}}
+
+Output ONLY the JSON, nothing else."#,
+        tool = sequence.fix.tool_name,
+        failures = failures.join("\n\n"),
+        fix = excerpt(&sequence.fix),
+    )
+}
+
+// ============================================================================
+// Command
+// ============================================================================
+
+/// Mine a session's tool calls for gotchas.
+///
+/// `session` is a database session UUID or a Claude session ID. With
+/// `dry_run`, sequences are listed without calling the extraction backend.
+/// Each extraction spends one call from the `extraction_cap` daily budget.
+pub async fn mine_gotchas(
+    pool: &PgPool,
+    session: &str,
+    project_path: Option<&str>,
+    dry_run: bool,
+    extraction_cap: Option<u32>,
+    duplicate_check: DuplicateCheck,
+    lint: &LintRules,
+) -> Result<MineGotchasResult> {
+    let found = match Uuid::parse_str(session) {
+        Ok(id) => queries::find_session_by_id(pool, id).await?,
+        Err(_) => queries::find_session_by_claude_id(pool, session).await?,
+    };
+    let session = found.ok_or_else(|| HippocampusError::SessionNotFound(session.to_string()))?;
+    let project_path = session.project_path.clone().or(project_path.map(String::from));
+
+    let calls = queries::list_session_tool_calls(pool, session.id).await?;
+    let mut candidates = Vec::new();
+    let mut saved = 0;
+
+    for sequence in find_failure_fixes(&calls) {
+        let mut candidate = GotchaCandidate {
+            tool_name: sequence.fix.tool_name.clone(),
+            target: call_target(&sequence.fix),
+            failed_attempts: sequence.failures.len(),
+            gotcha: None,
+            memory_id: None,
+            duplicate: false,
+            error: None,
+        };
+
+        if !dry_run && queries::reserve_extraction(pool, extraction_cap).await?.is_none() {
+            candidate.error = Some("daily extraction cap reached".to_string());
+        } else if !dry_run {
+            let extracted = run_extraction(&build_gotcha_prompt(&sequence))
+                .map(|output| parse_extraction_response(&output));
+            match extracted {
+                Ok(Some(result)) if result.memory_type == MemoryType::Gotcha.as_str() => {
+                    let opts = AddMemoryOptions {
+                        memory_type: MemoryType::Gotcha,
+                        content: result.conclusion.clone(),
+                        summary: None,
+                        tags: crate::cli::parse_tags(&result.tags),
+                        confidence: Confidence::Medium,
+                        tier: Tier::Project,
+                        project_path: project_path.clone(),
+                        source_session_id: Some(session.id),
+                        source_turn_id: sequence.fix.turn_id,
+                        supersedes: None,
+                        constraints: Vec::new(),
+                        duplicate_check,
+                        lint: lint.clone(),
+                    };
+                    match add_memory(pool, opts, None).await? {
+                        AddMemoryResult::Success(json) => {
+                            candidate.memory_id = json
+                                .get("id")
+                                .and_then(|id| id.as_str())
+                                .and_then(|id| Uuid::parse_str(id).ok());
+                            saved += 1;
+                        }
+                        AddMemoryResult::Duplicate(_) => candidate.duplicate = true,
+                        AddMemoryResult::Rejected(json) => {
+                            candidate.error = json
+                                .get("error")
+                                .and_then(|e| e.as_str())
+                                .map(str::to_string);
+                        }
+                    }
+                    candidate.gotcha = Some(result.conclusion);
+                }
+                Ok(_) => {}
+                Err(e) => candidate.error = Some(e.to_string()),
+            }
+        }
+
+        candidates.push(candidate);
+    }
+
+    Ok(MineGotchasResult {
+        session_id: session.id,
+        tool_calls: calls.len(),
+        candidates,
+        saved,
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn call(tool: &str, params: serde_json::Value, result: &str) -> ToolCall {
+        ToolCall {
+            id: Uuid::new_v4(),
+            session_id: None,
+            turn_id: None,
+            tool_name: tool.to_string(),
+            parameters: Some(params),
+            result_summary: Some(result.to_string()),
+            called_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_is_failed_call() {
+        let failed = call("Bash", json!({"command": "cargo build"}), r#"{"stderr":"error[E0425]: cannot find value"}"#);
+        let ok = call("Bash", json!({"command": "cargo test"}), r#"{"stdout":"test result: ok. 3 passed; 0 failed"}"#);
+        let tool_error = call("Edit", json!({"file_path": "a.rs"}), r#"{"is_error": true}"#);
+        assert!(is_failed_call(&failed));
+        assert!(!is_failed_call(&ok));
+        assert!(is_failed_call(&tool_error));
+    }
+
+    #[test]
+    fn test_is_failed_call_exit_code() {
+        let failed = call("Bash", json!({"command": "make"}), "exit code: 2\nmake: nothing");
+        let ok = call("Bash", json!({"command": "make"}), "exit code: 0\nbuilt");
+        assert!(is_failed_call(&failed));
+        assert!(!is_failed_call(&ok));
+    }
+
+    #[test]
+    fn test_call_target() {
+        assert_eq!(call_target(&call("Bash", json!({"command": "RUST_LOG=debug cargo run"}), "")), "cargo");
+        assert_eq!(call_target(&call("Edit", json!({"file_path": "src/a.rs"}), "")), "src/a.rs");
+        assert_eq!(call_target(&call("Glob", json!({"pattern": "*.rs"}), "")), "");
+    }
+
+    #[test]
+    fn test_find_failure_fixes_pairs_by_target() {
+        let calls = vec![
+            call("Bash", json!({"command": "cargo build"}), "error: linker `cc` not found"),
+            call("Read", json!({"file_path": "Cargo.toml"}), "[package]"),
+            call("Bash", json!({"command": "npm test"}), "ok"),
+            call("Bash", json!({"command": "cargo build --offline"}), "error: no matching package"),
+            call("Bash", json!({"command": "cargo build --offline --locked"}), "Finished"),
+        ];
+        let fixes = find_failure_fixes(&calls);
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(fixes[0].failures.len(), 2);
+        assert_eq!(fixes[0].fix.id, calls[4].id);
+    }
+
+    #[test]
+    fn test_find_failure_fixes_ignores_unresolved_failures() {
+        let calls = vec![
+            call("Bash", json!({"command": "make"}), "make: *** error: missing target"),
+            call("Bash", json!({"command": "cargo build"}), "Finished"),
+        ];
+        assert!(find_failure_fixes(&calls).is_empty());
+    }
+
+    #[test]
+    fn test_build_gotcha_prompt_limits_failures() {
+        let failures: Vec<ToolCall> = (0..5)
+            .map(|i| call("Bash", json!({"command": format!("cargo build #{}", i)}), "error: boom"))
+            .collect();
+        let sequence = FailureFix {
+            failures,
+            fix: call("Bash", json!({"command": "cargo build --release"}), "Finished"),
+        };
+        let prompt = build_gotcha_prompt(&sequence);
+        assert!(prompt.contains("FAILED ATTEMPT 3"));
+        assert!(!prompt.contains("FAILED ATTEMPT 4"));
+        assert!(prompt.contains("cargo build #4"));
+        assert!(!prompt.contains("cargo build #1"));
+        assert!(prompt.contains("--release"));
+    }
+}
@@ -0,0 +1,243 @@
+//! Health-report command: catch memory pipeline regressions early
+//!
+//! Compares the last complete day's memory activity with a trailing baseline.
+//! A sudden drop in accesses usually means the hooks stopped loading context;
+//! a spike in creations usually means extraction is misfiring and saving
+//! noise. A high share of failed extraction attempts usually means the
+//! extraction backend is broken. Thresholds come from `anomalies` in
+//! hippocampus.toml.
+
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+
+use crate::config::AnomalyThresholds;
+use crate::db::queries;
+use crate::error::Result;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Kind of anomaly detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AnomalyKind {
+    AccessDrop,
+    CreationSpike,
+    ExtractionFailures,
+}
+
+/// A flagged anomaly
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthAlert {
+    pub kind: AnomalyKind,
+    pub message: String,
+}
+
+/// One metric's last day against its baseline
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityTrend {
+    /// Count on the last complete UTC day
+    pub last_day: i64,
+    /// Daily mean over the baseline days
+    pub baseline_mean: f64,
+    /// Daily counts, oldest first (baseline days, then the last day)
+    pub daily: Vec<i64>,
+}
+
+/// Extraction attempts on the last complete UTC day
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractionOutcomes {
+    pub attempts: i64,
+    pub failed: i64,
+    /// Share of attempts that failed (0.0 - 1.0)
+    pub failure_rate: f64,
+}
+
+/// Result of health-report command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    pub baseline_days: u32,
+    pub creations: ActivityTrend,
+    pub accesses: ActivityTrend,
+    pub extractions: ExtractionOutcomes,
+    pub alerts: Vec<HealthAlert>,
+    pub healthy: bool,
+}
+
+// ============================================================================
+// Command
+// ============================================================================
+
+/// Check recent memory activity against the configured thresholds.
+pub async fn health_report(pool: &PgPool, thresholds: &AnomalyThresholds) -> Result<HealthReport> {
+    let days = thresholds.baseline_days.max(1) as i32 + 1;
+    queries::rollup_memory_access(pool).await?;
+    let creations = queries::daily_memory_creations(pool, days).await?;
+    let accesses = queries::daily_memory_accesses(pool, days).await?;
+    let extractions = queries::extraction_failures_last_day(pool).await?;
+
+    Ok(build_report(thresholds, creations, accesses, extractions))
+}
+
+fn build_report(
+    thresholds: &AnomalyThresholds,
+    creations: Vec<i64>,
+    accesses: Vec<i64>,
+    (attempts, failed): (i64, i64),
+) -> HealthReport {
+    let creations = trend(creations);
+    let accesses = trend(accesses);
+    let extractions = ExtractionOutcomes {
+        attempts,
+        failed,
+        failure_rate: if attempts > 0 { failed as f64 / attempts as f64 } else { 0.0 },
+    };
+
+    let mut alerts = Vec::new();
+    if let Some(alert) = creation_spike(&creations, thresholds) {
+        alerts.push(alert);
+    }
+    if let Some(alert) = access_drop(&accesses, thresholds) {
+        alerts.push(alert);
+    }
+    if let Some(alert) = extraction_failures(&extractions, thresholds) {
+        alerts.push(alert);
+    }
+
+    HealthReport {
+        baseline_days: thresholds.baseline_days,
+        creations,
+        accesses,
+        extractions,
+        healthy: alerts.is_empty(),
+        alerts,
+    }
+}
+
+// ============================================================================
+// Detection
+// ============================================================================
+
+/// Split daily counts into the last day and the baseline mean before it
+fn trend(daily: Vec<i64>) -> ActivityTrend {
+    let (last_day, baseline) = match daily.split_last() {
+        Some((last, baseline)) => (*last, baseline),
+        None => (0, &[][..]),
+    };
+    let baseline_mean = if baseline.is_empty() {
+        0.0
+    } else {
+        baseline.iter().sum::<i64>() as f64 / baseline.len() as f64
+    };
+
+    ActivityTrend {
+        last_day,
+        baseline_mean,
+        daily,
+    }
+}
+
+fn creation_spike(trend: &ActivityTrend, thresholds: &AnomalyThresholds) -> Option<HealthAlert> {
+    if trend.baseline_mean < thresholds.min_baseline {
+        return None;
+    }
+    let limit = trend.baseline_mean * thresholds.creation_spike_ratio;
+    (trend.last_day as f64 > limit).then(|| HealthAlert {
+        kind: AnomalyKind::CreationSpike,
+        message: format!(
+            "{} memories created yesterday vs. {:.1}/day baseline; check extraction for noise",
+            trend.last_day, trend.baseline_mean
+        ),
+    })
+}
+
+fn access_drop(trend: &ActivityTrend, thresholds: &AnomalyThresholds) -> Option<HealthAlert> {
+    if trend.baseline_mean < thresholds.min_baseline {
+        return None;
+    }
+    let floor = trend.baseline_mean * thresholds.access_drop_ratio;
+    ((trend.last_day as f64) < floor).then(|| HealthAlert {
+        kind: AnomalyKind::AccessDrop,
+        message: format!(
+            "{} memory accesses yesterday vs. {:.1}/day baseline; check that hooks are running",
+            trend.last_day, trend.baseline_mean
+        ),
+    })
+}
+
+fn extraction_failures(outcomes: &ExtractionOutcomes, thresholds: &AnomalyThresholds) -> Option<HealthAlert> {
+    if outcomes.attempts < thresholds.min_extractions {
+        return None;
+    }
+    (outcomes.failure_rate > thresholds.extraction_failure_ratio).then(|| HealthAlert {
+        kind: AnomalyKind::ExtractionFailures,
+        message: format!(
+            "{} of {} extraction attempts failed yesterday; see extraction-log for reasons",
+            outcomes.failed, outcomes.attempts
+        ),
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trend_splits_last_day() {
+        let t = trend(vec![2, 4, 6, 1]);
+        assert_eq!(t.last_day, 1);
+        assert!((t.baseline_mean - 4.0).abs() < f64::EPSILON);
+
+        let empty = trend(Vec::new());
+        assert_eq!(empty.last_day, 0);
+        assert_eq!(empty.baseline_mean, 0.0);
+    }
+
+    #[test]
+    fn test_creation_spike_detected() {
+        let report = build_report(&AnomalyThresholds::default(), vec![3, 2, 4, 40], vec![0; 4], (0, 0));
+        assert!(!report.healthy);
+        assert_eq!(report.alerts.len(), 1);
+        assert_eq!(report.alerts[0].kind, AnomalyKind::CreationSpike);
+    }
+
+    #[test]
+    fn test_access_drop_detected() {
+        let report = build_report(
+            &AnomalyThresholds::default(),
+            vec![1, 1, 1, 1],
+            vec![30, 25, 35, 0],
+            (0, 0),
+        );
+        assert_eq!(report.alerts.len(), 1);
+        assert_eq!(report.alerts[0].kind, AnomalyKind::AccessDrop);
+    }
+
+    #[test]
+    fn test_sparse_baseline_never_alerts() {
+        let report = build_report(&AnomalyThresholds::default(), vec![0, 1, 0, 9], vec![1, 0, 1, 0], (0, 0));
+        assert!(report.healthy);
+    }
+
+    #[test]
+    fn test_extraction_failures_detected() {
+        let thresholds = AnomalyThresholds::default();
+        let report = build_report(&thresholds, vec![1, 1, 1, 1], vec![0; 4], (10, 6));
+        assert_eq!(report.alerts.len(), 1);
+        assert_eq!(report.alerts[0].kind, AnomalyKind::ExtractionFailures);
+        assert!((report.extractions.failure_rate - 0.6).abs() < f64::EPSILON);
+
+        // Too few attempts to judge
+        let report = build_report(&thresholds, vec![1, 1, 1, 1], vec![0; 4], (2, 2));
+        assert!(report.healthy);
+    }
+}
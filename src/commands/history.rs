@@ -0,0 +1,186 @@
+//! History and diff commands: how a memory's content changed
+//!
+//! Every content edit by update-memory or edit-memory keeps the replaced
+//! content as a revision. Revisions are numbered from 1, the content the
+//! memory was added with; the current content is the highest number.
+//! Supersession links separate memories and is shown by show-chain instead.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::db::{self, MemoryRevision};
+use crate::error::{HippocampusError, Result};
+use crate::models::{ContentDiff, Memory};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// One content of a memory and when it was current
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevisionEntry {
+    pub revision: i32,
+    pub content: String,
+    pub from: DateTime<Utc>,
+    /// None for the current content
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// Result of history command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryResult {
+    pub memory_id: Uuid,
+    pub current_revision: i32,
+    /// Oldest first, ending with the current content
+    pub revisions: Vec<RevisionEntry>,
+}
+
+/// Result of diff command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevisionDiffResult {
+    pub memory_id: Uuid,
+    pub from_revision: i32,
+    pub to_revision: i32,
+    /// When the edit was made
+    pub edited_at: DateTime<Utc>,
+    pub from: String,
+    pub to: String,
+    pub diff: ContentDiff,
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// List every content a memory has had, oldest first.
+pub async fn history(pool: &PgPool, id: Uuid) -> Result<HistoryResult> {
+    let memory = db::get_memory(pool, id)
+        .await?
+        .ok_or_else(|| HippocampusError::NotFound(id.to_string()))?;
+    let revisions = timeline(&memory, db::list_memory_revisions(pool, id).await?);
+    Ok(HistoryResult {
+        memory_id: id,
+        current_revision: revisions.len() as i32,
+        revisions,
+    })
+}
+
+/// Show the edit that produced revision `rev` (by default the current
+/// content) as a diff from the revision before it.
+pub async fn diff(pool: &PgPool, id: Uuid, rev: Option<i32>) -> Result<RevisionDiffResult> {
+    let result = history(pool, id).await?;
+    let current = result.current_revision;
+    let to_revision = rev.unwrap_or(current);
+    if to_revision < 1 || to_revision > current {
+        return Err(HippocampusError::Validation(format!(
+            "Invalid --rev: {} (memory {} has revisions 1 to {})",
+            to_revision, id, current
+        )));
+    }
+    if to_revision == 1 {
+        let reason = if current == 1 {
+            format!("memory {} has never been edited", id)
+        } else {
+            format!("revision 1 of memory {} is the content it was added with", id)
+        };
+        return Err(HippocampusError::Validation(format!("Nothing to diff: {}", reason)));
+    }
+
+    let from = &result.revisions[(to_revision - 2) as usize];
+    let to = &result.revisions[(to_revision - 1) as usize];
+    Ok(RevisionDiffResult {
+        memory_id: id,
+        from_revision: from.revision,
+        to_revision: to.revision,
+        edited_at: to.from,
+        diff: ContentDiff::between(&from.content, &to.content),
+        from: from.content.clone(),
+        to: to.content.clone(),
+    })
+}
+
+/// The stored revisions followed by the current content, each current from
+/// the edit that replaced the one before it
+fn timeline(memory: &Memory, revisions: Vec<MemoryRevision>) -> Vec<RevisionEntry> {
+    let mut entries = Vec::with_capacity(revisions.len() + 1);
+    let mut from = memory.created_at;
+    for revision in revisions {
+        entries.push(RevisionEntry {
+            revision: revision.revision,
+            content: revision.content,
+            from,
+            until: Some(revision.replaced_at),
+        });
+        from = revision.replaced_at;
+    }
+    entries.push(RevisionEntry {
+        revision: entries.len() as i32 + 1,
+        content: memory.content.clone(),
+        from,
+        until: None,
+    });
+    entries
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_memory, MemoryType};
+    use chrono::TimeZone;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, hour, 0, 0).unwrap()
+    }
+
+    fn memory(content: &str) -> Memory {
+        Memory {
+            created_at: at(8),
+            updated_at: at(8),
+            ..test_memory(content, MemoryType::Gotcha)
+        }
+    }
+
+    #[test]
+    fn test_timeline_ends_with_current_content() {
+        let memory = memory("Use gsed on macOS");
+        let revisions = vec![
+            MemoryRevision {
+                revision: 1,
+                content: "Use sed".to_string(),
+                replaced_at: at(9),
+            },
+            MemoryRevision {
+                revision: 2,
+                content: "Use gsed".to_string(),
+                replaced_at: at(10),
+            },
+        ];
+
+        let entries = timeline(&memory, revisions);
+        assert_eq!(entries.len(), 3);
+        assert_eq!((entries[0].from, entries[0].until), (at(8), Some(at(9))));
+        assert_eq!((entries[1].from, entries[1].until), (at(9), Some(at(10))));
+        assert_eq!(entries[2].revision, 3);
+        assert_eq!(entries[2].content, "Use gsed on macOS");
+        assert_eq!((entries[2].from, entries[2].until), (at(10), None));
+    }
+
+    #[test]
+    fn test_timeline_without_edits() {
+        let memory = memory("Use sed");
+        let entries = timeline(&memory, Vec::new());
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].revision, 1);
+        assert_eq!(entries[0].from, at(8));
+    }
+}
@@ -0,0 +1,367 @@
+//! Install command: write the hook entries into Claude Code's settings.json
+//!
+//! Entries are recognised by their command, `<path>/claude-hippocampus hook
+//! <name>`, so installing again replaces entries for an older binary path
+//! instead of adding a second set, and uninstalling leaves every other hook
+//! alone.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::cli::HookType;
+use crate::error::{HippocampusError, Result};
+
+/// File name of the binary, used to recognise our hook commands
+const BINARY_NAME: &str = "claude-hippocampus";
+
+/// Result of install-hooks
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallHooksResult {
+    pub settings_path: String,
+    /// Binary the hooks run (None when uninstalling)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub binary: Option<String>,
+    /// Hook events now pointing at `binary`
+    pub installed: Vec<String>,
+    /// Existing claude-hippocampus hook entries removed or replaced
+    pub removed: usize,
+}
+
+/// Add the hooks to the user's settings.json, or the project's with
+/// `project`; with `uninstall`, remove them instead.
+///
+/// `binary` defaults to the running executable.
+pub fn install_hooks(
+    project: bool,
+    project_path: Option<&str>,
+    binary: Option<&str>,
+    uninstall: bool,
+) -> Result<InstallHooksResult> {
+    let path = if project {
+        let project_path = project_path
+            .ok_or_else(|| HippocampusError::Validation("No project path given".to_string()))?;
+        project_settings_path(project_path)
+    } else {
+        user_settings_path()
+    };
+
+    let mut settings = read_settings(&path)?;
+    let removed = remove_hooks(&mut settings)?;
+
+    let (binary, installed) = if uninstall {
+        (None, Vec::new())
+    } else {
+        let binary = match binary {
+            Some(binary) => binary.to_string(),
+            None => std::env::current_exe()?.display().to_string(),
+        };
+        let installed = add_hooks(&mut settings, &binary)?;
+        (Some(binary), installed)
+    };
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, format!("{}\n", serde_json::to_string_pretty(&settings)?))?;
+
+    Ok(InstallHooksResult {
+        settings_path: path.display().to_string(),
+        binary,
+        installed,
+        removed,
+    })
+}
+
+/// The user's settings.json
+pub(crate) fn user_settings_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".claude")
+        .join("settings.json")
+}
+
+/// A project's settings.json
+pub(crate) fn project_settings_path(project_path: &str) -> PathBuf {
+    Path::new(project_path).join(".claude").join("settings.json")
+}
+
+/// Claude Code event name for a hook
+pub(crate) fn event_name(hook: &HookType) -> &'static str {
+    match hook {
+        HookType::SessionStart => "SessionStart",
+        HookType::UserPromptSubmit => "UserPromptSubmit",
+        HookType::Stop => "Stop",
+        HookType::SubagentStop => "SubagentStop",
+        HookType::Notification => "Notification",
+        HookType::SessionEnd => "SessionEnd",
+        HookType::PostToolUse => "PostToolUse",
+    }
+}
+
+/// Settings as a JSON object; an empty one when the file does not exist
+pub(crate) fn read_settings(path: &Path) -> Result<Value> {
+    if !path.exists() {
+        return Ok(json!({}));
+    }
+    let content = fs::read_to_string(path)?;
+    if content.trim().is_empty() {
+        return Ok(json!({}));
+    }
+    let settings: Value = serde_json::from_str(&content).map_err(|e| {
+        HippocampusError::Config(format!("Failed to parse {}: {}", path.display(), e))
+    })?;
+    if !settings.is_object() {
+        return Err(HippocampusError::Config(format!(
+            "{} is not a JSON object",
+            path.display()
+        )));
+    }
+    Ok(settings)
+}
+
+/// The `hooks` object of `settings`, created when missing
+fn hooks_object(settings: &mut Value) -> Result<&mut Map<String, Value>> {
+    settings
+        .as_object_mut()
+        .and_then(|s| s.entry("hooks").or_insert_with(|| json!({})).as_object_mut())
+        .ok_or_else(|| HippocampusError::Config("\"hooks\" in settings is not an object".to_string()))
+}
+
+/// Append a matcher group running `binary hook <name>` for every hook event
+fn add_hooks(settings: &mut Value, binary: &str) -> Result<Vec<String>> {
+    let program = if binary.contains(' ') {
+        format!("\"{}\"", binary)
+    } else {
+        binary.to_string()
+    };
+    let hooks = hooks_object(settings)?;
+
+    let mut installed = Vec::new();
+    for hook in HookType::value_variants() {
+        let event = event_name(hook);
+        let group = json!({
+            "hooks": [{
+                "type": "command",
+                "command": format!("{} hook {}", program, hook.as_str()),
+            }]
+        });
+        match hooks.entry(event).or_insert_with(|| json!([])) {
+            Value::Array(groups) => groups.push(group),
+            _ => {
+                return Err(HippocampusError::Config(format!(
+                    "hooks.{} in settings is not an array",
+                    event
+                )))
+            }
+        }
+        installed.push(event.to_string());
+    }
+    Ok(installed)
+}
+
+/// Remove every claude-hippocampus hook entry, dropping groups and events
+/// left empty. Returns how many entries were removed.
+fn remove_hooks(settings: &mut Value) -> Result<usize> {
+    let hooks = hooks_object(settings)?;
+
+    let mut removed = 0;
+    for groups in hooks.values_mut() {
+        let Value::Array(groups) = groups else {
+            continue;
+        };
+        groups.retain_mut(|group| {
+            // Entries written directly in the event array, without a matcher group
+            if is_our_entry(group) {
+                removed += 1;
+                return false;
+            }
+            let Some(Value::Array(entries)) = group.get_mut("hooks") else {
+                return true;
+            };
+            let before = entries.len();
+            entries.retain(|entry| !is_our_entry(entry));
+            removed += before - entries.len();
+            !(entries.is_empty() && before > 0)
+        });
+    }
+    hooks.retain(|_, groups| !groups.as_array().is_some_and(|g| g.is_empty()));
+
+    if hooks.is_empty() {
+        if let Some(settings) = settings.as_object_mut() {
+            settings.remove("hooks");
+        }
+    }
+    Ok(removed)
+}
+
+/// Our hook entries in `settings` as (event, program) pairs
+pub(crate) fn registered_hooks(settings: &Value) -> Vec<(String, String)> {
+    let Some(hooks) = settings.get("hooks").and_then(|h| h.as_object()) else {
+        return Vec::new();
+    };
+    let mut registered = Vec::new();
+    for (event, groups) in hooks {
+        for group in groups.as_array().into_iter().flatten() {
+            let entries = match group.get("hooks").and_then(|h| h.as_array()) {
+                Some(entries) => entries.iter().collect(),
+                None => vec![group],
+            };
+            for entry in entries {
+                let command = entry.get("command").and_then(|c| c.as_str());
+                if let Some(program) = command.and_then(hook_program) {
+                    registered.push((event.clone(), program.to_string()));
+                }
+            }
+        }
+    }
+    registered
+}
+
+/// A hook entry whose command runs `claude-hippocampus hook ...`
+fn is_our_entry(entry: &Value) -> bool {
+    entry
+        .get("command")
+        .and_then(|c| c.as_str())
+        .is_some_and(is_our_command)
+}
+
+fn is_our_command(command: &str) -> bool {
+    hook_program(command).is_some()
+}
+
+/// The program of a `claude-hippocampus hook ...` command; None for others
+fn hook_program(command: &str) -> Option<&str> {
+    let command = command.trim_start();
+    let (program, rest) = match command.strip_prefix('"') {
+        Some(quoted) => quoted.split_once('"')?,
+        None => command.split_once(' ').unwrap_or((command, "")),
+    };
+    let file_name = Path::new(program)
+        .file_stem()
+        .and_then(|name| name.to_str());
+    (file_name == Some(BINARY_NAME) && rest.split_whitespace().next() == Some("hook"))
+        .then_some(program)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commands(settings: &Value, event: &str) -> Vec<String> {
+        settings["hooks"][event]
+            .as_array()
+            .map(|groups| {
+                groups
+                    .iter()
+                    .flat_map(|g| g["hooks"].as_array().cloned().unwrap_or_default())
+                    .filter_map(|e| e["command"].as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_is_our_command() {
+        assert!(is_our_command("~/.claude/bin/claude-hippocampus hook stop"));
+        assert!(is_our_command("\"/Applications/My Tools/claude-hippocampus\" hook stop"));
+        assert!(is_our_command("claude-hippocampus.exe hook stop"));
+        assert!(!is_our_command("claude-hippocampus stats"));
+        assert!(!is_our_command("~/bin/other-tool hook stop"));
+    }
+
+    #[test]
+    fn test_install_replaces_old_entries_and_keeps_others() {
+        let mut settings = json!({
+            "model": "sonnet",
+            "hooks": {
+                "Stop": [
+                    {"hooks": [{"type": "command", "command": "/old/claude-hippocampus hook stop"}]},
+                    {"hooks": [{"type": "command", "command": "notify-send done"}]}
+                ],
+                "SessionStart": [
+                    {"type": "command", "command": "/old/claude-hippocampus hook session-start"}
+                ]
+            }
+        });
+
+        assert_eq!(remove_hooks(&mut settings).unwrap(), 2);
+        let installed = add_hooks(&mut settings, "/new/claude-hippocampus").unwrap();
+        assert_eq!(installed.len(), HookType::value_variants().len());
+
+        assert_eq!(settings["model"], "sonnet");
+        assert_eq!(
+            commands(&settings, "Stop"),
+            vec!["notify-send done", "/new/claude-hippocampus hook stop"]
+        );
+        assert_eq!(
+            commands(&settings, "PostToolUse"),
+            vec!["/new/claude-hippocampus hook post-tool-use"]
+        );
+    }
+
+    #[test]
+    fn test_uninstall_removes_only_our_hooks() {
+        let mut settings = json!({"hooks": {
+            "Stop": [{"hooks": [{"type": "command", "command": "notify-send done"}]}]
+        }});
+        add_hooks(&mut settings, "/usr/local/bin/claude-hippocampus").unwrap();
+
+        assert_eq!(remove_hooks(&mut settings).unwrap(), HookType::value_variants().len());
+        assert_eq!(settings, json!({"hooks": {
+            "Stop": [{"hooks": [{"type": "command", "command": "notify-send done"}]}]
+        }}));
+
+        let mut settings = json!({});
+        add_hooks(&mut settings, "claude-hippocampus").unwrap();
+        remove_hooks(&mut settings).unwrap();
+        assert_eq!(settings, json!({}));
+    }
+
+    #[test]
+    fn test_registered_hooks() {
+        let mut settings = json!({"hooks": {
+            "Stop": [{"type": "command", "command": "/old/claude-hippocampus hook stop"}],
+            "Notification": [{"hooks": [{"type": "command", "command": "notify-send hi"}]}]
+        }});
+        assert_eq!(
+            registered_hooks(&settings),
+            vec![("Stop".to_string(), "/old/claude-hippocampus".to_string())]
+        );
+
+        remove_hooks(&mut settings).unwrap();
+        add_hooks(&mut settings, "/opt/my tools/claude-hippocampus").unwrap();
+        let registered = registered_hooks(&settings);
+        assert_eq!(registered.len(), HookType::value_variants().len());
+        assert!(registered.iter().all(|(_, p)| p == "/opt/my tools/claude-hippocampus"));
+    }
+
+    #[test]
+    fn test_binary_with_spaces_is_quoted() {
+        let mut settings = json!({});
+        add_hooks(&mut settings, "/opt/my tools/claude-hippocampus").unwrap();
+        let stop = commands(&settings, "Stop");
+        assert_eq!(stop, vec!["\"/opt/my tools/claude-hippocampus\" hook stop"]);
+        assert!(is_our_command(&stop[0]));
+    }
+
+    #[test]
+    fn test_read_settings_rejects_non_objects() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        assert_eq!(read_settings(&path).unwrap(), json!({}));
+
+        fs::write(&path, "[1, 2]").unwrap();
+        assert!(read_settings(&path).is_err());
+        fs::write(&path, "{not json").unwrap();
+        assert!(read_settings(&path).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! Turn-latency command: where a turn's wall-clock time goes
+//!
+//! Combines the recorded hook run times with turn durations and tool call
+//! counts, so hook overhead can be told apart from model and tool time.
+//! Turn duration runs from UserPromptSubmit to Stop; whatever the hooks do
+//! not account for is the model thinking and the tools running.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::db::queries::{self, HookTiming};
+use crate::error::{HippocampusError, Result};
+use crate::models::Turn;
+
+/// Time spent in one turn
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnLatency {
+    pub turn_number: i32,
+    /// None while the turn is in progress
+    pub duration_ms: Option<i64>,
+    pub hook_ms: i64,
+    pub hook_runs: usize,
+    pub tool_calls: i64,
+    /// Duration minus hook time: the model and the tools
+    pub other_ms: Option<i64>,
+}
+
+/// Cost of one hook across the session
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookCost {
+    pub hook: String,
+    pub runs: usize,
+    pub failures: usize,
+    pub total_ms: i64,
+    pub avg_ms: i64,
+    pub max_ms: i64,
+}
+
+/// Latency budget of a session
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnLatencyReport {
+    pub session_id: Uuid,
+    pub turns: Vec<TurnLatency>,
+    /// Most expensive hook first
+    pub hooks: Vec<HookCost>,
+    /// Duration of all completed turns
+    pub total_turn_ms: i64,
+    /// Hook time within completed turns
+    pub turn_hook_ms: i64,
+    /// Share of completed turn time spent in hooks (0.0 - 1.0)
+    pub hook_share: Option<f64>,
+}
+
+/// Report where a session's turn time went.
+///
+/// `session` is a database session UUID or a Claude session ID.
+pub async fn turn_latency(pool: &PgPool, session: &str) -> Result<TurnLatencyReport> {
+    let found = match Uuid::parse_str(session) {
+        Ok(id) => queries::find_session_by_id(pool, id).await?,
+        Err(_) => queries::find_session_by_claude_id(pool, session).await?,
+    };
+    let session = found.ok_or_else(|| HippocampusError::SessionNotFound(session.to_string()))?;
+
+    let turns = queries::list_session_turns(pool, session.id).await?;
+    let tool_calls: HashMap<Uuid, i64> = queries::count_tool_calls_by_turn(pool, session.id)
+        .await?
+        .into_iter()
+        .collect();
+    let timings = queries::list_hook_timings(pool, session.id).await?;
+
+    Ok(build_report(session.id, &turns, &tool_calls, &timings))
+}
+
+fn build_report(
+    session_id: Uuid,
+    turns: &[Turn],
+    tool_calls: &HashMap<Uuid, i64>,
+    timings: &[HookTiming],
+) -> TurnLatencyReport {
+    let turns: Vec<TurnLatency> = turns
+        .iter()
+        .map(|turn| {
+            let runs: Vec<&HookTiming> =
+                timings.iter().filter(|t| t.turn_id == Some(turn.id)).collect();
+            let hook_ms: i64 = runs.iter().map(|t| t.duration_ms as i64).sum();
+            let duration_ms = turn
+                .ended_at
+                .map(|end| (end - turn.started_at).num_milliseconds().max(0));
+            TurnLatency {
+                turn_number: turn.turn_number,
+                duration_ms,
+                hook_ms,
+                hook_runs: runs.len(),
+                tool_calls: tool_calls.get(&turn.id).copied().unwrap_or(0),
+                other_ms: duration_ms.map(|d| (d - hook_ms).max(0)),
+            }
+        })
+        .collect();
+
+    let completed = turns.iter().filter(|t| t.duration_ms.is_some());
+    let total_turn_ms: i64 = completed.clone().filter_map(|t| t.duration_ms).sum();
+    let turn_hook_ms: i64 = completed.map(|t| t.hook_ms).sum();
+    let hook_share = (total_turn_ms > 0)
+        .then(|| (turn_hook_ms as f64 / total_turn_ms as f64).min(1.0));
+
+    TurnLatencyReport {
+        session_id,
+        turns,
+        hooks: hook_costs(timings),
+        total_turn_ms,
+        turn_hook_ms,
+        hook_share,
+    }
+}
+
+fn hook_costs(timings: &[HookTiming]) -> Vec<HookCost> {
+    let mut by_hook: HashMap<&str, Vec<&HookTiming>> = HashMap::new();
+    for timing in timings {
+        by_hook.entry(&timing.hook).or_default().push(timing);
+    }
+
+    let mut costs: Vec<HookCost> = by_hook
+        .into_iter()
+        .map(|(hook, runs)| {
+            let total_ms: i64 = runs.iter().map(|t| t.duration_ms as i64).sum();
+            HookCost {
+                hook: hook.to_string(),
+                runs: runs.len(),
+                failures: runs.iter().filter(|t| !t.success).count(),
+                total_ms,
+                avg_ms: total_ms / runs.len() as i64,
+                max_ms: runs.iter().map(|t| t.duration_ms as i64).max().unwrap_or(0),
+            }
+        })
+        .collect();
+    costs.sort_by(|a, b| b.total_ms.cmp(&a.total_ms).then_with(|| a.hook.cmp(&b.hook)));
+    costs
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn turn(number: i32, duration_ms: Option<i64>) -> Turn {
+        let started_at = Utc::now();
+        Turn {
+            id: Uuid::new_v4(),
+            session_id: None,
+            turn_number: number,
+            user_prompt: format!("prompt {}", number),
+            assistant_response: None,
+            model_used: None,
+            input_tokens: None,
+            output_tokens: None,
+            started_at,
+            ended_at: duration_ms.map(|ms| started_at + Duration::milliseconds(ms)),
+            created_at: started_at,
+        }
+    }
+
+    fn timing(turn: Option<&Turn>, hook: &str, duration_ms: i32, success: bool) -> HookTiming {
+        HookTiming {
+            turn_id: turn.map(|t| t.id),
+            hook: hook.to_string(),
+            duration_ms,
+            success,
+        }
+    }
+
+    #[test]
+    fn test_report_splits_turn_time() {
+        let turns = vec![turn(1, Some(10_000)), turn(2, None)];
+        let tool_calls = HashMap::from([(turns[0].id, 4)]);
+        let timings = vec![
+            timing(None, "session-start", 300, true),
+            timing(Some(&turns[0]), "user-prompt-submit", 200, true),
+            timing(Some(&turns[0]), "post-tool-use", 50, true),
+            timing(Some(&turns[0]), "stop", 750, true),
+            timing(Some(&turns[1]), "user-prompt-submit", 100, false),
+        ];
+
+        let report = build_report(Uuid::nil(), &turns, &tool_calls, &timings);
+        assert_eq!(
+            report.turns[0],
+            TurnLatency {
+                turn_number: 1,
+                duration_ms: Some(10_000),
+                hook_ms: 1_000,
+                hook_runs: 3,
+                tool_calls: 4,
+                other_ms: Some(9_000),
+            }
+        );
+        // The open turn has hook time but no duration, and stays out of the share
+        assert_eq!(report.turns[1].duration_ms, None);
+        assert_eq!(report.turns[1].hook_ms, 100);
+        assert_eq!(report.total_turn_ms, 10_000);
+        assert_eq!(report.turn_hook_ms, 1_000);
+        assert_eq!(report.hook_share, Some(0.1));
+    }
+
+    #[test]
+    fn test_hook_costs_sorted_by_total() {
+        let timings = vec![
+            timing(None, "post-tool-use", 40, true),
+            timing(None, "stop", 500, true),
+            timing(None, "post-tool-use", 80, false),
+        ];
+
+        let costs = hook_costs(&timings);
+        assert_eq!(costs[0].hook, "stop");
+        assert_eq!(
+            costs[1],
+            HookCost {
+                hook: "post-tool-use".to_string(),
+                runs: 2,
+                failures: 1,
+                total_ms: 120,
+                avg_ms: 60,
+                max_ms: 80,
+            }
+        );
+    }
+
+    #[test]
+    fn test_report_without_hook_runs() {
+        let turns = vec![turn(1, Some(2_000))];
+        let report = build_report(Uuid::nil(), &turns, &HashMap::new(), &[]);
+        assert!(report.hooks.is_empty());
+        assert_eq!(report.turns[0].other_ms, Some(2_000));
+        assert_eq!(report.hook_share, Some(0.0));
+    }
+}
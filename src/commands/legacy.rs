@@ -0,0 +1,562 @@
+//! Legacy import command: migrate-legacy
+//!
+//! Copies memories from a database written by the original Node.js
+//! `memory.js` into the current schema. Older installs named some columns
+//! differently, used other type and scope names, and stored tags as
+//! comma-separated or JSON text, so each row is read as JSON and mapped
+//! column by column. The source is only read. Imports keep their IDs and
+//! timestamps and skip memories already present, so the command can be
+//! re-run; the report reads every import back to verify it. Rows are
+//! written by `--jobs` workers at a time.
+
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::commands::tags::dedup_ignoring_case;
+use crate::db::queries::{self, ImportedMemory};
+use crate::error::{HippocampusError, Result};
+use crate::models::{Confidence, Memory, MemoryType, Scope};
+use crate::util::workers::map_ordered;
+
+/// Legacy column names accepted for each current column, in order of preference
+const COLUMN_ALIASES: &[(&str, &[&str])] = &[
+    ("id", &["id"]),
+    ("type", &["type", "memory_type", "kind", "category"]),
+    ("scope", &["scope", "tier"]),
+    ("project_path", &["project_path", "projectPath", "project"]),
+    ("content", &["content", "text", "body"]),
+    ("tags", &["tags", "keywords"]),
+    ("confidence", &["confidence"]),
+    ("created_at", &["created_at", "createdAt", "created", "timestamp"]),
+    ("updated_at", &["updated_at", "updatedAt", "updated"]),
+    ("accessed_at", &["accessed_at", "accessedAt", "last_accessed"]),
+    ("access_count", &["access_count", "accessCount"]),
+];
+
+/// Outcome of a legacy import
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LegacyMigrationReport {
+    pub source_table: String,
+    /// Nothing was written; counts are what an import would do
+    pub dry_run: bool,
+    /// Current column each legacy column was read into
+    pub columns: BTreeMap<String, String>,
+    /// Legacy columns with no current counterpart
+    pub ignored_columns: Vec<String>,
+    pub read: usize,
+    pub imported: usize,
+    /// Already present, by ID or identical content
+    pub skipped: usize,
+    pub rejected: Vec<RejectedRow>,
+    /// Values rewritten on the way in
+    pub mappings: Vec<ValueMapping>,
+    pub verification: Verification,
+}
+
+/// A legacy row that could not be mapped
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectedRow {
+    pub legacy_id: Option<String>,
+    pub reason: String,
+}
+
+/// A legacy value and how many rows it was rewritten for
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ValueMapping {
+    pub field: String,
+    pub from: String,
+    pub to: String,
+    pub count: usize,
+}
+
+/// Imported memories read back from the target
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Verification {
+    pub found: usize,
+    /// Imports whose stored type, scope, content or tags differ from the mapping
+    pub mismatched: Vec<Uuid>,
+    /// Every row is accounted for and every import reads back unchanged
+    pub ok: bool,
+}
+
+/// Rewritten values, counted by (field, from, to)
+type Mappings = BTreeMap<(&'static str, String, String), usize>;
+
+/// Import the memories in `table` of the `source` database into `target`,
+/// `jobs` rows at a time
+pub async fn migrate_legacy(
+    source: &PgPool,
+    target: &PgPool,
+    table: &str,
+    dry_run: bool,
+    jobs: usize,
+) -> Result<LegacyMigrationReport> {
+    check_table_name(table)?;
+    let columns = queries::list_table_columns(source, table).await?;
+    if columns.is_empty() {
+        return Err(HippocampusError::Validation(format!("Legacy table not found: {}", table)));
+    }
+    let (columns, ignored_columns) = map_columns(&columns);
+    if !columns.contains_key("content") {
+        return Err(HippocampusError::Validation(format!(
+            "Legacy table {} has no content column (content, text or body)",
+            table
+        )));
+    }
+
+    let rows = queries::fetch_rows_as_json(source, table).await?;
+    let mut mappings = Mappings::new();
+    let mut mapped = Vec::new();
+    let mut seen = Seen::default();
+    let mut skipped = 0;
+    let mut rejected = Vec::new();
+
+    for row in &rows {
+        match map_row(row, &columns, &mut mappings) {
+            // Workers would race to insert a repeat, which a serial run skips
+            Ok(memory) if !seen.insert(&memory) => skipped += 1,
+            Ok(memory) => mapped.push(memory),
+            Err(reason) => rejected.push(RejectedRow {
+                legacy_id: column(row, &columns, "id").map(value_text),
+                reason,
+            }),
+        }
+    }
+
+    let written = map_ordered(mapped, jobs, |memory| {
+        let target = target.clone();
+        async move {
+            let written = queries::import_memory(&target, &memory).await?;
+            Ok((memory, written))
+        }
+    })
+    .await?;
+    let mut imported = Vec::new();
+    for (memory, written) in written {
+        if written {
+            imported.push(memory);
+        } else {
+            skipped += 1;
+        }
+    }
+
+    let ids: Vec<Uuid> = imported.iter().map(|m| m.id).collect();
+    let stored = queries::get_memories_by_ids(target, &ids).await?;
+    let mut verification = verify_imports(&imported, &stored);
+    verification.ok &= imported.len() + skipped + rejected.len() == rows.len();
+
+    Ok(LegacyMigrationReport {
+        source_table: table.to_string(),
+        dry_run,
+        columns: columns
+            .into_iter()
+            .map(|(current, legacy)| (legacy, current.to_string()))
+            .collect(),
+        ignored_columns,
+        read: rows.len(),
+        imported: imported.len(),
+        skipped,
+        rejected,
+        mappings: mappings
+            .into_iter()
+            .map(|((field, from, to), count)| ValueMapping {
+                field: field.to_string(),
+                from,
+                to,
+                count,
+            })
+            .collect(),
+        verification,
+    })
+}
+
+/// IDs and placed contents of the rows mapped so far
+#[derive(Default)]
+struct Seen {
+    ids: HashSet<Uuid>,
+    contents: HashSet<(String, &'static str, Option<String>)>,
+}
+
+impl Seen {
+    /// Record `memory`; false when an earlier row has its ID, or its content
+    /// in the same scope and project, so that importing it would be skipped
+    fn insert(&mut self, memory: &ImportedMemory) -> bool {
+        let place = (memory.content.clone(), memory.scope.as_str(), memory.project_path.clone());
+        if self.ids.contains(&memory.id) || self.contents.contains(&place) {
+            return false;
+        }
+        self.ids.insert(memory.id);
+        self.contents.insert(place);
+        true
+    }
+}
+
+/// Accept `table` or `schema.table` made of plain identifiers, since the
+/// name ends up in the query text
+fn check_table_name(table: &str) -> Result<()> {
+    let parts: Vec<&str> = table.split('.').collect();
+    let valid = parts.len() <= 2
+        && parts.iter().all(|part| {
+            let mut chars = part.chars();
+            chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(HippocampusError::Validation(format!("Invalid table name: {}", table)))
+    }
+}
+
+/// Pick the legacy column for each current column; the rest are ignored
+fn map_columns(legacy: &[String]) -> (BTreeMap<&'static str, String>, Vec<String>) {
+    let mut mapped = BTreeMap::new();
+    for (current, aliases) in COLUMN_ALIASES {
+        if let Some(name) = aliases.iter().find(|alias| legacy.iter().any(|c| c == *alias)) {
+            mapped.insert(*current, name.to_string());
+        }
+    }
+    let ignored = legacy
+        .iter()
+        .filter(|c| !mapped.values().any(|m| m == *c))
+        .cloned()
+        .collect();
+    (mapped, ignored)
+}
+
+/// A row's non-null value for a current column
+fn column<'a>(row: &'a Value, columns: &BTreeMap<&str, String>, current: &str) -> Option<&'a Value> {
+    columns
+        .get(current)
+        .and_then(|legacy| row.get(legacy))
+        .filter(|value| !value.is_null())
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Map one legacy row, or give the reason it cannot be imported
+fn map_row(
+    row: &Value,
+    columns: &BTreeMap<&str, String>,
+    mappings: &mut Mappings,
+) -> std::result::Result<ImportedMemory, String> {
+    let content = column(row, columns, "content")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .ok_or("empty content")?;
+
+    let raw_type = column(row, columns, "type").map(value_text).ok_or("missing type")?;
+    let memory_type =
+        map_type(&raw_type).ok_or_else(|| format!("unknown type '{}'", raw_type))?;
+
+    let project_path = column(row, columns, "project_path")
+        .and_then(Value::as_str)
+        .filter(|p| !p.is_empty())
+        .map(str::to_string);
+    let raw_scope = column(row, columns, "scope").map(value_text);
+    let scope = map_scope(raw_scope.as_deref(), project_path.is_some())?;
+    let (tags, tag_format) = map_tags(column(row, columns, "tags"));
+    let raw_confidence = column(row, columns, "confidence");
+    let confidence = map_confidence(raw_confidence);
+
+    // Only rows that map count towards the rewrites
+    record(mappings, "type", &raw_type, memory_type.as_str());
+    if let Some(raw) = &raw_scope {
+        record(mappings, "scope", raw, scope.as_str());
+    }
+    if let Some(format) = tag_format {
+        record(mappings, "tags", format, "text[]");
+    }
+    if let Some(raw) = raw_confidence {
+        record(mappings, "confidence", &value_text(raw), confidence.as_str());
+    }
+
+    let id = column(row, columns, "id")
+        .and_then(Value::as_str)
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .unwrap_or_else(Uuid::new_v4);
+    let access_count = column(row, columns, "access_count")
+        .and_then(|v| v.as_i64().or_else(|| v.as_str()?.parse().ok()))
+        .and_then(|n| i32::try_from(n).ok())
+        .unwrap_or(0);
+
+    Ok(ImportedMemory {
+        id,
+        memory_type,
+        scope,
+        project_path: if scope == Scope::Project { project_path } else { None },
+        content: content.to_string(),
+        tags,
+        confidence,
+        created_at: column(row, columns, "created_at").and_then(map_timestamp),
+        updated_at: column(row, columns, "updated_at").and_then(map_timestamp),
+        accessed_at: column(row, columns, "accessed_at").and_then(map_timestamp),
+        access_count,
+    })
+}
+
+/// Count a rewrite, ignoring ones that only change case
+fn record(mappings: &mut Mappings, field: &'static str, from: &str, to: &str) {
+    if !from.eq_ignore_ascii_case(to) {
+        *mappings.entry((field, from.to_string(), to.to_string())).or_default() += 1;
+    }
+}
+
+/// Current type for a legacy type name, plurals and older names included
+fn map_type(raw: &str) -> Option<MemoryType> {
+    let lower = raw.trim().to_lowercase();
+    let singular = lower.strip_suffix('s').unwrap_or(&lower);
+    if let Ok(memory_type) = lower.parse().or_else(|_| singular.parse()) {
+        return Some(memory_type);
+    }
+    match singular {
+        "pattern" | "style" | "rule" => Some(MemoryType::Convention),
+        "decision" | "design" | "structure" => Some(MemoryType::Architecture),
+        "bug" | "pitfall" | "warning" | "issue" => Some(MemoryType::Gotcha),
+        "endpoint" | "interface" => Some(MemoryType::Api),
+        "fact" | "note" | "insight" | "lesson" => Some(MemoryType::Learning),
+        "pref" | "setting" => Some(MemoryType::Preference),
+        _ => None,
+    }
+}
+
+/// Current scope for a legacy scope name; without one, memories with a
+/// project path are project memories
+fn map_scope(raw: Option<&str>, has_project: bool) -> std::result::Result<Scope, String> {
+    let scope = match raw.map(|s| s.trim().to_lowercase()).as_deref() {
+        None => {
+            if has_project {
+                Scope::Project
+            } else {
+                Scope::Global
+            }
+        }
+        Some("project" | "local" | "repo" | "workspace") => Scope::Project,
+        Some("global" | "user" | "personal" | "shared") => Scope::Global,
+        Some(_) => return Err(format!("unknown scope '{}'", raw.unwrap_or_default())),
+    };
+    if scope == Scope::Project && !has_project {
+        return Err("project scope without a project path".to_string());
+    }
+    Ok(scope)
+}
+
+/// Tags from an array, JSON text, a Postgres array literal or comma-separated
+/// text, with the text format named when it needed converting
+fn map_tags(value: Option<&Value>) -> (Vec<String>, Option<&'static str>) {
+    let (tags, format): (Vec<String>, _) = match value {
+        None => (Vec::new(), None),
+        Some(Value::Array(items)) => (items.iter().map(value_text).collect(), None),
+        Some(Value::String(text)) => {
+            let text = text.trim();
+            if let Some(Value::Array(items)) = text
+                .starts_with('[')
+                .then(|| serde_json::from_str(text).ok())
+                .flatten()
+            {
+                (items.iter().map(value_text).collect(), Some("json text"))
+            } else if let Some(inner) = text.strip_prefix('{').and_then(|t| t.strip_suffix('}')) {
+                let tags = inner.split(',').map(|t| t.trim().trim_matches('"').to_string());
+                (tags.collect(), Some("array literal"))
+            } else {
+                let tags = text.split(',').map(str::to_string).collect();
+                (tags, Some("comma-separated text"))
+            }
+        }
+        Some(other) => (vec![value_text(other)], Some("scalar")),
+    };
+    let tags: Vec<String> = tags
+        .into_iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    (dedup_ignoring_case(&tags), format.filter(|_| !tags.is_empty()))
+}
+
+/// Current confidence for a legacy level or a 0-1 score; anything else is medium
+fn map_confidence(value: Option<&Value>) -> Confidence {
+    let score = match value {
+        None => return Confidence::Medium,
+        Some(Value::Number(n)) => n.as_f64(),
+        Some(other) => {
+            let text = value_text(other);
+            if let Ok(confidence) = text.parse() {
+                return confidence;
+            }
+            text.trim().parse().ok()
+        }
+    };
+    match score {
+        Some(s) if s >= 0.8 => Confidence::High,
+        Some(s) if s >= 0.5 => Confidence::Medium,
+        Some(_) => Confidence::Low,
+        None => Confidence::Medium,
+    }
+}
+
+/// A timestamp from RFC 3339, a timestamp without zone (taken as UTC), or
+/// epoch milliseconds as written by `Date.now()`
+fn map_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::String(text) => DateTime::parse_from_rfc3339(text)
+            .map(|t| t.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+                    .iter()
+                    .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+                    .map(|t| t.and_utc())
+            }),
+        Value::Number(n) => {
+            let n = n.as_i64()?;
+            // Seconds until the year 5138, milliseconds after
+            if n > 100_000_000_000 {
+                DateTime::from_timestamp_millis(n)
+            } else {
+                DateTime::from_timestamp(n, 0)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Compare imports with what the target stored
+fn verify_imports(imported: &[ImportedMemory], stored: &[Memory]) -> Verification {
+    let mismatched: Vec<Uuid> = imported
+        .iter()
+        .filter(|memory| {
+            !stored.iter().any(|s| {
+                s.id == memory.id
+                    && s.memory_type == memory.memory_type
+                    && s.scope == memory.scope
+                    && s.content == memory.content
+                    && s.tags == memory.tags
+            })
+        })
+        .map(|memory| memory.id)
+        .collect();
+
+    Verification {
+        found: stored.len(),
+        ok: mismatched.is_empty() && stored.len() == imported.len(),
+        mismatched,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn legacy_columns(names: &[&str]) -> BTreeMap<&'static str, String> {
+        let names: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+        map_columns(&names).0
+    }
+
+    #[test]
+    fn test_map_columns_prefers_current_names() {
+        let legacy: Vec<String> = ["id", "kind", "text", "content", "tier", "extra"]
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        let (columns, ignored) = map_columns(&legacy);
+        assert_eq!(columns["content"], "content");
+        assert_eq!(columns["type"], "kind");
+        assert_eq!(columns["scope"], "tier");
+        assert_eq!(ignored, vec!["text", "extra"]);
+    }
+
+    #[test]
+    fn test_map_type_and_scope() {
+        assert_eq!(map_type("Gotchas"), Some(MemoryType::Gotcha));
+        assert_eq!(map_type("pattern"), Some(MemoryType::Convention));
+        assert_eq!(map_type("decisions"), Some(MemoryType::Architecture));
+        assert_eq!(map_type("todo"), None);
+
+        assert_eq!(map_scope(Some("user"), false), Ok(Scope::Global));
+        assert_eq!(map_scope(None, true), Ok(Scope::Project));
+        assert!(map_scope(Some("local"), false).is_err());
+        assert!(map_scope(Some("team"), true).is_err());
+    }
+
+    #[test]
+    fn test_map_tags_formats() {
+        assert_eq!(map_tags(Some(&json!(["rust", "Rust", "db"]))), (vec!["rust".into(), "db".into()], None));
+        assert_eq!(
+            map_tags(Some(&json!("rust, db ,"))),
+            (vec!["rust".into(), "db".into()], Some("comma-separated text"))
+        );
+        assert_eq!(map_tags(Some(&json!(r#"["a", "b"]"#))).1, Some("json text"));
+        assert_eq!(map_tags(Some(&json!(r#"{a,"b c"}"#))).0, vec!["a", "b c"]);
+        assert_eq!(map_tags(Some(&json!(""))), (vec![], None));
+    }
+
+    #[test]
+    fn test_map_confidence_and_timestamps() {
+        assert_eq!(map_confidence(Some(&json!("HIGH"))), Confidence::High);
+        assert_eq!(map_confidence(Some(&json!(0.9))), Confidence::High);
+        assert_eq!(map_confidence(Some(&json!("0.3"))), Confidence::Low);
+        assert_eq!(map_confidence(Some(&json!("sure"))), Confidence::Medium);
+
+        let expected = "2024-03-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(map_timestamp(&json!("2024-03-01T12:00:00+00:00")), Some(expected));
+        assert_eq!(map_timestamp(&json!("2024-03-01 12:00:00")), Some(expected));
+        assert_eq!(map_timestamp(&json!(1709294400000_i64)), Some(expected));
+        assert_eq!(map_timestamp(&json!(1709294400)), Some(expected));
+    }
+
+    #[test]
+    fn test_map_row_records_rewrites() {
+        let columns = legacy_columns(&["id", "kind", "text", "tags", "project", "confidence"]);
+        let mut mappings = Mappings::new();
+        let row = json!({
+            "id": 7,
+            "kind": "bug",
+            "text": "  Pool must be closed  ",
+            "tags": "db,pool",
+            "project": "/repo",
+            "confidence": 0.95
+        });
+
+        let memory = map_row(&row, &columns, &mut mappings).unwrap();
+        assert_eq!(memory.memory_type, MemoryType::Gotcha);
+        assert_eq!(memory.scope, Scope::Project);
+        assert_eq!(memory.project_path.as_deref(), Some("/repo"));
+        assert_eq!(memory.content, "Pool must be closed");
+        assert_eq!(memory.confidence, Confidence::High);
+        assert_eq!(mappings[&("type", "bug".to_string(), "gotcha".to_string())], 1);
+        assert_eq!(mappings[&("confidence", "0.95".to_string(), "high".to_string())], 1);
+
+        let row = json!({"id": 8, "kind": "todo", "text": "Later"});
+        assert_eq!(map_row(&row, &columns, &mut mappings).unwrap_err(), "unknown type 'todo'");
+        let row = json!({"id": 9, "kind": "note", "text": "No path", "tier": "local"});
+        assert!(map_row(&row, &legacy_columns(&["id", "kind", "text", "tier"]), &mut mappings).is_err());
+        assert_eq!(mappings.len(), 3);
+    }
+
+    #[test]
+    fn test_check_table_name() {
+        assert!(check_table_name("memories").is_ok());
+        assert!(check_table_name("legacy.memories_v1").is_ok());
+        assert!(check_table_name("memories; DROP TABLE memories").is_err());
+        assert!(check_table_name("a.b.c").is_err());
+        assert!(check_table_name("").is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! Link commands: link, unlink, supersede, unsupersede
+//!
+//! Links relate memories beyond the linear supersession chain: one memory
+//! relates to, contradicts or refines another. Links are directed, from the
+//! first memory to the second. `show-chain` lists a memory's links, and
+//! `get-memory --neighborhood` returns the memories linked to it.
+//! `supersede` extends the chain itself, retiring a memory in favour of one
+//! that already exists, and `unsupersede` brings a memory back when its
+//! correction turns out to be wrong.
+
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::error::{HippocampusError, Result};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// How a memory relates to the one it links to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LinkRelation {
+    /// Covers a related subject
+    #[default]
+    RelatesTo,
+    /// Says the opposite, and one of the two may be wrong
+    Contradicts,
+    /// Adds detail to, or narrows, the other
+    Refines,
+}
+
+impl LinkRelation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RelatesTo => "relates-to",
+            Self::Contradicts => "contradicts",
+            Self::Refines => "refines",
+        }
+    }
+}
+
+/// Result of link
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkResult {
+    pub source_id: Uuid,
+    pub target_id: Uuid,
+    pub relation: &'static str,
+    /// False when the link already existed
+    pub changed: bool,
+}
+
+/// Result of unlink
+#[derive(Debug, Serialize)]
+pub struct UnlinkResult {
+    /// Links removed between the two memories
+    pub removed: u64,
+}
+
+/// Result of supersede
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupersedeResult {
+    pub old_id: Uuid,
+    pub new_id: Uuid,
+    /// Memory that superseded the old one before, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous: Option<Uuid>,
+    /// False when the old memory already was superseded by the new one
+    pub changed: bool,
+}
+
+/// Result of unsupersede
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsupersedeResult {
+    pub memory_id: Uuid,
+    /// Memory that had superseded it
+    pub successor_id: Uuid,
+    /// Whether the successor was retired in its place
+    pub successor_deactivated: bool,
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Link `source` to `target` with `relation`
+pub async fn link(
+    pool: &PgPool,
+    source: Uuid,
+    target: Uuid,
+    relation: LinkRelation,
+) -> Result<LinkResult> {
+    if source == target {
+        return Err(HippocampusError::Validation(
+            "A memory cannot be linked to itself".to_string(),
+        ));
+    }
+    for id in [source, target] {
+        if queries::get_memory(pool, id).await?.is_none() {
+            return Err(HippocampusError::NotFound(format!("Memory not found: {}", id)));
+        }
+    }
+
+    let changed = queries::insert_memory_link(pool, source, target, relation.as_str()).await?;
+    Ok(LinkResult {
+        source_id: source,
+        target_id: target,
+        relation: relation.as_str(),
+        changed,
+    })
+}
+
+/// Remove the links between two memories, either way round, only those of
+/// `relation` when given
+pub async fn unlink(
+    pool: &PgPool,
+    a: Uuid,
+    b: Uuid,
+    relation: Option<LinkRelation>,
+) -> Result<UnlinkResult> {
+    let relation = relation.map(|r| r.as_str());
+    let removed = queries::delete_memory_links(pool, a, b, relation).await?;
+    Ok(UnlinkResult { removed })
+}
+
+/// Mark `old` as superseded by the existing memory `new`.
+///
+/// An old memory superseded by another one is moved to `new`. Refused when
+/// `old` supersedes `new`, directly or further along the chain, as the
+/// chain would loop.
+pub async fn supersede(pool: &PgPool, old: Uuid, new: Uuid) -> Result<SupersedeResult> {
+    if old == new {
+        return Err(HippocampusError::Validation(
+            "A memory cannot supersede itself".to_string(),
+        ));
+    }
+    let not_found = |id: Uuid| HippocampusError::NotFound(format!("Memory not found: {}", id));
+    let previous = queries::get_memory(pool, old)
+        .await?
+        .ok_or_else(|| not_found(old))?
+        .superseded_by;
+    if queries::get_memory(pool, new).await?.is_none() {
+        return Err(not_found(new));
+    }
+    if queries::is_superseded_through(pool, new, old).await? {
+        return Err(HippocampusError::Validation(format!(
+            "Memory {} is already superseded by {} through its chain; superseding {} by it would create a cycle",
+            new, old, old
+        )));
+    }
+
+    let changed = previous != Some(new);
+    if changed {
+        queries::supersede_memory(pool, old, new).await?;
+    }
+    Ok(SupersedeResult {
+        old_id: old,
+        new_id: new,
+        previous: previous.filter(|id| *id != new),
+        changed,
+    })
+}
+
+/// Make the superseded memory `id` active again.
+///
+/// With `deactivate_successor`, the memory that superseded it is in turn
+/// superseded by it, if still active, so `show-chain` keeps the history.
+pub async fn unsupersede(pool: &PgPool, id: Uuid, deactivate_successor: bool) -> Result<UnsupersedeResult> {
+    let memory = queries::get_memory(pool, id)
+        .await?
+        .ok_or_else(|| HippocampusError::NotFound(format!("Memory not found: {}", id)))?;
+    let Some(successor) = memory.superseded_by else {
+        return Err(HippocampusError::Validation(format!("Memory {} is not superseded", id)));
+    };
+
+    queries::unsupersede_memory(pool, id).await?;
+    let mut successor_deactivated = false;
+    if deactivate_successor {
+        if let Some(successor) = queries::get_memory(pool, successor).await?.filter(|m| m.is_active) {
+            queries::supersede_memory(pool, successor.id, id).await?;
+            successor_deactivated = true;
+        }
+    }
+
+    Ok(UnsupersedeResult {
+        memory_id: id,
+        successor_id: successor,
+        successor_deactivated,
+    })
+}
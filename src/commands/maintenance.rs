@@ -1,17 +1,78 @@
+use std::future::Future;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use chrono::Utc;
+use serde::Serialize;
 use sqlx::postgres::PgPool;
 
-use crate::db;
-use crate::error::Result;
+use crate::db::{self, CrossScopeDuplicate, DecayStats, LockMode, MaintenanceLock, MergedDuplicate};
+use crate::error::{HippocampusError, Result};
+use crate::util::cursor::{self, Cursor};
 use crate::models::{
     ChainData, ConsolidateData, ListSupersededData, PruneDataResult, PurgeSupersededData,
     SaveSessionSummaryData, Scope, SuccessResponse, SupersededMemory, Tier, TieredPruneData,
 };
 
-/// Consolidate duplicate memories (remove exact duplicates)
+/// Word similarity at which a memory's summary counts as quoted in a
+/// session summary
+const SUMMARY_CITATION_SIMILARITY: f64 = 0.75;
+
+/// Result of a maintenance command skipped with --skip-if-running
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceSkipped {
+    pub skipped: bool,
+    pub operation: String,
+    pub reason: String,
+}
+
+/// Run a maintenance command under the maintenance lock, so consolidate,
+/// prune, decay, purge-superseded, prune-data and dedupe-across-scopes
+/// never overlap.
+pub async fn with_maintenance_lock<F>(
+    pool: &PgPool,
+    mode: LockMode,
+    operation: &str,
+    run: F,
+) -> Result<serde_json::Value>
+where
+    F: Future<Output = Result<serde_json::Value>>,
+{
+    let Some(lock) = MaintenanceLock::acquire(pool, mode).await? else {
+        if mode == LockMode::Skip {
+            let response = SuccessResponse::new(MaintenanceSkipped {
+                skipped: true,
+                operation: operation.to_string(),
+                reason: "Another maintenance run is in progress".to_string(),
+            });
+            return Ok(serde_json::to_value(response)?);
+        }
+        return Err(HippocampusError::MaintenanceRunning(operation.to_string()));
+    };
+
+    let result = run.await;
+    lock.release().await?;
+    result
+}
+
+/// Result of consolidate --merge
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsolidateMergeResult {
+    pub merged: usize,
+    pub merges: Vec<MergedDuplicate>,
+}
+
+/// Consolidate duplicate memories (remove duplicates and near-duplicates)
+///
+/// With `merge`, duplicates are folded into the copy kept and marked
+/// superseded by it instead of deleted.
 pub async fn consolidate(
     pool: &PgPool,
     tier: Tier,
     project_path: Option<&str>,
+    duplicate_similarity: f64,
+    merge: bool,
 ) -> Result<serde_json::Value> {
     let scope_filter = match tier {
         Tier::Global => Some(Scope::Global),
@@ -19,7 +80,18 @@ pub async fn consolidate(
         Tier::Both => None,
     };
 
-    let duplicate_ids = db::consolidate_duplicates(pool, scope_filter, project_path).await?;
+    if merge {
+        let merges =
+            db::merge_duplicates(pool, scope_filter, project_path, duplicate_similarity).await?;
+        let response = SuccessResponse::new(ConsolidateMergeResult {
+            merged: merges.len(),
+            merges,
+        });
+        return Ok(serde_json::to_value(response)?);
+    }
+
+    let duplicate_ids =
+        db::consolidate_duplicates(pool, scope_filter, project_path, duplicate_similarity).await?;
 
     let response = SuccessResponse::new(ConsolidateData {
         removed: duplicate_ids.len(),
@@ -29,6 +101,103 @@ pub async fn consolidate(
     Ok(serde_json::to_value(response)?)
 }
 
+/// One cross-scope duplicate folded into the memory kept in the preferred scope
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrossScopeMerge {
+    pub kept_scope: Scope,
+    #[serde(flatten)]
+    pub merge: MergedDuplicate,
+}
+
+/// Result of dedupe-across-scopes
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DedupeAcrossScopesResult {
+    pub found: usize,
+    pub merged: usize,
+    pub merges: Vec<CrossScopeMerge>,
+    /// Pairs left as they are
+    pub skipped: Vec<CrossScopeDuplicate>,
+}
+
+/// Merge project memories with the global memory that says the same
+/// thing, keeping the one in the `prefer` scope and superseding the other.
+/// Without `prefer`, asks on the terminal for each pair.
+pub async fn dedupe_across_scopes(
+    pool: &PgPool,
+    project_paths: &[String],
+    duplicate_similarity: f64,
+    prefer: Option<Scope>,
+) -> Result<serde_json::Value> {
+    if prefer.is_none() && !io::stdin().is_terminal() {
+        return Err(HippocampusError::Validation(
+            "--prefer global|project is required when stdin is not a terminal".to_string(),
+        ));
+    }
+
+    let pairs = db::find_cross_scope_duplicates(pool, project_paths, duplicate_similarity).await?;
+    let found = pairs.len();
+    let mut merges = Vec::new();
+    let mut skipped = Vec::new();
+    for pair in pairs {
+        let keep = match prefer {
+            Some(scope) => Some(scope),
+            None => ask_scope_to_keep(&pair)?,
+        };
+        let Some(keep) = keep else {
+            skipped.push(pair);
+            continue;
+        };
+        let (keep_id, duplicate_id) = match keep {
+            Scope::Global => (pair.global_id, pair.project_id),
+            Scope::Project => (pair.project_id, pair.global_id),
+        };
+        match db::merge_memory_into(pool, keep_id, duplicate_id).await? {
+            Some(merge) => merges.push(CrossScopeMerge { kept_scope: keep, merge }),
+            None => skipped.push(pair),
+        }
+    }
+
+    let response = SuccessResponse::new(DedupeAcrossScopesResult {
+        found,
+        merged: merges.len(),
+        merges,
+        skipped,
+    });
+    Ok(serde_json::to_value(response)?)
+}
+
+/// Show a pair on stderr and read which scope to keep; None skips it
+fn ask_scope_to_keep(pair: &CrossScopeDuplicate) -> Result<Option<Scope>> {
+    let mut stderr = io::stderr();
+    writeln!(stderr, "\n[{}]", pair.memory_type.as_str())?;
+    writeln!(stderr, "  project: {}", pair.project_summary)?;
+    writeln!(stderr, "  global:  {}", pair.global_summary)?;
+    loop {
+        write!(stderr, "Keep [g]lobal, [p]roject or [s]kip? ")?;
+        stderr.flush()?;
+        let mut answer = String::new();
+        if io::stdin().lock().read_line(&mut answer)? == 0 {
+            return Ok(None);
+        }
+        if let Some(choice) = parse_keep_answer(&answer) {
+            return Ok(choice);
+        }
+    }
+}
+
+/// The scope to keep from an answer to [`ask_scope_to_keep`]: Some(None)
+/// skips, None asks again
+fn parse_keep_answer(answer: &str) -> Option<Option<Scope>> {
+    match answer.trim().to_lowercase().as_str() {
+        "g" | "global" => Some(Some(Scope::Global)),
+        "p" | "project" => Some(Some(Scope::Project)),
+        "s" | "skip" | "" => Some(None),
+        _ => None,
+    }
+}
+
 /// Prune old low-confidence memories with no access using tiered retention
 /// - LOW confidence: pruned after `low_days` days with access_count=0
 /// - MEDIUM confidence: pruned after `medium_days` days with access_count=0
@@ -46,6 +215,9 @@ pub async fn prune(
         Tier::Both => None,
     };
 
+    // Pending accesses count: a memory read since the last rollup is not unused
+    db::rollup_memory_access(pool).await?;
+
     let (low_pruned_ids, medium_pruned_ids) =
         db::prune_old_memories_tiered(pool, low_days, medium_days, scope_filter, project_path)
             .await?;
@@ -63,20 +235,106 @@ pub async fn prune(
     Ok(serde_json::to_value(response)?)
 }
 
+/// Result of decay command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecayResult {
+    pub half_life_days: f64,
+    pub archive_days: i64,
+    /// Heat below which HIGH confidence memories are archived: that of a
+    /// never-used memory `archive_days` old
+    pub archive_heat: f64,
+    pub dry_run: bool,
+    #[serde(flatten)]
+    pub stats: DecayStats,
+}
+
+/// Cool memories by disuse and archive the cold HIGH confidence ones.
+///
+/// LOW and MEDIUM confidence memories only get their heat updated; `prune`
+/// deletes those. Archived memories leave the context block but stay
+/// searchable with `--include-archived`, and are restored by a later run
+/// once they are used again.
+pub async fn decay(
+    pool: &PgPool,
+    half_life_days: f64,
+    archive_days: i64,
+    dry_run: bool,
+) -> Result<serde_json::Value> {
+    if archive_days < 1 {
+        return Err(HippocampusError::Validation(format!(
+            "Invalid archive days: {} (must be at least 1)",
+            archive_days
+        )));
+    }
+    if half_life_days <= 0.0 {
+        return Err(HippocampusError::Config(format!(
+            "Invalid ranking.half_life_days: {} (must be positive)",
+            half_life_days
+        )));
+    }
+    let archive_heat = archive_heat(archive_days, half_life_days);
+
+    // Pending accesses count: a memory read since the last rollup is not cold
+    db::rollup_memory_access(pool).await?;
+    let stats = db::decay_memories(pool, half_life_days, archive_heat, dry_run).await?;
+
+    let response = SuccessResponse::new(DecayResult {
+        half_life_days,
+        archive_days,
+        archive_heat,
+        dry_run,
+        stats,
+    });
+    Ok(serde_json::to_value(response)?)
+}
+
+/// Heat of a never-used memory `days` old
+fn archive_heat(days: i64, half_life_days: f64) -> f64 {
+    0.5_f64.powf(days as f64 / half_life_days)
+}
+
 /// Save session summary to database
 pub async fn save_session_summary(
     pool: &PgPool,
     claude_session_id: &str,
     summary: &serde_json::Value,
 ) -> Result<serde_json::Value> {
-    let session_id = db::save_session_summary(pool, claude_session_id, summary).await?;
+    let project_path = db::find_session_by_claude_id(pool, claude_session_id)
+        .await?
+        .and_then(|session| session.project_path);
+    let memory_ids = db::find_memories_cited_in(
+        pool,
+        &summary_text(summary),
+        project_path.as_deref(),
+        SUMMARY_CITATION_SIMILARITY,
+    )
+    .await?;
+
+    let mut summary = summary.clone();
+    if let Some(fields) = summary.as_object_mut() {
+        fields.insert("memoryIds".to_string(), serde_json::to_value(&memory_ids)?);
+    }
+    let session_id = db::save_session_summary(pool, claude_session_id, &summary).await?;
 
-    let response = SuccessResponse::new(SaveSessionSummaryData { session_id });
+    let response = SuccessResponse::new(SaveSessionSummaryData {
+        session_id,
+        memory_ids,
+    });
 
     Ok(serde_json::to_value(response)?)
 }
 
-/// Show the supersession chain for a memory
+/// Text of a summary matched against memories: its `summary` field, or the
+/// whole value when it has none
+fn summary_text(summary: &serde_json::Value) -> String {
+    match summary.get("summary").unwrap_or(summary) {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Show the supersession chain for a memory, and the memories linked to it
 pub async fn show_chain(pool: &PgPool, memory_id: uuid::Uuid) -> Result<serde_json::Value> {
     let chain = db::show_chain(pool, memory_id).await?;
 
@@ -84,20 +342,35 @@ pub async fn show_chain(pool: &PgPool, memory_id: uuid::Uuid) -> Result<serde_js
         memory: chain.memory,
         predecessors: chain.predecessors,
         successors: chain.successors,
+        links: chain.links,
     });
 
     Ok(serde_json::to_value(response)?)
 }
 
-/// List superseded (inactive) memories
+/// List superseded (inactive) memories, most recently superseded first,
+/// skipping the first `offset` or starting after the row of `after`
 pub async fn list_superseded(
     pool: &PgPool,
     tier: Tier,
     limit: i64,
-    project_path: Option<&str>,
+    offset: i64,
+    after: Option<&Cursor>,
+    project_paths: &[String],
 ) -> Result<serde_json::Value> {
-    let entries = db::list_superseded(pool, tier, limit, project_path).await?;
+    // One extra row tells whether there is a next page
+    let mut entries = db::list_superseded(pool, tier, limit + 1, offset, after, project_paths).await?;
+    let has_more = cursor::take_page(&mut entries, limit as usize);
     let count = entries.len();
+    let next_cursor = entries.last().filter(|_| has_more).map(|e| {
+        Cursor {
+            order: db::queries::SUPERSEDED_ORDER.to_string(),
+            key: vec![cursor::time_key(e.superseded_at)],
+            id: e.memory.id,
+            at: Utc::now(),
+        }
+        .encode()
+    });
 
     let response = SuccessResponse::new(ListSupersededData {
         entries: entries
@@ -109,6 +382,7 @@ pub async fn list_superseded(
             })
             .collect(),
         count,
+        next_cursor,
     });
 
     Ok(serde_json::to_value(response)?)
@@ -171,9 +445,8 @@ pub async fn prune_data(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Utc;
     use uuid::Uuid;
-    use crate::models::{Confidence, MemoryType, MemorySummary, PruneData};
+    use crate::models::{Confidence, MemoryType, MemorySummary};
 
     #[test]
     fn test_tier_to_scope_filter_global() {
@@ -222,6 +495,52 @@ mod tests {
         assert!(json["duplicateIds"].is_array());
     }
 
+    #[test]
+    fn test_maintenance_skipped_serialization() {
+        let response = SuccessResponse::new(MaintenanceSkipped {
+            skipped: true,
+            operation: "prune".to_string(),
+            reason: "Another maintenance run is in progress".to_string(),
+        });
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["success"], true);
+        assert_eq!(json["skipped"], true);
+        assert_eq!(json["operation"], "prune");
+    }
+
+    #[test]
+    fn test_parse_keep_answer() {
+        assert_eq!(parse_keep_answer("g\n"), Some(Some(Scope::Global)));
+        assert_eq!(parse_keep_answer(" Project "), Some(Some(Scope::Project)));
+        assert_eq!(parse_keep_answer("\n"), Some(None));
+        assert_eq!(parse_keep_answer("both"), None);
+    }
+
+    #[test]
+    fn test_archive_heat() {
+        assert_eq!(archive_heat(30, 30.0), 0.5);
+        assert_eq!(archive_heat(90, 30.0), 0.125);
+    }
+
+    #[test]
+    fn test_decay_result_serialization() {
+        let result = DecayResult {
+            half_life_days: 30.0,
+            archive_days: 180,
+            archive_heat: archive_heat(180, 30.0),
+            dry_run: true,
+            stats: DecayStats {
+                scored: 40,
+                archived: 2,
+                restored: 1,
+            },
+        };
+        let json = serde_json::to_value(SuccessResponse::new(result)).unwrap();
+        assert_eq!(json["archiveDays"], 180);
+        assert_eq!(json["scored"], 40);
+        assert_eq!(json["restored"], 1);
+    }
+
     #[test]
     fn test_tiered_prune_data_serialization() {
         let data = TieredPruneData {
@@ -254,11 +573,13 @@ mod tests {
             superseded_by: None,
             superseded_at: None,
             is_active: true,
+            pinned: false,
         };
         let data = ChainData {
             memory: summary.clone(),
             predecessors: vec![],
             successors: vec![summary],
+            links: vec![],
         };
         let response = SuccessResponse::new(data);
         let json = serde_json::to_value(&response).unwrap();
@@ -267,6 +588,7 @@ mod tests {
         assert!(json["memory"].is_object());
         assert!(json["predecessors"].is_array());
         assert!(json["successors"].is_array());
+        assert!(json.get("links").is_none());
     }
 
     #[test]
@@ -291,12 +613,22 @@ mod tests {
     fn test_save_session_summary_data_serialization() {
         let data = SaveSessionSummaryData {
             session_id: Uuid::new_v4(),
+            memory_ids: vec![Uuid::new_v4()],
         };
         let response = SuccessResponse::new(data);
         let json = serde_json::to_value(&response).unwrap();
 
         assert_eq!(json["success"], true);
         assert!(json["sessionId"].is_string());
+        assert_eq!(json["memoryIds"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_summary_text() {
+        let summary = serde_json::json!({"summary": "Fixed the sqlx pool"});
+        assert_eq!(summary_text(&summary), "Fixed the sqlx pool");
+        let structured = serde_json::json!({"done": ["tests"]});
+        assert_eq!(summary_text(&structured), r#"{"done":["tests"]}"#);
     }
 
     // Note: Full integration tests require a database connection
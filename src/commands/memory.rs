@@ -1,17 +1,26 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use sqlx::postgres::PgPool;
 use uuid::Uuid;
 
+use crate::config::{DuplicateCheck, DuplicateScope};
+use crate::conflict::conflict_reason;
 use crate::db;
+use crate::environment::EnvConstraint;
 use crate::error::Result;
+use crate::lint::{self, LintMode, LintRules};
 use crate::models::{
-    AddMemoryData, Confidence, DeleteMemoryData, DuplicateResponse, ErrorResponse,
-    GetMemoryData, MemoryType, Scope, SuccessResponse, Tier, UpdateMemoryData,
+    summarize, AddMemoryData, Confidence, ConflictResponse, DeleteMemoryData, DuplicateResponse,
+    EditMemoryData, ErrorResponse, GetMemoryData, LintIssue, LintRejectedResponse, MemoryConflict,
+    MemoryType, Scope, SuccessResponse, Tier, UpdateMemoryData,
 };
 
 /// Options for adding a memory
 pub struct AddMemoryOptions {
     pub memory_type: MemoryType,
     pub content: String,
+    /// Summary to store instead of one generated from `content`
+    pub summary: Option<String>,
     pub tags: Vec<String>,
     pub confidence: Confidence,
     pub tier: Tier,
@@ -20,34 +29,110 @@ pub struct AddMemoryOptions {
     pub source_turn_id: Option<Uuid>,
     /// ID of memory this supersedes (marks old memory as inactive)
     pub supersedes: Option<Uuid>,
+    /// Environments the memory applies to (empty means everywhere)
+    pub constraints: Vec<EnvConstraint>,
+    /// Which existing memories count as duplicates
+    pub duplicate_check: DuplicateCheck,
+    /// Content rules checked before the memory is added
+    pub lint: LintRules,
+}
+
+impl AddMemoryOptions {
+    /// Summary stored with the memory: the given one when not blank,
+    /// otherwise one generated from the content
+    pub fn summary(&self) -> String {
+        match self.summary.as_deref().map(str::trim) {
+            Some(summary) if !summary.is_empty() => summarize(summary),
+            _ => summarize(&self.content),
+        }
+    }
 }
 
 /// Result of add_memory operation
 pub enum AddMemoryResult {
     Success(serde_json::Value),
     Duplicate(serde_json::Value),
+    Rejected(serde_json::Value),
 }
 
-/// Add a new memory with duplicate detection
-pub async fn add_memory(pool: &PgPool, opts: AddMemoryOptions) -> Result<AddMemoryResult> {
+/// Most conflicts reported for one new memory
+const MAX_CONFLICTS: i64 = 5;
+
+/// Add a new memory with duplicate detection and lint checks.
+///
+/// With `conflict_similarity`, an added memory is compared with the active
+/// memories of its type at least that similar, and those it may contradict
+/// are returned under `conflicts` (see [`crate::conflict`]).
+pub async fn add_memory(
+    pool: &PgPool,
+    opts: AddMemoryOptions,
+    conflict_similarity: Option<f64>,
+) -> Result<AddMemoryResult> {
+    match insert_checked(pool, &opts).await? {
+        Inserted::Added { id, warnings } => {
+            let conflicts = match conflict_similarity {
+                Some(similarity) => find_conflicts(pool, &opts, id, similarity).await?,
+                None => Vec::new(),
+            };
+            let response = SuccessResponse::new(AddMemoryData { id, warnings, conflicts });
+            Ok(AddMemoryResult::Success(serde_json::to_value(response)?))
+        }
+        Inserted::Duplicate(dup) => {
+            let response = DuplicateResponse::new(dup.id, &dup.scope, &dup.summary, dup.similarity)
+                .with_diff(&dup.content, &opts.content);
+            Ok(AddMemoryResult::Duplicate(serde_json::to_value(response)?))
+        }
+        Inserted::Rejected(issues) => {
+            let response = LintRejectedResponse::new(issues);
+            Ok(AddMemoryResult::Rejected(serde_json::to_value(response)?))
+        }
+    }
+}
+
+/// Outcome of inserting one memory
+pub(crate) enum Inserted {
+    /// Added, with any lint warnings
+    Added { id: Uuid, warnings: Vec<LintIssue> },
+    Duplicate(db::DuplicateInfo),
+    Rejected(Vec<LintIssue>),
+}
+
+/// Insert a memory unless lint rules reject it or it duplicates an existing one
+pub(crate) async fn insert_checked(pool: &PgPool, opts: &AddMemoryOptions) -> Result<Inserted> {
+    let warnings = lint::check(opts.memory_type, &opts.content, &opts.lint);
+    if opts.lint.rejects(&warnings) {
+        return Ok(Inserted::Rejected(warnings));
+    }
+
     // Check for duplicates
-    if let Some(dup) = db::find_duplicate(pool, opts.memory_type, &opts.content).await? {
-        let response = DuplicateResponse::new(dup.id, &dup.scope, &dup.summary);
-        return Ok(AddMemoryResult::Duplicate(serde_json::to_value(response)?));
+    if let Some(dup) = find_duplicate_of(pool, opts).await? {
+        return Ok(Inserted::Duplicate(dup));
     }
 
-    // Determine scope from tier
-    let scope = match opts.tier {
-        Tier::Global => Scope::Global,
-        Tier::Project | Tier::Both => Scope::Project,
-    };
+    let id = store(pool, opts).await?;
+    Ok(Inserted::Added { id, warnings })
+}
 
-    // Only include project_path for project-scoped memories
-    let project_path = if scope == Scope::Project {
-        opts.project_path.as_deref()
-    } else {
-        None
-    };
+/// A memory the new one would duplicate
+async fn find_duplicate_of(pool: &PgPool, opts: &AddMemoryOptions) -> Result<Option<db::DuplicateInfo>> {
+    let (_, project_path) = scope_and_path(opts);
+    db::find_duplicate(
+        pool,
+        opts.memory_type,
+        &opts.content,
+        &opts.summary(),
+        opts.duplicate_check.similarity,
+        duplicate_scope(opts),
+        project_path,
+        opts.supersedes,
+    )
+    .await
+}
+
+/// Insert the memory, and mark the one it supersedes inactive
+async fn store(pool: &PgPool, opts: &AddMemoryOptions) -> Result<Uuid> {
+    let metadata = constraints_metadata(&opts.constraints);
+    let (scope, project_path) = scope_and_path(opts);
 
     // Insert the memory
     let id = db::insert_memory(
@@ -56,10 +141,12 @@ pub async fn add_memory(pool: &PgPool, opts: AddMemoryOptions) -> Result<AddMemo
         scope,
         project_path,
         &opts.content,
+        &opts.summary(),
         &opts.tags,
         opts.confidence,
         opts.source_session_id,
         opts.source_turn_id,
+        metadata.as_ref(),
     )
     .await?;
 
@@ -67,35 +154,489 @@ pub async fn add_memory(pool: &PgPool, opts: AddMemoryOptions) -> Result<AddMemo
     if let Some(old_id) = opts.supersedes {
         db::supersede_memory(pool, old_id, id).await?;
     }
+    Ok(id)
+}
+
+/// Memories visible to the new memory `id` that it may contradict; the one
+/// it supersedes is not among them
+async fn find_conflicts(
+    pool: &PgPool,
+    opts: &AddMemoryOptions,
+    id: Uuid,
+    similarity: f64,
+) -> Result<Vec<MemoryConflict>> {
+    let (scope, project_path) = scope_and_path(opts);
+    let exclude: Vec<Uuid> = std::iter::once(id).chain(opts.supersedes).collect();
+    let candidates = db::find_conflict_candidates(
+        pool,
+        opts.memory_type,
+        &opts.content,
+        similarity,
+        Some(scope),
+        project_path,
+        &exclude,
+        MAX_CONFLICTS,
+    )
+    .await?;
+
+    Ok(candidates
+        .into_iter()
+        .filter_map(|c| {
+            let reason = conflict_reason(opts.memory_type, &opts.content, &c.content)?;
+            Some(MemoryConflict {
+                id: c.id,
+                summary: c.summary,
+                similarity: c.similarity,
+                reason: reason.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Scope for the tier, and the project path for project-scoped memories only
+pub(crate) fn scope_and_path(opts: &AddMemoryOptions) -> (Scope, Option<&str>) {
+    match opts.tier {
+        Tier::Global => (Scope::Global, None),
+        Tier::Project | Tier::Both => (Scope::Project, opts.project_path.as_deref()),
+    }
+}
+
+/// Scope whose visible memories a new memory is checked against for
+/// duplicates; None checks every memory
+pub(crate) fn duplicate_scope(opts: &AddMemoryOptions) -> Option<Scope> {
+    match opts.duplicate_check.scope {
+        DuplicateScope::Project => Some(scope_and_path(opts).0),
+        DuplicateScope::Global => None,
+    }
+}
+
+/// Metadata recording environment constraints, None without any
+pub(crate) fn constraints_metadata(constraints: &[EnvConstraint]) -> Option<serde_json::Value> {
+    (!constraints.is_empty()).then(|| {
+        let constraints: Vec<String> = constraints.iter().map(|c| c.to_string()).collect();
+        serde_json::json!({ "constraints": constraints })
+    })
+}
+
+/// Environment constraints recorded in a memory's metadata; unreadable ones are skipped
+pub(crate) fn metadata_constraints(metadata: Option<&serde_json::Value>) -> Vec<EnvConstraint> {
+    metadata
+        .and_then(|m| m.get("constraints"))
+        .and_then(|c| c.as_array())
+        .map(|list| {
+            list.iter()
+                .filter_map(|c| c.as_str())
+                .filter_map(|c| c.parse::<EnvConstraint>().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// One line of `add-memories-batch` input; fields follow `add-memory`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct BatchRecord {
+    #[serde(rename = "type")]
+    memory_type: MemoryType,
+    content: String,
+    summary: Option<String>,
+    #[serde(default)]
+    tags: BatchTags,
+    #[serde(default = "default_batch_confidence")]
+    confidence: Confidence,
+    #[serde(default = "default_batch_tier")]
+    tier: Scope,
+    session: Option<Uuid>,
+    turn: Option<Uuid>,
+    supersedes: Option<Uuid>,
+    #[serde(default)]
+    constraints: Vec<String>,
+}
+
+/// Tags as a list or comma-separated, as `add-memory` takes them
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum BatchTags {
+    List(Vec<String>),
+    Text(String),
+}
+
+impl Default for BatchTags {
+    fn default() -> Self {
+        Self::List(Vec::new())
+    }
+}
+
+fn default_batch_confidence() -> Confidence {
+    Confidence::High
+}
+
+fn default_batch_tier() -> Scope {
+    Scope::Project
+}
+
+/// Outcome of one batch record, numbered by input line
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum BatchOutcome {
+    Added {
+        line: usize,
+        id: Uuid,
+    },
+    #[serde(rename_all = "camelCase")]
+    Duplicate {
+        line: usize,
+        existing_id: Uuid,
+        similarity: Option<f64>,
+    },
+    Invalid {
+        line: usize,
+        error: String,
+    },
+    Rejected {
+        line: usize,
+        issues: Vec<LintIssue>,
+    },
+}
+
+impl BatchOutcome {
+    /// Input line of the record
+    pub fn line(&self) -> usize {
+        match self {
+            Self::Added { line, .. }
+            | Self::Duplicate { line, .. }
+            | Self::Invalid { line, .. }
+            | Self::Rejected { line, .. } => *line,
+        }
+    }
+}
 
-    let response = SuccessResponse::new(AddMemoryData { id });
-    Ok(AddMemoryResult::Success(serde_json::to_value(response)?))
+/// Result of a batch add
+///
+/// A dry run leaves `results` empty and lists the first records that would
+/// not be added in `conflicts` instead.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddMemoriesBatchData {
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+    pub results: Vec<BatchOutcome>,
+    pub added: usize,
+    /// Added records that replace an existing memory
+    pub superseded: usize,
+    pub duplicates: usize,
+    pub invalid: usize,
+    pub rejected: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<BatchOutcome>,
+}
+
+/// Add memories from newline-delimited JSON records.
+///
+/// `pool` must come from [`db::create_transaction_pool`]: every record is
+/// inserted in its transaction, which is committed at the end, so a
+/// database error leaves nothing behind. Records are checked for duplicates
+/// against earlier records of the batch too. Blank lines are skipped; a
+/// line that does not parse is reported and does not stop the batch.
+///
+/// With `dry_run` set to a number of conflicts, the batch runs the same way
+/// but the transaction is left uncommitted, and up to that many records
+/// that would not be added are listed.
+pub async fn add_memories_batch(
+    pool: &PgPool,
+    input: &str,
+    project_path: Option<&str>,
+    duplicate_check: DuplicateCheck,
+    lint: &LintRules,
+    dry_run: Option<usize>,
+) -> Result<AddMemoriesBatchData> {
+    let mut results = Vec::new();
+    let mut superseded = 0;
+
+    for (index, text) in input.lines().enumerate() {
+        let line = index + 1;
+        if text.trim().is_empty() {
+            continue;
+        }
+        let opts = match parse_batch_record(text, project_path, duplicate_check, lint) {
+            Ok(opts) => opts,
+            Err(error) => {
+                results.push(BatchOutcome::Invalid { line, error });
+                continue;
+            }
+        };
+        results.push(match insert_checked(pool, &opts).await? {
+            Inserted::Added { id, .. } => {
+                superseded += usize::from(opts.supersedes.is_some());
+                BatchOutcome::Added { line, id }
+            }
+            Inserted::Duplicate(dup) => BatchOutcome::Duplicate {
+                line,
+                existing_id: dup.id,
+                similarity: dup.similarity,
+            },
+            Inserted::Rejected(issues) => BatchOutcome::Rejected { line, issues },
+        });
+    }
+    if dry_run.is_none() {
+        db::commit(pool).await?;
+    }
+
+    let count = |f: fn(&BatchOutcome) -> bool| results.iter().filter(|r| f(r)).count();
+    let mut data = AddMemoriesBatchData {
+        dry_run: dry_run.is_some(),
+        added: count(|r| matches!(r, BatchOutcome::Added { .. })),
+        superseded,
+        duplicates: count(|r| matches!(r, BatchOutcome::Duplicate { .. })),
+        invalid: count(|r| matches!(r, BatchOutcome::Invalid { .. })),
+        rejected: count(|r| matches!(r, BatchOutcome::Rejected { .. })),
+        results,
+        conflicts: Vec::new(),
+    };
+    if let Some(limit) = dry_run {
+        data.conflicts = std::mem::take(&mut data.results)
+            .into_iter()
+            .filter(|r| !matches!(r, BatchOutcome::Added { .. }))
+            .take(limit)
+            .collect();
+    }
+    Ok(data)
+}
+
+fn parse_batch_record(
+    text: &str,
+    project_path: Option<&str>,
+    duplicate_check: DuplicateCheck,
+    lint: &LintRules,
+) -> std::result::Result<AddMemoryOptions, String> {
+    let record: BatchRecord = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    if record.content.trim().is_empty() {
+        return Err("content is empty".to_string());
+    }
+    let tags = match record.tags {
+        BatchTags::List(tags) => tags
+            .into_iter()
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect(),
+        BatchTags::Text(tags) => crate::cli::parse_tags(&tags),
+    };
+    let constraints = record
+        .constraints
+        .iter()
+        .map(|c| c.parse::<EnvConstraint>().map_err(|e| e.to_string()))
+        .collect::<std::result::Result<_, _>>()?;
+
+    Ok(AddMemoryOptions {
+        memory_type: record.memory_type,
+        content: record.content,
+        summary: record.summary,
+        tags,
+        confidence: record.confidence,
+        tier: match record.tier {
+            Scope::Global => Tier::Global,
+            Scope::Project => Tier::Project,
+        },
+        project_path: project_path.map(str::to_string),
+        source_session_id: record.session,
+        source_turn_id: record.turn,
+        supersedes: record.supersedes,
+        constraints,
+        duplicate_check,
+        lint: lint.clone(),
+    })
 }
 
 /// Update an existing memory's content
+///
+/// With `expected_updated_at` (optimistic concurrency), the update only
+/// applies if the memory is unchanged since that revision; otherwise a
+/// [`ConflictResponse`] carries the current revision for a retry. New
+/// content is checked against `lint` as on add.
 pub async fn update_memory(
     pool: &PgPool,
     id: Uuid,
     content: &str,
     tier: Option<Tier>,
     project_path: Option<&str>,
+    expected_updated_at: Option<DateTime<Utc>>,
+    lint: &LintRules,
 ) -> Result<serde_json::Value> {
     let scope = tier.map(|t| match t {
         Tier::Global => Scope::Global,
         Tier::Project | Tier::Both => Scope::Project,
     });
 
-    let updated = db::update_memory(pool, id, content, scope, project_path).await?;
+    let warnings = lint_change(pool, id, Some(content), None, lint).await?;
+    if lint.rejects(&warnings) {
+        return Ok(serde_json::to_value(LintRejectedResponse::new(warnings))?);
+    }
+
+    let updated =
+        db::update_memory(pool, id, content, scope, project_path, expected_updated_at).await?;
 
-    if updated {
-        let response = SuccessResponse::new(UpdateMemoryData { id });
-        Ok(serde_json::to_value(response)?)
-    } else {
+    if let Some(updated_at) = updated {
+        let response = SuccessResponse::new(UpdateMemoryData {
+            id,
+            updated_at,
+            supersedes: None,
+            warnings,
+        });
+        return Ok(serde_json::to_value(response)?);
+    }
+
+    // Nothing matched: either the memory is gone or it changed under us
+    match (expected_updated_at, db::get_memory(pool, id).await?) {
+        (Some(expected), Some(current)) => {
+            Ok(serde_json::to_value(ConflictResponse::new(id, expected, &current))?)
+        }
+        _ => {
+            let response = ErrorResponse::new(format!("Memory not found: {}", id));
+            Ok(serde_json::to_value(response)?)
+        }
+    }
+}
+
+/// Replace a memory with a new one carrying `content`, marking the old one
+/// superseded so `show-chain` keeps the earlier wording.
+///
+/// The new memory keeps the old one's type, tags, confidence, source and
+/// environment constraints, in the scope of `tier`. A conflict with
+/// `expected_updated_at` is reported as in [`update_memory`]; lint issues
+/// and duplicates of other memories as by [`add_memory`].
+#[allow(clippy::too_many_arguments)]
+pub async fn update_memory_as_new(
+    pool: &PgPool,
+    id: Uuid,
+    content: &str,
+    tier: Tier,
+    project_path: Option<&str>,
+    expected_updated_at: Option<DateTime<Utc>>,
+    duplicate_check: DuplicateCheck,
+    lint: &LintRules,
+) -> Result<serde_json::Value> {
+    let Some(old) = db::get_memory(pool, id).await? else {
         let response = ErrorResponse::new(format!("Memory not found: {}", id));
-        Ok(serde_json::to_value(response)?)
+        return Ok(serde_json::to_value(response)?);
+    };
+    if let Some(expected) = expected_updated_at.filter(|t| *t != old.updated_at) {
+        return Ok(serde_json::to_value(ConflictResponse::new(id, expected, &old))?);
+    }
+    if !old.is_active {
+        let response = ErrorResponse::new(format!(
+            "Memory {} is already superseded; update the memory that replaced it",
+            id
+        ));
+        return Ok(serde_json::to_value(response)?);
+    }
+
+    let opts = AddMemoryOptions {
+        memory_type: old.memory_type,
+        content: content.to_string(),
+        summary: None,
+        tags: old.tags.clone(),
+        confidence: old.confidence,
+        tier,
+        project_path: project_path.map(str::to_string),
+        source_session_id: old.source_session_id,
+        source_turn_id: old.source_turn_id,
+        supersedes: Some(id),
+        constraints: metadata_constraints(old.metadata.as_ref()),
+        duplicate_check,
+        lint: lint.clone(),
+    };
+    match insert_checked(pool, &opts).await? {
+        Inserted::Added { id: new_id, warnings } => {
+            let updated_at = match db::get_memory(pool, new_id).await? {
+                Some(memory) => memory.updated_at,
+                None => Utc::now(),
+            };
+            let response = SuccessResponse::new(UpdateMemoryData {
+                id: new_id,
+                updated_at,
+                supersedes: Some(id),
+                warnings,
+            });
+            Ok(serde_json::to_value(response)?)
+        }
+        Inserted::Duplicate(dup) => {
+            let response = DuplicateResponse::new(dup.id, &dup.scope, &dup.summary, dup.similarity)
+                .with_diff(&dup.content, content);
+            Ok(serde_json::to_value(response)?)
+        }
+        Inserted::Rejected(issues) => {
+            Ok(serde_json::to_value(LintRejectedResponse::new(issues))?)
+        }
     }
 }
 
+/// Change individual fields of a memory: content, type, confidence, tags.
+///
+/// Fields not in `edit` keep their values. Conflicts with
+/// `expected_updated_at` and lint issues are reported as in [`update_memory`].
+pub async fn edit_memory(
+    pool: &PgPool,
+    id: Uuid,
+    edit: &db::MemoryEdit,
+    expected_updated_at: Option<DateTime<Utc>>,
+    lint: &LintRules,
+) -> Result<serde_json::Value> {
+    if let Some(tag) = edit.add_tags.iter().find(|tag| {
+        edit.remove_tags.iter().any(|removed| removed.to_lowercase() == tag.to_lowercase())
+    }) {
+        let response = ErrorResponse::new(format!("Tag both added and removed: {}", tag));
+        return Ok(serde_json::to_value(response)?);
+    }
+
+    let warnings = lint_change(pool, id, edit.content.as_deref(), edit.memory_type, lint).await?;
+    if lint.rejects(&warnings) {
+        return Ok(serde_json::to_value(LintRejectedResponse::new(warnings))?);
+    }
+
+    if let Some(memory) = db::edit_memory(pool, id, edit, expected_updated_at).await? {
+        let response = SuccessResponse::new(EditMemoryData {
+            updated_at: memory.updated_at,
+            memory: memory.to_summary(),
+            warnings,
+        });
+        return Ok(serde_json::to_value(response)?);
+    }
+
+    match (expected_updated_at, db::get_memory(pool, id).await?) {
+        (Some(expected), Some(current)) => {
+            Ok(serde_json::to_value(ConflictResponse::new(id, expected, &current))?)
+        }
+        _ => {
+            let response = ErrorResponse::new(format!("Memory not found: {}", id));
+            Ok(serde_json::to_value(response)?)
+        }
+    }
+}
+
+/// Lint a memory as it will be after changing its content or type.
+///
+/// Empty when neither changes, lint is off, or the memory does not exist
+/// (the caller reports that).
+async fn lint_change(
+    pool: &PgPool,
+    id: Uuid,
+    content: Option<&str>,
+    memory_type: Option<MemoryType>,
+    rules: &LintRules,
+) -> Result<Vec<LintIssue>> {
+    if rules.mode == LintMode::Off || (content.is_none() && memory_type.is_none()) {
+        return Ok(Vec::new());
+    }
+    let Some(current) = db::get_memory(pool, id).await? else {
+        return Ok(Vec::new());
+    };
+    Ok(lint::check(
+        memory_type.unwrap_or(current.memory_type),
+        content.unwrap_or(&current.content),
+        rules,
+    ))
+}
+
 /// Delete a memory by ID
 pub async fn delete_memory(pool: &PgPool, id: Uuid) -> Result<serde_json::Value> {
     let deleted = db::delete_memory(pool, id).await?;
@@ -110,17 +651,42 @@ pub async fn delete_memory(pool: &PgPool, id: Uuid) -> Result<serde_json::Value>
 }
 
 /// Get a memory by ID
-pub async fn get_memory(pool: &PgPool, id: Uuid) -> Result<serde_json::Value> {
-    match db::get_memory(pool, id).await? {
+///
+/// With `as_of`, returns the memory from the same supersession chain that was
+/// active at that time, which may be a predecessor or successor of `id`.
+/// With `neighborhood`, the memories linked to it are returned too.
+pub async fn get_memory(
+    pool: &PgPool,
+    id: Uuid,
+    as_of: Option<DateTime<Utc>>,
+    neighborhood: bool,
+) -> Result<serde_json::Value> {
+    let memory = match as_of {
+        Some(as_of) => db::get_memory_as_of(pool, id, as_of).await?,
+        None => db::get_memory(pool, id).await?,
+    };
+
+    match memory {
         Some(memory) => {
+            let neighborhood = if neighborhood {
+                Some(db::list_memory_links(pool, memory.id).await?)
+            } else {
+                None
+            };
             let response = SuccessResponse::new(GetMemoryData {
+                updated_at: memory.updated_at,
                 memory: memory.to_summary(),
+                as_of,
+                neighborhood,
             });
             Ok(serde_json::to_value(response)?)
         }
         None => {
-            let response = ErrorResponse::new(format!("Memory not found: {}", id));
-            Ok(serde_json::to_value(response)?)
+            let message = match as_of {
+                Some(as_of) => format!("Memory not found as of {}: {}", as_of.to_rfc3339(), id),
+                None => format!("Memory not found: {}", id),
+            };
+            Ok(serde_json::to_value(ErrorResponse::new(message))?)
         }
     }
 }
@@ -138,6 +704,7 @@ mod tests {
         let opts = AddMemoryOptions {
             memory_type: MemoryType::Learning,
             content: "Test content".to_string(),
+            summary: None,
             tags: vec!["test".to_string()],
             confidence: Confidence::High,
             tier: Tier::Project,
@@ -145,6 +712,9 @@ mod tests {
             source_session_id: None,
             source_turn_id: None,
             supersedes: None,
+            constraints: vec![],
+            duplicate_check: DuplicateCheck::default(),
+            lint: LintRules::default(),
         };
 
         assert_eq!(opts.memory_type, MemoryType::Learning);
@@ -160,6 +730,7 @@ mod tests {
         let opts = AddMemoryOptions {
             memory_type: MemoryType::Learning,
             content: "New content".to_string(),
+            summary: None,
             tags: vec![],
             confidence: Confidence::High,
             tier: Tier::Project,
@@ -167,11 +738,31 @@ mod tests {
             source_session_id: None,
             source_turn_id: None,
             supersedes: Some(supersedes_id),
+            constraints: vec![],
+            duplicate_check: DuplicateCheck::default(),
+            lint: LintRules::default(),
         };
 
         assert_eq!(opts.supersedes, Some(supersedes_id));
     }
 
+    #[test]
+    fn test_add_memory_options_summary() {
+        let mut opts = parse_batch_record(
+            r#"{"type":"gotcha","content":"Pool size caps at 20. Raise it in config."}"#,
+            None,
+            DuplicateCheck::default(),
+            &LintRules::default(),
+        )
+        .unwrap();
+        assert_eq!(opts.summary(), "Pool size caps at 20.");
+
+        opts.summary = Some("  ".to_string());
+        assert_eq!(opts.summary(), "Pool size caps at 20.");
+        opts.summary = Some("Connection pool limit".to_string());
+        assert_eq!(opts.summary(), "Connection pool limit");
+    }
+
     #[test]
     fn test_tier_to_scope_mapping_global() {
         let tier = Tier::Global;
@@ -203,6 +794,97 @@ mod tests {
         assert_eq!(scope, Scope::Project);
     }
 
+    #[test]
+    fn test_parse_batch_record_defaults() {
+        let opts = parse_batch_record(
+            r#"{"type": "gotcha", "content": "Pool needs closing", "tags": "db, pool"}"#,
+            Some("/repo"),
+            DuplicateCheck::default(),
+            &LintRules::default(),
+        )
+        .unwrap();
+        assert_eq!(opts.memory_type, MemoryType::Gotcha);
+        assert_eq!(opts.tags, vec!["db", "pool"]);
+        assert_eq!(opts.confidence, Confidence::High);
+        assert_eq!(opts.tier, Tier::Project);
+        assert_eq!(opts.project_path.as_deref(), Some("/repo"));
+
+        let opts = parse_batch_record(
+            r#"{"type": "api", "content": "x", "tags": ["a", " "], "tier": "global",
+                "constraints": ["os=linux"]}"#,
+            None,
+            DuplicateCheck::default(),
+            &LintRules::default(),
+        )
+        .unwrap();
+        assert_eq!(opts.tags, vec!["a"]);
+        assert_eq!(opts.tier, Tier::Global);
+        assert_eq!(opts.constraints.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_batch_record_errors() {
+        let parse = |text| parse_batch_record(text, None, DuplicateCheck::default(), &LintRules::default());
+        assert!(parse(r#"{"type": "todo", "content": "x"}"#).is_err());
+        assert!(parse(r#"{"type": "api", "content": " "}"#).is_err());
+        assert!(parse(r#"{"type": "api", "content": "x", "tag": "a"}"#).is_err());
+        assert!(parse(r#"{"type": "api", "content": "x", "constraints": ["os"]}"#).is_err());
+        assert!(parse("not json").is_err());
+    }
+
+    #[test]
+    fn test_batch_outcome_shape() {
+        let json = serde_json::to_value(BatchOutcome::Duplicate {
+            line: 3,
+            existing_id: Uuid::nil(),
+            similarity: None,
+        })
+        .unwrap();
+        assert_eq!(json["status"], "duplicate");
+        assert_eq!(json["line"], 3);
+        assert!(json.get("existingId").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_add_memories_batch_reports_each_line() {
+        use crate::db::migrations::with_scratch_database;
+
+        let input = [
+            r#"{"type": "gotcha", "content": "Pool needs closing"}"#,
+            r#"{"type": "api", "content": "Retries back off exponentially"}"#,
+            "not json",
+            r#"{"type": "gotcha", "content": "Pool needs closing"}"#,
+            r#"{"type": "learning", "content": "Builds use make"}"#,
+        ]
+        .join("\n");
+        let Some(result) = with_scratch_database(|pool| async move {
+            add_memories_batch(
+                &pool,
+                &input,
+                Some("/work/app"),
+                DuplicateCheck::default(),
+                &LintRules::default(),
+                None,
+            )
+            .await
+        })
+        .await
+        else {
+            return;
+        };
+
+        let data = result.unwrap();
+        let lines: Vec<_> = data.results.iter().map(BatchOutcome::line).collect();
+        assert_eq!(lines, vec![1, 2, 3, 4, 5]);
+        assert_eq!((data.added, data.duplicates, data.invalid), (3, 1, 1));
+        match (&data.results[0], &data.results[3]) {
+            (BatchOutcome::Added { id, .. }, BatchOutcome::Duplicate { existing_id, .. }) => {
+                assert_eq!(id, existing_id);
+            }
+            other => panic!("Expected line 4 to duplicate line 1, got {other:?}"),
+        }
+    }
+
     // Note: Full integration tests require a database connection
     // and are placed in tests/integration/memory_tests.rs
 }
@@ -1,17 +1,106 @@
+pub mod audit;
+pub mod briefing;
+pub mod bundle;
+pub mod config;
+pub mod doctor;
+pub mod drift;
+pub mod export;
+pub mod extract;
+pub mod fixtures;
+pub mod gotchas;
+pub mod health;
+pub mod history;
+pub mod install;
+pub mod latency;
+pub mod legacy;
+pub mod links;
 pub mod maintenance;
 pub mod memory;
+pub mod mute;
+pub mod onboard;
+pub mod pin;
+pub mod policy;
+pub mod projects;
+pub mod provenance;
+pub mod ranking;
+pub mod recall;
+pub mod review;
+pub mod schema;
 pub mod search;
+pub mod sessions;
+pub mod staging;
 pub mod stats;
+pub mod tags;
+pub mod transcript;
+pub mod turns;
+pub mod usage;
+pub mod verify;
+pub mod workspaces;
 
+pub use audit::{audit, AuditResult};
+pub use briefing::{set_briefing, show_briefing, SetBriefingResult, ShowBriefingResult};
+pub use bundle::{debug_bundle, DebugBundleResult};
+pub use config::{config_set, config_show, ConfigSetResult, ConfigShowResult};
+pub use doctor::{doctor, DoctorReport};
+pub use drift::{drift_check, DriftCheckResult};
+pub use export::{export_conversations, ConversationRecord, ExportConversationsResult, ExportFormat};
+pub use extract::{
+    extract_queued, extract_worker, extraction_log, reserve_extraction, ExtractQueuedResult,
+    ExtractWorkerResult, ExtractionLogResult, ReserveExtractionResult,
+};
+pub use fixtures::{generate_fixtures, FixtureOptions, GenerateFixturesResult};
+pub use gotchas::{mine_gotchas, MineGotchasResult};
 pub use maintenance::{
-    consolidate, list_superseded, prune, prune_data, purge_superseded, save_session_summary,
-    show_chain,
+    consolidate, decay, dedupe_across_scopes, list_superseded, prune, prune_data,
+    purge_superseded, save_session_summary, show_chain, with_maintenance_lock, DecayResult,
+    DedupeAcrossScopesResult, MaintenanceSkipped,
+};
+pub use health::{health_report, HealthReport};
+pub use history::{diff, history, HistoryResult, RevisionDiffResult, RevisionEntry};
+pub use install::{install_hooks, InstallHooksResult};
+pub use latency::{turn_latency, TurnLatencyReport};
+pub use legacy::{migrate_legacy, LegacyMigrationReport};
+pub use links::{
+    link, supersede, unlink, unsupersede, LinkRelation, LinkResult, SupersedeResult, UnlinkResult,
+    UnsupersedeResult,
 };
 pub use memory::{
-    add_memory, delete_memory, get_memory, update_memory, AddMemoryOptions, AddMemoryResult,
+    add_memories_batch, add_memory, delete_memory, edit_memory, get_memory, update_memory,
+    update_memory_as_new, AddMemoriesBatchData, AddMemoryOptions, AddMemoryResult, BatchOutcome,
+};
+pub use mute::{mute, MuteResult};
+pub use pin::{pin, PinResult};
+pub use onboard::{onboard, OnboardResult};
+pub use policy::{
+    policy_set, policy_show, policy_unset, retention_policy, PolicySetResult, PolicyShowResult,
+};
+pub use projects::{
+    project_alias_add, project_alias_list, project_alias_remove, ProjectAliasRemoveResult,
+    ProjectAliasResult, ProjectListResult,
 };
+pub use provenance::{show_provenance, ProvenanceResult, SourceTurn};
+pub use recall::{
+    embed_memories, embed_turns, recall, EmbedMemoriesResult, EmbedTurnsResult, RecallResult, RecalledExchange,
+};
+pub use review::{list_review_queue, resolve_review};
+pub use schema::{schema, SchemaFormat};
 pub use search::{
-    get_context, list_recent, search_by_type, search_keyword, ContextResult, ListRecentResult,
-    MemorySearchItem, SearchByTypeOptions, SearchOptions, SearchResult,
+    get_context, list_recent, render_context_template, search, search_by_type, search_fts,
+    search_keyword, ContextLayout, ContextResult,
+    HybridMatch, HybridSearchOptions, HybridSearchResult, ListRecentResult, MemorySearchItem,
+    SearchByTypeOptions, SearchMode, SearchOptions, SearchResult, SourceScore,
+};
+pub use sessions::{list_sessions, search_meta, ListSessionsOptions, ListSessionsResult, SearchMetaResult};
+pub use staging::{commit_memory, expire_proposals, propose_memory, ProposeMemoryData};
+pub use stats::{
+    get_stats, rollup_access, top_memories, AccessRollup, ConfidenceCounts, MemoryStats,
+    ProjectCount, ScopeCounts, StatsOptions, TopMemoriesResult, TypeCounts,
+};
+pub use tags::{list_tags, retag, search_by_tag, ListTagsResult, RetagResult};
+pub use transcript::{show_session, SessionTranscript, TranscriptEvent};
+pub use turns::{get_turn, GetTurnResult, TurnInfo};
+pub use usage::{where_used, WhereUsedReport};
+pub use verify::{verify, VerifyResult};
+pub use workspaces::{
+    workspace_add_project, workspace_create, workspace_list, WorkspaceListResult, WorkspaceResult,
 };
-pub use stats::{get_stats, ConfidenceCounts, MemoryStats, ScopeCounts, StatsOptions, TypeCounts};
@@ -0,0 +1,66 @@
+//! Mute command: keep a memory out of one session's context
+//!
+//! A memory that is right in general can be noise for the task at hand.
+//! Muting it leaves the memory itself untouched and only stops the session
+//! from having it injected again, e.g. when the context is reloaded after
+//! compaction or `/clear`. Mutes end with the session.
+
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::error::{HippocampusError, Result};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Result of mute command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MuteResult {
+    pub memory_id: Uuid,
+    /// Database ID of the session
+    pub session_id: Uuid,
+    /// Whether the memory is muted for the session now
+    pub muted: bool,
+    /// False when the memory already was in that state
+    pub changed: bool,
+    /// All memories muted for the session
+    pub muted_memories: Vec<Uuid>,
+}
+
+// ============================================================================
+// Command
+// ============================================================================
+
+/// Mute (or with `undo`, unmute) a memory for the rest of a session.
+///
+/// `session` is a database UUID or a Claude session ID.
+pub async fn mute(pool: &PgPool, memory_id: Uuid, session: &str, undo: bool) -> Result<MuteResult> {
+    let found = match Uuid::parse_str(session) {
+        Ok(id) => queries::find_session_by_id(pool, id).await?,
+        Err(_) => queries::find_session_by_claude_id(pool, session).await?,
+    };
+    let session = found.ok_or_else(|| HippocampusError::SessionNotFound(session.to_string()))?;
+
+    if queries::get_memory(pool, memory_id).await?.is_none() {
+        return Err(HippocampusError::NotFound(memory_id.to_string()));
+    }
+
+    let changed = if undo {
+        queries::unmute_memory(pool, session.id, memory_id).await?
+    } else {
+        queries::mute_memory(pool, session.id, memory_id).await?
+    };
+    let muted_memories = queries::list_muted_memories(pool, session.id).await?;
+
+    Ok(MuteResult {
+        memory_id,
+        session_id: session.id,
+        muted: !undo,
+        changed,
+        muted_memories,
+    })
+}
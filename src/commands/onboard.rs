@@ -0,0 +1,281 @@
+//! Onboard command: a project brief for a new contributor
+//!
+//! Assembles what the store knows about a project into one report: the most
+//! relied-upon architecture memories, the active conventions, known gotchas
+//! grouped by area, and what recent sessions worked on. Everything comes from
+//! the database; nothing is read from the project's files.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::error::Result;
+use crate::models::{Memory, MemorySummary, MemoryType, Session};
+
+/// Area used for gotchas without tags
+const UNTAGGED_AREA: &str = "general";
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A recent session and what it was about
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionBrief {
+    pub session_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    pub started_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<DateTime<Utc>>,
+    pub summary: String,
+}
+
+/// Result of onboard command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnboardResult {
+    pub project_path: String,
+    /// Architecture memories, most trusted and most used first
+    pub architecture: Vec<MemorySummary>,
+    /// Active conventions, most trusted first
+    pub conventions: Vec<MemorySummary>,
+    /// Gotchas keyed by their first tag
+    pub gotchas_by_area: BTreeMap<String, Vec<MemorySummary>>,
+    pub recent_sessions: Vec<SessionBrief>,
+    /// The same report as markdown
+    pub brief: String,
+}
+
+// ============================================================================
+// Command
+// ============================================================================
+
+/// Build an onboarding brief for a project.
+///
+/// `limit` caps the architecture and convention sections; `sessions` caps the
+/// number of recent session summaries.
+pub async fn onboard(
+    pool: &PgPool,
+    project_path: &str,
+    limit: usize,
+    sessions: i64,
+) -> Result<OnboardResult> {
+    let memories = queries::list_project_memories_by_types(
+        pool,
+        project_path,
+        &[MemoryType::Architecture, MemoryType::Convention, MemoryType::Gotcha],
+    )
+    .await?;
+    let recent = queries::list_session_summaries(pool, project_path, sessions).await?;
+
+    Ok(build_report(project_path, memories, &recent, limit))
+}
+
+/// Sort and group memories into the report sections.
+fn build_report(
+    project_path: &str,
+    memories: Vec<Memory>,
+    sessions: &[Session],
+    limit: usize,
+) -> OnboardResult {
+    let mut architecture = Vec::new();
+    let mut conventions = Vec::new();
+    let mut gotchas = Vec::new();
+    for memory in memories {
+        match memory.memory_type {
+            MemoryType::Architecture => architecture.push(memory),
+            MemoryType::Convention => conventions.push(memory),
+            _ => gotchas.push(memory),
+        }
+    }
+
+    // Most trusted first; among equals, the ones sessions actually load
+    let by_trust = |a: &Memory, b: &Memory| {
+        a.confidence
+            .sort_order()
+            .cmp(&b.confidence.sort_order())
+            .then(b.access_count.cmp(&a.access_count))
+    };
+    architecture.sort_by(by_trust);
+    conventions.sort_by(by_trust);
+    gotchas.sort_by(by_trust);
+
+    let mut gotchas_by_area: BTreeMap<String, Vec<MemorySummary>> = BTreeMap::new();
+    for gotcha in &gotchas {
+        let area = gotcha
+            .tags
+            .first()
+            .map(|t| t.to_lowercase())
+            .unwrap_or_else(|| UNTAGGED_AREA.to_string());
+        gotchas_by_area.entry(area).or_default().push(gotcha.to_summary());
+    }
+
+    let architecture: Vec<MemorySummary> =
+        architecture.iter().take(limit).map(Memory::to_summary).collect();
+    let conventions: Vec<MemorySummary> =
+        conventions.iter().take(limit).map(Memory::to_summary).collect();
+    let recent_sessions: Vec<SessionBrief> = sessions.iter().map(session_brief).collect();
+
+    let brief = format_brief(
+        project_path,
+        &architecture,
+        &conventions,
+        &gotchas_by_area,
+        &recent_sessions,
+    );
+
+    OnboardResult {
+        project_path: project_path.to_string(),
+        architecture,
+        conventions,
+        gotchas_by_area,
+        recent_sessions,
+        brief,
+    }
+}
+
+fn session_brief(session: &Session) -> SessionBrief {
+    // save-session-summary stores {"summary": "..."}; other shapes are shown raw
+    let summary = match &session.summary {
+        Some(value) => value
+            .get("summary")
+            .and_then(|s| s.as_str())
+            .map(String::from)
+            .unwrap_or_else(|| value.to_string()),
+        None => String::new(),
+    };
+
+    SessionBrief {
+        session_id: session.id,
+        branch: session.git_status.as_ref().map(|g| g.branch.clone()),
+        started_at: session.started_at,
+        ended_at: session.ended_at,
+        summary,
+    }
+}
+
+/// Render the report as markdown
+fn format_brief(
+    project_path: &str,
+    architecture: &[MemorySummary],
+    conventions: &[MemorySummary],
+    gotchas_by_area: &BTreeMap<String, Vec<MemorySummary>>,
+    sessions: &[SessionBrief],
+) -> String {
+    let mut brief = format!("# Onboarding: {}\n", project_path);
+
+    let push_entries = |brief: &mut String, title: &str, entries: &[MemorySummary]| {
+        brief.push_str(&format!("\n## {}\n\n", title));
+        if entries.is_empty() {
+            brief.push_str("Nothing recorded yet.\n");
+        }
+        for entry in entries {
+            brief.push_str(&format!("- {} {}\n", entry.confidence.symbol(), entry.summary));
+        }
+    };
+
+    push_entries(&mut brief, "Architecture", architecture);
+    push_entries(&mut brief, "Conventions", conventions);
+
+    brief.push_str("\n## Known Gotchas\n");
+    if gotchas_by_area.is_empty() {
+        brief.push_str("\nNothing recorded yet.\n");
+    }
+    for (area, entries) in gotchas_by_area {
+        brief.push_str(&format!("\n### {}\n\n", area));
+        for entry in entries {
+            brief.push_str(&format!("- {} {}\n", entry.confidence.symbol(), entry.summary));
+        }
+    }
+
+    brief.push_str("\n## Recent Sessions\n\n");
+    if sessions.is_empty() {
+        brief.push_str("No session summaries yet.\n");
+    }
+    for session in sessions {
+        let date = session.ended_at.unwrap_or(session.started_at).format("%Y-%m-%d");
+        match &session.branch {
+            Some(branch) => brief.push_str(&format!("- {} ({}): {}\n", date, branch, session.summary)),
+            None => brief.push_str(&format!("- {}: {}\n", date, session.summary)),
+        }
+    }
+
+    brief
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_memory, Confidence, SessionStatus};
+
+    fn memory(memory_type: MemoryType, content: &str, confidence: Confidence, tags: &[&str]) -> Memory {
+        Memory {
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            confidence,
+            ..test_memory(content, memory_type)
+        }
+    }
+
+    fn session(summary: serde_json::Value) -> Session {
+        Session {
+            id: Uuid::new_v4(),
+            claude_session_id: "s".to_string(),
+            project_path: Some("/test".to_string()),
+            git_status: None,
+            environment: None,
+            models_used: None,
+            status: SessionStatus::Completed,
+            summary: Some(summary),
+            started_at: Utc::now(),
+            ended_at: None,
+            created_at: Utc::now(),
+            skipped_prompts: 0,
+        }
+    }
+
+    #[test]
+    fn test_build_report_sections() {
+        let memories = vec![
+            memory(MemoryType::Architecture, "Hooks talk to the db directly", Confidence::Medium, &[]),
+            memory(MemoryType::Architecture, "Commands return JSON values", Confidence::High, &[]),
+            memory(MemoryType::Convention, "Tests live inline", Confidence::High, &[]),
+            memory(MemoryType::Gotcha, "sqlx needs DATABASE_URL offline", Confidence::High, &["SQLx", "build"]),
+            memory(MemoryType::Gotcha, "Stop hook output goes to stdout", Confidence::Low, &[]),
+        ];
+        let report = build_report("/test", memories, &[], 1);
+
+        assert_eq!(report.architecture.len(), 1);
+        assert_eq!(report.architecture[0].summary, "Commands return JSON values");
+        assert_eq!(report.conventions.len(), 1);
+        assert_eq!(report.gotchas_by_area["sqlx"].len(), 1);
+        assert_eq!(report.gotchas_by_area[UNTAGGED_AREA].len(), 1);
+        assert!(report.brief.contains("### sqlx"));
+        assert!(report.brief.contains("No session summaries yet."));
+    }
+
+    #[test]
+    fn test_session_brief_summary_shapes() {
+        let brief = session_brief(&session(serde_json::json!({"summary": "Added FTS"})));
+        assert_eq!(brief.summary, "Added FTS");
+
+        let brief = session_brief(&session(serde_json::json!({"done": ["a"]})));
+        assert_eq!(brief.summary, r#"{"done":["a"]}"#);
+    }
+
+    #[test]
+    fn test_format_brief_empty_store() {
+        let brief = format_brief("/test", &[], &[], &BTreeMap::new(), &[]);
+        assert!(brief.starts_with("# Onboarding: /test\n"));
+        assert_eq!(brief.matches("Nothing recorded yet.").count(), 3);
+    }
+}
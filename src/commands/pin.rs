@@ -0,0 +1,44 @@
+//! Pin commands: pin, unpin
+//!
+//! A pinned memory heads every context block of its scope, whatever its
+//! rank, and counts toward the block's limit without ever being cut for it.
+//! `prune`, `decay` and `consolidate` leave pinned memories alone.
+
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::error::{HippocampusError, Result};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Result of pin and unpin
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PinResult {
+    pub memory_id: Uuid,
+    /// Whether the memory is pinned now
+    pub pinned: bool,
+    /// False when the memory already was in that state
+    pub changed: bool,
+}
+
+// ============================================================================
+// Command
+// ============================================================================
+
+/// Pin (or unpin) a memory
+pub async fn pin(pool: &PgPool, memory_id: Uuid, pinned: bool) -> Result<PinResult> {
+    let changed = queries::set_memory_pinned(pool, memory_id, pinned)
+        .await?
+        .ok_or_else(|| HippocampusError::NotFound(memory_id.to_string()))?;
+
+    Ok(PinResult {
+        memory_id,
+        pinned,
+        changed,
+    })
+}
@@ -0,0 +1,175 @@
+//! Policy commands: policy show, policy set, policy unset
+//!
+//! `[prune]` in the config files sets retention per machine. The retention
+//! policy is stored in the database instead, so a cron job and an
+//! interactive shell pointed at the same database prune alike. Keys set in
+//! the policy take precedence over `[prune]`; day counts given to a
+//! maintenance command still win over both.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sqlx::postgres::PgPool;
+
+use crate::config::{DbConfig, PruneThresholds};
+use crate::db::queries;
+use crate::error::{HippocampusError, Result};
+
+/// Settings key the policy is stored under
+pub const POLICY_SETTING: &str = "retention_policy";
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Result of policy show
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyShowResult {
+    /// Retention the maintenance commands use
+    pub policy: PruneThresholds,
+    /// Keys set in the database; the others come from `[prune]`
+    pub stored: Map<String, Value>,
+}
+
+/// Result of policy set and policy unset
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicySetResult {
+    pub key: String,
+    /// The stored value; None after unset
+    pub value: Option<i64>,
+    pub policy: PruneThresholds,
+    pub stored: Map<String, Value>,
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// The retention maintenance commands use: `[prune]` overridden by the
+/// keys stored in the database.
+pub async fn retention_policy(pool: &PgPool, config: &DbConfig) -> Result<PruneThresholds> {
+    merge(&config.prune, &stored_policy(pool).await?)
+}
+
+/// Show the effective policy and which keys the database sets.
+pub async fn policy_show(pool: &PgPool, config: &DbConfig) -> Result<PolicyShowResult> {
+    let stored = stored_policy(pool).await?;
+    Ok(PolicyShowResult {
+        policy: merge(&config.prune, &stored)?,
+        stored,
+    })
+}
+
+/// Store one key of the policy.
+pub async fn policy_set(pool: &PgPool, config: &DbConfig, key: &str, days: i64) -> Result<PolicySetResult> {
+    validate_key(key)?;
+    if days < 1 {
+        return Err(HippocampusError::Validation(format!(
+            "Invalid value for {}: {} (days must be at least 1)",
+            key, days
+        )));
+    }
+
+    let mut stored = stored_policy(pool).await?;
+    stored.insert(key.to_string(), Value::from(days));
+    save(pool, config, key, Some(days), stored).await
+}
+
+/// Remove one key from the policy, so `[prune]` applies to it again.
+pub async fn policy_unset(pool: &PgPool, config: &DbConfig, key: &str) -> Result<PolicySetResult> {
+    validate_key(key)?;
+
+    let mut stored = stored_policy(pool).await?;
+    stored.remove(key);
+    save(pool, config, key, None, stored).await
+}
+
+async fn save(
+    pool: &PgPool,
+    config: &DbConfig,
+    key: &str,
+    value: Option<i64>,
+    stored: Map<String, Value>,
+) -> Result<PolicySetResult> {
+    let policy = merge(&config.prune, &stored)?;
+    queries::set_setting(pool, POLICY_SETTING, &Value::Object(stored.clone())).await?;
+    Ok(PolicySetResult {
+        key: key.to_string(),
+        value,
+        policy,
+        stored,
+    })
+}
+
+/// The policy keys stored in the database
+async fn stored_policy(pool: &PgPool) -> Result<Map<String, Value>> {
+    match queries::get_setting(pool, POLICY_SETTING).await? {
+        Some(Value::Object(stored)) => Ok(stored),
+        _ => Ok(Map::new()),
+    }
+}
+
+/// Override `base` with the stored keys
+fn merge(base: &PruneThresholds, stored: &Map<String, Value>) -> Result<PruneThresholds> {
+    let mut merged = match serde_json::to_value(base)? {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    };
+    merged.extend(stored.iter().map(|(k, v)| (k.clone(), v.clone())));
+    serde_json::from_value(Value::Object(merged))
+        .map_err(|e| HippocampusError::Config(format!("Invalid retention policy in the database: {}", e)))
+}
+
+/// Check `key` names a retention setting
+fn validate_key(key: &str) -> Result<()> {
+    let keys = match serde_json::to_value(PruneThresholds::default())? {
+        Value::Object(map) => map.keys().cloned().collect::<Vec<_>>(),
+        _ => Vec::new(),
+    };
+    if keys.iter().any(|k| k == key) {
+        return Ok(());
+    }
+    Err(HippocampusError::Validation(format!(
+        "Unknown policy key: {} (expected one of: {})",
+        key,
+        keys.join(", ")
+    )))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_overrides_config() {
+        let base = PruneThresholds {
+            low_days: 7,
+            ..PruneThresholds::default()
+        };
+        let stored: Map<String, Value> =
+            serde_json::from_str(r#"{"medium_days": 45, "sessions_days": 365}"#).unwrap();
+        let merged = merge(&base, &stored).unwrap();
+        assert_eq!(merged.low_days, 7);
+        assert_eq!(merged.medium_days, 45);
+        assert_eq!(merged.sessions_days, 365);
+        assert_eq!(merged.turns_days, 30);
+    }
+
+    #[test]
+    fn test_merge_rejects_bad_stored_value() {
+        let stored: Map<String, Value> = serde_json::from_str(r#"{"low_days": "soon"}"#).unwrap();
+        assert!(merge(&PruneThresholds::default(), &stored).is_err());
+    }
+
+    #[test]
+    fn test_validate_key() {
+        assert!(validate_key("superseded_days").is_ok());
+        let err = validate_key("low").unwrap_err().to_string();
+        assert!(err.contains("low_days"));
+    }
+}
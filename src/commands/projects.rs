@@ -0,0 +1,137 @@
+//! Project commands: project alias add, project alias list, project alias remove
+//!
+//! Project memories are keyed by the path of their project, so moving a
+//! repository leaves them behind under the old path. An alias records such
+//! a path as belonging to a project: scoped reads include the memories of
+//! every alias, and new memories are stored under the canonical path.
+//! Symlinks need no alias, as project paths are resolved before use.
+
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+
+use crate::db::{self, normalize_project_path, Project};
+use crate::error::{HippocampusError, Result};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Result of project alias add
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectAliasResult {
+    pub alias: String,
+    /// False when the alias was already recorded for the project
+    pub added: bool,
+    pub project: Project,
+}
+
+/// Result of project alias list
+#[derive(Debug, Serialize)]
+pub struct ProjectListResult {
+    pub count: usize,
+    pub projects: Vec<Project>,
+}
+
+/// Result of project alias remove
+#[derive(Debug, Serialize)]
+pub struct ProjectAliasRemoveResult {
+    pub alias: String,
+    pub removed: bool,
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Record `alias` as another path of `project`, or of `current_project`
+/// when not given. A project that is itself an alias stands for the
+/// project it belongs to.
+pub async fn project_alias_add(
+    pool: &PgPool,
+    alias: &str,
+    project: Option<&str>,
+    current_project: Option<&str>,
+) -> Result<ProjectAliasResult> {
+    let target = project
+        .map(normalize_project_path)
+        .or_else(|| current_project.map(str::to_string))
+        .ok_or_else(|| {
+            HippocampusError::Validation("No project to alias: pass --project or set PROJECT_PATH".to_string())
+        })?;
+    let canonical = match db::find_project(pool, &target).await? {
+        Some(project) => project.canonical_path,
+        None => target,
+    };
+    let alias = trim_path(alias);
+    if alias == canonical {
+        return Err(HippocampusError::Validation(format!("{} is the project's own path", alias)));
+    }
+
+    let mut added = true;
+    if let Some(other) = db::find_project(pool, &alias).await? {
+        if other.canonical_path == alias {
+            return Err(HippocampusError::Validation(format!(
+                "{} is a project with aliases of its own; remove them first",
+                alias
+            )));
+        }
+        if other.canonical_path != canonical {
+            return Err(HippocampusError::Validation(format!(
+                "{} is already an alias of {}; remove it first",
+                alias, other.canonical_path
+            )));
+        }
+        added = false;
+    }
+    if added {
+        db::add_project_alias(pool, &canonical, &alias).await?;
+    }
+
+    let project = db::find_project(pool, &canonical)
+        .await?
+        .ok_or_else(|| HippocampusError::NotFound(format!("Project not found: {}", canonical)))?;
+    Ok(ProjectAliasResult { alias, added, project })
+}
+
+/// Every project with aliases, by canonical path
+pub async fn project_alias_list(pool: &PgPool) -> Result<ProjectListResult> {
+    let projects = db::list_projects(pool).await?;
+    Ok(ProjectListResult {
+        count: projects.len(),
+        projects,
+    })
+}
+
+/// Forget an alias; its memories stay under it, out of the project's reads
+pub async fn project_alias_remove(pool: &PgPool, alias: &str) -> Result<ProjectAliasRemoveResult> {
+    let alias = trim_path(alias);
+    let removed = db::remove_project_alias(pool, &alias).await?;
+    Ok(ProjectAliasRemoveResult { alias, removed })
+}
+
+/// An alias as given, without a trailing slash. Aliases name paths memories
+/// were stored under, which need not exist any more, so they are not
+/// resolved like project paths.
+fn trim_path(path: &str) -> String {
+    match path.trim().trim_end_matches('/') {
+        "" => path.trim().to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trim_path() {
+        assert_eq!(trim_path("/old/repo/"), "/old/repo");
+        assert_eq!(trim_path(" /old/repo "), "/old/repo");
+        assert_eq!(trim_path("/"), "/");
+    }
+}
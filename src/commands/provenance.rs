@@ -0,0 +1,227 @@
+//! Show-provenance command: where a memory came from
+//!
+//! Memories extracted by the hooks record the session and turn they were
+//! extracted from. This follows those links back to the conversation: the
+//! session, the prompt and response of the turn, and the tool calls made
+//! around the time the memory was written.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::db::queries::{self, ToolCall};
+use crate::error::{HippocampusError, Result};
+use crate::models::{Memory, Session, Turn};
+
+/// Longest assistant response excerpt shown
+const RESPONSE_EXCERPT_CHARS: usize = 500;
+
+/// Without a source turn, tool calls this close to the memory's creation are shown
+const TOOL_CALL_WINDOW_MINUTES: i64 = 5;
+
+/// Most tool calls shown
+const MAX_TOOL_CALLS: usize = 20;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// The turn a memory was extracted from
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceTurn {
+    pub id: Uuid,
+    pub turn_number: i32,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub user_prompt: String,
+    /// Start of the assistant's response
+    pub response_excerpt: Option<String>,
+    /// Whether the excerpt is shorter than the response
+    pub response_truncated: bool,
+}
+
+/// Result of show-provenance command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvenanceResult {
+    pub memory: Memory,
+    /// Session the memory was extracted in; None for memories added by hand
+    pub session: Option<Session>,
+    pub turn: Option<SourceTurn>,
+    /// Tool calls of the source turn, or near the memory's creation when
+    /// the turn is unknown
+    pub tool_calls: Vec<ToolCall>,
+    /// Why part of the provenance is missing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+// ============================================================================
+// Command
+// ============================================================================
+
+/// Trace a memory back to the session and turn it was extracted from.
+pub async fn show_provenance(pool: &PgPool, memory_id: Uuid) -> Result<ProvenanceResult> {
+    let memory = queries::get_memory(pool, memory_id)
+        .await?
+        .ok_or_else(|| HippocampusError::NotFound(memory_id.to_string()))?;
+
+    let session = match memory.source_session_id {
+        Some(id) => queries::find_session_by_id(pool, id).await?,
+        None => None,
+    };
+    let turn = match memory.source_turn_id {
+        Some(id) => queries::find_turn_by_id(pool, id).await?,
+        None => None,
+    };
+
+    let tool_calls = match &session {
+        Some(session) => {
+            let calls = queries::list_session_tool_calls(pool, session.id).await?;
+            calls_around(calls, turn.as_ref(), memory.created_at)
+        }
+        None => Vec::new(),
+    };
+
+    let note = match (&memory.source_session_id, &session, &turn) {
+        (None, _, _) => Some("Memory was not extracted from a session".to_string()),
+        (Some(_), None, _) => Some("Source session no longer exists".to_string()),
+        (Some(_), Some(_), None) if memory.source_turn_id.is_some() => {
+            Some("Source turn no longer exists".to_string())
+        }
+        _ => None,
+    };
+
+    Ok(ProvenanceResult {
+        turn: turn.map(source_turn),
+        memory,
+        session,
+        tool_calls,
+        note,
+    })
+}
+
+/// The tool calls of `turn`, or without one those within
+/// [`TOOL_CALL_WINDOW_MINUTES`] of `at`; at most [`MAX_TOOL_CALLS`],
+/// nearest to `at` first kept, in call order.
+fn calls_around(calls: Vec<ToolCall>, turn: Option<&Turn>, at: DateTime<Utc>) -> Vec<ToolCall> {
+    let window = Duration::minutes(TOOL_CALL_WINDOW_MINUTES);
+    let mut selected: Vec<ToolCall> = calls
+        .into_iter()
+        .filter(|call| match turn {
+            Some(turn) => call.turn_id == Some(turn.id),
+            None => (call.called_at - at).abs() <= window,
+        })
+        .collect();
+
+    if selected.len() > MAX_TOOL_CALLS {
+        selected.sort_by_key(|call| (call.called_at - at).abs());
+        selected.truncate(MAX_TOOL_CALLS);
+    }
+    selected.sort_by_key(|call| call.called_at);
+    selected
+}
+
+fn source_turn(turn: Turn) -> SourceTurn {
+    let (response_excerpt, response_truncated) = match &turn.assistant_response {
+        Some(response) if response.chars().count() > RESPONSE_EXCERPT_CHARS => {
+            let cut: String = response.chars().take(RESPONSE_EXCERPT_CHARS - 3).collect();
+            (Some(format!("{}...", cut.trim_end())), true)
+        }
+        Some(response) => (Some(response.clone()), false),
+        None => (None, false),
+    };
+    SourceTurn {
+        id: turn.id,
+        turn_number: turn.turn_number,
+        started_at: turn.started_at,
+        ended_at: turn.ended_at,
+        user_prompt: turn.user_prompt,
+        response_excerpt,
+        response_truncated,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 10, minute, 0).unwrap()
+    }
+
+    fn turn(response: Option<String>) -> Turn {
+        Turn {
+            id: Uuid::new_v4(),
+            session_id: None,
+            turn_number: 3,
+            user_prompt: "Why does the build fail?".to_string(),
+            assistant_response: response,
+            model_used: None,
+            input_tokens: None,
+            output_tokens: None,
+            started_at: at(0),
+            ended_at: Some(at(1)),
+            created_at: at(0),
+        }
+    }
+
+    fn call(turn_id: Option<Uuid>, minute: u32) -> ToolCall {
+        ToolCall {
+            id: Uuid::new_v4(),
+            session_id: None,
+            turn_id,
+            tool_name: "Bash".to_string(),
+            parameters: None,
+            result_summary: None,
+            called_at: at(minute),
+        }
+    }
+
+    #[test]
+    fn test_calls_around_uses_source_turn() {
+        let source = turn(None);
+        let calls = vec![call(None, 1), call(Some(source.id), 2), call(Some(Uuid::new_v4()), 2)];
+        let selected = calls_around(calls, Some(&source), at(2));
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].turn_id, Some(source.id));
+    }
+
+    #[test]
+    fn test_calls_around_time_window() {
+        let calls = vec![call(None, 1), call(None, 12), call(None, 16), call(None, 30)];
+        let minutes: Vec<DateTime<Utc>> = calls_around(calls, None, at(15))
+            .iter()
+            .map(|c| c.called_at)
+            .collect();
+        assert_eq!(minutes, vec![at(12), at(16)]);
+    }
+
+    #[test]
+    fn test_calls_around_keeps_nearest_in_order() {
+        let calls: Vec<ToolCall> = (0..30).map(|m| call(None, 20 + m / 6)).collect();
+        let selected = calls_around(calls, None, at(20));
+        assert_eq!(selected.len(), MAX_TOOL_CALLS);
+        assert!(selected.windows(2).all(|w| w[0].called_at <= w[1].called_at));
+        assert_eq!(selected.last().unwrap().called_at, at(23));
+    }
+
+    #[test]
+    fn test_source_turn_excerpt() {
+        let short = source_turn(turn(Some("Missing feature flag.".to_string())));
+        assert_eq!(short.response_excerpt.as_deref(), Some("Missing feature flag."));
+        assert!(!short.response_truncated);
+
+        let long = source_turn(turn(Some("word ".repeat(200))));
+        let excerpt = long.response_excerpt.unwrap();
+        assert!(long.response_truncated);
+        assert!(excerpt.ends_with("...") && excerpt.chars().count() <= RESPONSE_EXCERPT_CHARS);
+    }
+}
@@ -0,0 +1,517 @@
+//! Ranking strategies for search results and the context block
+//!
+//! Ranking runs in SQL so `LIMIT` keeps the best rows: a [`Ranker`] pushes
+//! the sort keys the queries in `db::queries` order by, binding any values
+//! it compares against. The same keys mark where a page ended (see
+//! [`crate::util::cursor`]). Strategies are chosen in the `[ranking]` config
+//! table, separately for search and context. Full-text search keeps
+//! ordering by relevance.
+
+use std::fmt;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::Postgres;
+use sqlx::QueryBuilder;
+
+use crate::git::GitStatus;
+use crate::util::cursor::Cursor;
+
+/// Confidence as an ordinal level, 2 for high
+const CONFIDENCE_LEVEL: &str = "CASE confidence WHEN 'high' THEN 2 WHEN 'medium' THEN 1 ELSE 0 END";
+
+/// Confidence as a score from 0.3 (low) to 1.0 (high)
+const CONFIDENCE_SCORE: &str =
+    "CASE confidence WHEN 'high' THEN 1.0 WHEN 'medium' THEN 0.6 ELSE 0.3 END";
+
+/// Orders memories for a query by sort keys, highest first, then by id
+pub trait Ranker: fmt::Debug + Send + Sync {
+    /// Strategy name, as written in config
+    fn name(&self) -> &'static str;
+
+    /// SQL types of the sort keys, most significant first
+    fn key_types(&self) -> &'static [&'static str];
+
+    /// Push sort key `index` over `memories` columns, with recency as of `at`
+    fn push_key(&self, query: &mut QueryBuilder<'_, Postgres>, index: usize, at: DateTime<Utc>);
+
+    /// Push the `ORDER BY` list: the keys, then id, each highest first
+    fn push_order_by(&self, query: &mut QueryBuilder<'_, Postgres>, at: DateTime<Utc>) {
+        for index in 0..self.key_types().len() {
+            self.push_key(query, index, at);
+            query.push(" DESC, ");
+        }
+        query.push("id DESC");
+    }
+
+    /// Push the keys as a text array, for the cursor of each row
+    fn push_sort_key(&self, query: &mut QueryBuilder<'_, Postgres>, at: DateTime<Utc>) {
+        query.push("ARRAY[");
+        for index in 0..self.key_types().len() {
+            if index > 0 {
+                query.push(", ");
+            }
+            query.push("(");
+            self.push_key(query, index, at);
+            query.push(")::text");
+        }
+        query.push("]");
+    }
+
+    /// Push the condition matching the rows ordered after `cursor`, whose
+    /// key must have one value per key type
+    fn push_after(&self, query: &mut QueryBuilder<'_, Postgres>, cursor: &Cursor) {
+        query.push("(");
+        for index in 0..self.key_types().len() {
+            self.push_key(query, index, cursor.at);
+            query.push(", ");
+        }
+        query.push("id) < (");
+        for (value, sql_type) in cursor.key.iter().zip(self.key_types()) {
+            query.push_bind(value.clone()).push(format!("::{}, ", sql_type));
+        }
+        query.push_bind(cursor.id).push(")");
+    }
+}
+
+/// Available ranking strategies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RankingStrategy {
+    /// High confidence first, newest first within each level
+    Confidence,
+    /// Newest first, confidence breaking ties
+    Recency,
+    /// Confidence score decayed by time since last access
+    Decay,
+    /// Confidence level first, then decayed recency within each level
+    Hybrid,
+    /// Weighted sum of confidence, recency, access count and, for the
+    /// context, tag match with the working tree (`ranking.weights`)
+    Weighted,
+}
+
+impl RankingStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Confidence => "confidence",
+            Self::Recency => "recency",
+            Self::Decay => "decay",
+            Self::Hybrid => "hybrid",
+            Self::Weighted => "weighted",
+        }
+    }
+}
+
+/// Branch-name words that say nothing about what is being worked on
+const GENERIC_BRANCH_WORDS: &[&str] = &[
+    "main", "master", "develop", "dev", "head", "feature", "feat", "fix", "bugfix", "hotfix",
+    "chore", "release", "wip",
+];
+
+/// Path components too common to hint at an area
+const GENERIC_PATH_WORDS: &[&str] = &["src", "lib", "mod", "index", "main", "tests", "test"];
+
+/// Most context tags taken from the working tree
+const MAX_CONTEXT_TAGS: usize = 20;
+
+/// Weights for the `weighted` strategy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RankWeights {
+    /// Weight of the confidence score (0.3 - 1.0)
+    pub confidence: f64,
+    /// Weight of the recency score (1.0 when just accessed, halving every half-life)
+    pub recency: f64,
+    /// Weight of `ln(1 + access_count)`
+    pub access: f64,
+    /// Weight of a tag matching the current branch or changed files
+    /// (context only)
+    pub tags: f64,
+}
+
+impl Default for RankWeights {
+    fn default() -> Self {
+        Self {
+            confidence: 1.0,
+            recency: 1.0,
+            access: 0.1,
+            tags: 0.5,
+        }
+    }
+}
+
+/// High confidence first, newest first within each level
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConfidenceFirst;
+
+impl Ranker for ConfidenceFirst {
+    fn name(&self) -> &'static str {
+        "confidence"
+    }
+
+    fn key_types(&self) -> &'static [&'static str] {
+        &["int", "timestamptz"]
+    }
+
+    fn push_key(&self, query: &mut QueryBuilder<'_, Postgres>, index: usize, _at: DateTime<Utc>) {
+        query.push(if index == 0 { CONFIDENCE_LEVEL } else { "created_at" });
+    }
+}
+
+/// Newest first, confidence breaking ties
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Recency;
+
+impl Ranker for Recency {
+    fn name(&self) -> &'static str {
+        "recency"
+    }
+
+    fn key_types(&self) -> &'static [&'static str] {
+        &["timestamptz", "int"]
+    }
+
+    fn push_key(&self, query: &mut QueryBuilder<'_, Postgres>, index: usize, _at: DateTime<Utc>) {
+        query.push(if index == 0 { "created_at" } else { CONFIDENCE_LEVEL });
+    }
+}
+
+/// Confidence score halving every `half_life_days` without access
+#[derive(Debug, Clone, Copy)]
+pub struct Decay {
+    pub half_life_days: f64,
+}
+
+impl Ranker for Decay {
+    fn name(&self) -> &'static str {
+        "decay"
+    }
+
+    fn key_types(&self) -> &'static [&'static str] {
+        &["float8", "timestamptz"]
+    }
+
+    fn push_key(&self, query: &mut QueryBuilder<'_, Postgres>, index: usize, at: DateTime<Utc>) {
+        if index > 0 {
+            query.push("created_at");
+            return;
+        }
+        query.push(format!("(({}) * ", CONFIDENCE_SCORE));
+        push_recency_score(query, self.half_life_days, at);
+        query.push(")::float8");
+    }
+}
+
+/// Confidence level first, then decayed recency within each level
+#[derive(Debug, Clone, Copy)]
+pub struct Hybrid {
+    pub half_life_days: f64,
+}
+
+impl Ranker for Hybrid {
+    fn name(&self) -> &'static str {
+        "hybrid"
+    }
+
+    fn key_types(&self) -> &'static [&'static str] {
+        &["int", "float8", "timestamptz"]
+    }
+
+    fn push_key(&self, query: &mut QueryBuilder<'_, Postgres>, index: usize, at: DateTime<Utc>) {
+        match index {
+            0 => {
+                query.push(CONFIDENCE_LEVEL);
+            }
+            1 => {
+                query.push("(");
+                push_recency_score(query, self.half_life_days, at);
+                query.push(")::float8");
+            }
+            _ => {
+                query.push("created_at");
+            }
+        }
+    }
+}
+
+/// Weighted sum of confidence, recency, access count and tag match
+#[derive(Debug, Clone)]
+pub struct Weighted {
+    pub weights: RankWeights,
+    pub half_life_days: f64,
+    /// Lowercase tags describing the current work (see [`context_tags`])
+    pub context_tags: Vec<String>,
+}
+
+impl Ranker for Weighted {
+    fn name(&self) -> &'static str {
+        "weighted"
+    }
+
+    fn key_types(&self) -> &'static [&'static str] {
+        &["float8", "timestamptz"]
+    }
+
+    fn push_key(&self, query: &mut QueryBuilder<'_, Postgres>, index: usize, at: DateTime<Utc>) {
+        if index > 0 {
+            query.push("created_at");
+            return;
+        }
+        query.push(format!(
+            "({} * ({}) + {} * ",
+            sql_number(self.weights.confidence),
+            CONFIDENCE_SCORE,
+            sql_number(self.weights.recency),
+        ));
+        push_recency_score(query, self.half_life_days, at);
+        query.push(format!(" + {} * ln(1 + access_count)", sql_number(self.weights.access)));
+        if !self.context_tags.is_empty() {
+            query.push(format!(" + {} * ", sql_number(self.weights.tags)));
+            push_tag_match_score(query, &self.context_tags);
+        }
+        query.push(")::float8");
+    }
+}
+
+/// Build the ranker for a strategy; `context_tags` only affect `weighted`
+pub fn ranker(
+    strategy: RankingStrategy,
+    half_life_days: f64,
+    weights: &RankWeights,
+    context_tags: &[String],
+) -> Arc<dyn Ranker> {
+    match strategy {
+        RankingStrategy::Confidence => Arc::new(ConfidenceFirst),
+        RankingStrategy::Recency => Arc::new(Recency),
+        RankingStrategy::Decay => Arc::new(Decay { half_life_days }),
+        RankingStrategy::Hybrid => Arc::new(Hybrid { half_life_days }),
+        RankingStrategy::Weighted => Arc::new(Weighted {
+            weights: weights.clone(),
+            half_life_days,
+            context_tags: context_tags.to_vec(),
+        }),
+    }
+}
+
+/// Tags describing the current work: the words of the branch name and the
+/// names and directories of changed files, lowercased.
+///
+/// `feature/billing-retry` with `src/payments/invoice.rs` modified gives
+/// `billing`, `retry`, `payments`, `invoice`.
+pub fn context_tags(git: &GitStatus) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    let mut push = |word: &str, generic: &[&str]| {
+        let word = word.to_lowercase();
+        if word.chars().count() >= 3
+            && !word.chars().all(|c| c.is_ascii_digit())
+            && !generic.contains(&word.as_str())
+            && !tags.contains(&word)
+        {
+            tags.push(word);
+        }
+    };
+
+    for word in git.branch.split(|c: char| !c.is_alphanumeric()) {
+        push(word, GENERIC_BRANCH_WORDS);
+    }
+    for file in git.staged.iter().chain(&git.modified).chain(&git.untracked) {
+        let path = std::path::Path::new(file.trim_end_matches('/'));
+        if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+            push(stem, GENERIC_PATH_WORDS);
+        }
+        for dir in path.parent().into_iter().flat_map(|p| p.iter()) {
+            if let Some(dir) = dir.to_str() {
+                push(dir, GENERIC_PATH_WORDS);
+            }
+        }
+    }
+
+    tags.truncate(MAX_CONTEXT_TAGS);
+    tags
+}
+
+/// 1.0 for a memory with any of `tags` (lowercase, matched ignoring case),
+/// else 0.0; the tags are bound as a `TEXT[]`
+fn push_tag_match_score(query: &mut QueryBuilder<'_, Postgres>, tags: &[String]) {
+    query
+        .push("(CASE WHEN EXISTS (SELECT 1 FROM unnest(tags) AS t WHERE LOWER(t) = ANY(")
+        .push_bind(tags.to_vec())
+        .push("::text[])) THEN 1.0 ELSE 0.0 END)");
+}
+
+/// 1.0 for a memory accessed (or created) at `at`, halving every
+/// `half_life_days` before it
+fn push_recency_score(query: &mut QueryBuilder<'_, Postgres>, half_life_days: f64, at: DateTime<Utc>) {
+    // Guard against a zero or negative half-life from config
+    let half_life = if half_life_days.is_finite() {
+        half_life_days.max(0.01)
+    } else {
+        30.0
+    };
+    query
+        .push("power(0.5, EXTRACT(EPOCH FROM ")
+        .push_bind(at)
+        .push(format!(
+            "::timestamptz - COALESCE(accessed_at, created_at)) / 86400.0 / {})",
+            sql_number(half_life)
+        ));
+}
+
+/// Config number as a SQL literal; NaN and infinities count as 0
+fn sql_number(value: f64) -> String {
+    if value.is_finite() {
+        format!("{:?}", value)
+    } else {
+        "0.0".to_string()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order_by(ranker: &dyn Ranker) -> String {
+        let mut query = QueryBuilder::new("");
+        ranker.push_order_by(&mut query, Utc::now());
+        query.sql().to_string()
+    }
+
+    #[test]
+    fn test_ranker_names_match_strategies() {
+        let weights = RankWeights::default();
+        for strategy in [
+            RankingStrategy::Confidence,
+            RankingStrategy::Recency,
+            RankingStrategy::Decay,
+            RankingStrategy::Hybrid,
+            RankingStrategy::Weighted,
+        ] {
+            assert_eq!(ranker(strategy, 30.0, &weights, &[]).name(), strategy.as_str());
+        }
+    }
+
+    #[test]
+    fn test_order_by_keys_then_id() {
+        assert_eq!(
+            order_by(&ConfidenceFirst),
+            "CASE confidence WHEN 'high' THEN 2 WHEN 'medium' THEN 1 ELSE 0 END DESC, created_at DESC, id DESC"
+        );
+        assert!(order_by(&Recency).starts_with("created_at DESC, CASE confidence"));
+    }
+
+    #[test]
+    fn test_after_compares_keys_and_id() {
+        let cursor = Cursor {
+            order: "confidence".to_string(),
+            key: vec!["2".to_string(), "2024-03-01T00:00:00.000000Z".to_string()],
+            id: uuid::Uuid::new_v4(),
+            at: Utc::now(),
+        };
+        let mut query = QueryBuilder::new("");
+        ConfidenceFirst.push_after(&mut query, &cursor);
+        assert!(query.sql().ends_with(", created_at, id) < ($1::int, $2::timestamptz, $3)"));
+    }
+
+    #[test]
+    fn test_weighted_order_by_uses_weights() {
+        let ranker = Weighted {
+            weights: RankWeights {
+                confidence: 2.0,
+                recency: 0.5,
+                access: 0.0,
+                tags: 0.5,
+            },
+            half_life_days: 14.0,
+            context_tags: Vec::new(),
+        };
+        let order = order_by(&ranker);
+        assert!(order.starts_with("(2.0 * (CASE confidence"));
+        assert!(order.contains("0.5 * power(0.5,"));
+        assert!(order.contains("/ 14.0)"));
+        assert!(order.contains("EXTRACT(EPOCH FROM $1::timestamptz - COALESCE(accessed_at, created_at))"));
+        assert!(order.contains("0.0 * ln(1 + access_count))::float8 DESC"));
+    }
+
+    #[test]
+    fn test_weighted_order_by_adds_tag_match() {
+        let ranker = Weighted {
+            weights: RankWeights::default(),
+            half_life_days: 30.0,
+            context_tags: vec!["billing".to_string(), "o'neil".to_string()],
+        };
+        let order = order_by(&ranker);
+        assert!(order.contains(" + 0.5 * (CASE WHEN EXISTS (SELECT 1 FROM unnest(tags)"));
+        assert!(order.contains("LOWER(t) = ANY($2::text[])"));
+        assert!(!order.contains("neil"));
+    }
+
+    #[tokio::test]
+    async fn test_weighted_context_ranks_tag_matches_first() {
+        use crate::db::migrations::with_scratch_database;
+        use crate::db::queries::{get_context_memories, insert_memory};
+        use crate::models::{Confidence, MemoryType, Scope};
+
+        let Some(result) = with_scratch_database(|pool| async move {
+            let paths = vec!["/work/app".to_string()];
+            let mut ids = Vec::new();
+            for (content, tags) in [("Untagged", vec![]), ("Tagged", vec!["O'Neil".to_string()])] {
+                let id = insert_memory(
+                    &pool, MemoryType::Learning, Scope::Project, Some("/work/app"), content, content,
+                    &tags, Confidence::High, None, None, None,
+                )
+                .await?;
+                ids.push(id);
+            }
+            let ranker = Weighted {
+                weights: RankWeights::default(),
+                half_life_days: 30.0,
+                context_tags: vec!["o'neil".to_string()],
+            };
+            let found = get_context_memories(&pool, &paths, 10, &ranker, None, &[]).await?;
+            Ok((ids, found.into_iter().map(|m| m.id).collect::<Vec<_>>()))
+        })
+        .await
+        else {
+            return;
+        };
+
+        let (ids, found) = result.unwrap();
+        assert_eq!(found, vec![ids[1], ids[0]]);
+    }
+
+    #[test]
+    fn test_context_tags_from_branch_and_files() {
+        let git = GitStatus {
+            branch: "feature/billing-retry-42".to_string(),
+            modified: vec!["src/payments/invoice.rs".to_string()],
+            untracked: vec!["docs/".to_string()],
+            staged: vec!["src/payments/mod.rs".to_string()],
+        };
+        assert_eq!(
+            context_tags(&git),
+            vec!["billing", "retry", "payments", "invoice", "docs"]
+        );
+    }
+
+    #[test]
+    fn test_context_tags_on_main_is_empty() {
+        let git = GitStatus {
+            branch: "main".to_string(),
+            ..Default::default()
+        };
+        assert!(context_tags(&git).is_empty());
+    }
+
+    #[test]
+    fn test_recency_score_clamps_half_life() {
+        let mut query = QueryBuilder::new("");
+        push_recency_score(&mut query, 0.0, Utc::now());
+        assert!(query.sql().ends_with("/ 0.01)"));
+    }
+}
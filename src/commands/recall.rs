@@ -0,0 +1,229 @@
+//! Recall, embed-turns and embed-memories commands: retrieval by meaning
+//!
+//! Memories keep what was concluded; turns keep what was said. With
+//! `embed_turns` set, the Stop hook embeds each finished turn, and
+//! `embed-turns` embeds those it missed. `recall` ranks embedded turns by
+//! similarity to a query and returns the exchanges with their sessions, for
+//! `show-session` to open. `embed-memories` embeds memory contents for the
+//! semantic half of hybrid `search`.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::config::DbConfig;
+use crate::db::{self, EmbeddedTurn};
+use crate::embedding::{cosine_similarity, memory_text, turn_text, Embedder};
+use crate::error::Result;
+
+/// Turns or memories sent to the embeddings endpoint per request
+const EMBED_BATCH: usize = 32;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Result of embed-turns command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbedTurnsResult {
+    pub model: String,
+    pub embedded: usize,
+    /// Answered turns still without an embedding from `model`
+    pub remaining: i64,
+}
+
+/// Result of embed-memories command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbedMemoriesResult {
+    pub model: String,
+    pub embedded: usize,
+    /// Active memories still without an up-to-date embedding from `model`
+    pub remaining: i64,
+}
+
+/// A past exchange similar to the query
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecalledExchange {
+    /// Cosine similarity to the query, up to 1
+    pub score: f64,
+    pub turn_id: Uuid,
+    pub session_id: Option<Uuid>,
+    pub claude_session_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_path: Option<String>,
+    pub turn_number: i32,
+    pub prompt: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Result of recall command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecallResult {
+    pub query: String,
+    pub model: String,
+    pub count: usize,
+    /// Most similar first
+    pub exchanges: Vec<RecalledExchange>,
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Embed up to `limit` answered turns that have no embedding from the
+/// configured model, newest first; with `turn`, only that turn.
+pub async fn embed_turns(
+    pool: &PgPool,
+    config: &DbConfig,
+    turn: Option<Uuid>,
+    limit: i64,
+) -> Result<EmbedTurnsResult> {
+    let embedder = Embedder::from_config(config)?;
+    let turns = db::list_unembedded_turns(pool, &embedder.model, turn, limit).await?;
+
+    let mut embedded = 0;
+    for batch in turns.chunks(EMBED_BATCH) {
+        let texts: Vec<String> = batch
+            .iter()
+            .map(|t| turn_text(&t.user_prompt, t.assistant_response.as_deref()))
+            .collect();
+        let vectors = embedder.embed(&texts)?;
+        for (turn, vector) in batch.iter().zip(&vectors) {
+            db::store_turn_embedding(pool, turn.id, &embedder.model, vector).await?;
+            embedded += 1;
+        }
+    }
+
+    Ok(EmbedTurnsResult {
+        remaining: db::count_unembedded_turns(pool, &embedder.model).await?,
+        model: embedder.model,
+        embedded,
+    })
+}
+
+/// Embed up to `limit` active memories that have no embedding from the
+/// configured model, or were changed since theirs, newest first.
+pub async fn embed_memories(pool: &PgPool, config: &DbConfig, limit: i64) -> Result<EmbedMemoriesResult> {
+    let embedder = Embedder::from_config(config)?;
+    let memories = db::list_unembedded_memories(pool, &embedder.model, limit).await?;
+
+    let mut embedded = 0;
+    for batch in memories.chunks(EMBED_BATCH) {
+        let texts: Vec<String> = batch
+            .iter()
+            .map(|m| memory_text(&m.summary, &m.content))
+            .collect();
+        let vectors = embedder.embed(&texts)?;
+        for (memory, vector) in batch.iter().zip(&vectors) {
+            db::store_memory_embedding(pool, memory.id, &embedder.model, vector).await?;
+            embedded += 1;
+        }
+    }
+
+    Ok(EmbedMemoriesResult {
+        remaining: db::count_unembedded_memories(pool, &embedder.model).await?,
+        model: embedder.model,
+        embedded,
+    })
+}
+
+/// The `limit` past exchanges most similar to `query`, from sessions of
+/// `project_paths` (every project when empty).
+pub async fn recall(
+    pool: &PgPool,
+    config: &DbConfig,
+    query: &str,
+    limit: usize,
+    project_paths: &[String],
+) -> Result<RecallResult> {
+    let embedder = Embedder::from_config(config)?;
+    let query_vector = embedder
+        .embed(&[query.to_string()])?
+        .pop()
+        .unwrap_or_default();
+    let candidates = db::list_turn_embeddings(pool, &embedder.model, project_paths).await?;
+
+    let exchanges = rank(&query_vector, candidates, limit);
+    Ok(RecallResult {
+        query: query.to_string(),
+        model: embedder.model,
+        count: exchanges.len(),
+        exchanges,
+    })
+}
+
+/// The `limit` candidates most similar to `query`, most similar first
+fn rank(query: &[f32], candidates: Vec<EmbeddedTurn>, limit: usize) -> Vec<RecalledExchange> {
+    let mut scored: Vec<(f64, EmbeddedTurn)> = candidates
+        .into_iter()
+        .map(|c| (cosine_similarity(query, &c.embedding), c))
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(score, c)| RecalledExchange {
+            score,
+            turn_id: c.turn.id,
+            session_id: c.turn.session_id,
+            claude_session_id: c.claude_session_id,
+            project_path: c.project_path,
+            turn_number: c.turn.turn_number,
+            prompt: c.turn.user_prompt,
+            response: c.turn.assistant_response,
+            created_at: c.turn.created_at,
+        })
+        .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Turn;
+
+    fn embedded(prompt: &str, embedding: Vec<f32>) -> EmbeddedTurn {
+        EmbeddedTurn {
+            claude_session_id: "claude-1".to_string(),
+            project_path: Some("/test".to_string()),
+            turn: Turn {
+                id: Uuid::new_v4(),
+                session_id: Some(Uuid::new_v4()),
+                turn_number: 1,
+                user_prompt: prompt.to_string(),
+                assistant_response: Some("ok".to_string()),
+                model_used: None,
+                input_tokens: None,
+                output_tokens: None,
+                started_at: Utc::now(),
+                ended_at: None,
+                created_at: Utc::now(),
+            },
+            embedding,
+        }
+    }
+
+    #[test]
+    fn test_rank_most_similar_first() {
+        let candidates = vec![
+            embedded("unrelated", vec![0.0, 1.0]),
+            embedded("close", vec![0.9, 0.1]),
+            embedded("exact", vec![1.0, 0.0]),
+        ];
+        let ranked = rank(&[1.0, 0.0], candidates, 2);
+        let prompts: Vec<&str> = ranked.iter().map(|e| e.prompt.as_str()).collect();
+        assert_eq!(prompts, vec!["exact", "close"]);
+        assert!(ranked[0].score > ranked[1].score);
+        assert_eq!(ranked[0].claude_session_id, "claude-1");
+    }
+}
@@ -0,0 +1,50 @@
+//! Review queue commands
+//!
+//! Memories get flagged `needs-review` when the code they describe changes
+//! underneath them (e.g. a referenced file is deleted). These commands list
+//! the flagged memories and clear the flag once a memory has been checked.
+
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::db;
+use crate::error::Result;
+use crate::models::{
+    ErrorResponse, ResolveReviewData, ReviewItem, ReviewQueueData, SuccessResponse, Tier,
+};
+
+/// List memories flagged for review
+pub async fn list_review_queue(
+    pool: &PgPool,
+    tier: Tier,
+    limit: i64,
+    project_paths: &[String],
+) -> Result<serde_json::Value> {
+    let entries = db::list_review_queue(pool, tier, limit, project_paths).await?;
+    let count = entries.len();
+
+    let response = SuccessResponse::new(ReviewQueueData {
+        entries: entries
+            .into_iter()
+            .map(|e| ReviewItem {
+                memory: e.memory,
+                reason: e.reason,
+                flagged_at: e.flagged_at,
+            })
+            .collect(),
+        count,
+    });
+
+    Ok(serde_json::to_value(response)?)
+}
+
+/// Clear the review flag on a memory
+pub async fn resolve_review(pool: &PgPool, id: Uuid) -> Result<serde_json::Value> {
+    if !db::resolve_review(pool, id).await? {
+        let response = ErrorResponse::new(format!("Memory not awaiting review: {}", id));
+        return Ok(serde_json::to_value(response)?);
+    }
+
+    let response = SuccessResponse::new(ResolveReviewData { id, resolved: true });
+    Ok(serde_json::to_value(response)?)
+}
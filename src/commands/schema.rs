@@ -0,0 +1,461 @@
+//! Schema command: the database schema this binary manages
+//!
+//! Built from the embedded migrations, not from a live database, so the
+//! schema can be reviewed before `migrate` is pointed at a shared Postgres.
+//! `sql` prints the migrations in the order they are applied; `mermaid`
+//! folds them into the resulting tables and draws them as an ER diagram.
+
+use crate::db::migrations::MIGRATOR;
+use crate::error::{HippocampusError, Result};
+use crate::output::OutputFormat;
+
+/// Output format of the schema command
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SchemaFormat {
+    /// The migration SQL, in the order `migrate` applies it
+    #[default]
+    Sql,
+    /// A Mermaid ER diagram of the resulting tables
+    Mermaid,
+}
+
+impl SchemaFormat {
+    /// The schema format `--format` asks for, SQL when not given
+    pub fn from_flag(format: Option<OutputFormat>) -> Result<Self> {
+        match format {
+            None | Some(OutputFormat::Sql) => Ok(SchemaFormat::Sql),
+            Some(OutputFormat::Mermaid) => Ok(SchemaFormat::Mermaid),
+            Some(other) => Err(HippocampusError::Validation(format!(
+                "schema prints --format sql or mermaid, not {}",
+                other.name()
+            ))),
+        }
+    }
+}
+
+/// Words that end a column's type in a column definition
+const COLUMN_CONSTRAINTS: &[&str] = &[
+    "PRIMARY",
+    "NOT",
+    "NULL",
+    "DEFAULT",
+    "REFERENCES",
+    "UNIQUE",
+    "CHECK",
+    "GENERATED",
+    "CONSTRAINT",
+    "COLLATE",
+];
+
+/// Words that start a table constraint rather than a column
+const TABLE_CONSTRAINTS: &[&str] = &["PRIMARY", "UNIQUE", "CONSTRAINT", "CHECK", "FOREIGN", "EXCLUDE"];
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A table as it stands after all migrations
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub name: String,
+    pub columns: Vec<Column>,
+}
+
+/// One column of a [`Table`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub name: String,
+    /// Type as written in the migration, lowercased
+    pub data_type: String,
+    pub primary_key: bool,
+    pub not_null: bool,
+    /// Table referenced by a foreign key on this column
+    pub references: Option<String>,
+}
+
+// ============================================================================
+// Command
+// ============================================================================
+
+/// The schema in the requested format, as printable text.
+pub fn schema(format: SchemaFormat) -> String {
+    match format {
+        SchemaFormat::Sql => schema_sql(),
+        SchemaFormat::Mermaid => to_mermaid(&tables()),
+    }
+}
+
+/// All embedded migrations, each under a header naming its version
+pub fn schema_sql() -> String {
+    let mut sql = String::from(
+        "-- Schema managed by claude-hippocampus, applied in order by `migrate`.\n\
+         -- Applied versions are recorded in _sqlx_migrations.\n",
+    );
+    for migration in MIGRATOR.iter().filter(|m| !m.migration_type.is_down_migration()) {
+        sql.push_str(&format!(
+            "\n-- ----------------------------------------------------------------------------\n\
+             -- Migration {:04}: {}\n\
+             -- ----------------------------------------------------------------------------\n\n",
+            migration.version, migration.description
+        ));
+        sql.push_str(migration.sql.trim_end());
+        sql.push('\n');
+    }
+    sql
+}
+
+/// The tables left by applying every embedded migration
+pub fn tables() -> Vec<Table> {
+    let mut tables = Vec::new();
+    for migration in MIGRATOR.iter().filter(|m| !m.migration_type.is_down_migration()) {
+        apply_sql(&mut tables, &migration.sql);
+    }
+    tables
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// Fold the table changes of a migration into `tables`.
+///
+/// Understands the statements the migrations use: CREATE TABLE, DROP TABLE
+/// and ALTER TABLE ... ADD/DROP/RENAME COLUMN. Everything else (indexes, data
+/// updates, extensions) leaves the table layout as it is.
+fn apply_sql(tables: &mut Vec<Table>, sql: &str) {
+    for statement in split_statements(&strip_comments(sql)) {
+        let words: Vec<&str> = statement.split_whitespace().collect();
+        let upper: Vec<String> = words.iter().map(|w| w.to_uppercase()).collect();
+        let starts = |prefix: &[&str]| upper.len() >= prefix.len() && upper.iter().zip(prefix).all(|(w, p)| w == p);
+
+        if starts(&["CREATE", "TABLE"]) {
+            if let Some(table) = parse_create_table(&statement) {
+                tables.retain(|t| t.name != table.name);
+                tables.push(table);
+            }
+        } else if starts(&["DROP", "TABLE"]) {
+            let name = skip_if_exists(&words[2..]).first().map(|n| n.trim_end_matches(','));
+            if let Some(name) = name {
+                tables.retain(|t| t.name != name);
+            }
+        } else if starts(&["ALTER", "TABLE"]) {
+            let rest = skip_if_exists(&words[2..]);
+            let Some(table) = rest.first().and_then(|name| tables.iter_mut().find(|t| t.name == *name)) else {
+                continue;
+            };
+            let actions = rest[1..].join(" ");
+            for action in split_top_level(&actions, ',') {
+                apply_alter(table, &action);
+            }
+        }
+    }
+}
+
+/// Apply one `ADD COLUMN` / `DROP COLUMN` / `RENAME COLUMN` action of an
+/// ALTER TABLE
+fn apply_alter(table: &mut Table, action: &str) {
+    let words: Vec<&str> = action.split_whitespace().collect();
+    let upper: Vec<String> = words.iter().map(|w| w.to_uppercase()).collect();
+    let verb = upper.first().map(String::as_str);
+    let mut rest = &words[1.min(words.len())..];
+    if upper.get(1).map(String::as_str) == Some("COLUMN") {
+        rest = &rest[1..];
+    }
+
+    match verb {
+        Some("ADD") => {
+            let definition = skip_if_not_exists(rest).join(" ");
+            if let Some(column) = parse_column(&definition) {
+                if !table.columns.iter().any(|c| c.name == column.name) {
+                    table.columns.push(column);
+                }
+            }
+        }
+        Some("DROP") => {
+            if let Some(name) = skip_if_exists(rest).first() {
+                table.columns.retain(|c| c.name != *name);
+            }
+        }
+        Some("RENAME") => {
+            if let [old, to, new] = rest {
+                let column = table.columns.iter_mut().find(|c| c.name == *old);
+                if let Some(column) = column.filter(|_| to.eq_ignore_ascii_case("TO")) {
+                    column.name = new.to_string();
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn parse_create_table(statement: &str) -> Option<Table> {
+    let open = statement.find('(')?;
+    let close = statement.rfind(')')?;
+    let head: Vec<&str> = statement[..open].split_whitespace().collect();
+    let name = skip_if_not_exists(&head[2..]).first()?.to_string();
+
+    let mut table = Table {
+        name,
+        columns: Vec::new(),
+    };
+    let mut primary_key = Vec::new();
+    for item in split_top_level(&statement[open + 1..close], ',') {
+        let first = item.split_whitespace().next().unwrap_or("").to_uppercase();
+        if TABLE_CONSTRAINTS.contains(&first.as_str()) {
+            if first == "PRIMARY" {
+                primary_key.extend(parenthesized_names(&item));
+            }
+        } else if let Some(column) = parse_column(&item) {
+            table.columns.push(column);
+        }
+    }
+    for column in &mut table.columns {
+        if primary_key.contains(&column.name) {
+            column.primary_key = true;
+            column.not_null = true;
+        }
+    }
+    Some(table)
+}
+
+/// Parse a column definition: `name type [constraints]`
+fn parse_column(definition: &str) -> Option<Column> {
+    let words: Vec<&str> = definition.split_whitespace().collect();
+    let name = words.first()?.to_string();
+    let upper: Vec<String> = words.iter().map(|w| w.to_uppercase()).collect();
+
+    let type_end = upper
+        .iter()
+        .skip(1)
+        .position(|w| COLUMN_CONSTRAINTS.contains(&w.as_str()))
+        .map(|i| i + 1)
+        .unwrap_or(words.len());
+    if type_end < 2 {
+        return None;
+    }
+    let data_type = words[1..type_end].join(" ").to_lowercase();
+
+    let primary_key = upper.windows(2).any(|w| w[0] == "PRIMARY" && w[1] == "KEY");
+    let not_null = primary_key || upper.windows(2).any(|w| w[0] == "NOT" && w[1] == "NULL");
+    let references = upper
+        .iter()
+        .position(|w| w == "REFERENCES")
+        .and_then(|i| words.get(i + 1))
+        .map(|target| target.split('(').next().unwrap_or(target).to_string());
+
+    Some(Column {
+        name,
+        data_type,
+        primary_key,
+        not_null,
+        references,
+    })
+}
+
+/// Names listed in the first parentheses of `item`, e.g. `PRIMARY KEY (a, b)`
+fn parenthesized_names(item: &str) -> Vec<String> {
+    let (Some(open), Some(close)) = (item.find('('), item.find(')')) else {
+        return Vec::new();
+    };
+    item[open + 1..close]
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+fn skip_if_exists<'a>(words: &'a [&'a str]) -> &'a [&'a str] {
+    skip_words(words, &["IF", "EXISTS"])
+}
+
+fn skip_if_not_exists<'a>(words: &'a [&'a str]) -> &'a [&'a str] {
+    skip_words(words, &["IF", "NOT", "EXISTS"])
+}
+
+fn skip_words<'a>(words: &'a [&'a str], skip: &[&str]) -> &'a [&'a str] {
+    let matches = words.len() >= skip.len()
+        && words.iter().zip(skip).all(|(w, s)| w.eq_ignore_ascii_case(s));
+    if matches {
+        &words[skip.len()..]
+    } else {
+        words
+    }
+}
+
+/// Drop `--` line comments outside string literals
+fn strip_comments(sql: &str) -> String {
+    sql.lines()
+        .map(|line| {
+            let mut in_string = false;
+            let chars: Vec<char> = line.chars().collect();
+            for (i, c) in chars.iter().enumerate() {
+                match c {
+                    '\'' => in_string = !in_string,
+                    '-' if !in_string && chars.get(i + 1) == Some(&'-') => {
+                        return chars[..i].iter().collect::<String>();
+                    }
+                    _ => {}
+                }
+            }
+            line.to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Split SQL into statements on semicolons outside string literals
+fn split_statements(sql: &str) -> Vec<String> {
+    split_top_level(sql, ';')
+}
+
+/// Split on `separator` outside parentheses and string literals, dropping
+/// empty parts
+fn split_top_level(text: &str, separator: char) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0i32;
+    let mut in_string = false;
+    for c in text.chars() {
+        match c {
+            '\'' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => depth -= 1,
+            c if c == separator && !in_string && depth == 0 => {
+                parts.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    parts.push(current);
+    parts
+        .into_iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+// ============================================================================
+// Mermaid output
+// ============================================================================
+
+/// Draw tables and their foreign keys as a Mermaid ER diagram
+pub fn to_mermaid(tables: &[Table]) -> String {
+    let mut text = String::from("erDiagram\n");
+    for table in tables {
+        text.push_str(&format!("    {} {{\n", table.name));
+        for column in &table.columns {
+            let keys: Vec<&str> = [
+                column.primary_key.then_some("PK"),
+                column.references.is_some().then_some("FK"),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            text.push_str(&format!(
+                "        {} {}{}\n",
+                mermaid_type(&column.data_type),
+                column.name,
+                if keys.is_empty() { String::new() } else { format!(" {}", keys.join(", ")) }
+            ));
+        }
+        text.push_str("    }\n");
+    }
+
+    for table in tables {
+        for column in &table.columns {
+            let Some(parent) = &column.references else {
+                continue;
+            };
+            // A key that is the child's whole primary key allows one child per parent
+            let sole_key = column.primary_key && table.columns.iter().filter(|c| c.primary_key).count() == 1;
+            let parent_side = if column.not_null { "||" } else { "|o" };
+            let child_side = if sole_key { "o|" } else { "o{" };
+            text.push_str(&format!(
+                "    {} {}--{} {} : \"{}\"\n",
+                parent, parent_side, child_side, table.name, column.name
+            ));
+        }
+    }
+    text
+}
+
+/// A column type as a single Mermaid attribute type token
+fn mermaid_type(data_type: &str) -> String {
+    data_type.replace(' ', "_")
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table<'a>(tables: &'a [Table], name: &str) -> &'a Table {
+        tables.iter().find(|t| t.name == name).unwrap()
+    }
+
+    fn column<'a>(table: &'a Table, name: &str) -> &'a Column {
+        table.columns.iter().find(|c| c.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_parse_create_and_alter() {
+        let mut tables = Vec::new();
+        apply_sql(
+            &mut tables,
+            "-- comment; not a statement\n\
+             CREATE TABLE IF NOT EXISTS parents (id UUID PRIMARY KEY, label VARCHAR(20) DEFAULT 'a,b');\n\
+             CREATE TABLE children (\n\
+               parent_id UUID NOT NULL REFERENCES parents(id) ON DELETE CASCADE,\n\
+               day DATE NOT NULL,\n\
+               PRIMARY KEY (parent_id, day)\n\
+             );\n\
+             ALTER TABLE parents ADD COLUMN IF NOT EXISTS note TEXT;\n\
+             ALTER TABLE parents DROP COLUMN label;\n\
+             CREATE INDEX IF NOT EXISTS idx ON children(day);",
+        );
+
+        let parents = table(&tables, "parents");
+        let names: Vec<&str> = parents.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "note"]);
+        assert!(column(parents, "id").primary_key);
+
+        let children = table(&tables, "children");
+        let parent_id = column(children, "parent_id");
+        assert_eq!(parent_id.references.as_deref(), Some("parents"));
+        assert!(parent_id.primary_key && parent_id.not_null);
+        assert_eq!(column(children, "day").data_type, "date");
+    }
+
+    #[test]
+    fn test_embedded_schema_has_later_columns() {
+        let tables = tables();
+        let memories = table(&tables, "memories");
+        assert!(memories.columns.iter().any(|c| c.name == "summary"));
+        assert_eq!(column(memories, "superseded_by").references.as_deref(), Some("memories"));
+        assert_eq!(column(memories, "search_vector").data_type, "tsvector");
+        assert!(tables.iter().any(|t| t.name == "session_mutes"));
+        assert_eq!(column(table(&tables, "sessions"), "models_used").data_type, "text[]");
+    }
+
+    #[test]
+    fn test_mermaid_relationships() {
+        let text = to_mermaid(&tables());
+        assert!(text.starts_with("erDiagram\n"));
+        assert!(text.contains("        uuid id PK\n"));
+        assert!(text.contains("    sessions |o--o{ conversation_turns : \"session_id\"\n"));
+        assert!(text.contains("    conversation_turns ||--o| extraction_queue : \"turn_id\"\n"));
+        assert!(text.contains("    memories ||--o{ session_mutes : \"memory_id\"\n"));
+    }
+
+    #[test]
+    fn test_schema_sql_lists_every_migration() {
+        let sql = schema_sql();
+        assert!(sql.contains("-- Migration 0001: initial schema\n"));
+        assert_eq!(sql.matches("-- Migration ").count(), MIGRATOR.iter().count());
+    }
+}
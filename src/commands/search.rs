@@ -1,38 +1,105 @@
-//! Search commands: searchKeyword, getContext, listRecent
+//! Search commands: searchKeyword, searchFts, search, getContext, listRecent
 //!
 //! These commands query the memories database and return formatted results.
 
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 use sqlx::postgres::PgPool;
-
-use crate::db::queries;
-use crate::error::Result;
-use crate::models::{Memory, MemorySummary, MemoryType, Scope, Tier};
+use uuid::Uuid;
+
+use crate::claude_md::ClaudeMd;
+use crate::commands::ranking::{ConfidenceFirst, Ranker};
+use crate::config::DbConfig;
+use crate::db::queries::{self, SearchFilters};
+use crate::embedding::{cosine_similarity, Embedder};
+use crate::environment::{constraints_match, EnvironmentSnapshot};
+use crate::error::{HippocampusError, Result};
+use crate::models::{Confidence, ContextSection, Memory, MemorySummary, MemoryType, Scope, Tier};
+use crate::util::cursor::{self, Cursor};
+use crate::util::tokens::{estimate_tokens, truncate_to_tokens};
+
+pub use crate::models::{MemorySearchItem, SearchResult};
+
+/// Extra candidates fetched for context when filtering by environment
+const CONSTRAINT_HEADROOM: i32 = 20;
+
+/// Context block sections in the order they are emitted, with headings
+const CONTEXT_SECTIONS: [(MemoryType, &str); 6] = [
+    (MemoryType::Convention, "Conventions"),
+    (MemoryType::Gotcha, "Gotchas"),
+    (MemoryType::Architecture, "Architecture"),
+    (MemoryType::Api, "APIs"),
+    (MemoryType::Preference, "Preferences"),
+    (MemoryType::Learning, "Learnings"),
+];
+
+/// Least room worth filling with a truncated entry or briefing
+const MIN_TRUNCATED_TOKENS: usize = 8;
+
+/// Fewest candidates hybrid search takes from each source
+const HYBRID_CANDIDATES: usize = 50;
+
+/// Reciprocal rank fusion constant: how much lower ranks still count
+const RRF_K: f64 = 60.0;
 
 // ============================================================================
 // Search Options
 // ============================================================================
 
+/// How keyword search matches memories
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SearchMode {
+    /// Substring match on content and tags, ordered by confidence then recency
+    #[default]
+    Ilike,
+    /// Full-text match on the `search_vector` column, ordered by `ts_rank`
+    Fts,
+}
+
 /// Options for keyword search
 #[derive(Debug, Clone)]
 pub struct SearchOptions {
     /// Search query string
     pub query: String,
+    /// Matching strategy
+    pub mode: SearchMode,
     /// Tier filter (project, global, or both)
     pub tier: Tier,
     /// Maximum number of results
     pub limit: i32,
-    /// Project path for project-scoped queries
-    pub project_path: Option<String>,
+    /// Results skipped, from `--offset`
+    pub offset: i64,
+    /// Start after the last result of a page, from `--cursor`
+    pub cursor: Option<Cursor>,
+    /// Projects whose memories project-scoped queries include
+    pub project_paths: Vec<String>,
+    /// Search the memories that were active at this time instead of now
+    pub as_of: Option<chrono::DateTime<chrono::Utc>>,
+    /// Also search memories archived by `decay`
+    pub include_archived: bool,
+    /// Date range, confidence and tag conditions
+    pub filters: SearchFilters,
+    /// Result order in ILIKE mode
+    pub ranker: Arc<dyn Ranker>,
 }
 
 impl Default for SearchOptions {
     fn default() -> Self {
         Self {
             query: String::new(),
+            mode: SearchMode::Ilike,
             tier: Tier::Both,
             limit: 30,
-            project_path: None,
+            offset: 0,
+            cursor: None,
+            project_paths: Vec::new(),
+            as_of: None,
+            include_archived: false,
+            filters: SearchFilters::default(),
+            ranker: Arc::new(ConfidenceFirst),
         }
     }
 }
@@ -48,61 +115,76 @@ pub struct SearchByTypeOptions {
     pub tier: Tier,
     /// Maximum number of results
     pub limit: i32,
-    /// Project path for project-scoped queries
-    pub project_path: Option<String>,
+    /// Results skipped, from `--offset`
+    pub offset: i64,
+    /// Start after the last result of a page, from `--cursor`
+    pub cursor: Option<Cursor>,
+    /// Projects whose memories project-scoped queries include
+    pub project_paths: Vec<String>,
+    /// Also search memories archived by `decay`
+    pub include_archived: bool,
+    /// Date range, confidence and tag conditions
+    pub filters: SearchFilters,
+    /// Result order
+    pub ranker: Arc<dyn Ranker>,
+}
+
+/// Options for hybrid search
+#[derive(Debug, Clone)]
+pub struct HybridSearchOptions {
+    /// Search query string
+    pub query: String,
+    /// Tier filter (project, global, or both)
+    pub tier: Tier,
+    /// Maximum number of results
+    pub limit: usize,
+    /// Projects whose memories project-scoped queries include
+    pub project_paths: Vec<String>,
+    /// Date range, confidence and tag conditions
+    pub filters: SearchFilters,
 }
 
 // ============================================================================
 // Search Results
 // ============================================================================
 
-/// Result of a keyword search
-#[derive(Debug, Serialize)]
-pub struct SearchResult {
-    /// Matched memories (with full content)
-    pub results: Vec<MemorySearchItem>,
-    /// Number of results
-    pub count: usize,
+/// Where a hybrid search result placed in one source
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SourceScore {
+    /// Position in the source's ranking, from 1
+    pub rank: usize,
+    /// The source's own score: `ts_rank`, or cosine similarity
+    pub score: f64,
+    /// `score` scaled to 0..1 over the source's candidates
+    pub normalized: f64,
 }
 
-/// A single search result item (includes full content unlike MemorySummary)
-#[derive(Debug, Clone, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct MemorySearchItem {
-    pub id: uuid::Uuid,
-    #[serde(rename = "type")]
-    pub memory_type: crate::models::MemoryType,
-    pub tier: Scope,
-    pub summary: String,
-    pub content: String,
-    pub tags: Vec<String>,
-    pub confidence: crate::models::Confidence,
-    pub created: chrono::DateTime<chrono::Utc>,
-    pub accessed: Option<chrono::DateTime<chrono::Utc>>,
-    pub access_count: i32,
-}
-
-impl From<Memory> for MemorySearchItem {
-    fn from(m: Memory) -> Self {
-        let summary = if m.content.len() > 100 {
-            format!("{}...", &m.content[..97])
-        } else {
-            m.content.clone()
-        };
+/// A memory ranked by hybrid search
+#[derive(Debug, Serialize)]
+pub struct HybridMatch {
+    #[serde(flatten)]
+    pub memory: MemorySearchItem,
+    /// Reciprocal rank fusion score, summed over the sources
+    pub score: f64,
+    /// Absent when full-text search did not match it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fts: Option<SourceScore>,
+    /// Absent when it has no embedding or was not among the most similar
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub semantic: Option<SourceScore>,
+}
 
-        Self {
-            id: m.id,
-            memory_type: m.memory_type,
-            tier: m.scope,
-            summary,
-            content: m.content,
-            tags: m.tags,
-            confidence: m.confidence,
-            created: m.created_at,
-            accessed: m.accessed_at,
-            access_count: m.access_count,
-        }
-    }
+/// Result of search command
+#[derive(Debug, Serialize)]
+pub struct HybridSearchResult {
+    pub query: String,
+    /// Embedding model of the semantic source; absent when
+    /// `embedding_model` is unset and only full-text search ran
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub count: usize,
+    /// Best first
+    pub results: Vec<HybridMatch>,
 }
 
 /// Result of getContext command
@@ -110,10 +192,57 @@ impl From<Memory> for MemorySearchItem {
 pub struct ContextResult {
     /// Formatted markdown context block
     pub context: String,
+    /// The project's pinned briefing, emitted ahead of the memories
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub briefing: Option<String>,
+    /// Only memories created or updated after this were included
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<DateTime<Utc>>,
     /// Number of entries
     pub count: usize,
-    /// Summary entries
+    /// Summary entries, in section order when grouped by type
     pub entries: Vec<MemorySummary>,
+    /// Sections of a block grouped by type (`--group-by-type`), in order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sections: Option<Vec<ContextSection>>,
+    /// How the block was fitted to `--max-tokens`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<ContextBudget>,
+}
+
+/// How get-context selects and lays out its entries
+#[derive(Debug, Clone, Default)]
+pub struct ContextLayout {
+    /// Put entries under a heading per memory type instead of one list
+    pub group_by_type: bool,
+    /// Most entries of a type (pinned ones included); other types are only
+    /// held to the overall limit
+    pub type_limits: Vec<(MemoryType, usize)>,
+    /// Leave out memories this CLAUDE.md covers at this overlap (see
+    /// [`ClaudeMd::covers`]); pinned ones are kept
+    pub claude_md: Option<(ClaudeMd, f64)>,
+}
+
+impl ContextLayout {
+    /// Limit for `memory_type`, if it has one
+    fn type_limit(&self, memory_type: MemoryType) -> Option<usize> {
+        self.type_limits
+            .iter()
+            .rev()
+            .find(|(t, _)| *t == memory_type)
+            .map(|(_, limit)| *limit)
+    }
+}
+
+/// Token budget of a context block
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextBudget {
+    pub max_tokens: usize,
+    /// Estimated tokens in `context` (see `util::tokens`)
+    pub estimated_tokens: usize,
+    /// Ranked memories left out to stay within the budget
+    pub omitted: usize,
 }
 
 /// Result of listRecent command
@@ -123,6 +252,9 @@ pub struct ListRecentResult {
     pub entries: Vec<MemorySummary>,
     /// Total count of matching memories
     pub total: usize,
+    /// Pass as `--cursor` for the next page; absent on the last page
+    #[serde(rename = "nextCursor", skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 // ============================================================================
@@ -131,110 +263,326 @@ pub struct ListRecentResult {
 
 /// Search memories by keyword (content or tags).
 ///
-/// Searches both content (ILIKE) and tags for matches.
-/// Results are ordered by confidence (high → medium → low), then by recency.
+/// In ILIKE mode, searches both content and tags for substring matches;
+/// results are ordered by `options.ranker`.
+/// In FTS mode, see [`search_fts`]. With `as_of`, searches the memories that
+/// were active at that time (content reflects later in-place updates).
 pub async fn search_keyword(pool: &PgPool, options: SearchOptions) -> Result<SearchResult> {
+    if options.mode == SearchMode::Fts {
+        return search_fts(pool, options).await;
+    }
+
     let (scope_filter, include_both) = tier_to_scope_filter(options.tier);
 
-    let memories = queries::search_keyword(
+    // One extra row tells whether there is a next page
+    let mut memories = queries::search_keyword(
         pool,
         &options.query,
         scope_filter,
-        options.project_path.as_deref(),
+        &options.project_paths,
         include_both,
-        options.limit,
+        options.limit + 1,
+        options.offset,
+        options.cursor.as_ref(),
+        options.as_of,
+        options.include_archived,
+        &options.filters,
+        options.ranker.as_ref(),
     )
     .await?;
+    let has_more = cursor::take_page(&mut memories, options.limit as usize);
+    let next_cursor = cursor::next(&memories, has_more);
 
-    // Mark returned memories as accessed
-    if !memories.is_empty() {
-        let ids: Vec<uuid::Uuid> = memories.iter().map(|m| m.id).collect();
+    // Mark returned memories as accessed (looking at history is not a use)
+    if !memories.is_empty() && options.as_of.is_none() {
+        let ids: Vec<uuid::Uuid> = memories.iter().map(|(m, _)| m.id).collect();
+        queries::mark_memories_accessed(pool, &ids).await?;
+    }
+
+    let results: Vec<MemorySearchItem> = memories.into_iter().map(|(m, _)| m.into()).collect();
+    let count = results.len();
+
+    Ok(SearchResult { results, count, next_cursor })
+}
+
+/// Full-text search over content and tags.
+///
+/// Uses the `search_vector` column (schema v6) and orders results by
+/// `ts_rank`, which is reported on each result.
+pub async fn search_fts(pool: &PgPool, options: SearchOptions) -> Result<SearchResult> {
+    let (scope_filter, include_both) = tier_to_scope_filter(options.tier);
+
+    let mut ranked = queries::search_fts(
+        pool,
+        &options.query,
+        scope_filter,
+        &options.project_paths,
+        include_both,
+        options.limit + 1,
+        options.offset,
+        options.cursor.as_ref(),
+        options.as_of,
+        options.include_archived,
+        &options.filters,
+    )
+    .await?;
+    let has_more = cursor::take_page(&mut ranked, options.limit as usize);
+    let at = options.cursor.as_ref().map_or_else(Utc::now, |c| c.at);
+    let next_cursor = ranked.last().filter(|_| has_more).map(|(memory, rank)| {
+        Cursor {
+            order: queries::FTS_ORDER.to_string(),
+            key: vec![rank.to_string()],
+            id: memory.id,
+            at,
+        }
+        .encode()
+    });
+
+    // Mark returned memories as accessed (looking at history is not a use)
+    if !ranked.is_empty() && options.as_of.is_none() {
+        let ids: Vec<uuid::Uuid> = ranked.iter().map(|(m, _)| m.id).collect();
         queries::mark_memories_accessed(pool, &ids).await?;
     }
 
-    let results: Vec<MemorySearchItem> = memories.into_iter().map(Into::into).collect();
+    let results: Vec<MemorySearchItem> = ranked
+        .into_iter()
+        .map(|(memory, rank)| MemorySearchItem {
+            rank: Some(rank),
+            ..memory.into()
+        })
+        .collect();
     let count = results.len();
 
-    Ok(SearchResult { results, count })
+    Ok(SearchResult { results, count, next_cursor })
+}
+
+/// Hybrid search: full-text and semantic rankings merged into one.
+///
+/// Takes the best candidates of full-text search and, with
+/// `embedding_model` set, of cosine similarity to the query over memories
+/// embedded by `embed-memories`, and merges them by reciprocal rank fusion.
+/// Each result carries its rank, raw and normalized score in each source.
+pub async fn search(pool: &PgPool, config: &DbConfig, options: HybridSearchOptions) -> Result<HybridSearchResult> {
+    let (scope_filter, include_both) = tier_to_scope_filter(options.tier);
+    let candidates = options.limit.max(HYBRID_CANDIDATES);
+
+    let fts: Vec<(Memory, f64)> = queries::search_fts(
+        pool,
+        &options.query,
+        scope_filter,
+        &options.project_paths,
+        include_both,
+        candidates as i32,
+        0,
+        None,
+        None,
+        false,
+        &options.filters,
+    )
+    .await?
+    .into_iter()
+    .map(|(memory, rank)| (memory, rank as f64))
+    .collect();
+
+    let embedder = match config.embedding_model {
+        Some(_) => Some(Embedder::from_config(config)?),
+        None => None,
+    };
+    let mut semantic = Vec::new();
+    if let Some(embedder) = &embedder {
+        let query_vector = embedder
+            .embed(std::slice::from_ref(&options.query))?
+            .pop()
+            .unwrap_or_default();
+        semantic = queries::list_memory_embeddings(
+            pool,
+            &embedder.model,
+            scope_filter,
+            &options.project_paths,
+            include_both,
+            &options.filters,
+        )
+        .await?
+        .into_iter()
+        .map(|e| {
+            let similarity = cosine_similarity(&query_vector, &e.embedding);
+            (e.memory, similarity)
+        })
+        .collect();
+        semantic.sort_by(|a: &(Memory, f64), b| b.1.total_cmp(&a.1));
+        semantic.truncate(candidates);
+    }
+
+    let results = fuse(fts, semantic, options.limit);
+    if !results.is_empty() {
+        let ids: Vec<Uuid> = results.iter().map(|r| r.memory.id).collect();
+        queries::mark_memories_accessed(pool, &ids).await?;
+    }
+
+    Ok(HybridSearchResult {
+        query: options.query,
+        model: embedder.map(|e| e.model),
+        count: results.len(),
+        results,
+    })
 }
 
 /// Search memories by type (with optional keyword filter).
 ///
 /// Filters by memory type first, then optionally by keyword.
-/// Results are ordered by confidence (high → medium → low), then by recency.
+/// Results are ordered by `options.ranker`.
 pub async fn search_by_type(pool: &PgPool, options: SearchByTypeOptions) -> Result<SearchResult> {
     let (scope_filter, include_both) = tier_to_scope_filter(options.tier);
 
-    let memories = queries::search_by_type(
+    let mut memories = queries::search_by_type(
         pool,
         options.memory_type,
         options.query.as_deref(),
         scope_filter,
-        options.project_path.as_deref(),
+        &options.project_paths,
         include_both,
-        options.limit,
+        options.limit + 1,
+        options.offset,
+        options.cursor.as_ref(),
+        options.include_archived,
+        &options.filters,
+        options.ranker.as_ref(),
     )
     .await?;
+    let has_more = cursor::take_page(&mut memories, options.limit as usize);
+    let next_cursor = cursor::next(&memories, has_more);
 
     // Mark returned memories as accessed
     if !memories.is_empty() {
-        let ids: Vec<uuid::Uuid> = memories.iter().map(|m| m.id).collect();
+        let ids: Vec<uuid::Uuid> = memories.iter().map(|(m, _)| m.id).collect();
         queries::mark_memories_accessed(pool, &ids).await?;
     }
 
-    let results: Vec<MemorySearchItem> = memories.into_iter().map(Into::into).collect();
+    let results: Vec<MemorySearchItem> = memories.into_iter().map(|(m, _)| m.into()).collect();
     let count = results.len();
 
-    Ok(SearchResult { results, count })
+    Ok(SearchResult { results, count, next_cursor })
 }
 
-/// Get context block for injection (top memories by relevance).
+/// Get context block for injection (top memories by `ranker`).
 ///
 /// Returns a formatted markdown block suitable for injection into prompts,
-/// along with the raw entry data.
+/// along with the raw entry data. With an environment, memories whose
+/// constraints it does not satisfy are left out. With `since` (see
+/// [`queries::last_session_boundary`]), only memories created or updated
+/// after it are included. Pinned memories come first, whatever their rank
+/// and `since`, and take their share of `limit` without ever being left
+/// out for it. `muted` memories (see [`queries::mute_memory`]) are never
+/// included. `layout` caps entries per type, can leave out memories the
+/// project's CLAUDE.md already says, and can group them under a heading per
+/// type. With `max_tokens`, the block is cut to fit that budget
+/// (see [`fit_to_budget`]).
+#[allow(clippy::too_many_arguments)]
 pub async fn get_context(
     pool: &PgPool,
     limit: i32,
-    project_path: Option<&str>,
+    project_paths: &[String],
+    environment: Option<&EnvironmentSnapshot>,
+    ranker: &dyn Ranker,
+    since: Option<DateTime<Utc>>,
+    muted: &[Uuid],
+    max_tokens: Option<usize>,
+    layout: &ContextLayout,
 ) -> Result<ContextResult> {
-    let memories = queries::get_context_memories(pool, project_path, limit).await?;
+    let pinned: Vec<Memory> = queries::get_pinned_memories(pool, project_paths, muted)
+        .await?
+        .into_iter()
+        .filter(|m| environment.is_none_or(|env| constraints_match(&m.constraints(), env)))
+        .collect();
+    let limit = (limit - pinned.len() as i32).max(0);
+
+    // Over-fetch when filtering so filtered-out memories don't shrink the context
+    let filtered =
+        environment.is_some() || !layout.type_limits.is_empty() || layout.claude_md.is_some();
+    let fetch = if filtered { limit + CONSTRAINT_HEADROOM } else { limit };
+    let candidates =
+        queries::get_context_memories(pool, project_paths, fetch, ranker, since, muted).await?;
+    let mut taken: Vec<MemoryType> = pinned.iter().map(|m| m.memory_type).collect();
+    let mut ranked: Vec<Memory> = Vec::new();
+    for memory in candidates {
+        if ranked.len() >= limit as usize {
+            break;
+        }
+        if environment.is_some_and(|env| !constraints_match(&memory.constraints(), env)) {
+            continue;
+        }
+        if let Some((claude_md, overlap)) = &layout.claude_md {
+            if claude_md.covers(&memory.summary, *overlap) {
+                continue;
+            }
+        }
+        if let Some(type_limit) = layout.type_limit(memory.memory_type) {
+            if taken.iter().filter(|t| **t == memory.memory_type).count() >= type_limit {
+                continue;
+            }
+        }
+        taken.push(memory.memory_type);
+        ranked.push(memory);
+    }
+
+    let entries: Vec<MemorySummary> = pinned.iter().chain(&ranked).map(|m| m.to_summary()).collect();
+    let briefing = queries::get_briefing(pool, project_paths).await?.map(|b| b.content);
+
+    let (briefing, mut entries, omitted) = match max_tokens {
+        Some(max_tokens) => fit_to_budget(briefing, entries, since, max_tokens, layout.group_by_type),
+        None => (briefing, entries, 0),
+    };
+    let sections = layout.group_by_type.then(|| group_by_type(&mut entries));
 
     // Mark returned memories as accessed
-    if !memories.is_empty() {
-        let ids: Vec<uuid::Uuid> = memories.iter().map(|m| m.id).collect();
+    if !entries.is_empty() {
+        let ids: Vec<Uuid> = entries.iter().map(|e| e.id).collect();
         queries::mark_memories_accessed(pool, &ids).await?;
     }
 
-    let entries: Vec<MemorySummary> = memories.iter().map(|m| m.to_summary()).collect();
-
     // Format as markdown context block
-    let context = format_context_block(&entries);
+    let context =
+        format_context_block(briefing.as_deref(), &entries, since, omitted, layout.group_by_type);
+    let budget = max_tokens.map(|max_tokens| ContextBudget {
+        max_tokens,
+        estimated_tokens: estimate_tokens(&context),
+        omitted,
+    });
 
     Ok(ContextResult {
         context,
+        briefing,
+        since,
         count: entries.len(),
         entries,
+        sections,
+        budget,
     })
 }
 
 /// List recent memories.
 ///
-/// Returns memories sorted by creation date (newest first).
+/// Returns memories sorted by creation date (newest first), skipping the
+/// first `offset` or starting after the row of `after`.
 pub async fn list_recent(
     pool: &PgPool,
     limit: i32,
+    offset: i64,
+    after: Option<&Cursor>,
     tier: Tier,
-    project_path: Option<&str>,
+    project_paths: &[String],
 ) -> Result<ListRecentResult> {
     let (scope_filter, include_both) = tier_to_scope_filter(tier);
 
-    let (memories, total) =
-        queries::list_recent(pool, scope_filter, project_path, include_both, limit).await?;
-
-    let entries: Vec<MemorySummary> = memories.iter().map(|m| m.to_summary()).collect();
+    // One extra row tells whether there is a next page
+    let (mut memories, total) =
+        queries::list_recent(pool, scope_filter, project_paths, include_both, limit + 1, offset, after)
+            .await?;
+    let has_more = cursor::take_page(&mut memories, limit as usize);
 
     Ok(ListRecentResult {
-        entries,
+        next_cursor: cursor::next(&memories, has_more),
+        entries: memories.iter().map(|(m, _)| m.to_summary()).collect(),
         total: total as usize,
     })
 }
@@ -244,7 +592,7 @@ pub async fn list_recent(
 // ============================================================================
 
 /// Convert Tier to (Option<Scope>, include_both) for query building
-fn tier_to_scope_filter(tier: Tier) -> (Option<Scope>, bool) {
+pub(crate) fn tier_to_scope_filter(tier: Tier) -> (Option<Scope>, bool) {
     match tier {
         Tier::Project => (Some(Scope::Project), false),
         Tier::Global => (Some(Scope::Global), false),
@@ -252,23 +600,274 @@ fn tier_to_scope_filter(tier: Tier) -> (Option<Scope>, bool) {
     }
 }
 
-/// Format entries as a markdown context block
-fn format_context_block(entries: &[MemorySummary]) -> String {
-    let mut context = String::from("## Memory Context\n\n");
+/// The field of a match holding one source's score
+type ScoreSlot = fn(&mut HybridMatch) -> &mut Option<SourceScore>;
+
+/// Merge two rankings, each best first, into the `limit` best by
+/// reciprocal rank fusion
+fn fuse(fts: Vec<(Memory, f64)>, semantic: Vec<(Memory, f64)>, limit: usize) -> Vec<HybridMatch> {
+    let mut matches: Vec<HybridMatch> = Vec::new();
+    let mut positions: HashMap<Uuid, usize> = HashMap::new();
+
+    let sources: [(Vec<(Memory, f64)>, ScoreSlot); 2] = [(fts, |m| &mut m.fts), (semantic, |m| &mut m.semantic)];
+    for (ranked, slot) in sources {
+        let scores: Vec<f64> = ranked.iter().map(|(_, score)| *score).collect();
+        let (low, high) = scores
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), s| (low.min(*s), high.max(*s)));
+        for (index, (memory, score)) in ranked.into_iter().enumerate() {
+            let rank = index + 1;
+            let position = *positions.entry(memory.id).or_insert_with(|| {
+                matches.push(HybridMatch {
+                    memory: memory.into(),
+                    score: 0.0,
+                    fts: None,
+                    semantic: None,
+                });
+                matches.len() - 1
+            });
+            let entry = &mut matches[position];
+            entry.score += 1.0 / (RRF_K + rank as f64);
+            *slot(entry) = Some(SourceScore {
+                rank,
+                score,
+                normalized: if high > low { (score - low) / (high - low) } else { 1.0 },
+            });
+        }
+    }
+
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    matches.truncate(limit);
+    matches
+}
+
+/// Keep the briefing and the highest-ranked entries that fit in
+/// `max_tokens`, returning them with the number of entries left out.
+///
+/// Entries are taken in rank order. The first one that does not fit keeps a
+/// truncated summary if enough room is left, and the rest are replaced by a
+/// note saying how many were left out. A briefing too long for the budget is
+/// truncated to half of it, leaving the other half for memories. When
+/// `grouped`, room is kept for the heading of every type among `entries`.
+fn fit_to_budget(
+    briefing: Option<String>,
+    entries: Vec<MemorySummary>,
+    since: Option<DateTime<Utc>>,
+    max_tokens: usize,
+    grouped: bool,
+) -> (Option<String>, Vec<MemorySummary>, usize) {
+    // Pieces are estimated separately, which only ever overestimates
+    let mut remaining = max_tokens.saturating_sub(estimate_tokens(&context_header(since)));
+    if grouped {
+        let headings: usize = CONTEXT_SECTIONS
+            .iter()
+            .filter(|(t, _)| entries.iter().any(|e| e.memory_type == *t))
+            .map(|(_, heading)| estimate_tokens(&format!("\n{}", section_heading(heading))))
+            .sum();
+        remaining = remaining.saturating_sub(headings);
+    }
+
+    let briefing = briefing.and_then(|briefing| {
+        let cost = estimate_tokens(&briefing_section(&briefing));
+        if cost <= remaining {
+            remaining -= cost;
+            return Some(briefing);
+        }
+        let room = (remaining / 2).saturating_sub(estimate_tokens(&briefing_section("")));
+        if room < MIN_TRUNCATED_TOKENS {
+            return None;
+        }
+        let cut = truncate_to_tokens(&briefing, room);
+        remaining = remaining.saturating_sub(estimate_tokens(&briefing_section(&cut)));
+        Some(cut)
+    });
+
+    let total: usize = entries.iter().map(|e| estimate_tokens(&entry_line(e))).sum();
+    if total <= remaining {
+        return (briefing, entries, 0);
+    }
+
+    let count = entries.len();
+    remaining = remaining.saturating_sub(estimate_tokens(&omitted_note(count)));
+    let mut kept = Vec::new();
+    for mut entry in entries {
+        let cost = estimate_tokens(&entry_line(&entry));
+        if cost <= remaining {
+            remaining -= cost;
+            kept.push(entry);
+            continue;
+        }
+        let bare = MemorySummary {
+            summary: String::new(),
+            ..entry.clone()
+        };
+        let room = remaining.saturating_sub(estimate_tokens(&entry_line(&bare)));
+        if room >= MIN_TRUNCATED_TOKENS {
+            entry.summary = truncate_to_tokens(&entry.summary, room);
+            kept.push(entry);
+        }
+        break;
+    }
+
+    let omitted = count - kept.len();
+    (briefing, kept, omitted)
+}
+
+/// Sort `entries` into section order, keeping rank order within each type,
+/// and describe the sections
+fn group_by_type(entries: &mut Vec<MemorySummary>) -> Vec<ContextSection> {
+    let mut sections = Vec::new();
+    let mut grouped = Vec::with_capacity(entries.len());
+    for (memory_type, heading) in CONTEXT_SECTIONS {
+        let before = grouped.len();
+        grouped.extend(entries.iter().filter(|e| e.memory_type == memory_type).cloned());
+        if grouped.len() > before {
+            sections.push(ContextSection {
+                memory_type,
+                heading: heading.to_string(),
+                count: grouped.len() - before,
+            });
+        }
+    }
+    *entries = grouped;
+    sections
+}
 
-    if entries.is_empty() {
-        context.push_str("No memories loaded.\n");
+/// Format entries as a markdown context block, led by the briefing if any.
+///
+/// When `grouped`, entries (already in section order, see [`group_by_type`])
+/// go under a heading per type instead of naming their type on each line.
+fn format_context_block(
+    briefing: Option<&str>,
+    entries: &[MemorySummary],
+    since: Option<DateTime<Utc>>,
+    omitted: usize,
+    grouped: bool,
+) -> String {
+    let mut context = briefing.map(briefing_section).unwrap_or_default();
+    context.push_str(&context_header(since));
+
+    if entries.is_empty() && omitted == 0 {
+        match since {
+            Some(_) => context.push_str("Nothing new since the last session.\n"),
+            None => context.push_str("No memories loaded.\n"),
+        }
+    }
+    if grouped {
+        for (memory_type, heading) in CONTEXT_SECTIONS {
+            let mut section = entries.iter().filter(|e| e.memory_type == memory_type).peekable();
+            if section.peek().is_none() {
+                continue;
+            }
+            if !context.ends_with("\n\n") {
+                context.push('\n');
+            }
+            context.push_str(&section_heading(heading));
+            for entry in section {
+                context.push_str(&grouped_entry_line(entry));
+            }
+        }
     } else {
         for entry in entries {
-            let symbol = entry.confidence.symbol();
-            let type_str = entry.memory_type.as_str();
-            context.push_str(&format!("- {} **{}**: {}\n", symbol, type_str, entry.summary));
+            context.push_str(&entry_line(entry));
         }
     }
+    if omitted > 0 {
+        context.push_str(&omitted_note(omitted));
+    }
 
     context
 }
 
+fn briefing_section(briefing: &str) -> String {
+    format!("## Project Briefing\n\n{}\n\n", briefing)
+}
+
+fn context_header(since: Option<DateTime<Utc>>) -> String {
+    match since {
+        Some(since) => format!(
+            "## New Since Last Session ({})\n\n",
+            since.format("%Y-%m-%d %H:%M UTC")
+        ),
+        None => String::from("## Memory Context\n\n"),
+    }
+}
+
+fn entry_line(entry: &MemorySummary) -> String {
+    let pin = if entry.pinned { "📌 " } else { "" };
+    let symbol = entry.confidence.symbol();
+    let type_str = entry.memory_type.as_str();
+    format!("- {}{} **{}**: {}\n", pin, symbol, type_str, entry.summary)
+}
+
+fn section_heading(heading: &str) -> String {
+    format!("### {}\n\n", heading)
+}
+
+/// Entry line under a type heading, which names the type instead
+fn grouped_entry_line(entry: &MemorySummary) -> String {
+    let pin = if entry.pinned { "📌 " } else { "" };
+    format!("- {}{} {}\n", pin, entry.confidence.symbol(), entry.summary)
+}
+
+fn omitted_note(omitted: usize) -> String {
+    format!(
+        "\n_{} more {} left out to fit the token budget._\n",
+        omitted,
+        if omitted == 1 { "memory" } else { "memories" }
+    )
+}
+
+/// Tera filter turning a confidence into ★, ◐ or ○
+fn confidence_symbol(value: &tera::Value, _args: &HashMap<String, tera::Value>) -> tera::Result<tera::Value> {
+    let confidence = value.as_str().and_then(|c| c.parse::<Confidence>().ok());
+    Ok(tera::Value::from(confidence.map_or("", |c| c.symbol())))
+}
+
+/// A Tera error with its causes, which say where the template went wrong
+fn template_error(error: &tera::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        message.push_str(&format!(": {}", cause));
+        source = cause.source();
+    }
+    message
+}
+
+/// Render a get-context result through a user's Tera template
+/// (`get-context --template`, or `context_template_file`).
+///
+/// The template sees the result as JSON, with each section also holding its
+/// `entries`, plus a `symbol` filter turning a confidence into ★, ◐ or ○.
+/// Output is not HTML-escaped.
+pub fn render_context_template(template: &str, result: &ContextResult) -> Result<String> {
+    let mut tera = tera::Tera::default();
+    tera.autoescape_on(Vec::new());
+    tera.register_filter("symbol", confidence_symbol);
+    tera.add_raw_template("context", template)
+        .map_err(|e| HippocampusError::Config(format!("Invalid context template: {}", template_error(&e))))?;
+
+    let mut data = serde_json::to_value(result)?;
+    if let Some(sections) = result.sections.as_ref() {
+        let mut entries = result.entries.iter();
+        let sections: Vec<serde_json::Value> = sections
+            .iter()
+            .map(|section| {
+                let mut value = serde_json::to_value(section)?;
+                let section_entries: Vec<&MemorySummary> = entries.by_ref().take(section.count).collect();
+                value["entries"] = serde_json::to_value(section_entries)?;
+                Ok(value)
+            })
+            .collect::<Result<_>>()?;
+        data["sections"] = serde_json::Value::Array(sections);
+    }
+
+    tera::Context::from_value(data)
+        .and_then(|context| tera.render("context", &context))
+        .map_err(|e| HippocampusError::Config(format!("Context template failed to render: {}", template_error(&e))))
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -288,88 +887,34 @@ mod tests {
     fn test_search_options_default() {
         let options = SearchOptions::default();
         assert_eq!(options.query, "");
+        assert_eq!(options.mode, SearchMode::Ilike);
         assert_eq!(options.tier, Tier::Both);
         assert_eq!(options.limit, 30);
-        assert_eq!(options.project_path, None);
+        assert!(options.project_paths.is_empty());
+        assert_eq!(options.as_of, None);
+        assert_eq!(options.ranker.name(), "confidence");
     }
 
     #[test]
     fn test_search_options_custom() {
         let options = SearchOptions {
             query: "test query".to_string(),
+            mode: SearchMode::Fts,
             tier: Tier::Project,
             limit: 10,
-            project_path: Some("/test/path".to_string()),
+            offset: 0,
+            cursor: None,
+            project_paths: vec!["/test/path".to_string()],
+            as_of: None,
+            include_archived: false,
+            filters: SearchFilters::default(),
+            ranker: Arc::new(ConfidenceFirst),
         };
 
         assert_eq!(options.query, "test query");
         assert_eq!(options.tier, Tier::Project);
         assert_eq!(options.limit, 10);
-        assert_eq!(options.project_path, Some("/test/path".to_string()));
-    }
-
-    // -------------------------------------------------------------------------
-    // MemorySearchItem tests
-    // -------------------------------------------------------------------------
-
-    #[test]
-    fn test_memory_search_item_from_memory_short_content() {
-        let memory = Memory {
-            id: Uuid::new_v4(),
-            memory_type: MemoryType::Learning,
-            scope: Scope::Project,
-            project_path: Some("/test".to_string()),
-            content: "Short content".to_string(),
-            tags: vec!["test".to_string()],
-            confidence: Confidence::High,
-            source_session_id: None,
-            source_turn_id: None,
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
-            accessed_at: None,
-            access_count: 5,
-            superseded_by: None,
-            superseded_at: None,
-            is_active: true,
-        };
-
-        let item: MemorySearchItem = memory.into();
-
-        assert_eq!(item.summary, "Short content");
-        assert_eq!(item.content, "Short content");
-        assert_eq!(item.memory_type, MemoryType::Learning);
-        assert_eq!(item.confidence, Confidence::High);
-        assert_eq!(item.access_count, 5);
-    }
-
-    #[test]
-    fn test_memory_search_item_from_memory_long_content() {
-        let long_content = "x".repeat(150);
-        let memory = Memory {
-            id: Uuid::new_v4(),
-            memory_type: MemoryType::Gotcha,
-            scope: Scope::Global,
-            project_path: None,
-            content: long_content.clone(),
-            tags: vec![],
-            confidence: Confidence::Medium,
-            source_session_id: None,
-            source_turn_id: None,
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
-            accessed_at: Some(Utc::now()),
-            access_count: 0,
-            superseded_by: None,
-            superseded_at: None,
-            is_active: true,
-        };
-
-        let item: MemorySearchItem = memory.into();
-
-        assert_eq!(item.summary.len(), 100); // 97 + "..."
-        assert!(item.summary.ends_with("..."));
-        assert_eq!(item.content, long_content);
-        assert!(item.accessed.is_some());
+        assert_eq!(options.project_paths, vec!["/test/path"]);
     }
 
     // -------------------------------------------------------------------------
@@ -404,12 +949,84 @@ mod tests {
     #[test]
     fn test_format_context_block_empty() {
         let entries: Vec<MemorySummary> = vec![];
-        let context = format_context_block(&entries);
+        let context = format_context_block(None, &entries, None, 0, false);
 
         assert!(context.contains("## Memory Context"));
         assert!(context.contains("No memories loaded."));
     }
 
+    #[test]
+    fn test_format_context_block_since_last_session() {
+        let since = DateTime::parse_from_rfc3339("2024-03-01T09:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let context = format_context_block(None, &[], Some(since), 0, false);
+
+        assert!(context.starts_with("## New Since Last Session (2024-03-01 09:30 UTC)"));
+        assert!(context.contains("Nothing new since the last session."));
+    }
+
+    fn learning(summary: &str) -> MemorySummary {
+        MemorySummary {
+            id: Uuid::new_v4(),
+            memory_type: MemoryType::Learning,
+            tier: Scope::Project,
+            summary: summary.to_string(),
+            tags: vec![],
+            confidence: Confidence::High,
+            created: Utc::now(),
+            access_count: 0,
+            superseded_by: None,
+            superseded_at: None,
+            is_active: true,
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn test_fit_to_budget_keeps_everything_that_fits() {
+        let entries = vec![learning("First"), learning("Second")];
+        let (briefing, kept, omitted) =
+            fit_to_budget(Some("Anchor".to_string()), entries, None, 1000, false);
+        assert_eq!(briefing.as_deref(), Some("Anchor"));
+        assert_eq!(kept.len(), 2);
+        assert_eq!(omitted, 0);
+    }
+
+    #[test]
+    fn test_fit_to_budget_truncates_and_omits_in_rank_order() {
+        let entries: Vec<MemorySummary> = (0..10)
+            .map(|i| learning(&format!("Memory number {} describes something at length", i)))
+            .collect();
+        let (_, kept, omitted) = fit_to_budget(None, entries, None, 60, false);
+
+        assert_eq!(kept.len() + omitted, 10);
+        assert!(omitted > 0);
+        assert!(kept[0].summary.starts_with("Memory number 0"));
+        let context = format_context_block(None, &kept, None, omitted, false);
+        assert!(estimate_tokens(&context) <= 60);
+        assert!(context.ends_with(&format!("_{} more memories left out to fit the token budget._\n", omitted)));
+    }
+
+    #[test]
+    fn test_fit_to_budget_truncates_long_briefing_to_half() {
+        let briefing = "Briefing text. ".repeat(100);
+        let (briefing, kept, _) =
+            fit_to_budget(Some(briefing), vec![learning("Short")], None, 100, false);
+        let briefing = briefing.unwrap();
+        assert!(briefing.ends_with("..."));
+        assert!(estimate_tokens(&briefing) <= 50);
+        assert_eq!(kept.len(), 1);
+    }
+
+    #[test]
+    fn test_format_context_block_briefing_first() {
+        let context = format_context_block(Some("# Billing\n\nOwned by payments."), &[], None, 0, false);
+
+        assert!(context.starts_with("## Project Briefing\n\n# Billing\n\nOwned by payments.\n\n"));
+        assert!(context.find("## Project Briefing") < context.find("## Memory Context"));
+    }
+
     #[test]
     fn test_format_context_block_with_entries() {
         let entries = vec![
@@ -425,6 +1042,7 @@ mod tests {
                 superseded_by: None,
                 superseded_at: None,
                 is_active: true,
+                pinned: false,
             },
             MemorySummary {
                 id: Uuid::new_v4(),
@@ -438,6 +1056,7 @@ mod tests {
                 superseded_by: None,
                 superseded_at: None,
                 is_active: true,
+                pinned: false,
             },
             MemorySummary {
                 id: Uuid::new_v4(),
@@ -451,10 +1070,11 @@ mod tests {
                 superseded_by: None,
                 superseded_at: None,
                 is_active: true,
+                pinned: false,
             },
         ];
 
-        let context = format_context_block(&entries);
+        let context = format_context_block(None, &entries, None, 0, false);
 
         assert!(context.contains("## Memory Context"));
         assert!(!context.contains("No memories loaded."));
@@ -485,6 +1105,7 @@ mod tests {
                 superseded_by: None,
                 superseded_at: None,
                 is_active: true,
+                pinned: false,
             },
             MemorySummary {
                 id: Uuid::new_v4(),
@@ -498,16 +1119,48 @@ mod tests {
                 superseded_by: None,
                 superseded_at: None,
                 is_active: true,
+                pinned: false,
             },
         ];
 
-        let context = format_context_block(&entries);
+        let context = format_context_block(None, &entries, None, 0, false);
         let first_pos = context.find("First").unwrap();
         let second_pos = context.find("Second").unwrap();
 
         assert!(first_pos < second_pos);
     }
 
+    #[test]
+    fn test_format_context_block_grouped_by_type() {
+        let gotcha = MemorySummary {
+            memory_type: MemoryType::Gotcha,
+            confidence: Confidence::Medium,
+            ..learning("Migrations lock the table")
+        };
+        let mut entries = vec![learning("Tokio needs a runtime"), gotcha, learning("Axum extracts in order")];
+        let sections = group_by_type(&mut entries);
+
+        let summary: Vec<(&str, usize)> = sections.iter().map(|s| (s.heading.as_str(), s.count)).collect();
+        assert_eq!(summary, vec![("Gotchas", 1), ("Learnings", 2)]);
+        let context = format_context_block(None, &entries, None, 0, true);
+        assert_eq!(
+            context,
+            "## Memory Context\n\n### Gotchas\n\n- ◐ Migrations lock the table\n\n\
+             ### Learnings\n\n- ★ Tokio needs a runtime\n- ★ Axum extracts in order\n"
+        );
+    }
+
+    #[test]
+    fn test_context_layout_type_limit() {
+        let layout = ContextLayout {
+            group_by_type: true,
+            type_limits: vec![(MemoryType::Gotcha, 3), (MemoryType::Gotcha, 1)],
+            claude_md: None,
+        };
+        assert_eq!(layout.type_limit(MemoryType::Gotcha), Some(1));
+        assert_eq!(layout.type_limit(MemoryType::Api), None);
+    }
+
     // -------------------------------------------------------------------------
     // SearchResult tests
     // -------------------------------------------------------------------------
@@ -517,6 +1170,7 @@ mod tests {
         let result = SearchResult {
             results: vec![],
             count: 0,
+            next_cursor: None,
         };
 
         assert!(result.results.is_empty());
@@ -534,14 +1188,19 @@ mod tests {
             query: Some("test query".to_string()),
             tier: Tier::Project,
             limit: 10,
-            project_path: Some("/test/path".to_string()),
+            offset: 0,
+            cursor: None,
+            project_paths: vec!["/test/path".to_string()],
+            include_archived: false,
+            filters: SearchFilters::default(),
+            ranker: Arc::new(ConfidenceFirst),
         };
 
         assert_eq!(options.memory_type, MemoryType::Gotcha);
         assert_eq!(options.query, Some("test query".to_string()));
         assert_eq!(options.tier, Tier::Project);
         assert_eq!(options.limit, 10);
-        assert_eq!(options.project_path, Some("/test/path".to_string()));
+        assert_eq!(options.project_paths, vec!["/test/path"]);
     }
 
     #[test]
@@ -551,14 +1210,19 @@ mod tests {
             query: None,
             tier: Tier::Both,
             limit: 30,
-            project_path: None,
+            offset: 0,
+            cursor: None,
+            project_paths: Vec::new(),
+            include_archived: false,
+            filters: SearchFilters::default(),
+            ranker: Arc::new(ConfidenceFirst),
         };
 
         assert_eq!(options.memory_type, MemoryType::Learning);
         assert!(options.query.is_none());
         assert_eq!(options.tier, Tier::Both);
         assert_eq!(options.limit, 30);
-        assert!(options.project_path.is_none());
+        assert!(options.project_paths.is_empty());
     }
 
     #[test]
@@ -576,7 +1240,12 @@ mod tests {
                 query: None,
                 tier: Tier::Both,
                 limit: 10,
-                project_path: None,
+                offset: 0,
+                cursor: None,
+                project_paths: Vec::new(),
+                include_archived: false,
+                filters: SearchFilters::default(),
+                ranker: Arc::new(ConfidenceFirst),
             };
             // Just ensure we can create options for all types
             assert_eq!(options.memory_type, memory_type);
@@ -591,8 +1260,12 @@ mod tests {
     fn test_context_result_structure() {
         let result = ContextResult {
             context: "## Memory Context\n\nNo memories loaded.\n".to_string(),
+            briefing: None,
+            since: None,
             count: 0,
             entries: vec![],
+            sections: None,
+            budget: None,
         };
 
         assert!(result.context.contains("Memory Context"));
@@ -600,6 +1273,38 @@ mod tests {
         assert!(result.entries.is_empty());
     }
 
+    #[test]
+    fn test_render_context_template() {
+        let gotcha = MemorySummary {
+            memory_type: MemoryType::Gotcha,
+            tags: vec!["db".to_string(), "sql".to_string()],
+            ..learning("Migrations lock the table")
+        };
+        let mut entries = vec![learning("Tokio needs a runtime"), gotcha];
+        let sections = group_by_type(&mut entries);
+        let result = ContextResult {
+            context: String::new(),
+            briefing: None,
+            since: None,
+            count: entries.len(),
+            entries,
+            sections: Some(sections),
+            budget: None,
+        };
+
+        let template = "{% for section in sections %}<{{ section.heading }}>{% for entry in section.entries %} \
+                        {{ entry.confidence | symbol }} {{ entry.summary }} [{{ entry.tags | join(sep=\",\") }}]\
+                        {% endfor %}\n{% endfor %}";
+        assert_eq!(
+            render_context_template(template, &result).unwrap(),
+            "<Gotchas> ★ Migrations lock the table [db,sql]\n<Learnings> ★ Tokio needs a runtime []\n"
+        );
+        assert!(matches!(
+            render_context_template("{% for entry in entries %}", &result),
+            Err(HippocampusError::Config(_))
+        ));
+    }
+
     // -------------------------------------------------------------------------
     // ListRecentResult tests
     // -------------------------------------------------------------------------
@@ -609,6 +1314,7 @@ mod tests {
         let result = ListRecentResult {
             entries: vec![],
             total: 100,
+            next_cursor: None,
         };
 
         assert!(result.entries.is_empty());
@@ -619,57 +1325,16 @@ mod tests {
     // JSON Serialization tests (Node.js compatibility)
     // -------------------------------------------------------------------------
 
-    #[test]
-    fn test_memory_search_item_json_serialization() {
-        let item = MemorySearchItem {
-            id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap(),
-            memory_type: MemoryType::Learning,
-            tier: Scope::Project,
-            summary: "Test summary".to_string(),
-            content: "Test content".to_string(),
-            tags: vec!["tag1".to_string(), "tag2".to_string()],
-            confidence: Confidence::High,
-            created: chrono::DateTime::parse_from_rfc3339("2024-01-15T10:00:00Z")
-                .unwrap()
-                .with_timezone(&Utc),
-            accessed: None,
-            access_count: 5,
-        };
-
-        let json = serde_json::to_string(&item).unwrap();
-
-        // Verify camelCase field names (matching Node.js output)
-        assert!(json.contains("\"type\":\"learning\"")); // renamed from memoryType
-        assert!(json.contains("\"accessCount\":5"));
-
-        // Verify field presence
-        assert!(json.contains("\"id\":"));
-        assert!(json.contains("\"tier\":\"project\""));
-        assert!(json.contains("\"summary\":"));
-        assert!(json.contains("\"content\":"));
-        assert!(json.contains("\"tags\":"));
-        assert!(json.contains("\"confidence\":\"high\""));
-    }
-
-    #[test]
-    fn test_search_result_json_serialization() {
-        let result = SearchResult {
-            results: vec![],
-            count: 0,
-        };
-
-        let json = serde_json::to_string(&result).unwrap();
-
-        assert!(json.contains("\"results\":[]"));
-        assert!(json.contains("\"count\":0"));
-    }
-
     #[test]
     fn test_context_result_json_serialization() {
         let result = ContextResult {
             context: "## Memory Context\n\n- ★ **learning**: Test".to_string(),
+            briefing: None,
+            since: None,
             count: 1,
             entries: vec![],
+            sections: None,
+            budget: None,
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -678,4 +1343,39 @@ mod tests {
         assert!(json.contains("\"count\":1"));
         assert!(json.contains("\"entries\":[]"));
     }
+
+    // -------------------------------------------------------------------------
+    // Hybrid search tests
+    // -------------------------------------------------------------------------
+
+    fn memory(content: &str) -> Memory {
+        crate::models::test_memory(content, MemoryType::Gotcha)
+    }
+
+    #[test]
+    fn test_fuse_ranks_memories_found_by_both_sources_first() {
+        let (pool, cache, redis) = (memory("pool"), memory("cache"), memory("redis"));
+        let fts = vec![(cache.clone(), 0.4), (pool.clone(), 0.2)];
+        let semantic = vec![(pool.clone(), 0.9), (redis.clone(), 0.5), (cache.clone(), 0.1)];
+
+        let fused = fuse(fts, semantic, 2);
+        let contents: Vec<&str> = fused.iter().map(|m| m.memory.content.as_str()).collect();
+        assert_eq!(contents, vec!["pool", "cache"]);
+
+        let top = &fused[0];
+        assert_eq!(top.fts, Some(SourceScore { rank: 2, score: 0.2, normalized: 0.0 }));
+        assert_eq!(top.semantic.map(|s| (s.rank, s.normalized)), Some((1, 1.0)));
+        assert!((top.score - (1.0 / 62.0 + 1.0 / 61.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_fuse_one_source() {
+        let fused = fuse(vec![(memory("only"), 0.3)], Vec::new(), 10);
+        assert_eq!(fused.len(), 1);
+        assert_eq!(fused[0].fts.unwrap().normalized, 1.0);
+        assert!(fused[0].semantic.is_none());
+        let json = serde_json::to_value(&fused[0]).unwrap();
+        assert_eq!(json["content"], "only");
+        assert!(json.get("semantic").is_none());
+    }
 }
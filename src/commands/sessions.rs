@@ -0,0 +1,135 @@
+//! List-sessions and search-meta commands: browse recorded sessions
+//!
+//! Sessions are created and ended by the hooks, one per Claude Code session.
+//! Listing them, newest first and with how much each one produced, is the
+//! way to find the session to pass to `show-session` or `get-session`.
+//! `search-meta` finds them by what they were about instead: their summary,
+//! git branch or the files changed when they started.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+
+use crate::db::queries;
+use crate::error::{HippocampusError, Result};
+use crate::models::{SessionMatch, SessionStatus, SessionSummary};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Filters and page for list-sessions
+#[derive(Debug, Clone, Default)]
+pub struct ListSessionsOptions {
+    pub status: Option<SessionStatus>,
+    pub project_path: Option<String>,
+    /// Only sessions started at or after this time
+    pub since: Option<DateTime<Utc>>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Result of list-sessions command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListSessionsResult {
+    pub sessions: Vec<SessionSummary>,
+    /// Sessions matching the filters, across all pages
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    /// Whether another page follows this one
+    pub has_more: bool,
+}
+
+/// Result of search-meta command
+#[derive(Debug, Serialize)]
+pub struct SearchMetaResult {
+    pub query: String,
+    pub count: usize,
+    /// Newest first
+    pub sessions: Vec<SessionMatch>,
+}
+
+// ============================================================================
+// Command
+// ============================================================================
+
+/// List sessions, newest first, one page at a time.
+pub async fn list_sessions(pool: &PgPool, options: &ListSessionsOptions) -> Result<ListSessionsResult> {
+    validate_page(options.limit, options.offset)?;
+
+    let (sessions, total) = queries::list_sessions(
+        pool,
+        options.status,
+        options.project_path.as_deref(),
+        options.since,
+        options.limit,
+        options.offset,
+    )
+    .await?;
+
+    Ok(ListSessionsResult {
+        has_more: options.offset + (sessions.len() as i64) < total,
+        sessions,
+        total,
+        limit: options.limit,
+        offset: options.offset,
+    })
+}
+
+/// Find up to `limit` sessions, newest first, whose summary, git branch or
+/// changed files contain `query`, in `project_path` or every project.
+pub async fn search_meta(
+    pool: &PgPool,
+    query: &str,
+    project_path: Option<&str>,
+    limit: i64,
+) -> Result<SearchMetaResult> {
+    validate_page(limit, 0)?;
+    let query = query.trim();
+    if query.is_empty() {
+        return Err(HippocampusError::Validation("Search query is empty".to_string()));
+    }
+
+    let sessions = queries::search_sessions(pool, query, project_path, limit).await?;
+    Ok(SearchMetaResult {
+        query: query.to_string(),
+        count: sessions.len(),
+        sessions,
+    })
+}
+
+/// Check that a page is non-empty and starts at or after the first row.
+fn validate_page(limit: i64, offset: i64) -> Result<()> {
+    if limit < 1 {
+        return Err(HippocampusError::Validation(format!(
+            "Invalid limit: {} (must be at least 1)",
+            limit
+        )));
+    }
+    if offset < 0 {
+        return Err(HippocampusError::Validation(format!(
+            "Invalid offset: {} (must not be negative)",
+            offset
+        )));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_page() {
+        assert!(validate_page(20, 0).is_ok());
+        assert!(validate_page(1, 40).is_ok());
+        assert!(validate_page(0, 0).is_err());
+        assert!(validate_page(20, -1).is_err());
+    }
+}
@@ -0,0 +1,330 @@
+//! Two-phase add: propose-memory and commit-memory
+//!
+//! `propose-memory` stages a candidate and reports how it compares with
+//! existing memories, without touching them. `commit-memory` adds it, with
+//! the usual duplicate check. A proposal nobody commits is not lost: once
+//! it expires it is added flagged for review, unless it duplicates a memory
+//! by then. Expired proposals are swept whenever either command or
+//! `review list` runs.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::commands::memory::{
+    constraints_metadata, duplicate_scope, insert_checked, metadata_constraints, scope_and_path,
+    AddMemoryOptions, Inserted,
+};
+use crate::config::DuplicateCheck;
+use crate::db::{self, StagedMemory};
+use crate::error::{HippocampusError, Result};
+use crate::lint::{self, LintRules};
+use crate::models::{
+    DuplicateResponse, LintIssue, LintRejectedResponse, Scope, SuccessResponse,
+    SupersedeSuggestion, Tier,
+};
+
+/// Lowest trigram similarity listed in a proposal's analysis
+const SIMILAR_MIN: f64 = 0.3;
+
+/// Most similar memories listed in a proposal's analysis
+const SIMILAR_LIMIT: i64 = 5;
+
+/// An existing memory resembling a proposal
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarItem {
+    pub id: Uuid,
+    pub summary: String,
+    pub similarity: f64,
+}
+
+/// The memory a proposal would be rejected as a duplicate of
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateMatch {
+    pub existing_id: Uuid,
+    pub existing_summary: String,
+    /// None for a matching summary
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<f64>,
+}
+
+/// How a proposal compares with existing memories
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarityAnalysis {
+    /// Set when commit-memory would currently reject the proposal
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate: Option<DuplicateMatch>,
+    /// Set when the proposal reads like an update of a near-duplicate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<SupersedeSuggestion>,
+    /// Most similar active memories of the same type (needs pg_trgm)
+    pub similar: Vec<SimilarItem>,
+    /// Lint issues in the content (lint mode `warn`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<LintIssue>,
+}
+
+/// Result of propose-memory command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposeMemoryData {
+    pub staging_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub analysis: SimilarityAnalysis,
+}
+
+/// Result of commit-memory command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitMemoryData {
+    pub id: Uuid,
+    pub staging_id: Uuid,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<LintIssue>,
+}
+
+/// What happened to expired proposals
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpiredProposals {
+    /// Memories added to the review queue
+    pub flagged: Vec<Uuid>,
+    /// Proposals dropped as duplicates or rejected by lint rules
+    pub dropped: usize,
+}
+
+/// Stage a memory for `ttl_hours` and analyse it against existing memories.
+///
+/// Content the lint rules reject is not staged, since it could never be
+/// committed.
+pub async fn propose_memory(
+    pool: &PgPool,
+    opts: AddMemoryOptions,
+    ttl_hours: u32,
+) -> Result<serde_json::Value> {
+    expire_proposals(pool, opts.duplicate_check, &opts.lint).await?;
+
+    let warnings = lint::check(opts.memory_type, &opts.content, &opts.lint);
+    if opts.lint.rejects(&warnings) {
+        return Ok(serde_json::to_value(LintRejectedResponse::new(warnings))?);
+    }
+
+    let duplicate = db::find_duplicate(
+        pool,
+        opts.memory_type,
+        &opts.content,
+        &opts.summary(),
+        opts.duplicate_check.similarity,
+        duplicate_scope(&opts),
+        scope_and_path(&opts).1,
+        opts.supersedes,
+    )
+    .await?;
+    let similar =
+        db::find_similar_memories(pool, opts.memory_type, &opts.content, SIMILAR_MIN, SIMILAR_LIMIT)
+            .await?;
+
+    let (scope, project_path) = scope_and_path(&opts);
+    let staged = db::stage_memory(
+        pool,
+        opts.memory_type,
+        scope,
+        project_path,
+        &opts.content,
+        &opts.tags,
+        opts.confidence,
+        opts.source_session_id,
+        opts.source_turn_id,
+        opts.supersedes,
+        constraints_metadata(&opts.constraints).as_ref(),
+        ttl_hours,
+    )
+    .await?;
+
+    let retry = format!("commit-memory {}", staged.id);
+    let analysis = SimilarityAnalysis {
+        suggestion: duplicate
+            .as_ref()
+            .and_then(|d| d.similarity.map(|s| SupersedeSuggestion::new(d.id, s, &retry))),
+        duplicate: duplicate.map(|d| DuplicateMatch {
+            existing_id: d.id,
+            existing_summary: d.summary,
+            similarity: d.similarity,
+        }),
+        similar: similar
+            .into_iter()
+            .filter(|m| Some(m.id) != opts.supersedes)
+            .map(|m| SimilarItem {
+                id: m.id,
+                summary: m.summary,
+                similarity: m.similarity,
+            })
+            .collect(),
+        warnings,
+    };
+
+    let response = SuccessResponse::new(ProposeMemoryData {
+        staging_id: staged.id,
+        expires_at: staged.expires_at,
+        analysis,
+    });
+    Ok(serde_json::to_value(response)?)
+}
+
+/// Add a staged memory.
+///
+/// `supersedes` overrides the one given at proposal time. A duplicate or
+/// lint rejection keeps the proposal staged, so it can be committed again,
+/// e.g. with `--supersedes`.
+pub async fn commit_memory(
+    pool: &PgPool,
+    staging_id: Uuid,
+    supersedes: Option<Uuid>,
+    duplicate_check: DuplicateCheck,
+    lint: &LintRules,
+) -> Result<serde_json::Value> {
+    expire_proposals(pool, duplicate_check, lint).await?;
+
+    let staged = db::get_staged_memory(pool, staging_id)
+        .await?
+        .ok_or_else(|| HippocampusError::NotFound(format!("staged {}", staging_id)))?;
+
+    let mut opts = staged_options(&staged, duplicate_check, lint);
+    if supersedes.is_some() {
+        opts.supersedes = supersedes;
+    }
+
+    match insert_checked(pool, &opts).await? {
+        Inserted::Added { id, warnings } => {
+            db::delete_staged_memory(pool, staging_id).await?;
+            let response = SuccessResponse::new(CommitMemoryData {
+                id,
+                staging_id,
+                warnings,
+            });
+            Ok(serde_json::to_value(response)?)
+        }
+        Inserted::Duplicate(dup) => {
+            let response = DuplicateResponse::new(dup.id, &dup.scope, &dup.summary, dup.similarity)
+                .with_retry(&format!("commit-memory {}", staging_id))
+                .with_diff(&dup.content, &opts.content);
+            Ok(serde_json::to_value(response)?)
+        }
+        Inserted::Rejected(issues) => {
+            Ok(serde_json::to_value(LintRejectedResponse::new(issues))?)
+        }
+    }
+}
+
+/// Add expired proposals flagged for review, dropping duplicates and
+/// proposals the lint rules reject
+pub async fn expire_proposals(
+    pool: &PgPool,
+    duplicate_check: DuplicateCheck,
+    lint: &LintRules,
+) -> Result<ExpiredProposals> {
+    let mut expired = ExpiredProposals::default();
+
+    for staged in db::list_expired_staged(pool).await? {
+        let opts = staged_options(&staged, duplicate_check, lint);
+        match insert_checked(pool, &opts).await? {
+            Inserted::Added { id, .. } => {
+                db::flag_memories_for_review(pool, &[id], &expiry_reason(&staged)).await?;
+                expired.flagged.push(id);
+            }
+            Inserted::Duplicate(_) | Inserted::Rejected(_) => expired.dropped += 1,
+        }
+        db::delete_staged_memory(pool, staged.id).await?;
+    }
+
+    Ok(expired)
+}
+
+/// Options that add a staged memory as proposed
+fn staged_options(
+    staged: &StagedMemory,
+    duplicate_check: DuplicateCheck,
+    lint: &LintRules,
+) -> AddMemoryOptions {
+    AddMemoryOptions {
+        memory_type: staged.memory_type,
+        content: staged.content.clone(),
+        summary: None,
+        tags: staged.tags.clone(),
+        confidence: staged.confidence,
+        tier: match staged.scope {
+            Scope::Global => Tier::Global,
+            Scope::Project => Tier::Project,
+        },
+        project_path: staged.project_path.clone(),
+        source_session_id: staged.source_session_id,
+        source_turn_id: staged.source_turn_id,
+        supersedes: staged.supersedes,
+        constraints: metadata_constraints(staged.metadata.as_ref()),
+        duplicate_check,
+        lint: lint.clone(),
+    }
+}
+
+/// Review reason for an expired proposal
+fn expiry_reason(staged: &StagedMemory) -> String {
+    match staged.supersedes {
+        Some(old) => format!("Proposed memory expired without commit (proposed to supersede {})", old),
+        None => "Proposed memory expired without commit".to_string(),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Confidence, MemoryType};
+
+    fn staged(scope: Scope, metadata: Option<serde_json::Value>) -> StagedMemory {
+        StagedMemory {
+            id: Uuid::new_v4(),
+            memory_type: MemoryType::Gotcha,
+            scope,
+            project_path: Some("/work/app".to_string()),
+            content: "Run migrations before tests".to_string(),
+            tags: vec!["db".to_string()],
+            confidence: Confidence::High,
+            source_session_id: None,
+            source_turn_id: None,
+            supersedes: None,
+            metadata,
+            created_at: Utc::now(),
+            expires_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_staged_options_round_trip() {
+        let metadata = serde_json::json!({"constraints": ["os=linux", "not a constraint"]});
+        let lint = LintRules::default();
+        let opts = staged_options(&staged(Scope::Global, Some(metadata)), DuplicateCheck::default(), &lint);
+        assert_eq!(opts.tier, Tier::Global);
+        assert_eq!(opts.constraints.len(), 1);
+        assert_eq!(opts.constraints[0].to_string(), "os=linux");
+        assert_eq!(opts.duplicate_check, DuplicateCheck::default());
+
+        let opts = staged_options(&staged(Scope::Project, None), DuplicateCheck::default(), &lint);
+        assert_eq!(opts.tier, Tier::Project);
+        assert!(opts.constraints.is_empty());
+    }
+
+    #[test]
+    fn test_expiry_reason_mentions_superseded_memory() {
+        let mut proposal = staged(Scope::Project, None);
+        assert_eq!(expiry_reason(&proposal), "Proposed memory expired without commit");
+        proposal.supersedes = Some(Uuid::nil());
+        assert!(expiry_reason(&proposal).ends_with(&format!("supersede {})", Uuid::nil())));
+    }
+}
@@ -1,37 +1,87 @@
-//! Stats command: get memory statistics
+//! Stats commands: memory statistics and access popularity
 //!
-//! Returns counts by type, confidence, and scope.
+//! `stats` returns counts by type, confidence, scope, project and tag,
+//! from one query whose filters are all bound parameters. Access numbers
+//! come from the daily rollup of access events (`rollup-access`), which
+//! both commands run first so they are current.
 
+use serde::Serialize;
 use sqlx::postgres::PgPool;
 
 use crate::db::queries;
 use crate::error::Result;
-use crate::models::{Scope, Tier};
+use crate::models::{MemorySummary, Scope, Tier};
 
-pub use crate::db::queries::{ConfidenceCounts, MemoryStats, ScopeCounts, TypeCounts};
+pub use crate::db::queries::{
+    AccessRollup, ConfidenceCounts, MemoryStats, ProjectCount, ScopeCounts, TagCount, TypeCounts,
+};
 
 /// Options for stats command
 #[derive(Debug, Clone)]
 pub struct StatsOptions {
     /// Tier filter (project, global, or both)
     pub tier: Tier,
-    /// Project path for project-scoped queries
-    pub project_path: Option<String>,
+    /// Projects whose memories project-scoped queries include
+    pub project_paths: Vec<String>,
+}
+
+/// A memory and how often it was accessed in the window
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopMemory {
+    #[serde(flatten)]
+    pub memory: MemorySummary,
+    pub accesses: i64,
+}
+
+/// Result of top-memories command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopMemoriesResult {
+    pub days: i32,
+    /// Most accessed first
+    pub entries: Vec<TopMemory>,
 }
 
 /// Get memory statistics.
 ///
-/// Returns counts grouped by type, confidence level, and scope.
+/// Returns counts grouped by type, confidence level, scope, project and tag.
 pub async fn get_stats(pool: &PgPool, options: StatsOptions) -> Result<MemoryStats> {
-    let (scope_filter, include_both) = tier_to_scope_filter(options.tier);
-
-    queries::get_stats(
-        pool,
-        scope_filter,
-        options.project_path.as_deref(),
-        include_both,
-    )
-    .await
+    let (scope_filter, _) = tier_to_scope_filter(options.tier);
+    queries::rollup_memory_access(pool).await?;
+
+    queries::get_stats(pool, scope_filter, &options.project_paths).await
+}
+
+/// The memories accessed most over the last `days` days.
+pub async fn top_memories(
+    pool: &PgPool,
+    tier: Tier,
+    project_paths: &[String],
+    days: i32,
+    limit: i64,
+) -> Result<TopMemoriesResult> {
+    queries::rollup_memory_access(pool).await?;
+    let (scope_filter, _) = tier_to_scope_filter(tier);
+    let top =
+        queries::top_accessed_memories(pool, scope_filter, project_paths, days.max(1), limit)
+            .await?;
+
+    Ok(TopMemoriesResult {
+        days: days.max(1),
+        entries: top
+            .into_iter()
+            .map(|(memory, accesses)| TopMemory {
+                memory: memory.to_summary(),
+                accesses,
+            })
+            .collect(),
+    })
+}
+
+/// Fold pending access events into the access counters and daily counts.
+pub async fn rollup_access(pool: &PgPool) -> Result<AccessRollup> {
+    queries::rollup_memory_access(pool).await
 }
 
 /// Convert Tier to (Option<Scope>, include_both) for query building
@@ -55,18 +105,18 @@ mod tests {
     fn test_stats_options_both() {
         let options = StatsOptions {
             tier: Tier::Both,
-            project_path: Some("/test/path".to_string()),
+            project_paths: vec!["/test/path".to_string()],
         };
 
         assert_eq!(options.tier, Tier::Both);
-        assert_eq!(options.project_path, Some("/test/path".to_string()));
+        assert_eq!(options.project_paths, vec!["/test/path"]);
     }
 
     #[test]
     fn test_stats_options_project() {
         let options = StatsOptions {
             tier: Tier::Project,
-            project_path: Some("/my/project".to_string()),
+            project_paths: vec!["/my/project".to_string()],
         };
 
         assert_eq!(options.tier, Tier::Project);
@@ -76,11 +126,11 @@ mod tests {
     fn test_stats_options_global() {
         let options = StatsOptions {
             tier: Tier::Global,
-            project_path: None,
+            project_paths: Vec::new(),
         };
 
         assert_eq!(options.tier, Tier::Global);
-        assert!(options.project_path.is_none());
+        assert!(options.project_paths.is_empty());
     }
 
     #[test]
@@ -165,11 +215,24 @@ mod tests {
                 project: 25,
                 global: 15,
             },
+            by_project: vec![ProjectCount {
+                project_path: "/my/project".to_string(),
+                count: 25,
+            }],
+            by_tag: vec![TagCount {
+                tag: "rust".to_string(),
+                count: 12,
+            }],
+            accesses_last_30_days: 0,
         };
 
         assert_eq!(stats.total, 40);
         assert_eq!(stats.by_type.gotcha, 10);
         assert_eq!(stats.by_confidence.high, 20);
         assert_eq!(stats.by_scope.project, 25);
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["by_project"][0]["project_path"], "/my/project");
+        assert_eq!(json["by_tag"][0]["tag"], "rust");
     }
 }
@@ -0,0 +1,126 @@
+//! Tag commands: searchByTag, listTags, renameTag, mergeTags
+//!
+//! Tags are matched ignoring case everywhere, so `Rust` and `rust` are the
+//! same tag. Renames and merges rewrite active memories only; superseded
+//! memories keep the tags they had.
+
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+
+use crate::commands::ranking::Ranker;
+use crate::commands::search::{tier_to_scope_filter, MemorySearchItem, SearchResult};
+use crate::db::queries::{self, TagCount};
+use crate::error::Result;
+use crate::models::Tier;
+
+/// Tags in use, most used first
+#[derive(Debug, Serialize)]
+pub struct ListTagsResult {
+    pub tags: Vec<TagCount>,
+    pub count: usize,
+}
+
+/// Result of renaming or merging tags
+#[derive(Debug, Serialize)]
+pub struct RetagResult {
+    pub from: Vec<String>,
+    pub to: String,
+    /// Memories whose tags changed
+    pub updated: u64,
+}
+
+/// Search memories carrying a tag, ordered by `ranker`
+#[allow(clippy::too_many_arguments)]
+pub async fn search_by_tag(
+    pool: &PgPool,
+    tag: &str,
+    tier: Tier,
+    limit: i32,
+    project_paths: &[String],
+    include_archived: bool,
+    ranker: &dyn Ranker,
+) -> Result<SearchResult> {
+    let (scope_filter, include_both) = tier_to_scope_filter(tier);
+    let memories = queries::search_by_tag(
+        pool,
+        tag,
+        scope_filter,
+        project_paths,
+        include_both,
+        limit,
+        include_archived,
+        ranker,
+    )
+    .await?;
+
+    // Mark returned memories as accessed
+    if !memories.is_empty() {
+        let ids: Vec<uuid::Uuid> = memories.iter().map(|m| m.id).collect();
+        queries::mark_memories_accessed(pool, &ids).await?;
+    }
+
+    let results: Vec<MemorySearchItem> = memories.into_iter().map(Into::into).collect();
+    let count = results.len();
+
+    Ok(SearchResult { results, count, next_cursor: None })
+}
+
+/// List the tags in use with how many memories carry each
+pub async fn list_tags(pool: &PgPool, tier: Tier, project_paths: &[String]) -> Result<ListTagsResult> {
+    let (scope_filter, include_both) = tier_to_scope_filter(tier);
+    let tags = queries::list_tags(pool, scope_filter, project_paths, include_both).await?;
+    let count = tags.len();
+
+    Ok(ListTagsResult { tags, count })
+}
+
+/// Replace one or more tags with `to`.
+///
+/// Renaming is merging a single tag. A memory that already carries `to`
+/// keeps one copy of it.
+pub async fn retag(
+    pool: &PgPool,
+    from: &[String],
+    to: &str,
+    tier: Tier,
+    project_path: Option<&str>,
+) -> Result<RetagResult> {
+    let from = dedup_ignoring_case(from);
+    let (scope_filter, include_both) = tier_to_scope_filter(tier);
+    let updated =
+        queries::replace_tags(pool, &from, to, scope_filter, project_path, include_both).await?;
+
+    Ok(RetagResult {
+        from,
+        to: to.to_string(),
+        updated,
+    })
+}
+
+pub(crate) fn dedup_ignoring_case(tags: &[String]) -> Vec<String> {
+    let mut seen = Vec::new();
+    let mut unique = Vec::new();
+    for tag in tags {
+        let lower = tag.to_lowercase();
+        if !seen.contains(&lower) {
+            seen.push(lower);
+            unique.push(tag.clone());
+        }
+    }
+    unique
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup_ignoring_case_keeps_first_spelling() {
+        let tags = vec!["Rust".to_string(), "rs".to_string(), "rust".to_string()];
+        assert_eq!(dedup_ignoring_case(&tags), vec!["Rust", "rs"]);
+    }
+}
@@ -0,0 +1,366 @@
+//! Show-session command: a past session as a chronological transcript
+//!
+//! Joins what the hooks recorded about a session — its turns' prompts and
+//! responses, the tool calls made in each turn, and the memories extracted
+//! along the way — into one timeline, as JSON or as readable text.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::db::queries::{self, ToolCall};
+use crate::error::{HippocampusError, Result};
+use crate::models::{Memory, Session, Turn};
+
+/// Longest tool parameter or result shown per line in the text transcript
+const TEXT_DETAIL_CHARS: usize = 160;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// One entry in a session's timeline
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum TranscriptEvent {
+    #[serde(rename_all = "camelCase")]
+    Prompt {
+        at: DateTime<Utc>,
+        turn_number: i32,
+        text: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    ToolCall {
+        at: DateTime<Utc>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        turn_number: Option<i32>,
+        tool: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        parameters: Option<serde_json::Value>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        result: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Memory {
+        at: DateTime<Utc>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        turn_number: Option<i32>,
+        id: Uuid,
+        memory_type: String,
+        summary: String,
+        is_active: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    Response {
+        at: DateTime<Utc>,
+        turn_number: i32,
+        text: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
+    },
+}
+
+impl TranscriptEvent {
+    fn at(&self) -> DateTime<Utc> {
+        match self {
+            Self::Prompt { at, .. }
+            | Self::ToolCall { at, .. }
+            | Self::Memory { at, .. }
+            | Self::Response { at, .. } => *at,
+        }
+    }
+
+    /// Order of events recorded at the same instant within a turn
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Prompt { .. } => 0,
+            Self::ToolCall { .. } => 1,
+            Self::Memory { .. } => 2,
+            Self::Response { .. } => 3,
+        }
+    }
+}
+
+/// Result of show-session command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionTranscript {
+    pub session: Session,
+    pub turns: usize,
+    pub tool_calls: usize,
+    pub memories_created: usize,
+    /// Everything that happened, oldest first
+    pub events: Vec<TranscriptEvent>,
+}
+
+// ============================================================================
+// Command
+// ============================================================================
+
+/// Assemble the transcript of a session.
+///
+/// `session` is a database session UUID or a Claude session ID.
+pub async fn show_session(pool: &PgPool, session: &str) -> Result<SessionTranscript> {
+    let found = match Uuid::parse_str(session) {
+        Ok(id) => queries::find_session_by_id(pool, id).await?,
+        Err(_) => queries::find_session_by_claude_id(pool, session).await?,
+    };
+    let session = found.ok_or_else(|| HippocampusError::SessionNotFound(session.to_string()))?;
+
+    let turns = queries::list_session_turns(pool, session.id).await?;
+    let tool_calls = queries::list_session_tool_calls(pool, session.id).await?;
+    let memories = queries::list_session_memories(pool, session.id).await?;
+
+    Ok(SessionTranscript {
+        turns: turns.len(),
+        tool_calls: tool_calls.len(),
+        memories_created: memories.len(),
+        events: build_events(&turns, &tool_calls, &memories),
+        session,
+    })
+}
+
+/// Merge turns, tool calls and memories into one timeline
+fn build_events(turns: &[Turn], tool_calls: &[ToolCall], memories: &[Memory]) -> Vec<TranscriptEvent> {
+    let turn_numbers: HashMap<Uuid, i32> = turns.iter().map(|t| (t.id, t.turn_number)).collect();
+    let turn_of = |id: Option<Uuid>| id.and_then(|id| turn_numbers.get(&id).copied());
+
+    let mut events = Vec::new();
+    for turn in turns {
+        events.push(TranscriptEvent::Prompt {
+            at: turn.started_at,
+            turn_number: turn.turn_number,
+            text: turn.user_prompt.clone(),
+        });
+        if let Some(response) = &turn.assistant_response {
+            events.push(TranscriptEvent::Response {
+                at: turn.ended_at.unwrap_or(turn.started_at),
+                turn_number: turn.turn_number,
+                text: response.clone(),
+                model: turn.model_used.clone(),
+            });
+        }
+    }
+    for call in tool_calls {
+        events.push(TranscriptEvent::ToolCall {
+            at: call.called_at,
+            turn_number: turn_of(call.turn_id),
+            tool: call.tool_name.clone(),
+            parameters: call.parameters.clone(),
+            result: call.result_summary.clone(),
+        });
+    }
+    for memory in memories {
+        events.push(TranscriptEvent::Memory {
+            at: memory.created_at,
+            turn_number: turn_of(memory.source_turn_id),
+            id: memory.id,
+            memory_type: memory.memory_type.as_str().to_string(),
+            summary: memory.summary.clone(),
+            is_active: memory.is_active,
+        });
+    }
+
+    events.sort_by_key(|e| (e.at(), e.rank()));
+    events
+}
+
+// ============================================================================
+// Text output
+// ============================================================================
+
+impl SessionTranscript {
+    /// The transcript as plain text, one block per turn
+    pub fn to_text(&self) -> String {
+        let session = &self.session;
+        let mut text = format!("# Session {}\n", session.claude_session_id);
+        if let Some(path) = &session.project_path {
+            text.push_str(&format!("Project: {}\n", path));
+        }
+        text.push_str(&format!(
+            "Started: {}  Ended: {}  Status: {}\n",
+            session.started_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            session
+                .ended_at
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            session.status.as_str()
+        ));
+        text.push_str(&format!(
+            "{} turns, {} tool calls, {} memories created\n",
+            self.turns, self.tool_calls, self.memories_created
+        ));
+        if session.skipped_prompts > 0 {
+            text.push_str(&format!("{} prompts not recorded as turns\n", session.skipped_prompts));
+        }
+
+        for event in &self.events {
+            let time = event.at().format("%H:%M:%S");
+            match event {
+                TranscriptEvent::Prompt { turn_number, text: prompt, .. } => {
+                    if !text.ends_with("\n\n") {
+                        text.push('\n');
+                    }
+                    text.push_str(&format!("## Turn {} ({})\n\n", turn_number, time));
+                    text.push_str(&quote("> ", prompt));
+                    text.push('\n');
+                }
+                TranscriptEvent::ToolCall { tool, parameters, result, .. } => {
+                    let mut line = format!("{}  [{}]", time, tool);
+                    if let Some(parameters) = parameters {
+                        line.push_str(&format!(" {}", clip(&parameters.to_string())));
+                    }
+                    if let Some(result) = result {
+                        line.push_str(&format!(" -> {}", clip(result)));
+                    }
+                    text.push_str(&line);
+                    text.push('\n');
+                }
+                TranscriptEvent::Memory { memory_type, summary, is_active, id, .. } => {
+                    text.push_str(&format!(
+                        "{}  + {}: {} ({}{})\n",
+                        time,
+                        memory_type,
+                        summary,
+                        id,
+                        if *is_active { "" } else { ", superseded" }
+                    ));
+                }
+                TranscriptEvent::Response { text: response, .. } => {
+                    if !text.ends_with("\n\n") {
+                        text.push('\n');
+                    }
+                    text.push_str(&quote("", response));
+                    text.push('\n');
+                }
+            }
+        }
+        text
+    }
+}
+
+/// Every line of `body` prefixed, ending in a newline
+fn quote(prefix: &str, body: &str) -> String {
+    let mut quoted: String = body
+        .trim_end()
+        .lines()
+        .map(|line| format!("{}{}", prefix, line).trim_end().to_string() + "\n")
+        .collect();
+    if quoted.is_empty() {
+        quoted.push('\n');
+    }
+    quoted
+}
+
+/// A single-line excerpt of at most [`TEXT_DETAIL_CHARS`] characters
+fn clip(detail: &str) -> String {
+    let line = detail.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() <= TEXT_DETAIL_CHARS {
+        return line;
+    }
+    let cut: String = line.chars().take(TEXT_DETAIL_CHARS - 3).collect();
+    format!("{}...", cut)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(secs: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 10, 0, secs).unwrap()
+    }
+
+    fn turn(number: i32, start: u32, end: u32) -> Turn {
+        Turn {
+            id: Uuid::new_v4(),
+            session_id: None,
+            turn_number: number,
+            user_prompt: format!("Prompt {}", number),
+            assistant_response: Some(format!("Response {}", number)),
+            model_used: None,
+            input_tokens: None,
+            output_tokens: None,
+            started_at: at(start),
+            ended_at: Some(at(end)),
+            created_at: at(start),
+        }
+    }
+
+    fn tool_call(turn_id: Uuid, secs: u32) -> ToolCall {
+        ToolCall {
+            id: Uuid::new_v4(),
+            session_id: None,
+            turn_id: Some(turn_id),
+            tool_name: "Bash".to_string(),
+            parameters: Some(serde_json::json!({"command": "cargo test"})),
+            result_summary: Some("exit 0".to_string()),
+            called_at: at(secs),
+        }
+    }
+
+    #[test]
+    fn test_build_events_is_chronological() {
+        let first = turn(1, 0, 10);
+        let second = turn(2, 20, 30);
+        let calls = vec![tool_call(second.id, 25), tool_call(first.id, 5)];
+        let events = build_events(&[first, second], &calls, &[]);
+
+        let kinds: Vec<(&str, Option<i32>)> = events
+            .iter()
+            .map(|e| match e {
+                TranscriptEvent::Prompt { turn_number, .. } => ("prompt", Some(*turn_number)),
+                TranscriptEvent::ToolCall { turn_number, .. } => ("tool", *turn_number),
+                TranscriptEvent::Memory { turn_number, .. } => ("memory", *turn_number),
+                TranscriptEvent::Response { turn_number, .. } => ("response", Some(*turn_number)),
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("prompt", Some(1)),
+                ("tool", Some(1)),
+                ("response", Some(1)),
+                ("prompt", Some(2)),
+                ("tool", Some(2)),
+                ("response", Some(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_events_unfinished_turn_has_no_response() {
+        let mut open = turn(1, 0, 0);
+        open.assistant_response = None;
+        open.ended_at = None;
+        let events = build_events(&[open], &[], &[]);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], TranscriptEvent::Prompt { .. }));
+    }
+
+    #[test]
+    fn test_event_serializes_with_kind() {
+        let event = TranscriptEvent::Prompt {
+            at: at(0),
+            turn_number: 1,
+            text: "Hi".to_string(),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["kind"], "prompt");
+        assert_eq!(json["turnNumber"], 1);
+    }
+
+    #[test]
+    fn test_clip_and_quote() {
+        assert_eq!(clip("a\n  b"), "a b");
+        assert_eq!(clip(&"x".repeat(200)).chars().count(), TEXT_DETAIL_CHARS);
+        assert_eq!(quote("> ", "one\n\ntwo\n"), "> one\n>\n> two\n");
+    }
+}
@@ -0,0 +1,99 @@
+//! Get-turn command: where a session's conversation is
+//!
+//! UserPromptSubmit opens a turn for every recorded prompt and Stop closes
+//! it with the response and token counts. `get-turn` reports the session's
+//! latest turn, or with `--all` every turn, without their text (see
+//! `show-session` and `transcript` for that).
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::error::{HippocampusError, Result};
+use crate::models::Turn;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A turn's number, timing and token counts
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnInfo {
+    pub id: Uuid,
+    pub turn_number: i32,
+    pub started_at: DateTime<Utc>,
+    /// None while the turn is in progress
+    pub ended_at: Option<DateTime<Utc>>,
+    pub input_tokens: Option<i32>,
+    pub output_tokens: Option<i32>,
+    pub model_used: Option<String>,
+}
+
+impl From<Turn> for TurnInfo {
+    fn from(turn: Turn) -> Self {
+        Self {
+            id: turn.id,
+            turn_number: turn.turn_number,
+            started_at: turn.started_at,
+            ended_at: turn.ended_at,
+            input_tokens: turn.input_tokens,
+            output_tokens: turn.output_tokens,
+            model_used: turn.model_used,
+        }
+    }
+}
+
+/// Result of get-turn command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTurnResult {
+    pub session_id: Uuid,
+    /// Number of the latest turn, 0 before the first
+    pub turn_number: i32,
+    /// The latest turn
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub turn: Option<TurnInfo>,
+    /// Every turn, in order (`--all`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub turns: Option<Vec<TurnInfo>>,
+}
+
+// ============================================================================
+// Command
+// ============================================================================
+
+/// Report the latest turn of a session, and with `all` every turn.
+///
+/// `session` is a database session UUID or a Claude session ID.
+pub async fn get_turn(pool: &PgPool, session: &str, all: bool) -> Result<GetTurnResult> {
+    let found = match Uuid::parse_str(session) {
+        Ok(id) => queries::find_session_by_id(pool, id).await?,
+        Err(_) => queries::find_session_by_claude_id(pool, session).await?,
+    };
+    let session = found.ok_or_else(|| HippocampusError::SessionNotFound(session.to_string()))?;
+
+    let (turn, turns) = if all {
+        let turns: Vec<TurnInfo> = queries::list_session_turns(pool, session.id)
+            .await?
+            .into_iter()
+            .map(TurnInfo::from)
+            .collect();
+        (turns.last().cloned(), Some(turns))
+    } else {
+        let turn = match queries::find_latest_turn_id(pool, session.id).await? {
+            Some(id) => queries::find_turn_by_id(pool, id).await?.map(TurnInfo::from),
+            None => None,
+        };
+        (turn, None)
+    };
+
+    Ok(GetTurnResult {
+        session_id: session.id,
+        turn_number: turn.as_ref().map_or(0, |t| t.turn_number),
+        turn,
+        turns,
+    })
+}
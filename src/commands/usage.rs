@@ -0,0 +1,146 @@
+//! Where-used command: the sessions and turns a memory was used in
+//!
+//! A memory is used when the SessionStart hook injects it into the context
+//! block, or when a memory command run during a turn returns it (cited). A
+//! memory that is never used is a candidate for pruning.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::db::{self, queries::MemoryCitation};
+use crate::error::{HippocampusError, Result};
+
+/// Uses of a memory within one session
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionUse {
+    pub session_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claude_session_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_path: Option<String>,
+    pub injected: usize,
+    pub cited: usize,
+    /// Turns the memory was cited in, ascending
+    pub turns: Vec<i32>,
+    pub last_used: DateTime<Utc>,
+}
+
+/// Usage report for a memory
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WhereUsedReport {
+    pub memory_id: Uuid,
+    pub injected: usize,
+    pub cited: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_used: Option<DateTime<Utc>>,
+    /// Most recently used first
+    pub sessions: Vec<SessionUse>,
+}
+
+/// Report the sessions and turns a memory was injected or cited in
+pub async fn where_used(pool: &PgPool, id: Uuid) -> Result<WhereUsedReport> {
+    if db::get_memory(pool, id).await?.is_none() {
+        return Err(HippocampusError::NotFound(id.to_string()));
+    }
+    let citations = db::list_memory_citations(pool, id).await?;
+
+    Ok(build_report(id, &citations))
+}
+
+/// Group citations, newest first, by session
+fn build_report(memory_id: Uuid, citations: &[MemoryCitation]) -> WhereUsedReport {
+    let mut sessions: Vec<SessionUse> = Vec::new();
+    for citation in citations {
+        let index = match sessions.iter().position(|s| s.session_id == citation.session_id) {
+            Some(index) => index,
+            None => {
+                sessions.push(SessionUse {
+                    session_id: citation.session_id,
+                    claude_session_id: citation.claude_session_id.clone(),
+                    project_path: citation.project_path.clone(),
+                    injected: 0,
+                    cited: 0,
+                    turns: Vec::new(),
+                    last_used: citation.created_at,
+                });
+                sessions.len() - 1
+            }
+        };
+        let session = &mut sessions[index];
+        if citation.kind == "injected" {
+            session.injected += 1;
+        } else {
+            session.cited += 1;
+            if let Some(turn) = citation.turn_number {
+                if !session.turns.contains(&turn) {
+                    session.turns.push(turn);
+                }
+            }
+        }
+    }
+    for session in &mut sessions {
+        session.turns.sort_unstable();
+    }
+
+    WhereUsedReport {
+        memory_id,
+        injected: sessions.iter().map(|s| s.injected).sum(),
+        cited: sessions.iter().map(|s| s.cited).sum(),
+        last_used: citations.first().map(|c| c.created_at),
+        sessions,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn citation(kind: &str, session: Uuid, turn: Option<i32>, at: DateTime<Utc>) -> MemoryCitation {
+        MemoryCitation {
+            kind: kind.to_string(),
+            session_id: Some(session),
+            claude_session_id: Some(format!("claude-{}", session.simple())),
+            project_path: Some("/work/app".to_string()),
+            turn_number: turn,
+            created_at: at,
+        }
+    }
+
+    #[test]
+    fn test_build_report_groups_by_session() {
+        let now = Utc::now();
+        let (recent, older) = (Uuid::new_v4(), Uuid::new_v4());
+        let citations = vec![
+            citation("cited", recent, Some(4), now),
+            citation("cited", recent, Some(2), now - Duration::minutes(5)),
+            citation("cited", recent, Some(4), now - Duration::minutes(6)),
+            citation("injected", recent, None, now - Duration::minutes(10)),
+            citation("injected", older, None, now - Duration::days(3)),
+        ];
+
+        let report = build_report(Uuid::new_v4(), &citations);
+        assert_eq!((report.injected, report.cited), (2, 3));
+        assert_eq!(report.last_used, Some(now));
+        assert_eq!(report.sessions.len(), 2);
+        assert_eq!(report.sessions[0].session_id, Some(recent));
+        assert_eq!(report.sessions[0].turns, vec![2, 4]);
+        assert_eq!(report.sessions[1].injected, 1);
+        assert!(report.sessions[1].turns.is_empty());
+    }
+
+    #[test]
+    fn test_build_report_without_citations() {
+        let report = build_report(Uuid::new_v4(), &[]);
+        assert!(report.sessions.is_empty());
+        assert!(report.last_used.is_none());
+    }
+}
@@ -0,0 +1,527 @@
+//! Verify command: cross-check memories against the current codebase
+//!
+//! Extracts file paths and identifiers referenced by api/convention memories
+//! and checks that they still exist in the project. Memories whose references
+//! have disappeared are reported as likely stale and flagged for review, so
+//! they show up in `review list`.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+
+use crate::db::queries;
+use crate::error::{HippocampusError, Result};
+use crate::models::{Memory, MemorySummary, MemoryType};
+
+/// Memory types whose content refers to concrete code
+const VERIFIED_TYPES: &[MemoryType] = &[MemoryType::Api, MemoryType::Convention];
+
+/// Directories skipped when walking a project that is not a git repository
+const SKIPPED_DIRS: &[&str] = &[".git", "target", "node_modules", "dist", "build", ".venv"];
+
+/// Files larger than this are not searched for identifiers
+const MAX_SCANNED_FILE_SIZE: u64 = 1_048_576;
+
+/// Identifiers shorter than this are too generic to verify
+const MIN_SYMBOL_LEN: usize = 4;
+
+/// File extensions recognised as paths outside of code spans
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "rs", "ts", "tsx", "js", "jsx", "mjs", "py", "go", "java", "kt", "rb", "swift", "c", "h",
+    "cpp", "hpp", "cs", "sql", "sh", "md", "toml", "json", "yaml", "yml", "html", "css",
+];
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A code reference found in memory content
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CodeReference {
+    /// A file or directory path (relative to the project root, or a bare file name)
+    Path(String),
+    /// A function, type, or other identifier
+    Symbol(String),
+}
+
+impl CodeReference {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Path(p) => p,
+            Self::Symbol(s) => s,
+        }
+    }
+}
+
+/// A memory whose references no longer resolve
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleMemory {
+    #[serde(flatten)]
+    pub memory: MemorySummary,
+    /// References that could not be found in the project
+    pub missing_references: Vec<String>,
+    /// Number of references that still resolve
+    pub found_references: usize,
+}
+
+/// Result of verify command
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyResult {
+    pub project_path: String,
+    /// Memories examined
+    pub checked: usize,
+    /// Memories that referenced at least one file or identifier
+    pub with_references: usize,
+    /// Likely-stale memories to review
+    pub flagged: Vec<StaleMemory>,
+    /// Flagged memories added to the review queue by this run; the others
+    /// were already in it
+    pub queued: usize,
+}
+
+// ============================================================================
+// Command
+// ============================================================================
+
+/// Verify api/convention memories of a project against its current files.
+pub async fn verify(pool: &PgPool, project_path: &str) -> Result<VerifyResult> {
+    let root = Path::new(project_path);
+    if !root.is_dir() {
+        return Err(HippocampusError::Validation(format!(
+            "Project path is not a directory: {}",
+            project_path
+        )));
+    }
+
+    let memories =
+        queries::list_project_memories_by_types(pool, project_path, VERIFIED_TYPES).await?;
+    let index = ProjectIndex::load(root);
+
+    let mut result = verify_memories(&memories, &index, project_path);
+    for stale in &result.flagged {
+        let reason = review_reason(&stale.missing_references);
+        result.queued += queries::flag_memories_for_review(pool, &[stale.memory.id], &reason)
+            .await?
+            .len();
+    }
+    Ok(result)
+}
+
+/// Review queue reason for a memory whose references are gone
+fn review_reason(missing: &[String]) -> String {
+    format!("missing references: {}", missing.join(", "))
+}
+
+/// Check each memory's references against a project index.
+fn verify_memories(memories: &[Memory], index: &ProjectIndex, project_path: &str) -> VerifyResult {
+    let mut symbol_cache: HashMap<String, bool> = HashMap::new();
+    let mut with_references = 0;
+    let mut flagged = Vec::new();
+
+    for memory in memories {
+        let references = extract_references(&memory.content);
+        if references.is_empty() {
+            continue;
+        }
+        with_references += 1;
+
+        let mut missing = Vec::new();
+        for reference in &references {
+            let exists = match reference {
+                CodeReference::Path(path) => index.has_path(path),
+                CodeReference::Symbol(symbol) => *symbol_cache
+                    .entry(symbol.clone())
+                    .or_insert_with(|| index.has_symbol(symbol)),
+            };
+            if !exists {
+                missing.push(reference.as_str().to_string());
+            }
+        }
+
+        if !missing.is_empty() {
+            flagged.push(StaleMemory {
+                memory: memory.to_summary(),
+                found_references: references.len() - missing.len(),
+                missing_references: missing,
+            });
+        }
+    }
+
+    VerifyResult {
+        project_path: project_path.to_string(),
+        checked: memories.len(),
+        with_references,
+        flagged,
+        queued: 0,
+    }
+}
+
+// ============================================================================
+// Reference Extraction
+// ============================================================================
+
+/// Extract file paths and identifiers referenced by memory content.
+///
+/// Inside backtick code spans any path or identifier counts. In prose only
+/// paths with a known source extension, `call()` syntax, and snake_case or
+/// `::`-qualified names are taken, to avoid treating ordinary words as code.
+pub fn extract_references(content: &str) -> Vec<CodeReference> {
+    let mut references = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (i, segment) in content.split('`').enumerate() {
+        let in_code = i % 2 == 1;
+        if in_code && segment.trim().contains(char::is_whitespace) {
+            // Multi-word code spans are usually shell commands, not references
+            continue;
+        }
+        for word in segment.split_whitespace() {
+            if let Some(reference) = classify_token(word, in_code) {
+                if seen.insert(reference.clone()) {
+                    references.push(reference);
+                }
+            }
+        }
+    }
+
+    references
+}
+
+fn classify_token(raw: &str, in_code: bool) -> Option<CodeReference> {
+    let token = raw
+        .trim_start_matches(|c: char| "\"'([{<".contains(c))
+        .trim_end_matches(|c: char| "\"'.,;:!?)]}>".contains(c));
+    if token.is_empty() || token.contains("://") {
+        return None;
+    }
+
+    if let Some(path) = as_path(token, in_code) {
+        return Some(CodeReference::Path(path));
+    }
+
+    // Calls keep their parens after trimming only when written `name()`
+    let (name, is_call) = match token.find('(') {
+        Some(idx) => (&token[..idx], true),
+        None => (token, false),
+    };
+    let last_segment = name
+        .rsplit([':', '.'])
+        .find(|s| !s.is_empty())
+        .unwrap_or(name);
+
+    if !is_identifier(last_segment) || last_segment.len() < MIN_SYMBOL_LEN {
+        return None;
+    }
+
+    let looks_like_code =
+        in_code || is_call || last_segment.contains('_') || name.contains("::");
+    looks_like_code.then(|| CodeReference::Symbol(last_segment.to_string()))
+}
+
+fn as_path(token: &str, in_code: bool) -> Option<String> {
+    let valid_chars = token
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "/._-".contains(c));
+    if !valid_chars {
+        return None;
+    }
+
+    let file_name = token.rsplit('/').next().unwrap_or(token);
+    let has_source_extension = file_name
+        .rsplit_once('.')
+        .is_some_and(|(stem, ext)| !stem.is_empty() && SOURCE_EXTENSIONS.contains(&ext));
+
+    let is_path = if token.contains('/') {
+        has_source_extension || (in_code && token.chars().any(|c| c.is_ascii_alphabetic()))
+    } else {
+        has_source_extension
+    };
+
+    is_path.then(|| token.trim_start_matches("./").to_string())
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// ============================================================================
+// Project Index
+// ============================================================================
+
+/// Files of a project, used to resolve references.
+struct ProjectIndex {
+    root: std::path::PathBuf,
+    /// Relative file paths using `/` separators
+    files: Vec<String>,
+}
+
+impl ProjectIndex {
+    /// List project files, preferring git's view (respects .gitignore).
+    fn load(root: &Path) -> Self {
+        let files = git_files(root).unwrap_or_else(|| {
+            let mut files = Vec::new();
+            walk_files(root, root, &mut files);
+            files
+        });
+        Self {
+            root: root.to_path_buf(),
+            files,
+        }
+    }
+
+    fn has_path(&self, path: &str) -> bool {
+        let trimmed = path.trim_end_matches('/');
+        if trimmed.is_empty() {
+            return true;
+        }
+        if self.root.join(trimmed).exists() {
+            return true;
+        }
+        let suffix = format!("/{}", trimmed);
+        let dir_prefix = format!("{}/", trimmed);
+        let nested_dir = format!("/{}/", trimmed);
+        self.files
+            .iter()
+            .any(|f| f.ends_with(&suffix) || f.starts_with(&dir_prefix) || f.contains(&nested_dir))
+    }
+
+    fn has_symbol(&self, symbol: &str) -> bool {
+        self.files.iter().any(|file| {
+            let path = self.root.join(file);
+            let small_enough = fs::metadata(&path)
+                .map(|m| m.len() <= MAX_SCANNED_FILE_SIZE)
+                .unwrap_or(false);
+            small_enough
+                && fs::read_to_string(&path)
+                    .map(|text| contains_word(&text, symbol))
+                    .unwrap_or(false)
+        })
+    }
+}
+
+fn git_files(root: &Path) -> Option<Vec<String>> {
+    let output = Command::new("git")
+        .args(["ls-files", "--cached", "--others", "--exclude-standard"])
+        .current_dir(root)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(String::from)
+            .collect(),
+    )
+}
+
+fn walk_files(root: &Path, dir: &Path, files: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if path.is_dir() {
+            if !SKIPPED_DIRS.contains(&name.as_str()) {
+                walk_files(root, &path, files);
+            }
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            files.push(relative.to_string_lossy().replace('\\', "/"));
+        }
+    }
+}
+
+/// Check whether `word` occurs in `text` delimited by non-identifier characters.
+fn contains_word(text: &str, word: &str) -> bool {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    text.match_indices(word).any(|(idx, _)| {
+        let before = text[..idx].chars().next_back();
+        let after = text[idx + word.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{summarize, test_memory, Confidence, Scope};
+
+    fn memory(content: &str) -> Memory {
+        test_memory(content, MemoryType::Api)
+    }
+
+    fn index_with(files: &[(&str, &str)]) -> (tempfile::TempDir, ProjectIndex) {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in files {
+            let full = dir.path().join(path);
+            fs::create_dir_all(full.parent().unwrap()).unwrap();
+            fs::write(full, content).unwrap();
+        }
+        let mut listed = Vec::new();
+        walk_files(dir.path(), dir.path(), &mut listed);
+        let index = ProjectIndex {
+            root: dir.path().to_path_buf(),
+            files: listed,
+        };
+        (dir, index)
+    }
+
+    // -------------------------------------------------------------------------
+    // Reference extraction tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_extract_paths() {
+        let refs = extract_references("Queries live in src/db/queries.rs, see `src/hooks/`.");
+        assert_eq!(
+            refs,
+            vec![
+                CodeReference::Path("src/db/queries.rs".to_string()),
+                CodeReference::Path("src/hooks/".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_symbols() {
+        let refs = extract_references(
+            "Call `create_pool` before get_context() and use `queries::find_duplicate`",
+        );
+        assert_eq!(
+            refs,
+            vec![
+                CodeReference::Symbol("create_pool".to_string()),
+                CodeReference::Symbol("get_context".to_string()),
+                CodeReference::Symbol("find_duplicate".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_ignores_prose_and_commands() {
+        let refs = extract_references(
+            "Always run `cargo test --workspace` and/or check https://example.com/a.rs first.",
+        );
+        assert!(refs.is_empty());
+    }
+
+    #[test]
+    fn test_extract_ignores_short_symbols() {
+        assert!(extract_references("use `id` and `foo()`").is_empty());
+    }
+
+    #[test]
+    fn test_extract_deduplicates() {
+        let refs = extract_references("`parse_tags` then parse_tags again");
+        assert_eq!(refs.len(), 1);
+    }
+
+    // -------------------------------------------------------------------------
+    // Matching tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_contains_word() {
+        assert!(contains_word("fn create_pool() {}", "create_pool"));
+        assert!(!contains_word("fn create_pool_with_password() {}", "create_pool"));
+        assert!(!contains_word("fn recreate_pool() {}", "create_pool"));
+        assert!(contains_word("create_pool", "create_pool"));
+    }
+
+    #[test]
+    fn test_index_has_path() {
+        let (_dir, index) = index_with(&[("src/db/queries.rs", "")]);
+        assert!(index.has_path("src/db/queries.rs"));
+        assert!(index.has_path("db/queries.rs"));
+        assert!(index.has_path("queries.rs"));
+        assert!(index.has_path("src/db/"));
+        assert!(!index.has_path("src/db/pool.rs"));
+    }
+
+    #[test]
+    fn test_verify_memories_flags_missing_references() {
+        let (_dir, index) = index_with(&[("src/lib.rs", "pub fn create_pool() {}\n")]);
+        let memories = vec![
+            memory("Use `create_pool` from src/lib.rs"),
+            memory("Call `open_connection` in src/db.rs"),
+            memory("Prefer small commits"),
+        ];
+
+        let result = verify_memories(&memories, &index, "/test");
+
+        assert_eq!(result.checked, 3);
+        assert_eq!(result.with_references, 2);
+        assert_eq!(result.flagged.len(), 1);
+        assert_eq!(result.flagged[0].memory.id, memories[1].id);
+        assert_eq!(
+            result.flagged[0].missing_references,
+            vec!["open_connection".to_string(), "src/db.rs".to_string()]
+        );
+        assert_eq!(result.flagged[0].found_references, 0);
+    }
+
+    #[tokio::test]
+    async fn test_verify_queues_stale_memories_for_review() {
+        use crate::db::migrations::with_scratch_database;
+        use crate::models::Tier;
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "pub fn create_pool() {}\n").unwrap();
+        let project = dir.path().to_string_lossy().to_string();
+
+        let Some(result) = with_scratch_database(|pool| async move {
+            for content in ["Use `create_pool` from src/lib.rs", "Call `open_connection` in src/db.rs"] {
+                queries::insert_memory(
+                    &pool,
+                    MemoryType::Api,
+                    Scope::Project,
+                    Some(&project),
+                    content,
+                    &summarize(content),
+                    &[],
+                    Confidence::High,
+                    None,
+                    None,
+                    None,
+                )
+                .await?;
+            }
+            let first = verify(&pool, &project).await?;
+            let again = verify(&pool, &project).await?;
+            let projects = vec![project.clone()];
+            let queue = queries::list_review_queue(&pool, Tier::Project, 10, &projects).await?;
+            Ok((first.queued, again.flagged.len(), again.queued, queue))
+        })
+        .await
+        else {
+            return;
+        };
+
+        let (queued, flagged_again, queued_again, queue) = result.unwrap();
+        assert_eq!(queued, 1);
+        assert_eq!((flagged_again, queued_again), (1, 0));
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].memory.summary, "Call `open_connection` in src/db.rs");
+        assert_eq!(
+            queue[0].reason.as_deref(),
+            Some("missing references: open_connection, src/db.rs")
+        );
+    }
+}
@@ -0,0 +1,92 @@
+//! Workspace commands: workspace create, workspace add-project, workspace list
+//!
+//! A workspace groups projects that belong together, such as a monorepo
+//! split across folders. Scoped reads run with `--workspace`, and session
+//! start with `context_include_workspace`, include the project memories of
+//! the other projects in the current project's workspaces. Writes still go
+//! to the current project.
+
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+
+use crate::db::{self, normalize_project_path, Workspace};
+use crate::error::{HippocampusError, Result};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Result of workspace create and workspace add-project
+#[derive(Debug, Serialize)]
+pub struct WorkspaceResult {
+    /// False when the workspace, or the project in it, already existed
+    pub changed: bool,
+    pub workspace: Workspace,
+}
+
+/// Result of workspace list
+#[derive(Debug, Serialize)]
+pub struct WorkspaceListResult {
+    pub count: usize,
+    pub workspaces: Vec<Workspace>,
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Create an empty workspace called `name`
+pub async fn workspace_create(pool: &PgPool, name: &str) -> Result<WorkspaceResult> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(HippocampusError::Validation("Workspace name is empty".to_string()));
+    }
+    let changed = db::create_workspace(pool, name).await?;
+    Ok(WorkspaceResult {
+        changed,
+        workspace: find(pool, name).await?,
+    })
+}
+
+/// Add `project`, or `current_project` when not given, to the workspace
+/// called `name`. A project path that is an alias is added as its project.
+pub async fn workspace_add_project(
+    pool: &PgPool,
+    name: &str,
+    project: Option<&str>,
+    current_project: Option<&str>,
+) -> Result<WorkspaceResult> {
+    let workspace = find(pool, name.trim()).await?;
+    let path = project
+        .map(normalize_project_path)
+        .or_else(|| current_project.map(str::to_string))
+        .ok_or_else(|| {
+            HippocampusError::Validation("No project to add: pass a path or set PROJECT_PATH".to_string())
+        })?;
+    let path = match db::find_project(pool, &path).await? {
+        Some(project) => project.canonical_path,
+        None => path,
+    };
+
+    let changed = db::add_workspace_project(pool, workspace.id, &path).await?;
+    Ok(WorkspaceResult {
+        changed,
+        workspace: find(pool, &workspace.name).await?,
+    })
+}
+
+/// Every workspace with its projects, by name
+pub async fn workspace_list(pool: &PgPool) -> Result<WorkspaceListResult> {
+    let workspaces = db::list_workspaces(pool).await?;
+    Ok(WorkspaceListResult {
+        count: workspaces.len(),
+        workspaces,
+    })
+}
+
+/// The workspace called `name`, or a not-found error
+async fn find(pool: &PgPool, name: &str) -> Result<Workspace> {
+    db::find_workspace(pool, name)
+        .await?
+        .ok_or_else(|| HippocampusError::NotFound(format!("Workspace not found: {}", name)))
+}
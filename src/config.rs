@@ -1,28 +1,374 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use crate::commands::ranking::{self, RankWeights, Ranker, RankingStrategy};
 use crate::error::{HippocampusError, Result};
+use crate::external::Alias;
+use crate::extraction::ProviderKind;
+use crate::hooks::rules::FailureRules;
+use crate::lint::LintRules;
+use crate::logging::LogLevel;
+use crate::models::Tier;
 
-/// Database configuration loaded from ~/.claude/config/db.json
-#[derive(Debug, Clone, Deserialize)]
+/// Configuration layered from, lowest precedence first,
+/// `~/.claude/config/db.json`, `~/.claude/hippocampus.toml` and the project's
+/// `.hippocampus.toml`. Each file only needs the keys it changes.
+///
+/// The project file is meant to be committed with the code, so it may only
+/// set memory behavior; see [`MACHINE_KEYS`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DbConfig {
     pub host: String,
     pub port: u16,
     pub database: String,
     pub user: String,
+    /// Full connection string; when set, host, port, database and user are ignored
+    #[serde(default)]
+    pub url: Option<String>,
     #[serde(default = "default_max_connections")]
     pub max_connections: u32,
     #[serde(default)]
     pub embedding_model: Option<String>,
     #[serde(default)]
     pub embedding_dimensions: Option<u32>,
+    /// OpenAI-compatible endpoint for embeddings; OpenAI's API when unset
+    #[serde(default)]
+    pub embedding_base_url: Option<String>,
+    /// Embed each finished turn for `recall` (opt-in; needs embedding_model)
+    #[serde(default)]
+    pub embed_turns: bool,
+    /// Archive raw hook inputs for `replay-last-hook` (opt-in)
+    #[serde(default)]
+    pub record_hooks: bool,
+    /// Number of hook recordings kept before the oldest are deleted
+    #[serde(default = "default_record_hooks_limit")]
+    pub record_hooks_limit: usize,
+    /// Surface hook failures instead of silently approving
+    #[serde(default)]
+    pub strict: StrictMode,
+    /// Run `mine-gotchas` in the background when a session ends
+    #[serde(default)]
+    pub auto_mine_gotchas: bool,
+    /// SessionStart injects only memories created or updated since the
+    /// project's previous session
+    #[serde(default)]
+    pub context_since_last_session: bool,
+    /// Token budget for the SessionStart context block (unlimited when unset)
+    #[serde(default)]
+    pub context_max_tokens: Option<usize>,
+    /// SessionStart also injects project memories of the other projects in
+    /// the project's workspaces
+    #[serde(default)]
+    pub context_include_workspace: bool,
+    /// Line format for SessionStart context entries, with `{pin}`,
+    /// `{confidence}`, `{type}`, `{summary}` and `{tags}` placeholders
+    #[serde(default)]
+    pub context_template: Option<String>,
+    /// Tera template `get-context` renders its block through, instead
+    /// of the built-in markdown
+    #[serde(default)]
+    pub context_template_file: Option<PathBuf>,
+    /// Share (0.0 - 1.0) of a memory's words one block of the project's
+    /// CLAUDE.md must contain for SessionStart to leave the memory out;
+    /// above 1.0 none are left out
+    #[serde(default = "default_claude_md_overlap")]
+    pub claude_md_overlap: f64,
+    /// Daily limit on extraction backend calls; over it, turns are queued for
+    /// `extract-queued` (unlimited when unset)
+    #[serde(default)]
+    pub extraction_daily_cap: Option<u32>,
+    /// Thresholds for `health-report` anomaly alerts
+    #[serde(default)]
+    pub anomalies: AnomalyThresholds,
+    /// Which tool calls PostToolUse records
+    #[serde(default)]
+    pub tool_recording: ToolRecording,
+    /// Which prompts UserPromptSubmit records as turns
+    #[serde(default)]
+    pub turn_recording: TurnRecording,
+    /// Which failing Bash commands PostToolUse turns into gotchas
+    #[serde(default)]
+    pub failure_rules: FailureRules,
+    /// Trigram similarity (0.0 - 1.0) at which two memories count as
+    /// duplicates; 1.0 or above only matches identical summaries
+    #[serde(default = "default_duplicate_similarity")]
+    pub duplicate_similarity: f64,
+    /// Which existing memories a new one is checked against for duplicates
+    #[serde(default)]
+    pub duplicate_scope: DuplicateScope,
+    /// `add-memory` always checks for conflicts, as with `--check-conflicts`
+    #[serde(default)]
+    pub check_conflicts: bool,
+    /// Trigram similarity at which an existing memory is checked for a
+    /// conflict with a new one
+    #[serde(default = "default_conflict_similarity")]
+    pub conflict_similarity: f64,
+    /// Tier searched and listed when a command is not given one
+    #[serde(default = "default_tier")]
+    pub default_tier: Tier,
+    /// Retention for `prune`, `purge-superseded`, `prune-data` and `decay`
+    #[serde(default)]
+    pub prune: PruneThresholds,
+    /// Model for extraction calls; the Claude CLI's default when unset,
+    /// required by the HTTP providers
+    #[serde(default)]
+    pub extraction_model: Option<String>,
+    /// Backend for extraction calls: claude, anthropic or openai
+    #[serde(default)]
+    pub extraction_provider: ProviderKind,
+    /// Endpoint for the HTTP providers; their public API when unset
+    #[serde(default)]
+    pub extraction_base_url: Option<String>,
+    /// Extra rules appended to the extraction prompt, one per line
+    #[serde(default)]
+    pub extraction_rules: Vec<String>,
+    /// How search results and the context block are ordered
+    #[serde(default)]
+    pub ranking: RankingConfig,
+    /// Content rules checked when memories are added or updated
+    #[serde(default)]
+    pub lint: LintRules,
+    /// Least severe events written to memory.log; debug adds every query
+    #[serde(default)]
+    pub log_level: LogLevel,
+    /// Subcommand names standing for other command lines (see `external`)
+    #[serde(default)]
+    pub aliases: BTreeMap<String, Alias>,
+}
+
+/// Ranking strategies for search and context (see `ranking`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RankingConfig {
+    /// Order of keyword, type and tag search results
+    pub search: RankingStrategy,
+    /// Order of the context block injected at session start
+    pub context: RankingStrategy,
+    /// Days for the recency score to halve (decay, hybrid, weighted)
+    pub half_life_days: f64,
+    /// Weights for the weighted strategy
+    pub weights: RankWeights,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        Self {
+            search: RankingStrategy::Confidence,
+            context: RankingStrategy::Recency,
+            half_life_days: 30.0,
+            weights: RankWeights::default(),
+        }
+    }
+}
+
+impl RankingConfig {
+    pub fn search_ranker(&self) -> Arc<dyn Ranker> {
+        ranking::ranker(self.search, self.half_life_days, &self.weights, &[])
+    }
+
+    /// Context ranker; `context_tags` (see `ranking::context_tags`) boost
+    /// matching memories under the weighted strategy
+    pub fn context_ranker(&self, context_tags: &[String]) -> Arc<dyn Ranker> {
+        ranking::ranker(self.context, self.half_life_days, &self.weights, context_tags)
+    }
+}
+
+/// Retention used when `prune`, `purge-superseded`, `prune-data` or `decay`
+/// are not given explicit day counts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PruneThresholds {
+    /// Days before unaccessed LOW confidence memories are pruned
+    pub low_days: i64,
+    /// Days before unaccessed MEDIUM confidence memories are pruned
+    pub medium_days: i64,
+    /// Days superseded memories are kept
+    pub superseded_days: i64,
+    pub tool_calls_days: i64,
+    pub turns_days: i64,
+    /// Days completed sessions are kept
+    pub sessions_days: i64,
+    /// Days without use before `decay` archives a HIGH confidence memory
+    /// (longer for memories used often)
+    pub archive_days: i64,
+}
+
+impl Default for PruneThresholds {
+    fn default() -> Self {
+        Self {
+            low_days: 30,
+            medium_days: 90,
+            superseded_days: 30,
+            tool_calls_days: 14,
+            turns_days: 30,
+            sessions_days: 90,
+            archive_days: 180,
+        }
+    }
+}
+
+/// When `health-report` flags a day's activity as anomalous.
+///
+/// The last complete UTC day is compared with the daily mean of the
+/// `baseline_days` before it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnomalyThresholds {
+    /// Days averaged for the baseline
+    pub baseline_days: u32,
+    /// Alert when accesses fall below this fraction of the baseline mean
+    pub access_drop_ratio: f64,
+    /// Alert when creations exceed this multiple of the baseline mean
+    pub creation_spike_ratio: f64,
+    /// Baseline daily mean below which a metric is too sparse to judge
+    pub min_baseline: f64,
+    /// Alert when more than this fraction of the last day's extraction
+    /// attempts failed
+    pub extraction_failure_ratio: f64,
+    /// Extraction attempts below which the failure rate is not judged
+    pub min_extractions: i64,
+}
+
+impl Default for AnomalyThresholds {
+    fn default() -> Self {
+        Self {
+            baseline_days: 14,
+            access_drop_ratio: 0.25,
+            creation_spike_ratio: 3.0,
+            min_baseline: 2.0,
+            extraction_failure_ratio: 0.25,
+            min_extractions: 4,
+        }
+    }
+}
+
+/// Which tool calls PostToolUse records.
+///
+/// A call is recorded when its tool is included (`"*"` includes every tool)
+/// and not excluded, and then only for `sample_rate` of such calls. File
+/// staleness checks run regardless.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolRecording {
+    /// Tools to record; defaults to the ones that change files or run commands
+    pub include: Vec<String>,
+    /// Tools never recorded, even when included
+    pub exclude: Vec<String>,
+    /// Fraction of matching calls recorded, from 0.0 to 1.0
+    pub sample_rate: f64,
+}
+
+impl Default for ToolRecording {
+    fn default() -> Self {
+        Self {
+            include: ["Bash", "Edit", "MultiEdit", "Write", "NotebookEdit"]
+                .iter()
+                .map(|t| t.to_string())
+                .collect(),
+            exclude: Vec::new(),
+            sample_rate: 1.0,
+        }
+    }
+}
+
+/// Which prompts become conversation turns.
+///
+/// A skipped prompt creates no turn; it is counted in the session's
+/// `skipped_prompts` instead. Both thresholds are off by default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TurnRecording {
+    /// Prompts shorter than this many characters (trimmed) are skipped
+    pub min_prompt_chars: usize,
+    /// Turns recorded per session; later prompts are skipped
+    pub max_per_session: Option<u32>,
+}
+
+impl TurnRecording {
+    /// Why a prompt is not recorded, given the turns its session already has
+    pub fn skip_reason(&self, prompt: &str, recorded_turns: i32) -> Option<&'static str> {
+        if prompt.trim().chars().count() < self.min_prompt_chars {
+            return Some("prompt shorter than min_prompt_chars");
+        }
+        match self.max_per_session {
+            Some(max) if i64::from(recorded_turns) >= i64::from(max) => {
+                Some("session reached max_per_session turns")
+            }
+            _ => None,
+        }
+    }
+}
+
+/// How hook failures are surfaced (see `hooks::strict`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StrictMode {
+    /// Log failures to the debug log and approve (default)
+    #[default]
+    Off,
+    /// Approve, but show failures to the user as a systemMessage
+    Warn,
+    /// Block where the hook supports it, otherwise warn
+    Block,
+}
+
+/// Which memories a new memory is checked against for duplicates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateScope {
+    /// Memories visible alongside it: global memories, plus its project's
+    /// for a project memory (default)
+    #[default]
+    Project,
+    /// Every memory, whatever its scope or project
+    Global,
+}
+
+/// How a new memory is checked for duplicates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DuplicateCheck {
+    /// Trigram similarity at which an existing memory counts as a duplicate
+    pub similarity: f64,
+    pub scope: DuplicateScope,
+}
+
+impl Default for DuplicateCheck {
+    fn default() -> Self {
+        Self {
+            similarity: default_duplicate_similarity(),
+            scope: DuplicateScope::default(),
+        }
+    }
 }
 
 fn default_max_connections() -> u32 {
     10
 }
 
+fn default_record_hooks_limit() -> usize {
+    100
+}
+
+fn default_duplicate_similarity() -> f64 {
+    0.8
+}
+
+fn default_conflict_similarity() -> f64 {
+    0.5
+}
+
+fn default_claude_md_overlap() -> f64 {
+    0.8
+}
+
+fn default_tier() -> Tier {
+    Tier::Both
+}
+
 impl Default for DbConfig {
     fn default() -> Self {
         Self {
@@ -30,46 +376,188 @@ impl Default for DbConfig {
             port: 5432,
             database: "claude_memory".to_string(),
             user: std::env::var("USER").unwrap_or_else(|_| "postgres".to_string()),
+            url: None,
             max_connections: 10,
             embedding_model: None,
             embedding_dimensions: None,
+            embedding_base_url: None,
+            embed_turns: false,
+            record_hooks: false,
+            record_hooks_limit: default_record_hooks_limit(),
+            strict: StrictMode::Off,
+            auto_mine_gotchas: false,
+            context_since_last_session: false,
+            context_max_tokens: None,
+            context_include_workspace: false,
+            context_template: None,
+            context_template_file: None,
+            claude_md_overlap: default_claude_md_overlap(),
+            extraction_daily_cap: None,
+            anomalies: AnomalyThresholds::default(),
+            tool_recording: ToolRecording::default(),
+            turn_recording: TurnRecording::default(),
+            failure_rules: FailureRules::default(),
+            duplicate_similarity: default_duplicate_similarity(),
+            duplicate_scope: DuplicateScope::default(),
+            check_conflicts: false,
+            conflict_similarity: default_conflict_similarity(),
+            default_tier: default_tier(),
+            prune: PruneThresholds::default(),
+            extraction_model: None,
+            extraction_provider: ProviderKind::default(),
+            extraction_base_url: None,
+            extraction_rules: Vec::new(),
+            ranking: RankingConfig::default(),
+            lint: LintRules::default(),
+            log_level: LogLevel::default(),
+            aliases: BTreeMap::new(),
         }
     }
 }
 
 impl DbConfig {
-    /// Load config from the standard location (~/.claude/config/db.json)
+    /// Duplicate check for new memories, from `duplicate_similarity` and
+    /// `duplicate_scope`
+    pub fn duplicate_check(&self) -> DuplicateCheck {
+        DuplicateCheck {
+            similarity: self.duplicate_similarity,
+            scope: self.duplicate_scope,
+        }
+    }
+
+    /// Rules for the extraction prompt: `extraction_rules`, plus the
+    /// `lint.memory_types` allow-list when it is set
+    pub fn extraction_prompt_rules(&self) -> Vec<String> {
+        let mut rules = self.extraction_rules.clone();
+        if !self.lint.memory_types.is_empty() {
+            let types: Vec<&str> = self.lint.memory_types.iter().map(|t| t.as_str()).collect();
+            rules.push(format!("Use only these types: {}", types.join(", ")));
+        }
+        rules
+    }
+
+    /// Similarity for `add-memory`'s conflict check, None when it is off
+    pub fn conflict_check(&self, requested: bool) -> Option<f64> {
+        (requested || self.check_conflicts).then_some(self.conflict_similarity)
+    }
+
+    /// Load config from the standard locations, with the current project's
+    /// `.hippocampus.toml` applied last
     pub fn load() -> Result<Self> {
-        let config_path = Self::config_path();
-        Self::load_from_path(&config_path)
+        let project_path = crate::db::get_project_path();
+        Self::load_from_files(&Self::config_files(project_path.as_deref()))
     }
 
-    /// Load config from a specific path
+    /// Load config from a specific path (TOML when it ends in `.toml`, else JSON)
     pub fn load_from_path(path: &PathBuf) -> Result<Self> {
-        if path.exists() {
+        Self::load_from_files(std::slice::from_ref(path))
+    }
+
+    /// Load config layered from `paths`, later files overriding earlier ones.
+    ///
+    /// Missing files are skipped; with none present the defaults apply.
+    pub fn load_from_files(paths: &[PathBuf]) -> Result<Self> {
+        let mut merged = serde_json::Value::Object(Default::default());
+        for path in paths {
+            if let Some(layer) = read_layer(path)? {
+                if is_project_file(path) {
+                    if let Some(table) = layer.as_object() {
+                        for key in table.keys() {
+                            check_project_key(path, key)?;
+                        }
+                    }
+                }
+                merge_layer(&mut merged, layer);
+            }
+        }
+        serde_json::from_value(merged)
+            .map_err(|e| HippocampusError::Config(format!("Invalid config: {}", e)))
+    }
+
+    /// Files read by [`load`](Self::load), lowest precedence first
+    pub fn config_files(project_path: Option<&str>) -> Vec<PathBuf> {
+        let mut files = vec![Self::config_path(), Self::global_toml_path()];
+        if let Some(project) = project_path {
+            files.push(Self::project_toml_path(project));
+        }
+        files
+    }
+
+    /// Get the standard config file path
+    pub fn config_path() -> PathBuf {
+        claude_dir().join("config").join("db.json")
+    }
+
+    /// Global TOML config (`~/.claude/hippocampus.toml`)
+    pub fn global_toml_path() -> PathBuf {
+        claude_dir().join("hippocampus.toml")
+    }
+
+    /// Per-project overrides (`<project>/.hippocampus.toml`)
+    pub fn project_toml_path(project_path: &str) -> PathBuf {
+        Path::new(project_path).join(PROJECT_FILE_NAME)
+    }
+
+    /// Set a dotted `key` (e.g. `prune.low_days`) in the TOML file at `path`.
+    ///
+    /// `value` is read as TOML (`20`, `true`, `["Bash"]`), falling back to a
+    /// plain string. The file is created if needed and rewritten without
+    /// comments. Returns the value as stored.
+    pub fn set_in_file(path: &Path, key: &str, value: &str) -> Result<toml::Value> {
+        let defaults = serde_json::to_value(Self::default())?;
+        let known = defaults
+            .pointer(&format!("/{}", key.replace('.', "/")))
+            .is_some_and(|v| !v.is_object())
+            || key.strip_prefix("aliases.").is_some_and(|name| !name.is_empty() && !name.contains('.'));
+        if !known {
+            return Err(HippocampusError::Config(format!("Unknown config key: {}", key)));
+        }
+        if is_project_file(path) {
+            check_project_key(path, key)?;
+        }
+
+        let mut table = if path.exists() {
             let content = fs::read_to_string(path).map_err(|e| {
                 HippocampusError::Config(format!("Failed to read config file: {}", e))
             })?;
-            let config: DbConfig = serde_json::from_str(&content).map_err(|e| {
-                HippocampusError::Config(format!("Failed to parse config JSON: {}", e))
-            })?;
-            Ok(config)
+            parse_toml(path, &content)?
         } else {
-            Ok(Self::default())
+            toml::Table::new()
+        };
+
+        let value = parse_toml_value(value);
+        let (parents, leaf) = match key.rsplit_once('.') {
+            Some((parents, leaf)) => (parents.split('.').collect(), leaf),
+            None => (Vec::new(), key),
+        };
+        let mut target = &mut table;
+        for part in parents {
+            target = target
+                .entry(part)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                .as_table_mut()
+                .ok_or_else(|| {
+                    HippocampusError::Config(format!("{} in {} is not a table", part, path.display()))
+                })?;
         }
-    }
+        target.insert(leaf.to_string(), value.clone());
 
-    /// Get the standard config file path
-    pub fn config_path() -> PathBuf {
-        dirs::home_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join(".claude")
-            .join("config")
-            .join("db.json")
+        // Reject values of the wrong type before they reach the file
+        serde_json::from_value::<DbConfig>(serde_json::to_value(&table)?)
+            .map_err(|e| HippocampusError::Config(format!("Invalid value for {}: {}", key, e)))?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, table.to_string())?;
+        Ok(value)
     }
 
     /// Build PostgreSQL connection string
     pub fn connection_string(&self) -> String {
+        if let Some(url) = &self.url {
+            return url.clone();
+        }
         format!(
             "postgres://{}@{}:{}/{}",
             self.user, self.host, self.port, self.database
@@ -79,12 +567,109 @@ impl DbConfig {
     /// Build connection string with password (if provided)
     pub fn connection_string_with_password(&self, password: Option<&str>) -> String {
         match password {
-            Some(pwd) => format!(
+            Some(pwd) if self.url.is_none() => format!(
                 "postgres://{}:{}@{}:{}/{}",
                 self.user, pwd, self.host, self.port, self.database
             ),
-            None => self.connection_string(),
+            _ => self.connection_string(),
+        }
+    }
+}
+
+/// Keys that describe one machine's setup rather than memory behavior. The
+/// project's `.hippocampus.toml` is shared through the repository, so it may
+/// not set them: a checked-in file should not pick the database, where turns
+/// are sent for extraction or embedding, or which commands aliases run.
+pub const MACHINE_KEYS: &[&str] = &[
+    "host",
+    "port",
+    "database",
+    "user",
+    "url",
+    "max_connections",
+    "embedding_model",
+    "embedding_dimensions",
+    "embedding_base_url",
+    "extraction_provider",
+    "extraction_base_url",
+    "record_hooks",
+    "record_hooks_limit",
+    "log_level",
+    "aliases",
+];
+
+const PROJECT_FILE_NAME: &str = ".hippocampus.toml";
+
+/// True for a project's `.hippocampus.toml`
+fn is_project_file(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == PROJECT_FILE_NAME)
+}
+
+/// Refuse a machine key set by the project file at `path`
+fn check_project_key(path: &Path, key: &str) -> Result<()> {
+    let top = key.split('.').next().unwrap_or(key);
+    if MACHINE_KEYS.contains(&top) {
+        return Err(HippocampusError::Config(format!(
+            "{} cannot be set in {}; set it in ~/.claude/hippocampus.toml",
+            top,
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+fn claude_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".claude")
+}
+
+/// Read one config file as JSON; None when it does not exist
+fn read_layer(path: &Path) -> Result<Option<serde_json::Value>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path)
+        .map_err(|e| HippocampusError::Config(format!("Failed to read config file: {}", e)))?;
+    let value = if path.extension().is_some_and(|ext| ext == "toml") {
+        serde_json::to_value(parse_toml(path, &content)?)?
+    } else {
+        serde_json::from_str(&content).map_err(|e| {
+            HippocampusError::Config(format!("Failed to parse config JSON: {}", e))
+        })?
+    };
+    Ok(Some(value))
+}
+
+fn parse_toml(path: &Path, content: &str) -> Result<toml::Table> {
+    content.parse().map_err(|e| {
+        HippocampusError::Config(format!("Failed to parse config TOML {}: {}", path.display(), e))
+    })
+}
+
+/// Read a command-line value as TOML, or as a string when it is not valid TOML
+fn parse_toml_value(value: &str) -> toml::Value {
+    format!("value = {}", value)
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(value.to_string()))
+}
+
+/// Overlay `layer` onto `base`, merging tables key by key
+fn merge_layer(base: &mut serde_json::Value, layer: serde_json::Value) {
+    match (base, layer) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(layer)) => {
+            for (key, value) in layer {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_layer(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
         }
+        (base, layer) => *base = layer,
     }
 }
 
@@ -95,6 +680,7 @@ impl DbConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::lint::LintMode;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -156,9 +742,41 @@ mod tests {
             port: 5432,
             database: "claude_memory".to_string(),
             user: "testuser".to_string(),
+            url: None,
             max_connections: 10,
             embedding_model: None,
             embedding_dimensions: None,
+            embedding_base_url: None,
+            embed_turns: false,
+            record_hooks: false,
+            record_hooks_limit: 100,
+            strict: StrictMode::Off,
+            auto_mine_gotchas: false,
+            context_since_last_session: false,
+            context_max_tokens: None,
+            context_include_workspace: false,
+            context_template: None,
+            context_template_file: None,
+            claude_md_overlap: default_claude_md_overlap(),
+            extraction_daily_cap: None,
+            anomalies: AnomalyThresholds::default(),
+            tool_recording: ToolRecording::default(),
+            turn_recording: TurnRecording::default(),
+            failure_rules: FailureRules::default(),
+            duplicate_similarity: 0.8,
+            duplicate_scope: DuplicateScope::Project,
+            check_conflicts: false,
+            conflict_similarity: 0.5,
+            default_tier: Tier::Both,
+            prune: PruneThresholds::default(),
+            extraction_model: None,
+            extraction_provider: ProviderKind::Claude,
+            extraction_base_url: None,
+            extraction_rules: Vec::new(),
+            ranking: RankingConfig::default(),
+            lint: LintRules::default(),
+            log_level: LogLevel::default(),
+            aliases: BTreeMap::new(),
         };
 
         assert_eq!(
@@ -174,9 +792,41 @@ mod tests {
             port: 5432,
             database: "claude_memory".to_string(),
             user: "testuser".to_string(),
+            url: None,
             max_connections: 10,
             embedding_model: None,
             embedding_dimensions: None,
+            embedding_base_url: None,
+            embed_turns: false,
+            record_hooks: false,
+            record_hooks_limit: 100,
+            strict: StrictMode::Off,
+            auto_mine_gotchas: false,
+            context_since_last_session: false,
+            context_max_tokens: None,
+            context_include_workspace: false,
+            context_template: None,
+            context_template_file: None,
+            claude_md_overlap: default_claude_md_overlap(),
+            extraction_daily_cap: None,
+            anomalies: AnomalyThresholds::default(),
+            tool_recording: ToolRecording::default(),
+            turn_recording: TurnRecording::default(),
+            failure_rules: FailureRules::default(),
+            duplicate_similarity: 0.8,
+            duplicate_scope: DuplicateScope::Project,
+            check_conflicts: false,
+            conflict_similarity: 0.5,
+            default_tier: Tier::Both,
+            prune: PruneThresholds::default(),
+            extraction_model: None,
+            extraction_provider: ProviderKind::Claude,
+            extraction_base_url: None,
+            extraction_rules: Vec::new(),
+            ranking: RankingConfig::default(),
+            lint: LintRules::default(),
+            log_level: LogLevel::default(),
+            aliases: BTreeMap::new(),
         };
 
         assert_eq!(
@@ -219,6 +869,108 @@ mod tests {
         let config = DbConfig::load_from_path(&temp_file.path().to_path_buf()).unwrap();
         assert!(config.embedding_model.is_none());
         assert!(config.embedding_dimensions.is_none());
+        assert!(!config.record_hooks);
+        assert_eq!(config.record_hooks_limit, 100);
+        assert_eq!(config.strict, StrictMode::Off);
+        assert!(!config.auto_mine_gotchas);
+        assert_eq!(config.extraction_daily_cap, None);
+        assert_eq!(config.anomalies, AnomalyThresholds::default());
+        assert_eq!(config.tool_recording, ToolRecording::default());
+        assert_eq!(config.duplicate_similarity, 0.8);
+        assert_eq!(config.default_tier, Tier::Both);
+        assert_eq!(config.prune, PruneThresholds::default());
+        assert!(config.url.is_none());
+        assert!(config.extraction_model.is_none());
+        assert_eq!(config.extraction_provider, ProviderKind::Claude);
+        assert_eq!(config.ranking, RankingConfig::default());
+    }
+
+    #[test]
+    fn test_anomaly_thresholds_partial_override() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(
+            temp_file,
+            r#"{{
+                "host": "localhost",
+                "port": 5432,
+                "database": "test",
+                "user": "user",
+                "anomalies": {{ "baseline_days": 7, "creation_spike_ratio": 5.0 }}
+            }}"#
+        )
+        .unwrap();
+
+        let config = DbConfig::load_from_path(&temp_file.path().to_path_buf()).unwrap();
+        assert_eq!(config.anomalies.baseline_days, 7);
+        assert_eq!(config.anomalies.creation_spike_ratio, 5.0);
+        assert_eq!(config.anomalies.access_drop_ratio, 0.25);
+    }
+
+    #[test]
+    fn test_tool_recording_loaded() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(
+            temp_file,
+            r#"{{
+                "host": "localhost",
+                "port": 5432,
+                "database": "test",
+                "user": "user",
+                "tool_recording": {{ "include": ["*"], "exclude": ["Read"], "sample_rate": 0.1 }}
+            }}"#
+        )
+        .unwrap();
+
+        let config = DbConfig::load_from_path(&temp_file.path().to_path_buf()).unwrap();
+        assert_eq!(config.tool_recording.include, vec!["*"]);
+        assert_eq!(config.tool_recording.exclude, vec!["Read"]);
+        assert_eq!(config.tool_recording.sample_rate, 0.1);
+    }
+
+    #[test]
+    fn test_turn_recording_skip_reason() {
+        assert_eq!(TurnRecording::default().skip_reason("ok", 500), None);
+
+        let recording = TurnRecording {
+            min_prompt_chars: 5,
+            max_per_session: Some(3),
+        };
+        assert!(recording.skip_reason("  ok  ", 0).is_some());
+        assert_eq!(recording.skip_reason("fix the build", 2), None);
+        assert!(recording.skip_reason("fix the build", 3).is_some());
+    }
+
+    #[test]
+    fn test_strict_mode_loaded() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(
+            temp_file,
+            r#"{{
+                "host": "localhost",
+                "port": 5432,
+                "database": "test",
+                "user": "user",
+                "strict": "warn"
+            }}"#
+        )
+        .unwrap();
+
+        let config = DbConfig::load_from_path(&temp_file.path().to_path_buf()).unwrap();
+        assert_eq!(config.strict, StrictMode::Warn);
+    }
+
+    #[test]
+    fn test_duplicate_check_from_config() {
+        assert_eq!(DbConfig::default().duplicate_check(), DuplicateCheck::default());
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, r#"{{"duplicate_similarity": 0.9, "duplicate_scope": "global"}}"#)
+            .unwrap();
+        let config = DbConfig::load_from_path(&temp_file.path().to_path_buf()).unwrap();
+        assert_eq!(
+            config.duplicate_check(),
+            DuplicateCheck { similarity: 0.9, scope: DuplicateScope::Global }
+        );
     }
 
     #[test]
@@ -241,4 +993,126 @@ mod tests {
         assert_eq!(config.embedding_model, Some("mxbai-embed-large".to_string()));
         assert_eq!(config.embedding_dimensions, Some(1024));
     }
+
+    #[test]
+    fn test_layers_override_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let json = dir.path().join("db.json");
+        let global = dir.path().join("hippocampus.toml");
+        let project = dir.path().join(".hippocampus.toml");
+        fs::write(&json, r#"{"host": "db.example.com", "user": "alice", "max_connections": 20}"#)
+            .unwrap();
+        fs::write(&global, "max_connections = 5\n\n[prune]\nlow_days = 7\nmedium_days = 60\n")
+            .unwrap();
+        fs::write(&project, "default_tier = \"project\"\n\n[prune]\nlow_days = 3\n").unwrap();
+
+        let config = DbConfig::load_from_files(&[json, global, project]).unwrap();
+        assert_eq!(config.host, "db.example.com");
+        assert_eq!(config.user, "alice");
+        assert_eq!(config.max_connections, 5);
+        assert_eq!(config.default_tier, Tier::Project);
+        // Tables merge key by key across layers
+        assert_eq!(config.prune.low_days, 3);
+        assert_eq!(config.prune.medium_days, 60);
+        assert_eq!(config.prune.sessions_days, 90);
+    }
+
+    #[test]
+    fn test_ranking_loaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hippocampus.toml");
+        fs::write(&path, "[ranking]\nsearch = \"weighted\"\n\n[ranking.weights]\naccess = 0.5\n")
+            .unwrap();
+
+        let config = DbConfig::load_from_path(&path).unwrap();
+        assert_eq!(config.ranking.search, RankingStrategy::Weighted);
+        assert_eq!(config.ranking.context, RankingStrategy::Recency);
+        assert_eq!(config.ranking.weights.access, 0.5);
+        assert_eq!(config.ranking.weights.confidence, 1.0);
+        assert_eq!(config.ranking.search_ranker().name(), "weighted");
+    }
+
+    #[test]
+    fn test_lint_rules_loaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hippocampus.toml");
+        fs::write(&path, "[lint]\nmode = \"reject\"\nmax_length = 400\n").unwrap();
+
+        let config = DbConfig::load_from_path(&path).unwrap();
+        assert_eq!(config.lint.mode, LintMode::Reject);
+        assert_eq!(config.lint.max_length, 400);
+        assert!(config.lint.imperative_conventions);
+        assert_eq!(config.lint.forbidden_prefixes, LintRules::default().forbidden_prefixes);
+    }
+
+    #[test]
+    fn test_url_overrides_connection_fields() {
+        let config = DbConfig {
+            url: Some("postgres://app:pw@db:6543/memories".to_string()),
+            ..DbConfig::default()
+        };
+        assert_eq!(config.connection_string(), "postgres://app:pw@db:6543/memories");
+        assert_eq!(
+            config.connection_string_with_password(Some("other")),
+            "postgres://app:pw@db:6543/memories"
+        );
+    }
+
+    #[test]
+    fn test_set_in_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("hippocampus.toml");
+
+        let value = DbConfig::set_in_file(&path, "prune.low_days", "14").unwrap();
+        assert_eq!(value, toml::Value::Integer(14));
+        DbConfig::set_in_file(&path, "extraction_model", "haiku").unwrap();
+        DbConfig::set_in_file(&path, "tool_recording.include", r#"["Bash", "Edit"]"#).unwrap();
+
+        let config = DbConfig::load_from_path(&path).unwrap();
+        assert_eq!(config.prune.low_days, 14);
+        assert_eq!(config.prune.medium_days, 90);
+        assert_eq!(config.extraction_model.as_deref(), Some("haiku"));
+        assert_eq!(config.tool_recording.include, vec!["Bash", "Edit"]);
+    }
+
+    #[test]
+    fn test_set_in_file_rejects_bad_keys_and_values() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hippocampus.toml");
+
+        let err = DbConfig::set_in_file(&path, "max_conections", "5").unwrap_err();
+        assert!(err.to_string().contains("Unknown config key: max_conections"));
+        // Whole tables are not values
+        assert!(DbConfig::set_in_file(&path, "prune", "7").is_err());
+
+        let err = DbConfig::set_in_file(&path, "default_tier", "everywhere").unwrap_err();
+        assert!(err.to_string().contains("Invalid value for default_tier"));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_project_file_limited_to_memory_behavior() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join(".hippocampus.toml");
+        fs::write(
+            &project,
+            "extraction_rules = [\"Skip build output\"]\n\n[lint]\nmemory_types = [\"gotcha\"]\n",
+        )
+        .unwrap();
+
+        let config = DbConfig::load_from_files(std::slice::from_ref(&project)).unwrap();
+        assert_eq!(
+            config.extraction_prompt_rules(),
+            vec!["Skip build output", "Use only these types: gotcha"]
+        );
+
+        fs::write(&project, "[aliases.deploy]\ncommand = \"rm\"\n").unwrap();
+        let err = DbConfig::load_from_files(std::slice::from_ref(&project)).unwrap_err();
+        assert!(err.to_string().contains("aliases cannot be set in"));
+
+        let err = DbConfig::set_in_file(&project, "url", "postgres://elsewhere/db").unwrap_err();
+        assert!(err.to_string().contains("~/.claude/hippocampus.toml"));
+        // The same keys are fine in the user's own file
+        DbConfig::set_in_file(&dir.path().join("hippocampus.toml"), "url", "postgres://x/db").unwrap();
+    }
 }
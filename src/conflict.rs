@@ -0,0 +1,102 @@
+//! Contradiction heuristics for new memories
+//!
+//! `add-memory --check-conflicts` compares a new memory with active memories
+//! of the same type that are similar without being duplicates. Two such
+//! memories likely contradict when one is negated and the other is not
+//! ("Run cargo fmt before committing" / "Never run cargo fmt ..."), and for
+//! conventions and preferences, which prescribe, when they differ in any
+//! content word ("Use tabs for indentation" / "Use spaces ..."). The caller
+//! decides whether to supersede, link or keep both.
+
+use crate::models::MemoryType;
+
+/// Words that turn advice or a statement into its opposite
+const NEGATIONS: &[&str] = &[
+    "not", "never", "no", "don't", "dont", "doesn't", "isn't", "aren't", "won't", "can't",
+    "cannot", "shouldn't", "mustn't", "avoid", "without",
+];
+
+/// Words too common to tell two memories' subjects apart
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "with", "this", "that", "are", "was", "use", "when", "from", "into",
+    "always", "should", "must", "its", "our", "all",
+];
+
+/// Why `content` likely contradicts the similar `existing` content of the
+/// same type, or None when it does not
+pub fn conflict_reason(memory_type: MemoryType, content: &str, existing: &str) -> Option<&'static str> {
+    if is_negated(content) != is_negated(existing) {
+        return Some("One negates the other");
+    }
+    let prescriptive = matches!(memory_type, MemoryType::Convention | MemoryType::Preference);
+    if prescriptive && content_words(content) != content_words(existing) {
+        return Some("Different advice on the same subject");
+    }
+    None
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !(c.is_alphanumeric() || "'\u{2019}_".contains(c)))
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase().replace('\u{2019}', "'"))
+}
+
+fn is_negated(text: &str) -> bool {
+    words(text).any(|w| NEGATIONS.contains(&w.as_str()) || w.ends_with("n't"))
+}
+
+/// Distinct words longer than two letters, other than stopwords and
+/// negations, sorted
+pub(crate) fn content_words(text: &str) -> Vec<String> {
+    let mut words: Vec<String> = words(text)
+        .filter(|w| w.len() > 2 && !STOPWORDS.contains(&w.as_str()) && !NEGATIONS.contains(&w.as_str()))
+        .collect();
+    words.sort();
+    words.dedup();
+    words
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negation_conflicts() {
+        assert_eq!(
+            conflict_reason(
+                MemoryType::Gotcha,
+                "Never run migrations during deploys",
+                "Run migrations during deploys",
+            ),
+            Some("One negates the other")
+        );
+        assert_eq!(
+            conflict_reason(
+                MemoryType::Gotcha,
+                "Don't cache the token",
+                "Do not cache the session token",
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_prescriptive_types_conflict_on_different_advice() {
+        let (tabs, spaces) = ("Use tabs for indentation", "Use spaces for indentation");
+        assert_eq!(
+            conflict_reason(MemoryType::Convention, tabs, spaces),
+            Some("Different advice on the same subject")
+        );
+        // Statements of fact may differ without contradicting
+        assert_eq!(conflict_reason(MemoryType::Learning, tabs, spaces), None);
+        // Wording alone is not different advice
+        assert_eq!(
+            conflict_reason(MemoryType::Convention, "Use tabs for indentation", "Always use tabs for indentation."),
+            None
+        );
+    }
+}
@@ -0,0 +1,561 @@
+//! Daemon mode: commands run on connections that stay open
+//!
+//! Every invocation normally connects to PostgreSQL itself, which hooks pay
+//! for on every prompt. `claude-hippocampus daemon` keeps its pools open
+//! (see `db::keep_pools_warm`) and listens on `~/.claude/hippocampus.sock`.
+//! While it runs, other invocations send their arguments, working
+//! directory, the environment variables commands read and their stdin over
+//! the socket, and print what the daemon answers, exit status included.
+//! Without a daemon, commands run in their own process as before.
+//!
+//! The daemon runs each command as it arrives, alongside those still
+//! running, so a hook never waits behind a maintenance command. Commands
+//! see the working directory and environment of the invocation that sent
+//! them through [`var`], [`current_dir`] and [`absolute`]; the daemon's own
+//! are left alone. Processes they spawn through [`command`] run there too,
+//! and never forward to the daemon, which could be busy waiting for them.
+//! A client that cannot hand its command over runs it itself; once the
+//! command is sent, it waits for the reply however long the command takes,
+//! since running it again would do the work twice. A daemon
+//! from another version declines commands and exits, so an upgrade takes
+//! effect at once.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::future::Future;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+use tokio::net::UnixListener;
+use tokio::sync::Notify;
+use tokio::task::JoinSet;
+
+use crate::error::{HippocampusError, Result};
+use crate::models::ErrorResponse;
+
+/// Socket file name under `~/.claude`
+const SOCKET_NAME: &str = "hippocampus.sock";
+
+/// Environment variables commands read, sent with each command
+pub const FORWARDED_ENV: &[&str] = &[
+    "PROJECT_PATH",
+    "CLAUDE_SESSION_ID",
+    "CLAUDE_MEMORY_EXTRACTION",
+    "USER",
+    "LOGNAME",
+];
+
+/// Set on processes that must run commands themselves rather than forward
+/// them, such as those the daemon's commands spawn
+pub const NO_DAEMON_ENV: &str = "HIPPOCAMPUS_NO_DAEMON";
+
+/// How long the daemon waits for a connected client's request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a client tries to send its command before running it itself
+pub const SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+tokio::task_local! {
+    /// The invocation whose command the daemon is running
+    static INVOCATION: Invocation;
+}
+
+/// Working directory and environment of the invocation a command runs for
+#[derive(Debug, Clone)]
+struct Invocation {
+    cwd: PathBuf,
+    env: BTreeMap<String, String>,
+}
+
+/// A command for the daemon to run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaemonRequest {
+    /// Version of the sending binary; another version's daemon declines
+    pub version: String,
+    /// Command line, program name first
+    pub args: Vec<String>,
+    pub cwd: PathBuf,
+    /// Values of the `FORWARDED_ENV` variables that are set
+    pub env: BTreeMap<String, String>,
+    /// Standard input, for commands that read it
+    #[serde(default)]
+    pub stdin: String,
+}
+
+impl DaemonRequest {
+    /// A request to run `args` as this process would: in its working
+    /// directory and with its environment
+    pub fn new(args: Vec<String>, stdin: String) -> Result<Self> {
+        Ok(Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            args,
+            cwd: env::current_dir()?,
+            env: FORWARDED_ENV
+                .iter()
+                .filter_map(|key| env::var(key).ok().map(|value| (key.to_string(), value)))
+                .collect(),
+            stdin,
+        })
+    }
+
+    /// Run `future` in the sender's working directory and environment, as
+    /// seen through [`var`], [`current_dir`], [`absolute`] and [`command`]
+    pub async fn enter<F: Future>(&self, future: F) -> F::Output {
+        let invocation = Invocation {
+            cwd: self.cwd.clone(),
+            env: self.env.clone(),
+        };
+        INVOCATION.scope(invocation, future).await
+    }
+}
+
+/// An environment variable of the invocation being run: the sender's value
+/// of a `FORWARDED_ENV` variable in the daemon, otherwise this process's
+pub fn var(key: &str) -> Option<String> {
+    INVOCATION
+        .try_with(|invocation| {
+            FORWARDED_ENV
+                .contains(&key)
+                .then(|| invocation.env.get(key).cloned())
+        })
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| env::var(key).ok())
+}
+
+/// Working directory of the invocation being run
+pub fn current_dir() -> io::Result<PathBuf> {
+    match INVOCATION.try_with(|invocation| invocation.cwd.clone()) {
+        Ok(cwd) => Ok(cwd),
+        Err(_) => env::current_dir(),
+    }
+}
+
+/// `path` as the invocation being run names it: in the daemon, a relative
+/// path is taken from the sender's working directory
+pub fn absolute(path: &Path) -> PathBuf {
+    match INVOCATION.try_with(|invocation| invocation.cwd.join(path)) {
+        Ok(path) => path,
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// A process for a command to spawn. It runs in the invocation's working
+/// directory and environment, and runs its own commands rather than
+/// forwarding them to a daemon that may be waiting for it.
+pub fn command(program: impl AsRef<OsStr>) -> std::process::Command {
+    let mut command = std::process::Command::new(program);
+    command.env(NO_DAEMON_ENV, "1");
+    let _ = INVOCATION.try_with(|invocation| {
+        command.current_dir(&invocation.cwd);
+        for key in FORWARDED_ENV {
+            match invocation.env.get(*key) {
+                Some(value) => command.env(key, value),
+                None => command.env_remove(key),
+            };
+        }
+    });
+    command
+}
+
+/// The daemon's answer to a request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum DaemonReply {
+    /// The command ran; `output` is what it prints
+    #[serde(rename_all = "camelCase")]
+    Ran { exit_code: i32, output: Value },
+    /// The daemon is another version and is exiting; run the command here
+    Declined { version: String },
+}
+
+impl DaemonReply {
+    fn from_result(result: Result<Value>) -> Self {
+        match result {
+            Ok(output) => DaemonReply::Ran { exit_code: 0, output },
+            Err(e) => DaemonReply::Ran {
+                exit_code: 1,
+                output: serde_json::to_value(
+                    ErrorResponse::new(e.to_string()).with_argument(e.argument()),
+                )
+                .unwrap_or_default(),
+            },
+        }
+    }
+}
+
+/// Result of the daemon command, once it exits
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaemonResult {
+    pub socket: String,
+    /// Commands run
+    pub served: usize,
+}
+
+/// Path of the daemon's socket
+pub fn socket_path() -> Result<PathBuf> {
+    let home = dirs::home_dir().ok_or_else(|| {
+        HippocampusError::Config("Could not determine home directory".to_string())
+    })?;
+    Ok(home.join(".claude").join(SOCKET_NAME))
+}
+
+/// A connection to a running daemon
+pub struct DaemonClient(UnixStream);
+
+impl DaemonClient {
+    /// The daemon listening on `path`; None when none is, or when this
+    /// process must run commands itself (`NO_DAEMON_ENV` is set)
+    pub fn connect(path: &Path) -> Option<Self> {
+        if env::var_os(NO_DAEMON_ENV).is_some() {
+            return None;
+        }
+        UnixStream::connect(path).ok().map(Self)
+    }
+
+    /// Send `request` and wait for the reply, however long the command runs.
+    /// None when the request could not be sent within `timeout`: the daemon
+    /// only runs complete requests, so the caller can run it instead.
+    pub fn send(self, request: &DaemonRequest, timeout: Duration) -> Result<Option<DaemonReply>> {
+        let mut stream = self.0;
+        stream.set_write_timeout(Some(timeout))?;
+        let mut line = serde_json::to_string(request)?;
+        line.push('\n');
+        if let Err(e) = stream.write_all(line.as_bytes()) {
+            tracing::warn!("Could not send the command to the daemon: {}", e);
+            return Ok(None);
+        }
+
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply)?;
+        if reply.is_empty() {
+            return Err(HippocampusError::Config(
+                "The daemon closed the connection without replying".to_string(),
+            ));
+        }
+        Ok(Some(serde_json::from_str(&reply)?))
+    }
+}
+
+/// Listen on `path` and run each request with `handle` as it arrives, until
+/// `idle_timeout` passes without one, a newer client arrives, or the process
+/// is interrupted. Commands still running are finished first. Returns the
+/// number of commands run.
+///
+/// Fails when another daemon already listens on `path`; a socket left
+/// behind by one that died is replaced. The socket is only accessible to
+/// its owner and is removed on exit.
+pub async fn serve<F, Fut>(path: &Path, idle_timeout: Option<Duration>, handle: F) -> Result<usize>
+where
+    F: Fn(DaemonRequest) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = Result<Value>> + Send + 'static,
+{
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(HippocampusError::Config(format!(
+                "A daemon is already listening on {}",
+                path.display()
+            )));
+        }
+        fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    tracing::info!(socket = %path.display(), "Daemon listening");
+
+    let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+    let served = Arc::new(AtomicUsize::new(0));
+    let stop = Arc::new(Notify::new());
+    let mut running = JoinSet::new();
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    tracing::warn!("Daemon could not accept a connection: {}", e);
+                    continue;
+                }
+            },
+            Some(_) = running.join_next(), if !running.is_empty() => continue,
+            _ = idle(idle_timeout), if running.is_empty() => break,
+            _ = stop.notified() => break,
+            _ = tokio::signal::ctrl_c() => break,
+            _ = terminate.recv() => break,
+        };
+        running.spawn(serve_connection(stream, handle.clone(), served.clone(), stop.clone()));
+    }
+
+    let _ = fs::remove_file(path);
+    while running.join_next().await.is_some() {}
+    Ok(served.load(Ordering::Relaxed))
+}
+
+/// Read one request from `stream`, run it with `handle` and send the reply.
+/// A request from another version is declined, and `stop`s the daemon.
+async fn serve_connection<F, Fut>(
+    stream: tokio::net::UnixStream,
+    handle: F,
+    served: Arc<AtomicUsize>,
+    stop: Arc<Notify>,
+) where
+    F: Fn(DaemonRequest) -> Fut,
+    Fut: Future<Output = Result<Value>>,
+{
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    let mut reader = tokio::io::BufReader::new(reader);
+    match tokio::time::timeout(REQUEST_TIMEOUT, reader.read_line(&mut line)).await {
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => {
+            tracing::warn!("Daemon could not read a request: {}", e);
+            return;
+        }
+        Err(_) => {
+            tracing::warn!("Daemon timed out waiting for a request");
+            return;
+        }
+    }
+
+    let reply = match serde_json::from_str::<DaemonRequest>(&line) {
+        Ok(request) if request.version != env!("CARGO_PKG_VERSION") => {
+            tracing::info!(client = %request.version, "Daemon exiting for a client of another version");
+            stop.notify_one();
+            let version = env!("CARGO_PKG_VERSION").to_string();
+            DaemonReply::Declined { version }
+        }
+        Ok(request) => {
+            let operation = request.args.get(1).cloned().unwrap_or_default();
+            let reply = DaemonReply::from_result(handle(request).await);
+            served.fetch_add(1, Ordering::Relaxed);
+            tracing::info!(operation, "Daemon ran a command");
+            reply
+        }
+        Err(e) => {
+            let error = HippocampusError::Config(format!("Invalid daemon request: {}", e));
+            DaemonReply::from_result(Err(error))
+        }
+    };
+
+    let reply = match serde_json::to_string(&reply) {
+        Ok(reply) => reply + "\n",
+        Err(e) => {
+            tracing::warn!("Daemon could not encode a reply: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = writer.write_all(reply.as_bytes()).await {
+        tracing::warn!("Daemon could not send a reply: {}", e);
+    }
+}
+
+/// Wait out `timeout`, or forever without one
+async fn idle(timeout: Option<Duration>) {
+    match timeout {
+        Some(timeout) => tokio::time::sleep(timeout).await,
+        None => std::future::pending().await,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn request(args: &[&str]) -> DaemonRequest {
+        DaemonRequest {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            cwd: PathBuf::from("/tmp"),
+            env: BTreeMap::new(),
+            stdin: "{}".to_string(),
+        }
+    }
+
+    /// Send each request to a daemon on a fresh socket, and return the
+    /// replies and the number of commands the daemon ran
+    async fn exchange(requests: Vec<DaemonRequest>) -> (Vec<DaemonReply>, usize) {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(SOCKET_NAME);
+        let daemon = serve(&path, Some(Duration::from_millis(300)), |request| async move {
+            match request.args.get(1).map(String::as_str) {
+                Some("fail") => Err(HippocampusError::NotFound("abc".to_string())),
+                _ => Ok(serde_json::json!({"args": request.args, "stdin": request.stdin})),
+            }
+        });
+        let client_path = path.clone();
+        let client = tokio::task::spawn_blocking(move || {
+            while !client_path.exists() {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            requests
+                .iter()
+                .map(|r| {
+                    let client = DaemonClient::connect(&client_path).unwrap();
+                    client.send(r, SEND_TIMEOUT).unwrap().unwrap()
+                })
+                .collect()
+        });
+        let (served, replies) = tokio::join!(daemon, client);
+        assert!(!path.exists());
+        (replies.unwrap(), served.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_daemon_runs_requests() {
+        let (replies, served) =
+            exchange(vec![request(&["claude-hippocampus", "stats"]), request(&["claude-hippocampus", "fail"])])
+                .await;
+        assert_eq!(served, 2);
+        assert_eq!(
+            replies[0],
+            DaemonReply::Ran {
+                exit_code: 0,
+                output: serde_json::json!({"args": ["claude-hippocampus", "stats"], "stdin": "{}"}),
+            }
+        );
+        match &replies[1] {
+            DaemonReply::Ran { exit_code, output } => {
+                assert_eq!(*exit_code, 1);
+                assert_eq!(output["error"], "Memory not found: abc");
+            }
+            other => panic!("Expected Ran, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_daemon_declines_other_version() {
+        let mut old = request(&["claude-hippocampus", "stats"]);
+        old.version = "0.0.0".to_string();
+        let (replies, served) = exchange(vec![old]).await;
+        assert_eq!(served, 0);
+        assert!(matches!(replies[0], DaemonReply::Declined { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_daemon_runs_a_request_while_another_waits_for_it() {
+        // As a Stop hook waits for the update-turn it spawned
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(SOCKET_NAME);
+        let released = Arc::new(Notify::new());
+        let handler_released = released.clone();
+        let daemon = serve(&path, Some(Duration::from_millis(300)), move |request| {
+            let released = handler_released.clone();
+            async move {
+                match request.args.get(1).map(String::as_str) {
+                    Some("hook") => released.notified().await,
+                    _ => released.notify_one(),
+                }
+                Ok(Value::Null)
+            }
+        });
+        let client_path = path.clone();
+        let clients = tokio::task::spawn_blocking(move || {
+            while !client_path.exists() {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            let hook = DaemonClient::connect(&client_path).unwrap();
+            let hook = std::thread::spawn(move || {
+                hook.send(&request(&["claude-hippocampus", "hook"]), SEND_TIMEOUT).unwrap()
+            });
+            std::thread::sleep(Duration::from_millis(50));
+            let child = DaemonClient::connect(&client_path).unwrap();
+            let child = child.send(&request(&["claude-hippocampus", "update-turn"]), SEND_TIMEOUT);
+            (hook.join().unwrap(), child.unwrap())
+        });
+        let (served, replies) =
+            tokio::time::timeout(Duration::from_secs(10), async { tokio::join!(daemon, clients) })
+                .await
+                .expect("daemon deadlocked");
+        assert_eq!(served.unwrap(), 2);
+        let (hook, child) = replies.unwrap();
+        assert!(matches!(hook, Some(DaemonReply::Ran { exit_code: 0, .. })));
+        assert!(matches!(child, Some(DaemonReply::Ran { exit_code: 0, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_client_waits_for_a_slow_reply() {
+        // Past the send timeout: running the command again would do it twice
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(SOCKET_NAME);
+        let daemon = serve(&path, Some(Duration::from_millis(300)), |_request| async {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            Ok(Value::Null)
+        });
+        let client_path = path.clone();
+        let client = tokio::task::spawn_blocking(move || {
+            while !client_path.exists() {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            let client = DaemonClient::connect(&client_path).unwrap();
+            client.send(&request(&["claude-hippocampus", "prune"]), Duration::from_millis(50))
+        });
+        let (served, reply) = tokio::join!(daemon, client);
+        assert_eq!(served.unwrap(), 1);
+        assert!(matches!(reply.unwrap().unwrap(), Some(DaemonReply::Ran { exit_code: 0, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_client_fails_when_the_daemon_hangs_up() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(SOCKET_NAME);
+        let listener = UnixListener::bind(&path).unwrap();
+        let daemon = async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut line = String::new();
+            tokio::io::BufReader::new(stream).read_line(&mut line).await.unwrap();
+        };
+        let client = tokio::task::spawn_blocking(move || {
+            let client = DaemonClient::connect(&path).unwrap();
+            client.send(&request(&["claude-hippocampus", "stats"]), SEND_TIMEOUT)
+        });
+        let (_, reply) = tokio::join!(daemon, client);
+        assert!(reply.unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_invocation_environment() {
+        let mut sent = request(&["claude-hippocampus", "stats"]);
+        sent.env.insert("PROJECT_PATH".to_string(), "/srv/app".to_string());
+        let (project, user, path, cwd) = sent
+            .enter(async {
+                (var("PROJECT_PATH"), var("USER"), absolute(Path::new("notes.md")), current_dir().unwrap())
+            })
+            .await;
+        assert_eq!(project.as_deref(), Some("/srv/app"));
+        // Forwarded variables the sender did not set are unset, not the daemon's
+        assert_eq!(user, None);
+        assert_eq!(path, PathBuf::from("/tmp/notes.md"));
+        assert_eq!(cwd, PathBuf::from("/tmp"));
+        assert_eq!(absolute(Path::new("notes.md")), PathBuf::from("notes.md"));
+    }
+
+    #[test]
+    fn test_spawned_commands_skip_the_daemon() {
+        let command = command("true");
+        assert!(command
+            .get_envs()
+            .any(|(key, value)| key == NO_DAEMON_ENV && value == Some(OsStr::new("1"))));
+    }
+
+    #[test]
+    fn test_connect_without_daemon() {
+        let dir = TempDir::new().unwrap();
+        assert!(DaemonClient::connect(&dir.path().join(SOCKET_NAME)).is_none());
+    }
+}
@@ -0,0 +1,195 @@
+//! Operation audit log
+//!
+//! Logs in `/tmp` and `~/.claude` stay on the machine that wrote them. The
+//! `operation_log` table keeps, next to the memories, a record of each
+//! command that changed them: who ran it, with which arguments, when, from
+//! which Claude session, and the memories it touched.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::postgres::PgPool;
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::error::Result;
+
+/// Most memory IDs kept for one operation
+const MAX_MEMORY_IDS: usize = 1000;
+
+/// A command to record
+#[derive(Debug, Clone, Default)]
+pub struct AuditRecord {
+    /// Subcommand name, e.g. `delete-memory`
+    pub operation: String,
+    /// Arguments after the subcommand
+    pub arguments: Vec<String>,
+    /// OS user who ran the command
+    pub actor: String,
+    pub session_id: Option<String>,
+    pub project_path: Option<String>,
+    pub memory_ids: Vec<Uuid>,
+    pub success: bool,
+}
+
+/// A recorded command
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub id: i64,
+    pub operation: String,
+    pub arguments: Vec<String>,
+    pub actor: String,
+    /// Database role the command connected as
+    pub db_user: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_path: Option<String>,
+    pub memory_ids: Vec<Uuid>,
+    pub success: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Which recorded commands to list; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    /// Commands that touched this memory
+    pub memory_id: Option<Uuid>,
+    /// Commands run from this Claude session
+    pub session_id: Option<String>,
+    pub operation: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub limit: i64,
+}
+
+/// Record a command.
+pub async fn record(pool: &PgPool, record: &AuditRecord) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO operation_log
+          (operation, arguments, actor, session_id, project_path, memory_ids, success)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+    )
+    .bind(&record.operation)
+    .bind(&record.arguments)
+    .bind(&record.actor)
+    .bind(&record.session_id)
+    .bind(&record.project_path)
+    .bind(&record.memory_ids)
+    .bind(record.success)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Recorded commands matching `query`, newest first.
+pub async fn list(pool: &PgPool, query: &AuditQuery) -> Result<Vec<AuditEntry>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, operation, arguments, actor, db_user, session_id, project_path,
+               memory_ids, success, created_at
+        FROM operation_log
+        WHERE ($1::uuid IS NULL OR memory_ids @> ARRAY[$1::uuid])
+          AND ($2::text IS NULL OR session_id = $2)
+          AND ($3::text IS NULL OR operation = $3)
+          AND ($4::timestamptz IS NULL OR created_at >= $4)
+          AND ($5::timestamptz IS NULL OR created_at <= $5)
+        ORDER BY created_at DESC, id DESC
+        LIMIT $6
+        "#,
+    )
+    .bind(query.memory_id)
+    .bind(&query.session_id)
+    .bind(&query.operation)
+    .bind(query.since)
+    .bind(query.until)
+    .bind(query.limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| AuditEntry {
+            id: r.get("id"),
+            operation: r.get("operation"),
+            arguments: r.get("arguments"),
+            actor: r.get("actor"),
+            db_user: r.get("db_user"),
+            session_id: r.get("session_id"),
+            project_path: r.get("project_path"),
+            memory_ids: r.get("memory_ids"),
+            success: r.get("success"),
+            created_at: r.get("created_at"),
+        })
+        .collect())
+}
+
+/// Memory IDs in a command's JSON result: UUIDs under `id` and keys ending
+/// in `Id` or `Ids`, except those naming sessions, turns and proposals.
+pub fn result_memory_ids(result: &Value) -> Vec<Uuid> {
+    let mut ids = Vec::new();
+    collect_ids(result, false, &mut ids);
+    ids.truncate(MAX_MEMORY_IDS);
+    ids
+}
+
+fn collect_ids(value: &Value, is_id: bool, ids: &mut Vec<Uuid>) {
+    match value {
+        Value::String(s) if is_id => {
+            if let Ok(id) = Uuid::parse_str(s) {
+                if !ids.contains(&id) && ids.len() < MAX_MEMORY_IDS {
+                    ids.push(id);
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_ids(item, is_id, ids)),
+        Value::Object(map) => {
+            for (key, value) in map {
+                collect_ids(value, is_memory_id_key(key), ids);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_memory_id_key(key: &str) -> bool {
+    let names_id = key == "id" || key.ends_with("Id") || key.ends_with("Ids");
+    let other = ["session", "claudeSession", "turn", "staging"]
+        .iter()
+        .any(|prefix| key.starts_with(prefix));
+    names_id && !other
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_result_memory_ids() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let result = json!({
+            "success": true,
+            "id": a.to_string(),
+            "sessionId": Uuid::new_v4().to_string(),
+            "stagingId": Uuid::new_v4().to_string(),
+            "merges": [{"keptId": b.to_string(), "mergedId": c.to_string(), "tags": ["x"]}],
+            "supersededIds": [a.to_string()],
+            "summary": a.to_string(),
+        });
+        assert_eq!(result_memory_ids(&result), vec![a, b, c]);
+    }
+
+    #[test]
+    fn test_result_memory_ids_ignores_non_uuids() {
+        let result = json!({"id": "not-a-uuid", "count": 3, "memoryIds": [1, 2]});
+        assert!(result_memory_ids(&result).is_empty());
+    }
+}
@@ -0,0 +1,72 @@
+//! Maintenance lock: one maintenance run at a time per database
+//!
+//! Maintenance can start from several places at once — a cron job, a shell,
+//! another machine sharing the database. A Postgres advisory lock, held on
+//! a connection of its own for the length of the run, keeps them from
+//! working over the same rows. The server drops it if the process dies.
+
+use sqlx::postgres::{PgConnection, PgPool};
+use sqlx::{Connection, Row};
+
+use crate::error::Result;
+
+/// Advisory lock key shared by every maintenance command ("hippomnt")
+const MAINTENANCE_LOCK_KEY: i64 = 0x6869_7070_6f6d_6e74;
+
+/// What to do when another maintenance run holds the lock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockMode {
+    /// Fail with [`HippocampusError::MaintenanceRunning`](crate::error::HippocampusError)
+    Fail,
+    /// Block until the other run finishes
+    Wait,
+    /// Skip this run
+    Skip,
+}
+
+/// The held maintenance lock; [`release`](Self::release) it when done
+pub struct MaintenanceLock {
+    conn: PgConnection,
+}
+
+impl MaintenanceLock {
+    /// Take the maintenance lock.
+    ///
+    /// Returns None when another run holds it and `mode` is not [`LockMode::Wait`].
+    pub async fn acquire(pool: &PgPool, mode: LockMode) -> Result<Option<Self>> {
+        // Detached so the lock's connection does not take a slot the run needs
+        let mut conn = pool.acquire().await?.detach();
+
+        let acquired = match mode {
+            LockMode::Wait => {
+                sqlx::query("SELECT pg_advisory_lock($1)")
+                    .bind(MAINTENANCE_LOCK_KEY)
+                    .execute(&mut conn)
+                    .await?;
+                true
+            }
+            LockMode::Fail | LockMode::Skip => sqlx::query("SELECT pg_try_advisory_lock($1) AS acquired")
+                .bind(MAINTENANCE_LOCK_KEY)
+                .fetch_one(&mut conn)
+                .await?
+                .get("acquired"),
+        };
+
+        if acquired {
+            Ok(Some(Self { conn }))
+        } else {
+            conn.close().await?;
+            Ok(None)
+        }
+    }
+
+    /// Release the lock and close its connection
+    pub async fn release(mut self) -> Result<()> {
+        sqlx::query("SELECT pg_advisory_unlock($1)")
+            .bind(MAINTENANCE_LOCK_KEY)
+            .execute(&mut self.conn)
+            .await?;
+        self.conn.close().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,233 @@
+//! Embedded schema migrations
+//!
+//! The SQL files in `migrations/` are compiled into the binary and applied
+//! by `claude-hippocampus migrate`. Applied versions are tracked in sqlx's
+//! `_sqlx_migrations` table. Every migration is idempotent, so databases set
+//! up by hand before this existed can be migrated in place.
+
+use serde::Serialize;
+use sqlx::migrate::Migrator;
+use sqlx::postgres::PgPool;
+use sqlx::Row;
+
+use crate::error::{HippocampusError, Result};
+
+/// Migrations embedded from `migrations/`
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// One embedded migration
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationInfo {
+    pub version: i64,
+    pub description: String,
+}
+
+/// Schema version of a database against the embedded migrations
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationStatus {
+    /// Highest applied version (None before the first `migrate`)
+    pub current_version: Option<i64>,
+    pub latest_version: i64,
+    pub pending: Vec<MigrationInfo>,
+    /// Migrations applied by this run (empty when only reporting)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub applied: Vec<MigrationInfo>,
+}
+
+/// Report the current schema version and the migrations not yet applied.
+pub async fn status(pool: &PgPool) -> Result<MigrationStatus> {
+    let applied = applied_versions(pool).await?;
+    Ok(build_status(&applied))
+}
+
+/// Fail unless every embedded migration is applied, so commands can rely
+/// on the schema this build's queries were written for.
+pub async fn require_current(pool: &PgPool) -> Result<()> {
+    let status = status(pool).await?;
+    if status.pending.is_empty() {
+        return Ok(());
+    }
+    Err(HippocampusError::SchemaOutdated {
+        current: status.current_version.unwrap_or(0),
+        latest: status.latest_version,
+    })
+}
+
+/// Apply all pending migrations, then report the resulting status.
+pub async fn run(pool: &PgPool) -> Result<MigrationStatus> {
+    let before = status(pool).await?;
+    MIGRATOR.run(pool).await?;
+
+    let mut after = status(pool).await?;
+    after.applied = before.pending;
+    Ok(after)
+}
+
+/// Versions recorded as successfully applied
+async fn applied_versions(pool: &PgPool) -> Result<Vec<i64>> {
+    let row = sqlx::query("SELECT to_regclass('_sqlx_migrations') IS NOT NULL AS present")
+        .fetch_one(pool)
+        .await?;
+    if !row.get::<bool, _>("present") {
+        return Ok(Vec::new());
+    }
+
+    let versions = sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success ORDER BY version")
+        .fetch_all(pool)
+        .await?;
+    Ok(versions)
+}
+
+fn embedded() -> Vec<MigrationInfo> {
+    MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| MigrationInfo {
+            version: m.version,
+            description: m.description.to_string(),
+        })
+        .collect()
+}
+
+fn build_status(applied: &[i64]) -> MigrationStatus {
+    let embedded = embedded();
+    MigrationStatus {
+        current_version: applied.iter().max().copied(),
+        latest_version: embedded.iter().map(|m| m.version).max().unwrap_or(0),
+        pending: embedded
+            .into_iter()
+            .filter(|m| !applied.contains(&m.version))
+            .collect(),
+        applied: Vec::new(),
+    }
+}
+
+/// Run `test` on a freshly migrated scratch database on the server at
+/// `DATABASE_URL`, dropped afterwards. None when the variable is not set,
+/// so database tests pass without a server.
+#[cfg(test)]
+pub(crate) async fn with_scratch_database<T, F, Fut>(test: F) -> Option<Result<T>>
+where
+    F: FnOnce(PgPool) -> Fut,
+    Fut: std::future::Future<Output = Result<T>>,
+{
+    use sqlx::postgres::PgConnectOptions;
+
+    let url = std::env::var("DATABASE_URL").ok()?;
+    let server = PgPool::connect(&url).await.unwrap();
+    let name = format!("hippocampus_test_{}", uuid::Uuid::new_v4().simple());
+    sqlx::query(&format!("CREATE DATABASE {}", name)).execute(&server).await.unwrap();
+
+    let options: PgConnectOptions = url.parse().unwrap();
+    let pool = PgPool::connect_with(options.database(&name)).await.unwrap();
+    let result = match MIGRATOR.run(&pool).await {
+        Ok(()) => test(pool.clone()).await,
+        Err(e) => Err(e.into()),
+    };
+    pool.close().await;
+    sqlx::query(&format!("DROP DATABASE {} WITH (FORCE)", name)).execute(&server).await.unwrap();
+    Some(result)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::queries;
+
+    #[test]
+    fn test_embedded_migrations_are_sequential() {
+        let versions: Vec<i64> = embedded().iter().map(|m| m.version).collect();
+        let expected: Vec<i64> = (1..=versions.len() as i64).collect();
+        assert_eq!(versions, expected);
+    }
+
+    #[test]
+    fn test_status_of_fresh_database() {
+        let status = build_status(&[]);
+        assert_eq!(status.current_version, None);
+        assert_eq!(status.pending.len(), embedded().len());
+        assert_eq!(status.pending[0].description, "initial schema");
+    }
+
+    #[test]
+    fn test_status_lists_only_unapplied() {
+        let status = build_status(&[1, 2, 3]);
+        assert_eq!(status.current_version, Some(3));
+        assert!(status.pending.iter().all(|m| m.version > 3));
+        assert_eq!(status.latest_version, embedded().len() as i64);
+    }
+
+    #[tokio::test]
+    async fn test_require_current_names_migrate_when_behind() {
+        let Some(result) = with_scratch_database(|pool| async move {
+            require_current(&pool).await?;
+            sqlx::query("DELETE FROM _sqlx_migrations WHERE version = (SELECT MAX(version) FROM _sqlx_migrations)")
+                .execute(&pool)
+                .await?;
+            Ok(require_current(&pool).await.unwrap_err().to_string())
+        })
+        .await
+        else {
+            return;
+        };
+
+        let message = result.unwrap();
+        assert!(message.ends_with("run `claude-hippocampus migrate`"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn test_migrated_database_round_trips_a_session() {
+        let Some(result) = with_scratch_database(|pool| async move {
+            let created = queries::create_session(&pool, "migrated", Some("/work/app"), None, None).await?;
+            sqlx::query("UPDATE sessions SET models_used = ARRAY['claude-opus'] WHERE id = $1")
+                .bind(created.id)
+                .execute(&pool)
+                .await?;
+            queries::find_session_by_id(&pool, created.id).await
+        })
+        .await
+        else {
+            return;
+        };
+
+        let session = result.unwrap().unwrap();
+        assert_eq!(session.claude_session_id, "migrated");
+        assert_eq!(session.models_used, Some(vec!["claude-opus".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn test_insert_without_summary_derives_it() {
+        let contents = [
+            "  Builds use make. Then run the tests.\nSecond line".to_string(),
+            "x".repeat(120),
+            "Version 2.0 is out! see notes".to_string(),
+        ];
+        let Some(result) = with_scratch_database(|pool| async move {
+            let mut summaries = Vec::new();
+            for content in &contents {
+                let summary: String = sqlx::query_scalar(
+                    "INSERT INTO memories (type, scope, content) VALUES ('learning', 'global', $1) RETURNING summary",
+                )
+                .bind(content)
+                .fetch_one(&pool)
+                .await?;
+                summaries.push((summary, crate::models::summarize(content)));
+            }
+            Ok(summaries)
+        })
+        .await
+        else {
+            return;
+        };
+
+        for (stored, derived) in result.unwrap() {
+            assert_eq!(stored, derived);
+        }
+    }
+}
@@ -1,16 +1,79 @@
+pub mod audit;
+pub mod lock;
+pub mod migrations;
 pub mod pool;
 pub mod queries;
 
-pub use pool::{create_pool, create_pool_with_password, get_project_path};
+pub use lock::{LockMode, MaintenanceLock};
+pub use pool::{
+    commit, create_pool, create_pool_with_password, create_rollback_pool, create_transaction_pool,
+    get_project_path, get_project_paths, keep_pools_warm, normalize_project_path, rollback,
+    split_project_paths,
+};
 pub use queries::{
-    consolidate_duplicates, delete_memory, find_duplicate, get_context_memories, get_memory,
-    insert_memory, list_recent, prune_old_memories_tiered, save_session_summary, search_keyword,
-    update_memory, DuplicateInfo,
+    consolidate_duplicates, delete_memory, edit_memory, find_duplicate, get_context_memories, get_memory,
+    decay_memories, get_memory_as_of, insert_memory, list_memories_page, list_recent,
+    merge_duplicates, merge_memory_into, prune_old_memories_tiered, DecayStats, MergedDuplicate,
+    find_cross_scope_duplicates, CrossScopeDuplicate,
+    list_memory_revisions, MemoryRevision,
+    find_memories_cited_in, save_session_summary, search_fts, search_keyword, update_memory, DuplicateInfo, MemoryEdit,
+    SearchFilters,
     // Session queries
     create_session, end_session, find_session_by_claude_id, find_session_by_id,
+    last_session_boundary, list_session_summaries, list_sessions, record_skipped_prompt, search_sessions,
     // Turn queries
-    create_turn, find_turn_by_id, get_next_turn_number, update_turn,
+    create_turn, find_latest_turn_id, find_turn_by_id, get_next_turn_number, list_answered_sessions,
+    list_session_memories, list_session_turns, update_turn, SessionTurn,
+    // Turn embedding queries
+    count_unembedded_turns, list_turn_embeddings, list_unembedded_turns, store_turn_embedding,
+    EmbeddedTurn,
+    // Memory embedding queries
+    count_unembedded_memories, list_memory_embeddings, list_unembedded_memories,
+    store_memory_embedding, EmbeddedMemory,
+    // Tag queries
+    list_tags, replace_tags, search_by_tag, TagCount,
     // Supersession queries
     list_superseded, prune_lifecycle_data, purge_superseded, show_chain, supersede_memory,
     ChainResult, LifecyclePruneResult, SupersededMemoryInfo,
+    // Review queue queries
+    count_review_reminder, find_memories_mentioning, flag_memories_for_review, list_review_queue,
+    resolve_review, ReviewQueueEntry, ReviewReminder,
+    // Tool call queries
+    count_tool_calls_by_turn, list_session_tool_calls,
+    // Extraction quota queries
+    count_extraction_queue, dequeue_extraction, extraction_calls_today, list_extraction_queue,
+    queue_extraction, reserve_extraction,
+    // Hook timing queries
+    list_hook_timings, record_hook_timing, HookTiming,
+    // Extraction attempt queries
+    extraction_failures_last_day, list_extractions, mark_extraction_retried, start_extraction,
+    update_extraction,
+    ExtractionAttempt, ExtractionStatus,
+    // Citation queries
+    list_memory_citations, list_session_citations, record_citations, CitationKind, MemoryCitation,
+    SessionCitation,
+    // Staged memory queries
+    delete_staged_memory, find_conflict_candidates, find_similar_memories, get_staged_memory,
+    list_expired_staged, stage_memory, ConflictCandidate, SimilarMemory, StagedMemory,
+    // Access rollup queries
+    rollup_memory_access, top_accessed_memories, AccessRollup,
+    // Session mute queries
+    list_muted_memories, mute_memory, unmute_memory,
+    // Memory link queries
+    delete_memory_links, insert_memory_link, list_memory_links,
+    // Project registry queries
+    add_project_alias, find_project, list_projects, remove_project_alias, resolve_project_paths,
+    Project,
+    // Workspace queries
+    add_workspace_project, create_workspace, find_workspace, list_workspaces,
+    with_workspace_projects, Workspace,
+    // Project briefing queries
+    delete_briefing, get_briefing, set_briefing, ProjectBriefing,
+    // Settings queries
+    get_setting, set_setting,
+    // Legacy import queries
+    fetch_rows_as_json, get_memories_by_ids, import_memory, list_table_columns, ImportedMemory,
+    // Fixture queries
+    delete_fixtures, insert_fixtures, FixtureMemory, FixtureSession, FIXTURE_METADATA,
+    FIXTURE_SESSION_PREFIX,
 };
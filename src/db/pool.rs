@@ -1,18 +1,53 @@
-use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::postgres::{PgPool, PgPoolOptions, PgTransactionManager};
+use sqlx::TransactionManager;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
 use std::time::Duration;
 
 use crate::config::DbConfig;
+use crate::daemon;
 use crate::error::Result;
 
+/// Pools kept open for reuse once [`keep_pools_warm`] is called, by
+/// connection string
+static WARM_POOLS: OnceLock<Mutex<HashMap<String, PgPool>>> = OnceLock::new();
+
+/// Keep pools from [`create_pool`] open for the rest of the process and
+/// hand out the same pool for the same database, so a long-running daemon
+/// connects once instead of per command.
+pub fn keep_pools_warm() {
+    WARM_POOLS.get_or_init(Default::default);
+}
+
 /// Create a PostgreSQL connection pool from config
 pub async fn create_pool(config: &DbConfig) -> Result<PgPool> {
+    let url = config.connection_string();
+    let Some(warm) = WARM_POOLS.get() else {
+        let pool = PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .acquire_timeout(Duration::from_secs(5))
+            .idle_timeout(Duration::from_secs(30))
+            .connect(&url)
+            .await?;
+        return Ok(pool);
+    };
+
+    let cached = warm.lock().ok().and_then(|pools| pools.get(&url).cloned());
+    if let Some(pool) = cached.filter(|pool| !pool.is_closed()) {
+        return Ok(pool);
+    }
+    // One connection stays open between commands
     let pool = PgPoolOptions::new()
         .max_connections(config.max_connections)
+        .min_connections(1)
         .acquire_timeout(Duration::from_secs(5))
-        .idle_timeout(Duration::from_secs(30))
-        .connect(&config.connection_string())
+        .idle_timeout(Duration::from_secs(300))
+        .connect(&url)
         .await?;
-
+    if let Ok(mut pools) = warm.lock() {
+        pools.insert(url, pool.clone());
+    }
     Ok(pool)
 }
 
@@ -29,13 +64,103 @@ pub async fn create_pool_with_password(config: &DbConfig, password: Option<&str>
     Ok(pool)
 }
 
-/// Get the current project path from environment or working directory
+/// Create a single-connection pool whose connection runs inside a transaction.
+///
+/// Nothing is ever committed: the caller calls [`rollback`] (or simply closes
+/// the pool) when done. Used to simulate hooks without persisting writes.
+pub async fn create_rollback_pool(config: &DbConfig) -> Result<PgPool> {
+    create_transaction_pool(config).await
+}
+
+/// Create a single-connection pool whose connection runs inside a transaction.
+///
+/// Writes persist only after [`commit`]; closing the pool first discards them.
+/// The transaction is opened through sqlx, so queries that call
+/// `pool.begin()` themselves get a savepoint rather than committing it.
+pub async fn create_transaction_pool(config: &DbConfig) -> Result<PgPool> {
+    let pool = transaction_pool_options()
+        .connect(&config.connection_string())
+        .await?;
+
+    Ok(pool)
+}
+
+fn transaction_pool_options() -> PgPoolOptions {
+    PgPoolOptions::new()
+        .max_connections(1)
+        .acquire_timeout(Duration::from_secs(5))
+        // A replacement connection would start a fresh transaction mid-run
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .after_connect(|conn, _meta| Box::pin(PgTransactionManager::begin(conn, None)))
+}
+
+/// Commit the transaction of a pool from [`create_transaction_pool`]
+pub async fn commit(pool: &PgPool) -> Result<()> {
+    let mut conn = pool.acquire().await?;
+    PgTransactionManager::commit(&mut conn).await?;
+    Ok(())
+}
+
+/// Discard the transaction of a pool from [`create_transaction_pool`]
+pub async fn rollback(pool: &PgPool) -> Result<()> {
+    let mut conn = pool.acquire().await?;
+    PgTransactionManager::rollback(&mut conn).await?;
+    Ok(())
+}
+
+/// Get the current project path from environment or working directory.
+///
+/// When `PROJECT_PATH` lists several projects, this is the first one: the
+/// project new memories and config belong to.
 pub fn get_project_path() -> Option<String> {
-    std::env::var("PROJECT_PATH").ok().or_else(|| {
-        std::env::current_dir()
-            .ok()
-            .map(|p| p.to_string_lossy().to_string())
-    })
+    get_project_paths().into_iter().next()
+}
+
+/// Get every project scoped reads include.
+///
+/// `PROJECT_PATH` may be a colon-separated list, e.g. a monorepo root and
+/// the package being worked on; without it, the working directory. Paths
+/// are normalized (see [`normalize_project_path`]); registered aliases are
+/// added by `queries::resolve_project_paths`.
+pub fn get_project_paths() -> Vec<String> {
+    let paths = match daemon::var("PROJECT_PATH") {
+        Some(value) if !split_project_paths(&value).is_empty() => split_project_paths(&value),
+        _ => daemon::current_dir()
+            .map(|p| vec![p.to_string_lossy().to_string()])
+            .unwrap_or_default(),
+    };
+    let mut normalized: Vec<String> = Vec::new();
+    for path in paths.iter().map(|p| normalize_project_path(p)) {
+        if !normalized.contains(&path) {
+            normalized.push(path);
+        }
+    }
+    normalized
+}
+
+/// The path a project is keyed by: with symlinks and `..` resolved when it
+/// exists, so a repository reached through a link shares its memories, and
+/// without a trailing slash
+pub fn normalize_project_path(path: &str) -> String {
+    match std::fs::canonicalize(daemon::absolute(Path::new(path))) {
+        Ok(real) => real.to_string_lossy().to_string(),
+        Err(_) => match path.trim_end_matches('/') {
+            "" => path.to_string(),
+            trimmed => trimmed.to_string(),
+        },
+    }
+}
+
+/// Split a colon-separated project list, dropping empty entries and repeats
+pub fn split_project_paths(value: &str) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    for path in value.split(':').filter(|p| !p.is_empty()) {
+        if !paths.iter().any(|p| p == path) {
+            paths.push(path.to_string());
+        }
+    }
+    paths
 }
 
 // ============================================================================
@@ -62,6 +187,83 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_normalize_project_path() {
+        assert_eq!(normalize_project_path("/no/such/repo/"), "/no/such/repo");
+        assert_eq!(normalize_project_path("/"), "/");
+
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().join("repo");
+        std::fs::create_dir(&real).unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+        let real = std::fs::canonicalize(&real).unwrap().to_string_lossy().to_string();
+        assert_eq!(normalize_project_path(&format!("{}/", link.display())), real);
+    }
+
+    #[test]
+    fn test_split_project_paths() {
+        assert_eq!(split_project_paths("/repo:/repo/pkg:/repo:"), vec!["/repo", "/repo/pkg"]);
+        assert_eq!(split_project_paths("/repo"), vec!["/repo"]);
+        assert!(split_project_paths(":").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_transaction_pool_survives_queries_that_commit() {
+        use crate::db::migrations::with_scratch_database;
+        use crate::db::queries::{self, MemoryEdit};
+        use crate::models::{Confidence, MemoryType, Scope};
+
+        let Some(result) = with_scratch_database(|pool| async move {
+            let options = pool.connect_options().as_ref().clone();
+            let count = || async {
+                sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM memories").fetch_one(&pool).await
+            };
+
+            // edit_memory begins and commits a transaction of its own
+            let write = |transaction: PgPool| async move {
+                let id = queries::insert_memory(
+                    &transaction,
+                    MemoryType::Learning,
+                    Scope::Global,
+                    None,
+                    "Builds use make",
+                    "Builds use make",
+                    &[],
+                    Confidence::High,
+                    None,
+                    None,
+                    None,
+                )
+                .await?;
+                let edit = MemoryEdit {
+                    content: Some("Builds use just".to_string()),
+                    ..MemoryEdit::default()
+                };
+                queries::edit_memory(&transaction, id, &edit, None).await?;
+                Ok::<_, crate::error::HippocampusError>(())
+            };
+
+            let discarded = transaction_pool_options().connect_with(options.clone()).await?;
+            write(discarded.clone()).await?;
+            discarded.close().await;
+            let after_close = count().await?;
+
+            let committed = transaction_pool_options().connect_with(options).await?;
+            write(committed.clone()).await?;
+            commit(&committed).await?;
+            committed.close().await;
+            Ok((after_close, count().await?))
+        })
+        .await
+        else {
+            return;
+        };
+
+        assert_eq!(result.unwrap(), (0, 1));
+    }
+
     // Note: Integration tests for create_pool require a running database
     // Those tests will be in tests/integration/
 }
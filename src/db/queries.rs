@@ -1,54 +1,103 @@
-use sqlx::postgres::PgPool;
-use sqlx::Row;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::{PgPool, Postgres};
+use sqlx::QueryBuilder;
 use uuid::Uuid;
 
+use crate::commands::ranking::{Ranker, Recency};
 use crate::error::{HippocampusError, Result};
+use crate::environment::EnvironmentSnapshot;
 use crate::git::GitStatus;
-use crate::models::{Confidence, Memory, MemoryType, Scope, Session};
+use crate::models::{
+    summarize, Confidence, LenientJson, LinkedMemory, Memory, MemoryType, Scope, Session, SessionMatch, SessionStatus,
+    SessionSummary,
+};
+use crate::util::cursor::Cursor;
 
-/// Check for duplicate memory by matching first 100 chars of content
+/// Check for a duplicate memory of the same type.
+///
+/// Matches the same `summary` (ignoring case), or an active memory whose
+/// trigram similarity reaches `similarity`. `exclude` is left out, so a
+/// memory can be superseded by a rewording of itself.
+///
+/// With `scope`, only memories visible alongside a new memory of that
+/// scope are checked: global memories, plus those of `project_path` for a
+/// project memory. Without it, every memory is.
+#[allow(clippy::too_many_arguments)]
 pub async fn find_duplicate(
     pool: &PgPool,
     memory_type: MemoryType,
     content: &str,
+    summary: &str,
+    similarity: f64,
+    scope: Option<Scope>,
+    project_path: Option<&str>,
+    exclude: Option<Uuid>,
 ) -> Result<Option<DuplicateInfo>> {
-    let content_prefix = content
-        .chars()
-        .take(100)
-        .collect::<String>()
-        .to_lowercase();
-
-    let row = sqlx::query(
-        r#"
-        SELECT id, content, scope, confidence
-        FROM memories
-        WHERE type = $1
-          AND LOWER(SUBSTRING(content, 1, 100)) = $2
-        LIMIT 1
-        "#,
-    )
-    .bind(memory_type.as_str())
-    .bind(&content_prefix)
-    .fetch_optional(pool)
-    .await?;
+    let summary_key = summary.to_lowercase();
+    let scope = scope.map(|s| s.as_str());
 
-    match row {
-        Some(row) => {
-            let content: String = row.get("content");
-            let summary = if content.len() > 100 {
-                format!("{}...", &content[..97])
-            } else {
-                content
-            };
-
-            Ok(Some(DuplicateInfo {
-                id: row.get("id"),
-                scope: row.get::<String, _>("scope"),
-                summary,
-            }))
-        }
-        None => Ok(None),
-    }
+    let duplicate = if fuzzy_dedup_enabled(similarity) {
+        sqlx::query!(
+            r#"
+            SELECT id, summary, scope, content,
+                   LOWER(summary) = $2 AS "summary_match!",
+                   round(similarity(content, $3)::numeric, 3)::float8 AS similarity
+            FROM memories
+            WHERE type = $1
+              AND id IS DISTINCT FROM $5
+              AND ($6::text IS NULL OR scope = 'global'
+                   OR ($6 = 'project' AND project_path = $7))
+              AND (LOWER(summary) = $2
+                   OR (is_active AND similarity(content, $3) >= $4::float8))
+            ORDER BY LOWER(summary) = $2 DESC, similarity(content, $3) DESC
+            LIMIT 1
+            "#,
+            memory_type.as_str(),
+            summary_key,
+            content,
+            similarity,
+            exclude,
+            scope,
+            project_path,
+        )
+        .fetch_optional(pool)
+        .await?
+        .map(|r| DuplicateInfo {
+            id: r.id,
+            scope: r.scope,
+            summary: r.summary,
+            content: r.content,
+            similarity: if r.summary_match { None } else { r.similarity },
+        })
+    } else {
+        sqlx::query!(
+            r#"
+            SELECT id, summary, scope, content
+            FROM memories
+            WHERE type = $1
+              AND id IS DISTINCT FROM $3
+              AND ($4::text IS NULL OR scope = 'global'
+                   OR ($4 = 'project' AND project_path = $5))
+              AND LOWER(summary) = $2
+            LIMIT 1
+            "#,
+            memory_type.as_str(),
+            summary_key,
+            exclude,
+            scope,
+            project_path,
+        )
+        .fetch_optional(pool)
+        .await?
+        .map(|r| DuplicateInfo {
+            id: r.id,
+            scope: r.scope,
+            summary: r.summary,
+            content: r.content,
+            similarity: None,
+        })
+    };
+    Ok(duplicate)
 }
 
 /// Information about a duplicate memory
@@ -57,84 +106,241 @@ pub struct DuplicateInfo {
     pub id: Uuid,
     pub scope: String,
     pub summary: String,
+    pub content: String,
+    /// Trigram similarity when matched as a near-duplicate (None for a
+    /// matching summary)
+    pub similarity: Option<f64>,
+}
+
+/// Whether duplicate checks use trigram similarity: with a threshold below 1.0
+fn fuzzy_dedup_enabled(similarity: f64) -> bool {
+    similarity < 1.0
 }
 
 /// Insert a new memory entry
+#[allow(clippy::too_many_arguments)]
 pub async fn insert_memory(
     pool: &PgPool,
     memory_type: MemoryType,
     scope: Scope,
     project_path: Option<&str>,
     content: &str,
+    summary: &str,
     tags: &[String],
     confidence: Confidence,
     source_session_id: Option<Uuid>,
     source_turn_id: Option<Uuid>,
+    metadata: Option<&serde_json::Value>,
 ) -> Result<Uuid> {
-    let row = sqlx::query(
+    let id = sqlx::query_scalar!(
         r#"
-        INSERT INTO memories (type, scope, project_path, content, tags, confidence, source_session_id, source_turn_id)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        INSERT INTO memories (type, scope, project_path, content, tags, confidence, source_session_id, source_turn_id, metadata, summary)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
         RETURNING id
         "#,
+        memory_type.as_str(),
+        scope.as_str(),
+        project_path,
+        content,
+        tags,
+        confidence.as_str(),
+        source_session_id,
+        source_turn_id,
+        metadata,
+        summary,
     )
-    .bind(memory_type.as_str())
-    .bind(scope.as_str())
-    .bind(project_path)
-    .bind(content)
-    .bind(tags)
-    .bind(confidence.as_str())
-    .bind(source_session_id)
-    .bind(source_turn_id)
     .fetch_one(pool)
     .await?;
 
-    Ok(row.get("id"))
+    Ok(id)
 }
 
-/// Update an existing memory's content
+/// Update an existing memory's content, regenerating its summary.
+///
+/// With `expected_updated_at`, the row is only changed if it was not updated
+/// since then. Returns the new `updated_at`, or None when no row matched.
 pub async fn update_memory(
     pool: &PgPool,
     id: Uuid,
     content: &str,
     scope: Option<Scope>,
     project_path: Option<&str>,
-) -> Result<bool> {
-    let result = if let Some(s) = scope {
-        sqlx::query(
+    expected_updated_at: Option<DateTime<Utc>>,
+) -> Result<Option<DateTime<Utc>>> {
+    let mut tx = pool.begin().await?;
+    let previous = fetch_memory_for_update(&mut tx, id).await?;
+
+    let updated_at = if let Some(s) = scope {
+        sqlx::query_scalar!(
             r#"
             UPDATE memories
-            SET content = $2, scope = $3, project_path = $4, updated_at = NOW()
-            WHERE id = $1
+            SET content = $2, summary = $6, scope = $3, project_path = $4, updated_at = NOW()
+            WHERE id = $1 AND ($5::timestamptz IS NULL OR updated_at = $5)
+            RETURNING updated_at AS "updated_at!"
             "#,
+            id,
+            content,
+            s.as_str(),
+            project_path,
+            expected_updated_at,
+            summarize(content),
         )
-        .bind(id)
-        .bind(content)
-        .bind(s.as_str())
-        .bind(project_path)
-        .execute(pool)
+        .fetch_optional(&mut *tx)
         .await?
     } else {
-        sqlx::query(
+        sqlx::query_scalar!(
             r#"
             UPDATE memories
-            SET content = $2, updated_at = NOW()
-            WHERE id = $1
+            SET content = $2, summary = $4, updated_at = NOW()
+            WHERE id = $1 AND ($3::timestamptz IS NULL OR updated_at = $3)
+            RETURNING updated_at AS "updated_at!"
             "#,
+            id,
+            content,
+            expected_updated_at,
+            summarize(content),
         )
-        .bind(id)
-        .bind(content)
-        .execute(pool)
+        .fetch_optional(&mut *tx)
         .await?
     };
 
-    Ok(result.rows_affected() > 0)
+    if let (Some(_), Some(previous)) = (updated_at, previous) {
+        if previous.content != content {
+            record_revision(&mut tx, id, &previous).await?;
+        }
+    }
+    tx.commit().await?;
+
+    Ok(updated_at)
+}
+
+/// Fields to change in [`edit_memory`]; `None` and empty lists leave a field as is
+#[derive(Debug, Clone, Default)]
+pub struct MemoryEdit {
+    pub content: Option<String>,
+    pub memory_type: Option<MemoryType>,
+    pub confidence: Option<Confidence>,
+    /// Tags appended unless already present (ignoring case)
+    pub add_tags: Vec<String>,
+    /// Tags removed (ignoring case)
+    pub remove_tags: Vec<String>,
+}
+
+/// Change individual fields of a memory, leaving the others untouched.
+///
+/// Returns the edited memory, or None when no memory matched (it does not
+/// exist, or its `updated_at` differs from `expected_updated_at`).
+pub async fn edit_memory(
+    pool: &PgPool,
+    id: Uuid,
+    edit: &MemoryEdit,
+    expected_updated_at: Option<DateTime<Utc>>,
+) -> Result<Option<Memory>> {
+    let remove: Vec<String> = edit.remove_tags.iter().map(|t| t.to_lowercase()).collect();
+    let mut tx = pool.begin().await?;
+    let previous = fetch_memory_for_update(&mut tx, id).await?;
+
+    let edited: Option<Memory> = sqlx::query_as!(
+        Memory,
+        r#"
+        UPDATE memories
+        SET content = COALESCE($2, content),
+            summary = COALESCE($8, summary),
+            type = COALESCE($3, type),
+            confidence = COALESCE($4, confidence),
+            tags = (
+              SELECT COALESCE(array_agg(tag ORDER BY ord), '{}')
+              FROM (
+                SELECT DISTINCT ON (lower(tag)) tag, ord
+                FROM unnest(tags || $5::text[]) WITH ORDINALITY AS u(tag, ord)
+                WHERE NOT lower(tag) = ANY($6)
+                ORDER BY lower(tag), ord
+              ) kept
+            ),
+            updated_at = NOW()
+        WHERE id = $1 AND ($7::timestamptz IS NULL OR updated_at = $7)
+        RETURNING id, type AS "memory_type: MemoryType", scope AS "scope: Scope", project_path, content,
+                  tags AS "tags!", confidence AS "confidence!: Confidence", source_session_id, source_turn_id,
+                  created_at AS "created_at!", updated_at AS "updated_at!", accessed_at, access_count AS "access_count!",
+                  superseded_by, superseded_at, is_active AS "is_active!",
+                  NULLIF(metadata, '{}') AS metadata, summary, archived_at, pinned
+        "#,
+        id,
+        edit.content.as_deref(),
+        edit.memory_type.map(|t| t.as_str()),
+        edit.confidence.map(|c| c.as_str()),
+        &edit.add_tags,
+        &remove,
+        expected_updated_at,
+        edit.content.as_deref().map(summarize),
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    if let (Some(edited), Some(previous)) = (&edited, previous) {
+        if previous.content != edited.content {
+            record_revision(&mut tx, id, &previous).await?;
+        }
+    }
+    tx.commit().await?;
+
+    Ok(edited)
+}
+
+/// Keep the content and summary of `previous` as the memory's next
+/// revision; the memory must be locked
+async fn record_revision(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    memory_id: Uuid,
+    previous: &Memory,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO memory_revisions (memory_id, revision, content, summary)
+        SELECT $1, COALESCE(MAX(revision), 0) + 1, $2, $3
+        FROM memory_revisions
+        WHERE memory_id = $1
+        "#,
+        memory_id,
+        previous.content,
+        previous.summary,
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// A past content of a memory
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryRevision {
+    pub revision: i32,
+    pub content: String,
+    /// When the next edit replaced this content
+    pub replaced_at: DateTime<Utc>,
+}
+
+/// A memory's past contents, oldest first
+pub async fn list_memory_revisions(pool: &PgPool, memory_id: Uuid) -> Result<Vec<MemoryRevision>> {
+    let revisions = sqlx::query_as!(
+        MemoryRevision,
+        r#"
+        SELECT revision, content, replaced_at
+        FROM memory_revisions
+        WHERE memory_id = $1
+        ORDER BY revision
+        "#,
+        memory_id,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(revisions)
 }
 
 /// Delete a memory by ID
 pub async fn delete_memory(pool: &PgPool, id: Uuid) -> Result<bool> {
-    let result = sqlx::query("DELETE FROM memories WHERE id = $1")
-        .bind(id)
+    let result = sqlx::query!("DELETE FROM memories WHERE id = $1", id)
         .execute(pool)
         .await?;
 
@@ -143,359 +349,574 @@ pub async fn delete_memory(pool: &PgPool, id: Uuid) -> Result<bool> {
 
 /// Get a single memory by ID
 pub async fn get_memory(pool: &PgPool, id: Uuid) -> Result<Option<Memory>> {
-    let row = sqlx::query(
+    let memory = sqlx::query_as!(
+        Memory,
         r#"
-        SELECT id, type, scope, project_path, content, tags, confidence,
-               source_session_id, source_turn_id, created_at, updated_at,
-               accessed_at, access_count, superseded_by, superseded_at, is_active
+        SELECT id, type AS "memory_type: MemoryType", scope AS "scope: Scope", project_path, content,
+               tags AS "tags!", confidence AS "confidence!: Confidence", source_session_id, source_turn_id,
+               created_at AS "created_at!", updated_at AS "updated_at!", accessed_at, access_count AS "access_count!",
+               superseded_by, superseded_at, is_active AS "is_active!",
+               NULLIF(metadata, '{}') AS metadata, summary, archived_at, pinned
         FROM memories
         WHERE id = $1
         "#,
+        id,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(memory)
+}
+
+/// Get the version of a memory's supersession chain that was active at a time,
+/// with the content and summary it had then: those of its first revision
+/// replaced after `as_of` (revision N holds them before the Nth edit), or
+/// its current ones if none was.
+///
+/// Walks both directions from `id`, so asking about a replacement returns
+/// the memory it replaced if the replacement did not exist yet.
+pub async fn get_memory_as_of(
+    pool: &PgPool,
+    id: Uuid,
+    as_of: DateTime<Utc>,
+) -> Result<Option<Memory>> {
+    let memory = sqlx::query_as!(
+        Memory,
+        r#"
+        WITH RECURSIVE predecessors AS (
+            SELECT id FROM memories WHERE id = $1
+            UNION
+            SELECT m.id FROM memories m JOIN predecessors p ON m.superseded_by = p.id
+        ), successors AS (
+            SELECT id, superseded_by FROM memories WHERE id = $1
+            UNION
+            SELECT m.id, m.superseded_by FROM memories m JOIN successors s ON m.id = s.superseded_by
+        )
+        SELECT id, type AS "memory_type: MemoryType", scope AS "scope: Scope", project_path,
+               COALESCE(past.content, memories.content) AS "content!",
+               tags AS "tags!", confidence AS "confidence!: Confidence", source_session_id, source_turn_id,
+               created_at AS "created_at!", updated_at AS "updated_at!", accessed_at, access_count AS "access_count!",
+               superseded_by, superseded_at, is_active AS "is_active!", NULLIF(metadata, '{}') AS metadata,
+               COALESCE(past.summary, memories.summary) AS "summary!", archived_at, pinned
+        FROM memories
+        LEFT JOIN LATERAL (
+            SELECT r.content, r.summary FROM memory_revisions r
+            WHERE r.memory_id = memories.id AND r.replaced_at > $2
+            ORDER BY r.revision
+            LIMIT 1
+        ) past ON true
+        WHERE id IN (SELECT id FROM predecessors UNION SELECT id FROM successors)
+          AND created_at <= $2
+          AND (superseded_at IS NULL OR superseded_at > $2)
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#,
+        id,
+        as_of,
     )
-    .bind(id)
     .fetch_optional(pool)
     .await?;
 
-    match row {
-        Some(row) => Ok(Some(row_to_memory(&row)?)),
-        None => Ok(None),
+    Ok(memory)
+}
+
+/// Memory columns, in the order `Memory` is decoded from; an empty metadata
+/// object reads as none
+const MEMORY_COLUMNS: &str = "id, type, scope, project_path, content, tags, confidence, \
+    source_session_id, source_turn_id, created_at, updated_at, accessed_at, access_count, \
+    superseded_by, superseded_at, is_active, NULLIF(metadata, '{}') AS metadata, summary, archived_at, pinned";
+
+/// Narrowing a keyword or type search on top of its tier
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchFilters {
+    /// Only memories created at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only memories created at or before this time
+    pub until: Option<DateTime<Utc>>,
+    pub confidence: Option<Confidence>,
+    /// Only memories carrying this tag, ignoring case
+    pub tag: Option<String>,
+}
+
+/// Conditions of a memory search, composed into a query with bound values.
+///
+/// Archived memories are left out unless `include_archived` (or `as_of`)
+/// is set. With `as_of`, the memories active at that time match instead of
+/// the currently active ones. A new filter is a field here and one arm in
+/// [`MemoryFilter::push_where`].
+#[derive(Debug, Clone, Copy, Default)]
+struct MemoryFilter<'a> {
+    scope: Option<Scope>,
+    /// Global memories plus project memories of `project_paths`
+    both_scopes: bool,
+    project_paths: &'a [String],
+    as_of: Option<DateTime<Utc>>,
+    include_archived: bool,
+    memory_type: Option<MemoryType>,
+    /// Substring of the content or of a tag, ignoring case
+    keyword: Option<&'a str>,
+    /// Tag, ignoring case
+    tag: Option<&'a str>,
+    created_since: Option<DateTime<Utc>>,
+    created_until: Option<DateTime<Utc>>,
+    confidence: Option<Confidence>,
+}
+
+impl<'a> MemoryFilter<'a> {
+    fn new(scope: Option<Scope>, both_scopes: bool, project_paths: &'a [String]) -> Self {
+        Self {
+            scope,
+            both_scopes,
+            project_paths,
+            ..Self::default()
+        }
+    }
+
+    /// With the conditions of `filters` added
+    fn narrowed(self, filters: &'a SearchFilters) -> Self {
+        Self {
+            tag: filters.tag.as_deref(),
+            created_since: filters.since,
+            created_until: filters.until,
+            confidence: filters.confidence,
+            ..self
+        }
+    }
+
+    /// Push ` FROM memories`. With `as_of`, the memories as they were then:
+    /// each with the content and summary it had, as in [`get_memory_as_of`],
+    /// and the search vector of that content.
+    fn push_from(&self, query: &mut QueryBuilder<'a, Postgres>) {
+        let Some(as_of) = self.as_of else {
+            query.push(" FROM memories");
+            return;
+        };
+        // The select over these reads metadata through NULLIF
+        let columns: Vec<String> = MEMORY_COLUMNS
+            .replace("NULLIF(metadata, '{}') AS metadata", "metadata")
+            .split(", ")
+            .map(|column| match column {
+                "content" | "summary" => format!("COALESCE(past.{column}, m.{column}) AS {column}"),
+                column => format!("m.{}", column),
+            })
+            .collect();
+        query
+            .push(" FROM (SELECT ")
+            .push(columns.join(", "))
+            .push(
+                ", setweight(array_to_tsvector(COALESCE(m.tags, '{}')), 'A') || \
+                 setweight(to_tsvector('english', COALESCE(past.content, m.content)), 'B') AS search_vector \
+                 FROM memories m LEFT JOIN LATERAL (SELECT r.content, r.summary \
+                 FROM memory_revisions r WHERE r.memory_id = m.id AND r.replaced_at > ",
+            )
+            .push_bind(as_of)
+            .push(" ORDER BY r.revision LIMIT 1) past ON true) AS memories");
+    }
+
+    /// Push ` WHERE` and the conditions, each value as a bind parameter
+    fn push_where(&self, query: &mut QueryBuilder<'a, Postgres>) {
+        query.push(" WHERE ");
+        let mut conditions = query.separated(" AND ");
+
+        if self.both_scopes {
+            conditions
+                .push("(scope = 'global' OR (scope = 'project' AND project_path = ANY(")
+                .push_bind_unseparated(self.project_paths)
+                .push_unseparated(")))");
+        } else {
+            match self.scope {
+                Some(Scope::Project) => {
+                    conditions
+                        .push("scope = 'project' AND project_path = ANY(")
+                        .push_bind_unseparated(self.project_paths)
+                        .push_unseparated(")");
+                }
+                Some(Scope::Global) => {
+                    conditions.push("scope = 'global'");
+                }
+                None => {}
+            }
+        }
+
+        if let Some(as_of) = self.as_of {
+            // Active at that time: created by then and not yet superseded
+            conditions
+                .push("created_at <= ")
+                .push_bind_unseparated(as_of)
+                .push_unseparated(" AND (superseded_at IS NULL OR superseded_at > ")
+                .push_bind_unseparated(as_of)
+                .push_unseparated(")");
+        } else {
+            conditions.push("is_active = true");
+            if !self.include_archived {
+                conditions.push("archived_at IS NULL");
+            }
+        }
+
+        if let Some(memory_type) = self.memory_type {
+            conditions.push("type = ").push_bind_unseparated(memory_type.as_str());
+        }
+        if let Some(keyword) = self.keyword {
+            let pattern = format!("%{}%", keyword);
+            conditions
+                .push("(content ILIKE ")
+                .push_bind_unseparated(pattern.clone())
+                .push_unseparated(" OR EXISTS (SELECT 1 FROM unnest(tags) AS t WHERE t ILIKE ")
+                .push_bind_unseparated(pattern)
+                .push_unseparated("))");
+        }
+        if let Some(tag) = self.tag {
+            conditions
+                .push("EXISTS (SELECT 1 FROM unnest(tags) AS t WHERE lower(t) = lower(")
+                .push_bind_unseparated(tag)
+                .push_unseparated("))");
+        }
+        if let Some(since) = self.created_since {
+            conditions.push("created_at >= ").push_bind_unseparated(since);
+        }
+        if let Some(until) = self.created_until {
+            conditions.push("created_at <= ").push_bind_unseparated(until);
+        }
+        if let Some(confidence) = self.confidence {
+            conditions.push("confidence = ").push_bind_unseparated(confidence.as_str());
+        }
+    }
+
+    /// One page of the matching memories ordered by `ranker`, each with the
+    /// cursor of the page ending on it. The page starts `offset` rows in, or
+    /// after the row of `after`.
+    async fn fetch_page(
+        &self,
+        pool: &PgPool,
+        ranker: &dyn Ranker,
+        limit: i64,
+        offset: i64,
+        after: Option<&Cursor>,
+    ) -> Result<Vec<(Memory, Cursor)>> {
+        let after = cursor_in(after, ranker.name())?;
+        let at = after.map_or_else(Utc::now, |cursor| cursor.at);
+
+        let mut query = QueryBuilder::new(format!("SELECT {}, ", MEMORY_COLUMNS));
+        ranker.push_sort_key(&mut query, at);
+        query.push(" AS sort_key");
+        self.push_from(&mut query);
+        self.push_where(&mut query);
+        if let Some(cursor) = after {
+            query.push(" AND ");
+            ranker.push_after(&mut query, cursor);
+        }
+        query.push(" ORDER BY ");
+        ranker.push_order_by(&mut query, at);
+        query
+            .push(" LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+
+        let rows: Vec<KeyedMemory> = query.build_query_as().fetch_all(pool).await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let cursor = Cursor {
+                    order: ranker.name().to_string(),
+                    key: row.sort_key,
+                    id: row.memory.id,
+                    at,
+                };
+                (row.memory, cursor)
+            })
+            .collect())
     }
 }
 
-/// Search memories by keyword (content or tags)
+/// `after`, unless it is the cursor of a page in another order
+fn cursor_in<'a>(after: Option<&'a Cursor>, order: &str) -> Result<Option<&'a Cursor>> {
+    match after {
+        Some(cursor) if cursor.order != order => Err(HippocampusError::Validation(format!(
+            "Invalid cursor: it is from a page in {} order, not {}",
+            cursor.order, order
+        ))),
+        _ => Ok(after),
+    }
+}
+
+/// A memory with the values of its sort keys
+#[derive(sqlx::FromRow)]
+struct KeyedMemory {
+    #[sqlx(flatten)]
+    memory: Memory,
+    sort_key: Vec<String>,
+}
+
+/// Search memories by keyword (content or tags), ordered by `ranker`, each
+/// with the cursor of the page ending on it
+///
+/// With `as_of`, searches the memories that were active at that time.
+#[allow(clippy::too_many_arguments)]
 pub async fn search_keyword(
     pool: &PgPool,
     query: &str,
     scope_filter: Option<Scope>,
-    project_path: Option<&str>,
+    project_paths: &[String],
     include_both_scopes: bool,
     limit: i32,
-) -> Result<Vec<Memory>> {
-    let query_pattern = format!("%{}%", query);
+    offset: i64,
+    after: Option<&Cursor>,
+    as_of: Option<DateTime<Utc>>,
+    include_archived: bool,
+    filters: &SearchFilters,
+    ranker: &dyn Ranker,
+) -> Result<Vec<(Memory, Cursor)>> {
+    let filter = MemoryFilter {
+        as_of,
+        include_archived,
+        keyword: Some(query),
+        ..MemoryFilter::new(scope_filter, include_both_scopes, project_paths)
+    }
+    .narrowed(filters);
 
-    // Build the WHERE clause based on scope filter
-    let rows = if include_both_scopes {
-        // Search both global and project (with matching path)
-        sqlx::query(
-            r#"
-            SELECT id, type, scope, project_path, content, tags, confidence,
-                   source_session_id, source_turn_id, created_at, updated_at,
-                   accessed_at, access_count, superseded_by, superseded_at, is_active
-            FROM memories
-            WHERE is_active = true
-              AND (scope = 'global' OR (scope = 'project' AND project_path = $3))
-              AND (content ILIKE $1 OR EXISTS (SELECT 1 FROM unnest(tags) AS t WHERE t ILIKE $1))
-            ORDER BY
-              CASE confidence WHEN 'high' THEN 0 WHEN 'medium' THEN 1 ELSE 2 END,
-              created_at DESC
-            LIMIT $2
-            "#,
-        )
-        .bind(&query_pattern)
-        .bind(limit as i64)
-        .bind(project_path)
-        .fetch_all(pool)
-        .await?
-    } else if let Some(scope) = scope_filter {
-        // Search specific scope
-        if scope == Scope::Project {
-            sqlx::query(
-                r#"
-                SELECT id, type, scope, project_path, content, tags, confidence,
-                       source_session_id, source_turn_id, created_at, updated_at,
-                       accessed_at, access_count, superseded_by, superseded_at, is_active
-                FROM memories
-                WHERE is_active = true
-                  AND scope = 'project' AND project_path = $3
-                  AND (content ILIKE $1 OR EXISTS (SELECT 1 FROM unnest(tags) AS t WHERE t ILIKE $1))
-                ORDER BY
-                  CASE confidence WHEN 'high' THEN 0 WHEN 'medium' THEN 1 ELSE 2 END,
-                  created_at DESC
-                LIMIT $2
-                "#,
-            )
-            .bind(&query_pattern)
-            .bind(limit as i64)
-            .bind(project_path)
-            .fetch_all(pool)
-            .await?
-        } else {
-            sqlx::query(
-                r#"
-                SELECT id, type, scope, project_path, content, tags, confidence,
-                       source_session_id, source_turn_id, created_at, updated_at,
-                       accessed_at, access_count, superseded_by, superseded_at, is_active
-                FROM memories
-                WHERE is_active = true
-                  AND scope = 'global'
-                  AND (content ILIKE $1 OR EXISTS (SELECT 1 FROM unnest(tags) AS t WHERE t ILIKE $1))
-                ORDER BY
-                  CASE confidence WHEN 'high' THEN 0 WHEN 'medium' THEN 1 ELSE 2 END,
-                  created_at DESC
-                LIMIT $2
-                "#,
-            )
-            .bind(&query_pattern)
-            .bind(limit as i64)
-            .fetch_all(pool)
-            .await?
-        }
-    } else {
-        // No filter, search all
-        sqlx::query(
-            r#"
-            SELECT id, type, scope, project_path, content, tags, confidence,
-                   source_session_id, source_turn_id, created_at, updated_at,
-                   accessed_at, access_count, superseded_by, superseded_at, is_active
-            FROM memories
-            WHERE is_active = true
-              AND (content ILIKE $1 OR EXISTS (SELECT 1 FROM unnest(tags) AS t WHERE t ILIKE $1))
-            ORDER BY
-              CASE confidence WHEN 'high' THEN 0 WHEN 'medium' THEN 1 ELSE 2 END,
-              created_at DESC
-            LIMIT $2
-            "#,
-        )
-        .bind(&query_pattern)
-        .bind(limit as i64)
-        .fetch_all(pool)
-        .await?
+    filter.fetch_page(pool, ranker, limit as i64, offset, after).await
+}
+
+/// One page of active memories, newest first, with the total matching count.
+///
+/// Optionally narrowed to a type and to memories carrying `tag` (ignoring case).
+#[allow(clippy::too_many_arguments)]
+pub async fn list_memories_page(
+    pool: &PgPool,
+    memory_type: Option<MemoryType>,
+    tag: Option<&str>,
+    scope_filter: Option<Scope>,
+    project_paths: &[String],
+    include_both_scopes: bool,
+    offset: i64,
+    limit: i64,
+) -> Result<(Vec<Memory>, i64)> {
+    let filter = MemoryFilter {
+        memory_type,
+        tag,
+        ..MemoryFilter::new(scope_filter, include_both_scopes, project_paths)
     };
 
-    rows.iter().map(row_to_memory).collect()
+    let mut count_query = QueryBuilder::new("SELECT COUNT(*)");
+    filter.push_from(&mut count_query);
+    filter.push_where(&mut count_query);
+    let total = count_query.build_query_scalar().fetch_one(pool).await?;
+
+    let page = filter.fetch_page(pool, &Recency, limit, offset, None).await?;
+
+    Ok((page.into_iter().map(|(memory, _)| memory).collect(), total))
 }
 
-/// Search memories by type (with optional keyword filter)
+/// Full-text search over content and tags, ranked by `ts_rank`.
+///
+/// Requires the `search_vector` column (schema v6). The query accepts web
+/// search syntax (`"quoted phrase"`, `-excluded`, `or`). Stemmed (english)
+/// and unstemmed (simple) matches both count, so tags match verbatim.
+/// With `as_of`, searches the memories that were active at that time. A
+/// page starts `offset` rows in, or after the row of `after`, whose key is
+/// its rank.
+#[allow(clippy::too_many_arguments)]
+pub async fn search_fts(
+    pool: &PgPool,
+    query: &str,
+    scope_filter: Option<Scope>,
+    project_paths: &[String],
+    include_both_scopes: bool,
+    limit: i32,
+    offset: i64,
+    after: Option<&Cursor>,
+    as_of: Option<DateTime<Utc>>,
+    include_archived: bool,
+    filters: &SearchFilters,
+) -> Result<Vec<(Memory, f32)>> {
+    let filter = MemoryFilter {
+        as_of,
+        include_archived,
+        ..MemoryFilter::new(scope_filter, include_both_scopes, project_paths)
+    }
+    .narrowed(filters);
+
+    let after = cursor_in(after, FTS_ORDER)?.map(|cursor| (cursor.key.concat(), cursor.id));
+
+    let mut sql_query = QueryBuilder::new(format!("SELECT {}, {} AS rank", MEMORY_COLUMNS, FTS_RANK));
+    filter.push_from(&mut sql_query);
+    sql_query
+        .push(", websearch_to_tsquery('english', ")
+        .push_bind(query)
+        .push(") AS english, websearch_to_tsquery('simple', ")
+        .push_bind(query)
+        .push(") AS simple");
+    filter.push_where(&mut sql_query);
+    sql_query.push(" AND (search_vector @@ english OR search_vector @@ simple)");
+    if let Some((rank, id)) = after {
+        sql_query
+            .push(format!(" AND ({}, id) < (", FTS_RANK))
+            .push_bind(rank)
+            .push("::real, ")
+            .push_bind(id)
+            .push(")");
+    }
+    sql_query
+        .push(" ORDER BY rank DESC, id DESC LIMIT ")
+        .push_bind(limit as i64)
+        .push(" OFFSET ")
+        .push_bind(offset);
+    let rows: Vec<RankedMemory> = sql_query.build_query_as().fetch_all(pool).await?;
+
+    Ok(rows.into_iter().map(|r| (r.memory, r.rank)).collect())
+}
+
+/// Order of full-text search cursors, whose key is the rank
+pub const FTS_ORDER: &str = "rank";
+
+/// Full-text search rank, over the `english` and `simple` queries
+const FTS_RANK: &str = "(ts_rank(search_vector, english) + ts_rank(search_vector, simple))::real";
+
+/// A memory with its full-text search rank
+#[derive(sqlx::FromRow)]
+struct RankedMemory {
+    #[sqlx(flatten)]
+    memory: Memory,
+    rank: f32,
+}
+
+/// Search memories by type (with optional keyword filter), ordered by `ranker`
+#[allow(clippy::too_many_arguments)]
 pub async fn search_by_type(
     pool: &PgPool,
     memory_type: MemoryType,
     query: Option<&str>,
     scope_filter: Option<Scope>,
-    project_path: Option<&str>,
+    project_paths: &[String],
     include_both_scopes: bool,
     limit: i32,
+    offset: i64,
+    after: Option<&Cursor>,
+    include_archived: bool,
+    filters: &SearchFilters,
+    ranker: &dyn Ranker,
+) -> Result<Vec<(Memory, Cursor)>> {
+    let filter = MemoryFilter {
+        include_archived,
+        memory_type: Some(memory_type),
+        keyword: query,
+        ..MemoryFilter::new(scope_filter, include_both_scopes, project_paths)
+    }
+    .narrowed(filters);
+
+    filter.fetch_page(pool, ranker, limit as i64, offset, after).await
+}
+
+/// Get memories for the context block, ordered by `ranker`; archived and
+/// pinned ones are left out (see [`get_pinned_memories`])
+pub async fn get_context_memories(
+    pool: &PgPool,
+    project_paths: &[String],
+    limit: i32,
+    ranker: &dyn Ranker,
+    since: Option<DateTime<Utc>>,
+    exclude: &[Uuid],
 ) -> Result<Vec<Memory>> {
-    let query_pattern = query.map(|q| format!("%{}%", q));
+    let mut query = QueryBuilder::new(format!(
+        "SELECT {} FROM memories WHERE is_active = true AND archived_at IS NULL AND NOT pinned",
+        MEMORY_COLUMNS
+    ));
+    query
+        .push(" AND (scope = 'global' OR (scope = 'project' AND project_path = ANY(")
+        .push_bind(project_paths)
+        .push(")))");
+    if let Some(since) = since {
+        query.push(" AND updated_at > ").push_bind(since);
+    }
+    query.push(" AND NOT (id = ANY(").push_bind(exclude).push(")) ORDER BY ");
+    ranker.push_order_by(&mut query, Utc::now());
+    query.push(" LIMIT ").push_bind(limit as i64);
+    let memories = query.build_query_as().fetch_all(pool).await?;
 
-    // Build the WHERE clause based on scope filter and optional query
-    let rows = match (include_both_scopes, scope_filter, &query_pattern) {
-        // Both scopes, with keyword
-        (true, _, Some(pattern)) => {
-            sqlx::query(
-                r#"
-                SELECT id, type, scope, project_path, content, tags, confidence,
-                       source_session_id, source_turn_id, created_at, updated_at,
-                       accessed_at, access_count, superseded_by, superseded_at, is_active
-                FROM memories
-                WHERE is_active = true
-                  AND type = $1
-                  AND (scope = 'global' OR (scope = 'project' AND project_path = $4))
-                  AND (content ILIKE $2 OR EXISTS (SELECT 1 FROM unnest(tags) AS t WHERE t ILIKE $2))
-                ORDER BY
-                  CASE confidence WHEN 'high' THEN 0 WHEN 'medium' THEN 1 ELSE 2 END,
-                  created_at DESC
-                LIMIT $3
-                "#,
-            )
-            .bind(memory_type.as_str())
-            .bind(pattern)
-            .bind(limit as i64)
-            .bind(project_path)
-            .fetch_all(pool)
-            .await?
-        }
-        // Both scopes, no keyword
-        (true, _, None) => {
-            sqlx::query(
-                r#"
-                SELECT id, type, scope, project_path, content, tags, confidence,
-                       source_session_id, source_turn_id, created_at, updated_at,
-                       accessed_at, access_count, superseded_by, superseded_at, is_active
-                FROM memories
-                WHERE is_active = true
-                  AND type = $1
-                  AND (scope = 'global' OR (scope = 'project' AND project_path = $3))
-                ORDER BY
-                  CASE confidence WHEN 'high' THEN 0 WHEN 'medium' THEN 1 ELSE 2 END,
-                  created_at DESC
-                LIMIT $2
-                "#,
-            )
-            .bind(memory_type.as_str())
-            .bind(limit as i64)
-            .bind(project_path)
-            .fetch_all(pool)
-            .await?
-        }
-        // Project scope, with keyword
-        (false, Some(Scope::Project), Some(pattern)) => {
-            sqlx::query(
-                r#"
-                SELECT id, type, scope, project_path, content, tags, confidence,
-                       source_session_id, source_turn_id, created_at, updated_at,
-                       accessed_at, access_count, superseded_by, superseded_at, is_active
-                FROM memories
-                WHERE is_active = true
-                  AND type = $1
-                  AND scope = 'project' AND project_path = $4
-                  AND (content ILIKE $2 OR EXISTS (SELECT 1 FROM unnest(tags) AS t WHERE t ILIKE $2))
-                ORDER BY
-                  CASE confidence WHEN 'high' THEN 0 WHEN 'medium' THEN 1 ELSE 2 END,
-                  created_at DESC
-                LIMIT $3
-                "#,
-            )
-            .bind(memory_type.as_str())
-            .bind(pattern)
-            .bind(limit as i64)
-            .bind(project_path)
-            .fetch_all(pool)
-            .await?
-        }
-        // Project scope, no keyword
-        (false, Some(Scope::Project), None) => {
-            sqlx::query(
-                r#"
-                SELECT id, type, scope, project_path, content, tags, confidence,
-                       source_session_id, source_turn_id, created_at, updated_at,
-                       accessed_at, access_count, superseded_by, superseded_at, is_active
-                FROM memories
-                WHERE is_active = true
-                  AND type = $1
-                  AND scope = 'project' AND project_path = $3
-                ORDER BY
-                  CASE confidence WHEN 'high' THEN 0 WHEN 'medium' THEN 1 ELSE 2 END,
-                  created_at DESC
-                LIMIT $2
-                "#,
-            )
-            .bind(memory_type.as_str())
-            .bind(limit as i64)
-            .bind(project_path)
-            .fetch_all(pool)
-            .await?
-        }
-        // Global scope, with keyword
-        (false, Some(Scope::Global), Some(pattern)) => {
-            sqlx::query(
-                r#"
-                SELECT id, type, scope, project_path, content, tags, confidence,
-                       source_session_id, source_turn_id, created_at, updated_at,
-                       accessed_at, access_count, superseded_by, superseded_at, is_active
-                FROM memories
-                WHERE is_active = true
-                  AND type = $1
-                  AND scope = 'global'
-                  AND (content ILIKE $2 OR EXISTS (SELECT 1 FROM unnest(tags) AS t WHERE t ILIKE $2))
-                ORDER BY
-                  CASE confidence WHEN 'high' THEN 0 WHEN 'medium' THEN 1 ELSE 2 END,
-                  created_at DESC
-                LIMIT $3
-                "#,
-            )
-            .bind(memory_type.as_str())
-            .bind(pattern)
-            .bind(limit as i64)
-            .fetch_all(pool)
-            .await?
-        }
-        // Global scope, no keyword
-        (false, Some(Scope::Global), None) => {
-            sqlx::query(
-                r#"
-                SELECT id, type, scope, project_path, content, tags, confidence,
-                       source_session_id, source_turn_id, created_at, updated_at,
-                       accessed_at, access_count, superseded_by, superseded_at, is_active
-                FROM memories
-                WHERE is_active = true
-                  AND type = $1
-                  AND scope = 'global'
-                ORDER BY
-                  CASE confidence WHEN 'high' THEN 0 WHEN 'medium' THEN 1 ELSE 2 END,
-                  created_at DESC
-                LIMIT $2
-                "#,
-            )
-            .bind(memory_type.as_str())
-            .bind(limit as i64)
-            .fetch_all(pool)
-            .await?
-        }
-        // No scope filter, with keyword
-        (false, None, Some(pattern)) => {
-            sqlx::query(
-                r#"
-                SELECT id, type, scope, project_path, content, tags, confidence,
-                       source_session_id, source_turn_id, created_at, updated_at,
-                       accessed_at, access_count, superseded_by, superseded_at, is_active
-                FROM memories
-                WHERE is_active = true
-                  AND type = $1
-                  AND (content ILIKE $2 OR EXISTS (SELECT 1 FROM unnest(tags) AS t WHERE t ILIKE $2))
-                ORDER BY
-                  CASE confidence WHEN 'high' THEN 0 WHEN 'medium' THEN 1 ELSE 2 END,
-                  created_at DESC
-                LIMIT $3
-                "#,
-            )
-            .bind(memory_type.as_str())
-            .bind(pattern)
-            .bind(limit as i64)
-            .fetch_all(pool)
-            .await?
-        }
-        // No scope filter, no keyword
-        (false, None, None) => {
-            sqlx::query(
-                r#"
-                SELECT id, type, scope, project_path, content, tags, confidence,
-                       source_session_id, source_turn_id, created_at, updated_at,
-                       accessed_at, access_count, superseded_by, superseded_at, is_active
-                FROM memories
-                WHERE is_active = true
-                  AND type = $1
-                ORDER BY
-                  CASE confidence WHEN 'high' THEN 0 WHEN 'medium' THEN 1 ELSE 2 END,
-                  created_at DESC
-                LIMIT $2
-                "#,
-            )
-            .bind(memory_type.as_str())
-            .bind(limit as i64)
-            .fetch_all(pool)
-            .await?
-        }
-    };
+    Ok(memories)
+}
+
+/// Get the active pinned memories in scope, oldest pin first
+pub async fn get_pinned_memories(
+    pool: &PgPool,
+    project_paths: &[String],
+    exclude: &[Uuid],
+) -> Result<Vec<Memory>> {
+    let memories = sqlx::query_as!(
+        Memory,
+        r#"
+        SELECT id, type AS "memory_type: MemoryType", scope AS "scope: Scope", project_path, content,
+               tags AS "tags!", confidence AS "confidence!: Confidence", source_session_id, source_turn_id,
+               created_at AS "created_at!", updated_at AS "updated_at!", accessed_at, access_count AS "access_count!",
+               superseded_by, superseded_at, is_active AS "is_active!",
+               NULLIF(metadata, '{}') AS metadata, summary, archived_at, pinned
+        FROM memories
+        WHERE is_active = true
+          AND pinned
+          AND (scope = 'global' OR (scope = 'project' AND project_path = ANY($1)))
+          AND NOT (id = ANY($2))
+        ORDER BY pinned_at, created_at
+        "#,
+        project_paths,
+        exclude,
+    )
+    .fetch_all(pool)
+    .await?;
 
-    rows.iter().map(row_to_memory).collect()
+    Ok(memories)
 }
 
-/// Get memories for context (high priority, recent)
-pub async fn get_context_memories(
+/// Pin or unpin a memory. Returns None when no memory has the ID, else
+/// whether its flag changed.
+pub async fn set_memory_pinned(pool: &PgPool, id: Uuid, pinned: bool) -> Result<Option<bool>> {
+    let previous = sqlx::query_scalar!(
+        r#"
+        UPDATE memories m
+        SET pinned = $2, pinned_at = CASE WHEN $2 THEN COALESCE(old.pinned_at, NOW()) END
+        FROM memories old
+        WHERE m.id = $1 AND old.id = m.id
+        RETURNING old.pinned
+        "#,
+        id,
+        pinned,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(previous.map(|was| was != pinned))
+}
+
+/// Get active project memories of the given types
+pub async fn list_project_memories_by_types(
     pool: &PgPool,
-    project_path: Option<&str>,
-    limit: i32,
+    project_path: &str,
+    memory_types: &[MemoryType],
 ) -> Result<Vec<Memory>> {
-    let rows = sqlx::query(
+    let types: Vec<String> = memory_types.iter().map(|t| t.as_str().to_string()).collect();
+
+    let memories = sqlx::query_as!(
+        Memory,
         r#"
-        SELECT id, type, scope, project_path, content, tags, confidence,
-               source_session_id, source_turn_id, created_at, updated_at,
-               accessed_at, access_count, superseded_by, superseded_at, is_active
+        SELECT id, type AS "memory_type: MemoryType", scope AS "scope: Scope", project_path, content,
+               tags AS "tags!", confidence AS "confidence!: Confidence", source_session_id, source_turn_id,
+               created_at AS "created_at!", updated_at AS "updated_at!", accessed_at, access_count AS "access_count!",
+               superseded_by, superseded_at, is_active AS "is_active!",
+               NULLIF(metadata, '{}') AS metadata, summary, archived_at, pinned
         FROM memories
         WHERE is_active = true
-          AND (scope = 'global' OR (scope = 'project' AND project_path = $2))
-        ORDER BY
-          created_at DESC,
-          CASE confidence WHEN 'high' THEN 0 WHEN 'medium' THEN 1 ELSE 2 END
-        LIMIT $1
+          AND scope = 'project'
+          AND project_path = $1
+          AND type = ANY($2)
+        ORDER BY created_at DESC
         "#,
+        project_path,
+        &types,
     )
-    .bind(limit as i64)
-    .bind(project_path)
     .fetch_all(pool)
     .await?;
 
-    rows.iter().map(row_to_memory).collect()
+    Ok(memories)
 }
 
 /// Memory statistics
@@ -505,6 +926,13 @@ pub struct MemoryStats {
     pub by_type: TypeCounts,
     pub by_confidence: ConfidenceCounts,
     pub by_scope: ScopeCounts,
+    /// Project memories per project, most first
+    pub by_project: Vec<ProjectCount>,
+    /// Memories per tag, most first
+    pub by_tag: Vec<TagCount>,
+    /// Accesses of these memories over the last 30 days, as of the last
+    /// access rollup
+    pub accesses_last_30_days: i64,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -530,379 +958,693 @@ pub struct ScopeCounts {
     pub global: i64,
 }
 
-/// Get memory statistics
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ProjectCount {
+    pub project_path: String,
+    pub count: i64,
+}
+
+/// Get memory statistics.
+///
+/// With `scope_filter` unset, counts global memories and project memories
+/// of `project_paths`; otherwise only memories of that scope.
 pub async fn get_stats(
     pool: &PgPool,
     scope_filter: Option<Scope>,
-    project_path: Option<&str>,
-    include_both_scopes: bool,
+    project_paths: &[String],
 ) -> Result<MemoryStats> {
-    // Build WHERE clause based on scope filter
-    let where_clause = if include_both_scopes {
-        format!(
-            "WHERE (scope = 'global' OR (scope = 'project' AND project_path = '{}'))",
-            project_path.unwrap_or("")
-        )
-    } else if let Some(scope) = scope_filter {
-        if scope == Scope::Project {
-            format!(
-                "WHERE scope = 'project' AND project_path = '{}'",
-                project_path.unwrap_or("")
-            )
-        } else {
-            "WHERE scope = 'global'".to_string()
-        }
-    } else {
-        String::new()
-    };
-
-    // Get total count
-    let total: i64 = sqlx::query_scalar(&format!(
-        "SELECT COUNT(*) FROM memories {}",
-        where_clause
-    ))
-    .fetch_one(pool)
-    .await?;
+    let scope = scope_filter.map(|s| s.as_str());
 
-    // Get counts by type
-    let type_rows = sqlx::query(&format!(
-        "SELECT type, COUNT(*) as count FROM memories {} GROUP BY type",
-        where_clause
-    ))
+    // One pass over the filtered memories, one row per (dimension, key)
+    let rows = sqlx::query!(
+        r#"
+        WITH scoped AS (
+          SELECT id, type, confidence, scope, project_path, tags
+          FROM memories
+          WHERE ($1::text IS NULL OR scope = $1)
+            AND (scope = 'global' OR project_path = ANY($2))
+        )
+        SELECT 'total' AS "dimension!", '' AS "key!", COUNT(*) AS "count!" FROM scoped
+        UNION ALL
+        SELECT 'type', type, COUNT(*) FROM scoped GROUP BY type
+        UNION ALL
+        SELECT 'confidence', confidence, COUNT(*) FROM scoped GROUP BY confidence
+        UNION ALL
+        SELECT 'scope', scope, COUNT(*) FROM scoped GROUP BY scope
+        UNION ALL
+        SELECT 'project', project_path, COUNT(*)
+        FROM scoped WHERE scope = 'project' AND project_path IS NOT NULL
+        GROUP BY project_path
+        UNION ALL
+        SELECT 'tag', tag, COUNT(*) FROM scoped, unnest(tags) AS tag GROUP BY tag
+        "#,
+        scope,
+        project_paths,
+    )
     .fetch_all(pool)
     .await?;
 
-    let mut by_type = TypeCounts {
-        convention: 0,
-        architecture: 0,
-        gotcha: 0,
-        api: 0,
-        learning: 0,
-        preference: 0,
+    let mut stats = MemoryStats {
+        total: 0,
+        by_type: TypeCounts {
+            convention: 0,
+            architecture: 0,
+            gotcha: 0,
+            api: 0,
+            learning: 0,
+            preference: 0,
+        },
+        by_confidence: ConfidenceCounts {
+            high: 0,
+            medium: 0,
+            low: 0,
+        },
+        by_scope: ScopeCounts {
+            project: 0,
+            global: 0,
+        },
+        by_project: Vec::new(),
+        by_tag: Vec::new(),
+        accesses_last_30_days: 0,
     };
 
-    for row in &type_rows {
-        let type_str: String = row.get("type");
-        let count: i64 = row.get("count");
-        match type_str.as_str() {
-            "convention" => by_type.convention = count,
-            "architecture" => by_type.architecture = count,
-            "gotcha" => by_type.gotcha = count,
-            "api" => by_type.api = count,
-            "learning" => by_type.learning = count,
-            "preference" => by_type.preference = count,
+    for row in rows {
+        let count = row.count;
+        match (row.dimension.as_str(), row.key.as_str()) {
+            ("total", _) => stats.total = count,
+            ("type", "convention") => stats.by_type.convention = count,
+            ("type", "architecture") => stats.by_type.architecture = count,
+            ("type", "gotcha") => stats.by_type.gotcha = count,
+            ("type", "api") => stats.by_type.api = count,
+            ("type", "learning") => stats.by_type.learning = count,
+            ("type", "preference") => stats.by_type.preference = count,
+            ("confidence", "high") => stats.by_confidence.high = count,
+            ("confidence", "medium") => stats.by_confidence.medium = count,
+            ("confidence", "low") => stats.by_confidence.low = count,
+            ("scope", "project") => stats.by_scope.project = count,
+            ("scope", "global") => stats.by_scope.global = count,
+            ("project", _) => stats.by_project.push(ProjectCount {
+                project_path: row.key,
+                count,
+            }),
+            ("tag", _) => stats.by_tag.push(TagCount { tag: row.key, count }),
             _ => {}
         }
     }
+    stats
+        .by_project
+        .sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.project_path.cmp(&b.project_path)));
+    stats.by_tag.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
 
-    // Get counts by confidence
-    let conf_rows = sqlx::query(&format!(
-        "SELECT confidence, COUNT(*) as count FROM memories {} GROUP BY confidence",
-        where_clause
-    ))
-    .fetch_all(pool)
+    // Read from the daily rollup rather than scanning access events
+    stats.accesses_last_30_days = sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(SUM(accesses), 0)::bigint AS "accesses!"
+        FROM memory_access_daily
+        WHERE day > (NOW() AT TIME ZONE 'UTC')::date - 30
+          AND memory_id IN (
+            SELECT id FROM memories
+            WHERE ($1::text IS NULL OR scope = $1)
+              AND (scope = 'global' OR project_path = ANY($2))
+          )
+        "#,
+        scope,
+        project_paths,
+    )
+    .fetch_one(pool)
     .await?;
 
-    let mut by_confidence = ConfidenceCounts {
-        high: 0,
-        medium: 0,
-        low: 0,
-    };
+    Ok(stats)
+}
 
-    for row in &conf_rows {
-        let conf_str: String = row.get("confidence");
-        let count: i64 = row.get("count");
-        match conf_str.as_str() {
-            "high" => by_confidence.high = count,
-            "medium" => by_confidence.medium = count,
-            "low" => by_confidence.low = count,
-            _ => {}
-        }
+/// Update access tracking for memories (accessed_at, access_count).
+///
+/// This only appends to `memory_access_events`, so hot memories are not
+/// locked on every read; [`rollup_memory_access`] applies the events to the
+/// counters later.
+pub async fn mark_memories_accessed(pool: &PgPool, ids: &[Uuid]) -> Result<u64> {
+    if ids.is_empty() {
+        return Ok(0);
     }
 
-    // Get counts by scope
-    let scope_rows = sqlx::query(&format!(
-        "SELECT scope, COUNT(*) as count FROM memories {} GROUP BY scope",
-        where_clause
-    ))
-    .fetch_all(pool)
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO memory_access_events (memory_id)
+        SELECT id FROM memories WHERE id = ANY($1)
+        "#,
+        ids,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Outcome of an access rollup
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessRollup {
+    /// Access events folded in
+    pub events: i64,
+    /// Memories whose counters were updated
+    pub memories: i64,
+}
+
+/// Fold pending access events into `memories.accessed_at`/`access_count`,
+/// the per-memory `memory_access_daily` counts and the `memory_access_stats`
+/// day totals, in one statement.
+pub async fn rollup_memory_access(pool: &PgPool) -> Result<AccessRollup> {
+    let rollup = sqlx::query_as!(
+        AccessRollup,
+        r#"
+        WITH drained AS (
+            DELETE FROM memory_access_events
+            RETURNING memory_id, accessed_at
+        ), per_day AS (
+            SELECT (accessed_at AT TIME ZONE 'UTC')::date AS day, memory_id,
+                   COUNT(*) AS n, MAX(accessed_at) AS last
+            FROM drained
+            WHERE memory_id IN (SELECT id FROM memories)
+            GROUP BY 1, 2
+        ), daily AS (
+            INSERT INTO memory_access_daily (day, memory_id, accesses)
+            SELECT day, memory_id, n FROM per_day
+            ON CONFLICT (day, memory_id)
+            DO UPDATE SET accesses = memory_access_daily.accesses + EXCLUDED.accesses
+        ), totals AS (
+            INSERT INTO memory_access_stats (day, accesses)
+            SELECT day, SUM(n) FROM per_day GROUP BY day
+            ON CONFLICT (day)
+            DO UPDATE SET accesses = memory_access_stats.accesses + EXCLUDED.accesses
+        ), bumped AS (
+            UPDATE memories m
+            SET access_count = m.access_count + a.n::int,
+                accessed_at = GREATEST(m.accessed_at, a.last)
+            FROM (
+                SELECT memory_id, SUM(n) AS n, MAX(last) AS last
+                FROM per_day
+                GROUP BY memory_id
+            ) a
+            WHERE m.id = a.memory_id
+            RETURNING m.id
+        )
+        SELECT (SELECT COUNT(*) FROM drained) AS "events!",
+               (SELECT COUNT(*) FROM bumped) AS "memories!"
+        "#,
+    )
+    .fetch_one(pool)
     .await?;
 
-    let mut by_scope = ScopeCounts {
-        project: 0,
-        global: 0,
+    Ok(rollup)
+}
+
+/// Most accessed active memories over the last `days` UTC days (including
+/// today), with their access counts, from the rolled-up daily counts.
+///
+/// `scope_filter` None means both scopes.
+pub async fn top_accessed_memories(
+    pool: &PgPool,
+    scope_filter: Option<Scope>,
+    project_paths: &[String],
+    days: i32,
+    limit: i64,
+) -> Result<Vec<(Memory, i64)>> {
+    let scope_clause = match scope_filter {
+        Some(Scope::Project) => "scope = 'project' AND project_path = ANY($1)",
+        Some(Scope::Global) => "scope = 'global'",
+        None => "(scope = 'global' OR (scope = 'project' AND project_path = ANY($1)))",
     };
+    let sql = format!(
+        r#"
+        SELECT id, type, scope, project_path, content, tags, confidence,
+               source_session_id, source_turn_id, created_at, updated_at,
+               accessed_at, access_count, superseded_by, superseded_at, is_active,
+               NULLIF(metadata, '{{}}') AS metadata, summary, archived_at, pinned,
+               a.accesses
+        FROM (
+            SELECT memory_id, SUM(accesses)::bigint AS accesses
+            FROM memory_access_daily
+            WHERE day > (NOW() AT TIME ZONE 'UTC')::date - $2
+            GROUP BY memory_id
+        ) a
+        JOIN memories ON id = a.memory_id
+        WHERE is_active = true
+          AND {}
+        ORDER BY a.accesses DESC, accessed_at DESC NULLS LAST
+        LIMIT $3
+        "#,
+        scope_clause
+    );
+    let rows = sqlx::query_as::<_, AccessedMemory>(&sql)
+        .bind(project_paths)
+        .bind(days)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
 
-    for row in &scope_rows {
-        let scope_str: String = row.get("scope");
-        let count: i64 = row.get("count");
-        match scope_str.as_str() {
-            "project" => by_scope.project = count,
-            "global" => by_scope.global = count,
-            _ => {}
-        }
-    }
+    Ok(rows.into_iter().map(|r| (r.memory, r.accesses)).collect())
+}
 
-    Ok(MemoryStats {
-        total,
-        by_type,
-        by_confidence,
-        by_scope,
-    })
+/// A memory with its access count over a period
+#[derive(sqlx::FromRow)]
+struct AccessedMemory {
+    #[sqlx(flatten)]
+    memory: Memory,
+    accesses: i64,
 }
 
-/// Update access tracking for memories (accessed_at, access_count)
-pub async fn mark_memories_accessed(pool: &PgPool, ids: &[Uuid]) -> Result<u64> {
-    if ids.is_empty() {
-        return Ok(0);
-    }
+/// Memories created on each of the last `days` complete UTC days, oldest first
+pub async fn daily_memory_creations(pool: &PgPool, days: i32) -> Result<Vec<i64>> {
+    let counts = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(m.id) AS "n!"
+        FROM generate_series(1, $1) AS g(offset_days)
+        CROSS JOIN LATERAL (
+            SELECT (NOW() AT TIME ZONE 'UTC')::date - g.offset_days AS day
+        ) d
+        LEFT JOIN memories m ON (m.created_at AT TIME ZONE 'UTC')::date = d.day
+        GROUP BY d.day
+        ORDER BY d.day
+        "#,
+        days,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(counts)
+}
 
-    let result = sqlx::query(
+/// Memory accesses on each of the last `days` complete UTC days, oldest first
+pub async fn daily_memory_accesses(pool: &PgPool, days: i32) -> Result<Vec<i64>> {
+    let counts = sqlx::query_scalar!(
         r#"
-        UPDATE memories
-        SET accessed_at = NOW(), access_count = access_count + 1
-        WHERE id = ANY($1)
+        SELECT COALESCE(s.accesses, 0)::bigint AS "n!"
+        FROM generate_series(1, $1) AS g(offset_days)
+        CROSS JOIN LATERAL (
+            SELECT (NOW() AT TIME ZONE 'UTC')::date - g.offset_days AS day
+        ) d
+        LEFT JOIN memory_access_stats s ON s.day = d.day
+        ORDER BY d.day
         "#,
+        days,
     )
-    .bind(ids)
-    .execute(pool)
+    .fetch_all(pool)
     .await?;
 
-    Ok(result.rows_affected())
+    Ok(counts)
 }
 
-/// List recent memories
+/// One page of recent active memories, newest first, each with the cursor
+/// of the page ending on it, and the total count
 pub async fn list_recent(
     pool: &PgPool,
     scope_filter: Option<Scope>,
-    project_path: Option<&str>,
+    project_paths: &[String],
     include_both_scopes: bool,
     limit: i32,
-) -> Result<(Vec<Memory>, i64)> {
-    // Get total count (only active memories)
-    let total: i64 = if include_both_scopes {
-        sqlx::query_scalar(
-            r#"
-            SELECT COUNT(*) FROM memories
-            WHERE is_active = true
-              AND (scope = 'global' OR (scope = 'project' AND project_path = $1))
-            "#,
-        )
-        .bind(project_path)
-        .fetch_one(pool)
-        .await?
-    } else if let Some(scope) = scope_filter {
-        if scope == Scope::Project {
-            sqlx::query_scalar(
-                r#"SELECT COUNT(*) FROM memories WHERE is_active = true AND scope = 'project' AND project_path = $1"#,
-            )
-            .bind(project_path)
-            .fetch_one(pool)
-            .await?
-        } else {
-            sqlx::query_scalar(r#"SELECT COUNT(*) FROM memories WHERE is_active = true AND scope = 'global'"#)
-                .fetch_one(pool)
-                .await?
-        }
-    } else {
-        sqlx::query_scalar(r#"SELECT COUNT(*) FROM memories WHERE is_active = true"#)
-            .fetch_one(pool)
-            .await?
+    offset: i64,
+    after: Option<&Cursor>,
+) -> Result<(Vec<(Memory, Cursor)>, i64)> {
+    let filter = MemoryFilter {
+        include_archived: true,
+        ..MemoryFilter::new(scope_filter, include_both_scopes, project_paths)
     };
 
-    // Get recent entries (only active memories)
-    let rows = if include_both_scopes {
-        sqlx::query(
-            r#"
-            SELECT id, type, scope, project_path, content, tags, confidence,
-                   source_session_id, source_turn_id, created_at, updated_at,
-                   accessed_at, access_count, superseded_by, superseded_at, is_active
-            FROM memories
-            WHERE is_active = true
-              AND (scope = 'global' OR (scope = 'project' AND project_path = $2))
-            ORDER BY created_at DESC
-            LIMIT $1
-            "#,
-        )
-        .bind(limit as i64)
-        .bind(project_path)
-        .fetch_all(pool)
-        .await?
-    } else if let Some(scope) = scope_filter {
-        if scope == Scope::Project {
-            sqlx::query(
-                r#"
-                SELECT id, type, scope, project_path, content, tags, confidence,
-                       source_session_id, source_turn_id, created_at, updated_at,
-                       accessed_at, access_count, superseded_by, superseded_at, is_active
-                FROM memories
-                WHERE is_active = true
-                  AND scope = 'project' AND project_path = $2
-                ORDER BY created_at DESC
-                LIMIT $1
-                "#,
-            )
-            .bind(limit as i64)
-            .bind(project_path)
-            .fetch_all(pool)
-            .await?
-        } else {
-            sqlx::query(
-                r#"
-                SELECT id, type, scope, project_path, content, tags, confidence,
-                       source_session_id, source_turn_id, created_at, updated_at,
-                       accessed_at, access_count, superseded_by, superseded_at, is_active
-                FROM memories
-                WHERE is_active = true
-                  AND scope = 'global'
-                ORDER BY created_at DESC
-                LIMIT $1
-                "#,
-            )
-            .bind(limit as i64)
-            .fetch_all(pool)
-            .await?
-        }
-    } else {
-        sqlx::query(
-            r#"
-            SELECT id, type, scope, project_path, content, tags, confidence,
-                   source_session_id, source_turn_id, created_at, updated_at,
-                   accessed_at, access_count, superseded_by, superseded_at, is_active
-            FROM memories
-            WHERE is_active = true
-            ORDER BY created_at DESC
-            LIMIT $1
-            "#,
-        )
-        .bind(limit as i64)
-        .fetch_all(pool)
-        .await?
-    };
+    let mut count_query = QueryBuilder::new("SELECT COUNT(*)");
+    filter.push_from(&mut count_query);
+    filter.push_where(&mut count_query);
+    let total = count_query.build_query_scalar().fetch_one(pool).await?;
+
+    let memories = filter.fetch_page(pool, &Recency, limit as i64, offset, after).await?;
 
-    let memories: Result<Vec<Memory>> = rows.iter().map(row_to_memory).collect();
-    Ok((memories?, total))
+    Ok((memories, total))
 }
 
 /// Find and remove duplicate memories (consolidate)
+///
+/// Pairs match like [`find_duplicate`]: same type and summary, or
+/// (below 1.0) two active memories at `similarity` or above.
 pub async fn consolidate_duplicates(
     pool: &PgPool,
     scope_filter: Option<Scope>,
     project_path: Option<&str>,
+    similarity: f64,
 ) -> Result<Vec<Uuid>> {
-    // Find duplicates (same type, same first 100 chars)
-    let duplicate_rows = if let Some(scope) = scope_filter {
-        if scope == Scope::Project {
-            sqlx::query(
-                r#"
-                SELECT m2.id
-                FROM memories m1
-                JOIN memories m2 ON m1.id < m2.id AND m1.type = m2.type
-                WHERE LOWER(SUBSTRING(m1.content, 1, 100)) = LOWER(SUBSTRING(m2.content, 1, 100))
-                  AND m1.scope = 'project' AND m2.scope = 'project'
-                  AND m1.project_path = $1 AND m2.project_path = $1
-                "#,
-            )
-            .bind(project_path)
-            .fetch_all(pool)
-            .await?
-        } else {
-            sqlx::query(
-                r#"
-                SELECT m2.id
-                FROM memories m1
-                JOIN memories m2 ON m1.id < m2.id AND m1.type = m2.type
-                WHERE LOWER(SUBSTRING(m1.content, 1, 100)) = LOWER(SUBSTRING(m2.content, 1, 100))
-                  AND m1.scope = 'global' AND m2.scope = 'global'
-                "#,
-            )
-            .fetch_all(pool)
-            .await?
-        }
-    } else {
-        sqlx::query(
-            r#"
-            SELECT m2.id
-            FROM memories m1
-            JOIN memories m2 ON m1.id < m2.id AND m1.type = m2.type
-            WHERE LOWER(SUBSTRING(m1.content, 1, 100)) = LOWER(SUBSTRING(m2.content, 1, 100))
-            "#,
-        )
-        .fetch_all(pool)
-        .await?
-    };
+    let pairs = find_duplicate_pairs(pool, scope_filter, project_path, similarity, false).await?;
+    let removals = pick_duplicates(&pairs);
 
-    let duplicate_ids: Vec<Uuid> = duplicate_rows.iter().map(|r| r.get("id")).collect();
-
-    // Delete duplicates
-    for id in &duplicate_ids {
-        sqlx::query("DELETE FROM memories WHERE id = $1")
-            .bind(id)
+    // Delete duplicates, pointing memories they superseded at the kept copy
+    for (keep, id) in &removals {
+        sqlx::query!("UPDATE memories SET superseded_by = $1 WHERE superseded_by = $2", keep, id)
+            .execute(pool)
+            .await?;
+        sqlx::query!("DELETE FROM memories WHERE id = $1", id)
             .execute(pool)
             .await?;
     }
 
-    Ok(duplicate_ids)
+    Ok(removals.into_iter().map(|(_, id)| id).collect())
 }
 
-/// Prune old memories with tiered retention policy
-/// - LOW confidence: pruned after `low_days` days with access_count=0 and is_active=true
-/// - MEDIUM confidence: pruned after `medium_days` days with access_count=0, is_active=true, not superseded
-/// - HIGH confidence: never pruned
-pub async fn prune_old_memories_tiered(
+/// One duplicate folded into the memory kept in its place
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergedDuplicate {
+    pub kept_id: Uuid,
+    /// Now superseded by the kept memory
+    pub merged_id: Uuid,
+    pub merged_summary: String,
+    /// Tags of the kept memory after the merge
+    pub tags: Vec<String>,
+    /// Tags the kept memory gained from the duplicate
+    pub tags_added: Vec<String>,
+    pub confidence: Confidence,
+    /// Confidence of the kept memory before the merge, when the duplicate's was higher
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence_raised_from: Option<Confidence>,
+    /// Access count of the kept memory after the merge
+    pub access_count: i32,
+}
+
+/// Fold duplicate memories into the copy kept, instead of deleting them.
+///
+/// Only active memories are merged. The kept memory gains the duplicate's
+/// tags, the higher of the two confidences and the sum of their access
+/// counts; the duplicate is marked superseded by it, so `show-chain`
+/// still finds it until `purge-superseded` removes it.
+pub async fn merge_duplicates(
     pool: &PgPool,
-    low_days: i32,
-    medium_days: i32,
     scope_filter: Option<Scope>,
     project_path: Option<&str>,
-) -> Result<(Vec<Uuid>, Vec<Uuid>)> {
-    // Prune LOW confidence memories
-    let low_pruned = if let Some(scope) = scope_filter {
-        if scope == Scope::Project {
-            sqlx::query(
-                r#"
-                DELETE FROM memories
-                WHERE confidence = 'low'
-                  AND access_count = 0
+    similarity: f64,
+) -> Result<Vec<MergedDuplicate>> {
+    let pairs = find_duplicate_pairs(pool, scope_filter, project_path, similarity, true).await?;
+    let merges = pick_duplicates(&pairs);
+
+    let mut tx = pool.begin().await?;
+    let mut merged = Vec::with_capacity(merges.len());
+    for (keep_id, duplicate_id) in merges {
+        // Earlier merges in this run may have changed the kept memory
+        if let Some(merge) = merge_pair(&mut tx, keep_id, duplicate_id).await? {
+            merged.push(merge);
+        }
+    }
+    tx.commit().await?;
+
+    Ok(merged)
+}
+
+/// Fold one memory into another, as [`merge_duplicates`] does. None when
+/// either memory no longer exists.
+pub async fn merge_memory_into(
+    pool: &PgPool,
+    keep_id: Uuid,
+    duplicate_id: Uuid,
+) -> Result<Option<MergedDuplicate>> {
+    let mut tx = pool.begin().await?;
+    let merge = merge_pair(&mut tx, keep_id, duplicate_id).await?;
+    tx.commit().await?;
+    Ok(merge)
+}
+
+/// Merge `duplicate_id` into `keep_id` and mark it superseded
+async fn merge_pair(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    keep_id: Uuid,
+    duplicate_id: Uuid,
+) -> Result<Option<MergedDuplicate>> {
+    let keep = fetch_memory_for_update(tx, keep_id).await?;
+    let duplicate = fetch_memory_for_update(tx, duplicate_id).await?;
+    let (Some(keep), Some(duplicate)) = (keep, duplicate) else {
+        return Ok(None);
+    };
+    let merge = merge_into(&keep, &duplicate);
+
+    sqlx::query!(
+        r#"
+        UPDATE memories
+        SET tags = $2, confidence = $3, access_count = $4, updated_at = NOW()
+        WHERE id = $1
+        "#,
+        merge.kept_id,
+        &merge.tags,
+        merge.confidence.as_str(),
+        merge.access_count,
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query!(
+        r#"
+        UPDATE memories
+        SET superseded_by = $1, superseded_at = NOW(), is_active = false
+        WHERE id = $2
+        "#,
+        merge.kept_id,
+        merge.merged_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(Some(merge))
+}
+
+/// A memory, locked for the rest of the transaction
+async fn fetch_memory_for_update(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    id: Uuid,
+) -> Result<Option<Memory>> {
+    let memory = sqlx::query_as!(
+        Memory,
+        r#"
+        SELECT id, type AS "memory_type: MemoryType", scope AS "scope: Scope", project_path, content,
+               tags AS "tags!", confidence AS "confidence!: Confidence", source_session_id, source_turn_id,
+               created_at AS "created_at!", updated_at AS "updated_at!", accessed_at, access_count AS "access_count!",
+               superseded_by, superseded_at, is_active AS "is_active!",
+               NULLIF(metadata, '{}') AS metadata, summary, archived_at, pinned
+        FROM memories
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        id,
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+    Ok(memory)
+}
+
+/// The kept memory's fields once `duplicate` is folded into it
+fn merge_into(keep: &Memory, duplicate: &Memory) -> MergedDuplicate {
+    let tags_added: Vec<String> = duplicate
+        .tags
+        .iter()
+        .filter(|tag| !keep.tags.contains(tag))
+        .cloned()
+        .collect();
+    let raised = duplicate.confidence.sort_order() < keep.confidence.sort_order();
+
+    MergedDuplicate {
+        kept_id: keep.id,
+        merged_id: duplicate.id,
+        merged_summary: duplicate.summary.clone(),
+        tags: keep.tags.iter().chain(&tags_added).cloned().collect(),
+        tags_added,
+        confidence: if raised { duplicate.confidence } else { keep.confidence },
+        confidence_raised_from: raised.then_some(keep.confidence),
+        access_count: keep.access_count.saturating_add(duplicate.access_count),
+    }
+}
+
+/// Matching `(keep, duplicate)` pairs; the older memory is kept
+async fn find_duplicate_pairs(
+    pool: &PgPool,
+    scope_filter: Option<Scope>,
+    project_path: Option<&str>,
+    similarity: f64,
+    active_only: bool,
+) -> Result<Vec<(Uuid, Uuid)>> {
+    let scope_condition = match scope_filter {
+        Some(Scope::Project) => {
+            "AND m1.scope = 'project' AND m2.scope = 'project'
+              AND m1.project_path = $2 AND m2.project_path = $2"
+        }
+        Some(Scope::Global) => "AND m1.scope = 'global' AND m2.scope = 'global'",
+        None => "",
+    };
+    let fuzzy_condition = if fuzzy_dedup_enabled(similarity) {
+        "OR (m1.is_active AND m2.is_active AND similarity(m1.content, m2.content) >= $1)"
+    } else {
+        ""
+    };
+    let active_condition = if active_only {
+        "AND m1.is_active AND m2.is_active"
+    } else {
+        ""
+    };
+
+    let sql = format!(
+        r#"
+        SELECT m1.id AS keep_id, m2.id AS duplicate_id
+        FROM memories m1
+        JOIN memories m2 ON (m1.created_at, m1.id) < (m2.created_at, m2.id) AND m1.type = m2.type
+        WHERE (LOWER(m1.summary) = LOWER(m2.summary)
+               {fuzzy_condition})
+          AND NOT m2.pinned
+          {scope_condition}
+          {active_condition}
+        ORDER BY m1.created_at, m1.id, m2.created_at, m2.id
+        "#
+    );
+    let mut sql_query = sqlx::query_as(&sql).bind(similarity);
+    if scope_filter == Some(Scope::Project) {
+        sql_query = sql_query.bind(project_path);
+    }
+    Ok(sql_query.fetch_all(pool).await?)
+}
+
+/// Choose which memories to remove from matching `(keep, duplicate)` pairs.
+///
+/// Similarity is not transitive: when A matches B and B matches C, removing
+/// B and C could lose C even though A does not resemble it. A pair only
+/// counts while its kept memory survives, so C stays unless A matches it.
+/// Returns the chosen pairs.
+fn pick_duplicates(pairs: &[(Uuid, Uuid)]) -> Vec<(Uuid, Uuid)> {
+    let mut removals: Vec<(Uuid, Uuid)> = Vec::new();
+    for &(keep, duplicate) in pairs {
+        let removed = |id: Uuid| removals.iter().any(|(_, r)| *r == id);
+        if !removed(keep) && !removed(duplicate) {
+            removals.push((keep, duplicate));
+        }
+    }
+    removals
+}
+
+/// A project memory and a global memory that say the same thing
+#[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CrossScopeDuplicate {
+    #[sqlx(rename = "type")]
+    pub memory_type: MemoryType,
+    pub project_id: Uuid,
+    pub project_summary: String,
+    pub global_id: Uuid,
+    pub global_summary: String,
+    /// Trigram similarity of their contents (None for a matching summary)
+    pub similarity: Option<f64>,
+}
+
+/// Active project memories of `project_paths` matched with an active
+/// global memory, as [`find_duplicate`] matches: same type and summary, or
+/// (below 1.0) contents at `similarity` or above. Each memory is in at
+/// most one pair, its closest match.
+pub async fn find_cross_scope_duplicates(
+    pool: &PgPool,
+    project_paths: &[String],
+    similarity: f64,
+) -> Result<Vec<CrossScopeDuplicate>> {
+    let (similarity_column, fuzzy_condition) = if fuzzy_dedup_enabled(similarity) {
+        (
+            "round(similarity(p.content, g.content)::numeric, 3)::float8",
+            "OR similarity(p.content, g.content) >= $2",
+        )
+    } else {
+        ("NULL::float8", "")
+    };
+
+    let sql = format!(
+        r#"
+        SELECT p.type, p.id AS project_id, p.summary AS project_summary,
+               g.id AS global_id, g.summary AS global_summary,
+               CASE WHEN LOWER(p.summary) = LOWER(g.summary) THEN NULL
+                    ELSE {similarity_column} END AS similarity
+        FROM memories p
+        JOIN memories g ON g.type = p.type AND g.scope = 'global' AND g.is_active
+        WHERE p.scope = 'project' AND p.project_path = ANY($1) AND p.is_active
+          AND (LOWER(p.summary) = LOWER(g.summary)
+               {fuzzy_condition})
+        ORDER BY LOWER(p.summary) = LOWER(g.summary) DESC, similarity DESC,
+                 p.created_at, g.created_at
+        "#
+    );
+    let pairs: Vec<CrossScopeDuplicate> = sqlx::query_as(&sql)
+        .bind(project_paths)
+        .bind(similarity)
+        .fetch_all(pool)
+        .await?;
+
+    let mut paired: Vec<Uuid> = Vec::new();
+    Ok(pairs
+        .into_iter()
+        .filter(|pair| {
+            let free = !paired.contains(&pair.project_id) && !paired.contains(&pair.global_id);
+            if free {
+                paired.extend([pair.project_id, pair.global_id]);
+            }
+            free
+        })
+        .collect())
+}
+
+/// Prune old memories with tiered retention policy
+/// - LOW confidence: pruned after `low_days` days with access_count=0 and is_active=true
+/// - MEDIUM confidence: pruned after `medium_days` days with access_count=0, is_active=true, not superseded
+/// - HIGH confidence: never pruned
+pub async fn prune_old_memories_tiered(
+    pool: &PgPool,
+    low_days: i32,
+    medium_days: i32,
+    scope_filter: Option<Scope>,
+    project_path: Option<&str>,
+) -> Result<(Vec<Uuid>, Vec<Uuid>)> {
+    // Prune LOW confidence memories
+    let low_pruned = if let Some(scope) = scope_filter {
+        if scope == Scope::Project {
+            sqlx::query_scalar!(
+                r#"
+                DELETE FROM memories
+                WHERE confidence = 'low'
+                  AND access_count = 0
                   AND is_active = true
-                  AND created_at < NOW() - INTERVAL '1 day' * $1
+                  AND pinned = false
+                  AND created_at < NOW() - make_interval(days => $1)
                   AND scope = 'project'
                   AND project_path = $2
                 RETURNING id
                 "#,
+                low_days,
+                project_path,
             )
-            .bind(low_days)
-            .bind(project_path)
             .fetch_all(pool)
             .await?
         } else {
-            sqlx::query(
+            sqlx::query_scalar!(
                 r#"
                 DELETE FROM memories
                 WHERE confidence = 'low'
                   AND access_count = 0
                   AND is_active = true
-                  AND created_at < NOW() - INTERVAL '1 day' * $1
+                  AND pinned = false
+                  AND created_at < NOW() - make_interval(days => $1)
                   AND scope = 'global'
                 RETURNING id
                 "#,
+                low_days,
             )
-            .bind(low_days)
             .fetch_all(pool)
             .await?
         }
     } else {
-        sqlx::query(
+        sqlx::query_scalar!(
             r#"
             DELETE FROM memories
             WHERE confidence = 'low'
               AND access_count = 0
               AND is_active = true
-              AND created_at < NOW() - INTERVAL '1 day' * $1
+              AND pinned = false
+              AND created_at < NOW() - make_interval(days => $1)
             RETURNING id
             "#,
+            low_days,
         )
-        .bind(low_days)
         .fetch_all(pool)
         .await?
     };
@@ -910,61 +1652,240 @@ pub async fn prune_old_memories_tiered(
     // Prune MEDIUM confidence memories (only if not superseded)
     let medium_pruned = if let Some(scope) = scope_filter {
         if scope == Scope::Project {
-            sqlx::query(
+            sqlx::query_scalar!(
                 r#"
                 DELETE FROM memories
                 WHERE confidence = 'medium'
                   AND access_count = 0
                   AND is_active = true
+                  AND pinned = false
                   AND superseded_by IS NULL
-                  AND created_at < NOW() - INTERVAL '1 day' * $1
+                  AND created_at < NOW() - make_interval(days => $1)
                   AND scope = 'project'
                   AND project_path = $2
                 RETURNING id
                 "#,
+                medium_days,
+                project_path,
             )
-            .bind(medium_days)
-            .bind(project_path)
             .fetch_all(pool)
             .await?
         } else {
-            sqlx::query(
+            sqlx::query_scalar!(
                 r#"
                 DELETE FROM memories
                 WHERE confidence = 'medium'
                   AND access_count = 0
                   AND is_active = true
+                  AND pinned = false
                   AND superseded_by IS NULL
-                  AND created_at < NOW() - INTERVAL '1 day' * $1
+                  AND created_at < NOW() - make_interval(days => $1)
                   AND scope = 'global'
                 RETURNING id
                 "#,
+                medium_days,
             )
-            .bind(medium_days)
             .fetch_all(pool)
             .await?
         }
     } else {
-        sqlx::query(
+        sqlx::query_scalar!(
             r#"
             DELETE FROM memories
             WHERE confidence = 'medium'
               AND access_count = 0
               AND is_active = true
+              AND pinned = false
               AND superseded_by IS NULL
-              AND created_at < NOW() - INTERVAL '1 day' * $1
+              AND created_at < NOW() - make_interval(days => $1)
             RETURNING id
             "#,
+            medium_days,
         )
-        .bind(medium_days)
         .fetch_all(pool)
         .await?
     };
 
-    Ok((
-        low_pruned.iter().map(|r| r.get("id")).collect(),
-        medium_pruned.iter().map(|r| r.get("id")).collect(),
-    ))
+    Ok((low_pruned, medium_pruned))
+}
+
+/// Outcome of a decay run
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecayStats {
+    /// Active memories whose heat was recomputed
+    pub scored: i64,
+    /// Memories archived by this run
+    pub archived: i64,
+    /// Archived memories warm again (used since), restored by this run
+    pub restored: i64,
+}
+
+/// Recompute the heat of active memories and archive the cold ones.
+///
+/// Heat is `1 + ln(1 + access_count)`, halved every `half_life_days` since
+/// the memory was last used (or created). HIGH confidence memories whose
+/// heat falls below `archive_heat` are archived; the lower tiers are left
+/// to `prune`. Archived memories back at or above it are restored. With
+/// `dry_run`, the changes are counted and rolled back.
+pub async fn decay_memories(
+    pool: &PgPool,
+    half_life_days: f64,
+    archive_heat: f64,
+    dry_run: bool,
+) -> Result<DecayStats> {
+    let mut tx = pool.begin().await?;
+    let stats = sqlx::query_as!(
+        DecayStats,
+        r#"
+        WITH scored AS (
+            SELECT id,
+                   archived_at IS NOT NULL AS was_archived,
+                   (1 + LN(1 + access_count))
+                     * POWER(0.5, EXTRACT(EPOCH FROM NOW() - COALESCE(accessed_at, created_at))
+                                  / 86400.0 / $1::float8) AS heat
+            FROM memories
+            WHERE is_active = true
+        ),
+        updated AS (
+            UPDATE memories m
+            SET heat = s.heat,
+                archived_at = CASE
+                    WHEN s.heat >= $2 THEN NULL
+                    WHEN m.confidence = 'high' AND NOT m.pinned THEN COALESCE(m.archived_at, NOW())
+                    ELSE m.archived_at
+                END
+            FROM scored s
+            WHERE m.id = s.id
+            RETURNING s.was_archived, m.archived_at IS NOT NULL AS is_archived
+        )
+        SELECT COUNT(*) AS "scored!",
+               COUNT(*) FILTER (WHERE is_archived AND NOT was_archived) AS "archived!",
+               COUNT(*) FILTER (WHERE was_archived AND NOT is_archived) AS "restored!"
+        FROM updated
+        "#,
+        half_life_days,
+        archive_heat,
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+
+    Ok(stats)
+}
+
+// ============================================================================
+// Tag Queries
+// ============================================================================
+
+/// A tag and the number of memories carrying it
+#[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: i64,
+}
+
+/// Active memories carrying `tag` (ignoring case), ordered by `ranker`
+#[allow(clippy::too_many_arguments)]
+pub async fn search_by_tag(
+    pool: &PgPool,
+    tag: &str,
+    scope_filter: Option<Scope>,
+    project_paths: &[String],
+    include_both_scopes: bool,
+    limit: i32,
+    include_archived: bool,
+    ranker: &dyn Ranker,
+) -> Result<Vec<Memory>> {
+    let filter = MemoryFilter {
+        include_archived,
+        tag: Some(tag),
+        ..MemoryFilter::new(scope_filter, include_both_scopes, project_paths)
+    };
+
+    let page = filter.fetch_page(pool, ranker, limit as i64, 0, None).await?;
+    Ok(page.into_iter().map(|(memory, _)| memory).collect())
+}
+
+/// Tags in use by active memories, most used first.
+///
+/// Spellings differing only in case are counted together under the most
+/// common one.
+pub async fn list_tags(
+    pool: &PgPool,
+    scope_filter: Option<Scope>,
+    project_paths: &[String],
+    include_both_scopes: bool,
+) -> Result<Vec<TagCount>> {
+    let filter = MemoryFilter {
+        include_archived: true,
+        ..MemoryFilter::new(scope_filter, include_both_scopes, project_paths)
+    };
+
+    let mut sql_query = QueryBuilder::new(
+        "SELECT mode() WITHIN GROUP (ORDER BY t) AS tag, COUNT(*) AS count \
+         FROM memories, unnest(tags) AS t",
+    );
+    filter.push_where(&mut sql_query);
+    sql_query.push(" GROUP BY lower(t) ORDER BY count DESC, tag");
+    Ok(sql_query.build_query_as().fetch_all(pool).await?)
+}
+
+/// Replace the tags in `from` (ignoring case) with `to` on active memories.
+///
+/// Tag order is kept, and a memory that ends up with the same tag twice
+/// keeps the first. Memories already spelling it `to` are left alone.
+/// Returns the number of memories changed.
+pub async fn replace_tags(
+    pool: &PgPool,
+    from: &[String],
+    to: &str,
+    scope_filter: Option<Scope>,
+    project_path: Option<&str>,
+    include_both_scopes: bool,
+) -> Result<u64> {
+    let from: Vec<String> = from.iter().map(|t| t.to_lowercase()).collect();
+    let project_paths: Vec<String> = project_path.map(str::to_string).into_iter().collect();
+    let filter = MemoryFilter {
+        include_archived: true,
+        ..MemoryFilter::new(scope_filter, include_both_scopes, &project_paths)
+    };
+
+    let mut sql_query = QueryBuilder::new(
+        "UPDATE memories SET tags = ( \
+           SELECT COALESCE(array_agg(tag ORDER BY ord), '{}') \
+           FROM ( \
+             SELECT DISTINCT ON (lower(tag)) tag, ord \
+             FROM ( \
+               SELECT CASE WHEN lower(t) = ANY(",
+    );
+    sql_query
+        .push_bind(&from)
+        .push(") THEN ")
+        .push_bind(to)
+        .push(
+            " ELSE t END AS tag, ord \
+               FROM unnest(tags) WITH ORDINALITY AS u(t, ord) \
+             ) renamed \
+             ORDER BY lower(tag), ord \
+           ) deduped \
+         ), updated_at = NOW()",
+        );
+    filter.push_where(&mut sql_query);
+    sql_query
+        .push(" AND EXISTS (SELECT 1 FROM unnest(tags) AS t WHERE lower(t) = ANY(")
+        .push_bind(&from)
+        .push(") AND t <> ")
+        .push_bind(to)
+        .push(")");
+    let result = sql_query.build().execute(pool).await?;
+
+    Ok(result.rows_affected())
 }
 
 // ============================================================================
@@ -975,27 +1896,69 @@ use crate::models::MemorySummary;
 
 /// Mark an old memory as superseded by a new one
 pub async fn supersede_memory(pool: &PgPool, old_id: Uuid, new_id: Uuid) -> Result<()> {
-    sqlx::query(
+    sqlx::query!(
         r#"
         UPDATE memories
         SET superseded_by = $2, superseded_at = NOW(), is_active = false
         WHERE id = $1
         "#,
+        old_id,
+        new_id,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Make a superseded memory active again, clearing what superseded it
+pub async fn unsupersede_memory(pool: &PgPool, id: Uuid) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE memories
+        SET superseded_by = NULL, superseded_at = NULL, is_active = true
+        WHERE id = $1
+        "#,
+        id,
     )
-    .bind(old_id)
-    .bind(new_id)
     .execute(pool)
     .await?;
 
     Ok(())
 }
 
+/// Whether `successor` supersedes `memory_id`, directly or further along
+/// its chain
+pub async fn is_superseded_through(pool: &PgPool, memory_id: Uuid, successor: Uuid) -> Result<bool> {
+    // UNION (not UNION ALL) so a chain that already loops still terminates
+    let found = sqlx::query_scalar!(
+        r#"
+        WITH RECURSIVE chain(id) AS (
+            SELECT superseded_by FROM memories WHERE id = $1 AND superseded_by IS NOT NULL
+            UNION
+            SELECT m.superseded_by
+            FROM memories m
+            INNER JOIN chain c ON m.id = c.id
+            WHERE m.superseded_by IS NOT NULL
+        )
+        SELECT EXISTS (SELECT 1 FROM chain WHERE id = $2) AS "found!"
+        "#,
+        memory_id,
+        successor,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(found)
+}
+
 /// Result of showing a memory's supersession chain
 #[derive(Debug)]
 pub struct ChainResult {
     pub memory: MemorySummary,
     pub predecessors: Vec<MemorySummary>,
     pub successors: Vec<MemorySummary>,
+    pub links: Vec<LinkedMemory>,
 }
 
 /// Show the supersession chain for a memory
@@ -1006,7 +1969,7 @@ pub async fn show_chain(pool: &PgPool, memory_id: Uuid) -> Result<ChainResult> {
         .ok_or_else(|| HippocampusError::NotFound(format!("Memory not found: {}", memory_id)))?;
 
     // Find predecessors (memories that this one superseded - traverse back)
-    let predecessor_rows = sqlx::query(
+    let predecessor_ids = sqlx::query_scalar!(
         r#"
         WITH RECURSIVE chain AS (
             SELECT id, superseded_by, superseded_at
@@ -1017,23 +1980,22 @@ pub async fn show_chain(pool: &PgPool, memory_id: Uuid) -> Result<ChainResult> {
             FROM memories m
             INNER JOIN chain c ON m.superseded_by = c.id
         )
-        SELECT id FROM chain
+        SELECT id AS "id!" FROM chain
         "#,
+        memory_id,
     )
-    .bind(memory_id)
     .fetch_all(pool)
     .await?;
 
     let mut predecessors = Vec::new();
-    for row in predecessor_rows {
-        let id: Uuid = row.get("id");
+    for id in predecessor_ids {
         if let Some(mem) = get_memory(pool, id).await? {
             predecessors.push(mem.to_summary());
         }
     }
 
     // Find successors (memories that superseded this one - traverse forward)
-    let successor_rows = sqlx::query(
+    let successor_ids = sqlx::query_scalar!(
         r#"
         WITH RECURSIVE chain AS (
             SELECT id, superseded_by
@@ -1045,16 +2007,15 @@ pub async fn show_chain(pool: &PgPool, memory_id: Uuid) -> Result<ChainResult> {
             INNER JOIN chain c ON m.id = (SELECT superseded_by FROM memories WHERE id = c.id)
             WHERE m.id IS NOT NULL
         )
-        SELECT id FROM chain WHERE id IS NOT NULL
+        SELECT id AS "id!" FROM chain WHERE id IS NOT NULL
         "#,
+        memory_id,
     )
-    .bind(memory_id)
     .fetch_all(pool)
     .await?;
 
     let mut successors = Vec::new();
-    for row in successor_rows {
-        let id: Uuid = row.get("id");
+    for id in successor_ids {
         if let Some(mem) = get_memory(pool, id).await? {
             successors.push(mem.to_summary());
         }
@@ -1064,6 +2025,7 @@ pub async fn show_chain(pool: &PgPool, memory_id: Uuid) -> Result<ChainResult> {
         memory: memory.to_summary(),
         predecessors,
         successors,
+        links: list_memory_links(pool, memory_id).await?,
     })
 }
 
@@ -1075,78 +2037,119 @@ pub struct SupersededMemoryInfo {
     pub superseded_at: chrono::DateTime<chrono::Utc>,
 }
 
-/// List superseded (inactive) memories
+/// Order of `list_superseded` cursors, whose key is the time superseded
+pub const SUPERSEDED_ORDER: &str = "superseded";
+
+/// List superseded (inactive) memories, most recently superseded first.
+///
+/// A page starts `offset` rows in, or after the row of `after`, whose key
+/// is the time it was superseded.
 pub async fn list_superseded(
     pool: &PgPool,
     tier: crate::models::Tier,
     limit: i64,
-    project_path: Option<&str>,
+    offset: i64,
+    after: Option<&Cursor>,
+    project_paths: &[String],
 ) -> Result<Vec<SupersededMemoryInfo>> {
     use crate::models::Tier;
 
-    let rows = match tier {
+    let after = match cursor_in(after, SUPERSEDED_ORDER)? {
+        Some(cursor) => {
+            let at = DateTime::parse_from_rfc3339(&cursor.key.concat())
+                .map_err(|e| HippocampusError::Validation(format!("Invalid cursor: {}", e)))?;
+            Some((at.with_timezone(&Utc), cursor.id))
+        }
+        None => None,
+    };
+    let (after_at, after_id) = (after.map(|(at, _)| at), after.map(|(_, id)| id));
+
+    let memories: Vec<Memory> = match tier {
         Tier::Both => {
-            sqlx::query(
+            sqlx::query_as!(
+                Memory,
                 r#"
-                SELECT id, type, scope, project_path, content, tags, confidence,
-                       source_session_id, source_turn_id, created_at, updated_at,
-                       accessed_at, access_count, superseded_by, superseded_at, is_active
+                SELECT id, type AS "memory_type: MemoryType", scope AS "scope: Scope", project_path, content,
+                       tags AS "tags!", confidence AS "confidence!: Confidence", source_session_id, source_turn_id,
+                       created_at AS "created_at!", updated_at AS "updated_at!", accessed_at, access_count AS "access_count!",
+                       superseded_by, superseded_at, is_active AS "is_active!",
+                       NULLIF(metadata, '{}') AS metadata, summary, archived_at, pinned
                 FROM memories
                 WHERE is_active = false
                   AND superseded_by IS NOT NULL
-                  AND (scope = 'global' OR (scope = 'project' AND project_path = $2))
-                ORDER BY superseded_at DESC
-                LIMIT $1
+                  AND superseded_at IS NOT NULL
+                  AND (scope = 'global' OR (scope = 'project' AND project_path = ANY($2)))
+                  AND ($4::timestamptz IS NULL OR (superseded_at, id) < ($4, $5))
+                ORDER BY superseded_at DESC, id DESC
+                LIMIT $1 OFFSET $3
                 "#,
+                limit,
+                project_paths,
+                offset,
+                after_at,
+                after_id,
             )
-            .bind(limit)
-            .bind(project_path)
             .fetch_all(pool)
             .await?
         }
         Tier::Project => {
-            sqlx::query(
+            sqlx::query_as!(
+                Memory,
                 r#"
-                SELECT id, type, scope, project_path, content, tags, confidence,
-                       source_session_id, source_turn_id, created_at, updated_at,
-                       accessed_at, access_count, superseded_by, superseded_at, is_active
+                SELECT id, type AS "memory_type: MemoryType", scope AS "scope: Scope", project_path, content,
+                       tags AS "tags!", confidence AS "confidence!: Confidence", source_session_id, source_turn_id,
+                       created_at AS "created_at!", updated_at AS "updated_at!", accessed_at, access_count AS "access_count!",
+                       superseded_by, superseded_at, is_active AS "is_active!",
+                       NULLIF(metadata, '{}') AS metadata, summary, archived_at, pinned
                 FROM memories
                 WHERE is_active = false
                   AND superseded_by IS NOT NULL
+                  AND superseded_at IS NOT NULL
                   AND scope = 'project'
-                  AND project_path = $2
-                ORDER BY superseded_at DESC
-                LIMIT $1
+                  AND project_path = ANY($2)
+                  AND ($4::timestamptz IS NULL OR (superseded_at, id) < ($4, $5))
+                ORDER BY superseded_at DESC, id DESC
+                LIMIT $1 OFFSET $3
                 "#,
+                limit,
+                project_paths,
+                offset,
+                after_at,
+                after_id,
             )
-            .bind(limit)
-            .bind(project_path)
             .fetch_all(pool)
             .await?
         }
         Tier::Global => {
-            sqlx::query(
+            sqlx::query_as!(
+                Memory,
                 r#"
-                SELECT id, type, scope, project_path, content, tags, confidence,
-                       source_session_id, source_turn_id, created_at, updated_at,
-                       accessed_at, access_count, superseded_by, superseded_at, is_active
+                SELECT id, type AS "memory_type: MemoryType", scope AS "scope: Scope", project_path, content,
+                       tags AS "tags!", confidence AS "confidence!: Confidence", source_session_id, source_turn_id,
+                       created_at AS "created_at!", updated_at AS "updated_at!", accessed_at, access_count AS "access_count!",
+                       superseded_by, superseded_at, is_active AS "is_active!",
+                       NULLIF(metadata, '{}') AS metadata, summary, archived_at, pinned
                 FROM memories
                 WHERE is_active = false
                   AND superseded_by IS NOT NULL
+                  AND superseded_at IS NOT NULL
                   AND scope = 'global'
-                ORDER BY superseded_at DESC
-                LIMIT $1
+                  AND ($3::timestamptz IS NULL OR (superseded_at, id) < ($3, $4))
+                ORDER BY superseded_at DESC, id DESC
+                LIMIT $1 OFFSET $2
                 "#,
+                limit,
+                offset,
+                after_at,
+                after_id,
             )
-            .bind(limit)
             .fetch_all(pool)
             .await?
         }
     };
 
     let mut result = Vec::new();
-    for row in rows {
-        let memory = row_to_memory(&row)?;
+    for memory in memories {
         if let (Some(superseded_by), Some(superseded_at)) =
             (memory.superseded_by, memory.superseded_at)
         {
@@ -1168,54 +2171,54 @@ pub async fn purge_superseded(
     scope_filter: Option<Scope>,
     project_path: Option<&str>,
 ) -> Result<Vec<Uuid>> {
-    let purged_rows = if let Some(scope) = scope_filter {
+    let purged = if let Some(scope) = scope_filter {
         if scope == Scope::Project {
-            sqlx::query(
+            sqlx::query_scalar!(
                 r#"
                 DELETE FROM memories
                 WHERE is_active = false
                   AND superseded_at IS NOT NULL
-                  AND superseded_at < NOW() - INTERVAL '1 day' * $1
+                  AND superseded_at < NOW() - make_interval(days => $1)
                   AND scope = 'project'
                   AND project_path = $2
                 RETURNING id
                 "#,
+                days,
+                project_path,
             )
-            .bind(days)
-            .bind(project_path)
             .fetch_all(pool)
             .await?
         } else {
-            sqlx::query(
+            sqlx::query_scalar!(
                 r#"
                 DELETE FROM memories
                 WHERE is_active = false
                   AND superseded_at IS NOT NULL
-                  AND superseded_at < NOW() - INTERVAL '1 day' * $1
+                  AND superseded_at < NOW() - make_interval(days => $1)
                   AND scope = 'global'
                 RETURNING id
                 "#,
+                days,
             )
-            .bind(days)
             .fetch_all(pool)
             .await?
         }
     } else {
-        sqlx::query(
+        sqlx::query_scalar!(
             r#"
             DELETE FROM memories
             WHERE is_active = false
               AND superseded_at IS NOT NULL
-              AND superseded_at < NOW() - INTERVAL '1 day' * $1
+              AND superseded_at < NOW() - make_interval(days => $1)
             RETURNING id
             "#,
+            days,
         )
-        .bind(days)
         .fetch_all(pool)
         .await?
     };
 
-    Ok(purged_rows.iter().map(|r| r.get("id")).collect())
+    Ok(purged)
 }
 
 // ============================================================================
@@ -1240,34 +2243,34 @@ pub async fn prune_lifecycle_data(
 ) -> Result<LifecyclePruneResult> {
     if dry_run {
         // Just count what would be deleted
-        let tool_calls_count: i64 = sqlx::query_scalar(
+        let tool_calls_count: i64 = sqlx::query_scalar!(
             r#"
-            SELECT COUNT(*) FROM tool_calls
-            WHERE called_at < NOW() - INTERVAL '1 day' * $1
+            SELECT COUNT(*) AS "count!" FROM tool_calls
+            WHERE called_at < NOW() - make_interval(days => $1)
             "#,
+            tool_calls_days,
         )
-        .bind(tool_calls_days)
         .fetch_one(pool)
         .await?;
 
-        let turns_count: i64 = sqlx::query_scalar(
+        let turns_count: i64 = sqlx::query_scalar!(
             r#"
-            SELECT COUNT(*) FROM conversation_turns
-            WHERE created_at < NOW() - INTERVAL '1 day' * $1
+            SELECT COUNT(*) AS "count!" FROM conversation_turns
+            WHERE created_at < NOW() - make_interval(days => $1)
             "#,
+            turns_days,
         )
-        .bind(turns_days)
         .fetch_one(pool)
         .await?;
 
-        let sessions_count: i64 = sqlx::query_scalar(
+        let sessions_count: i64 = sqlx::query_scalar!(
             r#"
-            SELECT COUNT(*) FROM sessions
+            SELECT COUNT(*) AS "count!" FROM sessions
             WHERE status = 'completed'
-              AND ended_at < NOW() - INTERVAL '1 day' * $1
+              AND ended_at < NOW() - make_interval(days => $1)
             "#,
+            sessions_days,
         )
-        .bind(sessions_days)
         .fetch_one(pool)
         .await?;
 
@@ -1279,34 +2282,34 @@ pub async fn prune_lifecycle_data(
     }
 
     // Actually delete the data
-    let tool_calls_result = sqlx::query(
+    let tool_calls_result = sqlx::query!(
         r#"
         DELETE FROM tool_calls
-        WHERE called_at < NOW() - INTERVAL '1 day' * $1
+        WHERE called_at < NOW() - make_interval(days => $1)
         "#,
+        tool_calls_days,
     )
-    .bind(tool_calls_days)
     .execute(pool)
     .await?;
 
-    let turns_result = sqlx::query(
+    let turns_result = sqlx::query!(
         r#"
         DELETE FROM conversation_turns
-        WHERE created_at < NOW() - INTERVAL '1 day' * $1
+        WHERE created_at < NOW() - make_interval(days => $1)
         "#,
+        turns_days,
     )
-    .bind(turns_days)
     .execute(pool)
     .await?;
 
-    let sessions_result = sqlx::query(
+    let sessions_result = sqlx::query!(
         r#"
         DELETE FROM sessions
         WHERE status = 'completed'
-          AND ended_at < NOW() - INTERVAL '1 day' * $1
+          AND ended_at < NOW() - make_interval(days => $1)
         "#,
+        sessions_days,
     )
-    .bind(sessions_days)
     .execute(pool)
     .await?;
 
@@ -1323,21 +2326,21 @@ pub async fn save_session_summary(
     claude_session_id: &str,
     summary: &serde_json::Value,
 ) -> Result<Uuid> {
-    let row = sqlx::query(
+    let id = sqlx::query_scalar!(
         r#"
         UPDATE sessions
         SET summary = $2, status = 'completed', ended_at = NOW()
         WHERE claude_session_id = $1
         RETURNING id
         "#,
+        claude_session_id,
+        summary,
     )
-    .bind(claude_session_id)
-    .bind(summary)
     .fetch_optional(pool)
     .await?;
 
-    match row {
-        Some(r) => Ok(r.get("id")),
+    match id {
+        Some(id) => Ok(id),
         None => Err(HippocampusError::NotFound(format!(
             "Session not found: {}",
             claude_session_id
@@ -1345,317 +2348,2361 @@ pub async fn save_session_summary(
     }
 }
 
+/// Active memories a session summary refers to, best match first: those
+/// whose ID (or its first 8 characters) appears in `text`, and below 1.0
+/// those whose summary appears in it nearly word for word (word similarity
+/// at least `similarity`). Only global memories and those of
+/// `project_path` are considered.
+pub async fn find_memories_cited_in(
+    pool: &PgPool,
+    text: &str,
+    project_path: Option<&str>,
+    similarity: f64,
+) -> Result<Vec<Uuid>> {
+    let text = text.to_lowercase();
+    let trigram = fuzzy_dedup_enabled(similarity);
+    let score = if trigram {
+        "word_similarity(LOWER(summary), $1)"
+    } else {
+        "0::real"
+    };
+    let query = format!(
+        r#"
+        SELECT id
+        FROM (
+            SELECT id,
+                   position(left(id::text, 8) IN $1) > 0 AS mentioned,
+                   {score} AS score
+            FROM memories
+            WHERE is_active
+              AND (scope = 'global' OR project_path = $2)
+        ) candidates
+        WHERE mentioned OR score >= $3
+        ORDER BY mentioned DESC, score DESC
+        LIMIT 50
+        "#
+    );
+
+    let ids = sqlx::query_scalar(&query)
+        .bind(&text)
+        .bind(project_path)
+        .bind(similarity)
+        .fetch_all(pool)
+        .await?;
+    Ok(ids)
+}
+
 // ============================================================================
 // Session Queries
 // ============================================================================
 
-/// Create a new session
-pub async fn create_session(
-    pool: &PgPool,
-    claude_session_id: &str,
-    project_path: Option<&str>,
-    git_status: Option<&GitStatus>,
-) -> Result<Session> {
-    let git_status_json = git_status.map(|gs| serde_json::to_value(gs).ok()).flatten();
+/// Create a new session
+pub async fn create_session(
+    pool: &PgPool,
+    claude_session_id: &str,
+    project_path: Option<&str>,
+    git_status: Option<&GitStatus>,
+    environment: Option<&EnvironmentSnapshot>,
+) -> Result<Session> {
+    let git_status_json = git_status.and_then(|gs| serde_json::to_value(gs).ok());
+    let environment_json = environment.and_then(|env| serde_json::to_value(env).ok());
+
+    let session = sqlx::query_as!(
+        Session,
+        r#"
+        INSERT INTO sessions (claude_session_id, project_path, git_status, environment)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, claude_session_id AS "claude_session_id!", project_path,
+                  git_status AS "git_status!: LenientJson", environment AS "environment!: LenientJson",
+                  models_used, status AS "status!: SessionStatus", summary, started_at AS "started_at!",
+                  ended_at, created_at AS "created_at!", skipped_prompts
+        "#,
+        claude_session_id,
+        project_path,
+        git_status_json,
+        environment_json,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(session)
+}
+
+/// Find session by database UUID
+pub async fn find_session_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Session>> {
+    let session = sqlx::query_as!(
+        Session,
+        r#"
+        SELECT id, claude_session_id AS "claude_session_id!", project_path,
+               git_status AS "git_status!: LenientJson", environment AS "environment!: LenientJson",
+               models_used, status AS "status!: SessionStatus", summary, started_at AS "started_at!",
+               ended_at, created_at AS "created_at!", skipped_prompts
+        FROM sessions
+        WHERE id = $1
+        "#,
+        id,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(session)
+}
+
+/// List a project's most recently ended sessions that have a summary
+pub async fn list_session_summaries(
+    pool: &PgPool,
+    project_path: &str,
+    limit: i64,
+) -> Result<Vec<Session>> {
+    let sessions = sqlx::query_as!(
+        Session,
+        r#"
+        SELECT id, claude_session_id AS "claude_session_id!", project_path,
+               git_status AS "git_status!: LenientJson", environment AS "environment!: LenientJson",
+               models_used, status AS "status!: SessionStatus", summary, started_at AS "started_at!",
+               ended_at, created_at AS "created_at!", skipped_prompts
+        FROM sessions
+        WHERE project_path = $1
+          AND summary IS NOT NULL
+        ORDER BY COALESCE(ended_at, started_at) DESC
+        LIMIT $2
+        "#,
+        project_path,
+        limit,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(sessions)
+}
+
+/// List sessions, newest first, with their turn and memory counts.
+///
+/// Every filter is optional. Returns one page and the number of sessions
+/// matching the filters overall.
+pub async fn list_sessions(
+    pool: &PgPool,
+    status: Option<SessionStatus>,
+    project_path: Option<&str>,
+    since: Option<DateTime<Utc>>,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<SessionSummary>, i64)> {
+    let status = status.map(|s| s.as_str());
+
+    let total = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM sessions
+        WHERE ($1::text IS NULL OR status = $1)
+          AND ($2::text IS NULL OR project_path = $2)
+          AND ($3::timestamptz IS NULL OR started_at >= $3)
+        "#,
+        status,
+        project_path,
+        since,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let sessions = sqlx::query_as!(
+        SessionSummary,
+        r#"
+        SELECT s.id, s.claude_session_id AS "claude_session_id!", s.project_path,
+               s.git_status->>'branch' AS branch,
+               s.status AS "status!: SessionStatus", s.started_at AS "started_at!", s.ended_at,
+               (SELECT COUNT(*) FROM conversation_turns t WHERE t.session_id = s.id) AS "turn_count!",
+               (SELECT COUNT(*) FROM memories m WHERE m.source_session_id = s.id) AS "memory_count!"
+        FROM sessions s
+        WHERE ($1::text IS NULL OR s.status = $1)
+          AND ($2::text IS NULL OR s.project_path = $2)
+          AND ($3::timestamptz IS NULL OR s.started_at >= $3)
+        ORDER BY s.started_at DESC
+        LIMIT $4 OFFSET $5
+        "#,
+        status,
+        project_path,
+        since,
+        limit,
+        offset,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok((sessions, total))
+}
+
+/// Sessions whose git branch, files changed at start or summary text
+/// contain `query` (ignoring case), newest first.
+///
+/// Only string values of the summary are searched, not its keys.
+pub async fn search_sessions(
+    pool: &PgPool,
+    query: &str,
+    project_path: Option<&str>,
+    limit: i64,
+) -> Result<Vec<SessionMatch>> {
+    let pattern = format!("%{}%", query);
+    let rows = sqlx::query!(
+        r#"
+        SELECT s.id, s.claude_session_id AS "claude_session_id!", s.project_path,
+               s.git_status->>'branch' AS branch,
+               s.status AS "status!: SessionStatus", s.started_at AS "started_at!", s.ended_at,
+               s.summary,
+               (SELECT COUNT(*) FROM conversation_turns t WHERE t.session_id = s.id) AS "turn_count!",
+               (SELECT COUNT(*) FROM memories m WHERE m.source_session_id = s.id) AS "memory_count!",
+               f.matched AS "matched!"
+        FROM sessions s,
+        LATERAL (
+            SELECT ARRAY_REMOVE(ARRAY[
+                CASE WHEN s.git_status->>'branch' ILIKE $1 THEN 'branch' END,
+                CASE WHEN EXISTS (
+                    SELECT 1
+                    FROM jsonb_array_elements_text(
+                        jsonb_path_query_array(s.git_status, 'lax $.modified[*]')
+                        || jsonb_path_query_array(s.git_status, 'lax $.staged[*]')
+                        || jsonb_path_query_array(s.git_status, 'lax $.untracked[*]')
+                    ) AS file
+                    WHERE file ILIKE $1
+                ) THEN 'files' END,
+                CASE WHEN EXISTS (
+                    SELECT 1
+                    FROM jsonb_path_query(s.summary, 'strict $.**') AS v
+                    WHERE jsonb_typeof(v) = 'string' AND v #>> '{}' ILIKE $1
+                ) THEN 'summary' END
+            ], NULL) AS matched
+        ) f
+        WHERE cardinality(f.matched) > 0
+          AND ($2::text IS NULL OR s.project_path = $2)
+        ORDER BY s.started_at DESC
+        LIMIT $3
+        "#,
+        pattern,
+        project_path,
+        limit,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| SessionMatch {
+            session: SessionSummary {
+                id: r.id,
+                claude_session_id: r.claude_session_id,
+                project_path: r.project_path,
+                branch: r.branch,
+                status: r.status,
+                started_at: r.started_at,
+                ended_at: r.ended_at,
+                turn_count: r.turn_count,
+                memory_count: r.memory_count,
+            },
+            matched: r.matched,
+            summary: r.summary,
+        })
+        .collect())
+}
+
+/// When the most recent session of the projects, other than `exclude`,
+/// ended (or started, if it never ended). None before the first session.
+pub async fn last_session_boundary(
+    pool: &PgPool,
+    project_paths: &[String],
+    exclude: Option<Uuid>,
+) -> Result<Option<DateTime<Utc>>> {
+    let boundary = sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(ended_at, started_at)
+        FROM sessions
+        WHERE project_path = ANY($1)
+          AND id IS DISTINCT FROM $2
+        ORDER BY started_at DESC
+        LIMIT 1
+        "#,
+        project_paths,
+        exclude,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(boundary.flatten())
+}
+
+/// Count a prompt that was not recorded as a turn
+pub async fn record_skipped_prompt(pool: &PgPool, session_id: Uuid) -> Result<()> {
+    sqlx::query!(
+        "UPDATE sessions SET skipped_prompts = skipped_prompts + 1 WHERE id = $1",
+        session_id,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Find session by Claude session ID
+pub async fn find_session_by_claude_id(
+    pool: &PgPool,
+    claude_session_id: &str,
+) -> Result<Option<Session>> {
+    let session = sqlx::query_as!(
+        Session,
+        r#"
+        SELECT id, claude_session_id AS "claude_session_id!", project_path,
+               git_status AS "git_status!: LenientJson", environment AS "environment!: LenientJson",
+               models_used, status AS "status!: SessionStatus", summary, started_at AS "started_at!",
+               ended_at, created_at AS "created_at!", skipped_prompts
+        FROM sessions
+        WHERE claude_session_id = $1
+        "#,
+        claude_session_id,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(session)
+}
+
+/// End a session
+pub async fn end_session(
+    pool: &PgPool,
+    claude_session_id: &str,
+    summary: Option<&str>,
+) -> Result<Session> {
+    let summary_json = summary.map(|s| serde_json::json!({ "summary": s }));
+
+    let session = sqlx::query_as!(
+        Session,
+        r#"
+        UPDATE sessions
+        SET status = 'completed', ended_at = NOW(), summary = COALESCE($2, summary)
+        WHERE claude_session_id = $1
+        RETURNING id, claude_session_id AS "claude_session_id!", project_path,
+                  git_status AS "git_status!: LenientJson", environment AS "environment!: LenientJson",
+                  models_used, status AS "status!: SessionStatus", summary, started_at AS "started_at!",
+                  ended_at, created_at AS "created_at!", skipped_prompts
+        "#,
+        claude_session_id,
+        summary_json,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    session.ok_or_else(|| HippocampusError::SessionNotFound(claude_session_id.to_string()))
+}
+
+// ============================================================================
+// Turn Queries
+// ============================================================================
+
+use crate::models::Turn;
+
+/// Create a new conversation turn
+pub async fn create_turn(
+    pool: &PgPool,
+    session_id: Uuid,
+    turn_number: i32,
+    user_prompt: &str,
+    model_used: Option<&str>,
+) -> Result<Turn> {
+    let turn = sqlx::query_as!(
+        Turn,
+        r#"
+        INSERT INTO conversation_turns (session_id, turn_number, user_prompt, model_used, started_at)
+        VALUES ($1, $2, $3, $4, NOW())
+        RETURNING id, session_id, turn_number, user_prompt, assistant_response,
+                  model_used, input_tokens, output_tokens, started_at AS "started_at!", ended_at,
+                  created_at AS "created_at!"
+        "#,
+        session_id,
+        turn_number,
+        user_prompt,
+        model_used,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(turn)
+}
+
+/// Get the next turn number for a session
+pub async fn get_next_turn_number(pool: &PgPool, session_id: Uuid) -> Result<i32> {
+    let count = sqlx::query_scalar!(
+        "SELECT MAX(turn_number)::INT4 FROM conversation_turns WHERE session_id = $1",
+        session_id,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count.map(|n| n + 1).unwrap_or(1))
+}
+
+/// All turns of a session, in order
+pub async fn list_session_turns(pool: &PgPool, session_id: Uuid) -> Result<Vec<Turn>> {
+    let turns = sqlx::query_as!(
+        Turn,
+        r#"
+        SELECT id, session_id, turn_number, user_prompt, assistant_response,
+               model_used, input_tokens, output_tokens, started_at AS "started_at!", ended_at,
+               created_at AS "created_at!"
+        FROM conversation_turns
+        WHERE session_id = $1
+        ORDER BY turn_number
+        "#,
+        session_id,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(turns)
+}
+
+/// A turn with the Claude session it belongs to
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SessionTurn {
+    pub claude_session_id: String,
+    #[sqlx(flatten)]
+    pub turn: Turn,
+}
+
+/// A project's sessions with answered turns, oldest first, as
+/// (id, Claude session ID).
+///
+/// With `since`, only sessions started at or after it.
+pub async fn list_answered_sessions(
+    pool: &PgPool,
+    project_path: &str,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<(Uuid, String)>> {
+    let sessions = sqlx::query!(
+        r#"
+        SELECT s.id, s.claude_session_id AS "claude_session_id!"
+        FROM sessions s
+        WHERE s.project_path = $1
+          AND ($2::timestamptz IS NULL OR s.started_at >= $2)
+          AND EXISTS (
+            SELECT 1 FROM conversation_turns t
+            WHERE t.session_id = s.id AND t.assistant_response IS NOT NULL
+          )
+        ORDER BY s.started_at, s.id
+        "#,
+        project_path,
+        since,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(sessions.into_iter().map(|s| (s.id, s.claude_session_id)).collect())
+}
+
+/// Memories created during a session, oldest first, superseded ones included
+pub async fn list_session_memories(pool: &PgPool, session_id: Uuid) -> Result<Vec<Memory>> {
+    let memories = sqlx::query_as!(
+        Memory,
+        r#"
+        SELECT id, type AS "memory_type: MemoryType", scope AS "scope: Scope", project_path, content,
+               tags AS "tags!", confidence AS "confidence!: Confidence", source_session_id, source_turn_id,
+               created_at AS "created_at!", updated_at AS "updated_at!", accessed_at, access_count AS "access_count!",
+               superseded_by, superseded_at, is_active AS "is_active!",
+               NULLIF(metadata, '{}') AS metadata, summary, archived_at, pinned
+        FROM memories
+        WHERE source_session_id = $1
+        ORDER BY created_at
+        "#,
+        session_id,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(memories)
+}
+
+/// ID of a session's most recent turn
+pub async fn find_latest_turn_id(pool: &PgPool, session_id: Uuid) -> Result<Option<Uuid>> {
+    let id = sqlx::query_scalar!(
+        "SELECT id FROM conversation_turns WHERE session_id = $1 ORDER BY turn_number DESC LIMIT 1",
+        session_id,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(id)
+}
+
+/// Find turn by ID
+pub async fn find_turn_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Turn>> {
+    let turn = sqlx::query_as!(
+        Turn,
+        r#"
+        SELECT id, session_id, turn_number, user_prompt, assistant_response,
+               model_used, input_tokens, output_tokens, started_at AS "started_at!", ended_at,
+               created_at AS "created_at!"
+        FROM conversation_turns
+        WHERE id = $1
+        "#,
+        id,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(turn)
+}
+
+/// Update turn with assistant response
+pub async fn update_turn(
+    pool: &PgPool,
+    turn_id: Uuid,
+    response: &str,
+    input_tokens: Option<i32>,
+    output_tokens: Option<i32>,
+) -> Result<Turn> {
+    let turn: Option<Turn> = sqlx::query_as!(
+        Turn,
+        r#"
+        UPDATE conversation_turns
+        SET assistant_response = $2, input_tokens = $3, output_tokens = $4, ended_at = NOW()
+        WHERE id = $1
+        RETURNING id, session_id, turn_number, user_prompt, assistant_response,
+                  model_used, input_tokens, output_tokens, started_at AS "started_at!", ended_at,
+                  created_at AS "created_at!"
+        "#,
+        turn_id,
+        response,
+        input_tokens,
+        output_tokens,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    turn.ok_or_else(|| HippocampusError::NotFound(format!("Turn not found: {}", turn_id)))
+}
+
+// ============================================================================
+// Turn Embedding Queries
+// ============================================================================
+
+/// A turn with its embedding and the session it belongs to
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct EmbeddedTurn {
+    pub claude_session_id: String,
+    pub project_path: Option<String>,
+    #[sqlx(flatten)]
+    pub turn: Turn,
+    pub embedding: Vec<f32>,
+}
+
+/// Answered turns without an embedding from `model`, newest first.
+///
+/// With `turn`, only that turn.
+pub async fn list_unembedded_turns(
+    pool: &PgPool,
+    model: &str,
+    turn: Option<Uuid>,
+    limit: i64,
+) -> Result<Vec<Turn>> {
+    let turns = sqlx::query_as!(
+        Turn,
+        r#"
+        SELECT t.id, t.session_id, t.turn_number, t.user_prompt, t.assistant_response,
+               t.model_used, t.input_tokens, t.output_tokens, t.started_at AS "started_at!", t.ended_at,
+               t.created_at AS "created_at!"
+        FROM conversation_turns t
+        LEFT JOIN turn_embeddings e ON e.turn_id = t.id AND e.model = $1
+        WHERE e.turn_id IS NULL
+          AND t.assistant_response IS NOT NULL
+          AND ($2::uuid IS NULL OR t.id = $2)
+        ORDER BY t.created_at DESC
+        LIMIT $3
+        "#,
+        model,
+        turn,
+        limit,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(turns)
+}
+
+/// Number of answered turns without an embedding from `model`
+pub async fn count_unembedded_turns(pool: &PgPool, model: &str) -> Result<i64> {
+    let count = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM conversation_turns t
+        LEFT JOIN turn_embeddings e ON e.turn_id = t.id AND e.model = $1
+        WHERE e.turn_id IS NULL AND t.assistant_response IS NOT NULL
+        "#,
+        model,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count)
+}
+
+/// Store a turn's embedding, replacing one from another model
+pub async fn store_turn_embedding(
+    pool: &PgPool,
+    turn_id: Uuid,
+    model: &str,
+    embedding: &[f32],
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO turn_embeddings (turn_id, model, embedding)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (turn_id) DO UPDATE
+        SET model = EXCLUDED.model, embedding = EXCLUDED.embedding, created_at = NOW()
+        "#,
+        turn_id,
+        model,
+        embedding,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Turns embedded by `model` in sessions of `project_paths` (every project
+/// when empty)
+pub async fn list_turn_embeddings(
+    pool: &PgPool,
+    model: &str,
+    project_paths: &[String],
+) -> Result<Vec<EmbeddedTurn>> {
+    let turns = sqlx::query_as(
+        r#"
+        SELECT t.id, t.session_id, t.turn_number, t.user_prompt, t.assistant_response,
+               t.model_used, t.input_tokens, t.output_tokens, t.started_at, t.ended_at,
+               t.created_at, s.claude_session_id, s.project_path, e.embedding
+        FROM turn_embeddings e
+        JOIN conversation_turns t ON t.id = e.turn_id
+        JOIN sessions s ON s.id = t.session_id
+        WHERE e.model = $1
+          AND (cardinality($2::text[]) = 0 OR s.project_path = ANY($2))
+        "#,
+    )
+    .bind(model)
+    .bind(project_paths)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(turns)
+}
+
+// ============================================================================
+// Memory Embedding Queries
+// ============================================================================
+
+/// A memory with its embedding
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct EmbeddedMemory {
+    #[sqlx(flatten)]
+    pub memory: Memory,
+    pub embedding: Vec<f32>,
+}
+
+/// Active memories without an embedding from `model`, or changed since
+/// theirs, newest first
+pub async fn list_unembedded_memories(pool: &PgPool, model: &str, limit: i64) -> Result<Vec<Memory>> {
+    let memories = sqlx::query_as!(
+        Memory,
+        r#"
+        SELECT id, type AS "memory_type: MemoryType", scope AS "scope: Scope", project_path, content,
+               tags AS "tags!", confidence AS "confidence!: Confidence", source_session_id, source_turn_id,
+               created_at AS "created_at!", updated_at AS "updated_at!", accessed_at, access_count AS "access_count!",
+               superseded_by, superseded_at, is_active AS "is_active!",
+               NULLIF(metadata, '{}') AS metadata, summary, archived_at, pinned
+        FROM memories
+        LEFT JOIN memory_embeddings e ON e.memory_id = memories.id AND e.model = $1
+        WHERE is_active = true
+          AND (e.memory_id IS NULL OR e.embedded_at < updated_at)
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#,
+        model,
+        limit,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(memories)
+}
+
+/// Number of active memories without an up-to-date embedding from `model`
+pub async fn count_unembedded_memories(pool: &PgPool, model: &str) -> Result<i64> {
+    let count = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM memories m
+        LEFT JOIN memory_embeddings e ON e.memory_id = m.id AND e.model = $1
+        WHERE m.is_active = true
+          AND (e.memory_id IS NULL OR e.embedded_at < m.updated_at)
+        "#,
+        model,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count)
+}
+
+/// Store a memory's embedding, replacing an older one
+pub async fn store_memory_embedding(
+    pool: &PgPool,
+    memory_id: Uuid,
+    model: &str,
+    embedding: &[f32],
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO memory_embeddings (memory_id, model, embedding)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (memory_id) DO UPDATE
+        SET model = EXCLUDED.model, embedding = EXCLUDED.embedding, embedded_at = NOW()
+        "#,
+        memory_id,
+        model,
+        embedding,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Active memories embedded by `model` in the given scope, narrowed by
+/// `filters`
+pub async fn list_memory_embeddings(
+    pool: &PgPool,
+    model: &str,
+    scope_filter: Option<Scope>,
+    project_paths: &[String],
+    include_both_scopes: bool,
+    filters: &SearchFilters,
+) -> Result<Vec<EmbeddedMemory>> {
+    let filter = MemoryFilter::new(scope_filter, include_both_scopes, project_paths).narrowed(filters);
+
+    let mut query = QueryBuilder::new(format!(
+        "SELECT {}, e.embedding FROM memories \
+         JOIN memory_embeddings e ON e.memory_id = memories.id AND e.model = ",
+        MEMORY_COLUMNS
+    ));
+    query.push_bind(model);
+    filter.push_where(&mut query);
+    let memories = query.build_query_as().fetch_all(pool).await?;
+
+    Ok(memories)
+}
+
+// ============================================================================
+// Review Queue
+// ============================================================================
+
+/// A memory flagged for review
+#[derive(Debug)]
+pub struct ReviewQueueEntry {
+    pub memory: MemorySummary,
+    pub reason: Option<String>,
+    pub flagged_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct FlaggedMemory {
+    #[sqlx(flatten)]
+    memory: Memory,
+    review_reason: Option<String>,
+    review_flagged_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Find active memories of the projects whose content mentions a string (case-insensitive)
+pub async fn find_memories_mentioning(
+    pool: &PgPool,
+    project_paths: &[String],
+    needle: &str,
+) -> Result<Vec<Memory>> {
+    let pattern = format!("%{}%", needle);
+
+    let memories = sqlx::query_as!(
+        Memory,
+        r#"
+        SELECT id, type AS "memory_type: MemoryType", scope AS "scope: Scope", project_path, content,
+               tags AS "tags!", confidence AS "confidence!: Confidence", source_session_id, source_turn_id,
+               created_at AS "created_at!", updated_at AS "updated_at!", accessed_at, access_count AS "access_count!",
+               superseded_by, superseded_at, is_active AS "is_active!",
+               NULLIF(metadata, '{}') AS metadata, summary, archived_at, pinned
+        FROM memories
+        WHERE is_active = true
+          AND scope = 'project'
+          AND project_path = ANY($1)
+          AND content ILIKE $2
+        "#,
+        project_paths,
+        &pattern,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(memories)
+}
+
+/// Flag memories as needing review. Already-flagged memories keep their original reason.
+///
+/// Returns the IDs that were newly flagged.
+pub async fn flag_memories_for_review(
+    pool: &PgPool,
+    ids: &[Uuid],
+    reason: &str,
+) -> Result<Vec<Uuid>> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let flagged = sqlx::query_scalar!(
+        r#"
+        UPDATE memories
+        SET needs_review = true, review_reason = $2, review_flagged_at = NOW()
+        WHERE id = ANY($1) AND needs_review = false
+        RETURNING id
+        "#,
+        ids,
+        reason,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(flagged)
+}
+
+/// List active memories flagged for review
+pub async fn list_review_queue(
+    pool: &PgPool,
+    tier: crate::models::Tier,
+    limit: i64,
+    project_paths: &[String],
+) -> Result<Vec<ReviewQueueEntry>> {
+    use crate::models::Tier;
+
+    let rows: Vec<FlaggedMemory> = match tier {
+        Tier::Both => {
+            sqlx::query_as(
+                r#"
+                SELECT id, type, scope, project_path, content, tags, confidence,
+                       source_session_id, source_turn_id, created_at, updated_at,
+                       accessed_at, access_count, superseded_by, superseded_at, is_active,
+                       NULLIF(metadata, '{}') AS metadata, summary, archived_at, pinned,
+                       review_reason, review_flagged_at
+                FROM memories
+                WHERE is_active = true
+                  AND needs_review = true
+                  AND (scope = 'global' OR (scope = 'project' AND project_path = ANY($2)))
+                ORDER BY review_flagged_at DESC NULLS LAST
+                LIMIT $1
+                "#,
+            )
+            .bind(limit)
+            .bind(project_paths)
+            .fetch_all(pool)
+            .await?
+        }
+        Tier::Project => {
+            sqlx::query_as(
+                r#"
+                SELECT id, type, scope, project_path, content, tags, confidence,
+                       source_session_id, source_turn_id, created_at, updated_at,
+                       accessed_at, access_count, superseded_by, superseded_at, is_active,
+                       NULLIF(metadata, '{}') AS metadata, summary, archived_at, pinned,
+                       review_reason, review_flagged_at
+                FROM memories
+                WHERE is_active = true
+                  AND needs_review = true
+                  AND scope = 'project'
+                  AND project_path = ANY($2)
+                ORDER BY review_flagged_at DESC NULLS LAST
+                LIMIT $1
+                "#,
+            )
+            .bind(limit)
+            .bind(project_paths)
+            .fetch_all(pool)
+            .await?
+        }
+        Tier::Global => {
+            sqlx::query_as(
+                r#"
+                SELECT id, type, scope, project_path, content, tags, confidence,
+                       source_session_id, source_turn_id, created_at, updated_at,
+                       accessed_at, access_count, superseded_by, superseded_at, is_active,
+                       NULLIF(metadata, '{}') AS metadata, summary, archived_at, pinned,
+                       review_reason, review_flagged_at
+                FROM memories
+                WHERE is_active = true
+                  AND needs_review = true
+                  AND scope = 'global'
+                ORDER BY review_flagged_at DESC NULLS LAST
+                LIMIT $1
+                "#,
+            )
+            .bind(limit)
+            .fetch_all(pool)
+            .await?
+        }
+    };
+
+    Ok(rows
+        .into_iter()
+        .map(|row| ReviewQueueEntry {
+            memory: row.memory.to_summary(),
+            reason: row.review_reason,
+            flagged_at: row.review_flagged_at,
+        })
+        .collect())
+}
+
+/// Clear the review flag on a memory. Returns false if the memory was not flagged.
+pub async fn resolve_review(pool: &PgPool, id: Uuid) -> Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE memories
+        SET needs_review = false, review_reason = NULL, review_flagged_at = NULL
+        WHERE id = $1 AND needs_review = true
+        "#,
+        id,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Project memories wanting attention, counted for the SessionStart reminder
+#[derive(Debug, Clone, Default)]
+pub struct ReviewReminder {
+    /// Memories flagged for review
+    pub needs_review: i64,
+    /// Unused LOW and MEDIUM memories that `prune` deletes within the window
+    pub expiring: i64,
+}
+
+/// Count the active project memories of `project_paths` that are flagged
+/// for review, and those `prune` would delete within `within_days` under
+/// the `low_days` and `medium_days` thresholds.
+pub async fn count_review_reminder(
+    pool: &PgPool,
+    project_paths: &[String],
+    low_days: i32,
+    medium_days: i32,
+    within_days: i32,
+) -> Result<ReviewReminder> {
+    let row = sqlx::query!(
+        r#"
+        SELECT COUNT(*) FILTER (WHERE needs_review) AS "needs_review!",
+               COUNT(*) FILTER (
+                   WHERE access_count = 0
+                     AND NOT pinned
+                     AND ((confidence = 'low'
+                           AND created_at < NOW() - make_interval(days => $2::int - $4::int))
+                       OR (confidence = 'medium' AND superseded_by IS NULL
+                           AND created_at < NOW() - make_interval(days => $3::int - $4::int)))
+               ) AS "expiring!"
+        FROM memories
+        WHERE is_active = true
+          AND scope = 'project'
+          AND project_path = ANY($1)
+        "#,
+        project_paths,
+        low_days,
+        medium_days,
+        within_days,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(ReviewReminder {
+        needs_review: row.needs_review,
+        expiring: row.expiring,
+    })
+}
+
+// ============================================================================
+// Tool Call Recording
+// ============================================================================
+
+/// Tool call record
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ToolCall {
+    pub id: Uuid,
+    pub session_id: Option<Uuid>,
+    pub turn_id: Option<Uuid>,
+    pub tool_name: String,
+    pub parameters: Option<serde_json::Value>,
+    pub result_summary: Option<String>,
+    pub called_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Record a tool call to the database
+pub async fn record_tool_call(
+    pool: &PgPool,
+    session_id: Option<Uuid>,
+    turn_id: Option<Uuid>,
+    tool_name: &str,
+    parameters: Option<serde_json::Value>,
+    result_summary: Option<String>,
+) -> Result<ToolCall> {
+    let call = sqlx::query_as!(
+        ToolCall,
+        r#"
+        INSERT INTO tool_calls (session_id, turn_id, tool_name, parameters, result_summary)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, session_id, turn_id, tool_name, parameters, result_summary,
+                  called_at AS "called_at!"
+        "#,
+        session_id,
+        turn_id,
+        tool_name,
+        parameters,
+        result_summary,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(call)
+}
+
+/// List a session's tool calls in call order
+pub async fn list_session_tool_calls(pool: &PgPool, session_id: Uuid) -> Result<Vec<ToolCall>> {
+    let calls = sqlx::query_as!(
+        ToolCall,
+        r#"
+        SELECT id, session_id, turn_id, tool_name, parameters, result_summary,
+               called_at AS "called_at!"
+        FROM tool_calls
+        WHERE session_id = $1
+        ORDER BY called_at ASC
+        "#,
+        session_id,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(calls)
+}
+
+/// Recorded tool calls per turn of a session
+pub async fn count_tool_calls_by_turn(pool: &PgPool, session_id: Uuid) -> Result<Vec<(Uuid, i64)>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT turn_id AS "turn_id!", COUNT(*) AS "calls!"
+        FROM tool_calls
+        WHERE session_id = $1 AND turn_id IS NOT NULL AND tool_name <> 'Notification'
+        GROUP BY turn_id
+        "#,
+        session_id,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| (r.turn_id, r.calls)).collect())
+}
+
+// ============================================================================
+// Extraction Quota
+// ============================================================================
+
+/// Claim one extraction call from today's budget.
+///
+/// Returns the number of calls used today including this one, or None when
+/// the `cap` is already reached. Without a cap the call is always granted.
+pub async fn reserve_extraction(pool: &PgPool, cap: Option<u32>) -> Result<Option<i64>> {
+    if cap == Some(0) {
+        return Ok(None);
+    }
+
+    let calls = sqlx::query_scalar!(
+        r#"
+        INSERT INTO extraction_usage (day, calls)
+        VALUES ((NOW() AT TIME ZONE 'UTC')::date, 1)
+        ON CONFLICT (day) DO UPDATE SET calls = extraction_usage.calls + 1
+        WHERE $1::bigint IS NULL OR extraction_usage.calls < $1
+        RETURNING calls
+        "#,
+        cap.map(i64::from),
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(calls.map(i64::from))
+}
+
+/// Extraction calls made today (UTC)
+pub async fn extraction_calls_today(pool: &PgPool) -> Result<i64> {
+    let calls = sqlx::query_scalar!(
+        "SELECT calls FROM extraction_usage WHERE day = (NOW() AT TIME ZONE 'UTC')::date",
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(calls.unwrap_or(0) as i64)
+}
+
+/// Defer a turn's extraction until quota is available
+pub async fn queue_extraction(pool: &PgPool, turn_id: Uuid) -> Result<()> {
+    sqlx::query!(
+        "INSERT INTO extraction_queue (turn_id) VALUES ($1) ON CONFLICT (turn_id) DO NOTHING",
+        turn_id,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Queued turn IDs, oldest first
+pub async fn list_extraction_queue(pool: &PgPool, limit: i64) -> Result<Vec<Uuid>> {
+    let ids = sqlx::query_scalar!("SELECT turn_id FROM extraction_queue ORDER BY queued_at LIMIT $1", limit)
+        .fetch_all(pool)
+        .await?;
+    Ok(ids)
+}
+
+/// Number of turns waiting for extraction
+pub async fn count_extraction_queue(pool: &PgPool) -> Result<i64> {
+    let count = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM extraction_queue"#)
+        .fetch_one(pool)
+        .await?;
+    Ok(count)
+}
+
+/// Remove a turn from the extraction queue
+pub async fn dequeue_extraction(pool: &PgPool, turn_id: Uuid) -> Result<()> {
+    sqlx::query!("DELETE FROM extraction_queue WHERE turn_id = $1", turn_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// ============================================================================
+// Extraction Attempts
+// ============================================================================
+
+/// How far an extraction attempt got
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtractionStatus {
+    /// The worker started
+    Spawned,
+    /// The backend's output was parsed into a memory
+    Parsed,
+    /// The memory was saved
+    Saved,
+    /// Nothing was saved, by design: over the cap, a duplicate, rejected
+    Skipped,
+    /// The backend failed or its output could not be used
+    Failed,
+}
+
+impl ExtractionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Spawned => "spawned",
+            Self::Parsed => "parsed",
+            Self::Saved => "saved",
+            Self::Skipped => "skipped",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// One recorded extraction attempt
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractionAttempt {
+    pub id: Uuid,
+    pub turn_id: Option<Uuid>,
+    pub turn_number: Option<i32>,
+    /// What ran it: `worker` or `queued`
+    pub source: String,
+    pub status: String,
+    pub reason: Option<String>,
+    pub memory_id: Option<Uuid>,
+    /// The backend was asked again with the repair prompt
+    pub retried: bool,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// Record the start of an extraction attempt
+pub async fn start_extraction(
+    pool: &PgPool,
+    session_id: Option<Uuid>,
+    turn_id: Option<Uuid>,
+    source: &str,
+) -> Result<Uuid> {
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO extractions (session_id, turn_id, source, status)
+        VALUES ($1, $2, $3, 'spawned')
+        RETURNING id
+        "#,
+        session_id,
+        turn_id,
+        source,
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(id)
+}
+
+/// Move an extraction attempt on to `status`; every status after parsed
+/// finishes it
+pub async fn update_extraction(
+    pool: &PgPool,
+    id: Uuid,
+    status: ExtractionStatus,
+    reason: Option<&str>,
+    memory_id: Option<Uuid>,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE extractions
+        SET status = $2::text, reason = $3, memory_id = $4,
+            finished_at = CASE WHEN $2 IN ('saved', 'skipped', 'failed') THEN NOW() END
+        WHERE id = $1
+        "#,
+        id,
+        status.as_str(),
+        reason,
+        memory_id,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Mark an extraction attempt as retried with the repair prompt
+pub async fn mark_extraction_retried(pool: &PgPool, id: Uuid) -> Result<()> {
+    sqlx::query!("UPDATE extractions SET retried = true WHERE id = $1", id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// A session's extraction attempts, newest first
+pub async fn list_extractions(pool: &PgPool, session_id: Uuid, limit: i64) -> Result<Vec<ExtractionAttempt>> {
+    let attempts = sqlx::query_as!(
+        ExtractionAttempt,
+        r#"
+        SELECT e.id, e.turn_id, t.turn_number AS "turn_number?", e.source, e.status, e.reason,
+               e.memory_id, e.retried, e.started_at, e.finished_at
+        FROM extractions e
+        LEFT JOIN conversation_turns t ON t.id = e.turn_id
+        WHERE e.session_id = $1
+        ORDER BY e.started_at DESC
+        LIMIT $2
+        "#,
+        session_id,
+        limit,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(attempts)
+}
+
+/// Extraction attempts started on the last complete UTC day, and how many
+/// of them failed
+pub async fn extraction_failures_last_day(pool: &PgPool) -> Result<(i64, i64)> {
+    let row = sqlx::query!(
+        r#"
+        SELECT COUNT(*) AS "attempts!",
+               COUNT(*) FILTER (WHERE status = 'failed') AS "failed!"
+        FROM extractions
+        WHERE (started_at AT TIME ZONE 'UTC')::date = (NOW() AT TIME ZONE 'UTC')::date - 1
+        "#,
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok((row.attempts, row.failed))
+}
+
+// ============================================================================
+// Hook Timings
+// ============================================================================
+
+/// One recorded hook run
+#[derive(Debug, Clone)]
+pub struct HookTiming {
+    pub turn_id: Option<Uuid>,
+    pub hook: String,
+    pub duration_ms: i32,
+    pub success: bool,
+}
+
+/// Record how long a hook run took.
+///
+/// Without a known `session_id`, the session is looked up by its Claude ID.
+#[allow(clippy::too_many_arguments)]
+pub async fn record_hook_timing(
+    pool: &PgPool,
+    session_id: Option<Uuid>,
+    claude_session_id: Option<&str>,
+    turn_id: Option<Uuid>,
+    hook: &str,
+    started_at: DateTime<Utc>,
+    duration_ms: i32,
+    success: bool,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO hook_timings (session_id, turn_id, hook, started_at, duration_ms, success)
+        VALUES (
+          COALESCE($1, (SELECT id FROM sessions WHERE claude_session_id = $2)),
+          $3, $4, $5, $6, $7
+        )
+        "#,
+        session_id,
+        claude_session_id,
+        turn_id,
+        hook,
+        started_at,
+        duration_ms,
+        success,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// A session's hook runs, oldest first
+pub async fn list_hook_timings(pool: &PgPool, session_id: Uuid) -> Result<Vec<HookTiming>> {
+    let timings = sqlx::query_as!(
+        HookTiming,
+        r#"
+        SELECT turn_id, hook, duration_ms, success
+        FROM hook_timings
+        WHERE session_id = $1
+        ORDER BY started_at
+        "#,
+        session_id,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(timings)
+}
+
+// ============================================================================
+// Memory Citations
+// ============================================================================
+
+/// How a memory reached a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CitationKind {
+    /// Loaded into the session start context
+    Injected,
+    /// Returned by a memory command run during a turn
+    Cited,
+}
+
+impl CitationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Injected => "injected",
+            Self::Cited => "cited",
+        }
+    }
+}
+
+/// One use of a memory, with the session and turn it happened in
+#[derive(Debug, Clone)]
+pub struct MemoryCitation {
+    pub kind: String,
+    pub session_id: Option<Uuid>,
+    pub claude_session_id: Option<String>,
+    pub project_path: Option<String>,
+    pub turn_number: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Record that memories were used in a session.
+///
+/// IDs that are not memories are ignored. Returns how many citations were
+/// recorded.
+pub async fn record_citations(
+    pool: &PgPool,
+    memory_ids: &[Uuid],
+    session_id: Option<Uuid>,
+    turn_id: Option<Uuid>,
+    kind: CitationKind,
+) -> Result<u64> {
+    if memory_ids.is_empty() {
+        return Ok(0);
+    }
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO memory_citations (memory_id, session_id, turn_id, kind)
+        SELECT id, $2, $3, $4 FROM memories WHERE id = ANY($1)
+        "#,
+        memory_ids,
+        session_id,
+        turn_id,
+        kind.as_str(),
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// A memory's recorded uses, newest first
+pub async fn list_memory_citations(pool: &PgPool, memory_id: Uuid) -> Result<Vec<MemoryCitation>> {
+    let citations = sqlx::query_as!(
+        MemoryCitation,
+        r#"
+        SELECT c.kind, c.session_id, s.claude_session_id AS "claude_session_id?",
+               s.project_path AS "project_path?", t.turn_number AS "turn_number?", c.created_at
+        FROM memory_citations c
+        LEFT JOIN sessions s ON s.id = c.session_id
+        LEFT JOIN conversation_turns t ON t.id = c.turn_id
+        WHERE c.memory_id = $1
+        ORDER BY c.created_at DESC, c.id DESC
+        "#,
+        memory_id,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(citations)
+}
+
+/// A memory cited in a session, for export-conversations
+#[derive(Debug, Clone)]
+pub struct SessionCitation {
+    pub session_id: Uuid,
+    /// None for memories injected at session start
+    pub turn_id: Option<Uuid>,
+    pub kind: String,
+    pub memory_id: Uuid,
+    pub memory_type: String,
+    pub summary: String,
+}
+
+/// The citations of the given sessions, oldest first
+pub async fn list_session_citations(pool: &PgPool, session_ids: &[Uuid]) -> Result<Vec<SessionCitation>> {
+    if session_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let citations = sqlx::query_as!(
+        SessionCitation,
+        r#"
+        SELECT c.session_id AS "session_id!", c.turn_id, c.kind, c.memory_id,
+               m.type AS memory_type, m.summary
+        FROM memory_citations c
+        JOIN memories m ON m.id = c.memory_id
+        WHERE c.session_id = ANY($1)
+        ORDER BY c.created_at, c.id
+        "#,
+        session_ids,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(citations)
+}
+
+// ============================================================================
+// Staged Memories
+// ============================================================================
+
+/// A candidate from propose-memory awaiting commit-memory
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StagedMemory {
+    pub id: Uuid,
+    #[sqlx(rename = "type")]
+    pub memory_type: MemoryType,
+    pub scope: Scope,
+    pub project_path: Option<String>,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub confidence: Confidence,
+    pub source_session_id: Option<Uuid>,
+    pub source_turn_id: Option<Uuid>,
+    pub supersedes: Option<Uuid>,
+    pub metadata: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// An active memory resembling some content
+#[derive(Debug, Clone)]
+pub struct SimilarMemory {
+    pub id: Uuid,
+    pub summary: String,
+    pub similarity: f64,
+}
+
+const STAGED_COLUMNS: &str = "id, type, scope, project_path, content, tags, confidence, \
+    source_session_id, source_turn_id, supersedes, metadata, created_at, expires_at";
+
+/// Stage a candidate memory that expires after `ttl_hours`
+#[allow(clippy::too_many_arguments)]
+pub async fn stage_memory(
+    pool: &PgPool,
+    memory_type: MemoryType,
+    scope: Scope,
+    project_path: Option<&str>,
+    content: &str,
+    tags: &[String],
+    confidence: Confidence,
+    source_session_id: Option<Uuid>,
+    source_turn_id: Option<Uuid>,
+    supersedes: Option<Uuid>,
+    metadata: Option<&serde_json::Value>,
+    ttl_hours: u32,
+) -> Result<StagedMemory> {
+    let staged = sqlx::query_as(&format!(
+        r#"
+        INSERT INTO staged_memories (type, scope, project_path, content, tags, confidence,
+                                     source_session_id, source_turn_id, supersedes, metadata,
+                                     expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, NOW() + make_interval(hours => $11))
+        RETURNING {}
+        "#,
+        STAGED_COLUMNS
+    ))
+    .bind(memory_type.as_str())
+    .bind(scope.as_str())
+    .bind(project_path)
+    .bind(content)
+    .bind(tags)
+    .bind(confidence.as_str())
+    .bind(source_session_id)
+    .bind(source_turn_id)
+    .bind(supersedes)
+    .bind(metadata)
+    .bind(ttl_hours as i32)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(staged)
+}
+
+pub async fn get_staged_memory(pool: &PgPool, id: Uuid) -> Result<Option<StagedMemory>> {
+    let staged = sqlx::query_as(&format!("SELECT {} FROM staged_memories WHERE id = $1", STAGED_COLUMNS))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(staged)
+}
+
+pub async fn delete_staged_memory(pool: &PgPool, id: Uuid) -> Result<()> {
+    sqlx::query!("DELETE FROM staged_memories WHERE id = $1", id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Staged memories past their expiry, oldest first
+pub async fn list_expired_staged(pool: &PgPool) -> Result<Vec<StagedMemory>> {
+    let staged = sqlx::query_as(&format!(
+        "SELECT {} FROM staged_memories WHERE expires_at <= NOW() ORDER BY created_at",
+        STAGED_COLUMNS
+    ))
+    .fetch_all(pool)
+    .await?;
+    Ok(staged)
+}
+
+/// Active memories of a type whose trigram similarity to `content` reaches
+/// `min_similarity`, most similar first. Empty at 1.0 and above.
+pub async fn find_similar_memories(
+    pool: &PgPool,
+    memory_type: MemoryType,
+    content: &str,
+    min_similarity: f64,
+    limit: i64,
+) -> Result<Vec<SimilarMemory>> {
+    if !fuzzy_dedup_enabled(min_similarity) {
+        return Ok(Vec::new());
+    }
+    let similar = sqlx::query_as!(
+        SimilarMemory,
+        r#"
+        SELECT id, summary,
+               round(similarity(content, $2)::numeric, 3)::float8 AS "similarity!"
+        FROM memories
+        WHERE type = $1 AND is_active AND similarity(content, $2) >= $3::float8
+        ORDER BY similarity(content, $2) DESC
+        LIMIT $4
+        "#,
+        memory_type.as_str(),
+        content,
+        min_similarity,
+        limit,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(similar)
+}
+
+/// An active memory similar enough to a new one to check for a conflict
+#[derive(Debug, Clone)]
+pub struct ConflictCandidate {
+    pub id: Uuid,
+    pub summary: String,
+    pub content: String,
+    pub similarity: f64,
+}
+
+/// Active memories of a type visible from `scope` and `project_path` (as in
+/// [`find_duplicate`]) whose trigram similarity to `content` reaches
+/// `min_similarity`, most similar first, other than those in `exclude`.
+/// Empty at 1.0 and above.
+#[allow(clippy::too_many_arguments)]
+pub async fn find_conflict_candidates(
+    pool: &PgPool,
+    memory_type: MemoryType,
+    content: &str,
+    min_similarity: f64,
+    scope: Option<Scope>,
+    project_path: Option<&str>,
+    exclude: &[Uuid],
+    limit: i64,
+) -> Result<Vec<ConflictCandidate>> {
+    if !fuzzy_dedup_enabled(min_similarity) {
+        return Ok(Vec::new());
+    }
+    let candidates = sqlx::query_as!(
+        ConflictCandidate,
+        r#"
+        SELECT id, summary, content,
+               round(similarity(content, $2)::numeric, 3)::float8 AS "similarity!"
+        FROM memories
+        WHERE type = $1
+          AND is_active
+          AND NOT (id = ANY($6))
+          AND ($4::text IS NULL OR scope = 'global'
+               OR ($4 = 'project' AND project_path = $5))
+          AND similarity(content, $2) >= $3::float8
+        ORDER BY similarity(content, $2) DESC
+        LIMIT $7
+        "#,
+        memory_type.as_str(),
+        content,
+        min_similarity,
+        scope.map(|s| s.as_str()),
+        project_path,
+        exclude,
+        limit,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(candidates)
+}
+
+// ============================================================================
+// Session Mutes
+// ============================================================================
+
+/// Mute a memory for a session; false when it already was muted
+pub async fn mute_memory(pool: &PgPool, session_id: Uuid, memory_id: Uuid) -> Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO session_mutes (session_id, memory_id)
+        VALUES ($1, $2)
+        ON CONFLICT DO NOTHING
+        "#,
+        session_id,
+        memory_id,
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Unmute a memory for a session; false when it was not muted
+pub async fn unmute_memory(pool: &PgPool, session_id: Uuid, memory_id: Uuid) -> Result<bool> {
+    let result = sqlx::query!(
+        "DELETE FROM session_mutes WHERE session_id = $1 AND memory_id = $2",
+        session_id,
+        memory_id,
+    )
+    .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Memories muted for a session, oldest mute first
+pub async fn list_muted_memories(pool: &PgPool, session_id: Uuid) -> Result<Vec<Uuid>> {
+    let ids = sqlx::query_scalar!(
+        "SELECT memory_id FROM session_mutes WHERE session_id = $1 ORDER BY created_at",
+        session_id,
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(ids)
+}
+
+// ============================================================================
+// Memory Links
+// ============================================================================
+
+/// Link `source` to `target` with `relation`; false when that link existed
+pub async fn insert_memory_link(
+    pool: &PgPool,
+    source: Uuid,
+    target: Uuid,
+    relation: &str,
+) -> Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO memory_links (source_id, target_id, relation)
+        VALUES ($1, $2, $3)
+        ON CONFLICT DO NOTHING
+        "#,
+        source,
+        target,
+        relation,
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Remove the links between `a` and `b` in either direction, only those of
+/// `relation` when given; returns how many were removed
+pub async fn delete_memory_links(
+    pool: &PgPool,
+    a: Uuid,
+    b: Uuid,
+    relation: Option<&str>,
+) -> Result<u64> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM memory_links
+        WHERE ((source_id = $1 AND target_id = $2) OR (source_id = $2 AND target_id = $1))
+          AND ($3::text IS NULL OR relation = $3)
+        "#,
+        a,
+        b,
+        relation,
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Memories linked to `id` in either direction, oldest link first
+pub async fn list_memory_links(pool: &PgPool, id: Uuid) -> Result<Vec<LinkedMemory>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT relation,
+               source_id = $1 AS "outgoing!",
+               CASE WHEN source_id = $1 THEN target_id ELSE source_id END AS "other_id!"
+        FROM memory_links
+        WHERE source_id = $1 OR target_id = $1
+        ORDER BY created_at, relation
+        "#,
+        id,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut links = Vec::with_capacity(rows.len());
+    for row in rows {
+        if let Some(memory) = get_memory(pool, row.other_id).await? {
+            links.push(LinkedMemory {
+                relation: row.relation,
+                direction: if row.outgoing { "outgoing" } else { "incoming" }.to_string(),
+                memory: memory.to_summary(),
+            });
+        }
+    }
+    Ok(links)
+}
+
+// ============================================================================
+// Project Registry
+// ============================================================================
+
+/// A registered project and the other paths its memories may be stored under
+#[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    pub id: Uuid,
+    pub canonical_path: String,
+    /// Oldest first
+    pub aliases: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Projects with their aliases, before `WHERE` and `GROUP BY p.id`
+const PROJECT_SELECT: &str = "SELECT p.id, p.canonical_path, p.created_at, \
+    COALESCE(array_agg(a.path ORDER BY a.created_at, a.path) FILTER (WHERE a.path IS NOT NULL), '{}') \
+    AS aliases \
+    FROM projects p LEFT JOIN project_aliases a ON a.project_id = p.id";
+
+/// The project `path` is the canonical path or an alias of
+pub async fn find_project(pool: &PgPool, path: &str) -> Result<Option<Project>> {
+    let project = sqlx::query_as(&format!(
+        "{} WHERE p.canonical_path = $1 \
+            OR p.id IN (SELECT project_id FROM project_aliases WHERE path = $1) \
+         GROUP BY p.id",
+        PROJECT_SELECT
+    ))
+    .bind(path)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(project)
+}
+
+/// Every registered project, by canonical path
+pub async fn list_projects(pool: &PgPool) -> Result<Vec<Project>> {
+    let projects = sqlx::query_as(&format!(
+        "{} GROUP BY p.id ORDER BY p.canonical_path",
+        PROJECT_SELECT
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(projects)
+}
+
+/// Record `alias` as another path of the project at `canonical_path`,
+/// registering the project if needed. An alias already recorded is left
+/// as it is.
+pub async fn add_project_alias(pool: &PgPool, canonical_path: &str, alias: &str) -> Result<()> {
+    sqlx::query!(
+        r#"
+        WITH project AS (
+            INSERT INTO projects (canonical_path)
+            VALUES ($1)
+            ON CONFLICT (canonical_path) DO UPDATE SET canonical_path = EXCLUDED.canonical_path
+            RETURNING id
+        )
+        INSERT INTO project_aliases (path, project_id)
+        SELECT $2, id FROM project
+        ON CONFLICT (path) DO NOTHING
+        "#,
+        canonical_path,
+        alias,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Remove an alias, and its project once it has no other; whether the
+/// alias was recorded
+pub async fn remove_project_alias(pool: &PgPool, alias: &str) -> Result<bool> {
+    let removed = sqlx::query_scalar!(
+        r#"
+        WITH removed AS (
+            DELETE FROM project_aliases WHERE path = $1 RETURNING project_id
+        ), emptied AS (
+            DELETE FROM projects p
+            WHERE p.id IN (SELECT project_id FROM removed)
+              AND NOT EXISTS (
+                  SELECT 1 FROM project_aliases a WHERE a.project_id = p.id AND a.path <> $1
+              )
+        )
+        SELECT COUNT(*) AS "count!" FROM removed
+        "#,
+        alias,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(removed > 0)
+}
+
+/// The paths memories of `paths` may be stored under: for each path, its
+/// project's canonical path followed by the aliases, or the path itself
+/// when it is not registered
+pub async fn resolve_project_paths(pool: &PgPool, paths: &[String]) -> Result<Vec<String>> {
+    let mut resolved: Vec<String> = Vec::new();
+    for path in paths {
+        let known = match find_project(pool, path).await? {
+            Some(project) => std::iter::once(project.canonical_path).chain(project.aliases).collect(),
+            None => vec![path.clone()],
+        };
+        for known in known {
+            if !resolved.contains(&known) {
+                resolved.push(known);
+            }
+        }
+    }
+    Ok(resolved)
+}
+
+// ============================================================================
+// Workspaces
+// ============================================================================
+
+/// A named group of projects whose memories can be read together
+#[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Workspace {
+    pub id: Uuid,
+    pub name: String,
+    /// In the order they were added
+    pub projects: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Workspaces with their projects, before `WHERE` and `GROUP BY w.id`
+const WORKSPACE_SELECT: &str = "SELECT w.id, w.name, w.created_at, \
+    COALESCE(array_agg(p.project_path ORDER BY p.added_at, p.project_path) \
+        FILTER (WHERE p.project_path IS NOT NULL), '{}') AS projects \
+    FROM workspaces w LEFT JOIN workspace_projects p ON p.workspace_id = w.id";
+
+/// The workspace called `name`
+pub async fn find_workspace(pool: &PgPool, name: &str) -> Result<Option<Workspace>> {
+    let workspace = sqlx::query_as(&format!("{} WHERE w.name = $1 GROUP BY w.id", WORKSPACE_SELECT))
+        .bind(name)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(workspace)
+}
+
+/// Every workspace, by name
+pub async fn list_workspaces(pool: &PgPool) -> Result<Vec<Workspace>> {
+    let workspaces = sqlx::query_as(&format!("{} GROUP BY w.id ORDER BY w.name", WORKSPACE_SELECT))
+        .fetch_all(pool)
+        .await?;
+
+    Ok(workspaces)
+}
+
+/// Create an empty workspace; false when one is already called `name`
+pub async fn create_workspace(pool: &PgPool, name: &str) -> Result<bool> {
+    let result = sqlx::query!(
+        "INSERT INTO workspaces (name) VALUES ($1) ON CONFLICT (name) DO NOTHING",
+        name,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
 
-    let row = sqlx::query(
+/// Add a project to a workspace; false when it was already in it
+pub async fn add_workspace_project(pool: &PgPool, workspace_id: Uuid, project_path: &str) -> Result<bool> {
+    let result = sqlx::query!(
         r#"
-        INSERT INTO sessions (claude_session_id, project_path, git_status)
-        VALUES ($1, $2, $3)
-        RETURNING id, claude_session_id, project_path, git_status, models_used,
-                  status, summary, started_at, ended_at, created_at
+        INSERT INTO workspace_projects (workspace_id, project_path)
+        VALUES ($1, $2)
+        ON CONFLICT DO NOTHING
         "#,
+        workspace_id,
+        project_path,
     )
-    .bind(claude_session_id)
-    .bind(project_path)
-    .bind(&git_status_json)
-    .fetch_one(pool)
+    .execute(pool)
     .await?;
 
-    row_to_session(&row)
+    Ok(result.rows_affected() > 0)
 }
 
-/// Find session by database UUID
-pub async fn find_session_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Session>> {
-    let row = sqlx::query(
+/// `project_paths` followed by the other projects of the workspaces they
+/// are in, with their aliases
+pub async fn with_workspace_projects(pool: &PgPool, project_paths: &[String]) -> Result<Vec<String>> {
+    if project_paths.is_empty() {
+        return Ok(Vec::new());
+    }
+    let siblings = sqlx::query_scalar!(
         r#"
-        SELECT id, claude_session_id, project_path, git_status, models_used,
-               status, summary, started_at, ended_at, created_at
-        FROM sessions
-        WHERE id = $1
+        SELECT p.project_path
+        FROM workspace_projects p
+        WHERE p.workspace_id IN (
+            SELECT workspace_id FROM workspace_projects WHERE project_path = ANY($1)
+        )
+          AND NOT p.project_path = ANY($1)
+        GROUP BY p.project_path
+        ORDER BY MIN(p.added_at), p.project_path
         "#,
+        project_paths,
     )
-    .bind(id)
-    .fetch_optional(pool)
+    .fetch_all(pool)
     .await?;
 
-    match row {
-        Some(r) => Ok(Some(row_to_session(&r)?)),
-        None => Ok(None),
+    let mut paths = project_paths.to_vec();
+    for path in resolve_project_paths(pool, &siblings).await? {
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
     }
+    Ok(paths)
 }
 
-/// Find session by Claude session ID
-pub async fn find_session_by_claude_id(
+// ============================================================================
+// Project Briefings
+// ============================================================================
+
+/// A project's pinned briefing
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectBriefing {
+    pub project_path: String,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The briefing of the first project in `project_paths` that has one
+pub async fn get_briefing(
     pool: &PgPool,
-    claude_session_id: &str,
-) -> Result<Option<Session>> {
-    let row = sqlx::query(
+    project_paths: &[String],
+) -> Result<Option<ProjectBriefing>> {
+    if project_paths.is_empty() {
+        return Ok(None);
+    }
+    let briefing = sqlx::query_as!(
+        ProjectBriefing,
         r#"
-        SELECT id, claude_session_id, project_path, git_status, models_used,
-               status, summary, started_at, ended_at, created_at
-        FROM sessions
-        WHERE claude_session_id = $1
+        SELECT project_path, content, created_at, updated_at
+        FROM project_briefings
+        WHERE project_path = ANY($1)
+        ORDER BY array_position($1, project_path)
+        LIMIT 1
         "#,
+        project_paths,
     )
-    .bind(claude_session_id)
     .fetch_optional(pool)
     .await?;
 
-    match row {
-        Some(r) => Ok(Some(row_to_session(&r)?)),
-        None => Ok(None),
-    }
+    Ok(briefing)
 }
 
-/// End a session
-pub async fn end_session(
+/// Create or replace a project's briefing
+pub async fn set_briefing(
     pool: &PgPool,
-    claude_session_id: &str,
-    summary: Option<&str>,
-) -> Result<Session> {
-    let summary_json = summary.map(|s| serde_json::json!({ "summary": s }));
-
-    let row = sqlx::query(
+    project_path: &str,
+    content: &str,
+) -> Result<ProjectBriefing> {
+    let briefing = sqlx::query_as!(
+        ProjectBriefing,
         r#"
-        UPDATE sessions
-        SET status = 'completed', ended_at = NOW(), summary = COALESCE($2, summary)
-        WHERE claude_session_id = $1
-        RETURNING id, claude_session_id, project_path, git_status, models_used,
-                  status, summary, started_at, ended_at, created_at
+        INSERT INTO project_briefings (project_path, content)
+        VALUES ($1, $2)
+        ON CONFLICT (project_path)
+        DO UPDATE SET content = EXCLUDED.content, updated_at = NOW()
+        RETURNING project_path, content, created_at, updated_at
         "#,
+        project_path,
+        content,
     )
-    .bind(claude_session_id)
-    .bind(&summary_json)
-    .fetch_optional(pool)
+    .fetch_one(pool)
     .await?;
 
-    match row {
-        Some(r) => row_to_session(&r),
-        None => Err(HippocampusError::SessionNotFound(claude_session_id.to_string())),
-    }
+    Ok(briefing)
+}
+
+/// Remove a project's briefing; false when it had none
+pub async fn delete_briefing(pool: &PgPool, project_path: &str) -> Result<bool> {
+    let result = sqlx::query!("DELETE FROM project_briefings WHERE project_path = $1", project_path)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
 }
 
 // ============================================================================
-// Turn Queries
+// Settings
 // ============================================================================
 
-use crate::models::Turn;
+/// A setting's stored value
+pub async fn get_setting(pool: &PgPool, key: &str) -> Result<Option<serde_json::Value>> {
+    let value = sqlx::query_scalar!("SELECT value FROM settings WHERE key = $1", key)
+        .fetch_optional(pool)
+        .await?;
+    Ok(value)
+}
 
-/// Create a new conversation turn
-pub async fn create_turn(
-    pool: &PgPool,
-    session_id: Uuid,
-    turn_number: i32,
-    user_prompt: &str,
-    model_used: Option<&str>,
-) -> Result<Turn> {
-    let row = sqlx::query(
+/// Create or replace a setting
+pub async fn set_setting(pool: &PgPool, key: &str, value: &serde_json::Value) -> Result<()> {
+    sqlx::query!(
         r#"
-        INSERT INTO conversation_turns (session_id, turn_number, user_prompt, model_used, started_at)
-        VALUES ($1, $2, $3, $4, NOW())
-        RETURNING id, session_id, turn_number, user_prompt, assistant_response,
-                  model_used, input_tokens, output_tokens, started_at, ended_at, created_at
+        INSERT INTO settings (key, value)
+        VALUES ($1, $2)
+        ON CONFLICT (key)
+        DO UPDATE SET value = EXCLUDED.value, updated_at = NOW()
         "#,
+        key,
+        value,
     )
-    .bind(session_id)
-    .bind(turn_number)
-    .bind(user_prompt)
-    .bind(model_used)
-    .fetch_one(pool)
+    .execute(pool)
     .await?;
+    Ok(())
+}
+
+// ============================================================================
+// Legacy Import
+// ============================================================================
 
-    row_to_turn(&row)
+/// A memory from a legacy Node.js database, mapped to the current schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedMemory {
+    pub id: Uuid,
+    pub memory_type: MemoryType,
+    pub scope: Scope,
+    pub project_path: Option<String>,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub confidence: Confidence,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub accessed_at: Option<DateTime<Utc>>,
+    pub access_count: i32,
 }
 
-/// Get the next turn number for a session
-pub async fn get_next_turn_number(pool: &PgPool, session_id: Uuid) -> Result<i32> {
-    let count: Option<i32> = sqlx::query_scalar(
-        "SELECT MAX(turn_number)::INT4 FROM conversation_turns WHERE session_id = $1",
+/// Columns of a table, in table order.
+///
+/// `table` may be schema-qualified. Empty when the table does not exist.
+pub async fn list_table_columns(pool: &PgPool, table: &str) -> Result<Vec<String>> {
+    let columns = sqlx::query_scalar!(
+        r#"
+        SELECT attname::text AS "name!"
+        FROM pg_attribute
+        WHERE attrelid = to_regclass($1) AND attnum > 0 AND NOT attisdropped
+        ORDER BY attnum
+        "#,
+        table,
     )
-    .bind(session_id)
-    .fetch_one(pool)
+    .fetch_all(pool)
     .await?;
 
-    Ok(count.map(|n| n + 1).unwrap_or(1))
+    Ok(columns)
 }
 
-/// Find turn by ID
-pub async fn find_turn_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Turn>> {
-    let row = sqlx::query(
+/// Every row of a table as a JSON object keyed by column name.
+///
+/// `table` is interpolated into the query, so callers must pass a checked
+/// identifier.
+pub async fn fetch_rows_as_json(pool: &PgPool, table: &str) -> Result<Vec<serde_json::Value>> {
+    let rows = sqlx::query_scalar(&format!("SELECT to_jsonb(t) FROM {} t", table))
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows)
+}
+
+/// Insert an imported memory, keeping its ID and timestamps.
+///
+/// Returns false when the ID is taken or an identical memory already exists
+/// in the same scope and project, so an import can be re-run.
+pub async fn import_memory(pool: &PgPool, memory: &ImportedMemory) -> Result<bool> {
+    let row = sqlx::query_scalar!(
         r#"
-        SELECT id, session_id, turn_number, user_prompt, assistant_response,
-               model_used, input_tokens, output_tokens, started_at, ended_at, created_at
-        FROM conversation_turns
-        WHERE id = $1
+        INSERT INTO memories (id, type, scope, project_path, content, tags, confidence,
+                              created_at, updated_at, accessed_at, access_count, summary)
+        SELECT $1::uuid, $2::text, $3::text, $4::text, $5::text, $6::text[], $7::text,
+               COALESCE($8::timestamptz, now()), COALESCE($9::timestamptz, $8, now()),
+               $10::timestamptz, $11::int4, $12::text
+        WHERE NOT EXISTS (
+          SELECT 1 FROM memories
+          WHERE content = $5 AND scope = $3 AND project_path IS NOT DISTINCT FROM $4
+        )
+        ON CONFLICT (id) DO NOTHING
+        RETURNING id
         "#,
+        memory.id,
+        memory.memory_type.as_str(),
+        memory.scope.as_str(),
+        memory.project_path,
+        memory.content,
+        &memory.tags,
+        memory.confidence.as_str(),
+        memory.created_at,
+        memory.updated_at,
+        memory.accessed_at,
+        memory.access_count,
+        summarize(&memory.content),
     )
-    .bind(id)
     .fetch_optional(pool)
     .await?;
 
-    match row {
-        Some(r) => Ok(Some(row_to_turn(&r)?)),
-        None => Ok(None),
-    }
+    Ok(row.is_some())
 }
 
-/// Update turn with assistant response
-pub async fn update_turn(
-    pool: &PgPool,
-    turn_id: Uuid,
-    response: &str,
-    input_tokens: Option<i32>,
-    output_tokens: Option<i32>,
-) -> Result<Turn> {
-    let row = sqlx::query(
+/// Memories with the given IDs, in no particular order
+pub async fn get_memories_by_ids(pool: &PgPool, ids: &[Uuid]) -> Result<Vec<Memory>> {
+    let memories = sqlx::query_as!(
+        Memory,
         r#"
-        UPDATE conversation_turns
-        SET assistant_response = $2, input_tokens = $3, output_tokens = $4, ended_at = NOW()
-        WHERE id = $1
-        RETURNING id, session_id, turn_number, user_prompt, assistant_response,
-                  model_used, input_tokens, output_tokens, started_at, ended_at, created_at
+        SELECT id, type AS "memory_type: MemoryType", scope AS "scope: Scope", project_path, content,
+               tags AS "tags!", confidence AS "confidence!: Confidence", source_session_id, source_turn_id,
+               created_at AS "created_at!", updated_at AS "updated_at!", accessed_at, access_count AS "access_count!",
+               superseded_by, superseded_at, is_active AS "is_active!",
+               NULLIF(metadata, '{}') AS metadata, summary, archived_at, pinned
+        FROM memories
+        WHERE id = ANY($1)
         "#,
+        ids,
     )
-    .bind(turn_id)
-    .bind(response)
-    .bind(input_tokens)
-    .bind(output_tokens)
-    .fetch_optional(pool)
+    .fetch_all(pool)
     .await?;
 
-    match row {
-        Some(r) => row_to_turn(&r),
-        None => Err(HippocampusError::NotFound(format!("Turn not found: {}", turn_id))),
-    }
+    Ok(memories)
 }
 
 // ============================================================================
-// Helper Functions
+// Fixtures
 // ============================================================================
 
-fn row_to_turn(row: &sqlx::postgres::PgRow) -> Result<Turn> {
-    Ok(Turn {
-        id: row.get("id"),
-        session_id: row.get("session_id"),
-        turn_number: row.get("turn_number"),
-        user_prompt: row.get("user_prompt"),
-        assistant_response: row.get("assistant_response"),
-        model_used: row.get("model_used"),
-        input_tokens: row.get("input_tokens"),
-        output_tokens: row.get("output_tokens"),
-        started_at: row.get("started_at"),
-        ended_at: row.get("ended_at"),
-        created_at: row.get("created_at"),
-    })
-}
+/// Marker in the metadata of generated memories
+pub const FIXTURE_METADATA: &str = r#"{"fixture": true}"#;
 
-fn row_to_session(row: &sqlx::postgres::PgRow) -> Result<Session> {
-    let status_str: String = row.get("status");
-    let git_status_json: Option<serde_json::Value> = row.get("git_status");
-
-    let git_status = git_status_json
-        .map(|v| serde_json::from_value::<GitStatus>(v).ok())
-        .flatten();
-
-    Ok(Session {
-        id: row.get("id"),
-        claude_session_id: row.get("claude_session_id"),
-        project_path: row.get("project_path"),
-        git_status,
-        models_used: row.get("models_used"),
-        status: status_str.parse()?,
-        summary: row.get("summary"),
-        started_at: row.get("started_at"),
-        ended_at: row.get("ended_at"),
-        created_at: row.get("created_at"),
-    })
-}
+/// Prefix of the Claude session ID of generated sessions
+pub const FIXTURE_SESSION_PREFIX: &str = "fixture-";
 
-fn row_to_memory(row: &sqlx::postgres::PgRow) -> Result<Memory> {
-    let type_str: String = row.get("type");
-    let scope_str: String = row.get("scope");
-    let confidence_str: String = row.get("confidence");
-
-    Ok(Memory {
-        id: row.get("id"),
-        memory_type: type_str.parse()?,
-        scope: scope_str.parse()?,
-        project_path: row.get("project_path"),
-        content: row.get("content"),
-        tags: row.get("tags"),
-        confidence: confidence_str.parse()?,
-        source_session_id: row.get("source_session_id"),
-        source_turn_id: row.get("source_turn_id"),
-        created_at: row.get("created_at"),
-        updated_at: row.get("updated_at"),
-        accessed_at: row.get("accessed_at"),
-        access_count: row.get("access_count"),
-        superseded_by: row.get("superseded_by"),
-        superseded_at: row.get("superseded_at"),
-        is_active: row.get("is_active"),
-    })
+/// A generated session, ended and summarized
+#[derive(Debug, Clone)]
+pub struct FixtureSession {
+    pub id: Uuid,
+    pub claude_session_id: String,
+    pub project_path: String,
+    pub git_status: serde_json::Value,
+    pub summary: serde_json::Value,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
 }
 
-// ============================================================================
-// Tool Call Recording
-// ============================================================================
-
-/// Tool call record
-#[derive(Debug, Clone, serde::Serialize)]
-pub struct ToolCall {
+/// A generated project memory. It is inactive when superseded.
+#[derive(Debug, Clone)]
+pub struct FixtureMemory {
     pub id: Uuid,
-    pub session_id: Option<Uuid>,
-    pub turn_id: Option<Uuid>,
-    pub tool_name: String,
-    pub parameters: Option<serde_json::Value>,
-    pub result_summary: Option<String>,
-    pub called_at: chrono::DateTime<chrono::Utc>,
+    pub memory_type: MemoryType,
+    pub project_path: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub confidence: Confidence,
+    pub source_session_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub accessed_at: Option<DateTime<Utc>>,
+    pub access_count: i32,
+    pub superseded_by: Option<Uuid>,
+    pub superseded_at: Option<DateTime<Utc>>,
+    pub needs_review: bool,
 }
 
-/// Record a tool call to the database
-pub async fn record_tool_call(
+/// Insert generated sessions and memories in one transaction, keeping
+/// their IDs and timestamps
+pub async fn insert_fixtures(
     pool: &PgPool,
-    session_id: Option<Uuid>,
-    turn_id: Option<Uuid>,
-    tool_name: &str,
-    parameters: Option<serde_json::Value>,
-    result_summary: Option<String>,
-) -> Result<ToolCall> {
-    let row = sqlx::query(
+    sessions: &[FixtureSession],
+    memories: &[FixtureMemory],
+) -> Result<()> {
+    let metadata: serde_json::Value = serde_json::from_str(FIXTURE_METADATA)?;
+    let mut tx = pool.begin().await?;
+
+    for session in sessions {
+        sqlx::query!(
+            r#"
+            INSERT INTO sessions (id, claude_session_id, project_path, git_status, status, summary,
+                                  started_at, ended_at, created_at)
+            VALUES ($1, $2, $3, $4, 'completed', $5, $6, $7, $6)
+            "#,
+            session.id,
+            session.claude_session_id,
+            session.project_path,
+            session.git_status,
+            session.summary,
+            session.started_at,
+            session.ended_at,
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    // Links are set once every memory exists, as successors may come later
+    for memory in memories {
+        sqlx::query!(
+            r#"
+            INSERT INTO memories (id, type, scope, project_path, content, tags, confidence,
+                                  source_session_id, created_at, updated_at, accessed_at,
+                                  access_count, needs_review, review_reason, review_flagged_at,
+                                  metadata, summary)
+            VALUES ($1, $2, 'project', $3, $4, $5, $6, $7, $8, $9, $10, $11, $12,
+                    CASE WHEN $12::bool THEN 'Generated fixture' END,
+                    CASE WHEN $12::bool THEN $9::timestamptz END,
+                    $13, $14)
+            "#,
+            memory.id,
+            memory.memory_type.as_str(),
+            memory.project_path,
+            memory.content,
+            &memory.tags,
+            memory.confidence.as_str(),
+            memory.source_session_id,
+            memory.created_at,
+            memory.updated_at,
+            memory.accessed_at,
+            memory.access_count,
+            memory.needs_review,
+            metadata,
+            summarize(&memory.content),
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    for memory in memories.iter().filter(|m| m.superseded_by.is_some()) {
+        sqlx::query!(
+            r#"
+            UPDATE memories
+            SET superseded_by = $2, superseded_at = $3, is_active = false
+            WHERE id = $1
+            "#,
+            memory.id,
+            memory.superseded_by,
+            memory.superseded_at,
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Delete every generated memory and session, with the turns and tool
+/// calls recorded against those sessions. Returns the memories and
+/// sessions deleted.
+pub async fn delete_fixtures(pool: &PgPool) -> Result<(i64, i64)> {
+    let metadata: serde_json::Value = serde_json::from_str(FIXTURE_METADATA)?;
+    let session_pattern = format!("{}%", FIXTURE_SESSION_PREFIX);
+    let mut tx = pool.begin().await?;
+
+    let memories = sqlx::query!("DELETE FROM memories WHERE metadata @> $1", metadata)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    sqlx::query!(
         r#"
-        INSERT INTO tool_calls (session_id, turn_id, tool_name, parameters, result_summary)
-        VALUES ($1, $2, $3, $4, $5)
-        RETURNING id, session_id, turn_id, tool_name, parameters, result_summary, called_at
+        DELETE FROM tool_calls
+        WHERE session_id IN (SELECT id FROM sessions WHERE claude_session_id LIKE $1)
         "#,
+        session_pattern,
     )
-    .bind(session_id)
-    .bind(turn_id)
-    .bind(tool_name)
-    .bind(&parameters)
-    .bind(&result_summary)
-    .fetch_one(pool)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        r#"
+        DELETE FROM conversation_turns
+        WHERE session_id IN (SELECT id FROM sessions WHERE claude_session_id LIKE $1)
+        "#,
+        session_pattern,
+    )
+    .execute(&mut *tx)
     .await?;
+    let sessions = sqlx::query!("DELETE FROM sessions WHERE claude_session_id LIKE $1", session_pattern)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
 
-    Ok(ToolCall {
-        id: row.get("id"),
-        session_id: row.get("session_id"),
-        turn_id: row.get("turn_id"),
-        tool_name: row.get("tool_name"),
-        parameters: row.get("parameters"),
-        result_summary: row.get("result_summary"),
-        called_at: row.get("called_at"),
-    })
+    tx.commit().await?;
+    Ok((memories as i64, sessions as i64))
 }
 
 // ============================================================================
@@ -1672,11 +4719,308 @@ mod tests {
             id: Uuid::new_v4(),
             scope: "project".to_string(),
             summary: "Test summary".to_string(),
+            content: "Test summary. With details.".to_string(),
+            similarity: None,
         };
         assert_eq!(info.scope, "project");
         assert_eq!(info.summary, "Test summary");
     }
 
+    #[test]
+    fn test_pick_duplicates_keeps_one_per_group() {
+        let ids: Vec<Uuid> = (1..=3).map(Uuid::from_u128).collect();
+        let (a, b, c) = (ids[0], ids[1], ids[2]);
+
+        // All alike: the first survives
+        assert_eq!(pick_duplicates(&[(a, b), (a, c), (b, c)]), vec![(a, b), (a, c)]);
+        // A chain: C only resembles the removed B, so it stays
+        assert_eq!(pick_duplicates(&[(a, b), (b, c)]), vec![(a, b)]);
+        assert!(pick_duplicates(&[]).is_empty());
+    }
+
+    fn memory(tags: &[&str], confidence: Confidence, access_count: i32) -> Memory {
+        Memory {
+            project_path: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            confidence,
+            access_count,
+            ..crate::models::test_memory("Run migrations before tests", MemoryType::Gotcha)
+        }
+    }
+
+    #[test]
+    fn test_merge_into_combines_fields() {
+        let keep = memory(&["db", "tests"], Confidence::Medium, 3);
+        let duplicate = memory(&["tests", "ci"], Confidence::High, 4);
+        let merge = merge_into(&keep, &duplicate);
+        assert_eq!(merge.kept_id, keep.id);
+        assert_eq!(merge.merged_id, duplicate.id);
+        assert_eq!(merge.tags, vec!["db", "tests", "ci"]);
+        assert_eq!(merge.tags_added, vec!["ci"]);
+        assert_eq!(merge.confidence, Confidence::High);
+        assert_eq!(merge.confidence_raised_from, Some(Confidence::Medium));
+        assert_eq!(merge.access_count, 7);
+    }
+
+    #[test]
+    fn test_merge_into_keeps_higher_confidence() {
+        let keep = memory(&[], Confidence::High, 0);
+        let merge = merge_into(&keep, &memory(&[], Confidence::Low, 0));
+        assert_eq!(merge.confidence, Confidence::High);
+        assert!(merge.confidence_raised_from.is_none());
+        assert!(merge.tags.is_empty());
+    }
+
+    fn filter_sql(filter: MemoryFilter) -> String {
+        let mut query = QueryBuilder::new("SELECT 1 FROM memories");
+        filter.push_where(&mut query);
+        query.sql().to_string()
+    }
+
+    #[test]
+    fn test_memory_filter_current() {
+        let paths = vec!["/p".to_string()];
+        let sql = filter_sql(MemoryFilter::new(None, true, &paths));
+        assert!(sql.contains("project_path = ANY($1)"));
+        assert!(sql.contains("is_active = true"));
+        assert!(sql.contains("archived_at IS NULL"));
+
+        let sql = filter_sql(MemoryFilter {
+            include_archived: true,
+            ..MemoryFilter::new(None, true, &paths)
+        });
+        assert!(!sql.contains("archived_at"));
+    }
+
+    #[test]
+    fn test_memory_filter_as_of_numbering() {
+        let as_of = Some(Utc::now());
+        let sql = filter_sql(MemoryFilter {
+            as_of,
+            ..MemoryFilter::new(Some(Scope::Global), false, &[])
+        });
+        assert!(sql.contains("created_at <= $1"));
+        assert!(!sql.contains("is_active"));
+
+        let paths = vec!["/p".to_string()];
+        let sql = filter_sql(MemoryFilter {
+            as_of,
+            ..MemoryFilter::new(Some(Scope::Project), false, &paths)
+        });
+        assert!(sql.contains("project_path = ANY($1)"));
+        assert!(sql.contains("superseded_at > $3"));
+    }
+
+    #[tokio::test]
+    async fn test_as_of_reads_the_content_in_effect() {
+        use crate::db::migrations::with_scratch_database;
+        use crate::commands::ranking::Recency;
+
+        let Some(result) = with_scratch_database(|pool| async move {
+            let paths = vec!["/work/app".to_string()];
+            let id = insert_memory(
+                &pool,
+                MemoryType::Learning,
+                Scope::Project,
+                Some("/work/app"),
+                "Builds use make",
+                "Builds: make",
+                &[],
+                Confidence::High,
+                None,
+                None,
+                None,
+            )
+            .await?;
+            let edit = MemoryEdit {
+                content: Some("Builds use just".to_string()),
+                ..MemoryEdit::default()
+            };
+            edit_memory(&pool, id, &edit, None).await?;
+            sqlx::query("UPDATE memories SET created_at = NOW() - INTERVAL '2 hours' WHERE id = $1")
+                .bind(id)
+                .execute(&pool)
+                .await?;
+            sqlx::query("UPDATE memory_revisions SET replaced_at = NOW() - INTERVAL '1 hour' WHERE memory_id = $1")
+                .bind(id)
+                .execute(&pool)
+                .await?;
+
+            let before_edit = Utc::now() - chrono::Duration::minutes(90);
+            let then = get_memory_as_of(&pool, id, before_edit).await?.unwrap();
+            let now = get_memory_as_of(&pool, id, Utc::now()).await?.unwrap();
+            let search = |query: &'static str, as_of| {
+                let pool = pool.clone();
+                let paths = paths.clone();
+                async move {
+                    let filters = SearchFilters::default();
+                    let found = search_keyword(
+                        &pool, query, Some(Scope::Project), &paths, false, 10, 0, None, as_of, false, &filters, &Recency,
+                    )
+                    .await?;
+                    Ok::<_, crate::error::HippocampusError>(
+                        found.into_iter().map(|(m, _)| (m.content, m.summary)).collect::<Vec<_>>(),
+                    )
+                }
+            };
+            let found_then = search("make", Some(before_edit)).await?;
+            let missing_then = search("just", Some(before_edit)).await?;
+            let fts_then = search_fts(
+                &pool, "make", Some(Scope::Project), &paths, false, 10, 0, None, Some(before_edit), false,
+                &SearchFilters::default(),
+            )
+            .await?;
+            Ok((then, now, found_then, missing_then, fts_then.len()))
+        })
+        .await
+        else {
+            return;
+        };
+
+        let (then, now, found_then, missing_then, fts_then) = result.unwrap();
+        assert_eq!(then.content, "Builds use make");
+        assert_eq!(then.summary, "Builds: make");
+        assert_eq!(now.content, "Builds use just");
+        assert_eq!(now.summary, "Builds use just");
+        assert_eq!(
+            found_then,
+            vec![("Builds use make".to_string(), "Builds: make".to_string())]
+        );
+        assert!(missing_then.is_empty());
+        assert_eq!(fts_then, 1);
+    }
+
+    #[tokio::test]
+    async fn test_empty_metadata_reads_as_none() {
+        use crate::db::migrations::with_scratch_database;
+
+        let Some(result) = with_scratch_database(|pool| async move {
+            let id: Uuid = sqlx::query_scalar(
+                "INSERT INTO memories (type, scope, content) VALUES ('learning', 'global', 'No metadata') RETURNING id",
+            )
+            .fetch_one(&pool)
+            .await?;
+            let stored: Option<serde_json::Value> = sqlx::query_scalar("SELECT metadata FROM memories WHERE id = $1")
+                .bind(id)
+                .fetch_one(&pool)
+                .await?;
+            let memory = get_memory(&pool, id).await?.unwrap();
+            let (listed, _) = list_recent(&pool, Some(Scope::Global), &[], false, 10, 0, None).await?;
+            Ok((stored, memory.metadata, listed[0].0.metadata.clone()))
+        })
+        .await
+        else {
+            return;
+        };
+
+        let (stored, read, listed) = result.unwrap();
+        assert_eq!(stored, Some(serde_json::json!({})));
+        assert_eq!(read, None);
+        assert_eq!(listed, None);
+    }
+
+    #[tokio::test]
+    async fn test_cursor_resumes_after_its_row() {
+        use crate::db::migrations::with_scratch_database;
+        use crate::commands::ranking::ConfidenceFirst;
+
+        let Some(result) = with_scratch_database(|pool| async move {
+            let paths = vec!["/work/app".to_string()];
+            let add = |content: &'static str, confidence| {
+                let pool = pool.clone();
+                async move {
+                    insert_memory(
+                        &pool, MemoryType::Learning, Scope::Project, Some("/work/app"), content, content, &[],
+                        confidence, None, None, None,
+                    )
+                    .await
+                }
+            };
+            add("Paging step one", Confidence::High).await?;
+            add("Paging step two", Confidence::Medium).await?;
+            add("Paging step three", Confidence::Low).await?;
+
+            let filters = SearchFilters::default();
+            let page = |after: Option<Cursor>| {
+                let pool = pool.clone();
+                let paths = paths.clone();
+                let filters = filters.clone();
+                async move {
+                    search_keyword(
+                        &pool, "paging", Some(Scope::Project), &paths, false, 2, 0, after.as_ref(), None, false,
+                        &filters, &ConfidenceFirst,
+                    )
+                    .await
+                }
+            };
+            let first = page(None).await?;
+            // Ranks first: an offset of 2 would now repeat "Paging step two"
+            add("Paging step zero", Confidence::High).await?;
+            let second = page(first.last().map(|(_, c)| c.clone())).await?;
+
+            let fts = search_fts(
+                &pool, "paging", Some(Scope::Project), &paths, false, 2, 0, None, None, false, &filters,
+            )
+            .await?;
+            let after = Cursor {
+                order: FTS_ORDER.to_string(),
+                key: vec![fts[0].1.to_string()],
+                id: fts[0].0.id,
+                at: Utc::now(),
+            };
+            let fts_rest = search_fts(
+                &pool, "paging", Some(Scope::Project), &paths, false, 10, 0, Some(&after), None, false, &filters,
+            )
+            .await?;
+
+            let contents = |rows: Vec<(Memory, Cursor)>| rows.into_iter().map(|(m, _)| m.content).collect::<Vec<_>>();
+            Ok((contents(first), contents(second), fts_rest.len()))
+        })
+        .await
+        else {
+            return;
+        };
+
+        let (first, second, fts_rest) = result.unwrap();
+        assert_eq!(first, vec!["Paging step one", "Paging step two"]);
+        assert_eq!(second, vec!["Paging step three"]);
+        assert_eq!(fts_rest, 3);
+    }
+
+    #[test]
+    fn test_memory_filter_binds_values() {
+        let sql = filter_sql(MemoryFilter {
+            memory_type: Some(MemoryType::Gotcha),
+            keyword: Some("'; DROP TABLE memories; --"),
+            tag: Some("sqlx"),
+            ..MemoryFilter::new(Some(Scope::Global), false, &[])
+        });
+        assert!(sql.contains("type = $1"));
+        assert!(sql.contains("content ILIKE $2"));
+        assert!(sql.contains("lower(t) = lower($4)"));
+        assert!(!sql.contains("DROP"));
+    }
+
+    #[test]
+    fn test_memory_filter_narrowed() {
+        let filters = SearchFilters {
+            since: Some(Utc::now()),
+            until: Some(Utc::now()),
+            confidence: Some(Confidence::High),
+            tag: Some("billing".to_string()),
+        };
+        let sql = filter_sql(MemoryFilter::new(Some(Scope::Global), false, &[]).narrowed(&filters));
+        assert!(sql.contains("lower(t) = lower($1)"));
+        assert!(sql.contains("created_at >= $2 AND created_at <= $3"));
+        assert!(sql.contains("confidence = $4"));
+
+        let sql = filter_sql(
+            MemoryFilter::new(Some(Scope::Global), false, &[]).narrowed(&SearchFilters::default()),
+        );
+        assert!(!sql.contains("created_at"));
+        assert!(!sql.contains("confidence"));
+    }
+
     #[test]
     fn test_tool_call_struct() {
         let tool_call = ToolCall {
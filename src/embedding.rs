@@ -0,0 +1,187 @@
+//! Embeddings for recalling past conversation turns and for hybrid search.
+//!
+//! Turn prompts and responses, and memory contents, are sent to an OpenAI-compatible `/embeddings`
+//! endpoint (`embedding_base_url`, OpenAI's API when unset, keyed by the
+//! optional `OPENAI_API_KEY`) with `embedding_model`. Vectors are stored as
+//! plain arrays and compared here, so no database extension is needed.
+
+use std::time::Duration;
+
+use crate::config::DbConfig;
+use crate::error::{HippocampusError, Result};
+use crate::extraction::{endpoint, post_json, unexpected_response};
+
+/// How long the endpoint may take to answer
+const HTTP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Characters of a turn or memory embedded; the rest rarely changes what
+/// it is about
+const MAX_EMBED_CHARS: usize = 8000;
+
+/// An OpenAI-compatible embeddings endpoint
+#[derive(Debug, Clone)]
+pub struct Embedder {
+    pub base_url: String,
+    /// Sent as a bearer token; local servers often need none
+    pub api_key: Option<String>,
+    pub model: String,
+    /// Requested vector length; the model's own when unset
+    pub dimensions: Option<u32>,
+}
+
+impl Embedder {
+    /// The embedder configured by `embedding_model` and `embedding_base_url`
+    pub fn from_config(config: &DbConfig) -> Result<Self> {
+        let model = config.embedding_model.clone().ok_or_else(|| {
+            HippocampusError::Config(
+                "embedding_model must be set to use embeddings (see embedding_base_url)".to_string(),
+            )
+        })?;
+        Ok(Self {
+            base_url: config
+                .embedding_base_url
+                .clone()
+                .unwrap_or_else(|| "https://api.openai.com/v1".to_string()),
+            api_key: std::env::var("OPENAI_API_KEY").ok().filter(|k| !k.is_empty()),
+            model,
+            dimensions: config.embedding_dimensions,
+        })
+    }
+
+    /// One vector per input, in order
+    pub fn embed(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>> {
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut request = ureq::post(&endpoint(&self.base_url, "embeddings")).timeout(HTTP_TIMEOUT);
+        if let Some(key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {}", key));
+        }
+        let mut body = serde_json::json!({"model": self.model, "input": inputs});
+        if let Some(dimensions) = self.dimensions {
+            body["dimensions"] = dimensions.into();
+        }
+        let response = post_json("embeddings", request, body)?;
+        parse_embeddings(&response, inputs.len())
+            .ok_or_else(|| unexpected_response("embeddings", &response))
+    }
+}
+
+/// The text a turn is embedded as: its prompt and response, truncated
+pub fn turn_text(prompt: &str, response: Option<&str>) -> String {
+    let text = match response {
+        Some(response) => format!("User: {}\n\nAssistant: {}", prompt, response),
+        None => format!("User: {}", prompt),
+    };
+    text.chars().take(MAX_EMBED_CHARS).collect()
+}
+
+/// The text a memory is embedded as: its summary, unless taken from the
+/// content, and content, truncated
+pub fn memory_text(summary: &str, content: &str) -> String {
+    let text = if content.starts_with(summary) {
+        content.to_string()
+    } else {
+        format!("{}\n\n{}", summary, content)
+    };
+    text.chars().take(MAX_EMBED_CHARS).collect()
+}
+
+/// Cosine similarity of two vectors; 0 when their lengths differ or either
+/// is zero
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f64, 0.0f64, 0.0f64);
+    for (x, y) in a.iter().zip(b) {
+        let (x, y) = (*x as f64, *y as f64);
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+/// Vectors of an embeddings response, ordered by their `index`
+fn parse_embeddings(response: &serde_json::Value, expected: usize) -> Option<Vec<Vec<f32>>> {
+    let mut data: Vec<(u64, Vec<f32>)> = response
+        .get("data")?
+        .as_array()?
+        .iter()
+        .enumerate()
+        .map(|(position, item)| {
+            let index = item.get("index").and_then(|i| i.as_u64()).unwrap_or(position as u64);
+            let vector = item
+                .get("embedding")?
+                .as_array()?
+                .iter()
+                .map(|v| v.as_f64().map(|v| v as f32))
+                .collect::<Option<Vec<f32>>>()?;
+            Some((index, vector))
+        })
+        .collect::<Option<_>>()?;
+    data.sort_by_key(|(index, _)| *index);
+    (data.len() == expected).then(|| data.into_iter().map(|(_, vector)| vector).collect())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-9);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-9);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_parse_embeddings_orders_by_index() {
+        let response = serde_json::json!({
+            "data": [
+                {"index": 1, "embedding": [0.5, 0.5]},
+                {"index": 0, "embedding": [1, 0]},
+            ]
+        });
+        let vectors = parse_embeddings(&response, 2).unwrap();
+        assert_eq!(vectors, vec![vec![1.0, 0.0], vec![0.5, 0.5]]);
+        assert!(parse_embeddings(&response, 3).is_none());
+        assert!(parse_embeddings(&serde_json::json!({"error": "bad"}), 1).is_none());
+    }
+
+    #[test]
+    fn test_turn_text_truncates() {
+        assert_eq!(turn_text("Why?", None), "User: Why?");
+        let text = turn_text("q", Some(&"a".repeat(MAX_EMBED_CHARS)));
+        assert_eq!(text.chars().count(), MAX_EMBED_CHARS);
+        assert!(text.starts_with("User: q\n\nAssistant: a"));
+    }
+
+    #[test]
+    fn test_memory_text_skips_derived_summary() {
+        assert_eq!(memory_text("Pool caps at 20.", "Pool caps at 20. Raise it."), "Pool caps at 20. Raise it.");
+        assert_eq!(memory_text("Pool limit", "Caps at 20."), "Pool limit\n\nCaps at 20.");
+    }
+
+    #[test]
+    fn test_embedder_requires_model() {
+        let err = Embedder::from_config(&DbConfig::default()).unwrap_err();
+        assert!(err.to_string().contains("embedding_model"));
+
+        let config = DbConfig {
+            embedding_model: Some("nomic-embed-text".to_string()),
+            embedding_base_url: Some("http://localhost:11434/v1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(Embedder::from_config(&config).unwrap().base_url, "http://localhost:11434/v1");
+    }
+}
@@ -0,0 +1,493 @@
+//! Environment snapshot module for qualifying learnings
+//!
+//! Captures the operating system plus versions of the toolchains a project
+//! uses (detected from marker files such as `Cargo.toml` or `package.json`),
+//! so memories can later be tied to the environment they were true in.
+//! Memories may declare constraints such as `os=linux` or `rust>=1.75` that
+//! are checked against a snapshot.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::process::Command;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::HippocampusError;
+
+/// Toolchains detected from marker files: (marker file, tool, version args)
+const TOOL_MARKERS: &[(&str, &str, &[&str])] = &[
+    ("Cargo.toml", "rustc", &["--version"]),
+    ("Cargo.toml", "cargo", &["--version"]),
+    ("package.json", "node", &["--version"]),
+    ("package.json", "npm", &["--version"]),
+    ("pnpm-lock.yaml", "pnpm", &["--version"]),
+    ("yarn.lock", "yarn", &["--version"]),
+    ("bun.lockb", "bun", &["--version"]),
+    ("pyproject.toml", "python", &["--version"]),
+    ("requirements.txt", "python", &["--version"]),
+    ("setup.py", "python", &["--version"]),
+    ("go.mod", "go", &["version"]),
+    ("Gemfile", "ruby", &["--version"]),
+    ("pom.xml", "java", &["-version"]),
+    ("build.gradle", "java", &["-version"]),
+    ("build.gradle.kts", "java", &["-version"]),
+];
+
+/// Executables tried for each tool name, in order
+fn executables(tool: &str) -> &'static [&'static str] {
+    match tool {
+        "python" => &["python3", "python"],
+        "rustc" => &["rustc"],
+        "cargo" => &["cargo"],
+        "node" => &["node"],
+        "npm" => &["npm"],
+        "pnpm" => &["pnpm"],
+        "yarn" => &["yarn"],
+        "bun" => &["bun"],
+        "go" => &["go"],
+        "ruby" => &["ruby"],
+        "java" => &["java"],
+        _ => &[],
+    }
+}
+
+/// The environment a session ran in
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct EnvironmentSnapshot {
+    /// Operating system (e.g., "linux", "macos")
+    pub os: String,
+    /// CPU architecture (e.g., "x86_64", "aarch64")
+    pub arch: String,
+    /// Tool name to version (e.g., "rustc" -> "1.75.0")
+    pub tools: BTreeMap<String, String>,
+}
+
+impl EnvironmentSnapshot {
+    /// Snapshot of the OS/arch only, without probing any tools
+    pub fn current_platform() -> Self {
+        Self {
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            tools: BTreeMap::new(),
+        }
+    }
+}
+
+/// Tools a project uses, detected from marker files in its root
+pub fn detect_project_tools(project_path: &Path) -> Vec<&'static str> {
+    let mut tools: Vec<&'static str> = Vec::new();
+    for (marker, tool, _) in TOOL_MARKERS {
+        if project_path.join(marker).exists() && !tools.contains(tool) {
+            tools.push(tool);
+        }
+    }
+    tools
+}
+
+/// Capture the environment for a project.
+///
+/// Tools that are not installed are omitted. Without a project path only the
+/// OS and architecture are recorded.
+pub fn capture_environment(project_path: Option<&str>) -> EnvironmentSnapshot {
+    let mut snapshot = EnvironmentSnapshot::current_platform();
+
+    let Some(path) = project_path else {
+        return snapshot;
+    };
+
+    for tool in detect_project_tools(Path::new(path)) {
+        if let Some(version) = tool_version(tool) {
+            snapshot.tools.insert(tool.to_string(), version);
+        }
+    }
+
+    snapshot
+}
+
+/// Run a tool's version command and extract the version number
+fn tool_version(tool: &str) -> Option<String> {
+    let args = TOOL_MARKERS
+        .iter()
+        .find(|(_, t, _)| *t == tool)
+        .map(|(_, _, args)| *args)?;
+
+    executables(tool).iter().find_map(|exe| {
+        let output = Command::new(exe).args(args).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        // Some tools (java) print their version to stderr
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        parse_version(&stdout).or_else(|| parse_version(&stderr))
+    })
+}
+
+/// Extract the first dotted version number from tool output
+///
+/// Handles forms like `rustc 1.75.0 (82e1608df 2023-12-21)`, `v20.10.0`,
+/// `go version go1.21.5 linux/amd64` and `openjdk version "17.0.2"`.
+pub fn parse_version(output: &str) -> Option<String> {
+    output.split(|c: char| c.is_whitespace() || c == '"').find_map(|word| {
+        let start = word.find(|c: char| c.is_ascii_digit())?;
+        // Only strip short alphabetic prefixes like "v" or "go"
+        if !word[..start].chars().all(|c| c.is_ascii_alphabetic()) || start > 2 {
+            return None;
+        }
+        let candidate: String = word[start..]
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || *c == '.')
+            .collect();
+        let candidate = candidate.trim_end_matches('.');
+        candidate.contains('.').then(|| candidate.to_string())
+    })
+}
+
+// ============================================================================
+// Constraints
+// ============================================================================
+
+/// Comparison operator of an environment constraint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstraintOp {
+    Eq,
+    Ne,
+    Ge,
+    Le,
+    Gt,
+    Lt,
+}
+
+impl ConstraintOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Eq => "=",
+            Self::Ne => "!=",
+            Self::Ge => ">=",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Lt => "<",
+        }
+    }
+
+    fn accepts(&self, ordering: Ordering) -> bool {
+        match self {
+            Self::Eq => ordering == Ordering::Equal,
+            Self::Ne => ordering != Ordering::Equal,
+            Self::Ge => ordering != Ordering::Less,
+            Self::Le => ordering != Ordering::Greater,
+            Self::Gt => ordering == Ordering::Greater,
+            Self::Lt => ordering == Ordering::Less,
+        }
+    }
+}
+
+/// An applicability constraint on a memory, e.g. `os=linux` or `rust>=1.75`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvConstraint {
+    /// `os`, `arch`, or a tool name (`rust` is accepted for `rustc`)
+    pub key: String,
+    pub op: ConstraintOp,
+    pub value: String,
+}
+
+impl EnvConstraint {
+    /// Check the constraint against an environment snapshot.
+    ///
+    /// A tool that the snapshot does not know about counts as a match, since
+    /// absence usually means it was not detected rather than not installed.
+    pub fn matches(&self, env: &EnvironmentSnapshot) -> bool {
+        let actual = match self.key.as_str() {
+            "os" => Some(normalize_os(&env.os)),
+            "arch" => Some(env.arch.to_lowercase()),
+            tool => env.tools.get(tool).cloned(),
+        };
+        let Some(actual) = actual else {
+            return true;
+        };
+
+        let expected = if self.key == "os" {
+            normalize_os(&self.value)
+        } else {
+            self.value.to_lowercase()
+        };
+        self.op.accepts(compare_versions(&actual, &expected))
+    }
+}
+
+impl FromStr for EnvConstraint {
+    type Err = HippocampusError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || HippocampusError::InvalidConstraint(s.to_string());
+
+        // Two-character operators first so ">=" is not read as ">"
+        let ops = [
+            ConstraintOp::Ge,
+            ConstraintOp::Le,
+            ConstraintOp::Ne,
+            ConstraintOp::Eq,
+            ConstraintOp::Gt,
+            ConstraintOp::Lt,
+        ];
+        let (idx, op) = ops
+            .iter()
+            .filter_map(|op| s.find(op.as_str()).map(|idx| (idx, *op)))
+            .min_by_key(|(idx, op)| (*idx, std::cmp::Reverse(op.as_str().len())))
+            .ok_or_else(invalid)?;
+
+        let key = s[..idx].trim().to_lowercase();
+        let value = s[idx + op.as_str().len()..].trim().to_string();
+        let valid_key = key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if key.is_empty() || value.is_empty() || !valid_key {
+            return Err(invalid());
+        }
+
+        let key = match key.as_str() {
+            "rust" => "rustc".to_string(),
+            "python3" => "python".to_string(),
+            "nodejs" => "node".to_string(),
+            _ => key,
+        };
+
+        Ok(Self { key, op, value })
+    }
+}
+
+impl fmt::Display for EnvConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.key, self.op.as_str(), self.value)
+    }
+}
+
+/// Check whether all constraints hold. Unparseable constraints are ignored.
+pub fn constraints_match(constraints: &[String], env: &EnvironmentSnapshot) -> bool {
+    constraints
+        .iter()
+        .filter_map(|c| c.parse::<EnvConstraint>().ok())
+        .all(|c| c.matches(env))
+}
+
+fn normalize_os(os: &str) -> String {
+    match os.to_lowercase().as_str() {
+        "darwin" | "mac" | "osx" => "macos".to_string(),
+        "win" | "win32" => "windows".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Compare dotted versions numerically (`1.10` > `1.9`), missing parts as zero.
+///
+/// Non-numeric parts compare as strings, so plain values like `linux` work too.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let mut left = a.split('.');
+    let mut right = b.split('.');
+    loop {
+        match (left.next(), right.next()) {
+            (None, None) => return Ordering::Equal,
+            (l, r) => {
+                let l = l.unwrap_or("0");
+                let r = r.unwrap_or("0");
+                let ordering = match (l.parse::<u64>(), r.parse::<u64>()) {
+                    (Ok(l), Ok(r)) => l.cmp(&r),
+                    _ => l.cmp(r),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ========================================================================
+    // parse_version tests
+    // ========================================================================
+
+    #[test]
+    fn test_parse_version_rustc() {
+        assert_eq!(
+            parse_version("rustc 1.75.0 (82e1608df 2023-12-21)"),
+            Some("1.75.0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_version_node() {
+        assert_eq!(parse_version("v20.10.0\n"), Some("20.10.0".to_string()));
+    }
+
+    #[test]
+    fn test_parse_version_go() {
+        assert_eq!(
+            parse_version("go version go1.21.5 linux/amd64"),
+            Some("1.21.5".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_version_java() {
+        assert_eq!(
+            parse_version("openjdk version \"17.0.2\" 2022-01-18"),
+            Some("17.0.2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_version_python() {
+        assert_eq!(parse_version("Python 3.12.1"), Some("3.12.1".to_string()));
+    }
+
+    #[test]
+    fn test_parse_version_none() {
+        assert_eq!(parse_version("command not found"), None);
+        assert_eq!(parse_version("version 7"), None);
+        assert_eq!(parse_version(""), None);
+    }
+
+    // ========================================================================
+    // Detection tests
+    // ========================================================================
+
+    #[test]
+    fn test_detect_project_tools() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "").unwrap();
+        std::fs::write(dir.path().join("requirements.txt"), "").unwrap();
+        std::fs::write(dir.path().join("setup.py"), "").unwrap();
+
+        let tools = detect_project_tools(dir.path());
+        assert_eq!(tools, vec!["rustc", "cargo", "python"]);
+    }
+
+    #[test]
+    fn test_detect_project_tools_empty_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(detect_project_tools(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_capture_environment_without_project() {
+        let snapshot = capture_environment(None);
+        assert_eq!(snapshot.os, std::env::consts::OS);
+        assert_eq!(snapshot.arch, std::env::consts::ARCH);
+        assert!(snapshot.tools.is_empty());
+    }
+
+    #[test]
+    fn test_capture_environment_rust_project() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "").unwrap();
+
+        let snapshot = capture_environment(Some(dir.path().to_str().unwrap()));
+        // The test itself runs under cargo, so rustc must be available
+        assert!(snapshot.tools.contains_key("rustc"));
+        assert!(!snapshot.tools.contains_key("node"));
+    }
+
+    // ========================================================================
+    // Constraint tests
+    // ========================================================================
+
+    fn linux_env() -> EnvironmentSnapshot {
+        let mut env = EnvironmentSnapshot {
+            os: "linux".to_string(),
+            arch: "x86_64".to_string(),
+            tools: BTreeMap::new(),
+        };
+        env.tools.insert("rustc".to_string(), "1.75.0".to_string());
+        env
+    }
+
+    #[test]
+    fn test_parse_constraint() {
+        let c: EnvConstraint = "rust>=1.75".parse().unwrap();
+        assert_eq!(c.key, "rustc");
+        assert_eq!(c.op, ConstraintOp::Ge);
+        assert_eq!(c.value, "1.75");
+        assert_eq!(c.to_string(), "rustc>=1.75");
+
+        let c: EnvConstraint = "OS = linux".parse().unwrap();
+        assert_eq!(c.key, "os");
+        assert_eq!(c.op, ConstraintOp::Eq);
+        assert_eq!(c.value, "linux");
+
+        let c: EnvConstraint = "node!=18".parse().unwrap();
+        assert_eq!(c.op, ConstraintOp::Ne);
+        let c: EnvConstraint = "go<1.21".parse().unwrap();
+        assert_eq!(c.op, ConstraintOp::Lt);
+    }
+
+    #[test]
+    fn test_parse_constraint_invalid() {
+        assert!("linux".parse::<EnvConstraint>().is_err());
+        assert!("=linux".parse::<EnvConstraint>().is_err());
+        assert!("os=".parse::<EnvConstraint>().is_err());
+        assert!("my os=linux".parse::<EnvConstraint>().is_err());
+    }
+
+    #[test]
+    fn test_constraint_matches_os() {
+        let env = linux_env();
+        assert!("os=linux".parse::<EnvConstraint>().unwrap().matches(&env));
+        assert!(!"os=macos".parse::<EnvConstraint>().unwrap().matches(&env));
+        assert!("os!=darwin".parse::<EnvConstraint>().unwrap().matches(&env));
+    }
+
+    #[test]
+    fn test_constraint_matches_versions() {
+        let env = linux_env();
+        assert!("rust>=1.75".parse::<EnvConstraint>().unwrap().matches(&env));
+        assert!("rust>1.9".parse::<EnvConstraint>().unwrap().matches(&env));
+        assert!(!"rust<1.75".parse::<EnvConstraint>().unwrap().matches(&env));
+        assert!(!"rustc>=1.80".parse::<EnvConstraint>().unwrap().matches(&env));
+        assert!("rustc=1.75".parse::<EnvConstraint>().unwrap().matches(&env));
+    }
+
+    #[test]
+    fn test_constraint_unknown_tool_matches() {
+        let env = linux_env();
+        assert!("node>=20".parse::<EnvConstraint>().unwrap().matches(&env));
+    }
+
+    #[test]
+    fn test_constraints_match_all() {
+        let env = linux_env();
+        let ok = vec!["os=linux".to_string(), "rust>=1.70".to_string()];
+        let bad = vec!["os=linux".to_string(), "rust>=1.80".to_string()];
+        let garbage = vec!["???".to_string()];
+        assert!(constraints_match(&ok, &env));
+        assert!(!constraints_match(&bad, &env));
+        assert!(constraints_match(&garbage, &env));
+        assert!(constraints_match(&[], &env));
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.10", "1.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.75.0", "1.75"), Ordering::Equal);
+        assert_eq!(compare_versions("2", "10"), Ordering::Less);
+        assert_eq!(compare_versions("linux", "linux"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_snapshot_serialization_roundtrip() {
+        let mut snapshot = EnvironmentSnapshot::current_platform();
+        snapshot.tools.insert("node".to_string(), "20.10.0".to_string());
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["tools"]["node"], "20.10.0");
+
+        let parsed: EnvironmentSnapshot = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, snapshot);
+    }
+}
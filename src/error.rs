@@ -1,3 +1,4 @@
+use claude_hippocampus_client::ParseError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -5,9 +6,15 @@ pub enum HippocampusError {
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
 
+    #[error("Migration error: {0}")]
+    Migration(#[from] sqlx::migrate::MigrateError),
+
     #[error("Configuration error: {0}")]
     Config(String),
 
+    #[error("{0}")]
+    Validation(String),
+
     #[error("Invalid memory type: {0}. Must be one of: convention, architecture, gotcha, api, learning, preference")]
     InvalidMemoryType(String),
 
@@ -23,6 +30,9 @@ pub enum HippocampusError {
     #[error("Invalid session status: {0}. Must be one of: active, completed, orphaned")]
     InvalidSessionStatus(String),
 
+    #[error("Invalid environment constraint: {0}. Expected <key><op><value>, e.g. os=linux or rust>=1.75")]
+    InvalidConstraint(String),
+
     #[error("Memory not found: {0}")]
     NotFound(String),
 
@@ -32,6 +42,9 @@ pub enum HippocampusError {
     #[error("Session state error: {0}")]
     SessionState(String),
 
+    #[error("Extraction error: {0}")]
+    Extraction(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -40,10 +53,40 @@ pub enum HippocampusError {
 
     #[error("UUID parse error: {0}")]
     UuidParse(#[from] uuid::Error),
+
+    #[error("Another maintenance run is in progress ({0} not started); use --wait to wait for it or --skip-if-running to skip")]
+    MaintenanceRunning(String),
+
+    #[error("The database schema is at version {current}, this build needs {latest}: run `claude-hippocampus migrate`")]
+    SchemaOutdated { current: i64, latest: i64 },
+
+    #[error("Invalid {flag}: {value} is not a UUID")]
+    InvalidArgument { flag: String, value: String },
 }
 
 pub type Result<T> = std::result::Result<T, HippocampusError>;
 
+impl HippocampusError {
+    /// The command-line argument the error is about, if any
+    pub fn argument(&self) -> Option<&str> {
+        match self {
+            Self::InvalidArgument { flag, .. } => Some(flag),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for HippocampusError {
+    fn from(err: ParseError) -> Self {
+        match err {
+            ParseError::InvalidMemoryType(s) => Self::InvalidMemoryType(s),
+            ParseError::InvalidConfidence(s) => Self::InvalidConfidence(s),
+            ParseError::InvalidTier(s) => Self::InvalidTier(s),
+            ParseError::InvalidScope(s) => Self::InvalidScope(s),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -69,12 +112,51 @@ mod tests {
         assert!(err.to_string().contains("both"));
     }
 
+    #[test]
+    fn test_invalid_constraint_error_display() {
+        let err = HippocampusError::InvalidConstraint("macos".to_string());
+        assert!(err.to_string().contains("Invalid environment constraint: macos"));
+        assert!(err.to_string().contains("rust>=1.75"));
+    }
+
+    #[test]
+    fn test_validation_error_display() {
+        let err = HippocampusError::Validation("Search query is empty".to_string());
+        assert_eq!(err.to_string(), "Search query is empty");
+    }
+
     #[test]
     fn test_config_error_display() {
         let err = HippocampusError::Config("missing file".to_string());
         assert_eq!(err.to_string(), "Configuration error: missing file");
     }
 
+    #[test]
+    fn test_schema_outdated_names_migrate() {
+        let err = HippocampusError::SchemaOutdated { current: 30, latest: 32 };
+        assert_eq!(
+            err.to_string(),
+            "The database schema is at version 30, this build needs 32: run `claude-hippocampus migrate`"
+        );
+    }
+
+    #[test]
+    fn test_extraction_error_display() {
+        let err = HippocampusError::Extraction("claude exited with 1".to_string());
+        assert_eq!(err.to_string(), "Extraction error: claude exited with 1");
+    }
+
+    #[test]
+    fn test_invalid_argument_names_flag() {
+        let err = HippocampusError::InvalidArgument {
+            flag: "--session".to_string(),
+            value: "sess-123".to_string(),
+        };
+        assert_eq!(err.to_string(), "Invalid --session: sess-123 is not a UUID");
+        assert_eq!(err.argument(), Some("--session"));
+        assert_eq!(HippocampusError::NotFound("x".to_string()).argument(), None);
+    }
+
     #[test]
     fn test_not_found_error_display() {
         let err = HippocampusError::NotFound("abc-123".to_string());
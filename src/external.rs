@@ -0,0 +1,190 @@
+//! Aliases and external subcommands
+//!
+//! Teams extend the CLI without changing the parser, as with git:
+//! - `[aliases]` in the config names an argument list, so
+//!   `claude-hippocampus recent-gotchas` can stand for
+//!   `claude-hippocampus search-by-type gotcha 10`
+//! - any other unknown subcommand `foo` runs `claude-hippocampus-foo` from
+//!   `PATH` with the remaining arguments
+//!
+//! Built-in commands always win over aliases and external commands of the
+//! same name.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{HippocampusError, Result};
+
+/// Prefix of external subcommand executables
+pub const EXTERNAL_PREFIX: &str = "claude-hippocampus-";
+
+/// Environment variable giving external subcommands this binary's path
+pub const BIN_ENV: &str = "CLAUDE_HIPPOCAMPUS_BIN";
+
+/// Deepest chain of aliases naming aliases
+const MAX_ALIAS_DEPTH: usize = 10;
+
+/// What an alias expands to: a command line split on whitespace, or a list
+/// of arguments for values containing spaces
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Alias {
+    Line(String),
+    Args(Vec<String>),
+}
+
+impl Alias {
+    pub fn args(&self) -> Vec<String> {
+        match self {
+            Alias::Line(line) => line.split_whitespace().map(str::to_string).collect(),
+            Alias::Args(args) => args.clone(),
+        }
+    }
+}
+
+/// Replace an alias in the subcommand position of `args` (program name
+/// first) with its expansion, repeatedly when it names another alias.
+///
+/// `is_builtin` tells built-in subcommands, which are never expanded.
+pub fn expand_alias(
+    mut args: Vec<String>,
+    aliases: &BTreeMap<String, Alias>,
+    is_builtin: impl Fn(&str) -> bool,
+) -> Result<Vec<String>> {
+    let mut seen: Vec<String> = Vec::new();
+    while let Some(name) = args.get(1).cloned() {
+        if is_builtin(&name) {
+            break;
+        }
+        let Some(alias) = aliases.get(&name) else {
+            break;
+        };
+        if seen.contains(&name) || seen.len() >= MAX_ALIAS_DEPTH {
+            seen.push(name);
+            return Err(HippocampusError::Config(format!(
+                "Alias loop: {}",
+                seen.join(" -> ")
+            )));
+        }
+        let expansion = alias.args();
+        if expansion.is_empty() {
+            return Err(HippocampusError::Config(format!("Alias {} is empty", name)));
+        }
+        args.splice(1..2, expansion);
+        seen.push(name);
+    }
+    Ok(args)
+}
+
+/// The `claude-hippocampus-<name>` executable on `PATH`
+pub fn find_external(name: &str) -> Option<PathBuf> {
+    // A name with a separator would reach outside PATH
+    if name.is_empty() || name.contains(std::path::MAIN_SEPARATOR) || name.contains('/') {
+        return None;
+    }
+    let file = format!("{}{}", EXTERNAL_PREFIX, name);
+    let path = env::var_os("PATH")?;
+    env::split_paths(&path)
+        .map(|dir| dir.join(&file))
+        .find(|candidate| is_executable(candidate))
+}
+
+/// Run an external subcommand, `args` starting with its name, and return its
+/// exit code. Output goes straight to this process's stdout and stderr.
+pub fn run_external(args: &[String]) -> Result<i32> {
+    let (name, rest) = args
+        .split_first()
+        .ok_or_else(|| HippocampusError::Validation("No subcommand given".to_string()))?;
+    let program = find_external(name).ok_or_else(|| {
+        HippocampusError::Validation(format!(
+            "Unknown command: {} (no alias, and no {}{} on PATH)",
+            name, EXTERNAL_PREFIX, name
+        ))
+    })?;
+
+    let mut command = Command::new(&program);
+    command.args(rest);
+    if let Ok(exe) = env::current_exe() {
+        command.env(BIN_ENV, exe);
+    }
+    let status = command.status().map_err(|e| {
+        HippocampusError::Config(format!("Cannot run {}: {}", program.display(), e))
+    })?;
+    Ok(status.code().unwrap_or(1))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn aliases(entries: &[(&str, Alias)]) -> BTreeMap<String, Alias> {
+        entries.iter().map(|(k, v)| (k.to_string(), v.clone())).collect()
+    }
+
+    #[test]
+    fn test_expand_alias_keeps_trailing_args() {
+        let aliases = aliases(&[("gotchas", Alias::Line("search-by-type gotcha".to_string()))]);
+        let expanded = expand_alias(args("hippo gotchas 5"), &aliases, |_| false).unwrap();
+        assert_eq!(expanded, args("hippo search-by-type gotcha 5"));
+    }
+
+    #[test]
+    fn test_expand_alias_chains_and_lists() {
+        let aliases = aliases(&[
+            ("g", Alias::Line("gotchas".to_string())),
+            (
+                "gotchas",
+                Alias::Args(vec!["search".to_string(), "sed -i".to_string()]),
+            ),
+        ]);
+        let expanded = expand_alias(args("hippo g"), &aliases, |_| false).unwrap();
+        assert_eq!(expanded, vec!["hippo", "search", "sed -i"]);
+    }
+
+    #[test]
+    fn test_expand_alias_builtins_win() {
+        let aliases = aliases(&[("search", Alias::Line("list-recent".to_string()))]);
+        let expanded = expand_alias(args("hippo search x"), &aliases, |n| n == "search").unwrap();
+        assert_eq!(expanded, args("hippo search x"));
+    }
+
+    #[test]
+    fn test_expand_alias_loop() {
+        let aliases = aliases(&[
+            ("a", Alias::Line("b".to_string())),
+            ("b", Alias::Line("a --flag".to_string())),
+        ]);
+        let err = expand_alias(args("hippo a"), &aliases, |_| false).unwrap_err();
+        assert!(err.to_string().contains("a -> b -> a"));
+    }
+
+    #[test]
+    fn test_find_external_rejects_paths() {
+        assert!(find_external("../bin/sh").is_none());
+        assert!(find_external("").is_none());
+    }
+}
@@ -0,0 +1,539 @@
+//! Extraction backend for distilling memories with headless Claude.
+//!
+//! Prompts go to the [`Provider`] chosen by `extraction_provider`: the
+//! Claude CLI (`claude --print` with `CLAUDE_MEMORY_EXTRACTION=1` set, so the
+//! hooks of the spawned instance skip themselves), the Anthropic API, or an
+//! OpenAI-compatible endpoint. The JSON they answer with is parsed the same
+//! way for all of them. The Stop hook drives extraction through a detached
+//! `extract-worker` process.
+
+use std::fmt;
+use std::process::Stdio;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::DbConfig;
+use crate::daemon;
+use crate::error::{HippocampusError, Result};
+
+/// Represents an extracted memory decision from Claude
+#[derive(Debug, Clone)]
+pub struct ExtractionResult {
+    pub memory_type: String,
+    pub conclusion: String,
+    pub tags: String,
+}
+
+/// Build the extraction prompt for Claude --print, with `rules` (see
+/// [`DbConfig::extraction_prompt_rules`]) listed before the output format
+pub fn build_extraction_prompt(user_msg: &str, assistant_response: &str, rules: &[String]) -> String {
+    // Truncate inputs for efficiency
+    let user_preview: String = user_msg.chars().take(500).collect();
+    let response_preview: String = assistant_response.chars().take(1000).collect();
+    let rules_section: String = if rules.is_empty() {
+        String::new()
+    } else {
+        let lines: Vec<String> = rules.iter().map(|rule| format!("- {}", rule)).collect();
+        format!("PROJECT RULES:\n{}\n\n", lines.join("\n"))
+    };
+
+    format!(
+        r#"You are a memory extraction assistant. Extract a conclusion from this conversation turn.
+
+USER PROMPT:
+{}
+
+ASSISTANT RESPONSE:
+{}
+
+TASK: Extract a concise conclusion from this turn. ALWAYS save something unless it's completely trivial (just "yes", "ok", greeting).
+
+{}Output JSON:
+{{"type": "<learning|gotcha|convention|architecture|api|preference>", "conclusion": "<max 150 chars summarizing the turn>", "tags": "<comma,separated>"}}
+
+For most turns, use type "learning". Use "gotcha" for corrections or warnings, "convention" for patterns, "architecture" for design decisions.
+
+Output ONLY the JSON, nothing else."#,
+        user_preview, response_preview, rules_section
+    )
+}
+
+/// Build the prompt that asks again after `output` could not be parsed
+pub fn build_repair_prompt(output: &str) -> String {
+    let output_preview: String = output.chars().take(1000).collect();
+
+    format!(
+        r#"Your previous answer could not be parsed as the required JSON.
+
+PREVIOUS ANSWER:
+{}
+
+Rewrite it as a single JSON object with exactly these fields:
+{{"type": "<learning|gotcha|convention|architecture|api|preference>", "conclusion": "<max 150 chars>", "tags": "<comma,separated>"}}
+
+Output ONLY the JSON object. No explanation, no markdown fences, no text before or after it."#,
+        output_preview
+    )
+}
+
+/// Detect if user message is a correction (warrants high confidence)
+pub fn is_correction(user_msg: &str) -> bool {
+    let lower = user_msg.to_lowercase();
+
+    // Correction patterns (from JS implementation)
+    let patterns = [
+        "actually",     // "actually, use X instead"
+        "no, use",      // "no, use sqlx instead"
+        "no, it",       // "no, it's not like that"
+        "always use",   // "always use async here"
+        "always do",
+        "never use",    // "never do that in production"
+        "never do",
+        "should be",    // "should be different"
+        "must be",
+        "must use",
+        "not like that",
+        "the correct",  // "the correct way is..."
+        "remember that",
+        "remember to",
+        "this project uses",
+        "this project requires",
+    ];
+
+    patterns.iter().any(|p| lower.contains(p))
+}
+
+/// Run a prompt through the configured extraction provider and return its
+/// output
+pub fn run_extraction(prompt: &str) -> Result<String> {
+    provider(&DbConfig::load()?)?.complete(prompt)
+}
+
+// ============================================================================
+// Providers
+// ============================================================================
+
+/// Maximum tokens an HTTP provider may answer with
+const MAX_OUTPUT_TOKENS: u32 = 512;
+
+/// How long an HTTP provider may take to answer
+const HTTP_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Backend that answers an extraction prompt
+pub trait Provider: fmt::Debug {
+    /// Provider name, as written in config
+    fn name(&self) -> &'static str;
+
+    /// Answer `prompt` with the model's text output
+    fn complete(&self, prompt: &str) -> Result<String>;
+}
+
+/// Available extraction providers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    /// `claude --print` (default)
+    #[default]
+    Claude,
+    /// Anthropic Messages API, keyed by `ANTHROPIC_API_KEY`
+    Anthropic,
+    /// OpenAI-compatible chat completions, keyed by `OPENAI_API_KEY`
+    Openai,
+}
+
+impl ProviderKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Claude => "claude",
+            Self::Anthropic => "anthropic",
+            Self::Openai => "openai",
+        }
+    }
+}
+
+/// The Claude CLI in headless mode
+#[derive(Debug, Clone, Default)]
+pub struct ClaudeCli {
+    /// `--model`; Claude's default when unset
+    pub model: Option<String>,
+}
+
+impl Provider for ClaudeCli {
+    fn name(&self) -> &'static str {
+        "claude"
+    }
+
+    fn complete(&self, prompt: &str) -> Result<String> {
+        let mut command = daemon::command("claude");
+        if let Some(model) = &self.model {
+            command.arg("--model").arg(model);
+        }
+        let output = command
+            .arg("--print")
+            .arg("-p")
+            .arg(prompt)
+            .env("CLAUDE_MEMORY_EXTRACTION", "1")
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| HippocampusError::Extraction(format!("failed to run claude: {}", e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(HippocampusError::Extraction(format!(
+                "claude exited with {}: {}",
+                output.status,
+                stderr.trim()
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+/// The Anthropic Messages API
+#[derive(Debug, Clone)]
+pub struct AnthropicApi {
+    pub base_url: String,
+    pub api_key: String,
+    pub model: String,
+}
+
+impl Provider for AnthropicApi {
+    fn name(&self) -> &'static str {
+        "anthropic"
+    }
+
+    fn complete(&self, prompt: &str) -> Result<String> {
+        let request = ureq::post(&endpoint(&self.base_url, "v1/messages"))
+            .timeout(HTTP_TIMEOUT)
+            .set("x-api-key", &self.api_key)
+            .set("anthropic-version", "2023-06-01");
+        let body = serde_json::json!({
+            "model": self.model,
+            "max_tokens": MAX_OUTPUT_TOKENS,
+            "messages": [{"role": "user", "content": prompt}],
+        });
+        let response = post_json(self.name(), request, body)?;
+        anthropic_text(&response).ok_or_else(|| unexpected_response(self.name(), &response))
+    }
+}
+
+/// Any endpoint speaking the OpenAI chat completions API
+#[derive(Debug, Clone)]
+pub struct OpenAiCompatible {
+    pub base_url: String,
+    /// Sent as a bearer token; local servers often need none
+    pub api_key: Option<String>,
+    pub model: String,
+}
+
+impl Provider for OpenAiCompatible {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn complete(&self, prompt: &str) -> Result<String> {
+        let mut request =
+            ureq::post(&endpoint(&self.base_url, "chat/completions")).timeout(HTTP_TIMEOUT);
+        if let Some(key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {}", key));
+        }
+        let body = serde_json::json!({
+            "model": self.model,
+            "max_tokens": MAX_OUTPUT_TOKENS,
+            "messages": [{"role": "user", "content": prompt}],
+        });
+        let response = post_json(self.name(), request, body)?;
+        openai_text(&response).ok_or_else(|| unexpected_response(self.name(), &response))
+    }
+}
+
+/// Build the provider selected by `extraction_provider`.
+///
+/// HTTP providers need `extraction_model`; `extraction_base_url` points
+/// them at a proxy or self-hosted endpoint. API keys are read from the
+/// environment so they stay out of config files.
+pub fn provider(config: &DbConfig) -> Result<Box<dyn Provider>> {
+    let model = || {
+        config.extraction_model.clone().ok_or_else(|| {
+            HippocampusError::Config(format!(
+                "extraction_model must be set for the {} extraction provider",
+                config.extraction_provider.as_str()
+            ))
+        })
+    };
+    let base_url = |default: &str| {
+        config
+            .extraction_base_url
+            .clone()
+            .unwrap_or_else(|| default.to_string())
+    };
+
+    Ok(match config.extraction_provider {
+        ProviderKind::Claude => Box::new(ClaudeCli {
+            model: config.extraction_model.clone(),
+        }),
+        ProviderKind::Anthropic => Box::new(AnthropicApi {
+            base_url: base_url("https://api.anthropic.com"),
+            api_key: std::env::var("ANTHROPIC_API_KEY").map_err(|_| {
+                HippocampusError::Config(
+                    "ANTHROPIC_API_KEY must be set for the anthropic extraction provider"
+                        .to_string(),
+                )
+            })?,
+            model: model()?,
+        }),
+        ProviderKind::Openai => Box::new(OpenAiCompatible {
+            base_url: base_url("https://api.openai.com/v1"),
+            api_key: std::env::var("OPENAI_API_KEY").ok().filter(|k| !k.is_empty()),
+            model: model()?,
+        }),
+    })
+}
+
+/// `path` under `base_url`, whether or not it ends in a slash
+pub(crate) fn endpoint(base_url: &str, path: &str) -> String {
+    format!("{}/{}", base_url.trim_end_matches('/'), path)
+}
+
+/// POST `body` and parse the JSON answer, keeping error bodies in the message
+pub(crate) fn post_json(
+    provider: &str,
+    request: ureq::Request,
+    body: serde_json::Value,
+) -> Result<serde_json::Value> {
+    match request.send_json(body) {
+        Ok(response) => response.into_json().map_err(|e| {
+            HippocampusError::Extraction(format!("{} returned invalid JSON: {}", provider, e))
+        }),
+        Err(ureq::Error::Status(code, response)) => {
+            let body = response.into_string().unwrap_or_default();
+            Err(HippocampusError::Extraction(format!(
+                "{} returned {}: {}",
+                provider,
+                code,
+                body.trim()
+            )))
+        }
+        Err(e) => Err(HippocampusError::Extraction(format!(
+            "{} request failed: {}",
+            provider, e
+        ))),
+    }
+}
+
+pub(crate) fn unexpected_response(provider: &str, response: &serde_json::Value) -> HippocampusError {
+    let preview: String = response.to_string().chars().take(200).collect();
+    HippocampusError::Extraction(format!("unexpected {} response: {}", provider, preview))
+}
+
+/// Text blocks of a Messages API response, joined
+fn anthropic_text(response: &serde_json::Value) -> Option<String> {
+    let text: Vec<&str> = response
+        .get("content")?
+        .as_array()?
+        .iter()
+        .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("text"))
+        .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+        .collect();
+    (!text.is_empty()).then(|| text.join(""))
+}
+
+/// Message content of the first chat completion choice
+fn openai_text(response: &serde_json::Value) -> Option<String> {
+    response
+        .pointer("/choices/0/message/content")
+        .and_then(|c| c.as_str())
+        .map(str::to_string)
+}
+
+/// Parse JSON response from Claude extraction
+pub fn parse_extraction_response(output: &str) -> Option<ExtractionResult> {
+    // Find JSON in output (might have extra text before/after)
+    let start = output.find('{')?;
+    let end = output.rfind('}')? + 1;
+    let json_str = &output[start..end];
+
+    let json: serde_json::Value = serde_json::from_str(json_str).ok()?;
+
+    // Extract required fields
+    let memory_type = json.get("type")?.as_str()?.to_string();
+    let conclusion = json.get("conclusion")?.as_str()?.to_string();
+    let tags = json
+        .get("tags")
+        .and_then(|t| t.as_str())
+        .unwrap_or("")
+        .to_string();
+
+    // Validate required fields are present
+    if memory_type.is_empty() || conclusion.is_empty() {
+        return None;
+    }
+
+    Some(ExtractionResult {
+        memory_type,
+        conclusion,
+        tags,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // -------------------------------------------------------------------------
+    // Confidence detection tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_detect_correction_actually() {
+        assert!(is_correction("actually, use tokio instead"));
+        assert!(is_correction("Actually it's the other way"));
+    }
+
+    #[test]
+    fn test_detect_correction_no_use() {
+        assert!(is_correction("no, use sqlx instead"));
+        assert!(is_correction("No, it's not like that"));
+    }
+
+    #[test]
+    fn test_detect_correction_always_never() {
+        assert!(is_correction("always use async here"));
+        assert!(is_correction("never do that in production"));
+    }
+
+    #[test]
+    fn test_detect_correction_normal_message() {
+        assert!(!is_correction("How do I implement this?"));
+        assert!(!is_correction("What's the best way to do X?"));
+    }
+
+    // -------------------------------------------------------------------------
+    // Build extraction prompt tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_build_extraction_prompt_contains_user_msg() {
+        let prompt = build_extraction_prompt("How to use async?", "Use tokio...", &[]);
+        assert!(prompt.contains("How to use async?"));
+    }
+
+    #[test]
+    fn test_build_extraction_prompt_contains_response() {
+        let prompt = build_extraction_prompt("Question", "Use tokio for async runtime", &[]);
+        assert!(prompt.contains("Use tokio for async runtime"));
+    }
+
+    #[test]
+    fn test_build_extraction_prompt_contains_json_format() {
+        let prompt = build_extraction_prompt("Q", "A", &[]);
+        assert!(prompt.contains("\"type\""));
+        assert!(prompt.contains("\"conclusion\""));
+        assert!(prompt.contains("\"tags\""));
+    }
+
+    #[test]
+    fn test_build_extraction_prompt_lists_rules() {
+        assert!(!build_extraction_prompt("Q", "A", &[]).contains("PROJECT RULES"));
+
+        let rules = vec!["Never record file paths".to_string()];
+        let prompt = build_extraction_prompt("Q", "A", &rules);
+        assert!(prompt.contains("PROJECT RULES:\n- Never record file paths\n\nOutput JSON:"));
+    }
+
+    #[test]
+    fn test_build_repair_prompt_quotes_previous_answer() {
+        let prompt = build_repair_prompt("Sure! Here is the memory: type learning");
+        assert!(prompt.contains("Sure! Here is the memory"));
+        assert!(prompt.contains("Output ONLY the JSON object"));
+
+        let long = "x".repeat(5000);
+        assert!(build_repair_prompt(&long).len() < 2000);
+    }
+
+    #[test]
+    fn test_build_extraction_prompt_truncates_long_input() {
+        let long_msg = "x".repeat(1000);
+        let long_response = "y".repeat(2000);
+        let prompt = build_extraction_prompt(&long_msg, &long_response, &[]);
+        // Should truncate to reasonable limits
+        assert!(prompt.len() < 3000);
+    }
+
+    // -------------------------------------------------------------------------
+    // Response parsing tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_parse_extraction_response_valid() {
+        let output = r#"{"type": "learning", "conclusion": "Use tokio for async", "tags": "rust,async"}"#;
+        let result = parse_extraction_response(output).expect("should parse");
+        assert_eq!(result.memory_type, "learning");
+        assert_eq!(result.conclusion, "Use tokio for async");
+        assert_eq!(result.tags, "rust,async");
+    }
+
+    #[test]
+    fn test_parse_extraction_response_with_extra_text() {
+        let output = "Here's the extraction:\n{\"type\": \"gotcha\", \"conclusion\": \"Watch out\", \"tags\": \"warning\"}";
+        let result = parse_extraction_response(output).expect("should parse");
+        assert_eq!(result.memory_type, "gotcha");
+    }
+
+    #[test]
+    fn test_parse_extraction_response_invalid() {
+        assert!(parse_extraction_response("not json").is_none());
+        assert!(parse_extraction_response("").is_none());
+    }
+
+    #[test]
+    fn test_parse_extraction_response_missing_fields() {
+        let output = r#"{"type": "learning"}"#;
+        assert!(parse_extraction_response(output).is_none());
+    }
+
+    // -------------------------------------------------------------------------
+    // Providers
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_provider_defaults_to_claude_cli() {
+        let config = DbConfig::default();
+        assert_eq!(provider(&config).unwrap().name(), "claude");
+    }
+
+    #[test]
+    fn test_openai_provider_requires_model() {
+        let mut config = DbConfig {
+            extraction_provider: ProviderKind::Openai,
+            extraction_base_url: Some("http://localhost:8080/v1/".to_string()),
+            ..Default::default()
+        };
+        let err = provider(&config).unwrap_err();
+        assert!(err.to_string().contains("extraction_model must be set"));
+
+        config.extraction_model = Some("llama3".to_string());
+        assert_eq!(provider(&config).unwrap().name(), "openai");
+    }
+
+    #[test]
+    fn test_endpoint_joins_paths() {
+        assert_eq!(endpoint("http://localhost:8080/v1/", "chat/completions"), "http://localhost:8080/v1/chat/completions");
+        assert_eq!(endpoint("https://api.anthropic.com", "v1/messages"), "https://api.anthropic.com/v1/messages");
+    }
+
+    #[test]
+    fn test_response_text_extraction() {
+        let anthropic = serde_json::json!({
+            "content": [{"type": "text", "text": "{\"type\": "}, {"type": "text", "text": "\"api\"}"}]
+        });
+        assert_eq!(anthropic_text(&anthropic).as_deref(), Some("{\"type\": \"api\"}"));
+        assert!(anthropic_text(&serde_json::json!({"content": []})).is_none());
+
+        let openai = serde_json::json!({"choices": [{"message": {"role": "assistant", "content": "ok"}}]});
+        assert_eq!(openai_text(&openai).as_deref(), Some("ok"));
+        assert!(openai_text(&serde_json::json!({"error": "bad"})).is_none());
+    }
+}
@@ -1,10 +1,12 @@
 //! Shared debug logging utilities for hooks.
 //!
 //! Provides consistent logging across all hook handlers with per-hook log files.
+//! Messages are also debug events, shown on stderr with `-vv`.
 
 use chrono::Utc;
 use std::fs::OpenOptions;
 use std::io::Write;
+use std::path::PathBuf;
 
 /// Enable/disable debug logging globally
 pub const DEBUG: bool = true;
@@ -14,6 +16,23 @@ pub fn get_log_path(hook_name: &str) -> String {
     format!("/tmp/hippocampus-{}-hook.log", hook_name)
 }
 
+/// Debug log files that exist, one per hook that has run
+pub fn hook_log_paths() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir("/tmp")
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("hippocampus-") && n.ends_with("-hook.log"))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
 /// Debug logging with hook name prefix
 ///
 /// Writes timestamped log entries to hook-specific log files.
@@ -22,6 +41,7 @@ pub fn debug(hook_name: &str, msg: &str) {
     if !DEBUG {
         return;
     }
+    tracing::debug!(hook = hook_name, "{}", msg);
     let timestamp = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ");
     let line = format!("[{}] [{}] {}\n", timestamp, hook_name, msg);
     let log_path = get_log_path(hook_name);
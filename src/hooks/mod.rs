@@ -1,24 +1,41 @@
 //! Hook handlers for Claude Code settings.json integration.
 //!
 //! Each hook reads JSON from stdin and outputs JSON with decision/reason fields.
+//! Stdout carries nothing else: text for Claude goes in `hookSpecificOutput`,
+//! diagnostics go to stderr and the hook log files.
 
 pub mod debug;
 pub mod session_start;
 pub mod user_prompt_submit;
 pub mod stop;
+pub mod subagent_stop;
 pub mod session_end;
 pub mod post_tool_use;
+pub mod notification;
+pub mod staleness;
+pub mod simulate;
+pub mod recorder;
+pub mod rules;
+pub mod strict;
+pub mod timing;
 
 pub use session_start::handle_session_start;
 pub use user_prompt_submit::handle_user_prompt_submit;
 pub use stop::handle_stop;
+pub use subagent_stop::handle_subagent_stop;
 pub use session_end::handle_session_end;
 pub use post_tool_use::{handle_post_tool_use, PostToolUseInput};
+pub use notification::handle_notification;
+pub use simulate::{simulate_hook, SimulationReport};
 
 use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPool;
+
+use crate::cli::HookType;
+use crate::error::{HippocampusError, Result};
 
 /// Standard input format for hooks (from Claude Code)
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct HookInput {
     /// Claude's session identifier
     #[serde(alias = "sessionId")]
@@ -33,6 +50,14 @@ pub struct HookInput {
     pub permission_mode: Option<String>,
     /// Hook event name
     pub hook_event_name: Option<String>,
+    /// Subagent's own transcript (for SubagentStop)
+    pub agent_transcript_path: Option<String>,
+    /// Notification text (for Notification)
+    pub message: Option<String>,
+    /// Notification title (for Notification)
+    pub title: Option<String>,
+    /// Kind of notification, e.g. permission_prompt or idle_prompt
+    pub notification_type: Option<String>,
 }
 
 /// Standard output format for hooks
@@ -43,6 +68,18 @@ pub struct HookOutput {
     /// Optional reason/context message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<String>,
+    /// Text added to Claude's context
+    #[serde(rename = "hookSpecificOutput", skip_serializing_if = "Option::is_none")]
+    pub hook_specific_output: Option<HookSpecificOutput>,
+}
+
+/// Event-specific part of a hook's output
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookSpecificOutput {
+    /// Event the output answers, e.g. UserPromptSubmit
+    pub hook_event_name: String,
+    pub additional_context: String,
 }
 
 impl HookOutput {
@@ -51,6 +88,7 @@ impl HookOutput {
         Self {
             decision: "approve".to_string(),
             reason: None,
+            hook_specific_output: None,
         }
     }
 
@@ -59,6 +97,7 @@ impl HookOutput {
         Self {
             decision: "approve".to_string(),
             reason: Some(reason),
+            hook_specific_output: None,
         }
     }
 
@@ -67,14 +106,73 @@ impl HookOutput {
         Self {
             decision: "block".to_string(),
             reason: Some(reason),
+            hook_specific_output: None,
         }
     }
+
+    /// Add `context` to Claude's context for the `event` hook
+    pub fn with_context(mut self, event: &str, context: String) -> Self {
+        self.hook_specific_output = Some(HookSpecificOutput {
+            hook_event_name: event.to_string(),
+            additional_context: context,
+        });
+        self
+    }
+}
+
+/// Parse raw hook stdin; empty input yields an empty HookInput
+pub fn parse_hook_input(raw_input: &str) -> Result<HookInput> {
+    if raw_input.is_empty() {
+        return Ok(HookInput::default());
+    }
+    serde_json::from_str(raw_input)
+        .map_err(|e| HippocampusError::Config(format!("Failed to parse hook input: {}", e)))
+}
+
+/// Run the handler for a hook type against raw stdin input
+pub async fn run_hook(
+    pool: &PgPool,
+    hook_type: &HookType,
+    raw_input: &str,
+) -> Result<serde_json::Value> {
+    // PostToolUse has a different input format and never fails on bad input
+    if *hook_type == HookType::PostToolUse {
+        let input: PostToolUseInput = serde_json::from_str(raw_input).unwrap_or_default();
+        let output = handle_post_tool_use(pool, &input).await?;
+        return Ok(serde_json::to_value(&output)?);
+    }
+
+    let input = parse_hook_input(raw_input)?;
+    let output = match hook_type {
+        HookType::SessionStart => handle_session_start(pool, &input).await?,
+        HookType::UserPromptSubmit => handle_user_prompt_submit(pool, &input).await?,
+        HookType::Stop => handle_stop(&input).await?,
+        HookType::SubagentStop => handle_subagent_stop(&input).await?,
+        HookType::Notification => handle_notification(pool, &input).await?,
+        HookType::SessionEnd => handle_session_end(pool, &input).await?,
+        HookType::PostToolUse => unreachable!("Handled above"),
+    };
+
+    Ok(serde_json::to_value(&output)?)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_hook_input_empty() {
+        let input = parse_hook_input("").unwrap();
+        assert!(input.session_id.is_none());
+        assert!(input.prompt.is_none());
+    }
+
+    #[test]
+    fn test_parse_hook_input_invalid() {
+        let err = parse_hook_input("not json").unwrap_err();
+        assert!(err.to_string().contains("Failed to parse hook input"));
+    }
+
     #[test]
     fn test_hook_output_approve() {
         let output = HookOutput::approve();
@@ -89,6 +187,16 @@ mod tests {
         assert_eq!(output.reason, Some("context loaded".to_string()));
     }
 
+    #[test]
+    fn test_hook_output_with_context() {
+        let output = HookOutput::approve().with_context("UserPromptSubmit", "<task/>".to_string());
+        let json = serde_json::to_value(&output).unwrap();
+        assert_eq!(json["decision"], "approve");
+        assert_eq!(json["hookSpecificOutput"]["hookEventName"], "UserPromptSubmit");
+        assert_eq!(json["hookSpecificOutput"]["additionalContext"], "<task/>");
+        assert!(serde_json::to_value(HookOutput::approve()).unwrap().get("hookSpecificOutput").is_none());
+    }
+
     #[test]
     fn test_hook_output_block() {
         let output = HookOutput::block("error occurred".to_string());
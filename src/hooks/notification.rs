@@ -0,0 +1,82 @@
+//! Notification hook handler.
+//!
+//! Records Claude Code notifications (permission prompts, idle reminders) in
+//! the session's tool-call history under the `Notification` pseudo-tool, so
+//! they appear in order with the tool calls of the turn they interrupted.
+//! They are not counted as tool calls by `turn-latency`.
+
+use sqlx::PgPool;
+
+use crate::db::queries::record_tool_call;
+use crate::error::Result;
+
+use super::debug::debug as debug_log;
+use super::post_tool_use::resolve_session_and_turn;
+use super::strict::report_failure;
+use super::{HookInput, HookOutput};
+
+const HOOK_NAME: &str = "notification";
+
+/// Tool name notifications are recorded under
+pub const NOTIFICATION_TOOL: &str = "Notification";
+
+/// Debug logging wrapper for this hook
+fn debug(msg: &str) {
+    debug_log(HOOK_NAME, msg);
+}
+
+/// Handle the Notification hook
+pub async fn handle_notification(pool: &PgPool, input: &HookInput) -> Result<HookOutput> {
+    debug("=== Notification hook started ===");
+
+    let Some(claude_session_id) = input.session_id.as_deref() else {
+        debug("Skipping - no session ID");
+        return Ok(HookOutput::approve());
+    };
+    let (session_id, turn_id) = resolve_session_and_turn(pool, claude_session_id).await?;
+    if session_id.is_none() {
+        debug(&format!("Skipping - unknown session {}", claude_session_id));
+        return Ok(HookOutput::approve());
+    }
+
+    debug(&format!(
+        "Recording {:?} notification for session={:?}, turn={:?}",
+        input.notification_type, session_id, turn_id
+    ));
+    let parameters = serde_json::json!({
+        "notification_type": input.notification_type,
+        "title": input.title,
+        "message": input.message,
+    });
+    if let Err(e) =
+        record_tool_call(pool, session_id, turn_id, NOTIFICATION_TOOL, Some(parameters), None).await
+    {
+        report_failure(HOOK_NAME, format!("Failed to record notification: {}", e));
+    }
+
+    debug("=== Notification hook completed ===");
+    Ok(HookOutput::approve())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notification_input_parsing() {
+        let json = r#"{
+            "session_id": "abc",
+            "hook_event_name": "Notification",
+            "message": "Claude needs your permission to use Bash",
+            "notification_type": "permission_prompt"
+        }"#;
+        let input: HookInput = serde_json::from_str(json).unwrap();
+        assert_eq!(input.notification_type.as_deref(), Some("permission_prompt"));
+        assert_eq!(input.message.as_deref(), Some("Claude needs your permission to use Bash"));
+        assert!(input.title.is_none());
+    }
+}
@@ -1,28 +1,51 @@
 //! PostToolUse hook handler.
 //!
-//! Records tool calls to the database for session tracking.
+//! Records tool calls to the database for session tracking (filtered by
+//! `tool_recording` in hippocampus.toml), flags memories for review when a
+//! tool deletes or rewrites a file they reference, records memories that
+//! memory commands return as cited in the turn, and adds gotchas for
+//! failing Bash commands (see `rules`).
 //! Input: JSON with { tool_name, tool_input, tool_response, session_id }
 //! Output: JSON with decision: approve (always approve, just logging)
 
 use serde::Deserialize;
+use serde_json::Value;
 use sqlx::PgPool;
+use uuid::Uuid;
 
-use crate::db::queries::{find_session_by_claude_id, record_tool_call};
+use crate::commands::gotchas::is_failed_call;
+use crate::commands::memory::{add_memory, AddMemoryOptions, AddMemoryResult};
+use crate::config::{DbConfig, ToolRecording};
+use crate::db::{get_project_path, normalize_project_path};
+use crate::db::queries::{
+    find_latest_turn_id, find_session_by_claude_id, list_session_tool_calls, record_citations,
+    record_tool_call, resolve_project_paths, CitationKind,
+};
 use crate::error::Result;
-use crate::session::load_session_state;
+use crate::models::{Confidence, MemoryType, Tier};
+use crate::session::load_own_session_state;
 
 use super::debug::debug as debug_log;
+use super::rules::{command_key, derive_gotcha, failure_output};
+use super::staleness::{detect_file_changes, flag_stale_memories};
+use super::strict::report_failure;
 use super::HookOutput;
 
 const HOOK_NAME: &str = "post-tool-use";
 
+/// Longest result summary stored for a tool call, in characters
+const MAX_SUMMARY_CHARS: usize = 500;
+
+/// Trailing output lines kept from a Bash command
+const BASH_TAIL_LINES: usize = 5;
+
 /// Debug logging wrapper for this hook
 fn debug(msg: &str) {
     debug_log(HOOK_NAME, msg);
 }
 
 /// Input format for PostToolUse hook (different from standard HookInput)
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct PostToolUseInput {
     /// Tool name
     #[serde(alias = "toolName")]
@@ -36,6 +59,9 @@ pub struct PostToolUseInput {
     /// Claude session ID
     #[serde(alias = "sessionId")]
     pub session_id: Option<String>,
+    /// Current working directory
+    #[serde(default)]
+    pub cwd: Option<String>,
 }
 
 /// Handle the PostToolUse hook
@@ -48,44 +74,81 @@ pub async fn handle_post_tool_use(pool: &PgPool, input: &PostToolUseInput) -> Re
     // Get session and turn IDs
     let (session_id, turn_id) = if let Some(claude_session_id) = &input.session_id {
         debug(&format!("Session ID: {}", claude_session_id));
-        // Try session state file first
-        if let Ok(Some(state)) = load_session_state(Some(claude_session_id)) {
-            debug(&format!("Loaded session state: session={:?}, turn={:?}", state.session_id, state.current_turn_id));
-            (state.session_id, state.current_turn_id)
-        } else {
-            // Fallback to database lookup
-            debug("Session state not found, checking database");
-            let session = find_session_by_claude_id(pool, claude_session_id).await?;
-            (session.map(|s| s.id), None)
-        }
+        resolve_session_and_turn(pool, claude_session_id).await?
     } else {
         debug("No session ID provided");
         (None, None)
     };
+    debug(&format!("Attributing to session={:?}, turn={:?}", session_id, turn_id));
 
-    // Truncate response to summary (first 500 chars)
-    let result_summary = input.tool_response.as_ref().map(|r| {
-        let s = r.to_string();
-        if s.len() > 500 {
-            format!("{}...", &s[..497])
+    let config = DbConfig::load().unwrap_or_default();
+    let mut recorded = false;
+    if should_record(&config.tool_recording, tool_name, sample_roll()) {
+        let result_summary = input
+            .tool_response
+            .as_ref()
+            .map(|r| summarize_tool_response(tool_name, input.tool_input.as_ref(), r));
+
+        debug(&format!("Result summary length: {} chars", result_summary.as_ref().map(|s| s.len()).unwrap_or(0)));
+
+        // Record the tool call (don't fail the hook on logging failure)
+        debug("Recording tool call to database");
+        if let Err(e) = record_tool_call(
+            pool,
+            session_id,
+            turn_id,
+            tool_name,
+            input.tool_input.clone(),
+            result_summary,
+        )
+        .await
+        {
+            report_failure(HOOK_NAME, format!("Failed to record tool call: {}", e));
         } else {
-            s
+            recorded = true;
         }
-    });
-
-    debug(&format!("Result summary length: {} chars", result_summary.as_ref().map(|s| s.len()).unwrap_or(0)));
+    } else {
+        debug("Tool call not selected for recording");
+    }
 
-    // Record the tool call (ignore errors - don't block on logging failure)
-    debug("Recording tool call to database");
-    let _ = record_tool_call(
-        pool,
-        session_id,
-        turn_id,
+    // Flag memories referencing files this tool deleted or rewrote
+    let changes = detect_file_changes(
         tool_name,
-        input.tool_input.clone(),
-        result_summary,
-    )
-    .await;
+        input.tool_input.as_ref(),
+        input.tool_response.as_ref(),
+    );
+    if !changes.is_empty() {
+        let project_path = input.cwd.clone().or_else(get_project_path);
+        if let Some(project_path) = project_path {
+            match flag_stale_memories(pool, &project_path, &changes).await {
+                Ok(flagged) => debug(&format!(
+                    "{} file change(s), {} memories flagged for review",
+                    changes.len(),
+                    flagged
+                )),
+                Err(e) => {
+                    report_failure(HOOK_NAME, format!("Failed to flag stale memories: {}", e))
+                }
+            }
+        }
+    }
+
+    // Memories a memory command returned were cited in this turn
+    let cited = cited_memory_ids(tool_name, input.tool_input.as_ref(), input.tool_response.as_ref());
+    if !cited.is_empty() {
+        match record_citations(pool, &cited, session_id, turn_id, CitationKind::Cited).await {
+            Ok(recorded) => debug(&format!("{} memories cited", recorded)),
+            Err(e) => report_failure(HOOK_NAME, format!("Failed to record citations: {}", e)),
+        }
+    }
+
+    // Commands that keep failing the same way become gotchas
+    if config.failure_rules.enabled && tool_name == "Bash" {
+        let project_path = input.cwd.as_deref().map(normalize_project_path).or_else(get_project_path);
+        if let Err(e) = add_failure_gotcha(pool, &config, input, session_id, turn_id, recorded, project_path).await {
+            report_failure(HOOK_NAME, format!("Failed to add failure gotcha: {}", e));
+        }
+    }
 
     debug("=== Post tool use hook completed ===");
 
@@ -93,6 +156,283 @@ pub async fn handle_post_tool_use(pool: &PgPool, input: &PostToolUseInput) -> Re
     Ok(HookOutput::approve())
 }
 
+// ============================================================================
+// Failure Gotchas
+// ============================================================================
+
+/// Add the gotcha a failing Bash command matches, once the command has
+/// failed as often in the session as the rule asks (counted from the
+/// recorded tool calls, plus this one when it was not recorded)
+async fn add_failure_gotcha(
+    pool: &PgPool,
+    config: &DbConfig,
+    input: &PostToolUseInput,
+    session_id: Option<Uuid>,
+    turn_id: Option<Uuid>,
+    recorded: bool,
+    project_path: Option<String>,
+) -> Result<()> {
+    let Some(output) = input.tool_response.as_ref().and_then(failure_output) else {
+        return Ok(());
+    };
+    let command = input
+        .tool_input
+        .as_ref()
+        .and_then(|i| i.get("command"))
+        .and_then(Value::as_str)
+        .unwrap_or("");
+    let Some(gotcha) = derive_gotcha(&config.failure_rules.ordered(), command, &output)? else {
+        return Ok(());
+    };
+
+    if gotcha.repeats > 1 {
+        let key = command_key(command);
+        let failures = match session_id {
+            Some(id) => list_session_tool_calls(pool, id)
+                .await?
+                .iter()
+                .filter(|call| call.tool_name == "Bash" && is_failed_call(call))
+                .filter(|call| {
+                    let command = call.parameters.as_ref().and_then(|p| p.get("command")).and_then(Value::as_str);
+                    command.is_some_and(|c| command_key(c) == key)
+                })
+                .count(),
+            None => 0,
+        } + usize::from(!recorded);
+        if failures < gotcha.repeats {
+            debug(&format!("{} failed {} of {} times for {}", key, failures, gotcha.repeats, gotcha.rule));
+            return Ok(());
+        }
+    }
+
+    let project_path = match project_path {
+        Some(path) => resolve_project_paths(pool, &[path]).await?.into_iter().next(),
+        None => None,
+    };
+    let opts = AddMemoryOptions {
+        memory_type: MemoryType::Gotcha,
+        content: gotcha.content,
+        summary: None,
+        tags: vec!["failure".to_string(), gotcha.rule.clone()],
+        confidence: Confidence::Low,
+        tier: Tier::Project,
+        project_path,
+        source_session_id: session_id,
+        source_turn_id: turn_id,
+        supersedes: None,
+        constraints: Vec::new(),
+        duplicate_check: config.duplicate_check(),
+        lint: config.lint.clone(),
+    };
+    match add_memory(pool, opts, None).await? {
+        AddMemoryResult::Success(_) => debug(&format!("Added gotcha from rule {}", gotcha.rule)),
+        AddMemoryResult::Duplicate(_) => debug(&format!("Gotcha from rule {} already known", gotcha.rule)),
+        AddMemoryResult::Rejected(_) => debug(&format!("Gotcha from rule {} rejected by lint", gotcha.rule)),
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Turn Attribution
+// ============================================================================
+
+/// Database session and turn a tool call belongs to.
+///
+/// UserPromptSubmit writes the active turn to the session state file. Without
+/// a state file for this session (the legacy shared file may belong to a
+/// concurrent one), the session's latest turn is looked up in the database.
+pub(super) async fn resolve_session_and_turn(
+    pool: &PgPool,
+    claude_session_id: &str,
+) -> Result<(Option<Uuid>, Option<Uuid>)> {
+    let (session_id, turn_id) = match load_own_session_state(claude_session_id) {
+        Some(state) => {
+            debug(&format!("Loaded session state: session={:?}, turn={:?}", state.session_id, state.current_turn_id));
+            (state.session_id, state.current_turn_id)
+        }
+        None => {
+            debug("Session state not found, checking database");
+            let session = find_session_by_claude_id(pool, claude_session_id).await?;
+            (session.map(|s| s.id), None)
+        }
+    };
+
+    match (session_id, turn_id) {
+        (Some(session), None) => Ok((Some(session), find_latest_turn_id(pool, session).await?)),
+        ids => Ok(ids),
+    }
+}
+
+// ============================================================================
+// Recording Filter
+// ============================================================================
+
+/// Whether a call to `tool_name` is recorded, given a uniform `roll` in [0, 1)
+fn should_record(recording: &ToolRecording, tool_name: &str, roll: f64) -> bool {
+    let listed = |tools: &[String]| tools.iter().any(|t| t == "*" || t == tool_name);
+    listed(&recording.include) && !listed(&recording.exclude) && roll < recording.sample_rate
+}
+
+/// Uniform random number in [0, 1) for sampling
+fn sample_roll() -> f64 {
+    // The low 53 bits of a v4 UUID are random
+    (Uuid::new_v4().as_u128() & ((1 << 53) - 1)) as f64 / (1u64 << 53) as f64
+}
+
+// ============================================================================
+// Citations
+// ============================================================================
+
+/// Commands whose output puts memories in front of the model
+const READ_COMMANDS: &[&str] = &[
+    "get-memory",
+    "get-context",
+    "search-keyword",
+    "search-by-type",
+    "search-by-tag",
+    "list-recent",
+];
+
+/// Memory IDs in the output of a `claude-hippocampus` read command run
+/// through Bash
+fn cited_memory_ids(tool_name: &str, tool_input: Option<&Value>, response: Option<&Value>) -> Vec<Uuid> {
+    if tool_name != "Bash" {
+        return Vec::new();
+    }
+    let command = tool_input
+        .and_then(|i| i.get("command"))
+        .and_then(Value::as_str)
+        .unwrap_or("");
+    let mut words = command.split_whitespace();
+    let is_read = words.any(|w| w.ends_with("claude-hippocampus"))
+        && words.next().is_some_and(|sub| READ_COMMANDS.contains(&sub));
+    if !is_read {
+        return Vec::new();
+    }
+
+    let Some(output) = response
+        .and_then(|r| r.get("stdout"))
+        .and_then(Value::as_str)
+        .and_then(|stdout| serde_json::from_str::<Value>(stdout).ok())
+    else {
+        return Vec::new();
+    };
+    let mut ids = Vec::new();
+    collect_ids(&output, &mut ids);
+    ids
+}
+
+/// Every `"id"` field holding a UUID, at any depth
+fn collect_ids(value: &Value, ids: &mut Vec<Uuid>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(id) = map.get("id").and_then(Value::as_str).and_then(|s| Uuid::parse_str(s).ok()) {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+            map.values().for_each(|v| collect_ids(v, ids));
+        }
+        Value::Array(items) => items.iter().for_each(|v| collect_ids(v, ids)),
+        _ => {}
+    }
+}
+
+// ============================================================================
+// Result Summaries
+// ============================================================================
+
+/// Summarize a tool response for the tool-call history.
+///
+/// Bash keeps its exit code and last output lines, Read its path and size,
+/// Grep its match count. Other tools, and responses in an unexpected shape,
+/// keep the start of the raw JSON.
+pub fn summarize_tool_response(
+    tool_name: &str,
+    tool_input: Option<&Value>,
+    response: &Value,
+) -> String {
+    let summary = match tool_name {
+        "Bash" => summarize_bash(response),
+        "Read" => summarize_read(tool_input, response),
+        "Grep" => summarize_grep(response),
+        _ => None,
+    };
+    truncate_chars(&summary.unwrap_or_else(|| response.to_string()), MAX_SUMMARY_CHARS)
+}
+
+fn summarize_bash(response: &Value) -> Option<String> {
+    // Error responses keep their raw form so failures stay recognizable
+    if response.get("is_error").and_then(Value::as_bool) == Some(true) {
+        return None;
+    }
+    let stdout = response.get("stdout").and_then(Value::as_str);
+    let stderr = response.get("stderr").and_then(Value::as_str);
+    if stdout.is_none() && stderr.is_none() {
+        return None;
+    }
+
+    let mut lines = Vec::new();
+    let exit_code = ["exit_code", "exitCode", "returnCode"]
+        .iter()
+        .find_map(|key| response.get(*key).and_then(Value::as_i64));
+    if let Some(code) = exit_code {
+        lines.push(format!("exit code: {}", code));
+    }
+    if response.get("interrupted").and_then(Value::as_bool) == Some(true) {
+        lines.push("interrupted".to_string());
+    }
+    lines.extend(tail_lines(stdout.unwrap_or(""), BASH_TAIL_LINES));
+    let stderr_tail = tail_lines(stderr.unwrap_or(""), BASH_TAIL_LINES);
+    if !stderr_tail.is_empty() {
+        lines.push("stderr:".to_string());
+        lines.extend(stderr_tail);
+    }
+
+    Some(lines.join("\n"))
+}
+
+fn summarize_read(tool_input: Option<&Value>, response: &Value) -> Option<String> {
+    let file = response.get("file")?;
+    let content = file.get("content").and_then(Value::as_str)?;
+    let path = file
+        .get("filePath")
+        .and_then(Value::as_str)
+        .or_else(|| tool_input.and_then(|i| i.get("file_path")).and_then(Value::as_str))
+        .unwrap_or("unknown");
+
+    Some(format!("{} ({} bytes)", path, content.len()))
+}
+
+fn summarize_grep(response: &Value) -> Option<String> {
+    let count = |key: &str| response.get(key).and_then(Value::as_u64);
+    let files = count("numFiles")?;
+
+    let summary = match (count("numMatches"), response.get("mode").and_then(Value::as_str)) {
+        (Some(matches), _) => format!("{} matches in {} files", matches, files),
+        (None, Some("content")) => format!("{} matching lines", count("numLines").unwrap_or(0)),
+        _ => format!("{} files matched", files),
+    };
+    Some(summary)
+}
+
+/// Last `n` non-empty lines of some output
+fn tail_lines(output: &str, n: usize) -> Vec<String> {
+    let lines: Vec<&str> = output.lines().filter(|l| !l.trim().is_empty()).collect();
+    lines[lines.len().saturating_sub(n)..]
+        .iter()
+        .map(|l| l.to_string())
+        .collect()
+}
+
+fn truncate_chars(s: &str, max: usize) -> String {
+    if s.chars().count() > max {
+        format!("{}...", s.chars().take(max - 3).collect::<String>())
+    } else {
+        s.to_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,4 +515,125 @@ mod tests {
         let input: PostToolUseInput = serde_json::from_str(json).unwrap();
         assert!(input.tool_response.is_some());
     }
+
+    // -------------------------------------------------------------------------
+    // Recording filter tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_should_record_defaults_to_mutating_tools() {
+        let recording = ToolRecording::default();
+        assert!(should_record(&recording, "Bash", 0.0));
+        assert!(should_record(&recording, "Edit", 0.99));
+        assert!(!should_record(&recording, "Read", 0.0));
+        assert!(!should_record(&recording, "Glob", 0.0));
+    }
+
+    #[test]
+    fn test_should_record_wildcard_exclude_and_sampling() {
+        let recording = ToolRecording {
+            include: vec!["*".to_string()],
+            exclude: vec!["Glob".to_string()],
+            sample_rate: 0.25,
+        };
+        assert!(should_record(&recording, "Read", 0.1));
+        assert!(!should_record(&recording, "Read", 0.5));
+        assert!(!should_record(&recording, "Glob", 0.1));
+    }
+
+    #[test]
+    fn test_sample_roll_in_range() {
+        for _ in 0..100 {
+            let roll = sample_roll();
+            assert!((0.0..1.0).contains(&roll));
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // Result summary tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_summarize_bash_keeps_exit_code_and_tail() {
+        let stdout: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+        let response = serde_json::json!({
+            "stdout": stdout,
+            "stderr": "error: could not compile `foo`\n",
+            "exit_code": 101,
+            "interrupted": false
+        });
+        let summary = summarize_tool_response("Bash", None, &response);
+        assert_eq!(
+            summary,
+            "exit code: 101\nline 16\nline 17\nline 18\nline 19\nline 20\nstderr:\nerror: could not compile `foo`"
+        );
+    }
+
+    #[test]
+    fn test_summarize_bash_error_response_kept_raw() {
+        let response = serde_json::json!({"is_error": true, "stdout": "", "stderr": "boom"});
+        let summary = summarize_tool_response("Bash", None, &response);
+        assert!(summary.contains("\"is_error\":true"));
+    }
+
+    #[test]
+    fn test_summarize_read_path_and_size() {
+        let response = serde_json::json!({
+            "type": "text",
+            "file": {"filePath": "/repo/src/lib.rs", "content": "fn main() {}\n", "numLines": 1}
+        });
+        assert_eq!(
+            summarize_tool_response("Read", None, &response),
+            "/repo/src/lib.rs (13 bytes)"
+        );
+
+        let input = serde_json::json!({"file_path": "/repo/a.rs"});
+        let response = serde_json::json!({"file": {"content": "abc"}});
+        assert_eq!(summarize_tool_response("Read", Some(&input), &response), "/repo/a.rs (3 bytes)");
+    }
+
+    #[test]
+    fn test_summarize_grep_counts() {
+        let files = serde_json::json!({"mode": "files_with_matches", "numFiles": 4, "filenames": []});
+        assert_eq!(summarize_tool_response("Grep", None, &files), "4 files matched");
+
+        let content = serde_json::json!({"mode": "content", "numFiles": 0, "numLines": 12});
+        assert_eq!(summarize_tool_response("Grep", None, &content), "12 matching lines");
+
+        let count = serde_json::json!({"mode": "count", "numFiles": 3, "numMatches": 9});
+        assert_eq!(summarize_tool_response("Grep", None, &count), "9 matches in 3 files");
+    }
+
+    // -------------------------------------------------------------------------
+    // Citations
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_cited_memory_ids_from_search_output() {
+        let id = "550e8400-e29b-41d4-a716-446655440000";
+        let input = serde_json::json!({"command": "claude-hippocampus search-keyword auth both"});
+        let stdout = serde_json::json!({"success": true, "results": [{"id": id}, {"id": id}]});
+        let response = serde_json::json!({"stdout": stdout.to_string()});
+        assert_eq!(
+            cited_memory_ids("Bash", Some(&input), Some(&response)),
+            vec![Uuid::parse_str(id).unwrap()]
+        );
+
+        // Writes and other tools cite nothing
+        let add = serde_json::json!({"command": "claude-hippocampus add-memory gotcha x"});
+        assert!(cited_memory_ids("Bash", Some(&add), Some(&response)).is_empty());
+        assert!(cited_memory_ids("Read", Some(&input), Some(&response)).is_empty());
+    }
+
+    #[test]
+    fn test_summarize_other_tools_truncated() {
+        let response = serde_json::json!({"content": "é".repeat(600)});
+        let summary = summarize_tool_response("WebFetch", None, &response);
+        assert_eq!(summary.chars().count(), MAX_SUMMARY_CHARS);
+        assert!(summary.ends_with("..."));
+
+        // Unexpected shapes fall back to the raw JSON
+        let text = serde_json::json!("plain output");
+        assert_eq!(summarize_tool_response("Bash", None, &text), "\"plain output\"");
+    }
 }
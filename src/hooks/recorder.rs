@@ -0,0 +1,302 @@
+//! Hook input recorder.
+//!
+//! When `record_hooks` is enabled in hippocampus.toml, every raw hook stdin
+//! payload is archived (with secrets redacted) to
+//! `~/.claude/logs/hook-recordings/`. The
+//! directory is a ring buffer capped at `record_hooks_limit` files, so a
+//! transient failure can be reproduced later with `replay-last-hook`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::error::{HippocampusError, Result};
+
+const RECORDINGS_DIR_NAME: &str = "hook-recordings";
+const REDACTED: &str = "[REDACTED]";
+
+/// Key fragments whose values are always redacted
+const SENSITIVE_KEYS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "authorization",
+    "credential",
+    "private_key",
+];
+
+/// Prefixes of well-known credential formats
+const SECRET_PREFIXES: &[&str] = &[
+    "sk-", "ghp_", "gho_", "ghs_", "github_pat_", "glpat-", "xoxb-", "xoxp-", "AKIA",
+];
+
+/// Shortest string treated as a credential when it has a known prefix
+const MIN_SECRET_LEN: usize = 16;
+
+/// Get the recordings directory (`~/.claude/logs/hook-recordings`)
+pub fn recordings_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().ok_or_else(|| {
+        HippocampusError::Config("Could not determine home directory".to_string())
+    })?;
+    Ok(home.join(".claude").join("logs").join(RECORDINGS_DIR_NAME))
+}
+
+/// Archive a raw hook payload, keeping at most `limit` recordings.
+pub fn record_hook_input(
+    dir: &Path,
+    hook_name: &str,
+    raw_input: &str,
+    limit: usize,
+) -> Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+
+    let file_name = format!(
+        "{}-{}-{}.json",
+        chrono::Utc::now().timestamp_millis(),
+        std::process::id(),
+        hook_name
+    );
+    let path = dir.join(file_name);
+    fs::write(&path, redact_payload(raw_input))?;
+
+    prune_recordings(dir, limit)?;
+    Ok(path)
+}
+
+/// Find the most recent recording for a hook
+pub fn latest_recording(dir: &Path, hook_name: &str) -> Result<Option<PathBuf>> {
+    if !dir.exists() {
+        return Ok(None);
+    }
+    Ok(list_recordings(dir)?
+        .into_iter()
+        .rfind(|p| recording_hook_name(p).as_deref() == Some(hook_name)))
+}
+
+/// The most recent recording of each hook, by hook name
+pub fn latest_recordings(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut latest: Vec<PathBuf> = Vec::new();
+    for path in list_recordings(dir)?.into_iter().rev() {
+        let hook = recording_hook_name(&path);
+        if !latest.iter().any(|p| recording_hook_name(p) == hook) {
+            latest.push(path);
+        }
+    }
+    latest.sort_by_key(|p| recording_hook_name(p));
+    Ok(latest)
+}
+
+/// Recordings sorted oldest first (file names start with a millisecond timestamp)
+fn list_recordings(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| recording_hook_name(p).is_some())
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Hook name from a `{millis}-{pid}-{hook}.json` file name
+fn recording_hook_name(path: &Path) -> Option<String> {
+    let stem = path.file_name()?.to_str()?.strip_suffix(".json")?;
+    let mut parts = stem.splitn(3, '-');
+    let millis = parts.next()?;
+    let pid = parts.next()?;
+    let hook = parts.next()?;
+    let numeric = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    (numeric(millis) && numeric(pid)).then(|| hook.to_string())
+}
+
+/// Delete the oldest recordings beyond the limit
+fn prune_recordings(dir: &Path, limit: usize) -> Result<()> {
+    let recordings = list_recordings(dir)?;
+    let excess = recordings.len().saturating_sub(limit);
+    for path in &recordings[..excess] {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Redact secrets from a raw payload; non-JSON input is redacted as text
+pub fn redact_payload(raw_input: &str) -> String {
+    match serde_json::from_str::<Value>(raw_input) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            serde_json::to_string_pretty(&value).unwrap_or_else(|_| redact_text(raw_input))
+        }
+        Err(_) => redact_text(raw_input),
+    }
+}
+
+fn is_sensitive_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    SENSITIVE_KEYS.iter().any(|k| key.contains(k))
+}
+
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                // Only strings: counters like `output_tokens` stay readable
+                if is_sensitive_key(key) && v.is_string() {
+                    *v = Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(v);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        Value::String(s) => *s = redact_text(s),
+        _ => {}
+    }
+}
+
+/// Redact credentials inside free text such as shell commands or transcripts.
+///
+/// Catches `KEY=value` assignments with sensitive names, tokens after
+/// `Bearer`, and strings with well-known credential prefixes.
+pub fn redact_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut after_bearer = false;
+
+    for piece in text.split_inclusive(char::is_whitespace) {
+        let word = piece.trim_end_matches(char::is_whitespace);
+        let trailing = &piece[word.len()..];
+
+        if word.is_empty() {
+            out.push_str(piece);
+            continue;
+        }
+
+        let redacted = if after_bearer {
+            Some(REDACTED.to_string())
+        } else if let Some((key, value)) = word.split_once('=') {
+            (is_sensitive_key(key.trim_start_matches("--")) && !value.is_empty())
+                .then(|| format!("{}={}", key, REDACTED))
+        } else {
+            None
+        };
+
+        let bare = word.trim_matches(|c: char| "\"'`(),;:".contains(c));
+        let redacted = redacted.or_else(|| {
+            (bare.len() >= MIN_SECRET_LEN && SECRET_PREFIXES.iter().any(|p| bare.starts_with(p)))
+                .then(|| word.replace(bare, REDACTED))
+        });
+
+        after_bearer = bare.eq_ignore_ascii_case("bearer");
+        out.push_str(redacted.as_deref().unwrap_or(word));
+        out.push_str(trailing);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    // -------------------------------------------------------------------------
+    // Redaction tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_redact_text_assignments() {
+        assert_eq!(
+            redact_text("DB_PASSWORD=hunter2 cargo run"),
+            "DB_PASSWORD=[REDACTED] cargo run"
+        );
+        assert_eq!(redact_text("--api-token=abc x"), "--api-token=[REDACTED] x");
+        assert_eq!(redact_text("RUST_LOG=debug"), "RUST_LOG=debug");
+    }
+
+    #[test]
+    fn test_redact_text_known_prefixes_and_bearer() {
+        assert_eq!(
+            redact_text("key \"sk-abcdefghijklmnopqrstu\" used"),
+            "key \"[REDACTED]\" used"
+        );
+        assert_eq!(
+            redact_text("-H 'Authorization: Bearer abc.def.ghi'\nnext"),
+            "-H 'Authorization: Bearer [REDACTED]\nnext"
+        );
+        assert_eq!(redact_text("sk-short"), "sk-short");
+    }
+
+    #[test]
+    fn test_redact_payload_json() {
+        let raw = r#"{"session_id":"s1","input_tokens":5,"tool_input":{"command":"export GITHUB_TOKEN=ghp_0123456789abcdefghij","api_key":"k"}}"#;
+        let redacted: Value = serde_json::from_str(&redact_payload(raw)).unwrap();
+        assert_eq!(redacted["session_id"], "s1");
+        assert_eq!(redacted["input_tokens"], 5);
+        assert_eq!(redacted["tool_input"]["api_key"], REDACTED);
+        assert_eq!(
+            redacted["tool_input"]["command"],
+            "export GITHUB_TOKEN=[REDACTED]"
+        );
+    }
+
+    #[test]
+    fn test_redact_payload_not_json() {
+        assert_eq!(redact_payload("password=x"), "password=[REDACTED]");
+    }
+
+    // -------------------------------------------------------------------------
+    // Ring buffer tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_recording_hook_name() {
+        assert_eq!(
+            recording_hook_name(Path::new("/r/1700000000000-42-session-start.json")),
+            Some("session-start".to_string())
+        );
+        assert_eq!(recording_hook_name(Path::new("/r/notes.json")), None);
+        assert_eq!(recording_hook_name(Path::new("/r/1-2-stop.txt")), None);
+    }
+
+    #[test]
+    fn test_record_and_find_latest() {
+        let dir = TempDir::new().unwrap();
+        record_hook_input(dir.path(), "stop", r#"{"n":1}"#, 10).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = record_hook_input(dir.path(), "stop", r#"{"n":2}"#, 10).unwrap();
+        record_hook_input(dir.path(), "session-end", "{}", 10).unwrap();
+
+        assert_eq!(latest_recording(dir.path(), "stop").unwrap(), Some(second.clone()));
+        assert_eq!(latest_recording(dir.path(), "session-start").unwrap(), None);
+
+        let latest = latest_recordings(dir.path()).unwrap();
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[1], second);
+    }
+
+    #[test]
+    fn test_record_prunes_oldest() {
+        let dir = TempDir::new().unwrap();
+        for i in 0..5 {
+            record_hook_input(dir.path(), "stop", &format!(r#"{{"n":{}}}"#, i), 3).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        let remaining = list_recordings(dir.path()).unwrap();
+        assert_eq!(remaining.len(), 3);
+        let newest = fs::read_to_string(remaining.last().unwrap()).unwrap();
+        assert!(newest.contains("\"n\": 4"));
+    }
+
+    #[test]
+    fn test_latest_recording_missing_dir() {
+        let dir = TempDir::new().unwrap();
+        let missing = dir.path().join("nope");
+        assert_eq!(latest_recording(&missing, "stop").unwrap(), None);
+    }
+}
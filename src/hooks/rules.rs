@@ -0,0 +1,256 @@
+//! Failure rules for the PostToolUse hook.
+//!
+//! When a Bash command fails, its output is matched against these rules in
+//! order, and the first match becomes a low-confidence project gotcha once
+//! the same command has failed `repeats` times in the session ("cargo test
+//! requires DATABASE_URL"). Built-in rules cover unset environment
+//! variables, missing programs and Python modules, and Rust compiler
+//! errors; `[failure_rules]` in the config adds rules ahead of them.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{HippocampusError, Result};
+
+/// Turns the output of a failing command into a gotcha
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailureRule {
+    pub name: String,
+    /// Regex searched for in the command's output
+    pub pattern: String,
+    /// Gotcha content: `{command}` stands for the command (see
+    /// [`command_key`]), `$1` or `${name}` for the pattern's groups
+    pub gotcha: String,
+    /// Failures of the same command in a session before the gotcha is added
+    #[serde(default = "default_repeats")]
+    pub repeats: usize,
+}
+
+fn default_repeats() -> usize {
+    1
+}
+
+/// Which failing commands become gotchas
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FailureRules {
+    /// Add gotchas for failing commands
+    pub enabled: bool,
+    /// Apply the built-in rules after `rules`
+    pub builtin: bool,
+    /// Rules tried before the built-in ones
+    pub rules: Vec<FailureRule>,
+}
+
+impl Default for FailureRules {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            builtin: true,
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl FailureRules {
+    /// Rules in the order they are tried
+    pub fn ordered(&self) -> Vec<FailureRule> {
+        let mut rules = self.rules.clone();
+        if self.builtin {
+            rules.extend(builtin_rules());
+        }
+        rules
+    }
+}
+
+/// Rules applied unless `failure_rules.builtin` is off
+pub fn builtin_rules() -> Vec<FailureRule> {
+    let rule = |name: &str, pattern: &str, gotcha: &str, repeats: usize| FailureRule {
+        name: name.to_string(),
+        pattern: pattern.to_string(),
+        gotcha: gotcha.to_string(),
+        repeats,
+    };
+    vec![
+        rule(
+            "env-var-unset",
+            r"\b([A-Z][A-Z0-9]*_[A-Z0-9_]+)\b[`'\x22]?,? (?:must be set|is not set|not set|is not defined|not defined|is missing)",
+            "{command} requires $1",
+            1,
+        ),
+        rule(
+            "env-var-required",
+            r"(?:environment variable|env var|set) [`'\x22]?([A-Z][A-Z0-9]*_[A-Z0-9_]+)\b",
+            "{command} requires $1",
+            1,
+        ),
+        rule(
+            "command-not-found",
+            r"(?m)([\w.+-]+): (?:command )?not found$",
+            "$1 is not installed here; {command} cannot run it",
+            1,
+        ),
+        rule(
+            "python-module",
+            r"No module named '([\w.]+)'",
+            "{command} needs the Python module $1",
+            1,
+        ),
+        rule(
+            "rust-compiler-error",
+            r"error\[(E\d{4})\]: ([^\n]+)",
+            "{command} keeps failing with $1: $2",
+            3,
+        ),
+    ]
+}
+
+/// A gotcha derived from a failing command
+#[derive(Debug, Clone, PartialEq)]
+pub struct DerivedGotcha {
+    /// Name of the rule that matched
+    pub rule: String,
+    pub content: String,
+    pub repeats: usize,
+}
+
+/// The gotcha of the first rule matching `output`, filled in for `command`
+pub fn derive_gotcha(rules: &[FailureRule], command: &str, output: &str) -> Result<Option<DerivedGotcha>> {
+    for rule in rules {
+        let pattern = Regex::new(&rule.pattern).map_err(|e| {
+            HippocampusError::Config(format!("Invalid pattern in failure rule {}: {}", rule.name, e))
+        })?;
+        let Some(captures) = pattern.captures(output) else {
+            continue;
+        };
+        let mut content = String::new();
+        captures.expand(&rule.gotcha.replace("{command}", &command_key(command)), &mut content);
+        return Ok(Some(DerivedGotcha {
+            rule: rule.name.clone(),
+            content: content.trim().to_string(),
+            repeats: rule.repeats,
+        }));
+    }
+    Ok(None)
+}
+
+/// The program and subcommand of the last step of a command line
+/// (`cd app && cargo test --lib | tail` gives "cargo test"), used to tell
+/// whether the same command failed again
+pub fn command_key(command: &str) -> String {
+    let step = command
+        .split("&&")
+        .flat_map(|s| s.split("||"))
+        .flat_map(|s| s.split(';'))
+        .filter(|s| !s.trim().is_empty())
+        .last()
+        .unwrap_or("");
+    let step = step.split('|').next().unwrap_or("");
+    let mut words = step
+        .split_whitespace()
+        .skip_while(|w| w.contains('=') || *w == "sudo" || *w == "env");
+    let Some(program) = words.next() else {
+        return String::new();
+    };
+    match words.next() {
+        Some(sub) if sub.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') && !sub.starts_with('-') => {
+            format!("{} {}", program, sub)
+        }
+        _ => program.to_string(),
+    }
+}
+
+/// Output of a Bash call that failed, or None when it succeeded
+pub fn failure_output(response: &Value) -> Option<String> {
+    if let Some(text) = response.as_str() {
+        return text.starts_with("Error").then(|| text.to_string());
+    }
+    let text = |key: &str| response.get(key).and_then(Value::as_str).unwrap_or("");
+    let is_error = ["is_error", "isError"]
+        .iter()
+        .any(|key| response.get(*key).and_then(Value::as_bool) == Some(true));
+    let exit_code = ["exit_code", "exitCode", "returnCode"]
+        .iter()
+        .find_map(|key| response.get(*key).and_then(Value::as_i64));
+    if !is_error && exit_code.is_none_or(|code| code == 0) {
+        return None;
+    }
+    let output = [text("stdout"), text("stderr"), text("error")]
+        .into_iter()
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    Some(if output.is_empty() { response.to_string() } else { output })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn derive(command: &str, output: &str) -> Option<String> {
+        derive_gotcha(&builtin_rules(), command, output)
+            .unwrap()
+            .map(|g| g.content)
+    }
+
+    #[test]
+    fn test_builtin_rules() {
+        assert_eq!(
+            derive("cargo test --workspace", "thread 'main' panicked: DATABASE_URL must be set"),
+            Some("cargo test requires DATABASE_URL".to_string())
+        );
+        assert_eq!(
+            derive(
+                "cd app && cargo build 2>&1 | tail",
+                "error: set `DATABASE_URL` to use query macros online"
+            ),
+            Some("cargo build requires DATABASE_URL".to_string())
+        );
+        assert_eq!(
+            derive("make docs", "sh: 1: mdbook: not found"),
+            Some("mdbook is not installed here; make docs cannot run it".to_string())
+        );
+        assert_eq!(derive("cargo test", "test result: FAILED. 3 passed; 1 failed"), None);
+    }
+
+    #[test]
+    fn test_invalid_user_rule_is_a_config_error() {
+        let rules = vec![FailureRule {
+            name: "broken".to_string(),
+            pattern: "(".to_string(),
+            gotcha: "x".to_string(),
+            repeats: 1,
+        }];
+        assert!(matches!(
+            derive_gotcha(&rules, "make", "out"),
+            Err(HippocampusError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_command_key() {
+        assert_eq!(command_key("RUST_LOG=debug cargo test --lib"), "cargo test");
+        assert_eq!(command_key("cd web && npm run build 2>&1 | tail -5"), "npm run");
+        assert_eq!(command_key("./scripts/check.sh --fast"), "./scripts/check.sh");
+    }
+
+    #[test]
+    fn test_failure_output() {
+        assert_eq!(failure_output(&json!({"stdout": "ok", "exit_code": 0})), None);
+        assert_eq!(
+            failure_output(&json!({"stdout": "", "stderr": "boom", "exit_code": 2})),
+            Some("boom".to_string())
+        );
+        assert_eq!(
+            failure_output(&json!("Error: Exit code 1\nmissing")),
+            Some("Error: Exit code 1\nmissing".to_string())
+        );
+        assert_eq!(failure_output(&json!({"stdout": "fine"})), None);
+    }
+}
@@ -3,14 +3,19 @@
 //! Ends the session and cleans up state files.
 
 use std::fs;
+use std::process::Stdio;
 
 use sqlx::postgres::PgPool;
 
-use crate::db::queries::end_session;
+use crate::config::DbConfig;
+use crate::daemon;
+use crate::db::queries::{end_session, rollup_memory_access};
 use crate::error::Result;
 use crate::session::{clear_session_state, load_session_state};
 
 use super::debug::debug as debug_log;
+use super::simulate::suppress;
+use super::strict::report_failure;
 use super::{HookInput, HookOutput};
 
 const HOOK_NAME: &str = "session-end";
@@ -29,8 +34,10 @@ fn get_marker_file(claude_session_id: &str) -> String {
 ///
 /// 1. Load session state
 /// 2. End session in database
-/// 3. Clean up session state file
-/// 4. Return approval
+/// 3. Roll up memory access events
+/// 4. Clean up session state file
+/// 5. Mine gotchas in the background if `auto_mine_gotchas` is enabled
+/// 6. Return approval
 pub async fn handle_session_end(pool: &PgPool, input: &HookInput) -> Result<HookOutput> {
     debug("=== Session end hook started ===");
 
@@ -60,29 +67,75 @@ pub async fn handle_session_end(pool: &PgPool, input: &HookInput) -> Result<Hook
         Err(e) => return Err(e),
     }
 
+    // Sessions ending is the periodic point for folding in access events
+    match rollup_memory_access(pool).await {
+        Ok(rollup) => debug(&format!(
+            "Rolled up {} access events for {} memories",
+            rollup.events, rollup.memories
+        )),
+        Err(e) => report_failure(HOOK_NAME, format!("Access rollup failed: {}", e)),
+    }
+
     // Clean up session state file
     debug("Clearing session state file");
-    clear_session_state(Some(&claude_session_id))?;
+    if !suppress(|| format!("clear session state for {}", claude_session_id)) {
+        clear_session_state(Some(&claude_session_id))?;
+    }
 
     // Clean up marker file for this session only (safe for concurrent sessions)
     let marker_file = get_marker_file(&claude_session_id);
     debug(&format!("Removing marker file: {}", marker_file));
-    let _ = fs::remove_file(&marker_file);
+    if !suppress(|| format!("remove marker file {}", marker_file)) {
+        let _ = fs::remove_file(&marker_file);
+    }
+
+    if auto_mine_gotchas_enabled() {
+        spawn_mine_gotchas(&claude_session_id);
+    }
 
     debug("=== Session end hook completed ===");
     Ok(HookOutput::approve())
 }
 
+/// Whether to mine gotchas at session end.
+///
+/// Never true inside an extraction subprocess, whose own sessions would
+/// otherwise be mined recursively.
+fn auto_mine_gotchas_enabled() -> bool {
+    daemon::var("CLAUDE_MEMORY_EXTRACTION").is_none()
+        && DbConfig::load().map(|c| c.auto_mine_gotchas).unwrap_or(false)
+}
+
+/// Run `mine-gotchas` for the session as a detached background process
+fn spawn_mine_gotchas(claude_session_id: &str) {
+    if suppress(|| format!("spawn mine-gotchas for {}", claude_session_id)) {
+        return;
+    }
+
+    debug(&format!("Spawning detached mine-gotchas for session: {}", claude_session_id));
+    let exe = std::env::current_exe()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| "claude-hippocampus".to_string());
+
+    match daemon::command("sh")
+        .arg("-c")
+        .arg(r#"nohup "$0" mine-gotchas --session "$1" >/dev/null 2>&1 &"#)
+        .arg(exe)
+        .arg(claude_session_id)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(_) => debug("Detached mine-gotchas process spawned"),
+        Err(e) => report_failure(HOOK_NAME, format!("Failed to spawn mine-gotchas: {}", e)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::session::{save_session_state, get_session_state_path, SessionState};
-    use std::fs;
-
-    fn cleanup_session_file(claude_session_id: &str) {
-        let path = get_session_state_path(Some(claude_session_id));
-        let _ = fs::remove_file(&path);
-    }
 
     // -------------------------------------------------------------------------
     // Input handling tests
@@ -97,6 +150,7 @@ mod tests {
             cwd: None,
             permission_mode: None,
             hook_event_name: None,
+            ..Default::default()
         };
 
         assert!(input.session_id.is_none());
@@ -111,6 +165,7 @@ mod tests {
             cwd: None,
             permission_mode: None,
             hook_event_name: Some("SessionEnd".to_string()),
+            ..Default::default()
         };
 
         assert_eq!(input.session_id, Some("end-session-test".to_string()));
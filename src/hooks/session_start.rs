@@ -1,20 +1,39 @@
 //! Session start hook handler.
 //!
-//! Creates a new session record and loads memory context.
+//! Creates a new session record and loads memory context, recording which
+//! memories were injected.
 
 use sqlx::postgres::PgPool;
 
-use crate::commands::get_context;
-use crate::db::queries::{create_session, find_session_by_id};
+use crate::commands::ranking::context_tags;
+use crate::commands::{get_context, retention_policy, ContextLayout};
+use crate::claude_md::ClaudeMd;
+use crate::config::DbConfig;
+use crate::daemon;
+use crate::db::queries::{
+    count_review_reminder, create_session, find_session_by_id, last_session_boundary,
+    list_muted_memories, record_citations, resolve_project_paths, with_workspace_projects,
+    CitationKind, ReviewReminder,
+};
+use crate::db::{get_project_path, normalize_project_path, split_project_paths};
 use crate::error::Result;
+use crate::environment::capture_environment;
 use crate::git::get_git_status;
+use crate::models::MemorySummary;
 use crate::session::{load_session_state, save_session_state, SessionState};
 
 use super::debug::debug as debug_log;
+use super::simulate::suppress;
 use super::{HookInput, HookOutput};
 
 const HOOK_NAME: &str = "session-start";
 
+/// Context entry line when `context_template` is unset
+const DEFAULT_ENTRY_TEMPLATE: &str = "{pin}{confidence} [{type}] {summary}";
+
+/// Days ahead of `prune` that unused memories are reported as expiring
+const EXPIRY_REMINDER_DAYS: i32 = 7;
+
 /// Debug logging wrapper for this hook
 fn debug(msg: &str) {
     debug_log(HOOK_NAME, msg);
@@ -36,17 +55,27 @@ pub async fn handle_session_start(pool: &PgPool, input: &HookInput) -> Result<Ho
 
     debug(&format!("Session ID: {}", claude_session_id));
 
-    let project_path = input
-        .cwd
-        .clone()
-        .or_else(|| std::env::var("PROJECT_PATH").ok())
-        .or_else(|| std::env::current_dir().ok().map(|p| p.to_string_lossy().to_string()));
+    let project_path = input.cwd.as_deref().map(normalize_project_path).or_else(get_project_path);
 
     debug(&format!("Project path: {:?}", project_path));
 
+    // Other projects listed in PROJECT_PATH are read through as well, and
+    // so are the paths registered as aliases of each
+    let mut project_paths: Vec<String> = project_path.iter().cloned().collect();
+    for path in daemon::var("PROJECT_PATH").map(|v| split_project_paths(&v)).unwrap_or_default() {
+        let path = normalize_project_path(&path);
+        if !project_paths.contains(&path) {
+            project_paths.push(path);
+        }
+    }
+    let project_paths = resolve_project_paths(pool, &project_paths).await?;
+
+    let git_status = project_path.as_ref().and_then(|p| get_git_status(p).ok()).flatten();
+
     // Check for existing session (reconnection case)
     let existing_state = load_session_state(Some(&claude_session_id))?;
     let mut session_id = None;
+    let mut environment = None;
 
     if let Some(ref state) = existing_state {
         debug("Found existing session state, checking if active");
@@ -58,6 +87,7 @@ pub async fn handle_session_start(pool: &PgPool, input: &HookInput) -> Result<Ho
                     if session.status.as_str() == "active" {
                         debug(&format!("Resuming active session: {}", id));
                         session_id = Some(*id);
+                        environment = session.environment;
                     }
                 }
             }
@@ -67,9 +97,19 @@ pub async fn handle_session_start(pool: &PgPool, input: &HookInput) -> Result<Ho
     // Create new session if needed
     if session_id.is_none() {
         debug("Creating new session");
-        let git_status = project_path.as_ref().and_then(|p| get_git_status(p).ok()).flatten();
-        let session = create_session(pool, &claude_session_id, project_path.as_deref(), git_status.as_ref()).await?;
+        let snapshot = capture_environment(project_path.as_deref());
+        debug(&format!("Environment: {:?}", snapshot));
+        // Recorded under the project's canonical path when it has aliases
+        let session = create_session(
+            pool,
+            &claude_session_id,
+            project_paths.first().map(String::as_str),
+            git_status.as_ref(),
+            Some(&snapshot),
+        )
+        .await?;
         session_id = Some(session.id);
+        environment = Some(snapshot);
         debug(&format!("Created session: {}", session.id));
 
         // Save session state for other hooks
@@ -79,35 +119,107 @@ pub async fn handle_session_start(pool: &PgPool, input: &HookInput) -> Result<Ho
             turn_number: 0,
             current_turn_id: None,
         };
-        save_session_state(&new_state)?;
-        debug("Session state saved");
+        if !suppress(|| format!("save session state for {}", claude_session_id)) {
+            save_session_state(&new_state)?;
+            debug("Session state saved");
+        }
     }
 
     // Load memory context
     debug("Loading memory context");
-    let context_result = get_context(pool, 10, project_path.as_deref()).await?;
-    debug(&format!("Loaded {} context entries", context_result.count));
+    let environment =
+        environment.unwrap_or_else(|| capture_environment(project_path.as_deref()));
+    let config = DbConfig::load().unwrap_or_default();
+    // Memories tagged like the branch or changed files rank higher (weighted)
+    let tags = git_status.as_ref().map(context_tags).unwrap_or_default();
+    let ranker = config.ranking.context_ranker(&tags);
+    let since = if config.context_since_last_session {
+        last_session_boundary(pool, &project_paths, session_id).await?
+    } else {
+        None
+    };
+    // Memories muted earlier in this session stay out when context is reloaded
+    let muted = match session_id {
+        Some(id) => list_muted_memories(pool, id).await?,
+        None => Vec::new(),
+    };
+    let reminder = match review_reminder_counts(pool, &config, &project_paths).await {
+        Ok(reminder) => reminder,
+        Err(e) => {
+            debug(&format!("Failed to count memories to review: {}", e));
+            ReviewReminder::default()
+        }
+    };
+    // Sibling projects of the workspace add memories, not session history
+    let context_paths = if config.context_include_workspace {
+        with_workspace_projects(pool, &project_paths).await?
+    } else {
+        project_paths
+    };
+    // What CLAUDE.md says is already in the system prompt
+    let claude_md = project_path.as_deref().and_then(|p| ClaudeMd::load(std::path::Path::new(p)));
+    debug(&format!("CLAUDE.md found: {}", claude_md.is_some()));
+    let layout = ContextLayout {
+        claude_md: claude_md.map(|c| (c, config.claude_md_overlap)),
+        ..ContextLayout::default()
+    };
+    let context_result = get_context(
+        pool,
+        10,
+        &context_paths,
+        Some(&environment),
+        ranker.as_ref(),
+        since,
+        &muted,
+        config.context_max_tokens,
+        &layout,
+    )
+    .await?;
+    debug(&format!("Loaded {} context entries (since {:?})", context_result.count, since));
+
+    let injected: Vec<_> = context_result.entries.iter().map(|e| e.id).collect();
+    if let Err(e) = record_citations(pool, &injected, session_id, None, CitationKind::Injected).await {
+        debug(&format!("Failed to record injected memories: {}", e));
+    }
 
-    // Build context message from entries
+    // Build context message, briefing first, then entries
     let mut context_message = String::new();
+    if let Some(briefing) = &context_result.briefing {
+        context_message.push_str(&format!("\n<project-briefing>\n{}\n</project-briefing>\n", briefing));
+    }
     if !context_result.entries.is_empty() {
-        context_message.push_str(&format!("\n<memory-context loaded=\"{}\">\n", context_result.count));
+        match since {
+            Some(since) => {
+                context_message.push_str(&format!(
+                    "\n<memory-context loaded=\"{}\" since=\"{}\">\n",
+                    context_result.count,
+                    since.to_rfc3339()
+                ));
+                context_message.push_str(&format!(
+                    "Since you last worked here, {} {} learned:\n",
+                    context_result.count,
+                    if context_result.count == 1 { "thing was" } else { "things were" }
+                ));
+            }
+            None => context_message
+                .push_str(&format!("\n<memory-context loaded=\"{}\">\n", context_result.count)),
+        }
+        let template = config.context_template.as_deref().unwrap_or(DEFAULT_ENTRY_TEMPLATE);
         for entry in &context_result.entries {
-            let conf = match entry.confidence.as_str() {
-                "high" => "★",
-                "medium" => "◐",
-                _ => "○",
-            };
-            let entry_type = entry.memory_type.as_str();
-            let content = if entry.summary.len() > 80 {
-                &entry.summary[..80]
-            } else {
-                &entry.summary
-            };
-            context_message.push_str(&format!("{} [{}] {}\n", conf, entry_type, content));
+            context_message.push_str(&entry_line(template, entry));
+            context_message.push('\n');
+        }
+        if let Some(budget) = context_result.budget.as_ref().filter(|b| b.omitted > 0) {
+            context_message.push_str(&format!(
+                "({} more left out to fit the token budget)\n",
+                budget.omitted
+            ));
         }
         context_message.push_str("</memory-context>\n");
     }
+    if let Some(note) = review_note(&reminder) {
+        context_message.push_str(&format!("\n<memory-review>\n{}</memory-review>\n", note));
+    }
 
     debug("=== Session start hook completed ===");
 
@@ -118,9 +230,69 @@ pub async fn handle_session_start(pool: &PgPool, input: &HookInput) -> Result<Ho
     }
 }
 
+/// Count the project memories to mention in the review note, against the
+/// prune thresholds in effect
+async fn review_reminder_counts(
+    pool: &PgPool,
+    config: &DbConfig,
+    project_paths: &[String],
+) -> Result<ReviewReminder> {
+    if project_paths.is_empty() {
+        return Ok(ReviewReminder::default());
+    }
+    let retention = retention_policy(pool, config).await?;
+    count_review_reminder(
+        pool,
+        project_paths,
+        retention.low_days as i32,
+        retention.medium_days as i32,
+        EXPIRY_REMINDER_DAYS,
+    )
+    .await
+}
+
+/// Fill the `context_template` placeholders for one context entry
+fn entry_line(template: &str, entry: &MemorySummary) -> String {
+    let confidence = match entry.confidence.as_str() {
+        "high" => "★",
+        "medium" => "◐",
+        _ => "○",
+    };
+    template
+        .replace("{pin}", if entry.pinned { "📌 " } else { "" })
+        .replace("{confidence}", confidence)
+        .replace("{type}", entry.memory_type.as_str())
+        .replace("{tags}", &entry.tags.join(", "))
+        .replace("{summary}", &entry.summary)
+}
+
+/// One line per kind of memory wanting attention, or None when none do
+fn review_note(reminder: &ReviewReminder) -> Option<String> {
+    let plural = |n: i64| if n == 1 { "memory" } else { "memories" };
+    let mut note = String::new();
+    if reminder.needs_review > 0 {
+        note.push_str(&format!(
+            "{} {} {} review (`claude-hippocampus review list`)\n",
+            reminder.needs_review,
+            plural(reminder.needs_review),
+            if reminder.needs_review == 1 { "needs" } else { "need" }
+        ));
+    }
+    if reminder.expiring > 0 {
+        note.push_str(&format!(
+            "{} unused {} will be pruned within {} days unless used\n",
+            reminder.expiring,
+            plural(reminder.expiring),
+            EXPIRY_REMINDER_DAYS
+        ));
+    }
+    (!note.is_empty()).then_some(note)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::{Confidence, MemoryType, Scope};
 
     // -------------------------------------------------------------------------
     // Input parsing tests
@@ -182,6 +354,47 @@ mod tests {
         assert_eq!(parsed["reason"], context);
     }
 
+    #[test]
+    fn test_review_note() {
+        assert_eq!(review_note(&ReviewReminder::default()), None);
+
+        let note = review_note(&ReviewReminder { needs_review: 3, expiring: 1 }).unwrap();
+        assert_eq!(
+            note,
+            "3 memories need review (`claude-hippocampus review list`)\n\
+             1 unused memory will be pruned within 7 days unless used\n"
+        );
+
+        let note = review_note(&ReviewReminder { needs_review: 1, expiring: 0 }).unwrap();
+        assert_eq!(note, "1 memory needs review (`claude-hippocampus review list`)\n");
+    }
+
+    #[test]
+    fn test_entry_line_template() {
+        let entry = MemorySummary {
+            id: uuid::Uuid::nil(),
+            memory_type: MemoryType::Gotcha,
+            tier: Scope::Project,
+            summary: "Run migrations before tests".to_string(),
+            tags: vec!["db".to_string(), "ci".to_string()],
+            confidence: Confidence::High,
+            created: chrono::Utc::now(),
+            access_count: 0,
+            superseded_by: None,
+            superseded_at: None,
+            is_active: true,
+            pinned: true,
+        };
+        assert_eq!(
+            entry_line(DEFAULT_ENTRY_TEMPLATE, &entry),
+            "📌 ★ [gotcha] Run migrations before tests"
+        );
+        assert_eq!(
+            entry_line("- {type}: {summary} ({tags})", &entry),
+            "- gotcha: Run migrations before tests (db, ci)"
+        );
+    }
+
     // -------------------------------------------------------------------------
     // Session state tests (unit tests without DB)
     // -------------------------------------------------------------------------
@@ -0,0 +1,145 @@
+//! Dry-run hook simulation.
+//!
+//! Runs a hook handler against a captured input with every database write
+//! inside a transaction that is rolled back. File and process side effects
+//! (session state, marker files, background extraction) are recorded instead
+//! of performed, so a hook can be debugged without touching real state.
+
+use std::sync::Mutex;
+
+use sqlx::postgres::PgPool;
+use sqlx::Row;
+
+use crate::cli::HookType;
+use crate::config::DbConfig;
+use crate::db::{create_rollback_pool, migrations, rollback};
+use crate::error::Result;
+use crate::models::SimulatedWrite;
+
+use super::run_hook;
+use super::strict::{failure_scope, take_failures};
+
+tokio::task_local! {
+    /// Side effects skipped by the simulation running in this task
+    static EFFECTS: Mutex<Vec<String>>;
+}
+
+/// Tables checked for writes: (table, insert timestamp column, summary expression)
+const WRITE_TABLES: &[(&str, &str, &str)] = &[
+    ("sessions", "started_at", "claude_session_id"),
+    ("conversation_turns", "created_at", "'turn ' || turn_number || ': ' || LEFT(user_prompt, 80)"),
+    ("tool_calls", "called_at", "tool_name"),
+    ("memories", "created_at", "type || ': ' || LEFT(content, 80)"),
+];
+
+/// Check whether hooks are running under simulation
+pub fn is_dry_run() -> bool {
+    EFFECTS.try_with(|_| ()).is_ok()
+}
+
+/// Record a side effect instead of performing it when simulating.
+///
+/// Returns true if the caller should skip the effect.
+pub fn suppress(effect: impl FnOnce() -> String) -> bool {
+    EFFECTS
+        .try_with(|effects| {
+            if let Ok(mut effects) = effects.lock() {
+                effects.push(effect());
+            }
+        })
+        .is_ok()
+}
+
+/// What a simulated hook run would have done
+#[derive(Debug)]
+pub struct SimulationReport {
+    /// The hook's JSON output (including any injected context)
+    pub output: serde_json::Value,
+    /// Rows the hook inserted or updated before the rollback
+    pub writes: Vec<SimulatedWrite>,
+    /// File and process side effects that were skipped
+    pub effects: Vec<String>,
+    /// Failures the hook recovered from
+    pub failures: Vec<String>,
+}
+
+/// Run a hook handler with all writes rolled back.
+pub async fn simulate_hook(
+    config: &DbConfig,
+    hook_type: &HookType,
+    raw_input: &str,
+) -> Result<SimulationReport> {
+    let pool = create_rollback_pool(config).await?;
+    migrations::require_current(&pool).await?;
+
+    let simulation = async {
+        let result = run_hook(&pool, hook_type, raw_input).await;
+        let effects = EFFECTS.with(|e| e.lock().map(|mut e| std::mem::take(&mut *e)).unwrap_or_default());
+        (result, effects, take_failures())
+    };
+    let (result, effects, failures) =
+        failure_scope(EFFECTS.scope(Mutex::new(Vec::new()), simulation)).await;
+    let writes = match &result {
+        Ok(_) => list_transaction_writes(&pool).await,
+        Err(_) => Ok(Vec::new()),
+    };
+
+    // Closing the connection would abort the transaction anyway; be explicit
+    let _ = rollback(&pool).await;
+    pool.close().await;
+
+    Ok(SimulationReport {
+        output: result?,
+        writes: writes?,
+        effects,
+        failures,
+    })
+}
+
+/// List rows written by the current transaction
+async fn list_transaction_writes(pool: &PgPool) -> Result<Vec<SimulatedWrite>> {
+    let mut writes = Vec::new();
+
+    for (table, inserted_column, summary) in WRITE_TABLES {
+        // NOW() is the transaction start, so rows stamped with it were inserted here
+        let sql = format!(
+            "SELECT id, {inserted_column} = NOW() AS inserted, {summary} AS summary \
+             FROM {table} WHERE xmin = pg_current_xact_id()::xid"
+        );
+        for row in sqlx::query(&sql).fetch_all(pool).await? {
+            let inserted: Option<bool> = row.get("inserted");
+            writes.push(SimulatedWrite {
+                table: table.to_string(),
+                id: row.get("id"),
+                action: if inserted.unwrap_or(false) { "insert" } else { "update" }.to_string(),
+                summary: row.get("summary"),
+            });
+        }
+    }
+
+    Ok(writes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suppress_outside_simulation() {
+        assert!(!is_dry_run());
+        assert!(!suppress(|| "write marker".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_suppress_records_in_its_simulation() {
+        let effects = EFFECTS
+            .scope(Mutex::new(Vec::new()), async {
+                assert!(is_dry_run());
+                assert!(suppress(|| "write marker".to_string()));
+                EFFECTS.with(|e| e.lock().unwrap().clone())
+            })
+            .await;
+        assert_eq!(effects, vec!["write marker".to_string()]);
+        assert!(!is_dry_run());
+    }
+}
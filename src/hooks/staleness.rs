@@ -0,0 +1,373 @@
+//! Staleness detection for the PostToolUse hook.
+//!
+//! Spots tool calls that delete or heavily rewrite files and flags the
+//! project memories that reference those files as `needs-review`.
+
+use serde_json::Value;
+use sqlx::PgPool;
+
+use crate::commands::verify::{extract_references, CodeReference};
+use crate::db::normalize_project_path;
+use crate::db::queries::{find_memories_mentioning, flag_memories_for_review, resolve_project_paths};
+use crate::error::Result;
+
+/// A Write is a heavy rewrite when at least this share of the old file was replaced
+const REWRITE_RATIO: f64 = 0.5;
+
+/// How a file changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileChangeKind {
+    Deleted,
+    Rewritten,
+}
+
+impl FileChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Deleted => "deleted",
+            Self::Rewritten => "rewritten",
+        }
+    }
+}
+
+/// A file deleted or rewritten by a tool call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: String,
+    pub kind: FileChangeKind,
+}
+
+/// Detect file deletions and heavy rewrites from a tool call.
+pub fn detect_file_changes(
+    tool_name: &str,
+    tool_input: Option<&Value>,
+    tool_response: Option<&Value>,
+) -> Vec<FileChange> {
+    match tool_name {
+        "Write" => {
+            let path = tool_input
+                .and_then(|i| i.get("file_path"))
+                .and_then(|p| p.as_str());
+            match (path, tool_response) {
+                (Some(path), Some(response)) if is_heavy_rewrite(response) => vec![FileChange {
+                    path: path.to_string(),
+                    kind: FileChangeKind::Rewritten,
+                }],
+                _ => Vec::new(),
+            }
+        }
+        "Bash" => tool_input
+            .and_then(|i| i.get("command"))
+            .and_then(|c| c.as_str())
+            .map(|command| {
+                parse_deleted_paths(command)
+                    .into_iter()
+                    .map(|path| FileChange {
+                        path,
+                        kind: FileChangeKind::Deleted,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// Check whether a Write response replaced most of an existing file.
+///
+/// New files are never rewrites. Without a patch to measure, overwriting an
+/// existing file counts as a rewrite.
+fn is_heavy_rewrite(response: &Value) -> bool {
+    if response.get("type").and_then(|t| t.as_str()) != Some("update") {
+        return false;
+    }
+
+    let Some(hunks) = response.get("structuredPatch").and_then(|p| p.as_array()) else {
+        return true;
+    };
+
+    let (mut removed, mut kept) = (0usize, 0usize);
+    for line in hunks
+        .iter()
+        .filter_map(|h| h.get("lines").and_then(|l| l.as_array()))
+        .flatten()
+        .filter_map(|l| l.as_str())
+    {
+        match line.chars().next() {
+            Some('-') => removed += 1,
+            Some('+') => {}
+            _ => kept += 1,
+        }
+    }
+
+    // Lines outside the hunks are unchanged too
+    let new_lines = response
+        .get("content")
+        .and_then(|c| c.as_str())
+        .map(|c| c.lines().count())
+        .unwrap_or(0);
+    let hunk_new_lines: usize = hunks
+        .iter()
+        .filter_map(|h| h.get("newLines").and_then(|n| n.as_u64()))
+        .sum::<u64>() as usize;
+    kept += new_lines.saturating_sub(hunk_new_lines);
+
+    let original = removed + kept;
+    original > 0 && removed as f64 / original as f64 >= REWRITE_RATIO
+}
+
+/// Extract paths removed by `rm`, `git rm`, `mv` and `git mv` in a shell command.
+///
+/// For moves, the source paths count as deleted. Globs and variables are skipped.
+pub fn parse_deleted_paths(command: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+
+    for segment in command.split(['\n', ';', '|', '&']) {
+        let mut tokens: Vec<&str> = segment.split_whitespace().collect();
+        if tokens.first() == Some(&"sudo") {
+            tokens.remove(0);
+        }
+        if tokens.first() == Some(&"git") {
+            tokens.remove(0);
+        }
+
+        let (is_move, args) = match tokens.split_first() {
+            Some((&"rm", args)) => (false, args),
+            Some((&"mv", args)) => (true, args),
+            _ => continue,
+        };
+
+        let mut operands: Vec<&str> = args
+            .iter()
+            .map(|a| a.trim_matches(|c| c == '"' || c == '\''))
+            .filter(|a| !a.is_empty() && !a.starts_with('-'))
+            .collect();
+        if is_move {
+            // Last operand is the destination
+            operands.pop();
+        }
+
+        paths.extend(
+            operands
+                .into_iter()
+                .filter(|a| !a.contains(['*', '?', '$', '`']))
+                .map(String::from),
+        );
+    }
+
+    paths
+}
+
+/// Express a tool path relative to the project root.
+///
+/// Returns None for absolute paths outside the project.
+pub fn relative_to_project(project_path: &str, path: &str) -> Option<String> {
+    let relative = if path.starts_with('/') {
+        let root = project_path.trim_end_matches('/');
+        path.strip_prefix(root)?.strip_prefix('/')?
+    } else {
+        path.trim_start_matches("./")
+    };
+
+    let relative = relative.trim_end_matches('/');
+    (!relative.is_empty()).then(|| relative.to_string())
+}
+
+/// Check whether memory content references a project-relative path.
+///
+/// A reference matches the path itself, a suffix of it (`queries.rs` for
+/// `src/db/queries.rs`), or anything inside it when the path is a directory.
+pub fn references_path(content: &str, relative_path: &str) -> bool {
+    extract_references(content).iter().any(|reference| {
+        let CodeReference::Path(referenced) = reference else {
+            return false;
+        };
+        let referenced = referenced.trim_end_matches('/');
+        referenced == relative_path
+            || relative_path.ends_with(&format!("/{}", referenced))
+            || referenced.starts_with(&format!("{}/", relative_path))
+    })
+}
+
+/// Flag project memories that reference changed files for review.
+///
+/// Memories stored under the project's aliases are flagged too. Returns
+/// the number of newly flagged memories.
+pub async fn flag_stale_memories(
+    pool: &PgPool,
+    project_path: &str,
+    changes: &[FileChange],
+) -> Result<usize> {
+    let project_paths = resolve_project_paths(pool, &[normalize_project_path(project_path)]).await?;
+    let mut flagged = 0;
+
+    for change in changes {
+        let Some(relative) = relative_to_project(project_path, &change.path) else {
+            continue;
+        };
+        let file_name = relative.rsplit('/').next().unwrap_or(&relative);
+
+        let ids: Vec<_> = find_memories_mentioning(pool, &project_paths, file_name)
+            .await?
+            .into_iter()
+            .filter(|m| references_path(&m.content, &relative))
+            .map(|m| m.id)
+            .collect();
+
+        let reason = format!("{} was {}", relative, change.kind.as_str());
+        flagged += flag_memories_for_review(pool, &ids, &reason).await?.len();
+    }
+
+    Ok(flagged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // -------------------------------------------------------------------------
+    // Bash command parsing tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_parse_deleted_paths_rm() {
+        assert_eq!(
+            parse_deleted_paths("rm -rf src/legacy old.rs"),
+            vec!["src/legacy".to_string(), "old.rs".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_parse_deleted_paths_git_rm_and_mv() {
+        assert_eq!(
+            parse_deleted_paths("git rm --cached 'a.rs' && git mv src/b.rs src/c.rs"),
+            vec!["a.rs".to_string(), "src/b.rs".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_parse_deleted_paths_mv_multiple_sources() {
+        assert_eq!(
+            parse_deleted_paths("mv a.rs b.rs dest/"),
+            vec!["a.rs".to_string(), "b.rs".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_parse_deleted_paths_ignores_other_commands() {
+        assert!(parse_deleted_paths("cargo test; ls -la | grep rm").is_empty());
+        assert!(parse_deleted_paths("echo rm").is_empty());
+    }
+
+    #[test]
+    fn test_parse_deleted_paths_skips_globs() {
+        assert_eq!(
+            parse_deleted_paths("sudo rm *.log $TMP/x keep.txt"),
+            vec!["keep.txt".to_string()]
+        );
+    }
+
+    // -------------------------------------------------------------------------
+    // Tool call detection tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_detect_write_create_is_not_rewrite() {
+        let changes = detect_file_changes(
+            "Write",
+            Some(&json!({"file_path": "/p/new.rs"})),
+            Some(&json!({"type": "create", "filePath": "/p/new.rs"})),
+        );
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_detect_write_update_without_patch() {
+        let changes = detect_file_changes(
+            "Write",
+            Some(&json!({"file_path": "/p/lib.rs"})),
+            Some(&json!({"type": "update"})),
+        );
+        assert_eq!(
+            changes,
+            vec![FileChange {
+                path: "/p/lib.rs".to_string(),
+                kind: FileChangeKind::Rewritten
+            }]
+        );
+    }
+
+    #[test]
+    fn test_detect_write_small_update_is_not_rewrite() {
+        let response = json!({
+            "type": "update",
+            "content": "a\nb\nc\nd\nE\nf\ng\nh\ni\nj",
+            "structuredPatch": [
+                {"newStart": 4, "newLines": 3, "lines": [" d", "-e", "+E", " f"]}
+            ]
+        });
+        let changes =
+            detect_file_changes("Write", Some(&json!({"file_path": "x.rs"})), Some(&response));
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_detect_write_large_update_is_rewrite() {
+        let response = json!({
+            "type": "update",
+            "content": "X\nY\nc",
+            "structuredPatch": [
+                {"newStart": 1, "newLines": 3, "lines": ["-a", "-b", "+X", "+Y", " c"]}
+            ]
+        });
+        let changes =
+            detect_file_changes("Write", Some(&json!({"file_path": "x.rs"})), Some(&response));
+        assert_eq!(changes.len(), 1);
+    }
+
+    #[test]
+    fn test_detect_bash_rm() {
+        let changes = detect_file_changes("Bash", Some(&json!({"command": "rm src/a.rs"})), None);
+        assert_eq!(
+            changes,
+            vec![FileChange {
+                path: "src/a.rs".to_string(),
+                kind: FileChangeKind::Deleted
+            }]
+        );
+    }
+
+    #[test]
+    fn test_detect_other_tools() {
+        assert!(detect_file_changes("Read", Some(&json!({"file_path": "a.rs"})), None).is_empty());
+    }
+
+    // -------------------------------------------------------------------------
+    // Path matching tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_relative_to_project() {
+        assert_eq!(
+            relative_to_project("/repo", "/repo/src/a.rs"),
+            Some("src/a.rs".to_string())
+        );
+        assert_eq!(
+            relative_to_project("/repo/", "./src/a.rs"),
+            Some("src/a.rs".to_string())
+        );
+        assert_eq!(relative_to_project("/repo", "/other/a.rs"), None);
+        assert_eq!(relative_to_project("/repo", "/repository/a.rs"), None);
+    }
+
+    #[test]
+    fn test_references_path() {
+        let content = "Queries live in src/db/queries.rs and helpers in `util.rs`";
+        assert!(references_path(content, "src/db/queries.rs"));
+        assert!(references_path(content, "src/util.rs"));
+        assert!(references_path(content, "src/db"));
+        assert!(!references_path(content, "src/db/pool.rs"));
+        assert!(!references_path(content, "src/d"));
+    }
+}
@@ -3,14 +3,20 @@
 //! Runs after each Claude response. Manages marker files to prevent duplicate processing.
 //! Spawns headless Claude to extract conclusions and save them to memory.
 
-use std::fs;
+use std::fs::{self, OpenOptions};
+use std::os::unix::process::CommandExt;
 use std::path::Path;
-use std::process::{Command, Stdio};
+use std::process::Stdio;
 
+use crate::config::DbConfig;
+use crate::daemon;
 use crate::error::Result;
+use crate::extraction::{build_extraction_prompt, is_correction};
 use crate::session::load_session_state;
 
-use super::debug::debug as debug_log;
+use super::debug::{debug as debug_log, get_log_path};
+use super::simulate::suppress;
+use super::strict::report_failure;
 use super::{HookInput, HookOutput};
 
 const HOOK_NAME: &str = "stop";
@@ -21,7 +27,7 @@ fn debug(msg: &str) {
 }
 
 /// Safely truncate a string at char boundaries (not byte boundaries)
-fn truncate_str(s: &str, max_chars: usize) -> String {
+pub(super) fn truncate_str(s: &str, max_chars: usize) -> String {
     s.chars().take(max_chars).collect()
 }
 
@@ -44,7 +50,7 @@ fn update_turn_with_response(turn_id: &str, assistant_response: &str) {
     ));
 
     // Run update-turn command synchronously (it's fast)
-    match Command::new("claude-hippocampus")
+    match daemon::command("claude-hippocampus")
         .arg("update-turn")
         .arg("--turn-id")
         .arg(turn_id)
@@ -60,11 +66,11 @@ fn update_turn_with_response(turn_id: &str, assistant_response: &str) {
                 debug("Turn updated successfully");
             } else {
                 let stderr = String::from_utf8_lossy(&output.stderr);
-                debug(&format!("Turn update failed: {}", stderr));
+                report_failure(HOOK_NAME, format!("Turn update failed: {}", stderr.trim()));
             }
         }
         Err(e) => {
-            debug(&format!("Failed to run update-turn: {}", e));
+            report_failure(HOOK_NAME, format!("Failed to run update-turn: {}", e));
         }
     }
 }
@@ -74,14 +80,15 @@ fn update_turn_with_response(turn_id: &str, assistant_response: &str) {
 /// 1. Skip if extraction instance (prevent recursion)
 /// 2. Check marker file - skip if already processed this turn
 /// 3. Read transcript and extract last user/assistant messages
-/// 4. If substantive, spawn background extraction process
-/// 5. Set marker file to prevent duplicate processing
-/// 6. Return approval
+/// 4. If `embed_turns` is set, spawn background embedding of the turn
+/// 5. If substantive, spawn background extraction process
+/// 6. Set marker file to prevent duplicate processing
+/// 7. Return approval
 pub async fn handle_stop(input: &HookInput) -> Result<HookOutput> {
     debug("=== Stop hook started ===");
 
     // Skip if this is an extraction instance (prevent recursion)
-    if std::env::var("CLAUDE_MEMORY_EXTRACTION").is_ok() {
+    if daemon::var("CLAUDE_MEMORY_EXTRACTION").is_some() {
         debug("Skipping - extraction instance");
         return Ok(HookOutput::approve());
     }
@@ -108,14 +115,18 @@ pub async fn handle_stop(input: &HookInput) -> Result<HookOutput> {
     }
 
     // Set marker to prevent duplicate processing
-    let _ = fs::write(&marker_file, "1");
+    if !suppress(|| format!("write marker file {}", marker_file)) {
+        let _ = fs::write(&marker_file, "1");
+    }
 
     // Read transcript file if available
-    let transcript = input
-        .transcript_path
-        .as_ref()
-        .and_then(|path| fs::read_to_string(path).ok())
-        .unwrap_or_default();
+    let transcript = match &input.transcript_path {
+        Some(path) => fs::read_to_string(path).unwrap_or_else(|e| {
+            report_failure(HOOK_NAME, format!("Failed to read transcript {}: {}", path, e));
+            String::new()
+        }),
+        None => String::new(),
+    };
 
     // Extract last user and assistant messages
     let (user_msg, assistant_msg) = extract_last_messages(&transcript);
@@ -142,7 +153,20 @@ pub async fn handle_stop(input: &HookInput) -> Result<HookOutput> {
             turn_id_str,
             assistant_msg.len()
         ));
-        update_turn_with_response(&turn_id_str, &assistant_msg);
+        if !suppress(|| {
+            format!(
+                "update turn {} with assistant response ({} chars)",
+                turn_id_str,
+                assistant_msg.len()
+            )
+        }) {
+            update_turn_with_response(&turn_id_str, &assistant_msg);
+        }
+        if DbConfig::load().map(|c| c.embed_turns).unwrap_or(false)
+            && !suppress(|| format!("spawn embedding of turn {}", turn_id_str))
+        {
+            spawn_embedding(&turn_id_str);
+        }
     } else {
         debug("Warning: No turn_id available, cannot save assistant response to database");
     }
@@ -168,96 +192,87 @@ pub async fn handle_stop(input: &HookInput) -> Result<HookOutput> {
     ));
 
     // Spawn background extraction process
-    spawn_extraction(&ctx);
+    if !suppress(|| format!("spawn memory extraction (confidence: {})", ctx.confidence())) {
+        spawn_extraction(&ctx);
+    }
 
     debug("=== Stop hook completed ===");
     Ok(HookOutput::approve())
 }
 
 /// Spawn background process to extract conclusions using claude --print
-fn spawn_extraction(ctx: &ExtractionContext) {
-    let prompt = build_extraction_prompt(&ctx.user_msg, &ctx.assistant_response);
-    let confidence = ctx.confidence().to_string();
-    let claude_session_id = ctx.claude_session_id.clone();
-    let db_session_id = ctx.db_session_id.clone();
-    let turn_id = ctx.turn_id.clone();
+pub(super) fn spawn_extraction(ctx: &ExtractionContext) {
+    let rules = DbConfig::load()
+        .map(|c| c.extraction_prompt_rules())
+        .unwrap_or_default();
+    let prompt = build_extraction_prompt(&ctx.user_msg, &ctx.assistant_response, &rules);
 
     debug(&format!(
         "Spawning detached extraction for session: {}, db_session: {}, turn: {}",
-        claude_session_id, db_session_id, turn_id
+        ctx.claude_session_id, ctx.db_session_id, ctx.turn_id
     ));
 
-    // Escape prompt for shell (replace single quotes with escaped version)
-    let escaped_prompt = prompt.replace('\'', "'\"'\"'");
-
-    // Build optional flags for session and turn IDs
-    let session_flag = if !db_session_id.is_empty() {
-        format!("--session \"{}\"", db_session_id)
-    } else {
-        String::new()
-    };
-    let turn_flag = if !turn_id.is_empty() {
-        format!("--turn \"{}\"", turn_id)
-    } else {
-        String::new()
-    };
-
-    // Build shell command that runs in background
-    // The script: runs claude --print, parses JSON, saves to memory
-    let script = format!(
-        r#"
-LOG="/tmp/claude-stop-hook-rust.log"
-log() {{ echo "[$(date -u +%Y-%m-%dT%H:%M:%S.000Z)] $1" >> "$LOG"; }}
-
-log "Extraction subprocess started"
-
-# Run claude --print
-OUTPUT=$(CLAUDE_MEMORY_EXTRACTION=1 claude --print -p '{escaped_prompt}' 2>/dev/null)
-log "claude --print completed, output length: ${{#OUTPUT}}"
-
-# Extract JSON from output (find first {{ to last }})
-JSON=$(echo "$OUTPUT" | grep -o '{{.*}}' | head -1)
-if [ -z "$JSON" ]; then
-    log "Failed to extract JSON from output: ${{OUTPUT:0:200}}"
-    exit 1
-fi
-
-log "Extracted JSON: $JSON"
-
-# Parse JSON fields using jq
-TYPE=$(echo "$JSON" | jq -r '.type // empty')
-CONCLUSION=$(echo "$JSON" | jq -r '.conclusion // empty')
-TAGS=$(echo "$JSON" | jq -r '.tags // empty')
-
-if [ -z "$TYPE" ] || [ -z "$CONCLUSION" ]; then
-    log "Missing required fields: type=$TYPE, conclusion=$CONCLUSION"
-    exit 1
-fi
-
-log "Parsed: type=$TYPE, conclusion=${{CONCLUSION:0:50}}..."
-
-# Save to memory
-claude-hippocampus add-memory "$TYPE" "$CONCLUSION" "$TAGS" "{confidence}" project --claude-session "{claude_session_id}" {session_flag} {turn_flag} >> "$LOG" 2>&1
-log "Memory saved successfully"
-"#,
-        escaped_prompt = escaped_prompt,
-        confidence = confidence,
-        claude_session_id = claude_session_id,
-        session_flag = session_flag,
-        turn_flag = turn_flag
-    );
-
-    // Spawn as detached background process using nohup
-    match Command::new("sh")
-        .arg("-c")
-        .arg(format!("nohup sh -c '{}' >/dev/null 2>&1 &", script.replace('\'', "'\\''")))
+    // The worker reserves quota, runs claude --print and saves the memory.
+    // Its JSON result is appended to this hook's log.
+    let program = std::env::current_exe().unwrap_or_else(|_| "claude-hippocampus".into());
+    let mut command = daemon::command(program);
+    command
+        .arg("extract-worker")
+        .arg("--prompt")
+        .arg(&prompt)
+        .arg("--confidence")
+        .arg(ctx.confidence());
+    if !ctx.db_session_id.is_empty() {
+        command.arg("--session").arg(&ctx.db_session_id);
+    }
+    if !ctx.turn_id.is_empty() {
+        command.arg("--turn").arg(&ctx.turn_id);
+    }
+
+    let log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(get_log_path(HOOK_NAME))
+        .map(Stdio::from)
+        .unwrap_or_else(|_| Stdio::null());
+
+    // A process group of its own keeps the worker alive after the hook exits
+    match command
+        .process_group(0)
         .stdin(Stdio::null())
-        .stdout(Stdio::null())
+        .stdout(log)
         .stderr(Stdio::null())
         .spawn()
     {
         Ok(_) => debug("Detached extraction process spawned"),
-        Err(e) => debug(&format!("Failed to spawn extraction: {}", e)),
+        Err(e) => report_failure(HOOK_NAME, format!("Failed to spawn extraction: {}", e)),
+    }
+}
+
+/// Embed the saved turn for `recall` in a detached `embed-turns` process
+fn spawn_embedding(turn_id: &str) {
+    debug(&format!("Spawning detached embedding for turn: {}", turn_id));
+
+    let program = std::env::current_exe().unwrap_or_else(|_| "claude-hippocampus".into());
+    let log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(get_log_path(HOOK_NAME))
+        .map(Stdio::from)
+        .unwrap_or_else(|_| Stdio::null());
+
+    match daemon::command(program)
+        .arg("embed-turns")
+        .arg("--turn")
+        .arg(turn_id)
+        .process_group(0)
+        .stdin(Stdio::null())
+        .stdout(log)
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(_) => debug("Detached embedding process spawned"),
+        Err(e) => report_failure(HOOK_NAME, format!("Failed to spawn embedding: {}", e)),
     }
 }
 
@@ -268,15 +283,6 @@ pub struct TranscriptEntry {
     pub content: Option<String>,
 }
 
-/// Represents an extracted memory decision from Claude
-#[derive(Debug, Clone)]
-#[allow(dead_code)]
-pub struct ExtractionResult {
-    pub memory_type: String,
-    pub conclusion: String,
-    pub tags: String,
-}
-
 /// Context for extraction process
 #[derive(Debug, Clone)]
 pub struct ExtractionContext {
@@ -317,93 +323,8 @@ impl ExtractionContext {
     }
 }
 
-/// Parse JSON response from Claude extraction
-#[allow(dead_code)]
-fn parse_extraction_response(output: &str) -> Option<ExtractionResult> {
-    // Find JSON in output (might have extra text before/after)
-    let start = output.find('{')?;
-    let end = output.rfind('}')? + 1;
-    let json_str = &output[start..end];
-
-    let json: serde_json::Value = serde_json::from_str(json_str).ok()?;
-
-    // Extract required fields
-    let memory_type = json.get("type")?.as_str()?.to_string();
-    let conclusion = json.get("conclusion")?.as_str()?.to_string();
-    let tags = json
-        .get("tags")
-        .and_then(|t| t.as_str())
-        .unwrap_or("")
-        .to_string();
-
-    // Validate required fields are present
-    if memory_type.is_empty() || conclusion.is_empty() {
-        return None;
-    }
-
-    Some(ExtractionResult {
-        memory_type,
-        conclusion,
-        tags,
-    })
-}
-
-/// Build the extraction prompt for Claude --print
-fn build_extraction_prompt(user_msg: &str, assistant_response: &str) -> String {
-    // Truncate inputs for efficiency
-    let user_preview: String = user_msg.chars().take(500).collect();
-    let response_preview: String = assistant_response.chars().take(1000).collect();
-
-    format!(
-        r#"You are a memory extraction assistant. Extract a conclusion from this conversation turn.
-
-USER PROMPT:
-{}
-
-ASSISTANT RESPONSE:
-{}
-
-TASK: Extract a concise conclusion from this turn. ALWAYS save something unless it's completely trivial (just "yes", "ok", greeting).
-
-Output JSON:
-{{"type": "<learning|gotcha|convention|architecture|api|preference>", "conclusion": "<max 150 chars summarizing the turn>", "tags": "<comma,separated>"}}
-
-For most turns, use type "learning". Use "gotcha" for corrections or warnings, "convention" for patterns, "architecture" for design decisions.
-
-Output ONLY the JSON, nothing else."#,
-        user_preview, response_preview
-    )
-}
-
-/// Detect if user message is a correction (warrants high confidence)
-fn is_correction(user_msg: &str) -> bool {
-    let lower = user_msg.to_lowercase();
-
-    // Correction patterns (from JS implementation)
-    let patterns = [
-        "actually",     // "actually, use X instead"
-        "no, use",      // "no, use sqlx instead"
-        "no, it",       // "no, it's not like that"
-        "always use",   // "always use async here"
-        "always do",
-        "never use",    // "never do that in production"
-        "never do",
-        "should be",    // "should be different"
-        "must be",
-        "must use",
-        "not like that",
-        "the correct",  // "the correct way is..."
-        "remember that",
-        "remember to",
-        "this project uses",
-        "this project requires",
-    ];
-
-    patterns.iter().any(|p| lower.contains(p))
-}
-
 /// Check if a turn is substantive enough to warrant extraction
-fn should_extract(user_msg: &str, assistant_response: &str) -> bool {
+pub(super) fn should_extract(user_msg: &str, assistant_response: &str) -> bool {
     // Skip very short interactions
     if user_msg.len() < 20 && assistant_response.len() < 100 {
         return false;
@@ -429,7 +350,7 @@ fn should_extract(user_msg: &str, assistant_response: &str) -> bool {
 }
 
 /// Extract the last user message and last assistant response from transcript
-fn extract_last_messages(transcript: &str) -> (Option<String>, Option<String>) {
+pub(super) fn extract_last_messages(transcript: &str) -> (Option<String>, Option<String>) {
     let lines: Vec<&str> = transcript.lines().collect();
 
     let mut last_user_msg: Option<String> = None;
@@ -439,11 +360,9 @@ fn extract_last_messages(transcript: &str) -> (Option<String>, Option<String>) {
     for line in lines.iter().rev() {
         if let Some(entry) = parse_transcript_line(line) {
             match entry.entry_type.as_str() {
-                "user" if last_user_msg.is_none() => {
-                    // Only use user messages with string content (not tool results)
-                    if entry.content.is_some() {
-                        last_user_msg = entry.content;
-                    }
+                // Only use user messages with string content (not tool results)
+                "user" if last_user_msg.is_none() && entry.content.is_some() => {
+                    last_user_msg = entry.content;
                 }
                 "assistant" if last_assistant_msg.is_none() => {
                     last_assistant_msg = entry.content;
@@ -610,99 +529,6 @@ mod tests {
         assert!(!should_extract("hi", "hello"));
     }
 
-    // -------------------------------------------------------------------------
-    // Confidence detection tests
-    // -------------------------------------------------------------------------
-
-    #[test]
-    fn test_detect_correction_actually() {
-        assert!(is_correction("actually, use tokio instead"));
-        assert!(is_correction("Actually it's the other way"));
-    }
-
-    #[test]
-    fn test_detect_correction_no_use() {
-        assert!(is_correction("no, use sqlx instead"));
-        assert!(is_correction("No, it's not like that"));
-    }
-
-    #[test]
-    fn test_detect_correction_always_never() {
-        assert!(is_correction("always use async here"));
-        assert!(is_correction("never do that in production"));
-    }
-
-    #[test]
-    fn test_detect_correction_normal_message() {
-        assert!(!is_correction("How do I implement this?"));
-        assert!(!is_correction("What's the best way to do X?"));
-    }
-
-    // -------------------------------------------------------------------------
-    // Build extraction prompt tests
-    // -------------------------------------------------------------------------
-
-    #[test]
-    fn test_build_extraction_prompt_contains_user_msg() {
-        let prompt = build_extraction_prompt("How to use async?", "Use tokio...");
-        assert!(prompt.contains("How to use async?"));
-    }
-
-    #[test]
-    fn test_build_extraction_prompt_contains_response() {
-        let prompt = build_extraction_prompt("Question", "Use tokio for async runtime");
-        assert!(prompt.contains("Use tokio for async runtime"));
-    }
-
-    #[test]
-    fn test_build_extraction_prompt_contains_json_format() {
-        let prompt = build_extraction_prompt("Q", "A");
-        assert!(prompt.contains("\"type\""));
-        assert!(prompt.contains("\"conclusion\""));
-        assert!(prompt.contains("\"tags\""));
-    }
-
-    #[test]
-    fn test_build_extraction_prompt_truncates_long_input() {
-        let long_msg = "x".repeat(1000);
-        let long_response = "y".repeat(2000);
-        let prompt = build_extraction_prompt(&long_msg, &long_response);
-        // Should truncate to reasonable limits
-        assert!(prompt.len() < 3000);
-    }
-
-    // -------------------------------------------------------------------------
-    // Parse extraction response tests
-    // -------------------------------------------------------------------------
-
-    #[test]
-    fn test_parse_extraction_response_valid() {
-        let output = r#"{"type": "learning", "conclusion": "Use tokio for async", "tags": "rust,async"}"#;
-        let result = parse_extraction_response(output).expect("should parse");
-        assert_eq!(result.memory_type, "learning");
-        assert_eq!(result.conclusion, "Use tokio for async");
-        assert_eq!(result.tags, "rust,async");
-    }
-
-    #[test]
-    fn test_parse_extraction_response_with_extra_text() {
-        let output = "Here's the extraction:\n{\"type\": \"gotcha\", \"conclusion\": \"Watch out\", \"tags\": \"warning\"}";
-        let result = parse_extraction_response(output).expect("should parse");
-        assert_eq!(result.memory_type, "gotcha");
-    }
-
-    #[test]
-    fn test_parse_extraction_response_invalid() {
-        assert!(parse_extraction_response("not json").is_none());
-        assert!(parse_extraction_response("").is_none());
-    }
-
-    #[test]
-    fn test_parse_extraction_response_missing_fields() {
-        let output = r#"{"type": "learning"}"#;
-        assert!(parse_extraction_response(output).is_none());
-    }
-
     // -------------------------------------------------------------------------
     // Extraction context tests
     // -------------------------------------------------------------------------
@@ -770,6 +596,7 @@ mod tests {
             cwd: None,
             permission_mode: None,
             hook_event_name: Some("Stop".to_string()),
+            ..Default::default()
         };
 
         let result = handle_stop(&input).await.unwrap();
@@ -803,6 +630,7 @@ mod tests {
             cwd: None,
             permission_mode: None,
             hook_event_name: Some("Stop".to_string()),
+            ..Default::default()
         };
 
         let result = handle_stop(&input).await.unwrap();
@@ -852,6 +680,7 @@ mod tests {
             cwd: None,
             permission_mode: None,
             hook_event_name: None,
+            ..Default::default()
         };
 
         let result = handle_stop(&input).await.unwrap();
@@ -879,6 +708,7 @@ mod tests {
             cwd: None,
             permission_mode: None,
             hook_event_name: None,
+            ..Default::default()
         };
 
         let result = handle_stop(&input).await.unwrap();
@@ -896,6 +726,7 @@ mod tests {
             cwd: None,
             permission_mode: None,
             hook_event_name: None,
+            ..Default::default()
         };
 
         let result = handle_stop(&input).await.unwrap();
@@ -917,6 +748,7 @@ mod tests {
             cwd: Some("/tmp".to_string()),
             permission_mode: Some("acceptEdits".to_string()),
             hook_event_name: Some("Stop".to_string()),
+            ..Default::default()
         };
 
         let result = handle_stop(&input).await.unwrap();
@@ -939,6 +771,7 @@ mod tests {
             cwd: None,
             permission_mode: None,
             hook_event_name: None,
+            ..Default::default()
         };
 
         handle_stop(&input).await.unwrap();
@@ -963,6 +796,7 @@ mod tests {
             cwd: None,
             permission_mode: None,
             hook_event_name: None,
+            ..Default::default()
         };
 
         // Call twice
@@ -0,0 +1,166 @@
+//! Strict mode for hooks.
+//!
+//! Hooks normally swallow failures and approve so a broken memory setup never
+//! gets in the way of a session. That also means a misconfigured database can
+//! silently collect nothing for weeks. With `strict = "warn"` or
+//! `strict = "block"` in hippocampus.toml, failures are surfaced to the user
+//! instead.
+
+use std::future::Future;
+use std::sync::Mutex;
+
+use crate::cli::HookType;
+use crate::config::StrictMode;
+use crate::error::Result;
+
+use super::debug::debug;
+
+tokio::task_local! {
+    /// Failures recorded by the hook running in this task
+    static FAILURES: Mutex<Vec<String>>;
+}
+
+/// Run a hook in `future`, recording its failures apart from hooks the
+/// daemon runs at the same time
+pub async fn failure_scope<F: Future>(future: F) -> F::Output {
+    FAILURES.scope(Mutex::new(Vec::new()), future).await
+}
+
+/// Record a failure that the hook otherwise recovers from. Outside a
+/// [`failure_scope`] it is only logged.
+pub fn report_failure(hook_name: &str, message: impl Into<String>) {
+    let message = message.into();
+    tracing::warn!(hook = hook_name, "{}", message);
+    debug(hook_name, &format!("FAILURE: {}", message));
+    let _ = FAILURES.try_with(|failures| {
+        if let Ok(mut failures) = failures.lock() {
+            failures.push(message);
+        }
+    });
+}
+
+/// Take the failures recorded so far
+pub fn take_failures() -> Vec<String> {
+    FAILURES
+        .try_with(|failures| failures.lock().map(|mut f| std::mem::take(&mut *f)).unwrap_or_default())
+        .unwrap_or_default()
+}
+
+/// Whether a hook's `block` decision is safe to use for surfacing failures.
+///
+/// Blocking Stop would keep Claude running on every response, and
+/// SessionStart/SessionEnd cannot block, so those only warn.
+fn can_block(hook_type: &HookType) -> bool {
+    matches!(hook_type, HookType::UserPromptSubmit | HookType::PostToolUse)
+}
+
+/// Turn a hook result plus recorded failures into the final hook output.
+///
+/// With strict mode off the result is returned unchanged, so errors still
+/// propagate as before.
+pub fn apply_strict_mode(
+    mode: StrictMode,
+    hook_type: &HookType,
+    result: Result<serde_json::Value>,
+) -> Result<serde_json::Value> {
+    let mut failures = take_failures();
+    if mode == StrictMode::Off {
+        return result;
+    }
+
+    let mut output = match result {
+        Ok(output) => output,
+        Err(e) => {
+            failures.push(e.to_string());
+            serde_json::json!({ "decision": "approve" })
+        }
+    };
+    if failures.is_empty() {
+        return Ok(output);
+    }
+
+    let message = format!(
+        "claude-hippocampus {} hook failed: {}",
+        hook_type.as_str(),
+        failures.join("; ")
+    );
+
+    if let Some(obj) = output.as_object_mut() {
+        if mode == StrictMode::Block && can_block(hook_type) {
+            obj.insert("decision".to_string(), "block".into());
+            obj.insert("reason".to_string(), message.into());
+        } else {
+            obj.insert("systemMessage".to_string(), message.into());
+        }
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::HippocampusError;
+    use serde_json::json;
+
+    #[test]
+    fn test_off_passes_errors_through() {
+        let result = apply_strict_mode(
+            StrictMode::Off,
+            &HookType::Stop,
+            Err(HippocampusError::Config("db down".to_string())),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_warn_turns_error_into_system_message() {
+        let output = apply_strict_mode(
+            StrictMode::Warn,
+            &HookType::SessionStart,
+            Err(HippocampusError::Config("db down".to_string())),
+        )
+        .unwrap();
+        assert_eq!(output["decision"], "approve");
+        assert!(output["systemMessage"]
+            .as_str()
+            .unwrap()
+            .contains("session-start hook failed: Configuration error: db down"));
+    }
+
+    #[test]
+    fn test_block_only_where_supported() {
+        let err = || Err(HippocampusError::Config("db down".to_string()));
+
+        let output = apply_strict_mode(StrictMode::Block, &HookType::UserPromptSubmit, err()).unwrap();
+        assert_eq!(output["decision"], "block");
+        assert!(output["reason"].as_str().unwrap().contains("db down"));
+
+        let output = apply_strict_mode(StrictMode::Block, &HookType::Stop, err()).unwrap();
+        assert_eq!(output["decision"], "approve");
+        assert!(output.get("systemMessage").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_failures_stay_in_their_scope() {
+        let output = failure_scope(async {
+            report_failure("stop", "summary failed");
+            failure_scope(async { report_failure("stop", "other hook") }).await;
+            apply_strict_mode(StrictMode::Warn, &HookType::Stop, Ok(json!({"decision": "approve"})))
+        })
+        .await
+        .unwrap();
+        let message = output["systemMessage"].as_str().unwrap();
+        assert!(message.ends_with("failed: summary failed"));
+
+        report_failure("stop", "outside");
+        assert!(take_failures().is_empty());
+    }
+
+    #[test]
+    fn test_success_without_failures_unchanged() {
+        let output = json!({"decision": "approve", "reason": "ctx"});
+        let result = apply_strict_mode(StrictMode::Block, &HookType::Stop, Ok(output.clone()));
+        assert_eq!(result.unwrap(), output);
+    }
+}
@@ -0,0 +1,126 @@
+//! SubagentStop hook handler.
+//!
+//! Runs when a subagent (Task tool) finishes. Its own transcript holds the
+//! task it was given and its final answer; substantive answers are extracted
+//! to memory the same way the Stop hook extracts main turns, attributed to
+//! the turn that started the subagent.
+
+use std::fs;
+
+use crate::daemon;
+use crate::error::Result;
+use crate::session::load_session_state;
+
+use super::debug::debug as debug_log;
+use super::simulate::suppress;
+use super::stop::{
+    extract_last_messages, should_extract, spawn_extraction, truncate_str, ExtractionContext,
+};
+use super::strict::report_failure;
+use super::{HookInput, HookOutput};
+
+const HOOK_NAME: &str = "subagent-stop";
+
+/// Debug logging wrapper for this hook
+fn debug(msg: &str) {
+    debug_log(HOOK_NAME, msg);
+}
+
+/// Handle the SubagentStop hook.
+///
+/// 1. Skip if extraction instance (prevent recursion)
+/// 2. Read the subagent transcript for its task and final answer
+/// 3. If substantive, spawn background extraction process
+/// 4. Return approval
+pub async fn handle_subagent_stop(input: &HookInput) -> Result<HookOutput> {
+    debug("=== Subagent stop hook started ===");
+
+    if daemon::var("CLAUDE_MEMORY_EXTRACTION").is_some() {
+        debug("Skipping - extraction instance");
+        return Ok(HookOutput::approve());
+    }
+
+    let Some(path) = input.agent_transcript_path.as_deref() else {
+        debug("Skipping - no subagent transcript");
+        return Ok(HookOutput::approve());
+    };
+    let transcript = fs::read_to_string(path).unwrap_or_else(|e| {
+        report_failure(HOOK_NAME, format!("Failed to read transcript {}: {}", path, e));
+        String::new()
+    });
+
+    let (task, answer) = match extract_last_messages(&transcript) {
+        (Some(task), Some(answer)) => (task, answer),
+        _ => {
+            debug("Skipping - missing task or answer");
+            return Ok(HookOutput::approve());
+        }
+    };
+    debug(&format!(
+        "Extracted - task: {:?}, answer: {:?}",
+        truncate_str(&task, 50),
+        truncate_str(&answer, 50)
+    ));
+
+    if !should_extract(&task, &answer) {
+        debug("Skipping - answer not substantive");
+        return Ok(HookOutput::approve());
+    }
+
+    let claude_session_id = input.session_id.clone().unwrap_or_else(|| "unknown".to_string());
+    let state = load_session_state(Some(&claude_session_id)).ok().flatten();
+    let ctx = ExtractionContext::new(
+        task,
+        answer,
+        claude_session_id,
+        state
+            .as_ref()
+            .and_then(|s| s.session_id)
+            .map(|u| u.to_string())
+            .unwrap_or_default(),
+        state
+            .as_ref()
+            .and_then(|s| s.current_turn_id)
+            .map(|u| u.to_string())
+            .unwrap_or_default(),
+    );
+
+    if !suppress(|| format!("spawn subagent memory extraction (confidence: {})", ctx.confidence())) {
+        spawn_extraction(&ctx);
+    }
+
+    debug("=== Subagent stop hook completed ===");
+    Ok(HookOutput::approve())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_subagent_stop_without_transcript_approves() {
+        let input = HookInput {
+            session_id: Some("subagent-test".to_string()),
+            hook_event_name: Some("SubagentStop".to_string()),
+            ..Default::default()
+        };
+        let output = handle_subagent_stop(&input).await.unwrap();
+        assert_eq!(output.decision, "approve");
+    }
+
+    #[test]
+    fn test_subagent_stop_input_parsing() {
+        let json = r#"{
+            "session_id": "abc",
+            "transcript_path": "/tmp/main.jsonl",
+            "agent_transcript_path": "/tmp/agent.jsonl",
+            "hook_event_name": "SubagentStop"
+        }"#;
+        let input: HookInput = serde_json::from_str(json).unwrap();
+        assert_eq!(input.agent_transcript_path.as_deref(), Some("/tmp/agent.jsonl"));
+    }
+}
@@ -0,0 +1,85 @@
+//! Hook run timings.
+//!
+//! Each hook run records its wall-clock time in `hook_timings` so that
+//! `turn-latency` can show what the hooks add to a turn. Recording is
+//! best-effort: when the insert fails, the hook's own output is unaffected.
+
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgPool;
+
+use crate::cli::HookType;
+use crate::db::queries;
+use crate::session::load_own_session_state;
+
+/// Measures one hook run, from process start to output
+pub struct HookTimer {
+    started_at: DateTime<Utc>,
+    started: Instant,
+}
+
+impl HookTimer {
+    pub fn start() -> Self {
+        Self {
+            started_at: Utc::now(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Milliseconds since the timer started
+    pub fn elapsed_ms(&self) -> i32 {
+        self.started.elapsed().as_millis().min(i32::MAX as u128) as i32
+    }
+
+    /// Record the run against the session and turn it belongs to
+    pub async fn record(self, pool: &PgPool, hook_type: &HookType, raw_input: &str, success: bool) {
+        let duration_ms = self.elapsed_ms();
+        let claude_session_id = claude_session_id(raw_input);
+        // Read after the hook ran, so UserPromptSubmit is counted in the turn it opened
+        let state = claude_session_id.as_deref().and_then(load_own_session_state);
+
+        let _ = queries::record_hook_timing(
+            pool,
+            state.as_ref().and_then(|s| s.session_id),
+            claude_session_id.as_deref(),
+            state.as_ref().and_then(|s| s.current_turn_id),
+            hook_type.as_str(),
+            self.started_at,
+            duration_ms,
+            success,
+        )
+        .await;
+    }
+}
+
+/// Claude session ID from a raw hook payload (any hook's input format)
+fn claude_session_id(raw_input: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(raw_input.trim()).ok()?;
+    value
+        .get("session_id")
+        .or_else(|| value.get("sessionId"))
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claude_session_id_from_either_key() {
+        assert_eq!(
+            claude_session_id(r#"{"session_id": "abc", "prompt": "hi"}"#).as_deref(),
+            Some("abc")
+        );
+        assert_eq!(claude_session_id(r#"{"sessionId": "def"}"#).as_deref(), Some("def"));
+        assert_eq!(claude_session_id(r#"{"session_id": ""}"#), None);
+        assert_eq!(claude_session_id("not json"), None);
+    }
+}
@@ -1,15 +1,22 @@
 //! User prompt submit hook handler.
 //!
-//! Creates a conversation turn and outputs memory search instructions.
+//! Creates a conversation turn and adds memory search instructions to
+//! Claude's context. Prompts below the `turn_recording` thresholds create
+//! no turn and are only counted on the session.
 
 use sqlx::postgres::PgPool;
 use std::fs;
 
-use crate::db::queries::{create_turn, find_session_by_claude_id, get_next_turn_number};
+use crate::config::DbConfig;
+use crate::daemon;
+use crate::db::queries::{
+    create_turn, find_session_by_claude_id, get_next_turn_number, record_skipped_prompt,
+};
 use crate::error::Result;
 use crate::session::{load_session_state, save_session_state};
 
 use super::debug::debug as debug_log;
+use super::simulate::suppress;
 use super::{HookInput, HookOutput};
 
 const HOOK_NAME: &str = "user-prompt-submit";
@@ -56,14 +63,14 @@ fn should_search_memory(prompt: &str) -> bool {
 /// Handle the user-prompt-submit hook.
 ///
 /// 1. Skip if extraction instance (prevent recursion)
-/// 2. Create conversation turn
+/// 2. Create conversation turn, or count the prompt as skipped
 /// 3. Clear marker file
-/// 4. Output memory search instructions
+/// 4. Return memory search instructions as additional context
 pub async fn handle_user_prompt_submit(pool: &PgPool, input: &HookInput) -> Result<HookOutput> {
     debug("=== User prompt submit hook started ===");
 
     // Skip if this is an extraction instance (prevent recursion)
-    if std::env::var("CLAUDE_MEMORY_EXTRACTION").is_ok() {
+    if daemon::var("CLAUDE_MEMORY_EXTRACTION").is_some() {
         debug("Skipping - extraction instance");
         return Ok(HookOutput::approve());
     }
@@ -86,19 +93,34 @@ pub async fn handle_user_prompt_submit(pool: &PgPool, input: &HookInput) -> Resu
     // Find session and create turn
     if let Some(session) = find_session_by_claude_id(pool, &claude_session_id).await? {
         debug(&format!("Found session in DB: {}", session.id));
-        let turn_number = get_next_turn_number(pool, session.id).await?;
-        let turn = create_turn(pool, session.id, turn_number, &prompt, None).await?;
-        debug(&format!("Created turn {} with ID: {}", turn_number, turn.id));
+        let recording = DbConfig::load().map(|c| c.turn_recording).unwrap_or_default();
+        let mut turn_number = get_next_turn_number(pool, session.id).await?;
+        let current_turn_id = match recording.skip_reason(&prompt, turn_number - 1) {
+            Some(reason) => {
+                debug(&format!("Not recording a turn: {}", reason));
+                record_skipped_prompt(pool, session.id).await?;
+                turn_number -= 1;
+                None
+            }
+            None => {
+                let turn = create_turn(pool, session.id, turn_number, &prompt, None).await?;
+                debug(&format!("Created turn {} with ID: {}", turn_number, turn.id));
+                Some(turn.id)
+            }
+        };
 
-        // Update session state
+        // Update session state; without a current turn, Stop leaves the
+        // previous turn's response alone
         let new_state = crate::session::SessionState {
             session_id: Some(session.id),
             claude_session_id: Some(claude_session_id.clone()),
             turn_number,
-            current_turn_id: Some(turn.id),
+            current_turn_id,
         };
-        save_session_state(&new_state)?;
-        debug("Session state updated");
+        if !suppress(|| format!("save session state for {} (turn {})", claude_session_id, turn_number)) {
+            save_session_state(&new_state)?;
+            debug("Session state updated");
+        }
     } else {
         debug("Session not found in database");
     }
@@ -106,7 +128,9 @@ pub async fn handle_user_prompt_submit(pool: &PgPool, input: &HookInput) -> Resu
     // Clear stop hook marker to allow response recording
     let marker_file = get_marker_file(&claude_session_id);
     debug(&format!("Clearing marker file: {}", marker_file));
-    let _ = fs::remove_file(&marker_file);
+    if !suppress(|| format!("remove marker file {}", marker_file)) {
+        let _ = fs::remove_file(&marker_file);
+    }
 
     // Build output text
     let mut output_text = String::new();
@@ -162,14 +186,12 @@ IF no correction detected:
 - Do nothing, proceed normally
 </action>"#, claude_session_id));
 
-    // Output the instructions followed by JSON
-    println!("{}", output_text);
-
     let search_memory = should_search_memory(&prompt);
     debug(&format!("Should search memory: {}", search_memory));
     debug("=== User prompt submit hook completed ===");
 
-    Ok(HookOutput::approve())
+    // The instructions travel inside the JSON, the only thing on stdout
+    Ok(HookOutput::approve().with_context("UserPromptSubmit", output_text))
 }
 
 #[cfg(test)]
@@ -1,24 +1,36 @@
+pub mod browse;
+pub mod claude_md;
 pub mod cli;
 pub mod commands;
 pub mod config;
+pub mod conflict;
+pub mod daemon;
 pub mod db;
+pub mod environment;
+pub mod embedding;
 pub mod error;
+pub mod external;
+pub mod extraction;
 pub mod git;
 pub mod hooks;
+pub mod lint;
 pub mod logging;
 pub mod models;
+pub mod output;
 pub mod session;
+pub mod util;
 
-pub use cli::{parse_tags, Cli, Command, HookType};
+pub use cli::{is_builtin_subcommand, subcommand_position, parse_ref, parse_tags, parse_uuid_arg, Cli, Command, ConfigAction, HookType, PolicyAction, ProjectAction, AliasAction, ReviewAction, WorkspaceAction};
 pub use config::DbConfig;
 pub use error::{HippocampusError, Result};
-pub use logging::{clear_logs, log, read_logs, LogEntry};
+pub use logging::{clear_logs, log, read_logs, read_logs_filtered, LogEntry, LogFilter};
 pub use session::{
-    clear_session_state, get_session_state_path, load_session_state, save_session_state,
-    SessionState,
+    clear_session_state, get_session_state_path, load_own_session_state, load_session_state,
+    save_session_state, SessionState,
 };
+pub use environment::{capture_environment, EnvironmentSnapshot};
 pub use git::{get_git_status, GitStatus};
 pub use hooks::{
-    handle_session_end, handle_session_start, handle_stop, handle_user_prompt_submit,
-    HookInput, HookOutput,
+    handle_session_end, handle_session_start, handle_stop, handle_user_prompt_submit, run_hook,
+    simulate_hook, HookInput, HookOutput, SimulationReport,
 };
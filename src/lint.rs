@@ -0,0 +1,298 @@
+//! Content lint rules for memories
+//!
+//! Most memories are written by the extraction backend, so nothing but these
+//! rules keeps their style consistent. Rules are set in the `[lint]` config
+//! table and run on add (including batch, commit and extraction) and on
+//! update. In `warn` mode issues are returned alongside the saved memory; in
+//! `reject` mode any issue stops the write.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{LintIssue, MemoryType};
+
+/// What happens when content breaks a rule
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintMode {
+    /// Rules are not checked
+    Off,
+    /// Save, and report issues as warnings (default)
+    #[default]
+    Warn,
+    /// Refuse content with any issue
+    Reject,
+}
+
+/// Lint rules (`[lint]` config table)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LintRules {
+    pub mode: LintMode,
+    /// Longest content allowed, in characters; 0 for no limit
+    pub max_length: usize,
+    /// Openings content may not start with, ignoring case
+    pub forbidden_prefixes: Vec<String>,
+    /// Conventions must read as instructions ("Use ...", not "We use ...")
+    pub imperative_conventions: bool,
+    /// Refuse email addresses, phone numbers and API keys
+    pub no_pii: bool,
+    /// Types memories may have; empty allows every type
+    pub memory_types: Vec<MemoryType>,
+}
+
+impl Default for LintRules {
+    fn default() -> Self {
+        Self {
+            mode: LintMode::Warn,
+            max_length: 1000,
+            forbidden_prefixes: ["The user said", "User said", "The user asked"]
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            imperative_conventions: true,
+            no_pii: true,
+            memory_types: Vec::new(),
+        }
+    }
+}
+
+impl LintRules {
+    /// True when `issues` must stop the write
+    pub fn rejects(&self, issues: &[LintIssue]) -> bool {
+        self.mode == LintMode::Reject && !issues.is_empty()
+    }
+}
+
+/// First words that make a sentence descriptive rather than an instruction
+const NON_IMPERATIVE_OPENINGS: &[&str] = &[
+    "a", "an", "the", "this", "that", "these", "those", "there", "it", "its", "i", "we", "our",
+    "you", "they", "he", "she", "my", "user", "users", "should", "must",
+];
+
+/// Key prefixes of common credential formats
+const SECRET_PREFIXES: &[&str] = &[
+    "sk-", "sk_live_", "ghp_", "gho_", "github_pat_", "xoxb-", "xoxp-", "AKIA", "AIza",
+];
+
+/// Check `content` against `rules`; empty when it passes or lint is off
+pub fn check(memory_type: MemoryType, content: &str, rules: &LintRules) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    if rules.mode == LintMode::Off {
+        return issues;
+    }
+
+    if !rules.memory_types.is_empty() && !rules.memory_types.contains(&memory_type) {
+        issues.push(LintIssue::new(
+            "memory-type",
+            format!("Type {} is not in lint.memory_types", memory_type.as_str()),
+        ));
+    }
+
+    let length = content.chars().count();
+    if rules.max_length > 0 && length > rules.max_length {
+        issues.push(LintIssue::new(
+            "max-length",
+            format!("Content is {} characters; the limit is {}", length, rules.max_length),
+        ));
+    }
+
+    let trimmed = content.trim_start();
+    if let Some(prefix) = rules.forbidden_prefixes.iter().find(|p| {
+        trimmed
+            .get(..p.len())
+            .is_some_and(|start| start.eq_ignore_ascii_case(p))
+    }) {
+        issues.push(LintIssue::new(
+            "forbidden-prefix",
+            format!("Content starts with \"{}\"; state the learning itself", prefix),
+        ));
+    }
+
+    let convention = memory_type == MemoryType::Convention;
+    if rules.imperative_conventions && convention && !is_imperative(trimmed) {
+        issues.push(LintIssue::new(
+            "imperative-convention",
+            "Conventions should be instructions, e.g. \"Use snake_case for SQL columns\"",
+        ));
+    }
+
+    if rules.no_pii {
+        if let Some(kind) = find_pii(content) {
+            issues.push(LintIssue::new("no-pii", format!("Content contains {}", kind)));
+        }
+    }
+
+    issues
+}
+
+/// Heuristic: the first word is a bare verb, not a subject or inflected verb
+fn is_imperative(content: &str) -> bool {
+    let Some(first) = content.split_whitespace().next() else {
+        return false;
+    };
+    let word: String = first
+        .chars()
+        .filter(|c| c.is_alphabetic() || *c == '\'')
+        .collect::<String>()
+        .to_lowercase();
+    if word.is_empty() || NON_IMPERATIVE_OPENINGS.contains(&word.as_str()) {
+        return false;
+    }
+    if word == "always" {
+        return true;
+    }
+    // "Uses", "Used", "Using" describe rather than instruct
+    let third_person = word.ends_with('s') && !word.ends_with("ss") && !word.ends_with("us");
+    let participle = (word.ends_with("ed") && !word.ends_with("eed") && word.len() > 5)
+        || (word.ends_with("ing") && word.len() > 4 && word != "bring");
+    !(third_person || participle)
+}
+
+/// Describe the first email address, phone number or API key in `content`
+fn find_pii(content: &str) -> Option<&'static str> {
+    for word in content.split_whitespace() {
+        let word = word.trim_matches(|c: char| "()<>[]{},;:\"'`".contains(c));
+        if is_email(word) {
+            return Some("an email address");
+        }
+        if SECRET_PREFIXES
+            .iter()
+            .any(|p| word.starts_with(p) && word.len() >= p.len() + 16)
+        {
+            return Some("an API key");
+        }
+    }
+    has_phone_number(content).then_some("a phone number")
+}
+
+fn is_email(word: &str) -> bool {
+    let Some((local, domain)) = word.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && local.chars().all(|c| c.is_alphanumeric() || "._%+-".contains(c))
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && domain.chars().all(|c| c.is_alphanumeric() || ".-".contains(c))
+}
+
+/// 10 to 15 digits written as one number, optionally with `+`, spaces,
+/// dashes, dots or parentheses, and at least one separator or a leading `+`
+/// or `(` (a bare digit run is more likely an ID or timestamp)
+fn has_phone_number(content: &str) -> bool {
+    let chars: Vec<char> = content.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] == '+' || chars[i] == '(' || chars[i].is_ascii_digit() {
+            let (mut digits, mut separated) = (0, !chars[i].is_ascii_digit());
+            let mut pending_separator = false;
+            while i < chars.len() && (chars[i].is_ascii_digit() || " -.()+".contains(chars[i])) {
+                if chars[i].is_ascii_digit() {
+                    // Only separators between digits count
+                    separated |= pending_separator && digits > 0;
+                    pending_separator = false;
+                    digits += 1;
+                } else {
+                    pending_separator = true;
+                }
+                i += 1;
+            }
+            if (10..=15).contains(&digits) && separated {
+                return true;
+            }
+        }
+        i += 1;
+    }
+    false
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(mode: LintMode) -> LintRules {
+        LintRules {
+            mode,
+            ..Default::default()
+        }
+    }
+
+    fn rule_names(issues: &[LintIssue]) -> Vec<&str> {
+        issues.iter().map(|i| i.rule.as_str()).collect()
+    }
+
+    #[test]
+    fn test_clean_content_passes() {
+        let issues = check(
+            MemoryType::Convention,
+            "Use snake_case for SQL columns",
+            &rules(LintMode::Reject),
+        );
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn test_off_checks_nothing() {
+        let issues = check(MemoryType::Convention, "The user said we like tabs", &rules(LintMode::Off));
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn test_max_length_and_prefix() {
+        let rules = LintRules {
+            max_length: 20,
+            ..Default::default()
+        };
+        let issues = check(MemoryType::Learning, "  the USER SAID the build needs Node 20", &rules);
+        assert_eq!(rule_names(&issues), vec!["max-length", "forbidden-prefix"]);
+    }
+
+    #[test]
+    fn test_memory_types_allow_list() {
+        let rules = LintRules {
+            memory_types: vec![MemoryType::Convention, MemoryType::Gotcha],
+            ..Default::default()
+        };
+        assert!(check(MemoryType::Gotcha, "Cache is not cleared on deploy", &rules).is_empty());
+        let issues = check(MemoryType::Learning, "Cache is not cleared on deploy", &rules);
+        assert_eq!(rule_names(&issues), vec!["memory-type"]);
+    }
+
+    #[test]
+    fn test_imperative_conventions() {
+        assert!(is_imperative("Always run cargo fmt"));
+        assert!(is_imperative("Don't commit Cargo.lock"));
+        assert!(is_imperative("Pass --locked to cargo install"));
+        assert!(!is_imperative("We use tabs"));
+        assert!(!is_imperative("Uses tabs for indentation"));
+        assert!(!is_imperative("Using tabs is preferred"));
+        assert!(!is_imperative("The project prefers tabs"));
+
+        // Only conventions are held to it
+        let issues = check(MemoryType::Gotcha, "The cache is not cleared", &LintRules::default());
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn test_pii_detection() {
+        assert_eq!(find_pii("Ask jane.doe@example.com for access"), Some("an email address"));
+        assert_eq!(find_pii("Token ghp_abcdefghijklmnop1234 works"), Some("an API key"));
+        assert_eq!(find_pii("Call +1 415-555-0100 on failure"), Some("a phone number"));
+        assert_eq!(find_pii("Use git@github.com:org/repo.git"), None);
+        assert_eq!(find_pii("Timestamp 1700000000123 is in ms"), None);
+        assert_eq!(find_pii("Version 1.75.0 fixed it"), None);
+    }
+
+    #[test]
+    fn test_rejects_only_in_reject_mode() {
+        let issue = vec![LintIssue::new("no-pii", "Content contains an email address")];
+        assert!(rules(LintMode::Reject).rejects(&issue));
+        assert!(!rules(LintMode::Warn).rejects(&issue));
+        assert!(!rules(LintMode::Reject).rejects(&[]));
+    }
+}
@@ -1,25 +1,135 @@
-//! File-based logging for memory operations.
+//! Structured logging for memory operations.
 //!
-//! Logs to `~/.claude/logs/memory.log` with 1MB rotation.
+//! Events go through `tracing` and are written by `tracing-subscriber` as
+//! JSON lines to `~/.claude/logs/memory.log` with 1MB rotation. Each command
+//! run logs one event with its operation, duration, time spent in database
+//! queries and session. With `log_level = "debug"` every query is logged too.
+//!
+//! Diagnostics can also go to stderr in plain text, at the level chosen by
+//! `--quiet`/`--verbose`; stdout is left to command output.
+//!
+//! Lines written by older versions (JSON without a level, or
+//! `[%Y-%m-%d %H:%M:%S] OK op details`) are still readable.
 
+use crate::daemon;
 use crate::error::{HippocampusError, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs::{self, File, OpenOptions};
-use std::io::{BufRead, BufReader, BufWriter, Write};
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::{filter_fn, Targets};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
 
 const LOG_FILE_NAME: &str = "memory.log";
 const LOG_DIR_NAME: &str = "logs";
 const MAX_LOG_SIZE: u64 = 1_048_576; // 1MB
 
+/// Target of the events sqlx logs for each query
+const SQLX_QUERY_TARGET: &str = "sqlx::query";
+
+/// Nanoseconds spent in database queries so far, from sqlx's query events
+static DB_NANOS: AtomicU64 = AtomicU64::new(0);
+/// Database queries run so far
+static DB_QUERIES: AtomicU64 = AtomicU64::new(0);
+/// Claude session the running command belongs to, when known
+static SESSION_ID: Mutex<Option<String>> = Mutex::new(None);
+
+tokio::task_local! {
+    /// Session and database time of a command the daemon runs, apart from
+    /// the others it runs at the same time
+    static SCOPE: CommandScope;
+}
+
+/// What [`OperationLog`] reports for one command
+#[derive(Default)]
+struct CommandScope {
+    session_id: Mutex<Option<String>>,
+    db_nanos: AtomicU64,
+    db_queries: AtomicU64,
+}
+
+/// Severity of a log entry, least severe first
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    #[serde(alias = "TRACE")]
+    Trace,
+    #[serde(alias = "DEBUG")]
+    Debug,
+    #[default]
+    #[serde(alias = "INFO")]
+    Info,
+    #[serde(alias = "WARN")]
+    Warn,
+    #[serde(alias = "ERROR")]
+    Error,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+
+    fn as_tracing(self) -> Level {
+        match self {
+            LogLevel::Trace => Level::TRACE,
+            LogLevel::Debug => Level::DEBUG,
+            LogLevel::Info => Level::INFO,
+            LogLevel::Warn => Level::WARN,
+            LogLevel::Error => Level::ERROR,
+        }
+    }
+}
+
 /// A single log entry.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct LogEntry {
     pub timestamp: DateTime<Utc>,
+    pub level: LogLevel,
     pub operation: String,
     pub details: Option<String>,
     pub success: bool,
+    pub duration_ms: Option<u64>,
+    /// Time spent in database queries
+    pub db_ms: Option<u64>,
+    pub db_queries: Option<u64>,
+    pub session_id: Option<String>,
+}
+
+/// A JSON log line as written by this or an older version
+#[derive(Debug, Deserialize)]
+struct JsonLine {
+    timestamp: DateTime<Utc>,
+    level: Option<LogLevel>,
+    operation: Option<String>,
+    details: Option<String>,
+    success: Option<bool>,
+    duration_ms: Option<u64>,
+    db_ms: Option<u64>,
+    db_queries: Option<u64>,
+    session_id: Option<String>,
+    /// Message of events other than operations
+    message: Option<String>,
+    target: Option<String>,
+    /// Query summary and duration of sqlx's query events
+    summary: Option<String>,
+    elapsed_secs: Option<f64>,
 }
 
 impl LogEntry {
@@ -27,27 +137,50 @@ impl LogEntry {
     pub fn new(operation: impl Into<String>, details: Option<String>, success: bool) -> Self {
         Self {
             timestamp: Utc::now(),
+            level: if success { LogLevel::Info } else { LogLevel::Error },
             operation: operation.into(),
             details,
             success,
+            duration_ms: None,
+            db_ms: None,
+            db_queries: None,
+            session_id: None,
+        }
+    }
+
+    /// Parse from a log line (JSONL, falling back to the legacy format).
+    pub fn from_log_line(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.starts_with('{') {
+            return serde_json::from_str::<JsonLine>(line).ok().and_then(Self::from_json_line);
         }
+        Self::from_legacy_log_line(line)
     }
 
-    /// Format as a single log line.
-    pub fn to_log_line(&self) -> String {
-        let status = if self.success { "OK" } else { "ERR" };
-        let details = self.details.as_deref().unwrap_or("-");
-        format!(
-            "[{}] {} {} {}",
-            self.timestamp.format("%Y-%m-%d %H:%M:%S"),
-            status,
-            self.operation,
-            details
-        )
+    fn from_json_line(line: JsonLine) -> Option<Self> {
+        let operation = line.operation.or(line.target)?;
+        let success = line
+            .success
+            .unwrap_or_else(|| line.level.is_none_or(|level| level < LogLevel::Error));
+        Some(Self {
+            timestamp: line.timestamp,
+            level: line
+                .level
+                .unwrap_or(if success { LogLevel::Info } else { LogLevel::Error }),
+            operation,
+            details: line.details.or(line.summary).or(line.message),
+            success,
+            duration_ms: line
+                .duration_ms
+                .or(line.elapsed_secs.map(|secs| (secs * 1000.0).round() as u64)),
+            db_ms: line.db_ms,
+            db_queries: line.db_queries,
+            session_id: line.session_id,
+        })
     }
 
-    /// Parse from a log line.
-    pub fn from_log_line(line: &str) -> Option<Self> {
+    /// Parse a legacy plain-text log line.
+    fn from_legacy_log_line(line: &str) -> Option<Self> {
         // Format: [2024-01-24 10:30:45] OK addMemory some details
         if !line.starts_with('[') {
             return None;
@@ -71,12 +204,59 @@ impl LogEntry {
             .ok()
             .map(|dt| dt.and_utc())?;
 
-        Some(Self {
-            timestamp,
-            operation,
-            details,
-            success,
-        })
+        let mut entry = Self::new(operation, details, success);
+        entry.timestamp = timestamp;
+        Some(entry)
+    }
+}
+
+/// Filter applied when reading log entries.
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    /// Exact operation name (case-insensitive)
+    pub operation: Option<String>,
+    /// Only entries at or after this instant
+    pub since: Option<DateTime<Utc>>,
+    /// Only entries at or before this instant
+    pub until: Option<DateTime<Utc>>,
+    /// Only failed entries
+    pub failed_only: bool,
+    /// Operation name prefix (case-insensitive)
+    pub operation_prefix: Option<String>,
+    /// Only entries at this level or more severe
+    pub min_level: Option<LogLevel>,
+}
+
+impl LogFilter {
+    /// Check whether an entry passes the filter.
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        if let Some(op) = &self.operation {
+            if !entry.operation.eq_ignore_ascii_case(op) {
+                return false;
+            }
+        }
+        if let Some(prefix) = &self.operation_prefix {
+            let matches_prefix = entry
+                .operation
+                .get(..prefix.len())
+                .is_some_and(|head| head.eq_ignore_ascii_case(prefix));
+            if !matches_prefix {
+                return false;
+            }
+        }
+        if self.failed_only && entry.success {
+            return false;
+        }
+        if self.min_level.is_some_and(|level| entry.level < level) {
+            return false;
+        }
+        if self.since.is_some_and(|since| entry.timestamp < since) {
+            return false;
+        }
+        if self.until.is_some_and(|until| entry.timestamp > until) {
+            return false;
+        }
+        true
     }
 }
 
@@ -124,27 +304,197 @@ fn rotate_log(path: &PathBuf) -> Result<()> {
     Ok(())
 }
 
-/// Write a log entry.
-pub fn log(operation: impl Into<String>, details: Option<String>, success: bool) -> Result<()> {
-    let path = get_log_path()?;
+/// Send events at `level` and above to the log file, and those at `stderr`
+/// and above to stderr, for the rest of the process; None sends none
+/// there. Call once, before the command runs.
+///
+/// When the log file cannot be opened, stderr still gets its events and
+/// the error is returned.
+pub fn init(level: Option<LogLevel>, stderr: Option<LogLevel>) -> Result<()> {
+    let (file, error) = match level.map(|_| open_log_file()) {
+        Some(Ok(file)) => (Some(file), None),
+        Some(Err(e)) => (None, Some(e)),
+        None => (None, None),
+    };
+    subscriber(file, level.unwrap_or_default(), stderr)
+        .try_init()
+        .map_err(|e| HippocampusError::Config(format!("Failed to start logging: {}", e)))?;
+    error.map_or(Ok(()), Err)
+}
 
-    // Rotate if needed
+/// memory.log, rotated first if it is too large
+fn open_log_file() -> Result<File> {
+    let path = get_log_path()?;
     if needs_rotation(&path) {
         rotate_log(&path)?;
     }
+    Ok(OpenOptions::new().create(true).append(true).open(&path)?)
+}
 
-    let entry = LogEntry::new(operation, details, success);
-    let line = entry.to_log_line();
+/// JSON lines of this crate's events and sqlx's query events at `level`
+/// and above, the same as text on stderr at `stderr` and above, plus the
+/// query timer behind `db_ms`
+fn subscriber(
+    file: Option<File>,
+    level: LogLevel,
+    stderr: Option<LogLevel>,
+) -> impl Subscriber + Send + Sync {
+    let lines = file.map(|file| {
+        tracing_subscriber::fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_current_span(false)
+            .with_span_list(false)
+            .with_writer(Mutex::new(file))
+            .with_filter(targets(level))
+    });
+    let diagnostics = stderr.map(|stderr| {
+        tracing_subscriber::fmt::layer()
+            .with_target(false)
+            .with_writer(std::io::stderr)
+            .with_filter(targets(stderr))
+    });
+    let timer = DbTimer.with_filter(filter_fn(|meta| meta.target() == SQLX_QUERY_TARGET));
+
+    tracing_subscriber::registry().with(lines).with(diagnostics).with(timer)
+}
+
+/// This crate's events and sqlx's query events at `level` and above
+fn targets(level: LogLevel) -> Targets {
+    Targets::new()
+        .with_target(env!("CARGO_CRATE_NAME"), level.as_tracing())
+        .with_target(SQLX_QUERY_TARGET, level.as_tracing())
+}
+
+/// Adds up the durations of sqlx's query events
+struct DbTimer;
+
+impl<S: Subscriber> Layer<S> for DbTimer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut elapsed = ElapsedSecs(None);
+        event.record(&mut elapsed);
+        if let Some(secs) = elapsed.0 {
+            let nanos = (secs * 1e9) as u64;
+            let counted = SCOPE.try_with(|scope| {
+                scope.db_nanos.fetch_add(nanos, Ordering::Relaxed);
+                scope.db_queries.fetch_add(1, Ordering::Relaxed);
+            });
+            if counted.is_err() {
+                DB_NANOS.fetch_add(nanos, Ordering::Relaxed);
+                DB_QUERIES.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
 
-    let file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&path)?;
+struct ElapsedSecs(Option<f64>);
 
-    let mut writer = BufWriter::new(file);
-    writeln!(writer, "{}", line)?;
-    writer.flush()?;
+impl Visit for ElapsedSecs {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if field.name() == "elapsed_secs" {
+            self.0 = Some(value);
+        }
+    }
 
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+/// Set the Claude session logged with operations from now on.
+pub fn set_session_id(session_id: impl Into<String>) {
+    let session_id = session_id.into();
+    let set = |current: &Mutex<Option<String>>| {
+        if let Ok(mut current) = current.lock() {
+            *current = Some(session_id.clone());
+        }
+    };
+    if SCOPE.try_with(|scope| set(&scope.session_id)).is_err() {
+        set(&SESSION_ID);
+    }
+}
+
+/// Run `future` with a Claude session and database time of its own, as the
+/// daemon runs each command: neither is logged with its other commands.
+pub async fn command_scope<F: std::future::Future>(future: F) -> F::Output {
+    SCOPE.scope(CommandScope::default(), future).await
+}
+
+/// Database time and queries of the running command so far
+fn db_totals() -> (u64, u64) {
+    let load = |nanos: &AtomicU64, queries: &AtomicU64| {
+        (nanos.load(Ordering::Relaxed), queries.load(Ordering::Relaxed))
+    };
+    SCOPE
+        .try_with(|scope| load(&scope.db_nanos, &scope.db_queries))
+        .unwrap_or_else(|_| load(&DB_NANOS, &DB_QUERIES))
+}
+
+/// The Claude session operations are logged with: the one set by a hook,
+/// otherwise `CLAUDE_SESSION_ID`.
+pub fn session_id() -> Option<String> {
+    let current = |current: &Mutex<Option<String>>| current.lock().ok().and_then(|id| id.clone());
+    SCOPE
+        .try_with(|scope| current(&scope.session_id))
+        .unwrap_or_else(|_| current(&SESSION_ID))
+        .or_else(|| daemon::var("CLAUDE_SESSION_ID").filter(|id| !id.is_empty()))
+}
+
+/// A running operation, logged with its duration and database time when
+/// [`finish`](Self::finish)ed.
+pub struct OperationLog {
+    operation: String,
+    started: Instant,
+    db_nanos: u64,
+    db_queries: u64,
+}
+
+impl OperationLog {
+    pub fn start(operation: impl Into<String>) -> Self {
+        let (db_nanos, db_queries) = db_totals();
+        Self {
+            operation: operation.into(),
+            started: Instant::now(),
+            db_nanos,
+            db_queries,
+        }
+    }
+
+    /// Log the operation: at info level, or at error level with `error`.
+    pub fn finish(self, error: Option<&str>) {
+        let duration_ms = self.started.elapsed().as_millis() as u64;
+        let (db_nanos, db_queries) = db_totals();
+        let db_ms = (db_nanos - self.db_nanos) / 1_000_000;
+        let db_queries = db_queries - self.db_queries;
+        let session_id = session_id();
+        match error {
+            None => tracing::info!(
+                operation = %self.operation,
+                success = true,
+                duration_ms,
+                db_ms,
+                db_queries,
+                session_id,
+            ),
+            Some(details) => tracing::error!(
+                operation = %self.operation,
+                success = false,
+                details,
+                duration_ms,
+                db_ms,
+                db_queries,
+                session_id,
+            ),
+        }
+    }
+}
+
+/// Log a single event.
+pub fn log(operation: impl Into<String>, details: Option<String>, success: bool) -> Result<()> {
+    let operation = operation.into();
+    if success {
+        tracing::info!(operation, details, success);
+    } else {
+        tracing::error!(operation, details, success);
+    }
     Ok(())
 }
 
@@ -153,28 +503,36 @@ pub fn log(operation: impl Into<String>, details: Option<String>, success: bool)
 /// - `limit`: Maximum number of entries to return (most recent first)
 /// - `operation`: Optional filter by operation name
 pub fn read_logs(limit: usize, operation: Option<&str>) -> Result<Vec<LogEntry>> {
-    let path = get_log_path()?;
+    let filter = LogFilter {
+        operation: operation.map(String::from),
+        ..Default::default()
+    };
+    read_logs_filtered(limit, &filter)
+}
+
+/// Read log entries matching a filter, most recent first.
+pub fn read_logs_filtered(limit: usize, filter: &LogFilter) -> Result<Vec<LogEntry>> {
+    read_log_file(&get_log_path()?, limit, filter)
+}
 
+fn read_log_file(path: &Path, limit: usize, filter: &LogFilter) -> Result<Vec<LogEntry>> {
     if !path.exists() {
         return Ok(Vec::new());
     }
 
-    let file = File::open(&path)?;
+    let file = File::open(path)?;
     let reader = BufReader::new(file);
 
     let mut entries: Vec<LogEntry> = reader
         .lines()
-        .filter_map(|line| line.ok())
+        .map_while(std::result::Result::ok)
         .filter_map(|line| LogEntry::from_log_line(&line))
-        .filter(|entry| {
-            operation.map_or(true, |op| {
-                entry.operation.eq_ignore_ascii_case(op)
-            })
-        })
+        .filter(|entry| filter.matches(entry))
         .collect();
 
-    // Return most recent first
+    // Return most recent first; entries sharing a timestamp keep reverse file order
     entries.reverse();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
     entries.truncate(limit);
 
     Ok(entries)
@@ -208,9 +566,24 @@ pub fn clear_logs() -> Result<usize> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tempfile::TempDir;
+
+    /// Run `f` with events going to a fresh log file, and return its path
+    fn capture(level: LogLevel, f: impl FnOnce()) -> (TempDir, PathBuf) {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(LOG_FILE_NAME);
+        let file = File::create(&path).unwrap();
+        tracing::subscriber::with_default(subscriber(Some(file), level, None), f);
+        (dir, path)
+    }
 
-    // Note: These tests use the actual log path (~/.claude/logs/memory.log)
-    // In a real test environment, you'd want to mock the path
+    fn lines(path: &Path) -> Vec<serde_json::Value> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
 
     #[test]
     fn test_log_entry_new() {
@@ -219,51 +592,91 @@ mod tests {
         assert_eq!(entry.operation, "addMemory");
         assert_eq!(entry.details, Some("test details".to_string()));
         assert!(entry.success);
+        assert_eq!(entry.level, LogLevel::Info);
     }
 
     #[test]
-    fn test_log_entry_to_log_line_success() {
-        let entry = LogEntry {
-            timestamp: chrono::DateTime::parse_from_rfc3339("2024-01-24T10:30:45Z")
-                .unwrap()
-                .with_timezone(&Utc),
-            operation: "addMemory".to_string(),
-            details: Some("created memory".to_string()),
-            success: true,
-        };
-
-        let line = entry.to_log_line();
-        assert_eq!(line, "[2024-01-24 10:30:45] OK addMemory created memory");
+    fn test_log_writes_json_line() {
+        let (_dir, path) = capture(LogLevel::Info, || {
+            log("addMemory", Some("created memory".to_string()), true).unwrap();
+            log("deleteMemory", Some("not found".to_string()), false).unwrap();
+            log("consolidate", None, true).unwrap();
+        });
+
+        let json = lines(&path);
+        assert_eq!(json.len(), 3);
+        assert!(json[0]["timestamp"].as_str().unwrap().parse::<DateTime<Utc>>().is_ok());
+        assert_eq!(json[0]["level"], "INFO");
+        assert_eq!(json[0]["operation"], "addMemory");
+        assert_eq!(json[0]["details"], "created memory");
+        assert_eq!(json[0]["success"], true);
+        assert_eq!(json[1]["level"], "ERROR");
+        assert_eq!(json[1]["success"], false);
+        assert!(json[2]["details"].is_null());
     }
 
     #[test]
-    fn test_log_entry_to_log_line_failure() {
-        let entry = LogEntry {
-            timestamp: chrono::DateTime::parse_from_rfc3339("2024-01-24T10:30:45Z")
-                .unwrap()
-                .with_timezone(&Utc),
-            operation: "deleteMemory".to_string(),
-            details: Some("not found".to_string()),
-            success: false,
-        };
+    fn test_log_respects_level() {
+        let (_dir, path) = capture(LogLevel::Warn, || {
+            log("addMemory", None, true).unwrap();
+            log("deleteMemory", None, false).unwrap();
+        });
+        let json = lines(&path);
+        assert_eq!(json.len(), 1);
+        assert_eq!(json[0]["operation"], "deleteMemory");
+    }
 
-        let line = entry.to_log_line();
-        assert_eq!(line, "[2024-01-24 10:30:45] ERR deleteMemory not found");
+    #[tokio::test]
+    async fn test_command_scope_keeps_its_session() {
+        let inner = command_scope(async {
+            set_session_id("claude-session-scoped");
+            session_id()
+        })
+        .await;
+        assert_eq!(inner.as_deref(), Some("claude-session-scoped"));
+        assert_ne!(session_id().as_deref(), Some("claude-session-scoped"));
     }
 
     #[test]
-    fn test_log_entry_to_log_line_no_details() {
-        let entry = LogEntry {
-            timestamp: chrono::DateTime::parse_from_rfc3339("2024-01-24T10:30:45Z")
-                .unwrap()
-                .with_timezone(&Utc),
-            operation: "consolidate".to_string(),
-            details: None,
-            success: true,
-        };
+    fn test_operation_log_records_db_time() {
+        let (_dir, path) = capture(LogLevel::Info, || {
+            set_session_id("claude-session-1");
+            let operation = OperationLog::start("search-keyword");
+            tracing::debug!(target: "sqlx::query", summary = "SELECT id", elapsed_secs = 0.25);
+            tracing::debug!(target: "sqlx::query", summary = "SELECT 1", elapsed_secs = 0.05);
+            operation.finish(None);
+        });
+
+        // Query events are below the file's level but still timed
+        let json = lines(&path);
+        assert_eq!(json.len(), 1);
+        assert_eq!(json[0]["operation"], "search-keyword");
+        assert_eq!(json[0]["db_queries"], 2);
+        assert_eq!(json[0]["db_ms"], 300);
+        assert_eq!(json[0]["session_id"], "claude-session-1");
+        assert!(json[0]["duration_ms"].is_u64());
+    }
 
-        let line = entry.to_log_line();
-        assert_eq!(line, "[2024-01-24 10:30:45] OK consolidate -");
+    #[test]
+    fn test_command_scope_times_only_its_queries() {
+        let (_dir, path) = capture(LogLevel::Info, || {
+            SCOPE.sync_scope(CommandScope::default(), || {
+                let operation = OperationLog::start("stats");
+                SCOPE.sync_scope(CommandScope::default(), || {
+                    let scoped = OperationLog::start("search-keyword");
+                    tracing::debug!(target: "sqlx::query", summary = "SELECT id", elapsed_secs = 0.25);
+                    scoped.finish(None);
+                });
+                operation.finish(None);
+            })
+        });
+
+        let json = lines(&path);
+        assert_eq!(json.len(), 2);
+        assert_eq!(json[0]["operation"], "search-keyword");
+        assert_eq!(json[0]["db_queries"], 1);
+        assert_eq!(json[1]["operation"], "stats");
+        assert_eq!(json[1]["db_queries"], 0);
     }
 
     #[test]
@@ -284,6 +697,7 @@ mod tests {
         assert_eq!(entry.operation, "deleteMemory");
         assert_eq!(entry.details, Some("not found".to_string()));
         assert!(!entry.success);
+        assert_eq!(entry.level, LogLevel::Error);
     }
 
     #[test]
@@ -303,28 +717,148 @@ mod tests {
         assert!(LogEntry::from_log_line("[incomplete").is_none());
     }
 
+    #[test]
+    fn test_log_entry_from_unleveled_json_line() {
+        let line = r#"{"timestamp":"2024-01-24T10:30:45Z","operation":"deleteMemory","details":"not found","success":false}"#;
+        let entry = LogEntry::from_log_line(line).unwrap();
+
+        assert_eq!(entry.operation, "deleteMemory");
+        assert_eq!(entry.level, LogLevel::Error);
+        assert!(!entry.success);
+    }
+
+    #[test]
+    fn test_log_entry_from_query_event_line() {
+        let line = r#"{"timestamp":"2024-01-24T10:30:45.5Z","level":"DEBUG","summary":"SELECT id, type …","elapsed_secs":0.0123,"target":"sqlx::query"}"#;
+        let entry = LogEntry::from_log_line(line).unwrap();
+
+        assert_eq!(entry.operation, "sqlx::query");
+        assert_eq!(entry.level, LogLevel::Debug);
+        assert_eq!(entry.details.as_deref(), Some("SELECT id, type …"));
+        assert_eq!(entry.duration_ms, Some(12));
+        assert!(entry.success);
+    }
+
     #[test]
     fn test_log_entry_roundtrip() {
-        let original = LogEntry {
-            timestamp: chrono::DateTime::parse_from_rfc3339("2024-01-24T10:30:45Z")
-                .unwrap()
-                .with_timezone(&Utc),
-            operation: "searchKeyword".to_string(),
-            details: Some("query=test".to_string()),
-            success: true,
+        let (_dir, path) = capture(LogLevel::Info, || {
+            log("searchKeyword", Some("query=test".to_string()), true).unwrap();
+        });
+
+        let entries = read_log_file(&path, 10, &LogFilter::default()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].operation, "searchKeyword");
+        assert_eq!(entries[0].details.as_deref(), Some("query=test"));
+        assert!(entries[0].success);
+    }
+
+    #[test]
+    fn test_log_entry_roundtrip_preserves_subsecond_and_spaces() {
+        let (_dir, path) = capture(LogLevel::Info, || {
+            log("hook session start", Some("multi\nline details".to_string()), false).unwrap();
+        });
+
+        let entries = read_log_file(&path, 10, &LogFilter::default()).unwrap();
+        assert_eq!(entries[0].operation, "hook session start");
+        assert_eq!(entries[0].details.as_deref(), Some("multi\nline details"));
+        assert!(!entries[0].success);
+        assert_ne!(entries[0].timestamp.timestamp_subsec_nanos(), 0);
+    }
+
+    #[test]
+    fn test_log_entry_from_log_line_invalid_json() {
+        assert!(LogEntry::from_log_line("{\"operation\": \"x\"").is_none());
+        assert!(LogEntry::from_log_line("{}").is_none());
+    }
+
+    // ------------------------------------------------------------------------
+    // LogFilter tests
+    // ------------------------------------------------------------------------
+
+    fn entry_at(rfc3339: &str, operation: &str) -> LogEntry {
+        let mut entry = LogEntry::new(operation, None, true);
+        entry.timestamp = chrono::DateTime::parse_from_rfc3339(rfc3339)
+            .unwrap()
+            .with_timezone(&Utc);
+        entry
+    }
+
+    #[test]
+    fn test_log_filter_default_matches_everything() {
+        let filter = LogFilter::default();
+        assert!(filter.matches(&entry_at("2024-01-24T10:30:45Z", "addMemory")));
+    }
+
+    #[test]
+    fn test_log_filter_operation_case_insensitive() {
+        let filter = LogFilter {
+            operation: Some("addmemory".to_string()),
+            ..Default::default()
         };
+        assert!(filter.matches(&entry_at("2024-01-24T10:30:45Z", "addMemory")));
+        assert!(!filter.matches(&entry_at("2024-01-24T10:30:45Z", "deleteMemory")));
+    }
 
-        let line = original.to_log_line();
-        let parsed = LogEntry::from_log_line(&line).unwrap();
+    #[test]
+    fn test_log_filter_time_range() {
+        let filter = LogFilter {
+            since: Some("2024-01-24T00:00:00Z".parse().unwrap()),
+            until: Some("2024-01-25T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&entry_at("2024-01-24T10:30:45Z", "op")));
+        assert!(filter.matches(&entry_at("2024-01-24T00:00:00Z", "op")));
+        assert!(filter.matches(&entry_at("2024-01-25T00:00:00Z", "op")));
+        assert!(!filter.matches(&entry_at("2024-01-23T23:59:59Z", "op")));
+        assert!(!filter.matches(&entry_at("2024-01-25T00:00:01Z", "op")));
+    }
 
-        assert_eq!(parsed.operation, original.operation);
-        assert_eq!(parsed.details, original.details);
-        assert_eq!(parsed.success, original.success);
-        // Timestamps should match (within second precision)
-        assert_eq!(
-            parsed.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
-            original.timestamp.format("%Y-%m-%d %H:%M:%S").to_string()
-        );
+    #[test]
+    fn test_log_filter_failed_only() {
+        let filter = LogFilter {
+            failed_only: true,
+            ..Default::default()
+        };
+        let mut entry = entry_at("2024-01-24T10:30:45Z", "addMemory");
+        assert!(!filter.matches(&entry));
+        entry.success = false;
+        assert!(filter.matches(&entry));
+    }
+
+    #[test]
+    fn test_log_filter_operation_prefix() {
+        let filter = LogFilter {
+            operation_prefix: Some("Hook".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&entry_at("2024-01-24T10:30:45Z", "hookStop")));
+        assert!(filter.matches(&entry_at("2024-01-24T10:30:45Z", "hook")));
+        assert!(!filter.matches(&entry_at("2024-01-24T10:30:45Z", "addMemory")));
+        assert!(!filter.matches(&entry_at("2024-01-24T10:30:45Z", "ho")));
+    }
+
+    #[test]
+    fn test_log_filter_min_level() {
+        let filter = LogFilter {
+            min_level: Some(LogLevel::Warn),
+            ..Default::default()
+        };
+        let mut entry = entry_at("2024-01-24T10:30:45Z", "addMemory");
+        assert!(!filter.matches(&entry));
+        entry.level = LogLevel::Warn;
+        assert!(filter.matches(&entry));
+        entry.level = LogLevel::Error;
+        assert!(filter.matches(&entry));
+    }
+
+    #[test]
+    fn test_log_filter_legacy_line_in_range() {
+        let entry = LogEntry::from_log_line("[2024-01-24 10:30:45] OK addMemory created").unwrap();
+        let filter = LogFilter {
+            since: Some("2024-01-24T10:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&entry));
     }
 
     #[test]
@@ -342,44 +876,35 @@ mod tests {
         assert!(!needs_rotation(&path));
     }
 
-    // Integration test - writes to actual log file
     #[test]
     fn test_log_and_read() {
-        // Write some entries
-        log("test_operation", Some("test details".to_string()), true).unwrap();
-
-        // Read back
-        let entries = read_logs(10, Some("test_operation")).unwrap();
-
-        // Should have at least one entry
-        assert!(!entries.is_empty());
+        let (_dir, path) = capture(LogLevel::Info, || {
+            log("test_operation", Some("test details".to_string()), true).unwrap();
+        });
 
-        let entry = &entries[0];
-        assert_eq!(entry.operation, "test_operation");
+        let filter = LogFilter {
+            operation: Some("test_operation".to_string()),
+            ..Default::default()
+        };
+        let entries = read_log_file(&path, 10, &filter).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].operation, "test_operation");
     }
 
     #[test]
     fn test_read_logs_with_limit() {
-        // Write multiple entries
-        for i in 0..5 {
-            log(
-                "limit_test",
-                Some(format!("entry {}", i)),
-                true,
-            )
-            .unwrap();
-        }
-
-        // Read with limit
-        let entries = read_logs(3, Some("limit_test")).unwrap();
-
-        // Should respect limit
-        assert!(entries.len() <= 3);
-
-        // Should be most recent first
-        if entries.len() >= 2 {
-            assert!(entries[0].timestamp >= entries[1].timestamp);
-        }
+        let (_dir, path) = capture(LogLevel::Info, || {
+            for i in 0..5 {
+                log("limit_test", Some(format!("entry {}", i)), true).unwrap();
+            }
+        });
+
+        let entries = read_log_file(&path, 3, &LogFilter::default()).unwrap();
+
+        // Most recent first
+        assert_eq!(entries.len(), 3);
+        assert!(entries[0].timestamp >= entries[1].timestamp);
+        assert_eq!(entries[0].details.as_deref(), Some("entry 4"));
     }
 
     #[test]
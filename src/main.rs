@@ -3,82 +3,329 @@
 //! Main entry point for the CLI application.
 //! Dispatches commands to the appropriate handlers and outputs JSON results.
 
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
 use std::env;
+use std::ffi::OsString;
+use std::path::Path;
 use uuid::Uuid;
 
-use std::io::{self, BufRead};
+use std::io::{self, BufRead, Read};
+use std::time::Duration;
 
 use claude_hippocampus::{
-    clear_logs, parse_tags, read_logs, Cli, Command, DbConfig, HookType, Result,
-    HookInput, handle_session_start, handle_user_prompt_submit, handle_stop, handle_session_end,
+    clear_logs, parse_ref, parse_tags, parse_uuid_arg, read_logs_filtered, Cli, ConfigAction, LogFilter, PolicyAction, ProjectAction, AliasAction, ReviewAction, WorkspaceAction, Command, DbConfig,
+    HookType, HippocampusError, Result, is_builtin_subcommand, run_hook, simulate_hook, subcommand_position,
 };
+use claude_hippocampus::browse::browse;
 use claude_hippocampus::commands::{
-    add_memory, consolidate, delete_memory, get_context, get_memory, get_stats, list_recent,
-    list_superseded, prune, prune_data, purge_superseded, save_session_summary, search_by_type,
-    search_keyword, show_chain, update_memory, AddMemoryOptions, SearchByTypeOptions,
-    SearchOptions, StatsOptions,
+    add_memories_batch, add_memory, audit, commit_memory, config_set, config_show, consolidate, debug_bundle, decay, dedupe_across_scopes, diff, doctor, embed_memories, embed_turns, install_hooks, delete_memory, edit_memory, drift_check, export_conversations, extract_queued, extraction_log, extract_worker, generate_fixtures, get_context, get_memory, get_turn,
+    get_stats, health_report, history, link, list_recent, list_sessions, migrate_legacy, list_review_queue, list_superseded, list_tags,
+    mine_gotchas, mute, onboard, pin, policy_set, policy_show, policy_unset, project_alias_add, project_alias_list, project_alias_remove, workspace_add_project, workspace_create, workspace_list, expire_proposals, propose_memory, prune, prune_data, purge_superseded, recall, reserve_extraction,
+    resolve_review, retag, retention_policy, rollup_access, schema, save_session_summary, set_briefing, show_briefing, render_context_template, search, search_by_tag, search_meta, search_by_type, search_fts,
+    search_keyword, show_chain, show_provenance, show_session, supersede, top_memories, turn_latency, unlink, unsupersede, update_memory, update_memory_as_new, verify, where_used, with_maintenance_lock, AddMemoryOptions, ContextLayout, HybridSearchOptions, SearchByTypeOptions, SearchMode, SearchOptions,
+    ExportFormat, FixtureOptions, SchemaFormat, ListSessionsOptions, StatsOptions,
 };
-use claude_hippocampus::db::create_pool;
+use claude_hippocampus::daemon::{self, DaemonClient, DaemonReply, DaemonRequest, DaemonResult};
+use claude_hippocampus::db::audit::{result_memory_ids, AuditQuery, AuditRecord};
+use claude_hippocampus::db::{
+    create_pool, create_rollback_pool, create_transaction_pool, get_project_path, get_project_paths,
+    keep_pools_warm, migrations, resolve_project_paths, with_workspace_projects, MemoryEdit,
+};
+use claude_hippocampus::environment::capture_environment;
+use claude_hippocampus::logging::{self, OperationLog};
+use claude_hippocampus::output;
+use claude_hippocampus::external::{expand_alias, run_external};
+use claude_hippocampus::hooks::recorder::{
+    latest_recording, record_hook_input, recordings_dir, redact_text,
+};
+use claude_hippocampus::hooks::strict::{apply_strict_mode, failure_scope};
+use claude_hippocampus::hooks::timing::HookTimer;
 use claude_hippocampus::models::{
-    ClearLogsData, ErrorResponse, LogsData, Scope, SuccessResponse, Tier,
+    ClearLogsData, ErrorResponse, LogsData, Scope, SimulateHookData, SuccessResponse, Tier,
 };
 
-#[tokio::main]
-async fn main() {
-    // Parse CLI arguments
-    let cli = Cli::parse();
+/// Stack of the runtime's worker threads, where the daemon runs commands:
+/// as large as the main thread's, which commands otherwise run on
+const WORKER_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .thread_stack_size(WORKER_STACK_SIZE)
+        .build()
+        .unwrap_or_else(|e| exit_with_error(e.into()));
+    runtime.block_on(cli_main());
+}
+
+async fn cli_main() {
+    // Parse CLI arguments, expanding an alias from the config
+    let args = expanded_args().unwrap_or_else(|e| exit_with_error(e));
+    let matches = Cli::command().get_matches_from(&args);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    // Unknown subcommands run claude-hippocampus-<name> and exit with its code
+    if let Command::External(args) = &cli.command {
+        match run_external(args) {
+            Ok(code) => std::process::exit(code),
+            Err(e) => exit_with_error(e),
+        }
+    }
+
+    let format = output::resolve(cli.format, matches.subcommand_name().unwrap_or_default())
+        .unwrap_or_else(|e| exit_with_error(e));
+
+    // Log the run to memory.log, and diagnostics to stderr, leaving stdout
+    // to the JSON result; reading or clearing the log is not logged
+    let operation = match &cli.command {
+        Command::Logs { .. } | Command::ClearLogs => None,
+        Command::Hook { hook_type } => Some(format!("hook-{}", hook_type.as_str())),
+        _ => matches.subcommand_name().map(str::to_string),
+    };
+    let level = operation
+        .as_ref()
+        .map(|_| DbConfig::load().map(|config| config.log_level).unwrap_or_default());
+    let _ = logging::init(level, cli.stderr_level());
+    let operation = operation.map(OperationLog::start);
+
+    // With a daemon listening, it runs the command on its open connections
+    let invocation = invocation(matches.subcommand_name().unwrap_or_default(), &args);
+    let (result, exit_code) = match forward(&cli.command, &args) {
+        Forwarded::Ran { result, exit_code } => (result, exit_code),
+        Forwarded::RunHere(stdin) => match cli.command {
+            Command::Daemon { idle_timeout } => (serve_daemon(idle_timeout).await, 0),
+            _ => match stdin {
+                Some(stdin) => (FORWARDED_STDIN.scope(stdin, run(cli, invocation)).await, 0),
+                None => (run(cli, invocation).await, 0),
+            },
+        },
+    };
+    if let Some(operation) = operation {
+        let error = match &result {
+            Err(e) => Some(e.to_string()),
+            Ok(output) if exit_code != 0 => output["error"].as_str().map(str::to_string),
+            Ok(_) => None,
+        };
+        operation.finish(error.as_deref());
+    }
 
-    // Run the command and handle errors
-    match run(cli).await {
+    // Handle errors
+    match result {
+        // Plain-text reports (doctor --text) are printed as they are
+        Ok(serde_json::Value::String(text)) => {
+            print!("{}", text);
+        }
         Ok(json) => {
-            println!("{}", serde_json::to_string_pretty(&json).unwrap());
+            print!("{}", output::render(format, matches.subcommand_name().unwrap_or_default(), &json));
         }
-        Err(e) => {
-            let error_response = ErrorResponse::new(e.to_string());
-            println!("{}", serde_json::to_string_pretty(&error_response).unwrap());
-            std::process::exit(1);
+        Err(e) => exit_with_error(e),
+    }
+    // A forwarded command that failed printed its error above
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
+}
+
+/// What became of sending the command to the daemon
+enum Forwarded {
+    /// The daemon ran it, or sending it failed
+    Ran { result: Result<serde_json::Value>, exit_code: i32 },
+    /// Run it in this process, with the stdin read for the daemon if any
+    RunHere(Option<String>),
+}
+
+/// Send the command to the daemon when one is listening. It runs in this
+/// process when none is, or the command could not be sent or was declined;
+/// once the daemon has it, its reply is waited for.
+fn forward(command: &Command, args: &[OsString]) -> Forwarded {
+    if !command.is_forwardable() {
+        return Forwarded::RunHere(None);
+    }
+    let Some(client) = daemon::socket_path().ok().and_then(|path| DaemonClient::connect(&path)) else {
+        return Forwarded::RunHere(None);
+    };
+    let stdin = if command.reads_stdin() {
+        let mut input = Vec::new();
+        let _ = io::stdin().read_to_end(&mut input);
+        String::from_utf8_lossy(&input).into_owned()
+    } else {
+        String::new()
+    };
+    let args = args.iter().map(|arg| arg.to_string_lossy().into_owned()).collect();
+    let reply = DaemonRequest::new(args, stdin.clone())
+        .and_then(|request| client.send(&request, daemon::SEND_TIMEOUT));
+    match reply {
+        Ok(Some(DaemonReply::Declined { .. })) | Ok(None) => {
+            Forwarded::RunHere(command.reads_stdin().then_some(stdin))
         }
+        Ok(Some(DaemonReply::Ran { exit_code, output })) => Forwarded::Ran { result: Ok(output), exit_code },
+        Err(e) => Forwarded::Ran { result: Err(e), exit_code: 1 },
     }
 }
 
-/// Run the dispatched command
-async fn run(cli: Cli) -> Result<serde_json::Value> {
-    match cli.command {
-        // GetTurn outputs just the turn number (no JSON wrapper)
-        // Uses database as source of truth
-        Command::GetTurn { session_id } => {
-            use claude_hippocampus::db::queries::{find_session_by_claude_id, get_next_turn_number};
+/// Run forwarded commands on warm connections until idle for
+/// `idle_timeout` seconds (never when 0)
+async fn serve_daemon(idle_timeout: u64) -> Result<serde_json::Value> {
+    keep_pools_warm();
+    let path = daemon::socket_path()?;
+    let idle = (idle_timeout > 0).then(|| Duration::from_secs(idle_timeout));
+    let served = daemon::serve(&path, idle, run_forwarded).await?;
+    Ok(serde_json::to_value(SuccessResponse::new(DaemonResult {
+        socket: path.display().to_string(),
+        served,
+    }))?)
+}
 
-            let config = DbConfig::load()?;
-            let pool = create_pool(&config).await?;
+/// Run a forwarded command as the invocation that sent it would have
+async fn run_forwarded(request: DaemonRequest) -> Result<serde_json::Value> {
+    let run = logging::command_scope(run_request(&request.args));
+    FORWARDED_STDIN.scope(request.stdin.clone(), request.enter(run)).await
+}
 
-            // Find session and get current turn number from database
-            let session = find_session_by_claude_id(&pool, &session_id).await?;
-            let turn_number = match session {
-                Some(s) => {
-                    // get_next_turn_number returns next turn, so subtract 1 for current
-                    let next = get_next_turn_number(&pool, s.id).await?;
-                    if next > 1 { next - 1 } else { 0 }
-                }
-                None => 0,
-            };
-            Ok(serde_json::Value::Number(turn_number.into()))
+/// Run a forwarded command line, inside its invocation's scope
+async fn run_request(args: &[String]) -> Result<serde_json::Value> {
+    let invalid = |e: clap::Error| HippocampusError::Validation(e.to_string());
+    let matches = Cli::command().try_get_matches_from(args).map_err(invalid)?;
+    let cli = Cli::from_arg_matches(&matches).map_err(invalid)?;
+    if !cli.command.is_forwardable() {
+        return Err(HippocampusError::Validation(format!(
+            "{} cannot run in the daemon",
+            matches.subcommand_name().unwrap_or_default()
+        )));
+    }
+
+    let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+    let invocation = invocation(matches.subcommand_name().unwrap_or_default(), &args);
+    run(cli, invocation).await
+}
+
+/// Print `e` as a JSON error response and exit with status 1
+fn exit_with_error(e: HippocampusError) -> ! {
+    let error_response = ErrorResponse::new(e.to_string()).with_argument(e.argument());
+    println!("{}", serde_json::to_string_pretty(&error_response).unwrap());
+    std::process::exit(1);
+}
+
+/// The command-line arguments, with an alias in the subcommand position
+/// (after any global flags) expanded. The config is only read when the
+/// subcommand is not built in.
+fn expanded_args() -> Result<Vec<OsString>> {
+    let args: Vec<OsString> = env::args_os().collect();
+    let position = subcommand_position(&args);
+    let Some(name) = args.get(position).and_then(|arg| arg.to_str()) else {
+        return Ok(args);
+    };
+    if is_builtin_subcommand(name) {
+        return Ok(args);
+    }
+    let aliases = DbConfig::load().map(|config| config.aliases).unwrap_or_default();
+    if !aliases.contains_key(name) {
+        return Ok(args);
+    }
+
+    // Expand as if the subcommand came first, then put the flags back
+    let mut args: Vec<String> = args.iter().map(|arg| arg.to_string_lossy().into_owned()).collect();
+    let flags: Vec<String> = args.drain(1..position).collect();
+    let mut expanded = expand_alias(args, &aliases, is_builtin_subcommand)?;
+    expanded.splice(1..1, flags);
+    Ok(expanded.into_iter().map(OsString::from).collect())
+}
+
+/// The audit record of this run, before it is known how it went: the
+/// subcommand, its arguments with credentials redacted, and the OS user
+fn invocation(operation: &str, args: &[OsString]) -> AuditRecord {
+    let arguments = args
+        .iter()
+        .map(|arg| arg.to_string_lossy())
+        .skip_while(|arg| arg != operation)
+        .skip(1)
+        .map(|arg| redact_text(&arg))
+        .collect();
+    AuditRecord {
+        operation: operation.to_string(),
+        arguments,
+        actor: daemon::var("USER")
+            .or_else(|| daemon::var("LOGNAME"))
+            .unwrap_or_else(|| "unknown".to_string()),
+        ..AuditRecord::default()
+    }
+}
+
+/// Record an audited command in the operation log, with the memories named
+/// in its arguments and those in its result. Failing to record never fails
+/// the command.
+async fn record_operation(
+    pool: &sqlx::postgres::PgPool,
+    mut record: AuditRecord,
+    mut memory_ids: Vec<Uuid>,
+    result: &Result<serde_json::Value>,
+) {
+    if let Ok(value) = result {
+        for id in result_memory_ids(value) {
+            if !memory_ids.contains(&id) {
+                memory_ids.push(id);
+            }
         }
+    }
+    record.memory_ids = memory_ids;
+    record.success = match result {
+        Ok(value) => value["success"].as_bool().unwrap_or(true),
+        Err(_) => false,
+    };
+    record.session_id = logging::session_id();
+    record.project_path = get_project_path();
+    if let Err(e) = claude_hippocampus::db::audit::record(pool, &record).await {
+        let _ = logging::log("audit", Some(e.to_string()), false);
+    }
+}
+
+/// Run the dispatched command
+async fn run(cli: Cli, invocation: AuditRecord) -> Result<serde_json::Value> {
+    cli.command.validate_refs()?;
 
+    match cli.command {
         // Commands that don't require database connection
-        Command::Logs { n, operation } => {
-            let entries = read_logs(n as usize, operation.as_deref())?;
+        Command::Logs {
+            n,
+            operation,
+            failed,
+            since,
+            until,
+            op_prefix,
+            level,
+        } => {
+            let filter = LogFilter {
+                operation,
+                since,
+                until,
+                failed_only: failed,
+                operation_prefix: op_prefix,
+                min_level: level,
+            };
+            let entries = read_logs_filtered(n as usize, &filter)?;
             let count = entries.len();
             let log_data = LogsData {
                 entries: entries
                     .into_iter()
-                    .map(|e| claude_hippocampus::models::response::LogEntry {
-                        timestamp: e.timestamp.to_rfc3339(),
-                        level: if e.success { "info".to_string() } else { "error".to_string() },
-                        operation: e.operation,
-                        details: e.details.map(|d| serde_json::json!({"message": d}))
-                            .unwrap_or(serde_json::json!({})),
+                    .map(|e| {
+                        let mut details = serde_json::Map::new();
+                        let fields = [
+                            ("message", e.details.map(serde_json::Value::from)),
+                            ("durationMs", e.duration_ms.map(serde_json::Value::from)),
+                            ("dbMs", e.db_ms.map(serde_json::Value::from)),
+                            ("dbQueries", e.db_queries.map(serde_json::Value::from)),
+                            ("sessionId", e.session_id.map(serde_json::Value::from)),
+                        ];
+                        for (key, value) in fields {
+                            if let Some(value) = value {
+                                details.insert(key.to_string(), value);
+                            }
+                        }
+                        claude_hippocampus::models::response::LogEntry {
+                            timestamp: e.timestamp.to_rfc3339(),
+                            level: e.level.as_str().to_string(),
+                            operation: e.operation,
+                            details: serde_json::Value::Object(details),
+                        }
                     })
                     .collect(),
                 count,
@@ -94,35 +341,210 @@ async fn run(cli: Cli) -> Result<serde_json::Value> {
             }))?)
         }
 
+        Command::Config { action } => {
+            let project_path = get_project_path();
+            match action {
+                ConfigAction::Show => {
+                    let result = config_show(project_path.as_deref())?;
+                    Ok(serde_json::to_value(SuccessResponse::new(result))?)
+                }
+                ConfigAction::Set { key, value, project } => {
+                    let result = config_set(&key, &value, project, project_path.as_deref())?;
+                    Ok(serde_json::to_value(SuccessResponse::new(result))?)
+                }
+            }
+        }
+
+        Command::InstallHooks { project, uninstall, binary } => {
+            let project_path = get_project_path();
+            let result =
+                install_hooks(project, project_path.as_deref(), binary.as_deref(), uninstall)?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        // Built from the embedded migrations, so it needs no database
+        Command::Schema { format } => Ok(serde_json::Value::String(schema(SchemaFormat::from_flag(format)?))),
+
+        // Runs without a database so it can report why the connection fails
+        Command::Doctor { text } => {
+            let project_path = get_project_path();
+            let report = doctor(project_path.as_deref()).await;
+            if text {
+                Ok(serde_json::Value::String(report.to_text()))
+            } else {
+                Ok(serde_json::to_value(SuccessResponse::new(report))?)
+            }
+        }
+
+        // Records a failed connection in the bundle rather than failing
+        Command::DebugBundle { output, log_lines } => {
+            let project_path = get_project_path();
+            let result = debug_bundle(project_path.as_deref(), output, log_lines).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        // Hooks are recorded before connecting so connection failures can be replayed too
+        Command::Hook { hook_type } => {
+            let timer = HookTimer::start();
+            let raw_input = read_raw_stdin()?;
+            let payload = serde_json::from_str::<serde_json::Value>(&raw_input).unwrap_or_default();
+            if let Some(session_id) = payload["session_id"].as_str() {
+                logging::set_session_id(session_id);
+            }
+            let config = DbConfig::load()?;
+            if config.record_hooks {
+                let limit = config.record_hooks_limit;
+                let _ = recordings_dir()
+                    .and_then(|dir| record_hook_input(&dir, hook_type.as_str(), &raw_input, limit));
+            }
+            failure_scope(async {
+                let result = match create_pool(&config).await {
+                    Ok(pool) => {
+                        let result = match migrations::require_current(&pool).await {
+                            Ok(()) => run_hook(&pool, &hook_type, &raw_input).await,
+                            Err(e) => Err(e),
+                        };
+                        timer.record(&pool, &hook_type, &raw_input, result.is_ok()).await;
+                        result
+                    }
+                    Err(e) => Err(e),
+                };
+                apply_strict_mode(config.strict, &hook_type, result)
+            })
+            .await
+        }
+
+        Command::SimulateHook { hook_type, input } => {
+            let raw_input = std::fs::read_to_string(daemon::absolute(Path::new(&input)))?;
+            let config = DbConfig::load()?;
+            simulate(&config, &hook_type, &raw_input).await
+        }
+
+        Command::ReplayLastHook { hook_type, dry_run } => {
+            let Some(path) = latest_recording(&recordings_dir()?, hook_type.as_str())? else {
+                return Ok(serde_json::to_value(ErrorResponse::new(format!(
+                    "No recorded input for hook {} (run `config set record_hooks true`)",
+                    hook_type.as_str()
+                )))?);
+            };
+            let raw_input = std::fs::read_to_string(&path)?;
+            let config = DbConfig::load()?;
+            if dry_run {
+                simulate(&config, &hook_type, &raw_input).await
+            } else {
+                let pool = create_pool(&config).await?;
+                migrations::require_current(&pool).await?;
+                run_hook(&pool, &hook_type, raw_input.trim()).await
+            }
+        }
+
+        // Records share one connection so they commit together; a dry run
+        // closes it without committing
+        Command::AddMemoriesBatch { dry_run, conflicts } => {
+            let input = read_stdin()?;
+            let config = DbConfig::load()?;
+            let pool = create_transaction_pool(&config).await?;
+            migrations::require_current(&pool).await?;
+            let project_paths = resolve_project_paths(&pool, &get_project_paths()).await?;
+            let project_path = project_paths.first();
+            let preview = dry_run.then_some(conflicts);
+            let result = add_memories_batch(
+                &pool,
+                &input,
+                project_path.map(String::as_str),
+                config.duplicate_check(),
+                &config.lint,
+                preview,
+            )
+            .await
+            .and_then(|result| Ok(serde_json::to_value(SuccessResponse::new(result))?));
+            if !dry_run {
+                record_operation(&pool, invocation, Vec::new(), &result).await;
+            }
+            result
+        }
+
+        // Two databases: the legacy source and the configured target
+        Command::MigrateLegacy {
+            from,
+            table,
+            dry_run,
+            jobs,
+        } => {
+            let config = DbConfig::load()?;
+            let source = create_pool(&DbConfig {
+                url: Some(from),
+                max_connections: 1,
+                ..DbConfig::default()
+            })
+            .await?;
+            // A dry run imports inside a transaction that is never committed
+            let target = if dry_run {
+                create_rollback_pool(&config).await?
+            } else {
+                create_pool(&config).await?
+            };
+            migrations::require_current(&target).await?;
+            let result = migrate_legacy(&source, &target, &table, dry_run, jobs)
+                .await
+                .and_then(|result| Ok(serde_json::to_value(SuccessResponse::new(result))?));
+            if !dry_run {
+                record_operation(&target, invocation, Vec::new(), &result).await;
+            }
+            result
+        }
+
         Command::Stats { tier } => {
             // Stats requires database connection
             let config = DbConfig::load()?;
             let pool = create_pool(&config).await?;
-            let project_path = env::var("PROJECT_PATH")
-                .or_else(|_| env::current_dir().map(|p| p.to_string_lossy().to_string()))
-                .ok();
-
+            migrations::require_current(&pool).await?;
+            let mut project_paths = resolve_project_paths(&pool, &get_project_paths()).await?;
+            if cli.workspace {
+                project_paths = with_workspace_projects(&pool, &project_paths).await?;
+            }
             let options = StatsOptions {
-                tier,
-                project_path,
+                tier: tier.unwrap_or(config.default_tier),
+                project_paths,
             };
             let result = get_stats(&pool, options).await?;
             Ok(serde_json::to_value(SuccessResponse::new(result))?)
         }
 
+        Command::HealthReport => {
+            // Thresholds live in the same config as the connection
+            let config = DbConfig::load()?;
+            let pool = create_pool(&config).await?;
+            migrations::require_current(&pool).await?;
+            let result = health_report(&pool, &config.anomalies).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
         // Commands that require database connection
         _ => {
             // Load database configuration
             let config = DbConfig::load()?;
             let pool = create_pool(&config).await?;
+            // `migrate` brings the schema up to date; everything else needs it
+            if !matches!(cli.command, Command::Migrate { .. }) {
+                migrations::require_current(&pool).await?;
+            }
 
-            // Get project path from environment (same as Node.js: PROJECT_PATH)
-            // Falls back to current working directory
-            let project_path = env::var("PROJECT_PATH")
-                .or_else(|_| env::current_dir().map(|p| p.to_string_lossy().to_string()))
-                .ok();
+            // Get project paths from environment (same as Node.js: PROJECT_PATH)
+            // Falls back to current working directory. Writes go to the first
+            // project; scoped reads include all of them, and their aliases.
+            let mut project_paths = resolve_project_paths(&pool, &get_project_paths()).await?;
+            if cli.workspace {
+                project_paths = with_workspace_projects(&pool, &project_paths).await?;
+            }
 
-            dispatch_db_command(cli.command, &pool, project_path.as_deref()).await
+            let audited = cli.command.is_audited();
+            let memory_ids = cli.command.memory_refs();
+            let result = dispatch_db_command(cli.command, &pool, &config, &project_paths).await;
+            if audited {
+                record_operation(&pool, invocation, memory_ids, &result).await;
+            }
+            result
         }
     }
 }
@@ -131,8 +553,10 @@ async fn run(cli: Cli) -> Result<serde_json::Value> {
 async fn dispatch_db_command(
     command: Command,
     pool: &sqlx::postgres::PgPool,
-    project_path: Option<&str>,
+    config: &DbConfig,
+    project_paths: &[String],
 ) -> Result<serde_json::Value> {
+    let project_path = project_paths.first().map(String::as_str);
     match command {
         Command::AddMemory {
             memory_type,
@@ -144,19 +568,20 @@ async fn dispatch_db_command(
             source_turn_id,
             claude_session_id: _,
             supersedes,
+            constraints,
+            summary,
+            ignore_invalid_refs,
+            check_conflicts,
         } => {
             let tags_vec = parse_tags(&tags);
-            let source_session = source_session_id
-                .as_deref()
-                .and_then(|s| Uuid::parse_str(s).ok());
-            let source_turn = source_turn_id
-                .as_deref()
-                .and_then(|s| Uuid::parse_str(s).ok());
-            let supersedes_uuid = supersedes.as_deref().and_then(|s| Uuid::parse_str(s).ok());
+            let source_session = parse_ref("--session", source_session_id.as_deref(), ignore_invalid_refs)?;
+            let source_turn = parse_ref("--turn", source_turn_id.as_deref(), ignore_invalid_refs)?;
+            let supersedes_uuid = parse_ref("--supersedes", supersedes.as_deref(), ignore_invalid_refs)?;
 
             let opts = AddMemoryOptions {
                 memory_type,
                 content,
+                summary,
                 tags: tags_vec,
                 confidence,
                 tier: scope_to_tier(tier),
@@ -164,95 +589,400 @@ async fn dispatch_db_command(
                 source_session_id: source_session,
                 source_turn_id: source_turn,
                 supersedes: supersedes_uuid,
+                constraints,
+                duplicate_check: config.duplicate_check(),
+                lint: config.lint.clone(),
             };
 
-            let result = add_memory(pool, opts).await?;
+            let result = add_memory(pool, opts, config.conflict_check(check_conflicts)).await?;
             match result {
                 claude_hippocampus::commands::AddMemoryResult::Success(json) => Ok(json),
                 claude_hippocampus::commands::AddMemoryResult::Duplicate(json) => Ok(json),
+                claude_hippocampus::commands::AddMemoryResult::Rejected(json) => Ok(json),
             }
         }
 
-        Command::UpdateMemory { id, content, tier } => {
-            let uuid = Uuid::parse_str(&id)?;
-            update_memory(pool, uuid, &content, Some(scope_to_tier(tier)), project_path).await
+        Command::ProposeMemory {
+            memory_type,
+            content,
+            tags,
+            confidence,
+            tier,
+            source_session_id,
+            source_turn_id,
+            supersedes,
+            constraints,
+            ttl_hours,
+            ignore_invalid_refs,
+        } => {
+            let opts = AddMemoryOptions {
+                memory_type,
+                content,
+                summary: None,
+                tags: parse_tags(&tags),
+                confidence,
+                tier: scope_to_tier(tier),
+                project_path: project_path.map(|s| s.to_string()),
+                source_session_id: parse_ref("--session", source_session_id.as_deref(), ignore_invalid_refs)?,
+                source_turn_id: parse_ref("--turn", source_turn_id.as_deref(), ignore_invalid_refs)?,
+                supersedes: parse_ref("--supersedes", supersedes.as_deref(), ignore_invalid_refs)?,
+                constraints,
+                duplicate_check: config.duplicate_check(),
+                lint: config.lint.clone(),
+            };
+            propose_memory(pool, opts, ttl_hours).await
+        }
+
+        Command::CommitMemory { staging_id, supersedes } => {
+            let staging_id = parse_uuid_arg("<staging-id>", &staging_id)?;
+            let supersedes = parse_ref("--supersedes", supersedes.as_deref(), false)?;
+            commit_memory(pool, staging_id, supersedes, config.duplicate_check(), &config.lint)
+                .await
+        }
+
+        Command::UpdateMemory { id, content, tier, expected_updated_at, as_new } => {
+            let uuid = parse_uuid_arg("<id>", &id)?;
+            let tier = scope_to_tier(tier);
+            if as_new {
+                let duplicate_check = config.duplicate_check();
+                update_memory_as_new(
+                    pool,
+                    uuid,
+                    &content,
+                    tier,
+                    project_path,
+                    expected_updated_at,
+                    duplicate_check,
+                    &config.lint,
+                )
+                .await
+            } else {
+                update_memory(pool, uuid, &content, Some(tier), project_path, expected_updated_at, &config.lint)
+                    .await
+            }
+        }
+
+        Command::EditMemory {
+            id,
+            content,
+            memory_type,
+            confidence,
+            add_tags,
+            remove_tags,
+            expected_updated_at,
+        } => {
+            let uuid = parse_uuid_arg("<id>", &id)?;
+            let edit = MemoryEdit {
+                content,
+                memory_type,
+                confidence,
+                add_tags,
+                remove_tags,
+            };
+            edit_memory(pool, uuid, &edit, expected_updated_at, &config.lint).await
         }
 
         Command::DeleteMemory { id, tier: _ } => {
-            let uuid = Uuid::parse_str(&id)?;
+            let uuid = parse_uuid_arg("<id>", &id)?;
             delete_memory(pool, uuid).await
         }
 
-        Command::GetMemory { id } => {
-            let uuid = Uuid::parse_str(&id)?;
-            get_memory(pool, uuid).await
+        Command::GetMemory { id, as_of, neighborhood } => {
+            let uuid = parse_uuid_arg("<id>", &id)?;
+            get_memory(pool, uuid, as_of, neighborhood).await
         }
 
-        Command::SearchKeyword { query, tier, limit } => {
+        Command::SearchKeyword {
+            query,
+            tier,
+            limit,
+            mode,
+            as_of,
+            include_archived,
+            filters,
+            page,
+        } => {
             let options = SearchOptions {
                 query,
-                tier,
+                mode,
+                tier: tier.unwrap_or(config.default_tier),
                 limit: limit as i32,
-                project_path: project_path.map(|s| s.to_string()),
+                offset: page.offset(),
+                cursor: page.cursor,
+                project_paths: project_paths.to_vec(),
+                as_of,
+                include_archived,
+                filters: filters.filters(),
+                ranker: config.ranking.search_ranker(),
             };
             let result = search_keyword(pool, options).await?;
             Ok(serde_json::to_value(SuccessResponse::new(result))?)
         }
 
+        Command::SearchFts {
+            query,
+            tier,
+            limit,
+            as_of,
+            include_archived,
+            filters,
+            page,
+        } => {
+            let options = SearchOptions {
+                query,
+                mode: SearchMode::Fts,
+                tier: tier.unwrap_or(config.default_tier),
+                limit: limit as i32,
+                offset: page.offset(),
+                cursor: page.cursor,
+                project_paths: project_paths.to_vec(),
+                as_of,
+                include_archived,
+                filters: filters.filters(),
+                ranker: config.ranking.search_ranker(),
+            };
+            let result = search_fts(pool, options).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::Search {
+            query,
+            tier,
+            limit,
+            filters,
+        } => {
+            let options = HybridSearchOptions {
+                query,
+                tier: tier.unwrap_or(config.default_tier),
+                limit,
+                project_paths: project_paths.to_vec(),
+                filters: filters.filters(),
+            };
+            let result = search(pool, config, options).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
         Command::SearchByType {
             memory_type,
             query,
             tier,
             limit,
+            include_archived,
+            filters,
+            page,
         } => {
             let options = SearchByTypeOptions {
                 memory_type,
                 query,
-                tier,
+                tier: tier.unwrap_or(config.default_tier),
                 limit: limit as i32,
-                project_path: project_path.map(|s| s.to_string()),
+                offset: page.offset(),
+                cursor: page.cursor,
+                project_paths: project_paths.to_vec(),
+                include_archived,
+                filters: filters.filters(),
+                ranker: config.ranking.search_ranker(),
             };
             let result = search_by_type(pool, options).await?;
             Ok(serde_json::to_value(SuccessResponse::new(result))?)
         }
 
-        Command::GetContext { limit } => {
-            let result = get_context(pool, limit as i32, project_path).await?;
+        Command::SearchByTag { tag, tier, limit, include_archived } => {
+            let tier = tier.unwrap_or(config.default_tier);
+            let ranker = config.ranking.search_ranker();
+            let result = search_by_tag(
+                pool,
+                &tag,
+                tier,
+                limit as i32,
+                project_paths,
+                include_archived,
+                ranker.as_ref(),
+            )
+            .await?;
             Ok(serde_json::to_value(SuccessResponse::new(result))?)
         }
 
-        Command::ListRecent { n, tier } => {
-            let result = list_recent(pool, n as i32, tier, project_path).await?;
+        Command::ListTags { tier } => {
+            let tier = tier.unwrap_or(config.default_tier);
+            let result = list_tags(pool, tier, project_paths).await?;
             Ok(serde_json::to_value(SuccessResponse::new(result))?)
         }
 
-        Command::Consolidate { tier } => {
-            consolidate(pool, scope_to_tier(tier), project_path).await
+        Command::RenameTag { from, to, tier } => {
+            let result = retag(pool, &[from], &to, tier, project_path).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
         }
 
-        Command::Prune { low_days, medium_days, tier } => {
-            prune(pool, low_days as i32, medium_days as i32, scope_to_tier(tier), project_path).await
+        Command::MergeTags { sources, into, tier } => {
+            let result = retag(pool, &sources, &into, tier, project_path).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::GetContext {
+            limit,
+            since_last_session,
+            max_tokens,
+            group_by_type,
+            type_limits,
+            template,
+        } => {
+            use claude_hippocampus::db::queries::last_session_boundary;
+
+            use claude_hippocampus::git::get_git_status;
+            use claude_hippocampus::commands::ranking::context_tags;
+            use claude_hippocampus::util::tokens::estimate_tokens;
+
+            let environment = capture_environment(project_path);
+            let tags = project_path
+                .and_then(|p| get_git_status(p).ok().flatten())
+                .map(|git| context_tags(&git))
+                .unwrap_or_default();
+            let ranker = config.ranking.context_ranker(&tags);
+            let since = if since_last_session {
+                last_session_boundary(pool, project_paths, None).await?
+            } else {
+                None
+            };
+            let mut result = get_context(
+                pool,
+                limit as i32,
+                project_paths,
+                Some(&environment),
+                ranker.as_ref(),
+                since,
+                &[],
+                max_tokens,
+                &ContextLayout { group_by_type, type_limits, claude_md: None },
+            )
+            .await?;
+            if let Some(path) = template.or_else(|| config.context_template_file.clone()) {
+                let source = std::fs::read_to_string(daemon::absolute(&path)).map_err(|e| {
+                    HippocampusError::Config(format!(
+                        "Cannot read context template {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+                result.context = render_context_template(&source, &result)?;
+                if let Some(budget) = result.budget.as_mut() {
+                    budget.estimated_tokens = estimate_tokens(&result.context);
+                }
+            }
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::TopMemories { tier, days, limit } => {
+            let tier = tier.unwrap_or(config.default_tier);
+            let result = top_memories(pool, tier, project_paths, days, limit).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::RollupAccess => {
+            let result = rollup_access(pool).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::ListRecent { n, tier, page } => {
+            let tier = tier.unwrap_or(config.default_tier);
+            let result = list_recent(pool, n as i32, page.offset(), page.cursor.as_ref(), tier, project_paths).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::Consolidate { tier, merge, lock } => {
+            let similarity = config.duplicate_similarity;
+            let run = consolidate(pool, scope_to_tier(tier), project_path, similarity, merge);
+            with_maintenance_lock(pool, lock.mode(), "consolidate", run).await
+        }
+
+        Command::DedupeAcrossScopes { prefer, lock } => {
+            let similarity = config.duplicate_similarity;
+            let run = dedupe_across_scopes(pool, project_paths, similarity, prefer);
+            with_maintenance_lock(pool, lock.mode(), "dedupe-across-scopes", run).await
+        }
+
+        Command::Prune { low_days, medium_days, tier, lock } => {
+            let retention = retention_policy(pool, config).await?;
+            let low_days = low_days.unwrap_or(retention.low_days) as i32;
+            let medium_days = medium_days.unwrap_or(retention.medium_days) as i32;
+            let run = prune(pool, low_days, medium_days, scope_to_tier(tier), project_path);
+            with_maintenance_lock(pool, lock.mode(), "prune", run).await
+        }
+
+        Command::GenerateFixtures { memories, sessions, projects, days, seed, clear } => {
+            let options = FixtureOptions { memories, sessions, projects, days, seed, clear };
+            Ok(serde_json::to_value(generate_fixtures(pool, &options).await?)?)
+        }
+
+        Command::Decay { archive_days, dry_run, lock } => {
+            let retention = retention_policy(pool, config).await?;
+            let archive_days = archive_days.unwrap_or(retention.archive_days);
+            let run = decay(pool, config.ranking.half_life_days, archive_days, dry_run);
+            with_maintenance_lock(pool, lock.mode(), "decay", run).await
         }
 
         Command::SaveSessionSummary { summary } => {
             // Use empty session ID if not provided - the function will auto-detect
-            let session_id = env::var("CLAUDE_SESSION_ID").unwrap_or_else(|_| String::new());
+            let session_id = daemon::var("CLAUDE_SESSION_ID").unwrap_or_default();
             let summary_json = serde_json::json!({ "summary": summary });
             save_session_summary(pool, &session_id, &summary_json).await
         }
 
         // Supersession commands
         Command::ShowChain { id } => {
-            let uuid = Uuid::parse_str(&id)?;
+            let uuid = parse_uuid_arg("<id>", &id)?;
             show_chain(pool, uuid).await
         }
 
-        Command::ListSuperseded { tier, limit } => {
-            list_superseded(pool, tier, limit, project_path).await
+        Command::Link { source, target, relation } => {
+            let source = parse_uuid_arg("<id>", &source)?;
+            let target = parse_uuid_arg("<id>", &target)?;
+            let result = link(pool, source, target, relation).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
         }
 
-        Command::PurgeSuperseded { days, tier } => {
-            purge_superseded(pool, days as i32, scope_to_tier(tier), project_path).await
+        Command::Unlink { a, b, relation } => {
+            let result = unlink(pool, parse_uuid_arg("<id>", &a)?, parse_uuid_arg("<id>", &b)?, relation).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::Supersede { old_id, new_id } => {
+            let old_id = parse_uuid_arg("<old-id>", &old_id)?;
+            let new_id = parse_uuid_arg("<new-id>", &new_id)?;
+            let result = supersede(pool, old_id, new_id).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::Unsupersede { id, deactivate_successor } => {
+            let result = unsupersede(pool, parse_uuid_arg("<id>", &id)?, deactivate_successor).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::History { id } => {
+            let result = history(pool, parse_uuid_arg("<id>", &id)?).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::Diff { id, rev } => {
+            let result = diff(pool, parse_uuid_arg("<id>", &id)?, rev).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::ShowProvenance { id } => {
+            let result = show_provenance(pool, parse_uuid_arg("<id>", &id)?).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::ListSuperseded { tier, limit, page } => {
+            let tier = tier.unwrap_or(config.default_tier);
+            list_superseded(pool, tier, limit, page.offset(), page.cursor.as_ref(), project_paths).await
+        }
+
+        Command::PurgeSuperseded { days, tier, lock } => {
+            let retention = retention_policy(pool, config).await?;
+            let days = days.unwrap_or(retention.superseded_days) as i32;
+            let run = purge_superseded(pool, days, scope_to_tier(tier), project_path);
+            with_maintenance_lock(pool, lock.mode(), "purge-superseded", run).await
         }
 
         Command::PruneData {
@@ -260,8 +990,273 @@ async fn dispatch_db_command(
             turns_days,
             sessions_days,
             dry_run,
+            lock,
+        } => {
+            let retention = retention_policy(pool, config).await?;
+            let tool_calls_days = tool_calls_days.unwrap_or(retention.tool_calls_days);
+            let turns_days = turns_days.unwrap_or(retention.turns_days);
+            let sessions_days = sessions_days.unwrap_or(retention.sessions_days);
+            let run = prune_data(pool, tool_calls_days, turns_days, sessions_days, dry_run);
+            with_maintenance_lock(pool, lock.mode(), "prune-data", run).await
+        }
+
+        Command::Policy { action } => match action {
+            PolicyAction::Show => {
+                let result = policy_show(pool, config).await?;
+                Ok(serde_json::to_value(SuccessResponse::new(result))?)
+            }
+            PolicyAction::Set { key, days } => {
+                let result = policy_set(pool, config, &key, days).await?;
+                Ok(serde_json::to_value(SuccessResponse::new(result))?)
+            }
+            PolicyAction::Unset { key } => {
+                let result = policy_unset(pool, config, &key).await?;
+                Ok(serde_json::to_value(SuccessResponse::new(result))?)
+            }
+        },
+
+        Command::Workspace { action } => match action {
+            WorkspaceAction::Create { name } => {
+                let result = workspace_create(pool, &name).await?;
+                Ok(serde_json::to_value(SuccessResponse::new(result))?)
+            }
+            WorkspaceAction::AddProject { name, path } => {
+                let result = workspace_add_project(pool, &name, path.as_deref(), project_path).await?;
+                Ok(serde_json::to_value(SuccessResponse::new(result))?)
+            }
+            WorkspaceAction::List => {
+                let result = workspace_list(pool).await?;
+                Ok(serde_json::to_value(SuccessResponse::new(result))?)
+            }
+        },
+
+        Command::Project {
+            action: ProjectAction::Alias { action },
+        } => match action {
+            AliasAction::Add { path, project } => {
+                let result = project_alias_add(pool, &path, project.as_deref(), project_path).await?;
+                Ok(serde_json::to_value(SuccessResponse::new(result))?)
+            }
+            AliasAction::List => {
+                let result = project_alias_list(pool).await?;
+                Ok(serde_json::to_value(SuccessResponse::new(result))?)
+            }
+            AliasAction::Remove { path } => {
+                let result = project_alias_remove(pool, &path).await?;
+                Ok(serde_json::to_value(SuccessResponse::new(result))?)
+            }
+        },
+
+        Command::Review { action } => match action {
+            ReviewAction::List { tier, limit } => {
+                let tier = tier.unwrap_or(config.default_tier);
+                expire_proposals(pool, config.duplicate_check(), &config.lint).await?;
+                list_review_queue(pool, tier, limit, project_paths).await
+            }
+            ReviewAction::Resolve { id } => {
+                let uuid = parse_uuid_arg("<id>", &id)?;
+                resolve_review(pool, uuid).await
+            }
+        },
+
+        Command::Verify { project } => {
+            let path = project
+                .or_else(|| project_path.map(|p| p.to_string()))
+                .ok_or_else(|| {
+                    claude_hippocampus::error::HippocampusError::Validation(
+                        "No project path given".to_string(),
+                    )
+                })?;
+            let result = verify(pool, &path).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::Onboard {
+            project,
+            limit,
+            sessions,
+        } => {
+            let path = project
+                .or_else(|| project_path.map(|p| p.to_string()))
+                .ok_or_else(|| {
+                    claude_hippocampus::error::HippocampusError::Validation(
+                        "No project path given".to_string(),
+                    )
+                })?;
+            let result = onboard(pool, &path, limit, sessions).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::DriftCheck { project, patch } => {
+            let path = project
+                .or_else(|| project_path.map(|p| p.to_string()))
+                .ok_or_else(|| {
+                    claude_hippocampus::error::HippocampusError::Validation(
+                        "No project path given".to_string(),
+                    )
+                })?;
+            let result = drift_check(pool, &path, patch).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::SetBriefing {
+            content,
+            project,
+            clear,
+        } => {
+            let path = project
+                .or_else(|| project_path.map(|p| p.to_string()))
+                .ok_or_else(|| {
+                    claude_hippocampus::error::HippocampusError::Validation(
+                        "No project path given".to_string(),
+                    )
+                })?;
+            let content = match content {
+                Some(content) => content,
+                None if clear => String::new(),
+                None => read_stdin()?,
+            };
+            let result = set_briefing(pool, &path, &content, clear).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::ShowBriefing { project } => {
+            let path = project
+                .or_else(|| project_path.map(|p| p.to_string()))
+                .ok_or_else(|| {
+                    claude_hippocampus::error::HippocampusError::Validation(
+                        "No project path given".to_string(),
+                    )
+                })?;
+            let result = show_briefing(pool, &path).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::MineGotchas { session, dry_run } => {
+            let cap = config.extraction_daily_cap;
+            let check = config.duplicate_check();
+            let result =
+                mine_gotchas(pool, &session, project_path, dry_run, cap, check, &config.lint)
+                    .await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::Migrate { status } => {
+            let result = if status {
+                migrations::status(pool).await?
+            } else {
+                migrations::run(pool).await?
+            };
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::Browse => {
+            let result = browse(pool, project_paths).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::ReserveExtraction { turn } => {
+            let turn = parse_ref("--turn", turn.as_deref(), false)?;
+            let result = reserve_extraction(pool, config.extraction_daily_cap, turn).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::ExtractWorker {
+            prompt,
+            confidence,
+            session,
+            turn,
+        } => {
+            let session = parse_ref("--session", session.as_deref(), false)?;
+            let turn = parse_ref("--turn", turn.as_deref(), false)?;
+            let result = extract_worker(
+                pool,
+                &prompt,
+                confidence,
+                session,
+                turn,
+                project_path,
+                config.extraction_daily_cap,
+                config.duplicate_check(),
+                &config.lint,
+            )
+            .await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::ExtractQueued { limit } => {
+            let cap = config.extraction_daily_cap;
+            let check = config.duplicate_check();
+            let rules = config.extraction_prompt_rules();
+            let result = extract_queued(pool, cap, limit, check, &config.lint, &rules).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::ExtractionLog { session, limit } => {
+            let result = extraction_log(pool, &session, limit).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::WhereUsed { id } => {
+            let result = where_used(pool, parse_uuid_arg("<id>", &id)?).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::Mute { id, session, undo } => {
+            let result = mute(pool, parse_uuid_arg("<id>", &id)?, &session, undo).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::Pin { id } => {
+            let result = pin(pool, parse_uuid_arg("<id>", &id)?, true).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::Unpin { id } => {
+            let result = pin(pool, parse_uuid_arg("<id>", &id)?, false).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::Recall { query, limit, all_projects } => {
+            let project_paths = if all_projects { &[][..] } else { project_paths };
+            let result = recall(pool, config, &query, limit, project_paths).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::EmbedTurns { turn, limit } => {
+            let turn = parse_ref("--turn", turn.as_deref(), false)?;
+            let result = embed_turns(pool, config, turn, limit).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::EmbedMemories { limit } => {
+            let result = embed_memories(pool, config, limit).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::Audit {
+            memory,
+            session,
+            operation,
+            since,
+            until,
+            limit,
         } => {
-            prune_data(pool, tool_calls_days, turns_days, sessions_days, dry_run).await
+            let query = AuditQuery {
+                memory_id: parse_ref("--memory", memory.as_deref(), false)?,
+                session_id: session,
+                operation,
+                since,
+                until,
+                limit,
+            };
+            let result = audit(pool, &query).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::TurnLatency { session } => {
+            let result = turn_latency(pool, &session).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
         }
 
         // Session commands
@@ -275,17 +1270,68 @@ async fn dispatch_db_command(
             // Get project path (from arg, env, or cwd)
             let path = project_path_arg
                 .or_else(|| project_path.map(|p| p.to_string()))
-                .or_else(|| env::current_dir().ok().map(|p| p.to_string_lossy().to_string()));
+                .or_else(|| daemon::current_dir().ok().map(|p| p.to_string_lossy().to_string()));
 
             // Capture git status if we have a path
             let git_status = path.as_ref()
                 .and_then(|p| get_git_status(p).ok())
                 .flatten();
 
-            let session = create_session(pool, &claude_session_id, path.as_deref(), git_status.as_ref()).await?;
+            let environment = capture_environment(path.as_deref());
+
+            let session = create_session(
+                pool,
+                &claude_session_id,
+                path.as_deref(),
+                git_status.as_ref(),
+                Some(&environment),
+            )
+            .await?;
             Ok(serde_json::to_value(SuccessResponse::new(session))?)
         }
 
+        Command::ListSessions { status, project_path, since, limit, offset } => {
+            let options = ListSessionsOptions {
+                status,
+                project_path,
+                since,
+                limit,
+                offset,
+            };
+            let result = list_sessions(pool, &options).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::SearchMeta { query, project_path, limit } => {
+            let result = search_meta(pool, &query, project_path.as_deref(), limit).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
+        Command::ShowSession { id, text } => {
+            let transcript = show_session(pool, &id).await?;
+            if text {
+                Ok(serde_json::Value::String(transcript.to_text()))
+            } else {
+                Ok(serde_json::to_value(SuccessResponse::new(transcript))?)
+            }
+        }
+
+        Command::ExportConversations { project, since, format, jobs } => {
+            let format = ExportFormat::from_flag(format)?;
+            let project = project
+                .or_else(|| project_path.map(str::to_string))
+                .ok_or_else(|| {
+                    claude_hippocampus::error::HippocampusError::Validation(
+                        "No project: pass --project or set PROJECT_PATH".to_string(),
+                    )
+                })?;
+            let result = export_conversations(pool, &project, since, jobs).await?;
+            match format {
+                ExportFormat::Jsonl => Ok(serde_json::Value::String(result.to_jsonl()?)),
+                ExportFormat::Json => Ok(serde_json::to_value(SuccessResponse::new(result))?),
+            }
+        }
+
         Command::GetSession { id } => {
             use claude_hippocampus::db::queries::{find_session_by_id, find_session_by_claude_id};
 
@@ -310,6 +1356,11 @@ async fn dispatch_db_command(
         }
 
         // Turn commands
+        Command::GetTurn { session_id, all } => {
+            let result = get_turn(pool, &session_id, all).await?;
+            Ok(serde_json::to_value(SuccessResponse::new(result))?)
+        }
+
         Command::CreateTurn {
             session_id,
             prompt,
@@ -341,52 +1392,70 @@ async fn dispatch_db_command(
         } => {
             use claude_hippocampus::db::queries::update_turn;
 
-            let uuid = Uuid::parse_str(&turn_id)?;
+            let uuid = parse_uuid_arg("--turn-id", &turn_id)?;
             let turn = update_turn(pool, uuid, &response, input_tokens, output_tokens).await?;
             Ok(serde_json::to_value(SuccessResponse::new(turn))?)
         }
 
         // Hook commands
-        Command::Hook { hook_type } => {
-            use claude_hippocampus::hooks::{handle_post_tool_use, PostToolUseInput};
-
-            // PostToolUse has different input format, handle separately
-            if hook_type == HookType::PostToolUse {
-                let raw_input = read_raw_stdin()?;
-                let input: PostToolUseInput = serde_json::from_str(&raw_input)
-                    .unwrap_or_else(|_| PostToolUseInput {
-                        tool_name: None,
-                        tool_input: None,
-                        tool_response: None,
-                        session_id: None,
-                    });
-                let output = handle_post_tool_use(pool, &input).await?;
-                return Ok(serde_json::to_value(&output)?);
-            }
-
-            // Read JSON input from stdin for standard hooks
-            let input = read_hook_input()?;
-
-            let output = match hook_type {
-                HookType::SessionStart => handle_session_start(pool, &input).await?,
-                HookType::UserPromptSubmit => handle_user_prompt_submit(pool, &input).await?,
-                HookType::Stop => handle_stop(&input).await?,
-                HookType::SessionEnd => handle_session_end(pool, &input).await?,
-                HookType::PostToolUse => unreachable!("Handled above"),
-            };
-
-            Ok(serde_json::to_value(&output)?)
-        }
-
         // These are handled in run() before this function is called
-        Command::Logs { .. } | Command::ClearLogs | Command::Stats { .. } | Command::GetTurn { .. } => {
+        Command::Logs { .. }
+        | Command::ClearLogs
+        | Command::Config { .. }
+        | Command::InstallHooks { .. }
+        | Command::AddMemoriesBatch { .. }
+        | Command::MigrateLegacy { .. }
+        | Command::Stats { .. }
+        | Command::HealthReport
+        | Command::Doctor { .. }
+        | Command::DebugBundle { .. }
+        | Command::Schema { .. }
+        | Command::Hook { .. }
+        | Command::SimulateHook { .. }
+        | Command::ReplayLastHook { .. }
+        | Command::Daemon { .. }
+        | Command::External(_) => {
             unreachable!("These commands are handled in run() before database dispatch")
         }
     }
 }
 
+/// Run a hook with writes rolled back and report what it would have done
+async fn simulate(
+    config: &DbConfig,
+    hook_type: &HookType,
+    raw_input: &str,
+) -> Result<serde_json::Value> {
+    let report = simulate_hook(config, hook_type, raw_input.trim()).await?;
+    Ok(serde_json::to_value(SuccessResponse::new(SimulateHookData {
+        hook: hook_type.as_str().to_string(),
+        output: report.output,
+        writes: report.writes,
+        skipped_effects: report.effects,
+        failures: report.failures,
+        rolled_back: true,
+    }))?)
+}
+
+tokio::task_local! {
+    /// Standard input already read for the daemon: by the daemon for the
+    /// command it runs, or here for one it did not run
+    static FORWARDED_STDIN: String;
+}
+
+/// Read all of stdin
+fn read_stdin() -> Result<String> {
+    if let Ok(input) = FORWARDED_STDIN.try_with(String::clone) {
+        return Ok(input);
+    }
+    Ok(io::read_to_string(io::stdin())?)
+}
+
 /// Read raw stdin as string
 fn read_raw_stdin() -> Result<String> {
+    if let Ok(input) = FORWARDED_STDIN.try_with(|input| input.lines().collect()) {
+        return Ok(input);
+    }
     let stdin = io::stdin();
     let mut input = String::new();
 
@@ -400,30 +1469,6 @@ fn read_raw_stdin() -> Result<String> {
     Ok(input)
 }
 
-/// Read hook input from stdin
-fn read_hook_input() -> Result<HookInput> {
-    let input = read_raw_stdin()?;
-
-    if input.is_empty() {
-        // Return empty input if no stdin
-        Ok(HookInput {
-            session_id: None,
-            prompt: None,
-            transcript_path: None,
-            cwd: None,
-            permission_mode: None,
-            hook_event_name: None,
-        })
-    } else {
-        serde_json::from_str(&input).map_err(|e| {
-            claude_hippocampus::error::HippocampusError::Config(format!(
-                "Failed to parse hook input: {}",
-                e
-            ))
-        })
-    }
-}
-
 /// Convert Scope to Tier (Scope doesn't have Both, so we need this conversion)
 fn scope_to_tier(scope: Scope) -> Tier {
     match scope {
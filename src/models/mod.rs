@@ -1,14 +1,49 @@
-pub mod memory;
-pub mod response;
+//! Data models.
+//!
+//! Memory, turn and response types live in the `claude-hippocampus-client`
+//! crate so other tools can share them; they are re-exported here.
+
 pub mod session;
-pub mod turn;
 
-pub use memory::{Confidence, Memory, MemorySummary, MemoryType, Scope, Tier};
+pub use claude_hippocampus_client::{memory, response, turn};
+
+pub use memory::{summarize, Confidence, Memory, MemorySummary, MemoryType, Scope, Tier};
 pub use response::{
-    AddMemoryData, ChainData, ClearLogsData, ConsolidateData, ContextData, DeleteMemoryData,
-    DuplicateResponse, ErrorResponse, GetMemoryData, ListRecentData, ListSupersededData, LogEntry,
-    LogsData, PruneData, PruneDataResult, PurgeSupersededData, SaveSessionSummaryData,
-    SearchResultData, SuccessResponse, SupersededMemory, TieredPruneData, UpdateMemoryData,
+    AddMemoryData, ChainData, ClearLogsData, ConflictResponse, ConsolidateData, ContentDiff, ContextData, ContextSection,
+    DeleteMemoryData, DuplicateResponse, EditMemoryData, ErrorResponse, GetMemoryData, LinkedMemory, ListRecentData,
+    LintIssue, LintRejectedResponse, ListSupersededData, LogEntry, LogsData, MemoryConflict, MemorySearchItem, PruneData, PruneDataResult,
+    PurgeSupersededData, ResolveReviewData, ReviewItem, ReviewQueueData, SaveSessionSummaryData,
+    SearchResult, SearchResultData, SimulateHookData, SimulatedWrite, SuccessResponse,
+    SupersedeSuggestion, SupersededMemory, TieredPruneData, UpdateMemoryData,
 };
-pub use session::{Session, SessionStatus};
+pub use session::{LenientJson, Session, SessionMatch, SessionStatus, SessionSummary};
 pub use turn::{CreateTurn, Turn, TurnSummary, UpdateTurn};
+
+/// An active project memory of `content` under `/test`, for tests; set other
+/// fields with struct update syntax
+#[cfg(test)]
+pub(crate) fn test_memory(content: &str, memory_type: MemoryType) -> Memory {
+    let now = chrono::Utc::now();
+    Memory {
+        id: uuid::Uuid::new_v4(),
+        memory_type,
+        scope: Scope::Project,
+        project_path: Some("/test".to_string()),
+        content: content.to_string(),
+        summary: summarize(content),
+        tags: Vec::new(),
+        confidence: Confidence::High,
+        source_session_id: None,
+        source_turn_id: None,
+        created_at: now,
+        updated_at: now,
+        accessed_at: None,
+        access_count: 0,
+        superseded_by: None,
+        superseded_at: None,
+        is_active: true,
+        metadata: None,
+        archived_at: None,
+        pinned: false,
+    }
+}
@@ -1,13 +1,18 @@
 //! Session model for tracking Claude Code sessions.
 //!
-//! Represents database sessions with status, git context, and timing.
+//! Represents database sessions with status, git and environment context, and timing.
 
 use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgTypeInfo, PgValueRef, Postgres};
+use sqlx::{Decode, Type, ValueRef};
 use std::str::FromStr;
 use uuid::Uuid;
 
 use crate::error::HippocampusError;
+use crate::environment::EnvironmentSnapshot;
 use crate::git::GitStatus;
 
 // ============================================================================
@@ -15,9 +20,10 @@ use crate::git::GitStatus;
 // ============================================================================
 
 /// Status of a session
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum SessionStatus {
+    #[default]
     Active,
     Completed,
     Orphaned,
@@ -46,9 +52,49 @@ impl FromStr for SessionStatus {
     }
 }
 
-impl Default for SessionStatus {
-    fn default() -> Self {
-        Self::Active
+impl Type<Postgres> for SessionStatus {
+    fn type_info() -> PgTypeInfo {
+        <String as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <String as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for SessionStatus {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(<&str as Decode<Postgres>>::decode(value)?.parse()?)
+    }
+}
+
+/// A nullable JSONB column read leniently: a value that does not
+/// deserialize reads as None, as rows written by older versions may hold
+/// other shapes
+pub struct LenientJson(Option<serde_json::Value>);
+
+impl Type<Postgres> for LenientJson {
+    fn type_info() -> PgTypeInfo {
+        <serde_json::Value as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <serde_json::Value as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for LenientJson {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        if value.is_null() {
+            return Ok(Self(None));
+        }
+        Ok(Self(Some(<serde_json::Value as Decode<Postgres>>::decode(value)?)))
+    }
+}
+
+impl<T: DeserializeOwned> From<LenientJson> for Option<T> {
+    fn from(json: LenientJson) -> Self {
+        json.0.and_then(|v| serde_json::from_value(v).ok())
     }
 }
 
@@ -57,7 +103,7 @@ impl Default for SessionStatus {
 // ============================================================================
 
 /// Represents a Claude Code session in the database
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 #[serde(rename_all = "camelCase")]
 pub struct Session {
     /// Database ID (UUID)
@@ -67,7 +113,11 @@ pub struct Session {
     /// Project path where session was started
     pub project_path: Option<String>,
     /// Git status at session start
+    #[sqlx(try_from = "LenientJson")]
     pub git_status: Option<GitStatus>,
+    /// OS and toolchain versions at session start
+    #[sqlx(try_from = "LenientJson")]
+    pub environment: Option<EnvironmentSnapshot>,
     /// Models used during the session
     pub models_used: Option<Vec<String>>,
     /// Session status (active, completed, orphaned)
@@ -80,6 +130,9 @@ pub struct Session {
     pub ended_at: Option<DateTime<Utc>>,
     /// Record creation timestamp
     pub created_at: DateTime<Utc>,
+    /// Prompts not recorded as turns (see `turn_recording`)
+    #[serde(default)]
+    pub skipped_prompts: i32,
 }
 
 impl Session {
@@ -91,12 +144,14 @@ impl Session {
             claude_session_id,
             project_path: None,
             git_status: None,
+            environment: None,
             models_used: None,
             status: SessionStatus::Active,
             summary: None,
             started_at: now,
             ended_at: None,
             created_at: now,
+            skipped_prompts: 0,
         }
     }
 
@@ -111,6 +166,54 @@ impl Session {
         self.git_status = Some(status);
         self
     }
+
+    /// Create with environment snapshot
+    pub fn with_environment(mut self, environment: EnvironmentSnapshot) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+}
+
+// ============================================================================
+// SessionSummary
+// ============================================================================
+
+/// A session as listed by `list-sessions`, with activity counts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    /// Database ID (UUID)
+    pub id: Uuid,
+    /// Claude's session identifier
+    pub claude_session_id: String,
+    /// Project path where session was started
+    pub project_path: Option<String>,
+    /// Git branch at session start
+    pub branch: Option<String>,
+    /// Session status (active, completed, orphaned)
+    pub status: SessionStatus,
+    /// When the session started
+    pub started_at: DateTime<Utc>,
+    /// When the session ended
+    pub ended_at: Option<DateTime<Utc>>,
+    /// Conversation turns recorded in the session
+    pub turn_count: i64,
+    /// Memories extracted from the session
+    pub memory_count: i64,
+}
+
+/// A session found by `search-meta`, with where the query matched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionMatch {
+    #[serde(flatten)]
+    pub session: SessionSummary,
+    /// Fields that matched: `branch`, `files` (changed at session start) or
+    /// `summary`
+    pub matched: Vec<String>,
+    /// Session summary (set on completion)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<serde_json::Value>,
 }
 
 // ============================================================================
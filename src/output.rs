@@ -0,0 +1,311 @@
+//! Human-readable output for `--format table|plain`
+//!
+//! Commands return JSON, which stays the default and is what hooks and
+//! scripts read. `list-recent`, `search-keyword` and `stats` can also be
+//! printed as aligned tables with a header (`table`), or as tab-separated
+//! rows without one (`plain`) for `cut` and `grep`. Other commands, and
+//! failures, print JSON whatever the format.
+//!
+//! `--format` is one global flag, so `schema` and `export-conversations`
+//! take their formats (`sql`, `mermaid`, `jsonl`) from it too.
+
+use serde_json::Value;
+
+use crate::error::{HippocampusError, Result};
+
+/// How command results are printed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Pretty-printed JSON
+    #[default]
+    Json,
+    /// Aligned columns with a header
+    Table,
+    /// Tab-separated rows without a header
+    Plain,
+    /// One JSON record per line (export-conversations)
+    Jsonl,
+    /// The migration SQL (schema)
+    Sql,
+    /// A Mermaid ER diagram (schema)
+    Mermaid,
+}
+
+impl OutputFormat {
+    /// The flag value, e.g. `table`
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputFormat::Json => "json",
+            OutputFormat::Table => "table",
+            OutputFormat::Plain => "plain",
+            OutputFormat::Jsonl => "jsonl",
+            OutputFormat::Sql => "sql",
+            OutputFormat::Mermaid => "mermaid",
+        }
+    }
+}
+
+/// The format `command` prints in: `format`, or JSON when not given. sql
+/// and mermaid only apply to schema, and jsonl to export-conversations.
+pub fn resolve(format: Option<OutputFormat>, command: &str) -> Result<OutputFormat> {
+    let format = format.unwrap_or_default();
+    let only = match format {
+        OutputFormat::Sql | OutputFormat::Mermaid => "schema",
+        OutputFormat::Jsonl => "export-conversations",
+        _ => return Ok(format),
+    };
+    if command != only {
+        return Err(HippocampusError::Validation(format!(
+            "--format {} only applies to {}",
+            format.name(),
+            only
+        )));
+    }
+    Ok(format)
+}
+
+/// Characters of a memory ID shown in a table
+const SHORT_ID_CHARS: usize = 8;
+
+/// Columns of a memory row
+const MEMORY_HEADERS: &[&str] = &["ID", "TYPE", "CONFIDENCE", "TIER", "CREATED", "TAGS", "SUMMARY"];
+
+/// Print `output` of `command` in `format`, ending with a newline
+pub fn render(format: OutputFormat, command: &str, output: &Value) -> String {
+    let failed = output.get("success").and_then(Value::as_bool) == Some(false);
+    let text = match (format, command) {
+        (OutputFormat::Json, _) => None,
+        _ if failed => None,
+        (_, "list-recent") => Some(memory_list(format, output, "entries")),
+        (_, "search-keyword") => Some(memory_list(format, output, "results")),
+        (_, "stats") => Some(stats(format, output)),
+        _ => None,
+    };
+    text.unwrap_or_else(|| format!("{}\n", serde_json::to_string_pretty(output).unwrap_or_default()))
+}
+
+/// Memories under `key`, one row each, with a count and the next-page
+/// cursor under a table
+fn memory_list(format: OutputFormat, output: &Value, key: &str) -> String {
+    let memories = output.get(key).and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+    let rows: Vec<Vec<String>> = memories.iter().map(|m| memory_row(format, m)).collect();
+    let mut text = table(format, MEMORY_HEADERS, &rows);
+
+    if format == OutputFormat::Table {
+        let total = output.get("total").and_then(Value::as_u64).unwrap_or(rows.len() as u64);
+        text.push_str(&format!(
+            "\n{} of {} {}\n",
+            rows.len(),
+            total,
+            if total == 1 { "memory" } else { "memories" }
+        ));
+        if let Some(cursor) = output.get("nextCursor").and_then(Value::as_str) {
+            text.push_str(&format!("Next page: --cursor {}\n", cursor));
+        }
+    }
+    text
+}
+
+fn memory_row(format: OutputFormat, memory: &Value) -> Vec<String> {
+    let field = |key: &str| memory.get(key).and_then(Value::as_str).unwrap_or("").to_string();
+    let mut id = field("id");
+    if format == OutputFormat::Table {
+        id.truncate(SHORT_ID_CHARS);
+    }
+    let tags: Vec<&str> = memory
+        .get("tags")
+        .and_then(Value::as_array)
+        .map(|tags| tags.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    vec![
+        id,
+        field("type"),
+        field("confidence"),
+        field("tier"),
+        field("created").chars().take(10).collect(),
+        tags.join(","),
+        field("summary"),
+    ]
+}
+
+/// Totals as `name: value` lines, then a table per breakdown (`by_type`,
+/// `by_tag`, ...); plain output is `name<TAB>value` and
+/// `breakdown<TAB>key<TAB>count` rows
+fn stats(format: OutputFormat, output: &Value) -> String {
+    let Some(stats) = output.as_object() else {
+        return String::new();
+    };
+    let mut text = String::new();
+    for (name, value) in stats.iter().filter(|(name, _)| *name != "success") {
+        if value.is_number() {
+            match format {
+                OutputFormat::Plain => text.push_str(&format!("{}\t{}\n", name, value)),
+                _ => text.push_str(&format!("{}: {}\n", name.replace('_', " "), value)),
+            }
+        }
+    }
+
+    for (name, value) in stats {
+        let Some(breakdown) = name.strip_prefix("by_") else {
+            continue;
+        };
+        let rows = breakdown_rows(value);
+        match format {
+            OutputFormat::Plain => {
+                for row in rows {
+                    text.push_str(&format!("{}\t{}\t{}\n", breakdown, row[0], row[1]));
+                }
+            }
+            _ => {
+                let heading = breakdown.to_uppercase();
+                text.push('\n');
+                text.push_str(&table(format, &[heading.as_str(), "COUNT"], &rows));
+            }
+        }
+    }
+    text
+}
+
+/// Rows of `key, count` from a `{key: count}` object or from an array of
+/// objects holding a count and one other field
+fn breakdown_rows(value: &Value) -> Vec<Vec<String>> {
+    match value {
+        Value::Object(counts) => counts
+            .iter()
+            .map(|(key, count)| vec![key.clone(), count.to_string()])
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| {
+                let item = item.as_object()?;
+                let count = item.get("count")?;
+                let key = item
+                    .iter()
+                    .find(|(k, _)| *k != "count")
+                    .map(|(_, v)| v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string()))
+                    .unwrap_or_default();
+                Some(vec![key, count.to_string()])
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Rows under a header with columns padded to line up, numbers
+/// right-aligned; plain output is the rows alone, tab-separated
+fn table(format: OutputFormat, headers: &[&str], rows: &[Vec<String>]) -> String {
+    if format == OutputFormat::Plain {
+        return rows.iter().map(|row| format!("{}\n", row.join("\t"))).collect();
+    }
+
+    let width = |i: usize| {
+        rows.iter()
+            .map(|row| row[i].chars().count())
+            .chain([headers[i].len()])
+            .max()
+            .unwrap_or(0)
+    };
+    let widths: Vec<usize> = (0..headers.len()).map(width).collect();
+    let numeric: Vec<bool> = (0..headers.len())
+        .map(|i| !rows.is_empty() && rows.iter().all(|row| row[i].parse::<f64>().is_ok()))
+        .collect();
+
+    let line = |cells: Vec<&str>| {
+        let last = cells.len() - 1;
+        let padded: Vec<String> = cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| match (numeric[i], i == last) {
+                (true, _) => format!("{:>w$}", cell, w = widths[i]),
+                (false, true) => cell.to_string(),
+                (false, false) => format!("{:<w$}", cell, w = widths[i]),
+            })
+            .collect();
+        format!("{}\n", padded.join("  ").trim_end())
+    };
+    let mut text = line(headers.to_vec());
+    for row in rows {
+        text.push_str(&line(row.iter().map(String::as_str).collect()));
+    }
+    text
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn recent() -> Value {
+        json!({
+            "success": true,
+            "total": 3,
+            "nextCursor": "abc",
+            "entries": [{
+                "id": "822737db-11b2-490c-a7da-78f1ffbeb50e",
+                "type": "gotcha",
+                "confidence": "high",
+                "tier": "project",
+                "created": "2026-10-16T15:05:56.479656Z",
+                "tags": ["docker", "macos"],
+                "summary": "Docker volumes drop file events on macOS"
+            }]
+        })
+    }
+
+    #[test]
+    fn test_render_memory_table() {
+        assert_eq!(
+            render(OutputFormat::Table, "list-recent", &recent()),
+            "ID        TYPE    CONFIDENCE  TIER     CREATED     TAGS          SUMMARY\n\
+             822737db  gotcha  high        project  2026-10-16  docker,macos  Docker volumes drop file events on macOS\n\
+             \n1 of 3 memories\nNext page: --cursor abc\n"
+        );
+        assert_eq!(
+            render(OutputFormat::Plain, "list-recent", &recent()),
+            "822737db-11b2-490c-a7da-78f1ffbeb50e\tgotcha\thigh\tproject\t2026-10-16\tdocker,macos\t\
+             Docker volumes drop file events on macOS\n"
+        );
+    }
+
+    #[test]
+    fn test_render_stats() {
+        let stats = json!({
+            "success": true,
+            "total": 12,
+            "by_type": {"gotcha": 10, "learning": 2},
+            "by_tag": [{"tag": "docker", "count": 3}]
+        });
+        assert_eq!(
+            render(OutputFormat::Table, "stats", &stats),
+            "total: 12\n\nTAG     COUNT\ndocker      3\n\nTYPE      COUNT\ngotcha       10\nlearning      2\n"
+        );
+        assert_eq!(
+            render(OutputFormat::Plain, "stats", &stats),
+            "total\t12\ntag\tdocker\t3\ntype\tgotcha\t10\ntype\tlearning\t2\n"
+        );
+    }
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(resolve(None, "stats").unwrap(), OutputFormat::Json);
+        assert_eq!(resolve(Some(OutputFormat::Table), "get-memory").unwrap(), OutputFormat::Table);
+        assert_eq!(resolve(Some(OutputFormat::Mermaid), "schema").unwrap(), OutputFormat::Mermaid);
+        assert!(matches!(
+            resolve(Some(OutputFormat::Sql), "list-recent"),
+            Err(HippocampusError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_other_commands_and_failures_stay_json() {
+        let failure = json!({"success": false, "error": "boom"});
+        for (command, output) in [("stats", &failure), ("get-memory", &recent())] {
+            let text = render(OutputFormat::Table, command, output);
+            assert_eq!(serde_json::from_str::<Value>(&text).unwrap(), *output);
+        }
+    }
+}
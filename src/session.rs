@@ -9,12 +9,13 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
-const SESSION_STATE_DIR: &str = "/tmp";
+/// Directory holding the session state files
+pub const SESSION_STATE_DIR: &str = "/tmp";
 const SESSION_STATE_PREFIX: &str = "hippocampus-session-";
 const LEGACY_SESSION_STATE_PATH: &str = "/tmp/hippocampus-session-state.json";
 
 /// Session state persisted between hook invocations.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionState {
     /// Database session ID (UUID)
@@ -27,17 +28,6 @@ pub struct SessionState {
     pub current_turn_id: Option<Uuid>,
 }
 
-impl Default for SessionState {
-    fn default() -> Self {
-        Self {
-            session_id: None,
-            claude_session_id: None,
-            turn_number: 0,
-            current_turn_id: None,
-        }
-    }
-}
-
 /// Get the session state file path for a specific Claude session ID.
 ///
 /// Returns the per-session path if `claude_session_id` is provided,
@@ -85,6 +75,20 @@ pub fn load_session_state(claude_session_id: Option<&str>) -> Result<Option<Sess
     Ok(None)
 }
 
+/// Load the state of one Claude session, ignoring other sessions' state.
+///
+/// [`load_session_state`] falls back to the legacy shared file, which holds
+/// whichever session wrote last; with concurrent sessions that is often
+/// another one.
+pub fn load_own_session_state(claude_session_id: &str) -> Option<SessionState> {
+    let state = load_session_state(Some(claude_session_id)).ok().flatten();
+    state_for_session(state, claude_session_id)
+}
+
+fn state_for_session(state: Option<SessionState>, claude_session_id: &str) -> Option<SessionState> {
+    state.filter(|s| s.claude_session_id.as_deref() == Some(claude_session_id))
+}
+
 /// Save session state to file.
 ///
 /// Writes to session-specific file if `claude_session_id` is set,
@@ -213,6 +217,24 @@ mod tests {
         assert!(!json.contains("current_turn_id"));
     }
 
+    #[test]
+    fn test_state_for_session_ignores_other_sessions() {
+        let own = SessionState {
+            claude_session_id: Some("abc".to_string()),
+            current_turn_id: Some(Uuid::new_v4()),
+            ..SessionState::default()
+        };
+        assert_eq!(state_for_session(Some(own.clone()), "abc"), Some(own));
+
+        // The legacy file written by a concurrent session
+        let other = SessionState {
+            claude_session_id: Some("other".to_string()),
+            ..SessionState::default()
+        };
+        assert!(state_for_session(Some(other), "abc").is_none());
+        assert!(state_for_session(Some(SessionState::default()), "abc").is_none());
+    }
+
     #[test]
     fn test_save_and_load_session_state() {
         let test_id = test_session_id();
@@ -0,0 +1,111 @@
+//! Page cursors for list and search commands
+//!
+//! A cursor holds where the last page ended: the order it was in, the sort
+//! key of its last row, as text Postgres casts back to the key's types, and
+//! the row's id. The
+//! next page starts after that row (`WHERE (key, id) < (...)`), so rows
+//! added or removed meanwhile never shift it. Scores that decay with time
+//! are computed as of the time the first page was, carried along in `at`.
+//! A cursor is hex-encoded so callers treat it as an opaque token and pass
+//! back `nextCursor` as it came.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// The last row of a page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    /// Order of the page: a ranking's name, or the one of its command
+    pub order: String,
+    /// Sort key values, most significant first
+    pub key: Vec<String>,
+    pub id: Uuid,
+    /// Time the page's scores were computed at
+    pub at: DateTime<Utc>,
+}
+
+impl Cursor {
+    /// Opaque token for `--cursor`
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+        json.bytes().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Cursor of a token made by [`encode`](Self::encode); None when it is not one
+    pub fn decode(token: &str) -> Option<Self> {
+        let token = token.trim();
+        if !token.len().is_multiple_of(2) {
+            return None;
+        }
+        let bytes = (0..token.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(token.get(i..i + 2)?, 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+}
+
+/// A timestamp sort key as text Postgres reads back to the microsecond
+pub fn time_key(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// Token for the page after `rows`, from the cursor of the last one; None
+/// when there is nothing after it
+pub fn next<T>(rows: &[(T, Cursor)], has_more: bool) -> Option<String> {
+    rows.last().filter(|_| has_more).map(|(_, cursor)| cursor.encode())
+}
+
+/// Cut rows fetched with one extra down to a page of `limit`; whether the
+/// extra row showed there are more
+pub fn take_page<T>(rows: &mut Vec<T>, limit: usize) -> bool {
+    let has_more = rows.len() > limit;
+    rows.truncate(limit);
+    has_more
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor() -> Cursor {
+        Cursor {
+            order: "decay".to_string(),
+            key: vec!["0.42".to_string(), time_key(Utc::now())],
+            id: Uuid::new_v4(),
+            at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = cursor();
+        assert_eq!(Cursor::decode(&cursor.encode()), Some(cursor.clone()));
+
+        let rows = vec![((), cursor.clone())];
+        assert_eq!(next(&rows, true), Some(cursor.encode()));
+        assert_eq!(next(&rows, false), None);
+        assert_eq!(next::<()>(&[], true), None);
+    }
+
+    #[test]
+    fn test_take_page() {
+        let mut rows = vec![1, 2, 3];
+        assert!(take_page(&mut rows, 2));
+        assert_eq!(rows, vec![1, 2]);
+        assert!(!take_page(&mut rows, 2));
+    }
+
+    #[test]
+    fn test_decode_rejects_other_text() {
+        assert_eq!(Cursor::decode("30"), None);
+        assert_eq!(Cursor::decode("zz"), None);
+        assert_eq!(Cursor::decode("6f66667365743d3330"), None);
+        assert_eq!(Cursor::decode("é1"), None);
+    }
+}
@@ -0,0 +1,5 @@
+//! Small helpers shared across commands and hooks
+
+pub mod cursor;
+pub mod tokens;
+pub mod workers;
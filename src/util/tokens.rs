@@ -0,0 +1,61 @@
+//! Token estimates for text injected into prompts
+//!
+//! There is no tokenizer here: the budget only has to be respected roughly,
+//! so the usual rule of thumb of four characters per token is used. It
+//! overestimates for plain English and is close for code and identifiers.
+
+/// Characters counted as one token
+pub const CHARS_PER_TOKEN: usize = 4;
+
+/// Marker appended to text cut to fit a budget
+const ELLIPSIS: &str = "...";
+
+/// Estimated number of tokens in `text`
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// `text` cut on a character boundary so it fits in `max_tokens`, with
+/// "..." marking the cut. Returned unchanged when it already fits.
+pub fn truncate_to_tokens(text: &str, max_tokens: usize) -> String {
+    if estimate_tokens(text) <= max_tokens {
+        return text.to_string();
+    }
+    let keep = (max_tokens * CHARS_PER_TOKEN).saturating_sub(ELLIPSIS.len());
+    let cut: String = text.chars().take(keep).collect();
+    format!("{}{}", cut.trim_end(), ELLIPSIS)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_tokens_rounds_up() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abc"), 1);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        // Characters, not bytes
+        assert_eq!(estimate_tokens("日本語です"), 2);
+    }
+
+    #[test]
+    fn test_truncate_to_tokens() {
+        assert_eq!(truncate_to_tokens("short", 10), "short");
+
+        let cut = truncate_to_tokens("The parser retries failed requests twice", 4);
+        assert_eq!(cut, "The parser re...");
+        assert!(estimate_tokens(&cut) <= 4);
+    }
+
+    #[test]
+    fn test_truncate_to_tokens_multibyte() {
+        let cut = truncate_to_tokens(&"é".repeat(40), 2);
+        assert_eq!(cut, "ééééé...");
+    }
+}
@@ -0,0 +1,103 @@
+//! Bounded worker pools for bulk commands
+//!
+//! `add-memories-batch`, `migrate-legacy` and `export-conversations` spread
+//! their per-item queries over `--jobs` workers sharing one connection
+//! pool. Results come back in input order, whichever worker finishes first.
+
+use std::future::Future;
+
+use tokio::task::JoinSet;
+
+use crate::error::Result;
+
+/// Workers bulk commands run when `--jobs` is not given
+pub const DEFAULT_JOBS: usize = 4;
+
+/// Run `task` on each item, at most `jobs` at a time, and return the
+/// results in the order of `items`. The first error is returned and the
+/// tasks still running are cancelled.
+pub async fn map_ordered<T, R, F, Fut>(items: Vec<T>, jobs: usize, task: F) -> Result<Vec<R>>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = Result<R>> + Send + 'static,
+{
+    let mut results: Vec<Option<R>> = items.iter().map(|_| None).collect();
+    let mut pending = items.into_iter().enumerate();
+    let mut running = JoinSet::new();
+    loop {
+        while running.len() < jobs.max(1) {
+            let Some((index, item)) = pending.next() else {
+                break;
+            };
+            let run = task(item);
+            running.spawn(async move { (index, run.await) });
+        }
+        let Some(joined) = running.join_next().await else {
+            break;
+        };
+        let (index, result) = joined.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()));
+        results[index] = Some(result?);
+    }
+    Ok(results.into_iter().flatten().collect())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::error::HippocampusError;
+
+    #[tokio::test]
+    async fn test_map_ordered_keeps_input_order() {
+        // Earlier items take longer, so they finish last
+        let items: Vec<u64> = (0..8).collect();
+        let results = map_ordered(items, 4, |n| async move {
+            tokio::time::sleep(Duration::from_millis(40 - n * 5)).await;
+            Ok(n * 10)
+        })
+        .await
+        .unwrap();
+        assert_eq!(results, vec![0, 10, 20, 30, 40, 50, 60, 70]);
+    }
+
+    #[tokio::test]
+    async fn test_map_ordered_bounds_concurrency() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let most = Arc::new(AtomicUsize::new(0));
+        let results = map_ordered((0..12).collect(), 3, |n: i32| {
+            let (running, most) = (running.clone(), most.clone());
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                most.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok(n)
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(results.len(), 12);
+        assert_eq!(most.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_map_ordered_returns_the_error() {
+        let result = map_ordered(vec![1, 2, 3], 2, |n| async move {
+            match n {
+                2 => Err(HippocampusError::NotFound(n.to_string())),
+                _ => Ok(n),
+            }
+        })
+        .await;
+        assert!(matches!(result, Err(HippocampusError::NotFound(id)) if id == "2"));
+    }
+}